@@ -0,0 +1,17 @@
+-- Canonical entities merged across sources (see services/entity_resolver.rs)
+CREATE TABLE IF NOT EXISTS canonical_entities (
+    id UUID PRIMARY KEY,
+    entity_type TEXT NOT NULL,
+    type_group TEXT NOT NULL,
+    canonical_name TEXT NOT NULL,
+    normalized_name TEXT NOT NULL,
+    owner_id TEXT,
+    merged_properties JSONB NOT NULL DEFAULT '{}',
+    source_entities JSONB NOT NULL DEFAULT '[]',
+    confidence_score REAL NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS canonical_entities_key
+    ON canonical_entities (type_group, normalized_name, (COALESCE(owner_id, '')));
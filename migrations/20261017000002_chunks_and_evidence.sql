@@ -0,0 +1,50 @@
+-- Full chunk rows, mirrored from the chunk nodes written to the graph
+CREATE TABLE IF NOT EXISTS chunks (
+    id UUID PRIMARY KEY,
+    content TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    source_kind TEXT NOT NULL,
+    source_type TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    file_path TEXT,
+    repo_name TEXT,
+    branch TEXT,
+    language TEXT,
+    heading_path TEXT,
+    section_title TEXT,
+    owner_id TEXT NOT NULL,
+    author TEXT,
+    commit_sha TEXT,
+    commit_date TIMESTAMPTZ,
+    start_line INTEGER,
+    end_line INTEGER,
+    token_count INTEGER,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS chunks_owner_content_hash ON chunks (owner_id, content_hash);
+CREATE INDEX IF NOT EXISTS chunks_repo_file ON chunks (repo_name, file_path);
+
+-- How each cross-source link was inferred. Chunk ids aren't foreign keys:
+-- links can reach chunks ingested before chunks were stored here.
+CREATE TABLE IF NOT EXISTS relationship_evidence (
+    id UUID PRIMARY KEY,
+    from_chunk_id UUID NOT NULL,
+    to_chunk_id UUID NOT NULL,
+    from_entity_id UUID,
+    to_entity_id UUID,
+    relationship_type TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    extraction_method TEXT NOT NULL,
+    evidence_text TEXT,
+    similarity_score REAL,
+    temporal_distance_days INTEGER,
+    author_match BOOLEAN NOT NULL DEFAULT FALSE,
+    properties JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS relationship_evidence_from ON relationship_evidence (from_chunk_id);
+CREATE INDEX IF NOT EXISTS relationship_evidence_to ON relationship_evidence (to_chunk_id);
@@ -0,0 +1,6 @@
+-- Canonical entities are merged per repository as well as per owner
+ALTER TABLE canonical_entities ADD COLUMN IF NOT EXISTS repo_name TEXT NOT NULL DEFAULT '';
+
+DROP INDEX IF EXISTS canonical_entities_key;
+CREATE UNIQUE INDEX IF NOT EXISTS canonical_entities_key
+    ON canonical_entities (type_group, normalized_name, (COALESCE(owner_id, '')), repo_name);
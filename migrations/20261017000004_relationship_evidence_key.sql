@@ -0,0 +1,10 @@
+-- One evidence row per link: keep the newest of any duplicates, then enforce it
+DELETE FROM relationship_evidence older
+USING relationship_evidence newer
+WHERE older.from_chunk_id = newer.from_chunk_id
+  AND older.to_chunk_id = newer.to_chunk_id
+  AND older.relationship_type = newer.relationship_type
+  AND (older.created_at, older.id) < (newer.created_at, newer.id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS relationship_evidence_link
+    ON relationship_evidence (from_chunk_id, to_chunk_id, relationship_type);
@@ -1,46 +1,135 @@
 //! Configuration module for relation-graph service
 
+use std::collections::HashMap;
 use std::env;
+use std::ops::RangeInclusive;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     // Server
     pub port: u16,
+    #[allow(dead_code)]
     pub host: String,
+    pub health_check_timeout_ms: u64,  // per-dependency ping timeout for /health
+    pub shutdown_timeout_secs: u64,    // how long shutdown waits for ingestion jobs
+    
+    // Graph backend: "neo4j" or "memory" (in-process, no vector search)
+    pub graph_backend: String,
     
     // Neo4j (handles both graph AND vector storage)
     pub neo4j_uri: String,
     pub neo4j_user: String,
     pub neo4j_password: String,
+    #[allow(dead_code)]
     pub neo4j_database: String,
+    pub neo4j_max_connections: usize,
+    pub neo4j_fetch_size: usize,      // rows pulled per round trip when streaming results
+    pub neo4j_slow_wait_ms: u64,      // connection waits at least this long count as slow
+    
+    // Entity type -> node label overrides (default: uppercased type)
+    pub entity_label_mapping: HashMap<String, String>,
     
     // Vector configuration (stored in Neo4j)
     pub vector_dimension: usize,
+    pub auto_create_vector_indexes: bool,
+    pub query_dimension_mismatch: String,  // "error" or "skip"
     
     // PostgreSQL (evidence tracking, job queue)
     pub database_url: String,
     
     // Service URLs
     pub embedding_service_url: String,
+    #[allow(dead_code)]
     pub chunker_service_url: String,
+    #[allow(dead_code)]
     pub data_connector_service_url: String,
     
+    // Embedding service resilience and caching
+    pub embedding_provider: String,  // "microservice", "openai", "ollama", or "local"
+    pub openai_api_key: Option<String>,
+    pub openai_base_url: String,
+    pub openai_embedding_dimensions: Option<usize>,  // shortened output for text-embedding-3 models
+    pub ollama_url: String,
+    pub local_model_path: Option<String>,  // model directory for the local provider
+    pub embedding_model: String,
+    pub embedding_model_indexes: HashMap<String, String>,  // extra model -> chunk vector index
+    pub embedding_timeout_ms: u64,
+    pub embedding_connect_timeout_ms: u64,
+    pub embedding_max_retries: u32,  // retries after a transient failure (0 disables)
+    pub embedding_retry_base_ms: u64,  // first backoff, doubled per retry
+    pub embedding_batch_size: usize,  // max texts per embedding request
+    pub embedding_batch_concurrency: usize,  // sub-batch requests in flight per `embed_batch` call
+    pub max_embed_chars: usize,  // longest text sent to the model in one piece (0 disables)
+    pub embed_overflow_mode: String,  // "window" (average windows) or "truncate" (embed the head)
+    pub max_embed_windows: usize,  // windows averaged per text; the rest is left out
+    pub embedding_breaker_threshold: u32,
+    pub embedding_breaker_cooldown_secs: u64,
+    pub embedding_cache_size: usize,  // 0 disables the cache
+    pub embedding_cache_ttl_secs: u64,
+    
+    // Summarization (optional; disabled when no service URL is set)
+    pub summarization_service_url: Option<String>,
+    pub summarization_min_chars: usize,
+    pub summary_max_length: usize,
+    pub summary_embedding_mode: String,  // "content", "summary", or "both"
+    
     // Cross-source linking
     pub similarity_threshold: f32,
     pub max_cross_links_per_chunk: usize,
+    pub cross_link_pairs: Vec<(String, String)>,  // (from_kind, to_kind) pairs to link
+    pub linking_concurrency: usize,  // similarity lookups in flight per ingest (capped by the Neo4j pool)
     pub enable_temporal_proximity: bool,
     pub enable_explicit_mentions: bool,
     pub enable_author_overlap: bool,
     pub temporal_proximity_days: i64,
     
+    // Document concepts (capitalized phrases)
+    pub concept_false_positives: Vec<String>,  // phrases starting with these are never concepts
+    pub concept_min_length: usize,
+    pub concept_min_occurrences: usize,  // unless the phrase is in a heading
+    
+    // Link import stubs to the module/file nodes they refer to
+    pub resolve_imports: bool,
+    
+    // Create a COMMIT node per distinct commit_sha, containing its chunks
+    pub commit_nodes: bool,
+    
+    // Merge same-named entities across sources into canonical entities
+    pub entity_resolution: bool,
+    
+    // Low-signal chunk filtering (0 disables)
+    pub min_token_count: i32,
+    pub min_token_count_scope: String,  // "linking" or "search"
+    
+    // Asserted confidence for manually created relationships
+    pub default_manual_relationship_confidence: f32,
+    
     // Confidence boosters
     pub explicit_mention_boost: f32,
     pub temporal_proximity_boost: f32,
     pub author_overlap_boost: f32,
+    pub normalize_link_boosts: bool,  // scale earned boosts by what the pair's metadata allows
     
     // Graph traversal
     pub max_graph_hops: usize,
+    pub max_tree_depth: usize,        // depth cap for entity trees
     pub max_entities_per_traversal: usize,
+    pub max_query_rows: usize,
+    
+    // Search ranking
+    pub recency_half_life_days: f32,
+    pub hybrid_min_similarity: f32,   // default `min_similarity` for vector hits
+    pub cross_kind_expansion_weight: f32,  // default `cross_kind_weight` for single-kind searches
+    pub search_cache_size: usize,     // 0 disables the search result cache
+    pub search_cache_ttl_secs: u64,
+    
+    // Request size
+    pub max_body_bytes: usize,        // JSON request bodies (not imports or streamed ingests)
+    pub max_chunks_per_request: usize,
+    
+    // Rate limiting
+    pub rate_limit_rps: f64,          // requests per second per client IP (0 disables the limit)
+    pub rate_limit_burst: u32,        // bucket size (0 means one second's worth)
     
     // Redis (optional)
     pub redis_url: Option<String>,
@@ -54,6 +143,17 @@ impl Config {
                 .parse()
                 .expect("Invalid PORT"),
             host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
+            health_check_timeout_ms: env::var("HEALTH_CHECK_TIMEOUT_MS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse()
+                .unwrap_or(2000),
+            shutdown_timeout_secs: env::var("SHUTDOWN_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            
+            graph_backend: env::var("GRAPH_BACKEND")
+                .unwrap_or_else(|_| "neo4j".to_string()),
             
             neo4j_uri: env::var("NEO4J_URI")
                 .unwrap_or_else(|_| "bolt://localhost:7687".to_string()),
@@ -63,6 +163,19 @@ impl Config {
                 .unwrap_or_else(|_| "password".to_string()),
             neo4j_database: env::var("NEO4J_DATABASE")
                 .unwrap_or_else(|_| "neo4j".to_string()),
+            neo4j_max_connections: parse_in_range("NEO4J_MAX_CONNECTIONS", env::var("NEO4J_MAX_CONNECTIONS").ok(), NEO4J_MAX_CONNECTIONS_RANGE, 10)
+                .unwrap_or_else(|e| panic!("{}", e)),
+            neo4j_fetch_size: parse_in_range("NEO4J_FETCH_SIZE", env::var("NEO4J_FETCH_SIZE").ok(), NEO4J_FETCH_SIZE_RANGE, 500)
+                .unwrap_or_else(|e| panic!("{}", e)),
+            neo4j_slow_wait_ms: env::var("NEO4J_SLOW_WAIT_MS")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            
+            entity_label_mapping: load_label_mapping(
+                env::var("ENTITY_LABEL_MAPPING").ok(),
+                env::var("ENTITY_LABEL_MAPPING_FILE").ok(),
+            ),
             
             // Vector dimension for Neo4j native vector storage
             // 384-dim recommended for sentence-transformers
@@ -70,6 +183,12 @@ impl Config {
                 .unwrap_or_else(|_| "384".to_string())
                 .parse()
                 .unwrap_or(384),
+            auto_create_vector_indexes: env::var("AUTO_CREATE_VECTOR_INDEXES")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            query_dimension_mismatch: env::var("QUERY_DIMENSION_MISMATCH")
+                .unwrap_or_else(|_| "error".to_string()),
             
             database_url: env::var("DATABASE_URL")
                 .expect("DATABASE_URL must be set"),
@@ -81,6 +200,86 @@ impl Config {
             data_connector_service_url: env::var("DATA_CONNECTOR_SERVICE_URL")
                 .unwrap_or_else(|_| "http://localhost:3013".to_string()),
             
+            embedding_provider: env::var("EMBEDDING_PROVIDER")
+                .unwrap_or_else(|_| "microservice".to_string())
+                .trim()
+                .to_lowercase(),
+            openai_api_key: env::var("OPENAI_API_KEY").ok().filter(|key| !key.is_empty()),
+            openai_base_url: env::var("OPENAI_BASE_URL")
+                .unwrap_or_else(|_| "https://api.openai.com/v1".to_string()),
+            openai_embedding_dimensions: env::var("OPENAI_EMBEDDING_DIMENSIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|n| *n > 0),
+            ollama_url: env::var("OLLAMA_URL")
+                .unwrap_or_else(|_| "http://localhost:11434".to_string()),
+            local_model_path: env::var("LOCAL_MODEL_PATH").ok().filter(|path| !path.is_empty()),
+            embedding_model: env::var("EMBEDDING_MODEL")
+                .unwrap_or_else(|_| "sentence-transformers-384".to_string()),
+            embedding_model_indexes: parse_model_indexes(
+                &env::var("EMBEDDING_MODEL_INDEXES").unwrap_or_default(),
+            ),
+            embedding_timeout_ms: env::var("EMBEDDING_TIMEOUT_MS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+            embedding_connect_timeout_ms: env::var("EMBEDDING_CONNECT_TIMEOUT_MS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse()
+                .unwrap_or(2000),
+            embedding_max_retries: env::var("EMBEDDING_MAX_RETRIES")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            embedding_retry_base_ms: env::var("EMBEDDING_RETRY_BASE_MS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200),
+            embedding_batch_size: env::var("EMBEDDING_BATCH_SIZE")
+                .unwrap_or_else(|_| "32".to_string())
+                .parse()
+                .unwrap_or(32),
+            embedding_batch_concurrency: env::var("EMBEDDING_BATCH_CONCURRENCY")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()
+                .unwrap_or(4),
+            max_embed_chars: env::var("MAX_EMBED_CHARS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse()
+                .unwrap_or(2000),
+            embed_overflow_mode: parse_overflow_mode(env::var("EMBED_OVERFLOW_MODE").ok())
+                .unwrap_or_else(|e| panic!("{}", e)),
+            max_embed_windows: parse_in_range("MAX_EMBED_WINDOWS", env::var("MAX_EMBED_WINDOWS").ok(), MAX_EMBED_WINDOWS_RANGE, 16)
+                .unwrap_or_else(|e| panic!("{}", e)),
+            embedding_breaker_threshold: env::var("EMBEDDING_BREAKER_THRESHOLD")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            embedding_breaker_cooldown_secs: env::var("EMBEDDING_BREAKER_COOLDOWN_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            embedding_cache_size: env::var("EMBEDDING_CACHE_SIZE")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+            embedding_cache_ttl_secs: env::var("EMBEDDING_CACHE_TTL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            
+            summarization_service_url: env::var("SUMMARIZATION_SERVICE_URL").ok(),
+            summarization_min_chars: env::var("SUMMARIZATION_MIN_CHARS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse()
+                .unwrap_or(2000),
+            summary_max_length: env::var("SUMMARY_MAX_LENGTH")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200),
+            summary_embedding_mode: env::var("SUMMARY_EMBEDDING_MODE")
+                .unwrap_or_else(|_| "content".to_string()),
+            
             similarity_threshold: env::var("SIMILARITY_THRESHOLD")
                 .unwrap_or_else(|_| "0.5".to_string())
                 .parse()
@@ -89,6 +288,16 @@ impl Config {
                 .unwrap_or_else(|_| "5".to_string())
                 .parse()
                 .unwrap_or(5),
+            cross_link_pairs: parse_kind_pairs(
+                &env::var("CROSS_LINK_PAIRS").unwrap_or_else(|_| "document:code,code:document".to_string()),
+            )
+            .unwrap_or_else(|e| panic!("{}", e)),
+            linking_concurrency: env::var("LINKING_CONCURRENCY")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .unwrap_or(4),
             enable_temporal_proximity: env::var("ENABLE_TEMPORAL_PROXIMITY")
                 .unwrap_or_else(|_| "true".to_string())
                 .parse()
@@ -106,6 +315,47 @@ impl Config {
                 .parse()
                 .unwrap_or(7),
             
+            concept_false_positives: env::var("CONCEPT_FALSE_POSITIVES")
+                .map(|value| parse_list(&value))
+                .unwrap_or_else(|_| DEFAULT_CONCEPT_FALSE_POSITIVES.iter().map(|p| p.to_string()).collect()),
+            concept_min_length: env::var("CONCEPT_MIN_LENGTH")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            concept_min_occurrences: env::var("CONCEPT_MIN_OCCURRENCES")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            
+            resolve_imports: env::var("RESOLVE_IMPORTS")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            
+            commit_nodes: env::var("COMMIT_NODES")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            
+            entity_resolution: env::var("ENTITY_RESOLUTION")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            
+            min_token_count: env::var("MIN_TOKEN_COUNT")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            min_token_count_scope: env::var("MIN_TOKEN_COUNT_SCOPE")
+                .unwrap_or_else(|_| "linking".to_string()),
+            
+            default_manual_relationship_confidence: env::var("DEFAULT_MANUAL_RELATIONSHIP_CONFIDENCE")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse::<f32>()
+                .ok()
+                .filter(|c| (0.0..=1.0).contains(c))
+                .unwrap_or(1.0),
+            
             // Confidence boosters (configurable weights)
             explicit_mention_boost: env::var("EXPLICIT_MENTION_BOOST")
                 .unwrap_or_else(|_| "0.15".to_string())
@@ -119,17 +369,270 @@ impl Config {
                 .unwrap_or_else(|_| "0.10".to_string())
                 .parse()
                 .unwrap_or(0.10),
+            normalize_link_boosts: env::var("NORMALIZE_LINK_BOOSTS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
             
             max_graph_hops: env::var("MAX_GRAPH_HOPS")
                 .unwrap_or_else(|_| "2".to_string())
                 .parse()
                 .unwrap_or(2),
+            max_tree_depth: env::var("MAX_TREE_DEPTH")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
             max_entities_per_traversal: env::var("MAX_ENTITIES_PER_TRAVERSAL")
                 .unwrap_or_else(|_| "50".to_string())
                 .parse()
                 .unwrap_or(50),
+            max_query_rows: env::var("MAX_QUERY_ROWS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+            
+            recency_half_life_days: env::var("RECENCY_HALF_LIFE_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse::<f32>()
+                .ok()
+                .filter(|days| *days > 0.0)
+                .unwrap_or(30.0),
+            hybrid_min_similarity: env::var("HYBRID_MIN_SIMILARITY")
+                .unwrap_or_else(|_| "0.0".to_string())
+                .parse::<f32>()
+                .ok()
+                .filter(|s| (0.0..=1.0).contains(s))
+                .unwrap_or(0.0),
+            cross_kind_expansion_weight: env::var("CROSS_KIND_EXPANSION_WEIGHT")
+                .unwrap_or_else(|_| "0.0".to_string())
+                .parse::<f32>()
+                .ok()
+                .filter(|w| (0.0..=1.0).contains(w))
+                .unwrap_or(0.0),
+            search_cache_size: env::var("SEARCH_CACHE_SIZE")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            search_cache_ttl_secs: env::var("SEARCH_CACHE_TTL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            
+            max_body_bytes: env::var("MAX_BODY_BYTES")
+                .unwrap_or_else(|_| "16777216".to_string())
+                .parse()
+                .unwrap_or(16 * 1024 * 1024),
+            max_chunks_per_request: env::var("MAX_CHUNKS_PER_REQUEST")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+            
+            rate_limit_rps: env::var("RATE_LIMIT_RPS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+            rate_limit_burst: env::var("RATE_LIMIT_BURST")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
             
             redis_url: env::var("REDIS_URL").ok(),
         }
     }
 }
+
+/// Accepted `NEO4J_MAX_CONNECTIONS` values
+pub const NEO4J_MAX_CONNECTIONS_RANGE: RangeInclusive<usize> = 1..=256;
+
+/// Accepted `NEO4J_FETCH_SIZE` values
+pub const NEO4J_FETCH_SIZE_RANGE: RangeInclusive<usize> = 1..=10_000;
+
+/// Accepted `MAX_EMBED_WINDOWS` values
+pub const MAX_EMBED_WINDOWS_RANGE: RangeInclusive<usize> = 1..=256;
+
+/// Accepted `EMBED_OVERFLOW_MODE` values
+pub const EMBED_OVERFLOW_MODES: [&str; 2] = ["window", "truncate"];
+
+/// Phrases document concepts may not start with, unless `CONCEPT_FALSE_POSITIVES` is set
+pub const DEFAULT_CONCEPT_FALSE_POSITIVES: [&str; 3] = ["The Next", "This Is", "You Can"];
+
+/// Source kinds cross-source linking understands
+pub const CROSS_LINK_KINDS: [&str; 2] = ["code", "document"];
+
+/// Parse the count `name` is set to, using `default` when it's unset
+/// 
+/// A malformed or out-of-range value is a startup error rather than a silent
+/// fallback, so a typo can't leave the pool at a size nobody asked for.
+pub(crate) fn parse_in_range(
+    name: &str,
+    value: Option<String>,
+    range: RangeInclusive<usize>,
+    default: usize,
+) -> Result<usize, String> {
+    let Some(value) = value else {
+        return Ok(default);
+    };
+    value
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|n| range.contains(n))
+        .ok_or_else(|| format!(
+            "Invalid {} '{}' (expected {} to {})",
+            name, value, range.start(), range.end()
+        ))
+}
+
+/// Parse `EMBED_OVERFLOW_MODE`, defaulting to "window"
+/// 
+/// An unknown mode is a startup error: falling back would quietly embed
+/// something other than what was configured.
+fn parse_overflow_mode(value: Option<String>) -> Result<String, String> {
+    let Some(value) = value else {
+        return Ok("window".to_string());
+    };
+    let mode = value.trim().to_lowercase();
+    if EMBED_OVERFLOW_MODES.contains(&mode.as_str()) {
+        Ok(mode)
+    } else {
+        Err(format!("Invalid EMBED_OVERFLOW_MODE '{}' (expected one of {})", value, EMBED_OVERFLOW_MODES.join(", ")))
+    }
+}
+
+/// Parse a comma-separated list, dropping empty entries
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse `model=index,model=index` pairs, ignoring malformed entries
+fn parse_model_indexes(value: &str) -> HashMap<String, String> {
+    value
+        .split(',')
+        .filter_map(|pair| {
+            let (model, index) = pair.split_once('=')?;
+            let (model, index) = (model.trim(), index.trim());
+            (!model.is_empty() && !index.is_empty()).then(|| (model.to_string(), index.to_string()))
+        })
+        .collect()
+}
+
+/// Parse `CROSS_LINK_PAIRS` as `from:to` source kind pairs, dropping repeats
+/// 
+/// A malformed pair or unknown kind is a startup error, like
+/// `EMBED_OVERFLOW_MODE`: skipping it would quietly link less than configured.
+fn parse_kind_pairs(value: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    for entry in parse_list(value) {
+        let (from, to) = entry
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid CROSS_LINK_PAIRS entry '{}' (expected from:to)", entry))?;
+        let pair = (from.trim().to_string(), to.trim().to_string());
+        for kind in [&pair.0, &pair.1] {
+            if !CROSS_LINK_KINDS.contains(&kind.as_str()) {
+                return Err(format!(
+                    "Invalid CROSS_LINK_PAIRS kind '{}' in '{}' (expected one of {})",
+                    kind, entry, CROSS_LINK_KINDS.join(", ")
+                ));
+            }
+        }
+        if !pairs.contains(&pair) {
+            pairs.push(pair);
+        }
+    }
+    Ok(pairs)
+}
+
+/// Label overrides as a JSON object, inline or from a file (inline wins)
+/// 
+/// Malformed JSON is a startup error rather than a silent fallback, since it
+/// would put nodes under labels other tooling doesn't expect.
+fn load_label_mapping(inline: Option<String>, file: Option<String>) -> HashMap<String, String> {
+    let json = match (inline, file) {
+        (Some(json), _) => json,
+        (None, Some(path)) => std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Cannot read ENTITY_LABEL_MAPPING_FILE {}: {}", path, e)),
+        (None, None) => return HashMap::new(),
+    };
+    serde_json::from_str(&json).expect("Invalid ENTITY_LABEL_MAPPING (expected a JSON object of strings)")
+}
+
+#[cfg(test)]
+impl Config {
+    /// Default configuration for unit tests (no external services required)
+    pub fn test_default() -> Self {
+        if env::var("DATABASE_URL").is_err() {
+            env::set_var("DATABASE_URL", "postgres://localhost/relation_graph_test");
+        }
+        Self::from_env()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_model_indexes() {
+        let indexes = parse_model_indexes(" e5-large = chunk_e5_idx ,bge=chunk_bge_idx,broken,=x");
+        assert_eq!(indexes.len(), 2);
+        assert_eq!(indexes["e5-large"], "chunk_e5_idx");
+        assert_eq!(indexes["bge"], "chunk_bge_idx");
+        assert!(parse_model_indexes("").is_empty());
+    }
+    
+    #[test]
+    fn test_parse_in_range() {
+        let parse = |value: Option<&str>| {
+            parse_in_range("NEO4J_MAX_CONNECTIONS", value.map(str::to_string), NEO4J_MAX_CONNECTIONS_RANGE, 10)
+        };
+        assert_eq!(parse(Some(" 64 ")), Ok(64));
+        assert_eq!(parse(None), Ok(10));
+        assert_eq!(parse(Some("0")).unwrap_err(), "Invalid NEO4J_MAX_CONNECTIONS '0' (expected 1 to 256)");
+        assert!(parse(Some("1000")).is_err());
+        assert!(parse(Some("many")).is_err());
+    }
+    
+    #[test]
+    fn test_parse_overflow_mode() {
+        assert_eq!(parse_overflow_mode(None).unwrap(), "window");
+        assert_eq!(parse_overflow_mode(Some(" Truncate ".to_string())).unwrap(), "truncate");
+        assert!(parse_overflow_mode(Some("windows".to_string())).unwrap_err().contains("EMBED_OVERFLOW_MODE"));
+    }
+    
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(parse_list(" The Next , You Can,,"), ["The Next", "You Can"]);
+        assert!(parse_list("").is_empty());
+    }
+    
+    #[test]
+    fn test_parse_kind_pairs() {
+        let pairs = parse_kind_pairs(" document : code,code:document,document:code,").unwrap();
+        assert_eq!(pairs, [
+            ("document".to_string(), "code".to_string()),
+            ("code".to_string(), "document".to_string()),
+        ]);
+        assert!(parse_kind_pairs("").unwrap().is_empty());
+        assert!(parse_kind_pairs("document:code,broken").unwrap_err().contains("'broken'"));
+        assert!(parse_kind_pairs(":code").unwrap_err().contains("kind ''"));
+        assert!(parse_kind_pairs("docs:code").unwrap_err().contains("kind 'docs'"));
+    }
+    
+    #[test]
+    fn test_load_label_mapping() {
+        let mapping = load_label_mapping(Some(r#"{"function": "Function"}"#.to_string()), None);
+        assert_eq!(mapping["function"], "Function");
+        assert!(load_label_mapping(None, None).is_empty());
+        
+        let path = env::temp_dir().join(format!("labels-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"class": "Class"}"#).unwrap();
+        let from_file = load_label_mapping(None, Some(path.display().to_string()));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(from_file["class"], "Class");
+    }
+}
@@ -1,7 +1,7 @@
 //! Error types for relation-graph service
 
 use axum::{
-    http::StatusCode,
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -13,6 +13,7 @@ pub enum GraphError {
     Neo4j(String),
     
     #[error("Zilliz error: {0}")]
+    #[allow(dead_code)]
     Zilliz(String),
     
     #[error("Database error: {0}")]
@@ -21,21 +22,41 @@ pub enum GraphError {
     #[error("Entity not found: {0}")]
     EntityNotFound(String),
     
+    #[error("Job not found: {0}")]
+    JobNotFound(String),
+    
     #[error("Invalid entity type: {0}")]
     InvalidEntityType(String),
     
     #[error("Invalid relationship type: {0}")]
     InvalidRelationshipType(String),
     
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    
     #[error("Embedding error: {0}")]
     Embedding(String),
     
+    /// The embedding service didn't answer or answered with a server error
+    #[error("Embedding service unavailable: {0}")]
+    EmbeddingUnavailable(String),
+    
+    #[error("Summarization error: {0}")]
+    Summarization(String),
+    
     #[error("Configuration error: {0}")]
     Config(String),
     
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
     
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+    
+    /// Seconds until the caller may retry
+    #[error("Rate limit exceeded; retry in {0}s")]
+    RateLimited(u64),
+    
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -45,13 +66,23 @@ pub type GraphResult<T> = Result<T, GraphError>;
 impl IntoResponse for GraphError {
     fn into_response(self) -> Response {
         let (status, error_message) = match &self {
-            GraphError::EntityNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
-            GraphError::InvalidEntityType(_) | GraphError::InvalidRelationshipType(_) => {
+            GraphError::EntityNotFound(_) | GraphError::JobNotFound(_) => {
+                (StatusCode::NOT_FOUND, self.to_string())
+            }
+            GraphError::InvalidEntityType(_)
+            | GraphError::InvalidRelationshipType(_)
+            | GraphError::InvalidRequest(_) => {
                 (StatusCode::BAD_REQUEST, self.to_string())
             }
             GraphError::ServiceUnavailable(_) => {
                 (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
             }
+            GraphError::PayloadTooLarge(_) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, self.to_string())
+            }
+            GraphError::RateLimited(_) => {
+                (StatusCode::TOO_MANY_REQUESTS, self.to_string())
+            }
             _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
@@ -60,6 +91,10 @@ impl IntoResponse for GraphError {
             "status": status.as_u16(),
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let GraphError::RateLimited(retry_after) = self {
+            response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
     }
 }
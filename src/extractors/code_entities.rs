@@ -6,6 +6,9 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use crate::models::{EntityType, RelationshipType};
+use crate::utils::normalize_language;
+use serde::Serialize;
+use super::confidence;
 
 lazy_static! {
     /// Function definitions across languages
@@ -23,14 +26,17 @@ lazy_static! {
         r"(?:GET|POST|PUT|PATCH|DELETE|HEAD|OPTIONS)\s+(/[a-zA-Z0-9_/\-{}:]*)"
     ).unwrap();
     
-    /// Issue/ticket references
+    /// Issue/ticket references (`AUTH-123`)
     static ref TICKET_PATTERN: Regex = Regex::new(
-        r"([A-Z]{2,10}-\d+)"
+        r"\b([A-Z]{2,10}-\d{1,7})\b"
     ).unwrap();
     
-    /// PR/MR references
+    /// PR/MR references (`PR #1234`, `MR 56`, `pull/1234`, `owner/repo#1234`)
+    ///
+    /// A bare `#1234` needs one of those prefixes, since it can't be told
+    /// apart from a hex color like `#333` or `#123456`.
     static ref PR_PATTERN: Regex = Regex::new(
-        r"(?:PR|MR|#)(\d+)"
+        r"\b(?:PR|MR)[ \t]?#?(\d{2,7})\b|\b(?i:pull)(?:/|[ \t]?#|[ \t](?i:request)[ \t]?#?)(\d{1,7})\b|\b[\w.-]+/[\w.-]+#(\d{1,7})\b"
     ).unwrap();
     
     /// Import patterns for various languages
@@ -55,17 +61,20 @@ lazy_static! {
 }
 
 /// An extracted entity from code
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExtractedEntity {
     pub entity_type: EntityType,
     pub name: String,
     pub confidence: f32,
     pub start_line: Option<usize>,
     pub end_line: Option<usize>,
+    /// Definition text, for terms a document defines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 /// An extracted relationship between entities
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExtractedRelationship {
     pub from_name: String,
     pub to_name: String,
@@ -74,10 +83,59 @@ pub struct ExtractedRelationship {
 }
 
 /// Result of code entity extraction
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ExtractionResult {
     pub entities: Vec<ExtractedEntity>,
     pub relationships: Vec<ExtractedRelationship>,
+    /// Names of the `Module` entities that came from import statements
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<String>,
+}
+
+/// Relationship endpoint standing for the chunk being extracted, used for
+/// references made outside any function or class
+pub const CHUNK_ENDPOINT: &str = "@chunk";
+
+/// Prefixes of `ABC-123` tokens that are standards or encodings, not tickets
+const NON_TICKET_PREFIXES: &[&str] = &["UTF", "SHA", "ISO", "AES", "RSA", "MD", "RFC", "HTTP", "TLS", "SSL"];
+
+/// File extensions tried when resolving an import to a file
+const IMPORT_EXTENSIONS: &[&str] = &[".rs", ".py", ".js", ".jsx", ".ts", ".tsx", ".go", ".java"];
+
+/// Directory entry points tried when an import names a directory
+const IMPORT_INDEX_FILES: &[&str] = &["mod.rs", "__init__.py", "index.js", "index.ts"];
+
+/// File path suffixes an imported module could live at
+///
+/// `crate::models::chunk::Chunk` gives `models/chunk/Chunk.rs`,
+/// `models/chunk.rs`, `models/chunk/mod.rs`, ...; `app.models` gives
+/// `app/models.py`, ...; `./lib/api` gives `lib/api.ts`, .... Rust paths
+/// also try their parent, since `use` usually names an item inside a module.
+pub fn import_path_suffixes(import: &str) -> Vec<String> {
+    let import = import.trim();
+    let (segments, rust_path): (Vec<&str>, bool) = if import.contains('/') || import.starts_with('.') {
+        (import.split('/').filter(|s| !matches!(*s, "" | "." | "..")).collect(), false)
+    } else if import.contains("::") {
+        (import.split("::").filter(|s| !matches!(*s, "" | "crate" | "self" | "super")).collect(), true)
+    } else {
+        (import.split('.').filter(|s| !s.is_empty()).collect(), false)
+    };
+    
+    let mut stems = vec![segments.join("/")];
+    if rust_path && segments.len() > 1 {
+        stems.push(segments[..segments.len() - 1].join("/"));
+    }
+    
+    let mut suffixes = Vec::new();
+    for stem in stems.into_iter().filter(|s| !s.is_empty()) {
+        suffixes.extend(IMPORT_EXTENSIONS.iter().map(|ext| format!("{}{}", stem, ext)));
+        suffixes.extend(IMPORT_INDEX_FILES.iter().map(|index| format!("{}/{}", stem, index)));
+        // Already has an extension (`./utils.js`)
+        if stem.rsplit('/').next().is_some_and(|name| name.contains('.')) {
+            suffixes.push(stem);
+        }
+    }
+    suffixes
 }
 
 /// Code entity extractor
@@ -94,14 +152,13 @@ impl CodeEntityExtractor {
     }
     
     /// Extract entities and relationships from code content
-    pub fn extract_with_relationships(&self, content: &str, _language: Option<&str>) -> ExtractionResult {
+    pub fn extract_with_relationships(&self, content: &str, language: Option<&str>) -> ExtractionResult {
         let mut result = ExtractionResult::default();
         let mut function_names: Vec<String> = Vec::new();
+        // Byte range of each function name, for finding its body
+        let mut function_spans: Vec<(usize, usize)> = Vec::new();
         let mut class_names: Vec<String> = Vec::new();
         
-        // Track line numbers for entities
-        let lines: Vec<&str> = content.lines().collect();
-        
         // Extract modules
         for cap in MODULE_PATTERN.captures_iter(content) {
             for i in 1..4 {
@@ -109,9 +166,10 @@ impl CodeEntityExtractor {
                     result.entities.push(ExtractedEntity {
                         entity_type: EntityType::Module,
                         name: name.as_str().to_string(),
-                        confidence: 0.9,
+                        confidence: confidence::CODE_MODULE,
                         start_line: None,
                         end_line: None,
+                        description: None,
                     });
                     break;
                 }
@@ -128,6 +186,7 @@ impl CodeEntityExtractor {
                         continue;
                     }
                     function_names.push(fn_name.clone());
+                    function_spans.push((name.start(), name.end()));
                     
                     // Find line number
                     let start_pos = cap.get(0).map(|m| m.start()).unwrap_or(0);
@@ -136,9 +195,10 @@ impl CodeEntityExtractor {
                     result.entities.push(ExtractedEntity {
                         entity_type: EntityType::Function,
                         name: fn_name,
-                        confidence: 0.9,
+                        confidence: confidence::CODE_FUNCTION,
                         start_line: Some(line_num),
                         end_line: None,
+                        description: None,
                     });
                     break;
                 }
@@ -157,9 +217,10 @@ impl CodeEntityExtractor {
                 result.entities.push(ExtractedEntity {
                     entity_type: EntityType::Class,
                     name: class_name,
-                    confidence: 0.9,
+                    confidence: confidence::CODE_CLASS,
                     start_line: Some(line_num),
                     end_line: None,
+                    description: None,
                 });
             }
         }
@@ -170,38 +231,47 @@ impl CodeEntityExtractor {
                 result.entities.push(ExtractedEntity {
                     entity_type: EntityType::CodeEntity,
                     name: endpoint.as_str().to_string(),
-                    confidence: 0.85,
+                    confidence: confidence::CODE_API_ENDPOINT,
                     start_line: None,
                     end_line: None,
+                    description: None,
                 });
             }
         }
         
-        // Extract ticket references
+        // Extract ticket and PR references, each referenced by the function or
+        // class defined above it (or the chunk itself)
+        let mut references: Vec<(EntityType, String, f32, usize)> = Vec::new();
         for cap in TICKET_PATTERN.captures_iter(content) {
             if let Some(ticket) = cap.get(1) {
-                result.entities.push(ExtractedEntity {
-                    entity_type: EntityType::Issue,
-                    name: ticket.as_str().to_string(),
-                    confidence: 0.9,
-                    start_line: None,
-                    end_line: None,
-                });
+                let prefix = ticket.as_str().split('-').next().unwrap_or_default();
+                if NON_TICKET_PREFIXES.contains(&prefix) {
+                    continue;
+                }
+                references.push((EntityType::Issue, ticket.as_str().to_string(), confidence::CODE_TICKET, ticket.start()));
             }
         }
+        for cap in PR_PATTERN.captures_iter(content) {
+            if let Some(number) = cap.iter().skip(1).flatten().next() {
+                references.push((EntityType::PullRequest, format!("#{}", number.as_str()), confidence::CODE_PULL_REQUEST, number.start()));
+            }
+        }
+        self.add_reference_entities(&mut result, content, references);
         
         // Extract imports and create IMPORTS relationships
         for cap in IMPORT_PATTERN.captures_iter(content) {
             for i in 1..5 {
                 if let Some(import_name) = cap.get(i) {
                     let import_str = import_name.as_str().to_string();
+                    result.imports.push(import_str.clone());
                     
                     result.entities.push(ExtractedEntity {
                         entity_type: EntityType::Module,
                         name: import_str.clone(),
-                        confidence: 0.8,
+                        confidence: confidence::CODE_IMPORT,
                         start_line: None,
                         end_line: None,
+                        description: None,
                     });
                     
                     // If we have classes, they import this module
@@ -210,7 +280,7 @@ impl CodeEntityExtractor {
                             from_name: class_name.clone(),
                             to_name: import_str.clone(),
                             relationship_type: RelationshipType::Imports,
-                            confidence: 0.85,
+                            confidence: confidence::CODE_IMPORTS,
                         });
                     }
                     break;
@@ -226,7 +296,7 @@ impl CodeEntityExtractor {
                     from_name: struct_name.as_str().to_string(),
                     to_name: trait_name.as_str().to_string(),
                     relationship_type: RelationshipType::Implements,
-                    confidence: 0.95,
+                    confidence: confidence::CODE_IMPLEMENTS,
                 });
             }
             // JS/TS/Java: class Child extends Parent
@@ -235,7 +305,7 @@ impl CodeEntityExtractor {
                     from_name: child.as_str().to_string(),
                     to_name: parent.as_str().to_string(),
                     relationship_type: RelationshipType::Extends,
-                    confidence: 0.95,
+                    confidence: confidence::CODE_EXTENDS,
                 });
             }
         }
@@ -248,7 +318,7 @@ impl CodeEntityExtractor {
                     from_name: primary_class.clone(),
                     to_name: fn_name.clone(),
                     relationship_type: RelationshipType::Contains,
-                    confidence: 0.8,
+                    confidence: confidence::CODE_CONTAINS,
                 });
             }
         }
@@ -256,19 +326,31 @@ impl CodeEntityExtractor {
         // Extract function calls (CALLS relationships)
         let defined_functions: std::collections::HashSet<&str> = 
             function_names.iter().map(|s| s.as_str()).collect();
+        let scopes = function_scopes(content, language, &function_spans);
         
         for cap in FUNCTION_CALL_PATTERN.captures_iter(content) {
             if let Some(called_fn) = cap.get(1) {
                 let called_name = called_fn.as_str();
                 // Only track calls to functions defined in this file
                 if defined_functions.contains(called_name) {
-                    if let Some(caller) = function_names.first() {
+                    let caller = match &scopes {
+                        // A definition's own name isn't a call; calls outside
+                        // any function body have no caller
+                        Some(_) if function_spans.iter().any(|(start, _)| *start == called_fn.start()) => None,
+                        Some(scopes) => scopes
+                            .iter()
+                            .filter(|(start, end, _)| (*start..*end).contains(&called_fn.start()))
+                            .max_by_key(|(start, _, _)| *start)
+                            .map(|(_, _, index)| &function_names[*index]),
+                        None => function_names.first(),
+                    };
+                    if let Some(caller) = caller {
                         if caller != called_name {
                             result.relationships.push(ExtractedRelationship {
                                 from_name: caller.clone(),
                                 to_name: called_name.to_string(),
                                 relationship_type: RelationshipType::Calls,
-                                confidence: 0.7,
+                                confidence: confidence::CODE_CALLS,
                             });
                         }
                     }
@@ -278,6 +360,126 @@ impl CodeEntityExtractor {
         
         result
     }
+    
+    /// Add one entity per referenced issue or PR, and REFERENCES edges from
+    /// the definition enclosing each mention
+    fn add_reference_entities(
+        &self,
+        result: &mut ExtractionResult,
+        content: &str,
+        references: Vec<(EntityType, String, f32, usize)>,
+    ) {
+        let definitions: Vec<(usize, String)> = result.entities
+            .iter()
+            .filter(|e| matches!(e.entity_type, EntityType::Function | EntityType::Class))
+            .filter_map(|e| Some((e.start_line?, e.name.clone())))
+            .collect();
+        
+        let mut seen_entities = std::collections::HashSet::new();
+        let mut seen_edges = std::collections::HashSet::new();
+        for (entity_type, name, confidence, offset) in references {
+            let line = content[..offset].matches('\n').count() + 1;
+            if seen_entities.insert(name.clone()) {
+                result.entities.push(ExtractedEntity {
+                    entity_type,
+                    name: name.clone(),
+                    confidence,
+                    start_line: Some(line),
+                    end_line: None,
+                    description: None,
+                });
+            }
+            
+            let from_name = definitions
+                .iter()
+                .filter(|(start, _)| *start <= line)
+                .max_by_key(|(start, _)| *start)
+                .map_or(CHUNK_ENDPOINT.to_string(), |(_, name)| name.clone());
+            if seen_edges.insert((from_name.clone(), name.clone())) {
+                result.relationships.push(ExtractedRelationship {
+                    from_name,
+                    to_name: name,
+                    relationship_type: RelationshipType::References,
+                    confidence: confidence::CODE_REFERENCES,
+                });
+            }
+        }
+    }
+}
+
+/// Byte range `(start, end, function index)` of each function body, for
+/// languages whose bodies can be found without parsing
+///
+/// Python bodies run until the next line indented no deeper than the `def`;
+/// JavaScript and TypeScript bodies run from the first `{` after the name to
+/// its matching `}`. Braces inside strings and comments are not skipped.
+fn function_scopes(content: &str, language: Option<&str>, spans: &[(usize, usize)]) -> Option<Vec<(usize, usize, usize)>> {
+    let scope_end: fn(&str, usize, usize) -> usize = match normalize_language(language?).as_str() {
+        "python" => python_body_end,
+        "javascript" | "typescript" => brace_body_end,
+        _ => return None,
+    };
+    Some(spans
+        .iter()
+        .enumerate()
+        .map(|(index, (start, end))| (*start, scope_end(content, *start, *end), index))
+        .collect())
+}
+
+/// End of the Python function whose name spans `name_start..name_end`
+///
+/// The signature may span several lines, so the body starts after the line
+/// holding the `:` that closes it (the first one outside brackets).
+fn python_body_end(content: &str, name_start: usize, name_end: usize) -> usize {
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+    let line_start = content[..name_start].rfind('\n').map_or(0, |i| i + 1);
+    let def_indent = indent_of(&content[line_start..]);
+    let mut depth = 0usize;
+    let signature_end = content[name_end..].char_indices().find_map(|(i, c)| {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 => return Some(name_end + i),
+            _ => {}
+        }
+        None
+    });
+    let Some(body_start) = signature_end
+        .and_then(|end| content[end..].find('\n').map(|i| end + i + 1))
+    else {
+        return content.len();
+    };
+    
+    let mut offset = body_start;
+    for line in content[body_start..].split_inclusive('\n') {
+        let code = line.trim();
+        if !code.is_empty() && !code.starts_with('#') && indent_of(line) <= def_indent {
+            return offset;
+        }
+        offset += line.len();
+    }
+    content.len()
+}
+
+/// End of the brace-delimited function whose name ends at `name_end`
+fn brace_body_end(content: &str, _name_start: usize, name_end: usize) -> usize {
+    let Some(open) = content[name_end..].find('{').map(|i| name_end + i) else {
+        return content.len();
+    };
+    let mut depth = 0usize;
+    for (i, c) in content[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return open + i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    content.len()
 }
 
 impl Default for CodeEntityExtractor {
@@ -316,4 +518,97 @@ mod tests {
         let result = extractor.extract(code, Some("rust"));
         assert!(result.iter().any(|e| e.name == "UserService"));
     }
+    
+    #[test]
+    fn test_ticket_and_pr_references() {
+        let extractor = CodeEntityExtractor::new();
+        let code = "// Workaround for AUTH-42, see PR #1234\nconst COLOR: &str = \"#333\";\nconst STYLE: &str = \"color: #123456; border: 1px solid #fff\";\n\nfn refresh_token() {\n    // Fixed in acme/auth#5678 (UTF-8 safe), follow-up in #91\n    // https://github.com/acme/auth/pull/92 and pull request 93\n}\n";
+        
+        let result = extractor.extract_with_relationships(code, Some("rust"));
+        let entities: Vec<(&str, &str)> = result.entities.iter()
+            .filter(|e| matches!(e.entity_type, EntityType::Issue | EntityType::PullRequest))
+            .map(|e| (e.entity_type.as_str(), e.name.as_str()))
+            .collect();
+        assert_eq!(entities, [
+            ("issue", "AUTH-42"),
+            ("pull_request", "#1234"),
+            ("pull_request", "#5678"),
+            ("pull_request", "#92"),
+            ("pull_request", "#93"),
+        ]);
+        
+        let references: Vec<(&str, &str)> = result.relationships.iter()
+            .filter(|r| matches!(r.relationship_type, RelationshipType::References))
+            .map(|r| (r.from_name.as_str(), r.to_name.as_str()))
+            .collect();
+        assert_eq!(references, [
+            (CHUNK_ENDPOINT, "AUTH-42"),
+            (CHUNK_ENDPOINT, "#1234"),
+            ("refresh_token", "#5678"),
+            ("refresh_token", "#92"),
+            ("refresh_token", "#93"),
+        ]);
+    }
+    
+    fn calls(result: &ExtractionResult) -> Vec<(&str, &str)> {
+        result.relationships.iter()
+            .filter(|r| matches!(r.relationship_type, RelationshipType::Calls))
+            .map(|r| (r.from_name.as_str(), r.to_name.as_str()))
+            .collect()
+    }
+    
+    #[test]
+    fn test_python_calls_attach_to_enclosing_function() {
+        let extractor = CodeEntityExtractor::new();
+        let code = "def load(path):\n    return parse(read(path))\n\n\ndef save(path, data):\n    # keep the old copy\n    backup(path)\n    write(path, data)\n\ndef parse(text):\n    pass\n\ndef read(path):\n    pass\n\ndef backup(path):\n    pass\n\ndef write(path, data):\n    pass\n\nload('config.toml')\n";
+        
+        let result = extractor.extract_with_relationships(code, Some("python"));
+        assert_eq!(calls(&result), [("load", "parse"), ("load", "read"), ("save", "backup"), ("save", "write")]);
+    }
+    
+    #[test]
+    fn test_python_multiline_signature_keeps_its_body() {
+        let extractor = CodeEntityExtractor::new();
+        let code = "def load(\n    path,\n    options={'mode': 'r'},\n) -> dict:\n    return parse(read(path))\n\ndef parse(text):\n    pass\n\ndef read(path):\n    pass\n";
+        
+        let result = extractor.extract_with_relationships(code, Some("py"));
+        assert_eq!(calls(&result), [("load", "parse"), ("load", "read")]);
+    }
+    
+    #[test]
+    fn test_javascript_calls_attach_to_enclosing_function() {
+        let extractor = CodeEntityExtractor::new();
+        let code = "function render(state) {\n  if (state.ready) {\n    return draw(state);\n  }\n  return spinner();\n}\n\nfunction submit(form) {\n  validate(form);\n}\n\nfunction draw(state) {}\nfunction spinner() {}\nfunction validate(form) {}\n";
+        
+        let result = extractor.extract_with_relationships(code, Some("javascript"));
+        assert_eq!(calls(&result), [("render", "draw"), ("render", "spinner"), ("submit", "validate")]);
+    }
+    
+    #[test]
+    fn test_imports_are_reported() {
+        let extractor = CodeEntityExtractor::new();
+        let code = "use crate::models::chunk;\nimport { api } from './lib/api';\n";
+        
+        let result = extractor.extract_with_relationships(code, None);
+        assert_eq!(result.imports, vec!["crate::models::chunk", "./lib/api"]);
+    }
+    
+    #[test]
+    fn test_import_path_suffixes() {
+        let rust = import_path_suffixes("crate::models::chunk::Chunk");
+        assert!(rust.contains(&"models/chunk/Chunk.rs".to_string()));
+        assert!(rust.contains(&"models/chunk.rs".to_string()));
+        assert!(rust.contains(&"models/chunk/mod.rs".to_string()));
+        
+        let python = import_path_suffixes("app.models");
+        assert!(python.contains(&"app/models.py".to_string()));
+        assert!(python.contains(&"app/models/__init__.py".to_string()));
+        
+        let js = import_path_suffixes("../lib/api");
+        assert!(js.contains(&"lib/api.ts".to_string()));
+        assert!(js.contains(&"lib/api/index.js".to_string()));
+        assert!(import_path_suffixes("./utils.js").contains(&"utils.js".to_string()));
+        
+        assert!(import_path_suffixes("crate::").is_empty());
+    }
 }
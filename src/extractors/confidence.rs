@@ -0,0 +1,98 @@
+//! Confidence assigned by the regex extractors
+//!
+//! Every extracted entity and relationship gets a fixed confidence based on
+//! the pattern that found it. They live here so the values are listed in one
+//! place and can be reported by `GET /api/graph/schema`.
+
+// Code entities
+pub const CODE_MODULE: f32 = 0.9;
+pub const CODE_FUNCTION: f32 = 0.9;
+pub const CODE_CLASS: f32 = 0.9;
+pub const CODE_API_ENDPOINT: f32 = 0.85;
+pub const CODE_TICKET: f32 = 0.9;
+pub const CODE_PULL_REQUEST: f32 = 0.9;
+pub const CODE_IMPORT: f32 = 0.8;
+
+// Code relationships
+pub const CODE_IMPORTS: f32 = 0.85;
+pub const CODE_IMPLEMENTS: f32 = 0.95;
+pub const CODE_EXTENDS: f32 = 0.95;
+pub const CODE_CONTAINS: f32 = 0.8;
+pub const CODE_CALLS: f32 = 0.7;
+pub const CODE_REFERENCES: f32 = 0.8;
+
+// Document entities
+pub const DOC_SECTION: f32 = 0.95;
+pub const DOC_CODE_REFERENCE: f32 = 0.85;
+pub const DOC_CONCEPT: f32 = 0.7;
+pub const DOC_API_MENTION: f32 = 0.9;
+pub const DOC_DEFINITION: f32 = 0.85;
+pub const DOC_EXTERNAL_LINK: f32 = 0.95;
+
+// Document relationships
+pub const DOC_PARENT_OF: f32 = 1.0;
+pub const DOC_REFERENCES: f32 = 0.8;
+pub const DOC_EXPLAINS: f32 = 0.9;
+pub const DOC_DEFINES: f32 = 0.9;
+
+/// All extractor confidences, grouped by extractor and kind
+pub fn extraction_confidences() -> serde_json::Value {
+    serde_json::json!({
+        "code": {
+            "entities": {
+                "module": CODE_MODULE,
+                "function": CODE_FUNCTION,
+                "class": CODE_CLASS,
+                "api_endpoint": CODE_API_ENDPOINT,
+                "ticket": CODE_TICKET,
+                "pull_request": CODE_PULL_REQUEST,
+                "import": CODE_IMPORT,
+            },
+            "relationships": {
+                "IMPORTS": CODE_IMPORTS,
+                "IMPLEMENTS": CODE_IMPLEMENTS,
+                "EXTENDS": CODE_EXTENDS,
+                "CONTAINS": CODE_CONTAINS,
+                "CALLS": CODE_CALLS,
+                "REFERENCES": CODE_REFERENCES,
+            },
+        },
+        "document": {
+            "entities": {
+                "section": DOC_SECTION,
+                "code_reference": DOC_CODE_REFERENCE,
+                "concept": DOC_CONCEPT,
+                "api_mention": DOC_API_MENTION,
+                "definition": DOC_DEFINITION,
+                "external_link": DOC_EXTERNAL_LINK,
+            },
+            "relationships": {
+                "PARENT_OF": DOC_PARENT_OF,
+                "REFERENCES": DOC_REFERENCES,
+                "EXPLAINS": DOC_EXPLAINS,
+                "DEFINES": DOC_DEFINES,
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confidences_are_in_range() {
+        let table = extraction_confidences();
+        for extractor in ["code", "document"] {
+            for kind in ["entities", "relationships"] {
+                let values = table[extractor][kind].as_object().unwrap();
+                assert!(!values.is_empty());
+                for (name, value) in values {
+                    let value = value.as_f64().unwrap();
+                    assert!((0.0..=1.0).contains(&value), "{}.{}.{} = {}", extractor, kind, name, value);
+                }
+            }
+        }
+        assert_eq!(table["code"]["relationships"]["CALLS"].as_f64(), Some(CODE_CALLS as f64));
+    }
+}
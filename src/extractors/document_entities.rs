@@ -2,11 +2,18 @@
 //!
 //! Extracts sections, headings, and concepts from document content
 //! (Markdown, plain text, etc.)
+//!
+//! Fenced code blocks are not treated as prose: their contents go through
+//! `CodeEntityExtractor` with the fence's language hint, and the enclosing
+//! section gets an `EXPLAINS` relationship to each extracted definition.
 
 use lazy_static::lazy_static;
 use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use crate::config::{Config, DEFAULT_CONCEPT_FALSE_POSITIVES};
 use crate::models::{EntityType, RelationshipType};
-use super::code_entities::{ExtractedEntity, ExtractedRelationship, ExtractionResult};
+use super::confidence;
+use super::code_entities::{CodeEntityExtractor, ExtractedEntity, ExtractedRelationship, ExtractionResult};
 
 lazy_static! {
     /// Markdown headings
@@ -19,14 +26,19 @@ lazy_static! {
         r"`([a-zA-Z_][a-zA-Z0-9_]*(?:\(\))?)`"
     ).unwrap();
     
-    /// URL patterns
+    /// URL patterns (trailing punctuation is trimmed after matching)
     static ref URL_PATTERN: Regex = Regex::new(
-        r"https?://[^\s\)\]\>]+"
+        r#"https?://[^\s<>"'`]+"#
     ).unwrap();
     
-    /// Concept/term patterns (capitalized phrases, technical terms)
+    /// Concept/term patterns (capitalized phrases, technical terms) within one line
     static ref CONCEPT_PATTERN: Regex = Regex::new(
-        r"\b([A-Z][a-zA-Z]+(?:\s+[A-Z][a-zA-Z]+)+)\b"
+        r"\b([A-Z][a-zA-Z]+(?:[ \t]+[A-Z][a-zA-Z]+)+)\b"
+    ).unwrap();
+    
+    /// Inline code spans, which concepts are never taken from
+    static ref INLINE_CODE_PATTERN: Regex = Regex::new(
+        r"`[^`\n]*`"
     ).unwrap();
     
     /// API endpoint mentions in docs
@@ -34,9 +46,14 @@ lazy_static! {
         r"(?:endpoint|API|route)[:.\s]+`?(/[a-zA-Z0-9_/\-{}:]+)`?"
     ).unwrap();
     
-    /// Definition patterns
+    /// Fenced code blocks (```lang ... ```), capturing the language hint and body
+    static ref CODE_FENCE_PATTERN: Regex = Regex::new(
+        r"(?ms)^[ \t]*```[ \t]*([\w+#.-]*)[^\n]*\n(.*?)^[ \t]*```"
+    ).unwrap();
+    
+    /// Definitions: `- **Term**: meaning` list items or `Term: meaning` lines
     static ref DEFINITION_PATTERN: Regex = Regex::new(
-        r"(?i)(?:^|\n)[\*\-]\s*\*\*([^*]+)\*\*[:\s]+(.+)|(?:^|\n)([A-Z][a-zA-Z]+):\s+(.+)"
+        r"(?m)^[ \t]*[\*\-][ \t]*\*\*([^*\n]+)\*\*(?:[ \t]*:[ \t]*|[ \t]+)(.+)$|^([A-Z][a-zA-Z]+):[ \t]+(.+)$"
     ).unwrap();
 }
 
@@ -49,15 +66,72 @@ pub struct HeadingNode {
     pub children: Vec<HeadingNode>,
 }
 
+/// A fenced code block found in a document
+#[derive(Debug, Clone)]
+pub struct CodeFence {
+    /// Language hint after the opening fence, if any
+    pub language: Option<String>,
+    pub body: String,
+    /// 1-based line of the first line inside the fence
+    pub body_start_line: usize,
+}
+
+/// Which capitalized phrases are kept as concepts
+#[derive(Debug, Clone)]
+pub struct ConceptFilter {
+    /// Phrases starting with any of these are dropped
+    pub false_positives: Vec<String>,
+    /// Shortest phrase kept, in bytes
+    pub min_length: usize,
+    /// Times a phrase must appear, unless it's part of a heading
+    pub min_occurrences: usize,
+}
+
+impl Default for ConceptFilter {
+    fn default() -> Self {
+        Self {
+            false_positives: DEFAULT_CONCEPT_FALSE_POSITIVES.iter().map(|p| p.to_string()).collect(),
+            min_length: 5,
+            min_occurrences: 2,
+        }
+    }
+}
+
+impl ConceptFilter {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            false_positives: config.concept_false_positives.clone(),
+            min_length: config.concept_min_length,
+            min_occurrences: config.concept_min_occurrences,
+        }
+    }
+    
+    /// Whether a phrase seen `occurrences` times is a concept
+    fn keeps(&self, name: &str, occurrences: usize, in_heading: bool) -> bool {
+        name.len() >= self.min_length
+            && !self.false_positives.iter().any(|fp| name.starts_with(fp.as_str()))
+            && (in_heading || occurrences >= self.min_occurrences)
+    }
+}
+
 /// Document entity extractor
-pub struct DocumentEntityExtractor;
+pub struct DocumentEntityExtractor {
+    concepts: ConceptFilter,
+}
 
 impl DocumentEntityExtractor {
     pub fn new() -> Self {
-        Self
+        Self { concepts: ConceptFilter::default() }
+    }
+    
+    /// Keep only the concepts `filter` allows
+    pub fn with_concept_filter(mut self, filter: ConceptFilter) -> Self {
+        self.concepts = filter;
+        self
     }
     
     /// Extract entities from document content
+    #[allow(dead_code)]
     pub fn extract(&self, content: &str) -> Vec<ExtractedEntity> {
         self.extract_with_relationships(content).entities
     }
@@ -66,11 +140,23 @@ impl DocumentEntityExtractor {
     pub fn extract_with_relationships(&self, content: &str) -> ExtractionResult {
         let mut result = ExtractionResult::default();
         
+        // Prose patterns run with fence bodies blanked out (line numbers are kept)
+        let (prose, fences) = Self::split_code_fences(content);
+        let content = prose.as_str();
+        
         // Extract heading hierarchy
         let headings = self.extract_heading_hierarchy(content);
         for heading in &headings {
             self.add_heading_entities(&mut result, heading, None);
         }
+        let section_lines: Vec<(usize, String)> = result.entities
+            .iter()
+            .filter(|e| matches!(e.entity_type, EntityType::Section))
+            .filter_map(|e| Some((e.start_line?, e.name.clone())))
+            .collect();
+        
+        // Defined terms become concepts carrying their definition
+        let defined = self.add_definition_entities(&mut result, content, &section_lines);
         
         // Extract code references (function/class names mentioned in docs)
         for cap in CODE_REFERENCE_PATTERN.captures_iter(content) {
@@ -83,35 +169,42 @@ impl DocumentEntityExtractor {
                 result.entities.push(ExtractedEntity {
                     entity_type: EntityType::CodeEntity,
                     name,
-                    confidence: 0.85,
-                    start_line: None,
+                    confidence: confidence::DOC_CODE_REFERENCE,
+                    start_line: Some(line_at(content, code_ref.start())),
                     end_line: None,
+                    description: None,
                 });
             }
         }
         
-        // Extract concepts (capitalized multi-word phrases)
-        let mut seen_concepts = std::collections::HashSet::new();
-        for cap in CONCEPT_PATTERN.captures_iter(content) {
-            if let Some(concept) = cap.get(1) {
-                let name = concept.as_str().to_string();
-                // Skip if too short or already seen
-                if name.len() < 5 || seen_concepts.contains(&name) {
-                    continue;
-                }
-                // Skip common false positives
-                if ["The Next", "This Is", "You Can"].iter().any(|&fp| name.starts_with(fp)) {
-                    continue;
-                }
-                seen_concepts.insert(name.clone());
-                result.entities.push(ExtractedEntity {
-                    entity_type: EntityType::Concept,
-                    name,
-                    confidence: 0.7,
-                    start_line: None,
-                    end_line: None,
-                });
+        // Extract concepts (capitalized multi-word phrases outside inline code)
+        // that recur or name part of a heading
+        let concept_text = INLINE_CODE_PATTERN.replace_all(content, |cap: &regex::Captures| " ".repeat(cap[0].len()));
+        let phrases: Vec<&str> = CONCEPT_PATTERN.captures_iter(&concept_text)
+            .filter_map(|cap| cap.get(1))
+            .map(|m| m.as_str())
+            .collect();
+        let mut occurrences: HashMap<&str, usize> = HashMap::new();
+        for phrase in &phrases {
+            *occurrences.entry(phrase).or_default() += 1;
+        }
+        let mut seen_concepts = HashSet::new();
+        for phrase in phrases {
+            let in_heading = headings.iter().any(|heading| heading_contains(heading, phrase));
+            if defined.contains(phrase)
+                || !seen_concepts.insert(phrase)
+                || !self.concepts.keeps(phrase, occurrences[phrase], in_heading)
+            {
+                continue;
             }
+            result.entities.push(ExtractedEntity {
+                entity_type: EntityType::Concept,
+                name: phrase.to_string(),
+                confidence: confidence::DOC_CONCEPT,
+                start_line: None,
+                end_line: None,
+                description: None,
+            });
         }
         
         // Extract API mentions
@@ -120,19 +213,128 @@ impl DocumentEntityExtractor {
                 result.entities.push(ExtractedEntity {
                     entity_type: EntityType::CodeEntity,
                     name: endpoint.as_str().to_string(),
-                    confidence: 0.9,
-                    start_line: None,
+                    confidence: confidence::DOC_API_MENTION,
+                    start_line: Some(line_at(content, endpoint.start())),
                     end_line: None,
+                    description: None,
                 });
             }
         }
         
-        // Create REFERENCES relationships between sections and code entities
-        self.create_reference_relationships(&mut result, content);
+        // Create REFERENCES relationships from the section around each code mention
+        self.create_reference_relationships(&mut result, &section_lines);
+        
+        // External links, referenced by every section that links to them
+        self.add_external_link_entities(&mut result, content, &section_lines);
+        
+        // Extract code examples and link them to the section that shows them
+        for fence in &fences {
+            self.add_code_fence_entities(&mut result, fence, &section_lines);
+        }
         
         result
     }
     
+    /// Find fenced code blocks, returning the content with fence bodies blanked
+    pub fn split_code_fences(content: &str) -> (String, Vec<CodeFence>) {
+        let mut prose = String::with_capacity(content.len());
+        let mut fences = Vec::new();
+        let mut last = 0;
+        
+        for cap in CODE_FENCE_PATTERN.captures_iter(content) {
+            let (Some(lang), Some(body)) = (cap.get(1), cap.get(2)) else {
+                continue;
+            };
+            
+            prose.push_str(&content[last..body.start()]);
+            prose.extend(body.as_str().chars().filter(|&c| c == '\n'));
+            last = body.end();
+            
+            fences.push(CodeFence {
+                language: Some(lang.as_str().to_lowercase()).filter(|l| !l.is_empty()),
+                body: body.as_str().to_string(),
+                body_start_line: content[..body.start()].matches('\n').count() + 1,
+            });
+        }
+        prose.push_str(&content[last..]);
+        
+        (prose, fences)
+    }
+    
+    /// Add a concept per defined term and DEFINES edges from its enclosing
+    /// section, returning the defined terms
+    fn add_definition_entities(
+        &self,
+        result: &mut ExtractionResult,
+        content: &str,
+        section_lines: &[(usize, String)],
+    ) -> HashSet<String> {
+        let mut defined = HashSet::new();
+        for cap in DEFINITION_PATTERN.captures_iter(content) {
+            let (Some(term), Some(definition)) = (cap.get(1).or(cap.get(3)), cap.get(2).or(cap.get(4))) else {
+                continue;
+            };
+            // `**Term:** meaning` keeps its colon inside the bold
+            let term = term.as_str().trim().trim_end_matches(':').trim_end();
+            if term.is_empty() || !defined.insert(term.to_string()) {
+                continue;
+            }
+            let line = line_at(content, cap.get(0).map_or(0, |m| m.start()));
+            
+            result.entities.push(ExtractedEntity {
+                entity_type: EntityType::Concept,
+                name: term.to_string(),
+                confidence: confidence::DOC_DEFINITION,
+                start_line: Some(line),
+                end_line: None,
+                description: Some(definition.as_str().trim().to_string()),
+            });
+            if let Some(section) = enclosing_section(section_lines, line) {
+                result.relationships.push(ExtractedRelationship {
+                    from_name: section,
+                    to_name: term.to_string(),
+                    relationship_type: RelationshipType::Defines,
+                    confidence: confidence::DOC_DEFINES,
+                });
+            }
+        }
+        defined
+    }
+    
+    /// Add entities from a code fence and EXPLAINS edges from its enclosing section
+    fn add_code_fence_entities(
+        &self,
+        result: &mut ExtractionResult,
+        fence: &CodeFence,
+        section_lines: &[(usize, String)],
+    ) {
+        let extraction = CodeEntityExtractor::new()
+            .extract_with_relationships(&fence.body, fence.language.as_deref());
+        
+        let section = enclosing_section(section_lines, fence.body_start_line);
+        
+        for mut entity in extraction.entities {
+            // Report lines relative to the document rather than the fence
+            entity.start_line = entity.start_line.map(|line| line + fence.body_start_line - 1);
+            entity.end_line = entity.end_line.map(|line| line + fence.body_start_line - 1);
+            
+            let is_definition = matches!(
+                entity.entity_type,
+                EntityType::Function | EntityType::Class | EntityType::Module
+            );
+            if let (Some(section), true) = (&section, is_definition) {
+                result.relationships.push(ExtractedRelationship {
+                    from_name: section.clone(),
+                    to_name: entity.name.clone(),
+                    relationship_type: RelationshipType::Explains,
+                    confidence: confidence::DOC_EXPLAINS,
+                });
+            }
+            result.entities.push(entity);
+        }
+        result.relationships.extend(extraction.relationships);
+    }
+    
     /// Extract heading hierarchy from markdown
     fn extract_heading_hierarchy(&self, content: &str) -> Vec<HeadingNode> {
         let mut headings: Vec<(usize, String, usize)> = Vec::new();
@@ -196,9 +398,10 @@ impl DocumentEntityExtractor {
         result.entities.push(ExtractedEntity {
             entity_type: EntityType::Section,
             name: heading.title.clone(),
-            confidence: 0.95,
+            confidence: confidence::DOC_SECTION,
             start_line: Some(heading.line_number),
             end_line: None,
+            description: None,
         });
         
         // Create PARENT_OF relationship if there's a parent
@@ -207,7 +410,7 @@ impl DocumentEntityExtractor {
                 from_name: parent.to_string(),
                 to_name: heading.title.clone(),
                 relationship_type: RelationshipType::ParentOf,
-                confidence: 1.0,
+                confidence: confidence::DOC_PARENT_OF,
             });
         }
         
@@ -217,37 +420,66 @@ impl DocumentEntityExtractor {
         }
     }
     
-    /// Create REFERENCES relationships between sections and mentioned code entities
-    fn create_reference_relationships(&self, result: &mut ExtractionResult, content: &str) {
-        // Get section names
-        let section_names: Vec<String> = result.entities
-            .iter()
-            .filter(|e| matches!(e.entity_type, EntityType::Section))
-            .map(|e| e.name.clone())
-            .collect();
-        
-        // Get code entity names
-        let code_entities: Vec<String> = result.entities
+    /// Create REFERENCES relationships from the section containing each
+    /// code mention (once per section and entity)
+    /// 
+    /// A section spans from its heading to the next heading of any level;
+    /// mentions above the first heading have no section.
+    fn create_reference_relationships(&self, result: &mut ExtractionResult, section_lines: &[(usize, String)]) {
+        let mut seen = HashSet::new();
+        let references: Vec<ExtractedRelationship> = result.entities
             .iter()
             .filter(|e| matches!(e.entity_type, EntityType::CodeEntity))
-            .map(|e| e.name.clone())
+            .filter_map(|e| Some((enclosing_section(section_lines, e.start_line?)?, e.name.clone())))
+            .filter(|reference| seen.insert(reference.clone()))
+            .map(|(section, name)| ExtractedRelationship {
+                from_name: section,
+                to_name: name,
+                relationship_type: RelationshipType::References,
+                confidence: confidence::DOC_REFERENCES,
+            })
             .collect();
-        
-        // For each section, check if code entities are mentioned in that section
-        // (simplified: just create relationships if both exist)
-        if let Some(main_section) = section_names.first() {
-            for code_entity in &code_entities {
+        result.relationships.extend(references);
+    }
+    
+    /// Add one entity per distinct URL and REFERENCES edges from each section
+    /// that links to it
+    fn add_external_link_entities(
+        &self,
+        result: &mut ExtractionResult,
+        content: &str,
+        section_lines: &[(usize, String)],
+    ) {
+        let mut urls = HashSet::new();
+        let mut references = HashSet::new();
+        for url in URL_PATTERN.find_iter(content) {
+            let link = trim_url(url.as_str());
+            if urls.insert(link) {
+                result.entities.push(ExtractedEntity {
+                    entity_type: EntityType::ExternalLink,
+                    name: link.to_string(),
+                    confidence: confidence::DOC_EXTERNAL_LINK,
+                    start_line: Some(line_at(content, url.start())),
+                    end_line: None,
+                    description: None,
+                });
+            }
+            let Some(section) = enclosing_section(section_lines, line_at(content, url.start())) else {
+                continue;
+            };
+            if references.insert((section.clone(), link)) {
                 result.relationships.push(ExtractedRelationship {
-                    from_name: main_section.clone(),
-                    to_name: code_entity.clone(),
+                    from_name: section,
+                    to_name: link.to_string(),
                     relationship_type: RelationshipType::References,
-                    confidence: 0.8,
+                    confidence: confidence::DOC_REFERENCES,
                 });
             }
         }
     }
     
     /// Build heading path (e.g., "# Intro > ## Setup > ### Config")
+    #[allow(dead_code)]
     pub fn build_heading_path(headings: &[HeadingNode]) -> String {
         fn collect_path(node: &HeadingNode, path: &mut Vec<String>) {
             path.push(format!("{} {}", "#".repeat(node.level), node.title));
@@ -264,6 +496,52 @@ impl DocumentEntityExtractor {
     }
 }
 
+/// Strip sentence punctuation and unbalanced closing brackets a URL match
+/// picked up, e.g. from `(see https://example.com).` or `[docs](https://example.com)`
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '*']);
+        let trimmed = match trimmed.chars().last() {
+            Some(')') if trimmed.matches('(').count() < trimmed.matches(')').count() => &trimmed[..trimmed.len() - 1],
+            Some(']') if trimmed.matches('[').count() < trimmed.matches(']').count() => &trimmed[..trimmed.len() - 1],
+            _ => trimmed,
+        };
+        if trimmed == url {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+/// 1-based line of a byte offset
+fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// Nearest heading at or above `line`
+fn enclosing_section(section_lines: &[(usize, String)], line: usize) -> Option<String> {
+    section_lines
+        .iter()
+        .filter(|(start, _)| *start <= line)
+        .max_by_key(|(start, _)| *start)
+        .map(|(_, name)| name.clone())
+}
+
+/// Whether `phrase` appears in this heading's title or any heading below it
+fn heading_contains(heading: &HeadingNode, phrase: &str) -> bool {
+    heading.title.contains(phrase) || heading.children.iter().any(|child| heading_contains(child, phrase))
+}
+
+/// 1-based lines of the markdown headings in `content`
+pub fn heading_lines(content: &str) -> Vec<usize> {
+    content.lines()
+        .enumerate()
+        .filter(|(_, line)| HEADING_PATTERN.is_match(line))
+        .map(|(i, _)| i + 1)
+        .collect()
+}
+
 impl Default for DocumentEntityExtractor {
     fn default() -> Self {
         Self::new()
@@ -303,4 +581,140 @@ Set up the `config.json` file.
         let result = extractor.extract(doc);
         assert!(result.iter().any(|e| e.name == "authenticate"));
     }
+    
+    #[test]
+    fn test_concepts_recur_or_appear_in_headings() {
+        let extractor = DocumentEntityExtractor::new();
+        let doc = r#"
+# Relation Graph
+
+Relation Graph links code to the docs that explain it. It was written by
+Ada Lovelace during the Winter Sprint.
+
+## Vector Search
+
+Chunks are embedded and stored with a Vector Index. Call `Open Session` first.
+Queries use the Vector Index too, unlike `Open Session` which is code.
+
+You Can also run it locally. This Is handy. You Can skip Docker.
+        "#;
+        
+        // One-off names, phrases inside code spans, and false positives are dropped
+        let concepts: Vec<String> = extractor.extract(doc)
+            .into_iter()
+            .filter(|e| matches!(e.entity_type, EntityType::Concept))
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(concepts, ["Relation Graph", "Vector Search", "Vector Index"]);
+    }
+    
+    #[test]
+    fn test_concept_filter_is_configurable() {
+        let doc = "Ada Lovelace wrote notes on the Analytical Engine.";
+        let names = |extractor: DocumentEntityExtractor| -> Vec<String> {
+            extractor.extract(doc)
+                .into_iter()
+                .filter(|e| matches!(e.entity_type, EntityType::Concept))
+                .map(|e| e.name)
+                .collect()
+        };
+        
+        assert!(names(DocumentEntityExtractor::new()).is_empty());
+        let lenient = ConceptFilter {
+            false_positives: vec!["Ada".to_string()],
+            min_length: 5,
+            min_occurrences: 1,
+        };
+        assert_eq!(names(DocumentEntityExtractor::new().with_concept_filter(lenient)), ["Analytical Engine"]);
+    }
+    
+    #[test]
+    fn test_definitions_produce_concepts_and_defines_edges() {
+        let extractor = DocumentEntityExtractor::new();
+        let doc = "# Glossary\n\n- **Chunk**: a slice of a source file\n* **Owner:** the tenant a chunk belongs to\n\n## Linking\n\nBoost: extra confidence for a link\n";
+        
+        let result = extractor.extract_with_relationships(doc);
+        let definition = |term: &str| result.entities.iter()
+            .find(|e| e.name == term && matches!(e.entity_type, EntityType::Concept))
+            .and_then(|e| e.description.as_deref());
+        assert_eq!(definition("Chunk"), Some("a slice of a source file"));
+        assert_eq!(definition("Owner"), Some("the tenant a chunk belongs to"));
+        assert_eq!(definition("Boost"), Some("extra confidence for a link"));
+        
+        let defines: Vec<(&str, &str)> = result.relationships.iter()
+            .filter(|r| matches!(r.relationship_type, RelationshipType::Defines))
+            .map(|r| (r.from_name.as_str(), r.to_name.as_str()))
+            .collect();
+        assert_eq!(defines, [("Glossary", "Chunk"), ("Glossary", "Owner"), ("Linking", "Boost")]);
+    }
+    
+    #[test]
+    fn test_references_come_from_the_section_with_the_mention() {
+        let extractor = DocumentEntityExtractor::new();
+        let doc = "# Auth\n\nIntro.\n\n## Logging In\n\nCall `login()` with a token.\n\n## Logging Out\n\nCall `logout()` when done, then `login()` again.\n";
+        
+        let result = extractor.extract_with_relationships(doc);
+        let references: Vec<(&str, &str)> = result.relationships.iter()
+            .filter(|r| matches!(r.relationship_type, RelationshipType::References))
+            .map(|r| (r.from_name.as_str(), r.to_name.as_str()))
+            .collect();
+        assert_eq!(references, [
+            ("Logging In", "login"),
+            ("Logging Out", "logout"),
+            ("Logging Out", "login"),
+        ]);
+    }
+    
+    #[test]
+    fn test_external_links_are_trimmed_and_deduplicated() {
+        let extractor = DocumentEntityExtractor::new();
+        let doc = "# Setup\n\nSee the [guide](https://neo4j.com/docs/vector-indexes) (or https://example.com/faq).\n\n## Background\n\nRead https://en.wikipedia.org/wiki/Graph_(abstract_data_type), then https://neo4j.com/docs/vector-indexes again.\n";
+        
+        let result = extractor.extract_with_relationships(doc);
+        let links: Vec<&str> = result.entities.iter()
+            .filter(|e| matches!(e.entity_type, EntityType::ExternalLink))
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(links, [
+            "https://neo4j.com/docs/vector-indexes",
+            "https://example.com/faq",
+            "https://en.wikipedia.org/wiki/Graph_(abstract_data_type)",
+        ]);
+        
+        let references: Vec<(&str, &str)> = result.relationships.iter()
+            .filter(|r| r.to_name.starts_with("https://"))
+            .map(|r| (r.from_name.as_str(), r.to_name.as_str()))
+            .collect();
+        assert_eq!(references, [
+            ("Setup", "https://neo4j.com/docs/vector-indexes"),
+            ("Setup", "https://example.com/faq"),
+            ("Background", "https://en.wikipedia.org/wiki/Graph_(abstract_data_type)"),
+            ("Background", "https://neo4j.com/docs/vector-indexes"),
+        ]);
+    }
+    
+    #[test]
+    fn test_code_fence_entities_are_explained_by_section() {
+        let extractor = DocumentEntityExtractor::new();
+        let doc = "# Auth\n\n## Logging In\n\nCall it like this:\n\n```rust\n# not a heading\npub fn login_user(token: &str) {}\n```\n\nDone.\n";
+        
+        let (_, fences) = DocumentEntityExtractor::split_code_fences(doc);
+        assert_eq!(fences.len(), 1);
+        assert_eq!(fences[0].language.as_deref(), Some("rust"));
+        assert_eq!(fences[0].body_start_line, 8);
+        
+        let result = extractor.extract_with_relationships(doc);
+        let login = result.entities.iter().find(|e| e.name == "login_user").unwrap();
+        assert!(matches!(login.entity_type, EntityType::Function));
+        assert_eq!(login.start_line, Some(9));
+        
+        // Lines inside the fence are code, not headings
+        assert!(!result.entities.iter().any(|e| e.name == "not a heading"));
+        
+        assert!(result.relationships.iter().any(|r| {
+            matches!(r.relationship_type, RelationshipType::Explains)
+                && r.from_name == "Logging In"
+                && r.to_name == "login_user"
+        }));
+    }
 }
@@ -1,6 +1,7 @@
 //! Entity extractors module
 
 pub mod code_entities;
+pub mod confidence;
 pub mod document_entities;
 
 pub use code_entities::CodeEntityExtractor;
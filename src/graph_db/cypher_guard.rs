@@ -0,0 +1,129 @@
+//! Read-only guard for user-supplied Cypher
+//!
+//! Rejects any statement that could mutate the graph before it reaches Neo4j.
+//! The check is deliberately conservative: a query that merely mentions a write
+//! keyword outside a string literal (e.g. as a map key) is rejected too.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use crate::error::{GraphError, GraphResult};
+
+lazy_static! {
+    /// String literals, backtick-quoted names, and comments
+    static ref NON_CODE_PATTERN: Regex = Regex::new(
+        r#"'(?:[^'\\]|\\.)*'|"(?:[^"\\]|\\.)*"|`[^`]*`|//[^\n]*|/\*(?s:.*?)\*/"#
+    ).unwrap();
+
+    /// Keywords and dotted identifiers
+    static ref WORD_PATTERN: Regex = Regex::new(
+        r"[A-Za-z_][A-Za-z0-9_.]*"
+    ).unwrap();
+
+    /// A CALL clause followed directly by a procedure name
+    static ref CALL_PATTERN: Regex = Regex::new(
+        r"(?i)\bCALL\b\s*([A-Za-z_][A-Za-z0-9_.]*)?"
+    ).unwrap();
+}
+
+/// Clauses that write to the graph or change the schema/session
+const WRITE_KEYWORDS: &[&str] = &[
+    "CREATE", "MERGE", "SET", "DELETE", "DETACH", "REMOVE", "DROP",
+    "FOREACH", "LOAD", "USE", "ALTER", "GRANT", "DENY", "REVOKE",
+    "START", "STOP", "TERMINATE",
+];
+
+/// Procedures that are safe to CALL (compared case-insensitively)
+const READ_PROCEDURES: &[&str] = &[
+    "db.labels",
+    "db.relationshiptypes",
+    "db.propertykeys",
+    "db.schema.visualization",
+    "db.schema.nodetypeproperties",
+    "db.schema.reltypeproperties",
+    "db.index.vector.querynodes",
+    "db.index.fulltext.querynodes",
+    "db.index.fulltext.queryrelationships",
+    "apoc.path.expand",
+    "apoc.path.expandconfig",
+    "apoc.path.subgraphnodes",
+    "apoc.path.spanningtree",
+    "apoc.meta.schema",
+];
+
+/// Ensure a Cypher statement is read-only
+///
+/// Returns `InvalidRequest` (400) for anything that is not a single read query.
+pub fn ensure_read_only(cypher: &str) -> GraphResult<()> {
+    let code = NON_CODE_PATTERN.replace_all(cypher, " ");
+
+    if code.trim().is_empty() {
+        return Err(GraphError::InvalidRequest("Query is empty".to_string()));
+    }
+
+    if code.trim_end().trim_end_matches(';').contains(';') {
+        return Err(GraphError::InvalidRequest(
+            "Only a single statement is allowed".to_string(),
+        ));
+    }
+
+    for word in WORD_PATTERN.find_iter(&code) {
+        let upper = word.as_str().to_uppercase();
+        if WRITE_KEYWORDS.contains(&upper.as_str()) {
+            return Err(GraphError::InvalidRequest(format!(
+                "Write clause '{}' is not allowed in read-only queries",
+                upper
+            )));
+        }
+    }
+
+    // CALL is only allowed for known read procedures (no subqueries)
+    for cap in CALL_PATTERN.captures_iter(&code) {
+        let procedure = cap.get(1).map(|m| m.as_str().to_lowercase());
+        match procedure {
+            Some(name) if READ_PROCEDURES.contains(&name.as_str()) => {}
+            Some(name) => {
+                return Err(GraphError::InvalidRequest(format!(
+                    "Procedure '{}' is not allowed in read-only queries",
+                    name
+                )));
+            }
+            None => {
+                return Err(GraphError::InvalidRequest(
+                    "CALL subqueries are not allowed in read-only queries".to_string(),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse};
+
+    #[test]
+    fn test_match_return_is_allowed() {
+        assert!(ensure_read_only("MATCH (n:FUNCTION) WHERE n.name = $name RETURN n.id LIMIT 10").is_ok());
+        assert!(ensure_read_only("MATCH (n) WHERE n.name = 'CREATE TABLE' RETURN n // DELETE").is_ok());
+        assert!(ensure_read_only("CALL db.labels() YIELD label RETURN label;").is_ok());
+    }
+
+    #[test]
+    fn test_write_clauses_are_rejected_with_400() {
+        let err = ensure_read_only("CREATE (n:FUNCTION {name: 'x'}) RETURN n").unwrap_err();
+        assert!(matches!(err, GraphError::InvalidRequest(_)));
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+
+        for cypher in [
+            "MATCH (n) DETACH DELETE n",
+            "match (n) set n.x = 1 return n",
+            "MATCH (n) CALL { WITH n MATCH (m) RETURN m } RETURN n",
+            "CALL apoc.create.node(['X'], {}) YIELD node RETURN node",
+            "MATCH (n) RETURN n; MATCH (m) DELETE m",
+        ] {
+            assert!(ensure_read_only(cypher).is_err(), "should reject: {}", cypher);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Node labels for entity types
+//!
+//! Entity nodes are labelled with their uppercased type (`function` →
+//! `FUNCTION`) unless `ENTITY_LABEL_MAPPING` names another label, e.g. to
+//! match an existing schema's PascalCase labels. Cypher can't take labels as
+//! parameters, so every label is checked to be a plain identifier before it is
+//! interpolated into a query.
+
+use std::collections::HashMap;
+
+use crate::error::{GraphError, GraphResult};
+use crate::models::EntityType;
+
+/// Whether `label` can be interpolated into Cypher as-is
+pub fn is_valid_label(label: &str) -> bool {
+    let mut chars = label.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Entity type → node label, defaulting to the uppercased type
+#[derive(Debug, Clone, Default)]
+pub struct LabelMapping {
+    labels: HashMap<String, String>,
+}
+
+impl LabelMapping {
+    /// Validate a mapping keyed by entity type (`"function"`, `"pull_request"`, ...)
+    pub fn new(mapping: &HashMap<String, String>) -> Result<Self, String> {
+        let mut labels = HashMap::new();
+        for (entity_type, label) in mapping {
+            let entity_type = entity_type.trim().to_lowercase();
+            if EntityType::from_str(&entity_type).is_none() {
+                return Err(format!("Unknown entity type '{}' in label mapping", entity_type));
+            }
+            if !is_valid_label(label) {
+                return Err(format!("Invalid label '{}' for entity type '{}'", label, entity_type));
+            }
+            labels.insert(entity_type, label.clone());
+        }
+        Ok(Self { labels })
+    }
+
+    /// Label for an entity type string (as stored on `Entity`)
+    pub fn label(&self, entity_type: &str) -> GraphResult<String> {
+        let label = self.labels
+            .get(entity_type)
+            .cloned()
+            .unwrap_or_else(|| entity_type.to_uppercase());
+        if is_valid_label(&label) {
+            Ok(label)
+        } else {
+            Err(GraphError::InvalidEntityType(entity_type.to_string()))
+        }
+    }
+
+    /// Label for a known entity type (always a valid identifier)
+    pub fn for_type(&self, entity_type: EntityType) -> String {
+        self.labels
+            .get(entity_type.as_str())
+            .cloned()
+            .unwrap_or_else(|| entity_type.as_str().to_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping_overrides_uppercase_default() {
+        let mapping = HashMap::from([
+            ("function".to_string(), "Function".to_string()),
+            ("Pull_Request".to_string(), "PullRequest".to_string()),
+        ]);
+        let labels = LabelMapping::new(&mapping).unwrap();
+
+        assert_eq!(labels.label("function").unwrap(), "Function");
+        assert_eq!(labels.for_type(EntityType::PullRequest), "PullRequest");
+        assert_eq!(labels.label("class").unwrap(), "CLASS");
+        assert_eq!(LabelMapping::default().for_type(EntityType::Function), "FUNCTION");
+    }
+
+    #[test]
+    fn test_labels_must_be_identifiers() {
+        for label in ["Function`) DETACH DELETE n //", "Has Space", "1Function", "", "Fn:Other"] {
+            let mapping = HashMap::from([("function".to_string(), label.to_string())]);
+            assert!(LabelMapping::new(&mapping).is_err(), "accepted {:?}", label);
+        }
+
+        let unknown = HashMap::from([("widget".to_string(), "Widget".to_string())]);
+        assert!(LabelMapping::new(&unknown).is_err());
+
+        // Unmapped types are checked too, since `Entity.entity_type` is a plain string
+        assert!(LabelMapping::default().label("x`) DELETE n").is_err());
+    }
+}
@@ -0,0 +1,1653 @@
+//! In-memory graph backend
+//!
+//! Keeps entities, chunks, and relationships in process memory for tests and
+//! small deployments without Neo4j (`GRAPH_BACKEND=memory`). Nothing is
+//! persisted. Vector search is a brute-force scan scored like Neo4j's cosine
+//! index. Path queries and import resolution need Neo4j.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
+use std::sync::RwLock;
+use uuid::Uuid;
+
+use crate::error::{GraphError, GraphResult};
+use crate::graph_db::labels::LabelMapping;
+use crate::graph_db::neo4j_client::{chunk_owned_elsewhere, AvailableSignals, BatchEdge, BatchEdgeResult, ChunkVectorSearch, CrossSourceMatch, CrossSourceSubgraph, ExportEdge, ExportNode, LinkBoosts, LinkableChunkFilter, LinkedChunk, Neighbor, TreeEdge};
+use crate::graph_db::store::GraphStore;
+use crate::models::{
+    CanonicalEntity, Chunk, ChunkResult, CommitChunksResponse, EdgeDirection, Entity, EntityId, EntityType, ExtractionMethod, GraphPath, NeighborQuery,
+    merge_properties, OrphanNode, ORPHAN_PROTECTED_LABELS, RecentChunk, RelationshipType, StoredNode, StoredRelationship, validate_neighbor_limit,
+};
+use crate::utils::vector_score;
+
+/// Relationship type written by cross-source linking
+const CROSS_LINK_TYPE: &str = "SEMANTICALLY_SIMILAR";
+
+/// Relationship types followed by cross-source queries
+const CROSS_SOURCE_TYPES: [&str; 5] = [
+    "EXPLAINS", "DOCUMENTS", "SEMANTICALLY_SIMILAR", "MENTIONS_EXPLICITLY", "UPDATED_NEAR",
+];
+
+#[derive(Debug, Clone)]
+struct MemoryNode {
+    label: String,
+    name: String,
+    source: String,
+    owner_id: Option<String>,
+    properties: serde_json::Value,
+    content: Option<String>,
+    embedding: Option<Vec<f32>>,
+    chunk: Option<ChunkFields>,
+}
+
+/// Chunk properties used by search and linking
+#[derive(Debug, Clone)]
+struct ChunkFields {
+    content_hash: String,
+    source_kind: String,
+    source_type: String,
+    source_id: String,
+    file_path: Option<String>,
+    repo_name: Option<String>,
+    language: Option<String>,
+    heading_path: Option<String>,
+    section_title: Option<String>,
+    author: Option<String>,
+    commit_date: Option<DateTime<Utc>>,
+    entity_names: Vec<String>,
+    summary: Option<String>,
+    below_min_tokens: bool,
+    /// `(windows, truncated)` once recorded for an oversized chunk
+    embedding_windows: Option<(usize, bool)>,
+    last_modified: DateTime<Utc>,
+}
+
+impl ChunkFields {
+    /// Whether a search with these filters may return this chunk
+    fn matches(&self, search: &ChunkVectorSearch<'_>) -> bool {
+        !(search.exclude_below_min_tokens && self.below_min_tokens)
+            && search.source_types.is_none_or(|types| types.contains(&self.source_type))
+            && search.repo_name.is_none_or(|repo| self.repo_name.as_deref() == Some(repo))
+            && search.path_glob.is_none_or(|glob| self.file_path.as_deref().is_some_and(|path| glob.matches(path)))
+            && search.languages.is_none_or(|languages| {
+                self.language.as_deref().is_some_and(|language| languages.contains(&language.to_lowercase()))
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MemoryEdge {
+    /// Kept when the edge is upserted again, like `r.id`
+    id: Uuid,
+    from_id: String,
+    to_id: String,
+    rel_type: String,
+    confidence: f32,
+    extraction_method: ExtractionMethod,
+    properties: serde_json::Value,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    /// Keyed by entity id; ordered so listings are deterministic
+    nodes: BTreeMap<String, MemoryNode>,
+    edges: Vec<MemoryEdge>,
+}
+
+/// Graph held in process memory
+#[derive(Debug, Default)]
+pub struct InMemoryGraph {
+    state: RwLock<MemoryState>,
+    labels: LabelMapping,
+    /// Make every linking search fail
+    #[cfg(test)]
+    fail_link_searches: bool,
+}
+
+impl InMemoryGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Label entity nodes by `labels` instead of their uppercased type
+    pub fn with_label_mapping(mut self, labels: LabelMapping) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_failing_link_searches(mut self) -> Self {
+        self.fail_link_searches = true;
+        self
+    }
+}
+
+fn owner_matches(node: &MemoryNode, owner_id: Option<&str>) -> bool {
+    owner_id.is_none() || node.owner_id.as_deref() == owner_id
+}
+
+/// The far end of `edge` seen from `node_id`, if the edge touches it in a direction `filter` allows
+fn edge_from<'a>(edge: &'a MemoryEdge, node_id: &str, filter: Option<EdgeDirection>) -> Option<(&'a String, EdgeDirection)> {
+    let allowed = |direction| filter.is_none_or(|filter| filter == direction);
+    if edge.from_id == node_id && allowed(EdgeDirection::Outgoing) {
+        Some((&edge.to_id, EdgeDirection::Outgoing))
+    } else if edge.to_id == node_id && allowed(EdgeDirection::Incoming) {
+        Some((&edge.from_id, EdgeDirection::Incoming))
+    } else {
+        None
+    }
+}
+
+/// Search result for a stored chunk, with a score of 1.0 and no vector score
+/// 
+/// `None` (logged) when the chunk's id isn't a UUID.
+fn chunk_result(id: &str, node: &MemoryNode, chunk: &ChunkFields) -> Option<ChunkResult> {
+    Some(ChunkResult {
+        chunk_id: EntityId::from_stored(id)?.as_uuid(),
+        content: node.content.clone().unwrap_or_default(),
+        source_kind: chunk.source_kind.clone(),
+        source_type: chunk.source_type.clone(),
+        file_path: chunk.file_path.clone(),
+        repo_name: chunk.repo_name.clone(),
+        language: chunk.language.clone(),
+        heading_path: chunk.heading_path.clone(),
+        summary: chunk.summary.clone(),
+        similarity_score: 1.0,
+        raw_score: None,
+        final_score: 1.0,
+        last_modified: Some(chunk.last_modified),
+        pinned: false,
+        cross_source_link_count: None,
+        embedding: None,
+    })
+}
+
+fn unsupported(operation: &str) -> GraphError {
+    GraphError::ServiceUnavailable(format!("{} requires the Neo4j graph backend", operation))
+}
+
+#[async_trait]
+impl GraphStore for InMemoryGraph {
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn upsert_entity_node(&self, entity: &Entity) -> GraphResult<StoredNode> {
+        let id = entity.id.to_string();
+        let label = self.labels.label(&entity.entity_type)?;
+        let mut state = self.state.write().unwrap();
+        match state.nodes.get_mut(&id) {
+            Some(node) => {
+                node.name = entity.name.clone();
+                node.properties = entity.properties.clone();
+            }
+            None => {
+                state.nodes.insert(id.clone(), MemoryNode {
+                    label,
+                    name: entity.name.clone(),
+                    source: entity.source.clone(),
+                    owner_id: entity.owner_id(),
+                    properties: entity.properties.clone(),
+                    content: None,
+                    embedding: None,
+                    chunk: None,
+                });
+            }
+        }
+        Ok(StoredNode { id: EntityId(entity.id), element_id: None })
+    }
+
+    async fn create_relationship(
+        &self,
+        from_id: &EntityId,
+        to_id: &EntityId,
+        rel_type: RelationshipType,
+        confidence: f32,
+        extraction_method: ExtractionMethod,
+        properties: Option<serde_json::Value>,
+    ) -> GraphResult<StoredRelationship> {
+        let from_id = from_id.to_string();
+        let to_id = to_id.to_string();
+        let mut state = self.state.write().unwrap();
+        for id in [&from_id, &to_id] {
+            if !state.nodes.contains_key(id) {
+                return Err(GraphError::EntityNotFound(id.to_string()));
+            }
+        }
+
+        let id = upsert_edge(&mut state.edges, MemoryEdge {
+            id: Uuid::new_v4(),
+            from_id: from_id.to_string(),
+            to_id: to_id.to_string(),
+            rel_type: rel_type.as_str().to_string(),
+            confidence,
+            extraction_method,
+            properties: properties.unwrap_or(serde_json::json!({})),
+        });
+        Ok(StoredRelationship { id, element_id: None })
+    }
+
+    async fn upsert_canonical_node(&self, canonical: &CanonicalEntity) -> GraphResult<()> {
+        let mut state = self.state.write().unwrap();
+        state.nodes.insert(canonical.id.to_string(), MemoryNode {
+            label: "CANONICAL".to_string(),
+            name: canonical.canonical_name.clone(),
+            source: "canonical".to_string(),
+            owner_id: canonical.owner_id.clone(),
+            properties: serde_json::json!({
+                "entity_type": canonical.entity_type,
+                "source_count": canonical.source_count(),
+            }),
+            content: None,
+            embedding: None,
+            chunk: None,
+        });
+        Ok(())
+    }
+
+    async fn create_relationships_batch(
+        &self,
+        edges: &[BatchEdge],
+        extraction_method: ExtractionMethod,
+    ) -> GraphResult<BatchEdgeResult> {
+        let mut state = self.state.write().unwrap();
+        let mut result = BatchEdgeResult::default();
+        for edge in edges {
+            let (from_id, to_id, rel_type, confidence) = edge;
+            let (from_id, to_id) = (from_id.to_string(), to_id.to_string());
+            if !state.nodes.contains_key(&from_id) || !state.nodes.contains_key(&to_id) {
+                result.missing.push(edge.clone());
+                continue;
+            }
+            upsert_edge(&mut state.edges, MemoryEdge {
+                id: Uuid::new_v4(),
+                from_id,
+                to_id,
+                rel_type: rel_type.as_str().to_string(),
+                confidence: *confidence,
+                extraction_method: extraction_method.clone(),
+                properties: serde_json::json!({}),
+            });
+            result.created += 1;
+        }
+        Ok(result)
+    }
+
+    /// Breadth-first traversal up to `hops`
+    ///
+    /// Each neighbor is reported with the relationship that reached it. Like
+    /// the Neo4j query, traversal only passes through nodes in the owner's scope.
+    async fn get_neighbors(
+        &self,
+        entity_id: &EntityId,
+        relationship_types: Option<&[RelationshipType]>,
+        direction: &str,
+        hops: usize,
+        options: &NeighborQuery,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<Neighbor>> {
+        let entity_id = entity_id.to_string();
+        let state = self.state.read().unwrap();
+        match state.nodes.get(&entity_id) {
+            Some(start) if owner_matches(start, owner_id) => {}
+            _ => return Ok(Vec::new()),
+        }
+
+        let allowed_types: Option<HashSet<&str>> = relationship_types
+            .filter(|types| !types.is_empty())
+            .map(|types| types.iter().map(|t| t.as_str()).collect());
+        let (follow_outgoing, follow_incoming) = match direction {
+            "outgoing" => (true, false),
+            "incoming" => (false, true),
+            _ => (true, true),
+        };
+
+        let mut visited: HashSet<&str> = HashSet::from([entity_id.as_str()]);
+        let mut frontier = vec![entity_id.as_str()];
+        let mut seen = HashSet::new();
+        let mut neighbors = Vec::new();
+
+        for _ in 0..hops {
+            let mut next = Vec::new();
+            for current in frontier {
+                for edge in &state.edges {
+                    let other = if follow_outgoing && edge.from_id == current {
+                        edge.to_id.as_str()
+                    } else if follow_incoming && edge.to_id == current {
+                        edge.from_id.as_str()
+                    } else {
+                        continue;
+                    };
+                    if allowed_types.as_ref().is_some_and(|types| !types.contains(edge.rel_type.as_str())) {
+                        continue;
+                    }
+                    let Some(node) = state.nodes.get(other).filter(|node| owner_matches(node, owner_id)) else {
+                        continue;
+                    };
+
+                    if other != entity_id
+                        && edge.confidence >= options.min_confidence
+                        && seen.insert((other, edge.rel_type.as_str(), edge.confidence.to_bits()))
+                    {
+                        if let Some(id) = EntityId::from_stored(other) {
+                            neighbors.push(Neighbor {
+                                id,
+                                name: node.name.clone(),
+                                entity_type: node.label.clone(),
+                                source: Some(node.source.clone()),
+                                properties: node.properties.clone(),
+                                file_path: node.chunk.as_ref().and_then(|chunk| chunk.file_path.clone()),
+                                relationship: edge.rel_type.clone(),
+                                confidence: edge.confidence,
+                            });
+                        }
+                    }
+                    if visited.insert(other) {
+                        next.push(other);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        if options.order_by_confidence {
+            neighbors.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        }
+        neighbors.truncate(options.limit);
+        Ok(neighbors)
+    }
+
+    /// Breadth-first walk of outgoing edges; each node is reached once
+    async fn tree_edges(
+        &self,
+        root_id: &EntityId,
+        relationship_types: &[RelationshipType],
+        max_depth: usize,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<TreeEdge>> {
+        let root_id = root_id.to_string();
+        let state = self.state.read().unwrap();
+        match state.nodes.get(&root_id) {
+            Some(root) if owner_matches(root, owner_id) => {}
+            _ => return Ok(Vec::new()),
+        }
+
+        let allowed_types: HashSet<&str> = relationship_types.iter().map(|t| t.as_str()).collect();
+        let mut visited: HashSet<&str> = HashSet::from([root_id.as_str()]);
+        let mut frontier = vec![root_id.as_str()];
+        let mut edges = Vec::new();
+
+        for depth in 1..=max_depth {
+            let mut next = Vec::new();
+            for current in frontier {
+                for edge in state.edges.iter().filter(|edge| edge.from_id == current) {
+                    if !allowed_types.contains(edge.rel_type.as_str()) {
+                        continue;
+                    }
+                    let Some(node) = state.nodes.get(&edge.to_id).filter(|node| owner_matches(node, owner_id)) else {
+                        continue;
+                    };
+                    if !visited.insert(edge.to_id.as_str()) {
+                        continue;
+                    }
+                    let Some(id) = EntityId::from_stored(&edge.to_id) else {
+                        continue;
+                    };
+                    if edges.len() == limit {
+                        return Ok(edges);
+                    }
+                    edges.push(TreeEdge {
+                        parent_id: current.to_string(),
+                        child: Neighbor {
+                            id,
+                            name: node.name.clone(),
+                            entity_type: node.label.clone(),
+                            source: Some(node.source.clone()),
+                            properties: node.properties.clone(),
+                            file_path: node.chunk.as_ref().and_then(|chunk| chunk.file_path.clone()),
+                            relationship: edge.rel_type.clone(),
+                            confidence: edge.confidence,
+                        },
+                        depth,
+                    });
+                    next.push(edge.to_id.as_str());
+                }
+            }
+            frontier = next;
+        }
+        Ok(edges)
+    }
+
+    async fn find_entities(
+        &self,
+        entity_type: Option<EntityType>,
+        source: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<(String, String, String)>> {
+        validate_neighbor_limit(limit)?;
+        let label = entity_type.map(|t| self.labels.for_type(t));
+        let state = self.state.read().unwrap();
+
+        Ok(state.nodes
+            .iter()
+            .filter(|(_, node)| label.as_ref().is_none_or(|label| node.label == *label))
+            .filter(|(_, node)| source.is_none_or(|source| node.source == source))
+            .filter(|(_, node)| owner_matches(node, owner_id))
+            .take(limit)
+            .map(|(id, node)| (id.clone(), node.name.clone(), node.label.clone()))
+            .collect())
+    }
+
+    async fn get_entity(
+        &self,
+        entity_id: &EntityId,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<(String, String, String)>> {
+        let entity_id = entity_id.to_string();
+        let state = self.state.read().unwrap();
+        Ok(state.nodes
+            .get(&entity_id)
+            .filter(|node| owner_matches(node, owner_id))
+            .map(|node| (entity_id.to_string(), node.name.clone(), node.label.clone())))
+    }
+
+    async fn update_entity_properties(
+        &self,
+        entity_id: &EntityId,
+        name: Option<&str>,
+        patch: &serde_json::Map<String, serde_json::Value>,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<(String, String, String, serde_json::Value)>> {
+        let entity_id = entity_id.to_string();
+        let mut state = self.state.write().unwrap();
+        let Some(node) = state.nodes.get_mut(&entity_id).filter(|node| owner_matches(node, owner_id)) else {
+            return Ok(None);
+        };
+        node.properties = merge_properties(&node.properties, patch);
+        if let Some(name) = name {
+            node.name = name.to_string();
+        }
+        Ok(Some((entity_id.to_string(), node.name.clone(), node.label.clone(), node.properties.clone())))
+    }
+
+    async fn find_paths(
+        &self,
+        _start_id: &EntityId,
+        _relationship_types: Option<&[RelationshipType]>,
+        _direction: &str,
+        _hops: usize,
+        _limit: usize,
+        _owner_id: Option<&str>,
+    ) -> GraphResult<Vec<GraphPath>> {
+        let _start_id = _start_id.to_string();
+        Err(unsupported("Path search"))
+    }
+
+    async fn get_statistics(&self) -> GraphResult<serde_json::Value> {
+        let state = self.state.read().unwrap();
+        let mut by_label: BTreeMap<&str, usize> = BTreeMap::new();
+        for node in state.nodes.values() {
+            *by_label.entry(&node.label).or_default() += 1;
+        }
+        let mut by_relationship: BTreeMap<&str, usize> = BTreeMap::new();
+        for edge in &state.edges {
+            *by_relationship.entry(&edge.rel_type).or_default() += 1;
+        }
+        let embedded = state.nodes.values().filter(|node| node.embedding.is_some()).count();
+        Ok(serde_json::json!({
+            "connected": true,
+            "backend": "memory",
+            "node_count": state.nodes.len(),
+            "relationship_count": state.edges.len(),
+            "nodes_with_embeddings": embedded,
+            "nodes_without_embeddings": state.nodes.len() - embedded,
+            "by_label": by_label,
+            "by_relationship": by_relationship
+        }))
+    }
+
+    async fn upsert_chunk_node(
+        &self,
+        chunk: &Chunk,
+        summary: Option<&str>,
+        below_min_tokens: bool,
+    ) -> GraphResult<Option<String>> {
+        let id = chunk.id.to_string();
+        let mut state = self.state.write().unwrap();
+        let existing = state.nodes.get(&id);
+        if existing.is_some_and(|node| node.chunk.is_some() && node.owner_id.as_deref() != Some(chunk.owner_id.as_str())) {
+            return Err(chunk_owned_elsewhere(chunk));
+        }
+        let previous_hash = existing.and_then(|node| node.chunk.as_ref()).map(|c| c.content_hash.clone());
+        // Like the Cypher `SET`, rewriting a chunk keeps its embedding and entity names
+        let embedding = existing.and_then(|node| node.embedding.clone());
+        let entity_names = existing
+            .and_then(|node| node.chunk.as_ref())
+            .map(|c| c.entity_names.clone())
+            .unwrap_or_default();
+        let embedding_windows = existing.and_then(|node| node.chunk.as_ref()).and_then(|c| c.embedding_windows);
+
+        state.nodes.insert(id, MemoryNode {
+            label: "CHUNK".to_string(),
+            name: chunk.file_path.clone().unwrap_or_else(|| "unknown".to_string()),
+            source: chunk.source_type.clone(),
+            owner_id: Some(chunk.owner_id.clone()),
+            properties: serde_json::json!({}),
+            content: Some(chunk.content.clone()),
+            embedding,
+            chunk: Some(ChunkFields {
+                content_hash: chunk.content_hash.clone(),
+                source_kind: chunk.source_kind.clone(),
+                source_type: chunk.source_type.clone(),
+                source_id: chunk.source_id.clone(),
+                file_path: chunk.file_path.clone(),
+                repo_name: chunk.repo_name.clone(),
+                language: chunk.language.clone(),
+                heading_path: chunk.heading_path.clone(),
+                section_title: chunk.section_title.clone(),
+                author: chunk.author.clone(),
+                commit_date: chunk.commit_date,
+                entity_names,
+                summary: summary.map(|s| s.to_string()),
+                below_min_tokens,
+                embedding_windows,
+                last_modified: chunk.commit_date.unwrap_or_else(Utc::now),
+            }),
+        });
+        Ok(previous_hash)
+    }
+
+    async fn find_chunk_by_hash(&self, content_hash: &str, owner_id: &str) -> GraphResult<Option<String>> {
+        let state = self.state.read().unwrap();
+        Ok(state.nodes
+            .iter()
+            .find(|(_, node)| {
+                node.owner_id.as_deref() == Some(owner_id)
+                    && node.chunk.as_ref().is_some_and(|chunk| chunk.content_hash == content_hash)
+            })
+            .map(|(id, _)| id.clone()))
+    }
+
+    async fn set_node_embedding(
+        &self,
+        node_id: &EntityId,
+        embedding: Vec<f32>,
+        _model: &str,
+        _provider: &str,
+    ) -> GraphResult<()> {
+        let node_id = node_id.to_string();
+        if let Some(node) = self.state.write().unwrap().nodes.get_mut(&node_id) {
+            node.embedding = Some(embedding);
+        }
+        Ok(())
+    }
+
+    /// Summary vectors are not searched in memory, so they are dropped
+    async fn set_summary_embedding(&self, _node_id: &EntityId, _embedding: Vec<f32>) -> GraphResult<()> {
+        Ok(())
+    }
+
+    async fn set_chunk_entity_names(&self, chunk_id: &EntityId, names: &[String]) -> GraphResult<()> {
+        let chunk_id = chunk_id.to_string();
+        let mut state = self.state.write().unwrap();
+        if let Some(chunk) = state.nodes.get_mut(&chunk_id).and_then(|node| node.chunk.as_mut()) {
+            chunk.entity_names = names.to_vec();
+        }
+        Ok(())
+    }
+
+    async fn set_embedding_windows(&self, chunk_id: &EntityId, windows: usize, truncated: bool) -> GraphResult<()> {
+        let chunk_id = chunk_id.to_string();
+        let mut state = self.state.write().unwrap();
+        if let Some(chunk) = state.nodes.get_mut(&chunk_id).and_then(|node| node.chunk.as_mut()) {
+            chunk.embedding_windows = Some((windows, truncated));
+        }
+        Ok(())
+    }
+
+    async fn batch_set_embeddings(
+        &self,
+        updates: Vec<(EntityId, Vec<f32>, String, String)>,
+    ) -> GraphResult<usize> {
+        let mut state = self.state.write().unwrap();
+        let mut updated = 0;
+        for (node_id, embedding, _model, _provider) in updates {
+            if let Some(node) = state.nodes.get_mut(&node_id.to_string()) {
+                node.embedding = Some(embedding);
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    }
+
+    async fn nodes_missing_embeddings(
+        &self,
+        label: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<(EntityId, String)>> {
+        let state = self.state.read().unwrap();
+        Ok(state.nodes
+            .iter()
+            .filter(|(_, node)| node.embedding.is_none())
+            .filter(|(_, node)| label.is_none_or(|label| node.label == label))
+            .filter(|(_, node)| owner_matches(node, owner_id))
+            .filter_map(|(id, node)| {
+                let content = node.content.as_ref()?;
+                Some((EntityId::from_stored(id)?, content.clone()))
+            })
+            .take(limit)
+            .collect())
+    }
+
+    /// Brute-force scan over every chunk with an embedding
+    ///
+    /// All models share one vector space here, so `index_name` is ignored.
+    async fn find_similar_chunks(
+        &self,
+        embedding: Vec<f32>,
+        search: &ChunkVectorSearch<'_>,
+    ) -> GraphResult<Vec<ChunkResult>> {
+        let state = self.state.read().unwrap();
+        let mut results: Vec<ChunkResult> = state.nodes
+            .iter()
+            .filter(|(_, node)| owner_matches(node, search.owner_id))
+            .filter_map(|(id, node)| {
+                let chunk = node.chunk.as_ref().filter(|chunk| chunk.matches(search))?;
+                let stored = node.embedding.as_ref()?;
+
+                let score = vector_score(&embedding, stored);
+                if score < search.min_score {
+                    return None;
+                }
+                Some(ChunkResult {
+                    similarity_score: score,
+                    raw_score: Some(score),
+                    final_score: score,
+                    embedding: search.include_embeddings.then(|| stored.clone()),
+                    ..chunk_result(id, node, chunk)?
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| b.similarity_score.total_cmp(&a.similarity_score));
+        results.truncate(search.limit);
+        Ok(results)
+    }
+
+    /// Scan for chunks by file path or section title, and for entities by name
+    async fn find_exact_chunks(
+        &self,
+        text: &str,
+        search: &ChunkVectorSearch<'_>,
+    ) -> GraphResult<Vec<ChunkResult>> {
+        let state = self.state.read().unwrap();
+        let entity_chunks: HashSet<&str> = state.nodes
+            .values()
+            .filter(|node| node.chunk.is_none() && node.name == text)
+            .filter_map(|node| node.properties["chunk_id"].as_str())
+            .collect();
+
+        let mut results: Vec<ChunkResult> = state.nodes
+            .iter()
+            .filter(|(_, node)| owner_matches(node, search.owner_id))
+            .filter_map(|(id, node)| {
+                let chunk = node.chunk.as_ref().filter(|chunk| chunk.matches(search))?;
+                let exact = chunk.file_path.as_deref() == Some(text)
+                    || chunk.section_title.as_deref() == Some(text)
+                    || entity_chunks.contains(id.as_str());
+                if !exact {
+                    return None;
+                }
+                chunk_result(id, node, chunk)
+            })
+            .collect();
+
+        results.sort_by(|a, b| (&a.file_path, a.chunk_id).cmp(&(&b.file_path, b.chunk_id)));
+        results.truncate(search.limit);
+        Ok(results)
+    }
+
+    /// Import stubs are left unresolved in memory
+    async fn mark_import_stub(&self, _node_id: &EntityId, _path_suffixes: Vec<String>) -> GraphResult<()> {
+        Ok(())
+    }
+
+    async fn resolve_imports(&self) -> GraphResult<usize> {
+        Ok(0)
+    }
+
+    async fn link_commit_chunks(&self, commit_id: &EntityId, chunk_ids: &[EntityId]) -> GraphResult<usize> {
+        let commit_id = commit_id.to_string();
+        let mut state = self.state.write().unwrap();
+        if !state.nodes.contains_key(&commit_id) {
+            return Ok(0);
+        }
+        let mut linked = 0;
+        for chunk_id in chunk_ids.iter().map(EntityId::to_string) {
+            if state.nodes.get(&chunk_id).is_none_or(|node| node.chunk.is_none()) {
+                continue;
+            }
+            linked += 1;
+            let exists = state.edges.iter().any(|edge| {
+                edge.from_id == commit_id && edge.to_id == chunk_id && edge.rel_type == "CONTAINS"
+            });
+            if !exists {
+                state.edges.push(MemoryEdge {
+                    id: Uuid::new_v4(),
+                    from_id: commit_id.clone(),
+                    to_id: chunk_id,
+                    rel_type: RelationshipType::Contains.as_str().to_string(),
+                    confidence: 1.0,
+                    extraction_method: ExtractionMethod::Manual,
+                    properties: serde_json::json!({}),
+                });
+            }
+        }
+        Ok(linked)
+    }
+
+    async fn commit_chunks(&self, commit_sha: &str, owner_id: Option<&str>) -> GraphResult<Option<CommitChunksResponse>> {
+        let label = self.labels.for_type(EntityType::Commit);
+        let state = self.state.read().unwrap();
+        let commits: Vec<(&String, &MemoryNode)> = state.nodes
+            .iter()
+            .filter(|(_, node)| node.label == label && node.name == commit_sha && owner_matches(node, owner_id))
+            .collect();
+        let Some((_, first)) = commits.first() else {
+            return Ok(None);
+        };
+
+        let mut chunks: Vec<RecentChunk> = state.edges
+            .iter()
+            .filter(|edge| edge.rel_type == "CONTAINS" && commits.iter().any(|(id, _)| **id == edge.from_id))
+            .filter_map(|edge| {
+                let chunk = state.nodes.get(&edge.to_id)?.chunk.as_ref()?;
+                let chunk_id = EntityId::from_stored(&edge.to_id)?;
+                Some(RecentChunk {
+                    chunk_id: chunk_id.as_uuid(),
+                    source_kind: chunk.source_kind.clone(),
+                    source_type: chunk.source_type.clone(),
+                    source_id: chunk.source_id.clone(),
+                    file_path: chunk.file_path.clone(),
+                    updated_at: Some(chunk.last_modified),
+                })
+            })
+            .collect();
+        chunks.sort_by(|a, b| (&a.file_path, a.chunk_id).cmp(&(&b.file_path, b.chunk_id)));
+
+        Ok(Some(CommitChunksResponse {
+            commit_sha: commit_sha.to_string(),
+            committed_at: serde_json::from_value(first.properties["committed_at"].clone()).ok().flatten(),
+            chunks,
+        }))
+    }
+
+    /// Vector similarity plus the author-overlap boost
+    ///
+    /// Explicit mentions need the entity names Neo4j stores on chunks, so they
+    /// never boost links made in memory.
+    async fn find_similar_chunks_for_linking(
+        &self,
+        source_chunk_id: &EntityId,
+        target_source_kind: &str,
+        limit: usize,
+        min_similarity: f32,
+        boosts: &LinkBoosts,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<CrossSourceMatch>> {
+        #[cfg(test)]
+        if self.fail_link_searches {
+            return Err(GraphError::Neo4j("Linking search failed".to_string()));
+        }
+        let source_chunk_id = source_chunk_id.to_string();
+        let state = self.state.read().unwrap();
+        let Some(source) = state.nodes.get(&source_chunk_id).filter(|node| node.chunk.is_some()) else {
+            return Ok(Vec::new());
+        };
+        let (Some(source_embedding), Some(source_chunk)) = (&source.embedding, &source.chunk) else {
+            return Ok(Vec::new());
+        };
+
+        let mut matches: Vec<CrossSourceMatch> = state.nodes
+            .iter()
+            .filter(|(id, _)| id.as_str() != source_chunk_id)
+            .filter(|(_, node)| owner_matches(node, owner_id))
+            .filter_map(|(id, node)| {
+                let chunk = node.chunk.as_ref()?;
+                let embedding = node.embedding.as_ref()?;
+                if chunk.source_kind != target_source_kind || chunk.below_min_tokens {
+                    return None;
+                }
+
+                let similarity = vector_score(source_embedding, embedding);
+                if similarity < min_similarity {
+                    return None;
+                }
+                let has_explicit_mention = node.content.as_deref().is_some_and(|content| {
+                    source_chunk.entity_names.iter().any(|name| content.contains(name.as_str()))
+                });
+                let has_author_overlap = source_chunk.author.is_some() && source_chunk.author == chunk.author;
+                // Like the Cypher, each chunk is dated by its commit or its last write
+                let temporal_distance_days = (source_chunk.commit_date.is_some() || chunk.commit_date.is_some())
+                    .then(|| (source_chunk.last_modified - chunk.last_modified).num_days().abs());
+                let temporal_factor = temporal_distance_days.and_then(|days| boosts.temporal_factor(days));
+                Some(CrossSourceMatch {
+                    target_id: EntityId::from_stored(id)?,
+                    target_content: node.content.clone(),
+                    target_source_type: Some(chunk.source_type.clone()),
+                    target_file_path: chunk.file_path.clone(),
+                    similarity_score: similarity,
+                    confidence: boosts.normalized_confidence(similarity, has_explicit_mention, has_author_overlap, temporal_factor, AvailableSignals {
+                        author: source_chunk.author.is_some() && chunk.author.is_some(),
+                        temporal: temporal_distance_days.is_some(),
+                    }),
+                    has_explicit_mention,
+                    has_author_overlap,
+                    has_temporal_proximity: temporal_factor.is_some(),
+                    temporal_distance_days,
+                })
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        matches.truncate(limit);
+        Ok(matches)
+    }
+
+    async fn linkable_chunks(
+        &self,
+        filter: &LinkableChunkFilter<'_>,
+        after: &str,
+        limit: usize,
+    ) -> GraphResult<Vec<(String, String)>> {
+        let state = self.state.read().unwrap();
+        let linked: HashSet<&str> = state.edges
+            .iter()
+            .filter(|edge| edge.rel_type == CROSS_LINK_TYPE)
+            .map(|edge| edge.from_id.as_str())
+            .collect();
+
+        Ok(state.nodes
+            .range::<str, _>((std::ops::Bound::Excluded(after), std::ops::Bound::Unbounded))
+            .filter(|(id, _)| filter.chunk_ids.is_none_or(|ids| ids.contains(id)))
+            .filter(|(id, _)| filter.force || !linked.contains(id.as_str()))
+            .filter(|(_, node)| node.embedding.is_some() && owner_matches(node, filter.owner_id))
+            .filter_map(|(id, node)| {
+                let chunk = node.chunk.as_ref().filter(|chunk| !chunk.below_min_tokens)?;
+                filter.source_kind
+                    .is_none_or(|kind| chunk.source_kind == kind)
+                    .then(|| (id.clone(), chunk.source_kind.clone()))
+            })
+            .take(limit)
+            .collect())
+    }
+
+    /// Create or update the `SEMANTICALLY_SIMILAR` edge between two chunks
+    ///
+    /// Re-linking keeps the higher confidence and merges the evidence.
+    async fn create_cross_source_link(
+        &self,
+        from_id: &EntityId,
+        to_id: &EntityId,
+        confidence: f32,
+        similarity_score: f32,
+        extraction_methods: &[ExtractionMethod],
+    ) -> GraphResult<String> {
+        let from_id = from_id.to_string();
+        let to_id = to_id.to_string();
+        let mut state = self.state.write().unwrap();
+        for id in [&from_id, &to_id] {
+            if !state.nodes.contains_key(id) {
+                return Err(GraphError::EntityNotFound(id.to_string()));
+            }
+        }
+
+        let mut methods: Vec<String> = extraction_methods.iter().map(|m| m.as_str().to_string()).collect();
+        let existing = state.edges
+            .iter_mut()
+            .find(|edge| edge.from_id == from_id && edge.to_id == to_id && edge.rel_type == CROSS_LINK_TYPE);
+        match existing {
+            Some(edge) => {
+                let previous = edge.properties["extraction_methods"].as_array().cloned().unwrap_or_default();
+                for method in previous.iter().filter_map(|m| m.as_str()) {
+                    if !methods.iter().any(|m| m == method) {
+                        methods.push(method.to_string());
+                    }
+                }
+                edge.confidence = edge.confidence.max(confidence);
+                edge.properties = serde_json::json!({
+                    "similarity_score": similarity_score,
+                    "extraction_methods": methods,
+                });
+            }
+            None => state.edges.push(MemoryEdge {
+                id: Uuid::new_v4(),
+                from_id: from_id.to_string(),
+                to_id: to_id.to_string(),
+                rel_type: CROSS_LINK_TYPE.to_string(),
+                confidence,
+                extraction_method: ExtractionMethod::VectorSimilarity,
+                properties: serde_json::json!({
+                    "similarity_score": similarity_score,
+                    "extraction_methods": methods,
+                }),
+            }),
+        }
+        Ok(Uuid::new_v4().to_string())
+    }
+
+    async fn get_cross_source_subgraph(
+        &self,
+        chunk_id: &EntityId,
+        hops: usize,
+        direction: &str,
+        min_confidence: f32,
+        max_nodes: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<CrossSourceSubgraph> {
+        let chunk_id = chunk_id.to_string();
+        let state = self.state.read().unwrap();
+        let filter = EdgeDirection::filter(direction);
+        let mut subgraph = CrossSourceSubgraph::default();
+        if state.nodes.get(&chunk_id).is_none_or(|node| node.chunk.is_none()) {
+            return Ok(subgraph);
+        }
+        let mut visited = HashSet::from([chunk_id.to_string()]);
+        let mut frontier = vec![chunk_id.to_string()];
+        for depth in 1..=hops {
+            if frontier.is_empty() || subgraph.nodes.len() >= max_nodes {
+                break;
+            }
+            let links = state.edges
+                .iter()
+                .filter(|edge| CROSS_SOURCE_TYPES.contains(&edge.rel_type.as_str()) && edge.confidence >= min_confidence)
+                .filter_map(|edge| {
+                    let (from, to, direction) = [
+                        (&edge.from_id, &edge.to_id, EdgeDirection::Outgoing),
+                        (&edge.to_id, &edge.from_id, EdgeDirection::Incoming),
+                    ]
+                    .into_iter()
+                    .find(|(from, _, direction)| {
+                        frontier.contains(from) && filter.is_none_or(|filter| filter == *direction)
+                    })?;
+                    let chunk = state.nodes.get(to).filter(|node| owner_matches(node, owner_id))?.chunk.as_ref()?;
+                    Some((from.clone(), to.clone(), edge.rel_type.clone(), edge.confidence, direction, chunk.file_path.clone()))
+                })
+                .collect();
+            frontier = subgraph.add_hop(links, depth, &mut visited, max_nodes);
+        }
+        Ok(subgraph)
+    }
+
+    async fn cross_source_chunks(
+        &self,
+        chunk_ids: &[EntityId],
+        direction: &str,
+        max_links_per_hit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<LinkedChunk>> {
+        let state = self.state.read().unwrap();
+        let filter = EdgeDirection::filter(direction);
+        let strongest = |a: &LinkedChunk, b: &LinkedChunk| {
+            b.confidence.total_cmp(&a.confidence).then_with(|| a.chunk.chunk_id.cmp(&b.chunk.chunk_id))
+        };
+        let mut linked = Vec::new();
+        for hit_id in chunk_ids {
+            let hit_key = hit_id.to_string();
+            let Some(hit) = state.nodes.get(&hit_key).and_then(|node| node.chunk.as_ref()) else {
+                continue;
+            };
+            let mut hit_links = Vec::new();
+            for edge in state.edges.iter().filter(|edge| CROSS_SOURCE_TYPES.contains(&edge.rel_type.as_str())) {
+                let Some((other, direction)) = edge_from(edge, &hit_key, filter) else {
+                    continue;
+                };
+                let Some((node, chunk)) = state.nodes
+                    .get(other)
+                    .filter(|node| owner_matches(node, owner_id))
+                    .and_then(|node| node.chunk.as_ref().map(|chunk| (node, chunk)))
+                else {
+                    continue;
+                };
+                if chunk.source_kind == hit.source_kind {
+                    continue;
+                }
+                let Some(mut result) = chunk_result(other, node, chunk) else {
+                    continue;
+                };
+                result.set_score(edge.confidence);
+                hit_links.push(LinkedChunk {
+                    hit_id: *hit_id,
+                    relationship: edge.rel_type.clone(),
+                    confidence: edge.confidence,
+                    direction,
+                    chunk: result,
+                });
+            }
+            hit_links.sort_by(strongest);
+            hit_links.truncate(max_links_per_hit);
+            linked.extend(hit_links);
+        }
+        linked.sort_by(strongest);
+        Ok(linked)
+    }
+
+    async fn count_cross_source_links(
+        &self,
+        chunk_ids: &[EntityId],
+        owner_id: Option<&str>,
+    ) -> GraphResult<HashMap<EntityId, usize>> {
+        let state = self.state.read().unwrap();
+        let mut counts: HashMap<String, usize> = chunk_ids
+            .iter()
+            .map(EntityId::to_string)
+            .filter(|id| state.nodes.get(id).is_some_and(|node| node.chunk.is_some()))
+            .map(|id| (id, 0))
+            .collect();
+        for edge in state.edges.iter().filter(|edge| CROSS_SOURCE_TYPES.contains(&edge.rel_type.as_str())) {
+            for (end, other) in [(&edge.from_id, &edge.to_id), (&edge.to_id, &edge.from_id)] {
+                let visible = state.nodes.get(other).is_some_and(|node| owner_matches(node, owner_id));
+                if let (Some(count), true) = (counts.get_mut(end), visible) {
+                    *count += 1;
+                }
+            }
+        }
+        Ok(chunk_ids.iter().filter_map(|id| Some((*id, counts.remove(&id.to_string())?))).collect())
+    }
+
+    async fn delete_cross_links(&self, chunk_id: &EntityId) -> GraphResult<usize> {
+        let chunk_id = chunk_id.to_string();
+        let mut state = self.state.write().unwrap();
+        let before = state.edges.len();
+        state.edges.retain(|edge| !(edge.rel_type == CROSS_LINK_TYPE && (edge.from_id == chunk_id || edge.to_id == chunk_id)));
+        Ok(before - state.edges.len())
+    }
+
+    async fn export_page(
+        &self,
+        owner_id: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+        include_embeddings: bool,
+    ) -> GraphResult<Vec<ExportNode>> {
+        let state = self.state.read().unwrap();
+        let start = match after {
+            Some(after) => Bound::Excluded(after.to_string()),
+            None => Bound::Unbounded,
+        };
+        let page = state.nodes
+            .range((start, Bound::Unbounded))
+            .filter(|(_, node)| owner_matches(node, owner_id))
+            .take(limit)
+            .map(|(id, node)| {
+                let edges = state.edges
+                    .iter()
+                    .filter(|edge| edge.from_id == *id)
+                    .filter(|edge| state.nodes.get(&edge.to_id).is_some_and(|target| owner_matches(target, owner_id)))
+                    .map(|edge| ExportEdge {
+                        target: edge.to_id.clone(),
+                        rel_type: edge.rel_type.clone(),
+                        confidence: Some(edge.confidence),
+                        extraction_method: Some(edge.extraction_method.as_str().to_string()),
+                    })
+                    .collect();
+                ExportNode {
+                    id: id.clone(),
+                    labels: vec![node.label.clone()],
+                    properties: export_properties(id, node, include_embeddings),
+                    edges,
+                }
+            })
+            .collect();
+        Ok(page)
+    }
+
+    async fn import_nodes(&self, nodes: &[ExportNode], owner_id: Option<&str>) -> GraphResult<Vec<String>> {
+        let mut state = self.state.write().unwrap();
+        let mut written = Vec::new();
+        for node in nodes {
+            if owner_id.is_some() && state.nodes.get(&node.id).is_some_and(|existing| !owner_matches(existing, owner_id)) {
+                continue;
+            }
+            let imported = imported_node(node);
+            match state.nodes.get_mut(&node.id) {
+                Some(existing) => merge_imported(existing, imported),
+                None => {
+                    state.nodes.insert(node.id.clone(), imported);
+                }
+            }
+            written.push(node.id.clone());
+        }
+        Ok(written)
+    }
+
+    async fn import_relationships(&self, edges: &[(String, ExportEdge)], owner_id: Option<&str>) -> GraphResult<usize> {
+        let mut state = self.state.write().unwrap();
+        let mut written = 0;
+        for (from_id, edge) in edges {
+            let visible = |id: &str| state.nodes.get(id).is_some_and(|node| owner_matches(node, owner_id));
+            if !visible(from_id) || !visible(&edge.target) {
+                continue;
+            }
+            let imported = MemoryEdge {
+                id: Uuid::new_v4(),
+                from_id: from_id.clone(),
+                to_id: edge.target.clone(),
+                rel_type: edge.rel_type.clone(),
+                confidence: edge.confidence.unwrap_or(1.0),
+                extraction_method: edge.extraction_method
+                    .as_deref()
+                    .and_then(|method| serde_json::from_value(method.into()).ok())
+                    .unwrap_or(ExtractionMethod::Manual),
+                properties: serde_json::json!({}),
+            };
+            match state.edges.iter_mut().find(|existing| {
+                existing.from_id == imported.from_id && existing.to_id == imported.to_id && existing.rel_type == imported.rel_type
+            }) {
+                Some(existing) => *existing = MemoryEdge { id: existing.id, ..imported },
+                None => state.edges.push(imported),
+            }
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    async fn prune_owner_graph(
+        &self,
+        owner_id: &str,
+        keep_nodes: &[String],
+        keep_edges: &[String],
+        _batch_size: usize,
+    ) -> GraphResult<usize> {
+        let mut state = self.state.write().unwrap();
+        let MemoryState { nodes, edges } = &mut *state;
+        let owned = |id: &str| nodes.get(id).is_some_and(|node| node.owner_id.as_deref() == Some(owner_id));
+        edges.retain(|edge| {
+            !(owned(&edge.from_id) && owned(&edge.to_id))
+                || keep_edges.contains(&format!("{}|{}|{}", edge.from_id, edge.rel_type, edge.to_id))
+        });
+        let before = nodes.len();
+        nodes.retain(|id, node| node.owner_id.as_deref() != Some(owner_id) || keep_nodes.contains(id));
+        edges.retain(|edge| nodes.contains_key(&edge.from_id) && nodes.contains_key(&edge.to_id));
+        Ok(before - nodes.len())
+    }
+
+    async fn find_orphans(
+        &self,
+        label: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<OrphanNode>> {
+        let state = self.state.read().unwrap();
+        let connected = connected_ids(&state.edges);
+        Ok(state.nodes
+            .iter()
+            .filter(|(id, _)| !connected.contains(id.as_str()))
+            .filter(|(_, node)| match label {
+                Some(label) => node.label == label,
+                None => !ORPHAN_PROTECTED_LABELS.contains(&node.label.as_str()),
+            })
+            .filter(|(_, node)| owner_matches(node, owner_id))
+            .take(limit)
+            .map(|(id, node)| OrphanNode { id: id.clone(), label: node.label.clone(), name: Some(node.name.clone()) })
+            .collect())
+    }
+
+    async fn delete_orphans(&self, ids: &[String], owner_id: Option<&str>) -> GraphResult<Vec<String>> {
+        let mut state = self.state.write().unwrap();
+        let MemoryState { nodes, edges } = &mut *state;
+        let connected = connected_ids(edges);
+        let mut deleted = Vec::new();
+        nodes.retain(|id, node| {
+            let orphan = ids.contains(id) && !connected.contains(id.as_str()) && owner_matches(node, owner_id);
+            if orphan {
+                deleted.push(id.clone());
+            }
+            !orphan
+        });
+        Ok(deleted)
+    }
+}
+
+/// Add an edge, or update the one of the same type between the same nodes (like `MERGE`)
+fn upsert_edge(edges: &mut Vec<MemoryEdge>, edge: MemoryEdge) -> Uuid {
+    match edges.iter_mut().find(|existing| {
+        existing.from_id == edge.from_id && existing.to_id == edge.to_id && existing.rel_type == edge.rel_type
+    }) {
+        Some(existing) => {
+            *existing = MemoryEdge { id: existing.id, ..edge };
+            existing.id
+        }
+        None => {
+            let id = edge.id;
+            edges.push(edge);
+            id
+        }
+    }
+}
+
+/// Ids at either end of any relationship
+fn connected_ids(edges: &[MemoryEdge]) -> HashSet<&str> {
+    edges.iter().flat_map(|edge| [edge.from_id.as_str(), edge.to_id.as_str()]).collect()
+}
+
+/// Rebuild a node from the properties `export_properties` wrote
+fn imported_node(node: &ExportNode) -> MemoryNode {
+    let mut properties = node.properties.clone();
+    let mut take = |key: &str| match properties.remove(key) {
+        Some(serde_json::Value::String(value)) => Some(value),
+        _ => None,
+    };
+    let name = take("name").unwrap_or_default();
+    let source = take("source").unwrap_or_default();
+    let owner_id = take("owner_id");
+    let content = take("content");
+    let chunk = match (take("content_hash"), take("source_kind"), take("source_type"), take("source_id")) {
+        (Some(content_hash), Some(source_kind), Some(source_type), Some(source_id)) => Some(ChunkFields {
+            content_hash,
+            source_kind,
+            source_type,
+            source_id,
+            file_path: take("file_path"),
+            repo_name: take("repo_name"),
+            language: None,
+            heading_path: None,
+            section_title: None,
+            author: None,
+            commit_date: None,
+            entity_names: Vec::new(),
+            summary: None,
+            below_min_tokens: false,
+            embedding_windows: None,
+            last_modified: Utc::now(),
+        }),
+        _ => None,
+    };
+    properties.remove("id");
+    let embedding = properties
+        .remove("embedding")
+        .and_then(|embedding| serde_json::from_value(embedding).ok());
+
+    MemoryNode {
+        label: node.labels.first().cloned().unwrap_or_default(),
+        name,
+        source,
+        owner_id,
+        properties: serde_json::Value::Object(properties),
+        content,
+        embedding,
+        chunk,
+    }
+}
+
+/// Fold an imported node into a stored one, as `SET n += properties` would
+/// 
+/// The stored node keeps its label, and anything the import leaves out
+/// (such as an embedding dropped from the export) is kept.
+fn merge_imported(existing: &mut MemoryNode, imported: MemoryNode) {
+    if let (Some(stored), serde_json::Value::Object(properties)) = (existing.properties.as_object_mut(), imported.properties) {
+        stored.extend(properties);
+    }
+    if !imported.name.is_empty() {
+        existing.name = imported.name;
+    }
+    if !imported.source.is_empty() {
+        existing.source = imported.source;
+    }
+    existing.owner_id = imported.owner_id.or(existing.owner_id.take());
+    existing.content = imported.content.or(existing.content.take());
+    existing.embedding = imported.embedding.or(existing.embedding.take());
+    existing.chunk = imported.chunk.or(existing.chunk.take());
+}
+
+/// Node properties as Neo4j would store them
+fn export_properties(id: &str, node: &MemoryNode, include_embeddings: bool) -> serde_json::Map<String, serde_json::Value> {
+    let mut properties = match &node.properties {
+        serde_json::Value::Object(map) => map.clone(),
+        _ => serde_json::Map::new(),
+    };
+    properties.insert("id".to_string(), id.into());
+    properties.insert("name".to_string(), node.name.clone().into());
+    properties.insert("source".to_string(), node.source.clone().into());
+    if let Some(owner_id) = &node.owner_id {
+        properties.insert("owner_id".to_string(), owner_id.clone().into());
+    }
+    if let Some(content) = &node.content {
+        properties.insert("content".to_string(), content.clone().into());
+    }
+    if let Some(chunk) = &node.chunk {
+        properties.insert("content_hash".to_string(), chunk.content_hash.clone().into());
+        properties.insert("source_kind".to_string(), chunk.source_kind.clone().into());
+        properties.insert("source_type".to_string(), chunk.source_type.clone().into());
+        properties.insert("source_id".to_string(), chunk.source_id.clone().into());
+        if let Some(file_path) = &chunk.file_path {
+            properties.insert("file_path".to_string(), file_path.clone().into());
+        }
+        if let Some(repo_name) = &chunk.repo_name {
+            properties.insert("repo_name".to_string(), repo_name.clone().into());
+        }
+        if let Some((windows, truncated)) = chunk.embedding_windows {
+            properties.insert("embedding_windows".to_string(), windows.into());
+            properties.insert("embedding_truncated".to_string(), truncated.into());
+        }
+    }
+    if include_embeddings {
+        if let Some(embedding) = &node.embedding {
+            properties.insert("embedding".to_string(), embedding.clone().into());
+        }
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DataSource;
+    use std::collections::HashMap;
+
+    fn entity(name: &str, entity_type: EntityType, owner: &str) -> Entity {
+        let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(owner))]);
+        Entity::new(entity_type, DataSource::LocalFile, name.to_string(), name.to_string(), properties)
+    }
+
+    #[tokio::test]
+    async fn test_neighbors_follow_direction_hops_and_owner() {
+        let graph = InMemoryGraph::new();
+        let handler = entity("handler", EntityType::Function, "tenant-a");
+        let service = entity("service", EntityType::Function, "tenant-a");
+        let repo = entity("repo", EntityType::Class, "tenant-a");
+        let other = entity("other", EntityType::Function, "tenant-b");
+        for e in [&handler, &service, &repo, &other] {
+            graph.upsert_entity_node(e).await.unwrap();
+        }
+        let id = |e: &Entity| EntityId(e.id);
+
+        for (from, to, confidence) in [(&handler, &service, 0.9), (&service, &repo, 0.6), (&handler, &other, 0.8)] {
+            graph.create_relationship(&id(from), &id(to), RelationshipType::Calls, confidence, ExtractionMethod::Manual, None)
+                .await
+                .unwrap();
+        }
+
+        let names = |neighbors: Vec<Neighbor>| {
+            neighbors.into_iter().map(|neighbor| neighbor.name).collect::<Vec<_>>()
+        };
+        let options = NeighborQuery::default();
+
+        let one_hop = graph.get_neighbors(&id(&handler), None, "outgoing", 1, &options, None).await.unwrap();
+        assert_eq!(names(one_hop), ["service", "other"]);
+
+        // Owner scope stops traversal at other tenants' nodes
+        let scoped = graph.get_neighbors(&id(&handler), None, "both", 2, &options, Some("tenant-a")).await.unwrap();
+        assert_eq!(names(scoped), ["service", "repo"]);
+
+        let incoming = graph.get_neighbors(&id(&repo), None, "incoming", 2, &options, None).await.unwrap();
+        assert_eq!(names(incoming), ["service", "handler"]);
+
+        let strongest = NeighborQuery { order_by_confidence: true, min_confidence: 0.7, limit: 10 };
+        let filtered = graph.get_neighbors(&id(&handler), None, "both", 2, &strongest, None).await.unwrap();
+        assert_eq!(names(filtered), ["service", "other"]);
+
+        let missing = graph.create_relationship(
+            &id(&handler), &EntityId(Uuid::new_v4()), RelationshipType::Calls, 1.0, ExtractionMethod::Manual, None,
+        ).await;
+        assert!(matches!(missing, Err(GraphError::EntityNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_statistics_break_counts_down_by_label_and_type() {
+        let graph = InMemoryGraph::new();
+        let login = entity("login", EntityType::Function, "tenant-a");
+        let hash = entity("hash", EntityType::Function, "tenant-a");
+        let session = entity("Session", EntityType::Class, "tenant-a");
+        let readme = entity("README", EntityType::Document, "tenant-a");
+        for e in [&login, &hash, &session, &readme] {
+            graph.upsert_entity_node(e).await.unwrap();
+        }
+        let id = |e: &Entity| EntityId(e.id);
+        graph.set_node_embedding(&id(&readme), vec![1.0, 0.0], "model", "provider").await.unwrap();
+        for (from, to, rel_type) in [
+            (&login, &hash, RelationshipType::Calls),
+            (&session, &login, RelationshipType::Contains),
+            (&session, &hash, RelationshipType::Contains),
+        ] {
+            graph.create_relationship(&id(from), &id(to), rel_type, 0.9, ExtractionMethod::Manual, None)
+                .await
+                .unwrap();
+        }
+
+        let statistics = graph.get_statistics().await.unwrap();
+        assert_eq!(statistics["by_label"], serde_json::json!({"CLASS": 1, "DOCUMENT": 1, "FUNCTION": 2}));
+        assert_eq!(statistics["by_relationship"], serde_json::json!({"CALLS": 1, "CONTAINS": 2}));
+        assert_eq!(statistics["nodes_with_embeddings"], 1);
+        assert_eq!(statistics["nodes_without_embeddings"], 3);
+
+        let sum = |counts: &serde_json::Value| counts.as_object().unwrap().values().map(|c| c.as_u64().unwrap()).sum::<u64>();
+        assert_eq!(sum(&statistics["by_label"]), statistics["node_count"].as_u64().unwrap());
+        assert_eq!(sum(&statistics["by_relationship"]), statistics["relationship_count"].as_u64().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_non_uuid_node_ids_are_skipped() {
+        let graph = InMemoryGraph::new();
+        let handler = entity("handler", EntityType::Function, "tenant-a");
+        let service = entity("service", EntityType::Function, "tenant-a");
+        graph.upsert_entity_node(&handler).await.unwrap();
+        graph.upsert_entity_node(&service).await.unwrap();
+        let mut properties = serde_json::Map::new();
+        properties.insert("name".to_string(), serde_json::json!("legacy"));
+        let legacy = ExportNode { id: "n0".to_string(), labels: vec!["Function".to_string()], properties, edges: vec![] };
+        graph.import_nodes(&[legacy], None).await.unwrap();
+        let edge = |target: String| ExportEdge {
+            target,
+            rel_type: "CALLS".to_string(),
+            confidence: Some(1.0),
+            extraction_method: None,
+        };
+        let edges = [(handler.id.to_string(), edge("n0".to_string())), (handler.id.to_string(), edge(service.id.to_string()))];
+        graph.import_relationships(&edges, None).await.unwrap();
+
+        // Left out, not reported as a made-up UUID, and the rest still come back
+        let neighbors = graph.get_neighbors(&EntityId(handler.id), None, "outgoing", 1, &NeighborQuery::default(), None).await.unwrap();
+        assert_eq!(neighbors.iter().map(|n| n.id).collect::<Vec<_>>(), [EntityId(service.id)]);
+    }
+
+    async fn stored_chunk(graph: &InMemoryGraph, source_kind: &str) -> EntityId {
+        let chunk = serde_json::from_value::<crate::models::ChunkInput>(serde_json::json!({
+            "content": "session handling",
+            "source_kind": source_kind,
+            "source_type": "github",
+            "source_id": "repo",
+            "owner_id": "owner-1",
+        })).unwrap().into_chunk();
+        graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+        EntityId(chunk.id)
+    }
+
+    #[tokio::test]
+    async fn test_chunk_ids_held_by_another_owner_are_rejected() {
+        let graph = InMemoryGraph::new();
+        let chunk = |content: &str, owner: &str| serde_json::from_value::<crate::models::ChunkInput>(serde_json::json!({
+            "id": "6f1c2b1e-8a4d-4c1e-9a57-3f0b6d2e9c11",
+            "content": content,
+            "source_kind": "code",
+            "source_type": "github",
+            "source_id": "repo",
+            "owner_id": owner,
+        })).unwrap().into_chunk();
+        let original = chunk("fn login() {}", "owner-1");
+        graph.upsert_chunk_node(&original, None, false).await.unwrap();
+
+        let result = graph.upsert_chunk_node(&chunk("fn steal() {}", "owner-2"), None, false).await;
+        assert!(matches!(result, Err(GraphError::InvalidRequest(_))));
+        assert!(graph.get_entity(&EntityId(original.id), Some("owner-1")).await.unwrap().is_some());
+        assert!(graph.get_entity(&EntityId(original.id), Some("owner-2")).await.unwrap().is_none());
+
+        // The owner can still rewrite it, and the rejected content never landed
+        let previous = graph.upsert_chunk_node(&chunk("fn login(user: &User) {}", "owner-1"), None, false).await.unwrap();
+        assert_eq!(previous, Some(original.content_hash));
+    }
+
+    #[tokio::test]
+    async fn test_cross_source_chunks_keep_strongest_links_per_hit() {
+        let graph = InMemoryGraph::new();
+        let doc_id = stored_chunk(&graph, "document").await;
+        // Confidences 0.00..0.99, created out of order
+        for i in 0..100 {
+            let code_id = stored_chunk(&graph, "code").await;
+            let confidence = ((i * 37) % 100) as f32 / 100.0;
+            graph.create_cross_source_link(&doc_id, &code_id, confidence, confidence, &[]).await.unwrap();
+        }
+        // A second hit keeps its own link even though it is weaker than the first hit's top 20
+        let other_doc_id = stored_chunk(&graph, "document").await;
+        let other_code_id = stored_chunk(&graph, "code").await;
+        graph.create_cross_source_link(&other_doc_id, &other_code_id, 0.5, 0.5, &[]).await.unwrap();
+
+        let linked = graph.cross_source_chunks(&[doc_id, other_doc_id], "both", 20, None).await.unwrap();
+        let from_doc: Vec<f32> = linked.iter().filter(|link| link.hit_id == doc_id).map(|link| link.confidence).collect();
+        let expected: Vec<f32> = (80..100).rev().map(|i| i as f32 / 100.0).collect();
+        assert_eq!(from_doc, expected);
+        let from_other: Vec<_> = linked.iter().filter(|link| link.hit_id == other_doc_id).collect();
+        assert_eq!(from_other.len(), 1);
+        assert_eq!(from_other[0].chunk.chunk_id, other_code_id.as_uuid());
+    }
+
+    #[tokio::test]
+    async fn test_cross_source_links_report_edge_direction() {
+        let graph = InMemoryGraph::new();
+        let doc_id = stored_chunk(&graph, "document").await;
+        let code_id = stored_chunk(&graph, "code").await;
+        graph.create_relationship(&doc_id, &code_id, RelationshipType::Explains, 0.9, ExtractionMethod::Manual, None)
+            .await
+            .unwrap();
+
+        let from_doc = graph.cross_source_chunks(&[doc_id], "outgoing", 10, None).await.unwrap();
+        assert_eq!(from_doc.len(), 1);
+        assert_eq!((from_doc[0].chunk.chunk_id, from_doc[0].direction), (code_id.as_uuid(), EdgeDirection::Outgoing));
+        assert_eq!(from_doc[0].relationship, "EXPLAINS");
+        assert!(graph.cross_source_chunks(&[doc_id], "incoming", 10, None).await.unwrap().is_empty());
+
+        for direction in ["incoming", "both"] {
+            let from_code = graph.cross_source_chunks(&[code_id], direction, 10, None).await.unwrap();
+            assert_eq!(from_code.len(), 1);
+            assert_eq!((from_code[0].chunk.chunk_id, from_code[0].direction), (doc_id.as_uuid(), EdgeDirection::Incoming));
+
+            let subgraph = graph.get_cross_source_subgraph(&code_id, 1, direction, 0.0, 10, None).await.unwrap();
+            assert_eq!(subgraph.edges.len(), 1);
+            assert_eq!((subgraph.edges[0].to_id, subgraph.edges[0].direction), (doc_id, EdgeDirection::Incoming));
+        }
+        assert!(graph.cross_source_chunks(&[code_id], "outgoing", 10, None).await.unwrap().is_empty());
+        assert!(graph.get_cross_source_subgraph(&code_id, 1, "outgoing", 0.0, 10, None).await.unwrap().edges.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_relationships_report_missing_endpoints() {
+        let graph = InMemoryGraph::new();
+        let functions: Vec<_> = (0..101).map(|i| entity(&format!("fn_{}", i), EntityType::Function, "tenant-a")).collect();
+        for function in &functions {
+            graph.upsert_entity_node(function).await.unwrap();
+        }
+        let mut edges: Vec<_> = functions.windows(2)
+            .map(|pair| (EntityId(pair[0].id), EntityId(pair[1].id), RelationshipType::Calls, 0.7))
+            .collect();
+        let nope = EntityId(Uuid::new_v4());
+        edges.push((EntityId(functions[0].id), nope, RelationshipType::Calls, 0.7));
+
+        let result = graph.create_relationships_batch(&edges, ExtractionMethod::PatternMatch).await.unwrap();
+        assert_eq!(result.created, 100);
+        assert_eq!(result.missing.len(), 1);
+        assert_eq!(result.missing[0].1, nope);
+
+        let last = EntityId(functions[100].id);
+        let chain = graph.get_neighbors(&last, None, "incoming", 100, &NeighborQuery::default(), None).await.unwrap();
+        assert_eq!(chain.len(), 100);
+
+        // Writing the same edges again updates them in place
+        graph.create_relationships_batch(&edges, ExtractionMethod::PatternMatch).await.unwrap();
+        assert_eq!(graph.get_statistics().await.unwrap()["relationship_count"], 100);
+    }
+
+    #[tokio::test]
+    async fn test_linking_boosts_chunks_within_temporal_window() {
+        let graph = InMemoryGraph::new();
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for (source_kind, commit_date) in [("document", None), ("code", Some(now - chrono::Duration::days(2))), ("code", Some(now - chrono::Duration::days(30)))] {
+            let chunk = serde_json::from_value::<crate::models::ChunkInput>(serde_json::json!({
+                "content": "session handling",
+                "source_kind": source_kind,
+                "source_type": "github",
+                "source_id": "repo",
+                "owner_id": "owner-1",
+                "commit_date": commit_date,
+            })).unwrap().into_chunk();
+            graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+            graph.set_node_embedding(&EntityId(chunk.id), vec![0.8, 0.6], "test", "test").await.unwrap();
+            ids.push(EntityId(chunk.id));
+        }
+        graph.set_node_embedding(&ids[0], vec![1.0, 0.0], "test", "test").await.unwrap();
+
+        let boosts = LinkBoosts {
+            explicit_mention: 0.0,
+            author_overlap: 0.0,
+            temporal_proximity: 0.1,
+            temporal_days: Some(7),
+            normalize: false,
+        };
+        let matches = graph.find_similar_chunks_for_linking(&ids[0], "code", 10, 0.0, &boosts, None).await.unwrap();
+        let by_target = |id: &EntityId| matches.iter().find(|m| m.target_id == *id).unwrap();
+
+        let recent = by_target(&ids[1]);
+        assert!(recent.has_temporal_proximity);
+        assert_eq!(recent.temporal_distance_days, Some(2));
+        assert!((recent.confidence - (recent.similarity_score + 0.1 * (1.0 - 2.0 / 7.0))).abs() < 1e-6);
+        assert!(recent.extraction_methods().contains(&ExtractionMethod::TemporalProximity));
+
+        let stale = by_target(&ids[2]);
+        assert!(!stale.has_temporal_proximity);
+        assert_eq!(stale.temporal_distance_days, Some(30));
+        assert_eq!(stale.confidence, stale.similarity_score);
+
+        // A disabled signal reports the distance but never boosts
+        let disabled = LinkBoosts { temporal_days: None, ..boosts };
+        let matches = graph.find_similar_chunks_for_linking(&ids[0], "code", 10, 0.0, &disabled, None).await.unwrap();
+        assert!(matches.iter().all(|m| !m.has_temporal_proximity && m.confidence == m.similarity_score));
+        assert_eq!(matches.iter().find(|m| m.target_id == ids[1]).unwrap().temporal_distance_days, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_find_and_get_entities() {
+        let graph = InMemoryGraph::new();
+        let function = entity("parse", EntityType::Function, "tenant-a");
+        let class = entity("Parser", EntityType::Class, "tenant-b");
+        graph.upsert_entity_node(&function).await.unwrap();
+        graph.upsert_entity_node(&class).await.unwrap();
+
+        let classes = graph.find_entities(Some(EntityType::Class), None, 10, None).await.unwrap();
+        assert_eq!(classes, [(class.id.to_string(), "Parser".to_string(), "CLASS".to_string())]);
+        assert_eq!(graph.find_entities(None, None, 10, Some("tenant-a")).await.unwrap().len(), 1);
+
+        assert!(graph.get_entity(&EntityId(class.id), Some("tenant-a")).await.unwrap().is_none());
+        let found = graph.get_entity(&EntityId(function.id), Some("tenant-a")).await.unwrap();
+        assert_eq!(found.map(|(_, name, _)| name).as_deref(), Some("parse"));
+    }
+
+    #[tokio::test]
+    async fn test_nodes_get_mapped_labels() {
+        let mapping = HashMap::from([("function".to_string(), "Function".to_string())]);
+        let graph = InMemoryGraph::new().with_label_mapping(LabelMapping::new(&mapping).unwrap());
+        let function = entity("parse", EntityType::Function, "tenant-a");
+        let class = entity("Parser", EntityType::Class, "tenant-a");
+        graph.upsert_entity_node(&function).await.unwrap();
+        graph.upsert_entity_node(&class).await.unwrap();
+
+        let functions = graph.find_entities(Some(EntityType::Function), None, 10, None).await.unwrap();
+        assert_eq!(functions, [(function.id.to_string(), "parse".to_string(), "Function".to_string())]);
+        let found = graph.get_entity(&EntityId(class.id), None).await.unwrap();
+        assert_eq!(found.map(|(_, _, label)| label).as_deref(), Some("CLASS"));
+    }
+}
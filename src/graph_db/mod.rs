@@ -1,5 +1,14 @@
 //! Graph database module
 
+pub mod cypher_guard;
+pub mod labels;
+pub mod pool;
+pub mod memory;
 pub mod neo4j_client;
+pub mod store;
+pub mod vector_indexes;
 
+pub use labels::LabelMapping;
+pub use memory::InMemoryGraph;
 pub use neo4j_client::Neo4jClient;
+pub use store::GraphStore;
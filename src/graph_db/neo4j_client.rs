@@ -2,16 +2,38 @@
 //!
 //! Supports both local Neo4j and Neo4j AuraDB (cloud).
 
+use crate::config::Config;
 use crate::error::{GraphError, GraphResult};
-use crate::models::{Entity, EntityType, Relationship, RelationshipType};
-use neo4rs::{Graph, query, ConfigBuilder};
+use crate::models::{
+    CanonicalEntity, Chunk, ChunkResult, CommitChunksResponse, EdgeDirection, Entity, EntityId, EntityType, ExtractionMethod, GraphPath, NeighborQuery,
+    merge_properties, NodeElementId, OrphanNode, ORPHAN_PROTECTED_LABELS, RecentChunk, RelationshipQuery, RelationshipSummary, RelationshipType, ReindexVectorsResponse, StoredNode, StoredRelationship, validate_neighbor_limit, VectorIndexFailure, VectorIndexInfo,
+};
+use chrono::{DateTime, Utc};
+use crate::graph_db::cypher_guard::ensure_read_only;
+use crate::graph_db::labels::{is_valid_label, LabelMapping};
+use crate::utils::PathGlob;
+use crate::graph_db::pool::{PoolSettings, TrackedGraph};
+use crate::graph_db::vector_indexes::{vector_index_name, VectorIndexRegistry};
+use neo4rs::{BoltType, Graph, Row, query, ConfigBuilder};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Node properties stored as Neo4j datetimes, exported as ISO 8601 strings
+const TEMPORAL_NODE_PROPERTIES: [&str; 5] = ["created_at", "updated_at", "commit_date", "committed_at", "embedding_timestamp"];
+
+/// Relationship types that link chunks across sources
+const CROSS_SOURCE_TYPES: &str = "EXPLAINS|DOCUMENTS|SEMANTICALLY_SIMILAR|MENTIONS_EXPLICITLY|UPDATED_NEAR";
+
 /// Neo4j client for graph database operations
 pub struct Neo4jClient {
-    graph: Arc<Graph>,
+    graph: Arc<TrackedGraph>,
     uri: String,
+    vector_indexes: VectorIndexRegistry,
+    labels: LabelMapping,
+    /// Kernel version reported by the server, when detected
+    server_version: Option<String>,
 }
 
 impl Neo4jClient {
@@ -20,7 +42,14 @@ impl Neo4jClient {
     /// Supports:
     /// - Local: `bolt://localhost:7687`
     /// - AuraDB: `neo4j+s://xxxxx.databases.neo4j.io`
+    #[cfg(all(test, feature = "integration"))]
     pub async fn new(uri: &str, user: &str, password: &str) -> GraphResult<Self> {
+        Self::connect(uri, user, password, PoolSettings::default()).await
+    }
+    
+    /// Create a client with a connection pool of `pool.max_connections`,
+    /// streaming results `pool.fetch_size` rows at a time
+    pub async fn connect(uri: &str, user: &str, password: &str, pool: PoolSettings) -> GraphResult<Self> {
         tracing::info!("🔷 Connecting to Neo4j at: {}", uri);
         
         let config = ConfigBuilder::default()
@@ -28,8 +57,8 @@ impl Neo4jClient {
             .user(user)
             .password(password)
             .db("neo4j")
-            .fetch_size(500)
-            .max_connections(10)
+            .fetch_size(pool.fetch_size)
+            .max_connections(pool.max_connections)
             .build()
             .map_err(|e| GraphError::Neo4j(format!("Config build failed: {}", e)))?;
         
@@ -47,19 +76,84 @@ impl Neo4jClient {
         }
         
         Ok(Self {
-            graph: Arc::new(graph),
+            graph: Arc::new(TrackedGraph::new(graph, pool)),
             uri: uri.to_string(),
+            vector_indexes: VectorIndexRegistry::new(false, 384),
+            labels: LabelMapping::default(),
+            server_version: None,
         })
     }
     
+    /// Lazily create a vector index for each new label that gets an embedding
+    pub fn with_auto_vector_indexes(mut self, dimension: usize) -> Self {
+        self.vector_indexes = VectorIndexRegistry::new(true, dimension);
+        self
+    }
+    
+    /// Label entity nodes by `labels` instead of their uppercased type
+    pub fn with_label_mapping(mut self, labels: LabelMapping) -> Self {
+        self.labels = labels;
+        self
+    }
+    
+    /// Record the server version detected with `fetch_server_version`
+    pub fn with_server_version(mut self, version: Option<String>) -> Self {
+        self.server_version = version;
+        self
+    }
+    
+    /// Kernel version from `dbms.components()`, e.g. `5.26.0`
+    pub async fn fetch_server_version(&self) -> GraphResult<String> {
+        let mut result = self.graph.execute(query(
+            "CALL dbms.components() YIELD name, versions WHERE name = 'Neo4j Kernel' RETURN versions[0] AS version"
+        ))
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Version check failed: {}", e)))?;
+        
+        match result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            Some(row) => row.get("version").map_err(|e| GraphError::Neo4j(e.to_string())),
+            None => Err(GraphError::Neo4j("dbms.components() reported no kernel version".to_string())),
+        }
+    }
+    
+    /// Server version, if it was detected at startup
+    pub fn server_version(&self) -> Option<&str> {
+        self.server_version.as_deref()
+    }
+    
+    /// Whether the server supports native vector indexes (`None` when unknown)
+    pub fn native_vector_indexes(&self) -> Option<bool> {
+        self.server_version.as_deref().and_then(supports_vector_indexes)
+    }
+    
+    /// Run `RETURN 1` to check that Neo4j is reachable
+    pub async fn ping(&self) -> GraphResult<()> {
+        let mut result = self.graph.execute(query("RETURN 1 as test"))
+            .await
+            .map_err(|e| GraphError::Neo4j(format!("Ping failed: {}", e)))?;
+        result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        Ok(())
+    }
+    
+    /// Connection pool utilization and wait times
+    pub fn pool_metrics(&self) -> serde_json::Value {
+        self.graph.metrics()
+    }
+    
+    /// Names of the vector indexes this client has created or verified
+    pub fn vector_index_names(&self) -> Vec<String> {
+        self.vector_indexes.index_names()
+    }
+    
     /// Check if connected to AuraDB
     pub fn is_aura(&self) -> bool {
         self.uri.contains("neo4j.io") || self.uri.starts_with("neo4j+s://")
     }
     
-    /// Create an entity node in the graph
-    pub async fn create_entity_node(&self, entity: &Entity) -> GraphResult<String> {
-        let label = entity.entity_type.to_uppercase();
+    /// Create an entity node in the graph, returning Neo4j's id for it
+    #[allow(dead_code)]
+    pub async fn create_entity_node(&self, entity: &Entity) -> GraphResult<NodeElementId> {
+        let label = self.labels.label(&entity.entity_type)?;
         let cypher = format!(
             r#"
             CREATE (n:{} {{
@@ -67,6 +161,7 @@ impl Neo4jClient {
                 name: $name,
                 source: $source,
                 source_id: $source_id,
+                owner_id: $owner_id,
                 properties: $properties,
                 created_at: datetime()
             }})
@@ -81,6 +176,7 @@ impl Neo4jClient {
                 .param("name", entity.name.clone())
                 .param("source", entity.source.clone())
                 .param("source_id", entity.source_id.clone())
+                .param("owner_id", entity.owner_id())
                 .param("properties", entity.properties.to_string())
         )
         .await
@@ -88,15 +184,15 @@ impl Neo4jClient {
         
         if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
             let node_id: String = row.get("node_id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
-            Ok(node_id)
+            Ok(NodeElementId(node_id))
         } else {
             Err(GraphError::Neo4j("Failed to create entity node".to_string()))
         }
     }
     
     /// Find or create an entity node (upsert)
-    pub async fn upsert_entity_node(&self, entity: &Entity) -> GraphResult<String> {
-        let label = entity.entity_type.to_uppercase();
+    pub async fn upsert_entity_node(&self, entity: &Entity) -> GraphResult<StoredNode> {
+        let label = self.labels.label(&entity.entity_type)?;
         let cypher = format!(
             r#"
             MERGE (n:{} {{id: $id}})
@@ -104,13 +200,15 @@ impl Neo4jClient {
                 n.name = $name,
                 n.source = $source,
                 n.source_id = $source_id,
+                n.owner_id = $owner_id,
+                n.chunk_id = $chunk_id,
                 n.properties = $properties,
                 n.created_at = datetime()
             ON MATCH SET
                 n.name = $name,
                 n.properties = $properties,
                 n.updated_at = datetime()
-            RETURN elementId(n) as node_id
+            RETURN n.id as id, elementId(n) as element_id
             "#,
             label
         );
@@ -121,77 +219,285 @@ impl Neo4jClient {
                 .param("name", entity.name.clone())
                 .param("source", entity.source.clone())
                 .param("source_id", entity.source_id.clone())
+                .param("owner_id", entity.owner_id())
+                .param("chunk_id", entity.chunk_id())
                 .param("properties", entity.properties.to_string())
         )
         .await
         .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
         if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
-            let node_id: String = row.get("node_id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
-            Ok(node_id)
+            let id: String = row.get("id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            let element_id: String = row.get("element_id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            let stored_id = Uuid::parse_str(&id)
+                .map_err(|_| GraphError::Internal(format!("Stored node id '{}' is not a UUID", id)))?;
+            Ok(StoredNode { id: EntityId(stored_id), element_id: Some(NodeElementId(element_id)) })
         } else {
             Err(GraphError::Neo4j("Failed to upsert entity node".to_string()))
         }
     }
     
-    /// Create a relationship between two entities
+    /// Create or update the `CANONICAL` node for a resolved entity
+    pub async fn upsert_canonical_node(&self, canonical: &CanonicalEntity) -> GraphResult<()> {
+        let cypher = r#"
+            MERGE (c:CANONICAL {id: $id})
+            ON CREATE SET c.created_at = datetime()
+            SET c.name = $name,
+                c.entity_type = $entity_type,
+                c.owner_id = $owner_id,
+                c.confidence = $confidence,
+                c.source_count = $source_count,
+                c.updated_at = datetime()
+        "#;
+        
+        self.graph.run(
+            query(cypher)
+                .param("id", canonical.id.to_string())
+                .param("name", canonical.canonical_name.clone())
+                .param("entity_type", canonical.entity_type.clone())
+                .param("owner_id", canonical.owner_id.clone())
+                .param("confidence", canonical.confidence_score as f64)
+                .param("source_count", canonical.source_count() as i64)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Failed to upsert canonical node: {}", e)))
+    }
+    
+    /// Find or create a chunk node (upsert)
+    /// 
+    /// Chunk fields are stored as top-level node properties so the vector
+    /// and linking queries can filter on them directly. Returns the content
+    /// hash the node had before this write (`None` for a new node), so callers
+    /// can spot an id reused for different content. An id already held by
+    /// another owner's chunk is rejected and the node left as it was.
+    pub async fn upsert_chunk_node(
+        &self,
+        chunk: &Chunk,
+        summary: Option<&str>,
+        below_min_tokens: bool,
+    ) -> GraphResult<Option<String>> {
+        let cypher = r#"
+            OPTIONAL MATCH (existing:CHUNK {id: $id})
+            WITH existing, existing.content_hash as previous_hash
+            WHERE existing IS NULL OR existing.owner_id = $owner_id
+            MERGE (n:CHUNK {id: $id})
+            ON CREATE SET
+                n.created_at = datetime()
+            SET n.name = $name,
+                n.content = $content,
+                n.content_hash = $content_hash,
+                n.source_kind = $source_kind,
+                n.source_type = $source_type,
+                n.source_id = $source_id,
+                n.file_path = $file_path,
+                n.repo_name = $repo_name,
+                n.branch = $branch,
+                n.language = $language,
+                n.heading_path = $heading_path,
+                n.section_title = $section_title,
+                n.author = $author,
+                n.commit_sha = $commit_sha,
+                n.commit_date = datetime($commit_date),
+                n.owner_id = $owner_id,
+                n.summary = $summary,
+                n.token_count = $token_count,
+                n.below_min_tokens = $below_min_tokens,
+                n.updated_at = datetime()
+            RETURN previous_hash
+        "#;
+        
+        let mut result = self.graph.execute(
+            query(cypher)
+                .param("id", chunk.id.to_string())
+                .param("name", chunk.file_path.clone().unwrap_or_else(|| "unknown".to_string()))
+                .param("content", chunk.content.clone())
+                .param("content_hash", chunk.content_hash.clone())
+                .param("source_kind", chunk.source_kind.clone())
+                .param("source_type", chunk.source_type.clone())
+                .param("source_id", chunk.source_id.clone())
+                .param("file_path", chunk.file_path.clone())
+                .param("repo_name", chunk.repo_name.clone())
+                .param("branch", chunk.branch.clone())
+                .param("language", chunk.language.clone())
+                .param("heading_path", chunk.heading_path.clone())
+                .param("section_title", chunk.section_title.clone())
+                .param("author", chunk.author.clone())
+                .param("commit_sha", chunk.commit_sha.clone())
+                .param("commit_date", chunk.commit_date.map(|date| date.to_rfc3339()))
+                .param("owner_id", chunk.owner_id.clone())
+                .param("summary", summary.map(|s| s.to_string()))
+                .param("token_count", chunk.token_count)
+                .param("below_min_tokens", below_min_tokens)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        // No row means the owner guard filtered the write out
+        if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            Ok(row.get::<Option<String>>("previous_hash").unwrap_or(None))
+        } else {
+            Err(chunk_owned_elsewhere(chunk))
+        }
+    }
+    
+    /// Id of an owner's stored chunk with this content hash, if any
+    pub async fn find_chunk_by_hash(&self, content_hash: &str, owner_id: &str) -> GraphResult<Option<String>> {
+        let cypher = r#"
+            MATCH (c:CHUNK {content_hash: $content_hash, owner_id: $owner_id})
+            RETURN c.id AS id
+            LIMIT 1
+        "#;
+        
+        let mut result = self.graph.execute(
+            query(cypher)
+                .param("content_hash", content_hash)
+                .param("owner_id", owner_id)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        match result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            Some(row) => Ok(row.get::<String>("id").ok()),
+            None => Ok(None),
+        }
+    }
+    
+    /// Create a relationship from the node with id `from_id` to the one with id `to_id`
+    /// 
+    /// Nodes are matched on their `id` property only; passing an entity name
+    /// matches nothing and returns `EntityNotFound`. An existing relationship
+    /// of the same type between the two nodes is updated instead of duplicated.
     pub async fn create_relationship(
         &self,
-        from_id: &str,
-        to_id: &str,
+        from_id: &EntityId,
+        to_id: &EntityId,
         rel_type: RelationshipType,
         confidence: f32,
+        extraction_method: ExtractionMethod,
         properties: Option<serde_json::Value>,
-    ) -> GraphResult<String> {
+    ) -> GraphResult<StoredRelationship> {
         let props = properties.unwrap_or(serde_json::json!({}));
         let cypher = format!(
             r#"
             MATCH (a), (b)
             WHERE a.id = $from_id AND b.id = $to_id
-            CREATE (a)-[r:{} {{
+            MERGE (a)-[r:{}]->(b)
+            ON CREATE SET r.created_at = datetime()
+            SET r += {{
+                id: coalesce(r.id, $rel_id),
                 confidence: $confidence,
-                properties: $properties,
-                created_at: datetime()
-            }}]->(b)
-            RETURN elementId(r) as rel_id
+                extraction_method: $extraction_method,
+                extraction_methods: [$extraction_method],
+                properties: $properties
+            }}
+            RETURN r.id as id, elementId(r) as element_id
             "#,
             rel_type.as_str()
         );
         
         let mut result = self.graph.execute(
             query(&cypher)
-                .param("from_id", from_id)
-                .param("to_id", to_id)
+                .param("from_id", from_id.to_string())
+                .param("to_id", to_id.to_string())
+                .param("rel_id", Uuid::new_v4().to_string())
                 .param("confidence", confidence as f64)
+                .param("extraction_method", extraction_method.as_str())
                 .param("properties", props.to_string())
         )
         .await
         .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
         if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
-            let rel_id: String = row.get("rel_id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
-            Ok(rel_id)
+            let id: String = row.get("id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            let element_id: String = row.get("element_id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            let stored_id = Uuid::parse_str(&id)
+                .map_err(|_| GraphError::Internal(format!("Stored relationship id '{}' is not a UUID", id)))?;
+            Ok(StoredRelationship { id: stored_id, element_id: Some(element_id) })
         } else {
-            Err(GraphError::Neo4j("Failed to create relationship".to_string()))
+            // Nothing matched: one of the ids isn't an entity id (names never match)
+            Err(GraphError::EntityNotFound(format!("{} or {}", from_id, to_id)))
+        }
+    }
+    
+    /// Create many relationships with one `UNWIND` query per relationship type
+    /// 
+    /// Like `create_relationship`, endpoints are matched on `id` and an existing
+    /// relationship of the same type is updated. Edges with a missing endpoint
+    /// are skipped and returned in `missing`.
+    pub async fn create_relationships_batch(
+        &self,
+        edges: &[BatchEdge],
+        extraction_method: ExtractionMethod,
+    ) -> GraphResult<BatchEdgeResult> {
+        // Relationship types can't be parameters, so each type gets its own query
+        let mut by_type: HashMap<&RelationshipType, Vec<usize>> = HashMap::new();
+        for (index, (_, _, rel_type, _)) in edges.iter().enumerate() {
+            by_type.entry(rel_type).or_default().push(index);
+        }
+        
+        let mut result = BatchEdgeResult::default();
+        let mut matched = vec![false; edges.len()];
+        for (rel_type, indexes) in by_type {
+            let cypher = format!(
+                r#"
+                UNWIND $edges AS edge
+                MATCH (a {{id: edge.from_id}}), (b {{id: edge.to_id}})
+                MERGE (a)-[r:{}]->(b)
+                ON CREATE SET r.created_at = datetime(), r.properties = '{{}}'
+                SET r += {{
+                    confidence: edge.confidence,
+                    extraction_method: $extraction_method,
+                    extraction_methods: [$extraction_method]
+                }}
+                RETURN edge.index AS index
+                "#,
+                rel_type.as_str()
+            );
+            let edges_param: Vec<HashMap<String, BoltType>> = indexes.iter().map(|&index| {
+                let (from_id, to_id, _, confidence) = &edges[index];
+                HashMap::from([
+                    ("index".to_string(), BoltType::from(index as i64)),
+                    ("from_id".to_string(), BoltType::from(from_id.to_string())),
+                    ("to_id".to_string(), BoltType::from(to_id.to_string())),
+                    ("confidence".to_string(), BoltType::from(*confidence as f64)),
+                ])
+            }).collect();
+            
+            let mut rows = self.graph.execute(
+                query(&cypher)
+                    .param("edges", edges_param)
+                    .param("extraction_method", extraction_method.as_str())
+            )
+            .await
+            .map_err(|e| GraphError::Neo4j(format!("Failed to batch create relationships: {}", e)))?;
+            
+            while let Some(row) = rows.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+                let index: i64 = row.get("index").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+                matched[index as usize] = true;
+                result.created += 1;
+            }
         }
+        
+        result.missing = edges.iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(edge, _)| edge.clone())
+            .collect();
+        Ok(result)
     }
     
     /// Get neighbors of an entity (n-hop traversal)
     pub async fn get_neighbors(
         &self,
-        entity_id: &str,
+        entity_id: &EntityId,
         relationship_types: Option<&[RelationshipType]>,
         direction: &str,
         hops: usize,
-    ) -> GraphResult<Vec<(String, String, String, f32)>> {
+        options: &NeighborQuery,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<Neighbor>> {
         // Build relationship pattern
-        let rel_pattern = match relationship_types {
-            Some(types) => {
-                let type_strs: Vec<&str> = types.iter().map(|t| t.as_str()).collect();
-                format!(":{}", type_strs.join("|"))
-            }
-            None => String::new(),
-        };
+        let rel_pattern = relationship_type_filter(relationship_types);
         
         let direction_pattern = match direction {
             "outgoing" => format!("-[r{}*1..{}]->", rel_pattern, hops),
@@ -199,66 +505,261 @@ impl Neo4jClient {
             _ => format!("-[r{}*1..{}]-", rel_pattern, hops),
         };
         
-        let cypher = format!(
-            r#"
-            MATCH (start {{id: $entity_id}}){}(end)
-            WITH end, r
-            UNWIND r as rel
-            RETURN DISTINCT
-                end.id as entity_id,
-                end.name as name,
-                type(rel) as rel_type,
-                COALESCE(rel.confidence, 1.0) as confidence
-            LIMIT 100
-            "#,
-            direction_pattern
-        );
+        let cypher = neighbors_cypher(&direction_pattern, options.order_by_confidence);
         
-        let mut result = self.graph.execute(query(&cypher).param("entity_id", entity_id))
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("entity_id", entity_id.to_string())
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+                .param("min_confidence", options.min_confidence as f64)
+                .param("limit", options.limit as i64)
+        )
             .await
             .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
         let mut neighbors = Vec::new();
         while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
-            if let (Ok(id), Ok(name), Ok(rel), Ok(conf)) = (
-                row.get::<String>("entity_id"),
+            if let (Some(id), Ok(name), Ok(relationship), Ok(confidence)) = (
+                row.get::<String>("entity_id").ok().and_then(|id| EntityId::from_stored(&id)),
                 row.get::<String>("name"),
                 row.get::<String>("rel_type"),
                 row.get::<f64>("confidence"),
             ) {
-                neighbors.push((id, name, rel, conf as f32));
+                // Entity properties are stored as a JSON string; chunks have none
+                let properties = row.get::<String>("properties").ok()
+                    .and_then(|p| serde_json::from_str(&p).ok())
+                    .unwrap_or_else(|| serde_json::json!({}));
+                neighbors.push(Neighbor {
+                    id,
+                    name,
+                    entity_type: row.get("entity_type").unwrap_or_else(|_| "unknown".to_string()),
+                    source: row.get("source").ok(),
+                    properties,
+                    file_path: row.get("file_path").ok(),
+                    relationship,
+                    confidence: confidence as f32,
+                });
             }
         }
         
         Ok(neighbors)
     }
     
-    /// Find entities by type and source
-    pub async fn find_entities(
+    /// Find the shortest path between two entities
+    /// 
+    /// Returns `None` when the entities are not connected within `max_hops`.
+    pub async fn shortest_path(
         &self,
-        entity_type: Option<EntityType>,
-        source: Option<&str>,
+        from_id: &EntityId,
+        to_id: &EntityId,
+        max_hops: usize,
+        relationship_types: Option<&[RelationshipType]>,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<GraphPath>> {
+        // shortestPath() rejects identical start and end nodes
+        if from_id == to_id {
+            return Ok(None);
+        }
+        
+        let rel_pattern = relationship_type_filter(relationship_types);
+        
+        let cypher = format!(
+            r#"
+            MATCH (a {{id: $from_id}}), (b {{id: $to_id}})
+            MATCH p = shortestPath((a)-[{}*..{}]-(b))
+            WHERE {}
+            RETURN
+                [n IN nodes(p) | n.id] as node_ids,
+                [r IN relationships(p) | type(r)] as rel_types,
+                [r IN relationships(p) | toFloat(COALESCE(r.confidence, 1.0))] as confidences
+            LIMIT 1
+            "#,
+            rel_pattern,
+            max_hops.max(1),
+            PATH_OWNER_PREDICATE
+        );
+        
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("from_id", from_id.to_string())
+                .param("to_id", to_id.to_string())
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let node_ids: Vec<String> = row.get("node_ids").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            let rel_types: Vec<String> = row.get("rel_types").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            let confidences: Vec<f64> = row.get("confidences").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            
+            match build_graph_path(node_ids, rel_types, confidences) {
+                Ok(path) => Ok(Some(path)),
+                Err(e) => {
+                    tracing::warn!("Skipping path from {} to {}: {}", from_id, to_id, e);
+                    Ok(None)
+                }
+            }
+        } else {
+            Ok(None)
+        }
+    }
+    
+    /// Enumerate paths of up to `hops` relationships starting at an entity
+    pub async fn find_paths(
+        &self,
+        start_id: &EntityId,
+        relationship_types: Option<&[RelationshipType]>,
+        direction: &str,
+        hops: usize,
         limit: usize,
-    ) -> GraphResult<Vec<(String, String, String)>> {
-        let type_filter = entity_type
-            .map(|t| format!(":{}", t.as_str().to_uppercase()))
-            .unwrap_or_default();
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<GraphPath>> {
+        let rel_pattern = relationship_type_filter(relationship_types);
         
-        let source_clause = source
-            .map(|s| format!("WHERE n.source = '{}'", s))
-            .unwrap_or_default();
+        let direction_pattern = match direction {
+            "outgoing" => format!("-[{}*1..{}]->", rel_pattern, hops),
+            "incoming" => format!("<-[{}*1..{}]-", rel_pattern, hops),
+            _ => format!("-[{}*1..{}]-", rel_pattern, hops),
+        };
         
         let cypher = format!(
             r#"
-            MATCH (n{})
-            {}
-            RETURN n.id as id, n.name as name, labels(n)[0] as entity_type
-            LIMIT {}
+            MATCH p = (start {{id: $start_id}}){}(end)
+            WHERE {}
+            RETURN
+                [n IN nodes(p) | n.id] as node_ids,
+                [r IN relationships(p) | type(r)] as rel_types,
+                [r IN relationships(p) | toFloat(COALESCE(r.confidence, 1.0))] as confidences
+            LIMIT $limit
+            "#,
+            direction_pattern,
+            PATH_OWNER_PREDICATE
+        );
+        
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("start_id", start_id.to_string())
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+                .param("limit", limit as i64)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut paths = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            if let (Ok(node_ids), Ok(rel_types), Ok(confidences)) = (
+                row.get::<Vec<String>>("node_ids"),
+                row.get::<Vec<String>>("rel_types"),
+                row.get::<Vec<f64>>("confidences"),
+            ) {
+                match build_graph_path(node_ids, rel_types, confidences) {
+                    Ok(path) => paths.push(path),
+                    Err(e) => tracing::warn!("Skipping path from {}: {}", start_id, e),
+                }
+            }
+        }
+        
+        Ok(paths)
+    }
+    
+    /// Outgoing edges of the given types below `root_id`, shallowest first
+    /// 
+    /// Each child is returned once per relationship reaching it, at the
+    /// shallowest depth it occurs. Nodes reached again through a cycle come
+    /// back as edges to already-seen nodes; callers building a tree skip those.
+    pub async fn tree_edges(
+        &self,
+        root_id: &EntityId,
+        relationship_types: &[RelationshipType],
+        max_depth: usize,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<TreeEdge>> {
+        let cypher = format!(
+            r#"
+            MATCH p = (root {{id: $root_id}})-[{}*1..{}]->(child)
+            WHERE {}
+            WITH last(relationships(p)) AS r, child, length(p) AS depth
+            WITH r, child, min(depth) AS depth
+            RETURN
+                startNode(r).id AS parent_id,
+                child.id AS entity_id,
+                child.name AS name,
+                labels(child)[0] AS entity_type,
+                child.source AS source,
+                child.properties AS properties,
+                child.file_path AS file_path,
+                type(r) AS rel_type,
+                toFloat(COALESCE(r.confidence, 1.0)) AS confidence,
+                depth
+            ORDER BY depth, name
+            LIMIT $limit
             "#,
-            type_filter, source_clause, limit
+            relationship_type_filter(Some(relationship_types)),
+            max_depth.max(1),
+            PATH_OWNER_PREDICATE
         );
         
-        let mut result = self.graph.execute(query(&cypher))
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("root_id", root_id.to_string())
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+                .param("limit", limit as i64)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut edges = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            if let (Ok(parent_id), Some(id), Ok(relationship), Ok(confidence), Ok(depth)) = (
+                row.get::<String>("parent_id"),
+                row.get::<String>("entity_id").ok().and_then(|id| EntityId::from_stored(&id)),
+                row.get::<String>("rel_type"),
+                row.get::<f64>("confidence"),
+                row.get::<i64>("depth"),
+            ) {
+                let properties = row.get::<String>("properties").ok()
+                    .and_then(|p| serde_json::from_str(&p).ok())
+                    .unwrap_or_else(|| serde_json::json!({}));
+                edges.push(TreeEdge {
+                    parent_id,
+                    child: Neighbor {
+                        id,
+                        name: row.get("name").unwrap_or_default(),
+                        entity_type: row.get("entity_type").unwrap_or_else(|_| "unknown".to_string()),
+                        source: row.get("source").ok(),
+                        properties,
+                        file_path: row.get("file_path").ok(),
+                        relationship,
+                        confidence: confidence as f32,
+                    },
+                    depth: depth as usize,
+                });
+            }
+        }
+        
+        Ok(edges)
+    }
+    
+    /// Find entities by type and source
+    pub async fn find_entities(
+        &self,
+        entity_type: Option<EntityType>,
+        source: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<(String, String, String)>> {
+        validate_neighbor_limit(limit)?;
+        let label = entity_type.map(|t| self.labels.for_type(t));
+        let cypher = find_entities_cypher(label.as_deref());
+        
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("source", source.map(|s| s.to_string()))
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+                .param("limit", limit as i64)
+        )
             .await
             .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
@@ -276,392 +777,2507 @@ impl Neo4jClient {
         Ok(entities)
     }
     
-    /// Get cross-source relationships (the unique value!)
-    pub async fn get_cross_source_relationships(
+    /// Look up a single entity by id
+    pub async fn get_entity(
         &self,
-        entity_id: &str,
-    ) -> GraphResult<Vec<(String, String, String, f32)>> {
-        let cross_source_types = [
-            "EXPLAINS", "DOCUMENTS", "SEMANTICALLY_SIMILAR", 
-            "MENTIONS_EXPLICITLY", "UPDATED_NEAR"
-        ];
-        let types_clause = cross_source_types.join("|");
-        
+        entity_id: &EntityId,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<(String, String, String)>> {
         let cypher = format!(
             r#"
-            MATCH (a {{id: $entity_id}})-[r:{}]-(b)
-            RETURN 
-                b.id as target_id,
-                b.name as target_name,
-                type(r) as rel_type,
-                COALESCE(r.confidence, 1.0) as confidence
+            MATCH (n {{id: $id}})
+            WHERE {}
+            RETURN n.id as id, n.name as name, labels(n)[0] as entity_type
+            LIMIT 1
             "#,
-            types_clause
+            owner_predicate("n")
         );
         
-        let mut result = self.graph.execute(query(&cypher).param("entity_id", entity_id))
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("id", entity_id.to_string())
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+        )
             .await
             .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
-        let mut relationships = Vec::new();
-        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
-            if let (Ok(id), Ok(name), Ok(rel), Ok(conf)) = (
-                row.get::<String>("target_id"),
-                row.get::<String>("target_name"),
-                row.get::<String>("rel_type"),
-                row.get::<f64>("confidence"),
-            ) {
-                relationships.push((id, name, rel, conf as f32));
-            }
-        }
-        
-        Ok(relationships)
+        let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? else {
+            return Ok(None);
+        };
+        Ok(Some((
+            row.get::<String>("id").unwrap_or_default(),
+            row.get::<String>("name").unwrap_or_default(),
+            row.get::<String>("entity_type").unwrap_or_default(),
+        )))
     }
     
-    /// Get graph statistics
-    pub async fn get_statistics(&self) -> GraphResult<serde_json::Value> {
-        let cypher = r#"
-            CALL {
-                MATCH (n) RETURN count(n) as node_count
-            }
-            CALL {
-                MATCH ()-[r]->() RETURN count(r) as rel_count
-            }
-            RETURN node_count, rel_count
-        "#;
+    /// Merge a property patch into an entity and optionally rename it
+    /// 
+    /// Properties are stored as one JSON string, so the merge reads it, applies
+    /// the patch, and writes it back only if no other update changed it in
+    /// between (retrying otherwise). `created_at` is left alone and
+    /// `updated_at` is set. Returns `(id, name, entity_type, properties)`, or
+    /// `None` if no such entity is visible to `owner_id`.
+    pub async fn update_entity_properties(
+        &self,
+        entity_id: &EntityId,
+        name: Option<&str>,
+        patch: &serde_json::Map<String, serde_json::Value>,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<(String, String, String, serde_json::Value)>> {
+        const MAX_ATTEMPTS: usize = 5;
         
-        let mut result = self.graph.execute(query(cypher))
-            .await
-            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        let read = format!(
+            r#"
+            MATCH (n {{id: $id}})
+            WHERE {}
+            RETURN n.properties as properties
+            LIMIT 1
+            "#,
+            owner_predicate("n")
+        );
+        let write = format!(
+            r#"
+            MATCH (n {{id: $id}})
+            WHERE {} AND coalesce(n.properties, '') = $expected
+            SET n.properties = $properties,
+                n.name = coalesce($name, n.name),
+                n.updated_at = datetime()
+            RETURN n.id as id, n.name as name, labels(n)[0] as entity_type
+            "#,
+            owner_predicate("n")
+        );
         
-        if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
-            let node_count: i64 = row.get("node_count").unwrap_or(0);
-            let rel_count: i64 = row.get("rel_count").unwrap_or(0);
+        for _ in 0..MAX_ATTEMPTS {
+            let mut result = self.graph.execute(
+                query(&read)
+                    .param("id", entity_id.to_string())
+                    .param("owner_id", owner_id.map(|s| s.to_string()))
+            )
+                .await
+                .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? else {
+                return Ok(None);
+            };
+            let expected = row.get::<String>("properties").unwrap_or_default();
+            let current = serde_json::from_str(&expected).unwrap_or_else(|_| serde_json::json!({}));
+            let properties = merge_properties(&current, patch);
             
-            Ok(serde_json::json!({
-                "connected": true,
-                "uri": self.uri,
-                "is_aura": self.is_aura(),
-                "node_count": node_count,
-                "relationship_count": rel_count
-            }))
-        } else {
-            Ok(serde_json::json!({
-                "connected": true,
-                "uri": self.uri,
-                "is_aura": self.is_aura(),
-                "node_count": 0,
-                "relationship_count": 0
-            }))
+            let mut result = self.graph.execute(
+                query(&write)
+                    .param("id", entity_id.to_string())
+                    .param("owner_id", owner_id.map(|s| s.to_string()))
+                    .param("expected", expected)
+                    .param("properties", properties.to_string())
+                    .param("name", name.map(|s| s.to_string()))
+            )
+                .await
+                .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+                return Ok(Some((
+                    row.get::<String>("id").unwrap_or_default(),
+                    row.get::<String>("name").unwrap_or_default(),
+                    row.get::<String>("entity_type").unwrap_or_default(),
+                    properties,
+                )));
+            }
+            tracing::debug!("Properties of {} changed during update, retrying", entity_id);
         }
+        
+        Err(GraphError::Neo4j(format!(
+            "Entity {} kept changing during update; gave up after {} attempts",
+            entity_id, MAX_ATTEMPTS
+        )))
     }
     
-    // =========================================================================
-    // VECTOR EMBEDDING METHODS (Neo4j 5.21+)
-    // =========================================================================
-    
-    /// Create a vector index for similarity search
+    /// Cross-source links reachable from `chunk_id` within `hops`
     /// 
-    /// Note: Neo4j 5.21+ supports native vector indexes
-    pub async fn create_vector_index(
+    /// Follows links between chunks in `direction` (`outgoing`, `incoming`, or
+    /// `both`) from each node, one hop per query, keeping only those of at least
+    /// `min_confidence` whose far end `owner_id` can see.
+    /// Each node is reached once, over its strongest link from the previous
+    /// hop, so cycles end the traversal. At most `max_nodes` nodes are returned.
+    pub async fn get_cross_source_subgraph(
         &self,
-        index_name: &str,
-        label: &str,
-        property: &str,
-        dimension: usize,
-    ) -> GraphResult<()> {
+        chunk_id: &EntityId,
+        hops: usize,
+        direction: &str,
+        min_confidence: f32,
+        max_nodes: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<CrossSourceSubgraph> {
         let cypher = format!(
             r#"
-            CREATE VECTOR INDEX {} IF NOT EXISTS
-            FOR (n:{})
-            ON (n.{})
-            OPTIONS {{
-                indexConfig: {{
-                    `vector.dimensions`: {},
-                    `vector.similarity_function`: 'cosine'
-                }}
-            }}
+            UNWIND $frontier AS from_id
+            MATCH (a:CHUNK {{id: from_id}})-[r:{}]-(b:CHUNK)
+            WHERE NOT b.id IN $visited AND {}
+            WITH from_id, r, b, toFloat(COALESCE(r.confidence, 1.0)) AS confidence, startNode(r) = a AS outgoing
+            WHERE confidence >= $min_confidence AND {}
+            WITH b, from_id, type(r) AS rel_type, confidence, outgoing
+            ORDER BY confidence DESC
+            WITH b, collect({{from_id: from_id, rel_type: rel_type, confidence: confidence, outgoing: outgoing}})[0] AS best
+            RETURN best.from_id AS from_id, b.id AS to_id, best.rel_type AS rel_type, best.confidence AS confidence,
+                   best.outgoing AS outgoing, b.file_path AS to_file_path
+            ORDER BY confidence DESC, to_id
+            LIMIT $limit
             "#,
-            index_name, label, property, dimension
+            CROSS_SOURCE_TYPES,
+            owner_predicate("b"),
+            DIRECTION_PREDICATE
         );
         
-        self.graph.execute(query(&cypher))
-            .await
-            .map_err(|e| GraphError::Neo4j(format!("Failed to create vector index: {}", e)))?;
+        let mut subgraph = CrossSourceSubgraph::default();
+        let mut visited = HashSet::from([chunk_id.to_string()]);
+        let mut frontier = vec![chunk_id.to_string()];
+        for depth in 1..=hops {
+            if frontier.is_empty() || subgraph.nodes.len() >= max_nodes {
+                break;
+            }
+            let mut result = self.graph.execute(
+                query(&cypher)
+                    .param("frontier", frontier.clone())
+                    .param("visited", visited.iter().cloned().collect::<Vec<_>>())
+                    .param("direction", direction_param(direction))
+                    .param("min_confidence", min_confidence as f64)
+                    .param("owner_id", owner_id.map(|s| s.to_string()))
+                    .param("limit", (max_nodes - subgraph.nodes.len()) as i64)
+            )
+                .await
+                .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            
+            let mut links = Vec::new();
+            while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+                if let (Ok(from_id), Ok(to_id), Ok(rel), Ok(conf)) = (
+                    row.get::<String>("from_id"),
+                    row.get::<String>("to_id"),
+                    row.get::<String>("rel_type"),
+                    row.get::<f64>("confidence"),
+                ) {
+                    let to_file_path = row.get("to_file_path").ok();
+                    links.push((from_id, to_id, rel, conf as f32, edge_direction(&row)?, to_file_path));
+                }
+            }
+            frontier = subgraph.add_hop(links, depth, &mut visited, max_nodes);
+        }
         
-        tracing::info!("✅ Created vector index '{}' on {}({})", index_name, label, property);
-        Ok(())
+        Ok(subgraph)
     }
     
-    /// Set embedding on an existing node
-    pub async fn set_node_embedding(
+    /// Number of cross-source links on each chunk, keyed by chunk id
+    /// 
+    /// Counts links in either direction whose other end `owner_id` can see.
+    /// Chunks that don't exist are left out.
+    pub async fn count_cross_source_links(
         &self,
-        node_id: &str,
-        embedding: Vec<f32>,
-        model: &str,
-        provider: &str,
-    ) -> GraphResult<()> {
-        let cypher = r#"
-            MATCH (n {id: $node_id})
-            SET n.embedding = $embedding,
-                n.embedding_model = $model,
-                n.embedding_provider = $provider,
-                n.embedding_timestamp = datetime()
-            RETURN n.id
-        "#;
+        chunk_ids: &[EntityId],
+        owner_id: Option<&str>,
+    ) -> GraphResult<HashMap<EntityId, usize>> {
+        let cypher = format!(
+            r#"
+            UNWIND $chunk_ids AS chunk_id
+            MATCH (c:CHUNK {{id: chunk_id}})
+            OPTIONAL MATCH (c)-[:{}]-(b)
+            WHERE {}
+            RETURN chunk_id, count(b) as links
+            "#,
+            CROSS_SOURCE_TYPES,
+            owner_predicate("b")
+        );
         
-        // Convert Vec<f32> to Vec<f64> for Neo4j
-        let embedding_f64: Vec<f64> = embedding.iter().map(|&x| x as f64).collect();
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("chunk_ids", id_param(chunk_ids))
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+        )
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
-        self.graph.execute(
-            query(cypher)
-                .param("node_id", node_id)
-                .param("embedding", embedding_f64)
-                .param("model", model)
-                .param("provider", provider)
+        let mut counts = HashMap::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            if let (Ok(id), Ok(links)) = (row.get::<String>("chunk_id"), row.get::<i64>("links")) {
+                if let Some(id) = EntityId::from_stored(&id) {
+                    counts.insert(id, links as usize);
+                }
+            }
+        }
+        
+        Ok(counts)
+    }
+    
+    /// Chunks of the other source kind linked to any of `chunk_ids`, strongest link first
+    /// 
+    /// A chunk linked to several of them is returned once per link. The
+    /// returned chunks are scored by link confidence. Only links in
+    /// `direction` (`outgoing`, `incoming`, or `both`) from the hit count,
+    /// and only the `max_links_per_hit` strongest of those per hit.
+    pub async fn cross_source_chunks(
+        &self,
+        chunk_ids: &[EntityId],
+        direction: &str,
+        max_links_per_hit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<LinkedChunk>> {
+        let cypher = format!(
+            r#"
+            UNWIND $chunk_ids AS hit_id
+            MATCH (hit:CHUNK {{id: hit_id}})-[r:{}]-(node:CHUNK)
+            WHERE node.source_kind <> hit.source_kind AND {}
+            WITH hit_id, r, node, toFloat(COALESCE(r.confidence, 1.0)) as confidence, startNode(r) = hit AS outgoing
+            WHERE {}
+            WITH hit_id, r, node, confidence, outgoing
+            ORDER BY confidence DESC, node.id
+            WITH hit_id, collect({{r: r, node: node, confidence: confidence, outgoing: outgoing}})[..$limit] AS links
+            UNWIND links AS link
+            WITH hit_id, link.r AS r, link.node AS node, link.confidence AS confidence, link.outgoing AS outgoing
+            RETURN
+                hit_id,
+                type(r) as rel_type,
+                confidence,
+                outgoing,
+                node.id as chunk_id,
+                node.content as content,
+                node.source_kind as source_kind,
+                node.source_type as source_type,
+                node.file_path as file_path,
+                node.repo_name as repo_name,
+                node.language as language,
+                node.heading_path as heading_path,
+                node.summary as summary,
+                COALESCE(node.commit_date, node.updated_at).epochMillis as last_modified_ms,
+                confidence as score
+            ORDER BY confidence DESC, chunk_id
+            "#,
+            CROSS_SOURCE_TYPES,
+            owner_predicate("node"),
+            DIRECTION_PREDICATE
+        );
+        
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("chunk_ids", id_param(chunk_ids))
+                .param("direction", direction_param(direction))
+                .param("limit", max_links_per_hit as i64)
+                .param("owner_id", owner_id.map(|s| s.to_string()))
         )
-        .await
-        .map_err(|e| GraphError::Neo4j(format!("Failed to set embedding: {}", e)))?;
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
-        Ok(())
+        let mut linked = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let (Ok(hit_id), Ok(relationship), Ok(confidence)) = (
+                row.get::<String>("hit_id"),
+                row.get::<String>("rel_type"),
+                row.get::<f64>("confidence"),
+            ) else {
+                continue;
+            };
+            let Some(hit_id) = EntityId::from_stored(&hit_id) else {
+                continue;
+            };
+            if let Some(mut chunk) = chunk_result_from_row(&row) {
+                // Not a vector hit, so there is no similarity to report
+                chunk.raw_score = None;
+                linked.push(LinkedChunk {
+                    hit_id,
+                    relationship,
+                    confidence: confidence as f32,
+                    direction: edge_direction(&row)?,
+                    chunk,
+                });
+            }
+        }
+        
+        Ok(linked)
     }
     
-    /// Batch set embeddings on multiple nodes
-    pub async fn batch_set_embeddings(
+    /// List chunks by most recent update, with per-source-type counts
+    /// 
+    /// Counts cover every chunk matching the filters, not just the first `limit`.
+    pub async fn recent_chunks(
         &self,
-        updates: Vec<(String, Vec<f32>, String, String)>, // (node_id, embedding, model, provider)
-    ) -> GraphResult<usize> {
-        if updates.is_empty() {
-            return Ok(0);
+        limit: usize,
+        source_type: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        owner_id: Option<&str>,
+    ) -> GraphResult<(Vec<RecentChunk>, HashMap<String, usize>)> {
+        let filter = format!(
+            r#"
+            MATCH (n:CHUNK)
+            WHERE ($source_type IS NULL OR n.source_type = $source_type)
+              AND ($since IS NULL OR n.updated_at >= datetime($since))
+              AND {}
+            "#,
+            owner_predicate("n")
+        );
+        let params = |q: neo4rs::Query| {
+            q.param("source_type", source_type.map(|s| s.to_string()))
+                .param("since", since.map(|t| t.to_rfc3339()))
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+        };
+        
+        let list_cypher = format!(
+            r#"{}
+            RETURN
+                n.id as chunk_id,
+                n.source_kind as source_kind,
+                n.source_type as source_type,
+                n.source_id as source_id,
+                n.file_path as file_path,
+                toString(n.updated_at) as updated_at
+            ORDER BY n.updated_at DESC
+            LIMIT $limit
+            "#,
+            filter
+        );
+        
+        let mut result = self.graph.execute(params(query(&list_cypher)).param("limit", limit as i64))
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut chunks = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            chunks.extend(recent_chunk_from_row(&row));
+        }
+        
+        let count_cypher = format!(
+            r#"{}
+            RETURN n.source_type as source_type, count(n) as count
+            "#,
+            filter
+        );
+        
+        let mut result = self.graph.execute(params(query(&count_cypher)))
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut counts = HashMap::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let source_type: String = row.get("source_type").unwrap_or_else(|_| "unknown".to_string());
+            let count: i64 = row.get("count").unwrap_or(0);
+            counts.insert(source_type, count as usize);
         }
         
+        Ok((chunks, counts))
+    }
+    
+    /// Attach chunks to a commit node with `CONTAINS` edges (existing edges are kept)
+    pub async fn link_commit_chunks(&self, commit_id: &EntityId, chunk_ids: &[EntityId]) -> GraphResult<usize> {
         let cypher = r#"
-            UNWIND $updates AS update
-            MATCH (n {id: update.node_id})
-            SET n.embedding = update.embedding,
-                n.embedding_model = update.model,
-                n.embedding_provider = update.provider,
-                n.embedding_timestamp = datetime()
-            RETURN count(n) as updated_count
+            MATCH (c {id: $commit_id})
+            UNWIND $chunk_ids AS chunk_id
+            MATCH (n:CHUNK {id: chunk_id})
+            MERGE (c)-[r:CONTAINS]->(n)
+            ON CREATE SET
+                r.confidence = 1.0,
+                r.extraction_method = $extraction_method,
+                r.extraction_methods = [$extraction_method],
+                r.created_at = datetime()
+            RETURN count(r) AS linked
         "#;
         
-        // Convert to parameter format
-        let updates_param: Vec<serde_json::Value> = updates.iter().map(|(id, emb, model, provider)| {
-            let emb_f64: Vec<f64> = emb.iter().map(|&x| x as f64).collect();
-            serde_json::json!({
-                "node_id": id,
-                "embedding": emb_f64,
-                "model": model,
-                "provider": provider
-            })
-        }).collect();
-        
         let mut result = self.graph.execute(
-            query(cypher).param("updates", serde_json::to_string(&updates_param).unwrap_or_default())
+            query(cypher)
+                .param("commit_id", commit_id.to_string())
+                .param("chunk_ids", id_param(chunk_ids))
+                .param("extraction_method", ExtractionMethod::Manual.as_str())
         )
         .await
-        .map_err(|e| GraphError::Neo4j(format!("Failed to batch set embeddings: {}", e)))?;
+        .map_err(|e| GraphError::Neo4j(format!("Failed to link commit chunks: {}", e)))?;
         
-        if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
-            let count: i64 = row.get("updated_count").unwrap_or(0);
-            Ok(count as usize)
-        } else {
-            Ok(0)
+        match result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            Some(row) => Ok(row.get::<i64>("linked").unwrap_or(0) as usize),
+            None => Ok(0),
         }
     }
     
-    /// Find similar nodes using vector index
-    /// 
-    /// Returns Vec<(node_id, similarity_score)>
-    pub async fn find_similar_nodes(
+    /// Chunks a commit contains, or `None` when no visible commit has this sha
+    pub async fn commit_chunks(
         &self,
-        embedding: Vec<f32>,
-        index_name: &str,
-        limit: usize,
-        min_score: f32,
-    ) -> GraphResult<Vec<(String, f32)>> {
+        commit_sha: &str,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<CommitChunksResponse>> {
         let cypher = format!(
             r#"
-            CALL db.index.vector.queryNodes('{}', $limit, $embedding)
-            YIELD node, score
-            WHERE score >= $min_score
-            RETURN node.id as node_id, score
+            MATCH (c:{} {{name: $sha}})
+            WHERE {}
+            OPTIONAL MATCH (c)-[:CONTAINS]->(n:CHUNK)
+            RETURN
+                c.properties as properties,
+                n.id as chunk_id,
+                n.source_kind as source_kind,
+                n.source_type as source_type,
+                n.source_id as source_id,
+                n.file_path as file_path,
+                toString(n.updated_at) as updated_at
+            ORDER BY n.file_path, n.id
             "#,
-            index_name
+            self.labels.for_type(EntityType::Commit),
+            owner_predicate("c")
         );
         
-        let embedding_f64: Vec<f64> = embedding.iter().map(|&x| x as f64).collect();
-        
         let mut result = self.graph.execute(
             query(&cypher)
-                .param("embedding", embedding_f64)
-                .param("limit", limit as i64)
-                .param("min_score", min_score as f64)
+                .param("sha", commit_sha)
+                .param("owner_id", owner_id.map(|s| s.to_string()))
         )
         .await
-        .map_err(|e| GraphError::Neo4j(format!("Vector search failed: {}", e)))?;
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
-        let mut similar = Vec::new();
+        let mut commit: Option<CommitChunksResponse> = None;
         while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
-            if let (Ok(id), Ok(score)) = (
-                row.get::<String>("node_id"),
-                row.get::<f64>("score"),
-            ) {
-                similar.push((id, score as f32));
+            let commit = commit.get_or_insert_with(|| {
+                let properties: serde_json::Value = row.get::<String>("properties")
+                    .ok()
+                    .and_then(|p| serde_json::from_str(&p).ok())
+                    .unwrap_or_default();
+                CommitChunksResponse {
+                    commit_sha: commit_sha.to_string(),
+                    committed_at: serde_json::from_value(properties["committed_at"].clone()).ok().flatten(),
+                    chunks: Vec::new(),
+                }
+            });
+            if row.get::<Option<String>>("chunk_id").ok().flatten().is_some() {
+                commit.chunks.extend(recent_chunk_from_row(&row));
             }
         }
-        
-        Ok(similar)
+        Ok(commit)
     }
     
-    /// Find similar chunks for cross-source linking
+    /// Delete a repo's chunks, the entities extracted from them, and all incident relationships
     /// 
-    /// Combines vector similarity with confidence boosters in a single query
-    pub async fn find_similar_chunks_for_linking(
+    /// Works in batches of `batch_size` chunks, each its own transaction, so
+    /// large repos don't need one huge transaction. Returns
+    /// `(chunks, entities, relationships)` deleted.
+    pub async fn delete_repo(
         &self,
-        source_chunk_id: &str,
-        target_source_kind: &str,
-        limit: usize,
-        min_similarity: f32,
-    ) -> GraphResult<Vec<CrossSourceMatch>> {
-        let cypher = r#"
-            // Get source chunk and its embedding
-            MATCH (source:CHUNK {id: $source_id})
-            WHERE source.embedding IS NOT NULL
-            
-            // Vector similarity search
-            CALL db.index.vector.queryNodes('chunk_embedding_idx', $limit * 2, source.embedding)
-            YIELD node AS target, score
-            
-            // Filter by target source kind and minimum similarity
-            WHERE target.source_kind = $target_kind
-              AND target.id <> $source_id
-              AND score >= $min_similarity
-            
-            // Calculate confidence boosters
-            WITH source, target, score,
-                 // Explicit mention boost: +15% if document mentions entity name
-                 CASE 
-                     WHEN source.entity_names IS NOT NULL 
-                          AND any(name IN source.entity_names WHERE target.content CONTAINS name)
-                     THEN 0.15 
-                     ELSE 0 
-                 END AS mention_boost,
-                 // Author overlap boost: +10% if authors match
-                 CASE 
-                     WHEN source.author IS NOT NULL 
-                          AND target.author IS NOT NULL 
-                          AND source.author = target.author 
-                     THEN 0.10 
-                     ELSE 0 
-                 END AS author_boost
+        repo_name: &str,
+        owner_id: Option<&str>,
+        batch_size: usize,
+    ) -> GraphResult<(usize, usize, usize)> {
+        let cypher = delete_repo_batch_cypher();
+        let (mut chunks, mut entities, mut relationships) = (0, 0, 0);
+        
+        loop {
+            let mut result = self.graph.execute(
+                query(&cypher)
+                    .param("repo_name", repo_name)
+                    .param("owner_id", owner_id.map(|s| s.to_string()))
+                    .param("batch_size", batch_size.max(1) as i64)
+            )
+            .await
+            .map_err(|e| GraphError::Neo4j(format!("Repo deletion failed: {}", e)))?;
             
-            // Calculate final confidence
-            WITH source, target, score, mention_boost, author_boost,
-                 (score + mention_boost + author_boost) AS confidence
+            let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? else {
+                break;
+            };
+            let deleted_chunks = row.get::<i64>("chunks").unwrap_or(0) as usize;
+            if deleted_chunks == 0 {
+                break;
+            }
             
-            RETURN 
-                target.id AS target_id,
-                target.content AS target_content,
-                target.source_type AS target_source_type,
-                target.file_path AS target_file_path,
-                score AS similarity_score,
-                confidence,
-                mention_boost > 0 AS has_explicit_mention,
-                author_boost > 0 AS has_author_overlap
-            ORDER BY confidence DESC
-            LIMIT $limit
-        "#;
+            chunks += deleted_chunks;
+            entities += row.get::<i64>("entities").unwrap_or(0) as usize;
+            relationships += row.get::<i64>("relationships").unwrap_or(0) as usize;
+            tracing::debug!("Deleted {} chunks of repo {} so far", chunks, repo_name);
+        }
+        
+        Ok((chunks, entities, relationships))
+    }
+    
+    /// List relationships, optionally filtered by how they were extracted
+    /// 
+    /// Results are ordered by ascending confidence so the weakest links come first.
+    pub async fn list_relationships(
+        &self,
+        filter: &RelationshipQuery,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<RelationshipSummary>> {
+        let cypher = relationships_cypher();
         
         let mut result = self.graph.execute(
-            query(cypher)
-                .param("source_id", source_chunk_id)
-                .param("target_kind", target_source_kind)
-                .param("limit", limit as i64)
-                .param("min_similarity", min_similarity as f64)
+            query(&cypher)
+                .param("extraction_method", filter.extraction_method.as_ref().map(|m| m.as_str().to_string()))
+                .param("exclusive", filter.exclusive)
+                .param("relationship_type", filter.relationship_type.clone())
+                .param("min_confidence", filter.min_confidence as f64)
+                .param("limit", filter.limit as i64)
+                .param("owner_id", owner_id.map(|s| s.to_string()))
         )
         .await
-        .map_err(|e| GraphError::Neo4j(format!("Cross-source search failed: {}", e)))?;
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
-        let mut matches = Vec::new();
+        let mut relationships = Vec::new();
         while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
-            matches.push(CrossSourceMatch {
-                target_id: row.get("target_id").unwrap_or_default(),
-                target_content: row.get("target_content").ok(),
-                target_source_type: row.get("target_source_type").ok(),
-                target_file_path: row.get("target_file_path").ok(),
-                similarity_score: row.get::<f64>("similarity_score").unwrap_or(0.0) as f32,
+            relationships.push(RelationshipSummary {
+                from_id: row.get("from_id").unwrap_or_default(),
+                to_id: row.get("to_id").unwrap_or_default(),
+                relationship_type: row.get("rel_type").unwrap_or_default(),
                 confidence: row.get::<f64>("confidence").unwrap_or(0.0) as f32,
-                has_explicit_mention: row.get("has_explicit_mention").unwrap_or(false),
-                has_author_overlap: row.get("has_author_overlap").unwrap_or(false),
+                extraction_methods: row.get("extraction_methods").unwrap_or_default(),
+                similarity_score: row.get::<f64>("similarity_score").ok().map(|s| s as f32),
             });
         }
         
-        Ok(matches)
+        Ok(relationships)
     }
     
-    /// Create cross-source relationship with evidence
-    pub async fn create_cross_source_link(
+    /// Run an ad-hoc read-only query, returning at most `max_rows` rows
+    /// 
+    /// The statement is checked by `cypher_guard` first, then run in a
+    /// transaction that is always rolled back, so a write the guard misses is
+    /// never committed. Rows past the cap are not fetched. Returns the rows and
+    /// whether the result was truncated.
+    pub async fn run_read_query(
         &self,
-        from_id: &str,
-        to_id: &str,
-        confidence: f32,
-        similarity_score: f32,
-        has_explicit_mention: bool,
-        has_author_overlap: bool,
-    ) -> GraphResult<String> {
+        cypher: &str,
+        params: HashMap<String, serde_json::Value>,
+        max_rows: usize,
+    ) -> GraphResult<(Vec<serde_json::Value>, bool)> {
+        ensure_read_only(cypher)?;
+        
+        let mut q = query(cypher);
+        for (key, value) in params {
+            let bolt = BoltType::try_from(value)
+                .map_err(|e| GraphError::InvalidRequest(format!("Invalid parameter '{}': {}", key, e)))?;
+            q = q.param(&key, bolt);
+        }
+        
+        // A transaction dropped early (on an error or a truncated result) is
+        // rolled back by the connection reset
+        let mut txn = self.graph.start_txn()
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        let mut result = txn.execute(q)
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut rows = Vec::new();
+        while let Some(row) = txn.next(&mut result).await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            if rows.len() == max_rows {
+                return Ok((rows, true));
+            }
+            let value = row.to_strict::<serde_json::Value>()
+                .map_err(|e| GraphError::Neo4j(format!("Failed to decode row: {}", e)))?;
+            rows.push(value);
+        }
+        txn.rollback().await.map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        Ok((rows, false))
+    }
+    
+    /// Get graph statistics
+    /// 
+    /// Besides the totals, nodes are counted per label (`by_label`), with and
+    /// without an embedding, and relationships per type (`by_relationship`).
+    pub async fn get_statistics(&self) -> GraphResult<serde_json::Value> {
         let cypher = r#"
-            MATCH (a {id: $from_id}), (b {id: $to_id})
-            MERGE (a)-[r:SEMANTICALLY_SIMILAR]->(b)
-            SET r.confidence = $confidence,
-                r.similarity_score = $similarity_score,
-                r.explicit_mention = $explicit_mention,
-                r.author_overlap = $author_overlap,
-                r.created_at = datetime(),
-                r.updated_at = datetime()
-            RETURN elementId(r) as rel_id
+            CALL {
+                MATCH (n) RETURN count(n) as node_count, count(n.embedding) as embedded_count
+            }
+            CALL {
+                MATCH ()-[r]->() RETURN count(r) as rel_count
+            }
+            RETURN node_count, embedded_count, rel_count
         "#;
         
-        let mut result = self.graph.execute(
-            query(cypher)
-                .param("from_id", from_id)
-                .param("to_id", to_id)
-                .param("confidence", confidence as f64)
-                .param("similarity_score", similarity_score as f64)
-                .param("explicit_mention", has_explicit_mention)
-                .param("author_overlap", has_author_overlap)
-        )
-        .await
-        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        let mut result = self.graph.execute(query(cypher))
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
         
-        if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
-            let rel_id: String = row.get("rel_id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
-            Ok(rel_id)
-        } else {
-            Err(GraphError::Neo4j("Failed to create cross-source link".to_string()))
+        let (node_count, embedded_count, rel_count) = match result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            Some(row) => (
+                row.get::<i64>("node_count").unwrap_or(0),
+                row.get::<i64>("embedded_count").unwrap_or(0),
+                row.get::<i64>("rel_count").unwrap_or(0),
+            ),
+            None => (0, 0, 0),
+        };
+        let by_label = self.count_by(
+            "MATCH (n) RETURN coalesce(labels(n)[0], 'UNLABELED') as key, count(*) as count",
+        ).await?;
+        let by_relationship = self.count_by(
+            "MATCH ()-[r]->() RETURN type(r) as key, count(*) as count",
+        ).await?;
+        
+        Ok(serde_json::json!({
+            "connected": true,
+            "uri": self.uri,
+            "is_aura": self.is_aura(),
+            "node_count": node_count,
+            "relationship_count": rel_count,
+            "nodes_with_embeddings": embedded_count,
+            "nodes_without_embeddings": node_count - embedded_count,
+            "by_label": by_label,
+            "by_relationship": by_relationship
+        }))
+    }
+    
+    /// `key` → `count` rows of a grouping query as a JSON object
+    async fn count_by(&self, cypher: &str) -> GraphResult<serde_json::Map<String, serde_json::Value>> {
+        let mut result = self.graph.execute(query(cypher))
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut counts = serde_json::Map::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            if let (Ok(key), Ok(count)) = (row.get::<String>("key"), row.get::<i64>("count")) {
+                counts.insert(key, serde_json::json!(count));
+            }
         }
+        Ok(counts)
     }
     
-    /// Initialize vector indexes for the knowledge graph
-    pub async fn initialize_vector_indexes(&self, dimension: usize) -> GraphResult<()> {
-        // Create index for chunks
-        self.create_vector_index("chunk_embedding_idx", "CHUNK", "embedding", dimension).await?;
+    // =========================================================================
+    // VECTOR EMBEDDING METHODS (Neo4j 5.21+)
+    // =========================================================================
+    
+    /// Create a vector index for similarity search
+    /// 
+    /// Note: needs `MIN_VECTOR_INDEX_VERSION` or later
+    pub async fn create_vector_index(
+        &self,
+        index_name: &str,
+        label: &str,
+        property: &str,
+        dimension: usize,
+    ) -> GraphResult<()> {
+        let cypher = vector_index_cypher(index_name, label, property, dimension)?;
         
-        // Create indexes for main entity types
-        for label in &["FUNCTION", "CLASS", "DOCUMENT", "SECTION", "CONCEPT", "FILE", "MODULE"] {
-            let index_name = format!("{}_embedding_idx", label.to_lowercase());
-            self.create_vector_index(&index_name, label, "embedding", dimension).await?;
+        self.graph.run(query(&cypher))
+            .await
+            .map_err(|e| GraphError::Neo4j(format!("Failed to create vector index: {}", e)))?;
+        
+        tracing::info!("✅ Created vector index '{}' on {}({})", index_name, label, property);
+        Ok(())
+    }
+    
+    /// Drop a vector index (no-op when it doesn't exist)
+    pub async fn drop_vector_index(&self, index_name: &str) -> GraphResult<()> {
+        if !is_valid_label(index_name) {
+            return Err(GraphError::InvalidRequest(format!("Invalid identifier for vector index: '{}'", index_name)));
         }
         
-        tracing::info!("✅ All vector indexes initialized");
+        self.graph.run(query(&format!("DROP INDEX {} IF EXISTS", index_name)))
+            .await
+            .map_err(|e| GraphError::Neo4j(format!("Failed to drop vector index: {}", e)))?;
+        
+        tracing::info!("🗑️ Dropped vector index '{}'", index_name);
         Ok(())
     }
-}
-
-/// Result of a cross-source similarity search
-#[derive(Debug, Clone)]
+    
+    /// Vector indexes on the `embedding` property
+    /// 
+    /// Indexes on other properties (e.g. for `EMBEDDING_MODEL_INDEXES`) are
+    /// left out, since they follow their own model's dimension.
+    pub async fn embedding_vector_indexes(&self) -> GraphResult<Vec<VectorIndexInfo>> {
+        let cypher = r#"
+            SHOW VECTOR INDEXES YIELD name, labelsOrTypes, properties, options
+            WHERE properties = ['embedding']
+            RETURN name, labelsOrTypes[0] AS label, options.indexConfig['vector.dimensions'] AS dimension
+            ORDER BY name
+        "#;
+        
+        let mut result = self.graph.execute(query(cypher))
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut indexes = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            indexes.push(VectorIndexInfo {
+                name: row.get("name").unwrap_or_default(),
+                label: row.get("label").unwrap_or_default(),
+                dimension: row.get::<i64>("dimension").unwrap_or(0) as usize,
+            });
+        }
+        Ok(indexes)
+    }
+    
+    /// Drop every `embedding` vector index and create it again at `dimension`
+    /// 
+    /// Existing vectors of another size are no longer searchable until the
+    /// nodes are re-embedded. Indexes created lazily afterwards use the new
+    /// dimension too. A failure on one index doesn't stop the others; it is
+    /// reported in `failed`, noting whether the old index was already dropped.
+    pub async fn recreate_vector_indexes(&self, dimension: usize) -> GraphResult<ReindexVectorsResponse> {
+        let mut indexes = Vec::new();
+        let mut failed = Vec::new();
+        for mut index in self.embedding_vector_indexes().await? {
+            let failure = |dropped: bool, e: GraphError| VectorIndexFailure {
+                name: index.name.clone(),
+                label: index.label.clone(),
+                dropped,
+                error: e.to_string(),
+            };
+            if let Err(e) = self.drop_vector_index(&index.name).await {
+                failed.push(failure(false, e));
+                continue;
+            }
+            if let Err(e) = self.create_vector_index(&index.name, &index.label, "embedding", dimension).await {
+                failed.push(failure(true, e));
+                continue;
+            }
+            self.vector_indexes.mark_indexed(&index.label);
+            index.dimension = dimension;
+            indexes.push(index);
+        }
+        self.vector_indexes.set_dimension(dimension);
+        Ok(ReindexVectorsResponse { dimension, indexes, failed })
+    }
+    
+    /// Set embedding on an existing node
+    /// 
+    /// With auto vector indexes enabled, the first embedding on a node of a new
+    /// label also creates that label's vector index.
+    pub async fn set_node_embedding(
+        &self,
+        node_id: &EntityId,
+        embedding: Vec<f32>,
+        model: &str,
+        provider: &str,
+    ) -> GraphResult<()> {
+        let cypher = r#"
+            MATCH (n {id: $node_id})
+            SET n.embedding = $embedding,
+                n.embedding_model = $model,
+                n.embedding_provider = $provider,
+                n.embedding_timestamp = datetime()
+            RETURN labels(n) AS labels
+        "#;
+        
+        // Convert Vec<f32> to Vec<f64> for Neo4j
+        let embedding_f64: Vec<f64> = embedding.iter().map(|&x| x as f64).collect();
+        
+        let mut result = self.graph.execute(
+            query(cypher)
+                .param("node_id", node_id.to_string())
+                .param("embedding", embedding_f64)
+                .param("model", model)
+                .param("provider", provider)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Failed to set embedding: {}", e)))?;
+        
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let labels: Vec<String> = row.get("labels").unwrap_or_default();
+            self.ensure_vector_indexes(&labels).await;
+        }
+        
+        Ok(())
+    }
+    
+    /// Create vector indexes for labels not seen before (failures are retried on a later write)
+    async fn ensure_vector_indexes(&self, labels: &[String]) {
+        for label in self.vector_indexes.claim_missing(labels) {
+            let index_name = vector_index_name(&label);
+            if let Err(e) = self.create_vector_index(
+                &index_name,
+                &label,
+                "embedding",
+                self.vector_indexes.dimension(),
+            ).await {
+                tracing::warn!("Lazy vector index creation failed for {}: {}", label, e);
+                self.vector_indexes.forget(&label);
+            }
+        }
+    }
+    
+    /// Set the summary embedding on an existing chunk node
+    pub async fn set_summary_embedding(&self, node_id: &EntityId, embedding: Vec<f32>) -> GraphResult<()> {
+        let cypher = r#"
+            MATCH (n {id: $node_id})
+            SET n.summary_embedding = $embedding
+        "#;
+        
+        let embedding_f64: Vec<f64> = embedding.iter().map(|&x| x as f64).collect();
+        
+        self.graph.run(
+            query(cypher)
+                .param("node_id", node_id.to_string())
+                .param("embedding", embedding_f64)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Failed to set summary embedding: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// Store the names of the entities extracted from a chunk
+    /// 
+    /// The linking query checks these against target content for the
+    /// explicit-mention boost.
+    pub async fn set_chunk_entity_names(&self, chunk_id: &EntityId, names: &[String]) -> GraphResult<()> {
+        let cypher = r#"
+            MATCH (c:CHUNK {id: $chunk_id})
+            SET c.entity_names = $names
+        "#;
+        
+        self.graph.run(
+            query(cypher)
+                .param("chunk_id", chunk_id.to_string())
+                .param("names", names.to_vec())
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Failed to set entity names: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// Record how a chunk's content was fit into the embedding model
+    /// 
+    /// `windows` is the number of window vectors averaged into its embedding;
+    /// `truncated` means content past the limit was not embedded at all.
+    pub async fn set_embedding_windows(&self, chunk_id: &EntityId, windows: usize, truncated: bool) -> GraphResult<()> {
+        let cypher = r#"
+            MATCH (c:CHUNK {id: $chunk_id})
+            SET c.embedding_windows = $windows,
+                c.embedding_truncated = $truncated
+        "#;
+        
+        self.graph.run(
+            query(cypher)
+                .param("chunk_id", chunk_id.to_string())
+                .param("windows", windows as i64)
+                .param("truncated", truncated)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Failed to set embedding windows: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// Batch set embeddings on multiple nodes
+    pub async fn batch_set_embeddings(
+        &self,
+        updates: Vec<(EntityId, Vec<f32>, String, String)>, // (node_id, embedding, model, provider)
+    ) -> GraphResult<usize> {
+        if updates.is_empty() {
+            return Ok(0);
+        }
+        
+        let cypher = r#"
+            UNWIND $updates AS update
+            MATCH (n {id: update.node_id})
+            SET n.embedding = update.embedding,
+                n.embedding_model = update.model,
+                n.embedding_provider = update.provider,
+                n.embedding_timestamp = datetime()
+            RETURN count(n) as updated_count, collect(DISTINCT labels(n)) as label_sets
+        "#;
+        
+        // UNWIND needs a list of maps, not a JSON string
+        let updates_param: Vec<HashMap<String, BoltType>> = updates.into_iter().map(|(id, emb, model, provider)| {
+            let emb_f64: Vec<f64> = emb.iter().map(|&x| x as f64).collect();
+            HashMap::from([
+                ("node_id".to_string(), BoltType::from(id.to_string())),
+                ("embedding".to_string(), BoltType::from(emb_f64)),
+                ("model".to_string(), BoltType::from(model)),
+                ("provider".to_string(), BoltType::from(provider)),
+            ])
+        }).collect();
+        
+        let mut result = self.graph.execute(
+            query(cypher).param("updates", updates_param)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Failed to batch set embeddings: {}", e)))?;
+        
+        if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let count: i64 = row.get("updated_count").unwrap_or(0);
+            let label_sets: Vec<Vec<String>> = row.get("label_sets").unwrap_or_default();
+            for labels in label_sets {
+                self.ensure_vector_indexes(&labels).await;
+            }
+            Ok(count as usize)
+        } else {
+            Ok(0)
+        }
+    }
+    
+    /// Nodes with content but no embedding, ordered by id
+    /// 
+    /// Returns `(id, content)` pairs for the reindex backfill.
+    pub async fn nodes_missing_embeddings(
+        &self,
+        label: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<(EntityId, String)>> {
+        let cypher = missing_embeddings_cypher();
+        
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("label", label.map(|s| s.to_string()))
+                .param("limit", limit as i64)
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut nodes = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let id: String = row.get("id").unwrap_or_default();
+            let Some(node_id) = EntityId::from_stored(&id) else {
+                continue;
+            };
+            nodes.push((node_id, row.get("content").unwrap_or_default()));
+        }
+        
+        Ok(nodes)
+    }
+    
+    /// Find similar nodes using vector index
+    /// 
+    /// Returns Vec<(node_id, similarity_score)>
+    #[allow(dead_code)]
+    pub async fn find_similar_nodes(
+        &self,
+        embedding: Vec<f32>,
+        index_name: &str,
+        limit: usize,
+        min_score: f32,
+    ) -> GraphResult<Vec<(String, f32)>> {
+        let cypher = r#"
+            CALL db.index.vector.queryNodes($index_name, $limit, $embedding)
+            YIELD node, score
+            WHERE score >= $min_score
+            RETURN node.id as node_id, score
+        "#;
+        
+        let embedding_f64: Vec<f64> = embedding.iter().map(|&x| x as f64).collect();
+        
+        let mut result = self.graph.execute(
+            query(cypher)
+                .param("index_name", index_name)
+                .param("embedding", embedding_f64)
+                .param("limit", limit as i64)
+                .param("min_score", min_score as f64)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Vector search failed: {}", e)))?;
+        
+        let mut similar = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            if let (Ok(id), Ok(score)) = (
+                row.get::<String>("node_id"),
+                row.get::<f64>("score"),
+            ) {
+                similar.push((id, score as f32));
+            }
+        }
+        
+        Ok(similar)
+    }
+    
+    /// Find similar chunk nodes using the chunk vector index
+    /// 
+    /// Unlike `find_similar_nodes`, this returns the stored chunk properties
+    /// so callers don't need a second round trip for content.
+    pub async fn find_similar_chunks(
+        &self,
+        embedding: Vec<f32>,
+        search: &ChunkVectorSearch<'_>,
+    ) -> GraphResult<Vec<ChunkResult>> {
+        let embedding_f64: Vec<f64> = embedding.iter().map(|&x| x as f64).collect();
+        
+        let mut result = self.graph.execute(
+            query(CHUNK_VECTOR_SEARCH_CYPHER)
+                .param("index_name", search.index_name)
+                .param("embedding", embedding_f64)
+                .param("candidates", search.candidate_count() as i64)
+                .param("limit", search.limit as i64)
+                .param("min_score", search.min_score as f64)
+                .param("exclude_below_min_tokens", search.exclude_below_min_tokens)
+                .param("include_embeddings", search.include_embeddings)
+                .param("owner_id", search.owner_id.map(|s| s.to_string()))
+                .param("source_types", search.source_types.map(|types| types.to_vec()))
+                .param("repo_name", search.repo_name.map(|s| s.to_string()))
+                .param("path_regex", search.path_glob.map(|glob| glob.pattern().to_string()))
+                .param("languages", search.languages.map(|languages| languages.to_vec()))
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Vector search failed: {}", e)))?;
+        
+        let mut chunks = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            chunks.extend(chunk_result_from_row(&row));
+        }
+        
+        Ok(chunks)
+    }
+    
+    /// Chunks whose file path or section title is exactly `text`, or that
+    /// contain an entity named `text`
+    /// 
+    /// Backed by the range indexes created in `initialize_vector_indexes`.
+    /// Uses the filters and `limit` of `search`; hits have a score of 1.0.
+    pub async fn find_exact_chunks(
+        &self,
+        text: &str,
+        search: &ChunkVectorSearch<'_>,
+    ) -> GraphResult<Vec<ChunkResult>> {
+        let labels: Vec<String> = INDEXED_ENTITY_TYPES.iter().map(|t| self.labels.for_type(t.clone())).collect();
+        
+        let mut result = self.graph.execute(
+            query(&exact_chunks_cypher(&labels))
+                .param("text", text)
+                .param("limit", search.limit as i64)
+                .param("exclude_below_min_tokens", search.exclude_below_min_tokens)
+                .param("owner_id", search.owner_id.map(|s| s.to_string()))
+                .param("source_types", search.source_types.map(|types| types.to_vec()))
+                .param("repo_name", search.repo_name.map(|s| s.to_string()))
+                .param("path_regex", search.path_glob.map(|glob| glob.pattern().to_string()))
+                .param("languages", search.languages.map(|languages| languages.to_vec()))
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Exact match lookup failed: {}", e)))?;
+        
+        let mut chunks = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            chunks.extend(chunk_result_from_row(&row));
+        }
+        
+        Ok(chunks)
+    }
+    
+    /// Find similar chunks for cross-source linking
+    /// 
+    /// Combines vector similarity with confidence boosters in a single query
+    pub async fn find_similar_chunks_for_linking(
+        &self,
+        source_chunk_id: &EntityId,
+        target_source_kind: &str,
+        limit: usize,
+        min_similarity: f32,
+        boosts: &LinkBoosts,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<CrossSourceMatch>> {
+        let mut result = self.graph.execute(
+            query(CROSS_LINK_CYPHER)
+                .param("source_id", source_chunk_id.to_string())
+                .param("target_kind", target_source_kind)
+                .param("limit", limit as i64)
+                .param("min_similarity", min_similarity as f64)
+                .param("mention_boost", boosts.explicit_mention as f64)
+                .param("author_boost", boosts.author_overlap as f64)
+                .param("temporal_boost", boosts.temporal_proximity as f64)
+                .param("temporal_days", boosts.temporal_days)
+                .param("total_boost", boosts.total() as f64)
+                .param("normalize", boosts.normalize)
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Cross-source search failed: {}", e)))?;
+        
+        let mut matches = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let Some(target_id) = EntityId::from_stored(&row.get::<String>("target_id").unwrap_or_default()) else {
+                continue;
+            };
+            matches.push(CrossSourceMatch {
+                target_id,
+                target_content: row.get("target_content").ok(),
+                target_source_type: row.get("target_source_type").ok(),
+                target_file_path: row.get("target_file_path").ok(),
+                similarity_score: row.get::<f64>("similarity_score").unwrap_or(0.0) as f32,
+                confidence: row.get::<f64>("confidence").unwrap_or(0.0) as f32,
+                has_explicit_mention: row.get("has_explicit_mention").unwrap_or(false),
+                has_author_overlap: row.get("has_author_overlap").unwrap_or(false),
+                has_temporal_proximity: row.get("has_temporal_proximity").unwrap_or(false),
+                temporal_distance_days: row.get::<Option<i64>>("temporal_distance_days").unwrap_or(None),
+            });
+        }
+        
+        Ok(matches)
+    }
+    
+    /// Page through chunks eligible as link sources, ordered by id
+    /// 
+    /// Returns `(id, source_kind)` for chunks with an embedding that are not
+    /// below the token minimum. Unless `force` is set, chunks that already have
+    /// outgoing `SEMANTICALLY_SIMILAR` edges are skipped.
+    pub async fn linkable_chunks(
+        &self,
+        filter: &LinkableChunkFilter<'_>,
+        after: &str,
+        limit: usize,
+    ) -> GraphResult<Vec<(String, String)>> {
+        let cypher = linkable_chunks_cypher();
+        
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("after", after)
+                .param("limit", limit as i64)
+                .param("chunk_ids", filter.chunk_ids.map(|ids| ids.to_vec()))
+                .param("source_kind", filter.source_kind.map(|s| s.to_string()))
+                .param("force", filter.force)
+                .param("owner_id", filter.owner_id.map(|s| s.to_string()))
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut chunks = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            chunks.push((
+                row.get("id").unwrap_or_default(),
+                row.get("source_kind").unwrap_or_default(),
+            ));
+        }
+        
+        Ok(chunks)
+    }
+    
+    /// Mark a `MODULE` node as an import stub with the file paths it could refer to
+    pub async fn mark_import_stub(&self, node_id: &EntityId, path_suffixes: Vec<String>) -> GraphResult<()> {
+        let cypher = r#"
+            MATCH (n {id: $node_id})
+            SET n.is_import = true,
+                n.import_path_suffixes = $path_suffixes
+        "#;
+        
+        self.graph.run(
+            query(cypher)
+                .param("node_id", node_id.to_string())
+                .param("path_suffixes", path_suffixes)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Failed to mark import: {}", e)))
+    }
+    
+    /// Link unresolved import stubs to the modules and files they refer to
+    /// 
+    /// Covers stubs from earlier ingests too, so the import and its target can
+    /// arrive in either order. Returns the number of `RESOLVES_TO` edges created.
+    pub async fn resolve_imports(&self) -> GraphResult<usize> {
+        let mut result = self.graph.execute(
+            query(&resolve_imports_cypher(&self.labels.for_type(EntityType::Module)))
+                .param("extraction_method", ExtractionMethod::PatternMatch.as_str())
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Import resolution failed: {}", e)))?;
+        
+        match result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            Some(row) => Ok(row.get::<i64>("resolved").unwrap_or(0) as usize),
+            None => Ok(0),
+        }
+    }
+    
+    /// Up to `limit` nodes with ids after `after`, in id order, with their outgoing relationships
+    /// 
+    /// With `owner_id` set only that owner's nodes, and relationships between
+    /// them, are returned. Vector properties are left out unless
+    /// `include_embeddings` is set. Nodes are read per label from an id range,
+    /// so unlabelled nodes aren't exported.
+    pub async fn export_page(
+        &self,
+        owner_id: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+        include_embeddings: bool,
+    ) -> GraphResult<Vec<ExportNode>> {
+        let labels = self.stored_labels().await?;
+        if labels.is_empty() {
+            return Ok(Vec::new());
+        }
+        let cypher = export_page_cypher(&labels);
+        
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+                .param("after", after.unwrap_or(""))
+                .param("limit", limit as i64)
+                .param("include_embeddings", include_embeddings)
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut nodes = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let id: String = row.get("id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            let decode_error = |what: &str, e: neo4rs::DeError| {
+                GraphError::Neo4j(format!("Failed to decode {} of node {}: {}", what, id, e))
+            };
+            let properties = row.get::<serde_json::Map<String, serde_json::Value>>("properties")
+                .map_err(|e| decode_error("properties", e))?
+                .into_iter()
+                // Keys nulled out by the projection
+                .filter(|(_, v)| !v.is_null())
+                .collect();
+            let labels = row.get("labels").map_err(|e| decode_error("labels", e))?;
+            let edges = row.get("edges").map_err(|e| decode_error("edges", e))?;
+            nodes.push(ExportNode { id, labels, properties, edges });
+        }
+        
+        Ok(nodes)
+    }
+    
+    /// Labels in use, skipping any that can't be spliced into a query
+    async fn stored_labels(&self) -> GraphResult<Vec<String>> {
+        let mut result = self.graph.execute(query("CALL db.labels() YIELD label RETURN label"))
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut labels = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let label: String = row.get("label").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            if is_valid_label(&label) {
+                labels.push(label);
+            } else {
+                tracing::warn!("Skipping label '{}' that isn't a plain identifier", label);
+            }
+        }
+        Ok(labels)
+    }
+    
+    /// Create nodes or merge into existing ones by id, returning the ids written
+    /// 
+    /// Imported properties are added to a stored node's, so an export taken
+    /// without embeddings doesn't wipe them. A stored node is found by id under
+    /// any known label and keeps its labels. Map-valued properties are stored
+    /// as JSON strings since Neo4j can't hold maps. With `owner_id` set, ids
+    /// already taken by another owner's node are skipped. Labels must already
+    /// be validated with `is_valid_label`.
+    pub async fn import_nodes(&self, nodes: &[ExportNode], owner_id: Option<&str>) -> GraphResult<Vec<String>> {
+        // Labels can't be parameters, so each label set gets its own query
+        let mut by_labels: HashMap<&[String], Vec<&ExportNode>> = HashMap::new();
+        for node in nodes {
+            by_labels.entry(node.labels.as_slice()).or_default().push(node);
+        }
+        
+        // Every label an id may already be stored under
+        let mut known_labels: Vec<String> = EntityType::ALL.iter().map(|t| self.labels.for_type(t.clone())).collect();
+        known_labels.extend(["CHUNK".to_string(), "CANONICAL".to_string()]);
+        for label in by_labels.keys().flat_map(|labels| labels.iter()) {
+            if !known_labels.contains(label) {
+                known_labels.push(label.clone());
+            }
+        }
+        if let Some(label) = known_labels.iter().find(|label| !is_valid_label(label)) {
+            return Err(GraphError::InvalidRequest(format!("Invalid label '{}'", label)));
+        }
+        
+        let mut written = Vec::new();
+        for (labels, nodes) in by_labels {
+            if let Some(label) = labels.iter().find(|label| !is_valid_label(label)) {
+                return Err(GraphError::InvalidRequest(format!("Invalid label '{}'", label)));
+            }
+            let Some((first, rest)) = labels.split_first() else {
+                return Err(GraphError::InvalidRequest("Imported nodes need at least one label".to_string()));
+            };
+            let new_labels: String = std::iter::once(first).chain(rest).map(|label| format!(":{}", label)).collect();
+            // Exported datetimes come back as strings
+            let temporal: String = TEMPORAL_NODE_PROPERTIES
+                .iter()
+                .map(|key| format!(", n.{key} = datetime(n.{key})"))
+                .collect();
+            let cypher = format!(
+                r#"
+                UNWIND $nodes AS node
+                OPTIONAL MATCH (existing:{} {{id: node.id}})
+                WITH node, existing
+                WHERE existing IS NULL OR $owner_id IS NULL OR existing.owner_id = $owner_id
+                CALL {{
+                    WITH node, existing
+                    WITH node, existing WHERE existing IS NULL
+                    CREATE (created{} {{id: node.id}})
+                    RETURN created AS n
+                    UNION
+                    WITH node, existing
+                    WITH existing WHERE existing IS NOT NULL
+                    RETURN existing AS n
+                }}
+                SET n += node.properties
+                SET n.id = node.id{}
+                RETURN node.id AS id
+                "#,
+                known_labels.join("|"),
+                new_labels,
+                temporal,
+            );
+            
+            let mut nodes_param: Vec<HashMap<String, BoltType>> = Vec::with_capacity(nodes.len());
+            for node in nodes {
+                let mut properties = node.properties.clone();
+                properties.insert("id".to_string(), node.id.clone().into());
+                for value in properties.values_mut() {
+                    if value.is_object() {
+                        *value = value.to_string().into();
+                    }
+                }
+                let properties = BoltType::try_from(serde_json::Value::Object(properties))
+                    .map_err(|e| GraphError::InvalidRequest(format!("Invalid properties on node {}: {}", node.id, e)))?;
+                nodes_param.push(HashMap::from([
+                    ("id".to_string(), BoltType::from(node.id.clone())),
+                    ("properties".to_string(), properties),
+                ]));
+            }
+            
+            let mut rows = self.graph.execute(
+                query(&cypher)
+                    .param("nodes", nodes_param)
+                    .param("owner_id", owner_id.map(|s| s.to_string()))
+            )
+            .await
+            .map_err(|e| GraphError::Neo4j(format!("Failed to import nodes: {}", e)))?;
+            
+            while let Some(row) = rows.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+                written.push(row.get("id").map_err(|e| GraphError::Neo4j(e.to_string()))?);
+            }
+        }
+        
+        Ok(written)
+    }
+    
+    /// Create or update relationships given as `(source_id, edge)`, returning how many were written
+    /// 
+    /// Unlike `create_relationships_batch` an existing relationship of the same
+    /// type between the same nodes is updated, so re-importing doesn't
+    /// duplicate edges. Edges with a missing endpoint, or with `owner_id` set
+    /// an endpoint of another owner, are skipped. Types must already be
+    /// validated with `is_valid_label`.
+    pub async fn import_relationships(
+        &self,
+        edges: &[(String, ExportEdge)],
+        owner_id: Option<&str>,
+    ) -> GraphResult<usize> {
+        let mut by_type: HashMap<&str, Vec<&(String, ExportEdge)>> = HashMap::new();
+        for edge in edges {
+            by_type.entry(edge.1.rel_type.as_str()).or_default().push(edge);
+        }
+        
+        let mut written = 0;
+        for (rel_type, edges) in by_type {
+            if !is_valid_label(rel_type) {
+                return Err(GraphError::InvalidRequest(format!("Invalid relationship type '{}'", rel_type)));
+            }
+            let cypher = format!(
+                r#"
+                UNWIND $edges AS edge
+                MATCH (a {{id: edge.from_id}}), (b {{id: edge.to_id}})
+                WHERE $owner_id IS NULL OR (a.owner_id = $owner_id AND b.owner_id = $owner_id)
+                MERGE (a)-[r:{}]->(b)
+                ON CREATE SET r.created_at = datetime(), r.properties = '{{}}'
+                SET r.confidence = edge.confidence,
+                    r.extraction_method = edge.extraction_method,
+                    r.extraction_methods = [edge.extraction_method]
+                RETURN count(r) AS written
+                "#,
+                rel_type
+            );
+            let edges_param: Vec<HashMap<String, BoltType>> = edges.iter().map(|(from_id, edge)| {
+                HashMap::from([
+                    ("from_id".to_string(), BoltType::from(from_id.clone())),
+                    ("to_id".to_string(), BoltType::from(edge.target.clone())),
+                    ("confidence".to_string(), BoltType::from(edge.confidence.unwrap_or(1.0) as f64)),
+                    ("extraction_method".to_string(), BoltType::from(
+                        edge.extraction_method.clone().unwrap_or_else(|| ExtractionMethod::Manual.as_str().to_string()),
+                    )),
+                ])
+            }).collect();
+            
+            let mut result = self.graph.execute(
+                query(&cypher)
+                    .param("edges", edges_param)
+                    .param("owner_id", owner_id.map(|s| s.to_string()))
+            )
+            .await
+            .map_err(|e| GraphError::Neo4j(format!("Failed to import relationships: {}", e)))?;
+            
+            if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+                written += row.get::<i64>("written").unwrap_or(0) as usize;
+            }
+        }
+        
+        Ok(written)
+    }
+    
+    /// Delete `owner_id`'s nodes not in `keep_nodes` and relationships between its
+    /// nodes not in `keep_edges` (`from_id|TYPE|to_id`), `batch_size` per transaction
+    /// 
+    /// Returns how many nodes were removed.
+    pub async fn prune_owner_graph(
+        &self,
+        owner_id: &str,
+        keep_nodes: &[String],
+        keep_edges: &[String],
+        batch_size: usize,
+    ) -> GraphResult<usize> {
+        let delete_nodes = r#"
+            MATCH (n {owner_id: $owner_id})
+            WHERE NOT n.id IN $keep
+            WITH n LIMIT $batch_size
+            DETACH DELETE n
+            RETURN count(n) AS deleted
+        "#;
+        let delete_edges = r#"
+            MATCH (a {owner_id: $owner_id})-[r]->(b {owner_id: $owner_id})
+            WHERE NOT a.id + '|' + type(r) + '|' + b.id IN $keep
+            WITH r LIMIT $batch_size
+            DELETE r
+            RETURN count(r) AS deleted
+        "#;
+        
+        let nodes_deleted = self.delete_in_batches(delete_nodes, owner_id, keep_nodes, batch_size).await?;
+        self.delete_in_batches(delete_edges, owner_id, keep_edges, batch_size).await?;
+        Ok(nodes_deleted)
+    }
+    
+    /// Run a `$owner_id`/`$keep`/`$batch_size` delete until it removes nothing, returning the total
+    async fn delete_in_batches(&self, cypher: &str, owner_id: &str, keep: &[String], batch_size: usize) -> GraphResult<usize> {
+        let mut deleted = 0;
+        loop {
+            let mut result = self.graph.execute(
+                query(cypher)
+                    .param("owner_id", owner_id)
+                    .param("keep", keep.to_vec())
+                    .param("batch_size", batch_size.max(1) as i64)
+            )
+            .await
+            .map_err(|e| GraphError::Neo4j(format!("Owner deletion failed: {}", e)))?;
+            
+            let batch = match result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+                Some(row) => row.get::<i64>("deleted").unwrap_or(0) as usize,
+                None => 0,
+            };
+            if batch == 0 {
+                return Ok(deleted);
+            }
+            deleted += batch;
+        }
+    }
+    
+    /// Nodes without any relationship, in id order
+    /// 
+    /// Usually entities left behind by an earlier ingestion. Without a
+    /// `label`, chunk and canonical nodes are skipped (`ORPHAN_PROTECTED_LABELS`).
+    pub async fn find_orphans(
+        &self,
+        label: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<OrphanNode>> {
+        let cypher = format!(
+            r#"
+            MATCH (n)
+            WHERE NOT (n)--()
+              AND n.id IS NOT NULL
+              AND CASE WHEN $label IS NULL
+                    THEN none(l IN labels(n) WHERE l IN $protected)
+                    ELSE $label IN labels(n) END
+              AND {}
+            RETURN n.id AS id, labels(n)[0] AS label, n.name AS name
+            ORDER BY n.id
+            LIMIT $limit
+            "#,
+            owner_predicate("n")
+        );
+        
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("label", label.map(|s| s.to_string()))
+                .param("protected", ORPHAN_PROTECTED_LABELS.to_vec())
+                .param("limit", limit as i64)
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        let mut orphans = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            orphans.push(OrphanNode {
+                id: row.get("id").unwrap_or_default(),
+                label: row.get("label").unwrap_or_default(),
+                name: row.get("name").ok(),
+            });
+        }
+        
+        Ok(orphans)
+    }
+    
+    /// Delete the given nodes if they are still orphans, returning the ids removed
+    /// 
+    /// A node that gained a relationship since `find_orphans` is kept.
+    pub async fn delete_orphans(&self, ids: &[String], owner_id: Option<&str>) -> GraphResult<Vec<String>> {
+        let cypher = format!(
+            r#"
+            MATCH (n)
+            WHERE n.id IN $ids AND NOT (n)--() AND {}
+            WITH n, n.id AS id
+            DELETE n
+            RETURN id
+            "#,
+            owner_predicate("n")
+        );
+        
+        let mut result = self.graph.execute(
+            query(&cypher)
+                .param("ids", ids.to_vec())
+                .param("owner_id", owner_id.map(|s| s.to_string()))
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(format!("Orphan deletion failed: {}", e)))?;
+        
+        let mut deleted = Vec::new();
+        while let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            deleted.extend(row.get::<String>("id").ok());
+        }
+        Ok(deleted)
+    }
+    
+    /// Delete a chunk's `SEMANTICALLY_SIMILAR` edges in either direction, returning how many were removed
+    pub async fn delete_cross_links(&self, chunk_id: &EntityId) -> GraphResult<usize> {
+        let cypher = r#"
+            MATCH (c:CHUNK {id: $chunk_id})-[r:SEMANTICALLY_SIMILAR]-()
+            DELETE r
+            RETURN count(r) AS deleted
+        "#;
+        
+        let mut result = self.graph.execute(query(cypher).param("chunk_id", chunk_id.to_string()))
+            .await
+            .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        match result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            Some(row) => Ok(row.get::<i64>("deleted").unwrap_or(0) as usize),
+            None => Ok(0),
+        }
+    }
+    
+    /// Create or merge a cross-source relationship with evidence
+    /// 
+    /// The edge records every contributing `extraction_methods` entry plus a
+    /// primary `extraction_method` (`combined` when there is more than one).
+    /// Re-linking an existing pair keeps the higher confidence and merges the
+    /// evidence instead of overwriting it.
+    pub async fn create_cross_source_link(
+        &self,
+        from_id: &EntityId,
+        to_id: &EntityId,
+        confidence: f32,
+        similarity_score: f32,
+        extraction_methods: &[ExtractionMethod],
+    ) -> GraphResult<String> {
+        let mut result = self.graph.execute(
+            query(CROSS_LINK_UPSERT_CYPHER)
+                .param("from_id", from_id.to_string())
+                .param("to_id", to_id.to_string())
+                .param("confidence", confidence as f64)
+                .param("similarity_score", similarity_score as f64)
+                .param("explicit_mention", extraction_methods.contains(&ExtractionMethod::ExplicitMention))
+                .param("author_overlap", extraction_methods.contains(&ExtractionMethod::AuthorOverlap))
+                .param(
+                    "extraction_methods",
+                    extraction_methods.iter().map(|m| m.as_str().to_string()).collect::<Vec<_>>(),
+                )
+        )
+        .await
+        .map_err(|e| GraphError::Neo4j(e.to_string()))?;
+        
+        if let Some(row) = result.next().await.map_err(|e| GraphError::Neo4j(e.to_string()))? {
+            let rel_id: String = row.get("rel_id").map_err(|e| GraphError::Neo4j(e.to_string()))?;
+            Ok(rel_id)
+        } else {
+            Err(GraphError::Neo4j("Failed to create cross-source link".to_string()))
+        }
+    }
+    
+    /// Create a range index on `label.property` if it doesn't exist
+    async fn create_range_index(&self, label: &str, property: &str) -> GraphResult<()> {
+        let cypher = format!(
+            "CREATE INDEX {}_{}_idx IF NOT EXISTS FOR (n:{}) ON (n.{})",
+            label.to_lowercase(), property, label, property
+        );
+        self.graph.run(query(&cypher)).await.map_err(|e| GraphError::Neo4j(e.to_string()))
+    }
+    
+    /// Initialize vector indexes for the knowledge graph
+    pub async fn initialize_vector_indexes(&self, dimension: usize) -> GraphResult<()> {
+        // Create index for chunks
+        self.create_vector_index("chunk_embedding_idx", "CHUNK", "embedding", dimension).await?;
+        self.vector_indexes.mark_indexed("CHUNK");
+        
+        // Lookup indexes for ingest deduplication, exact-match search, and export paging
+        for property in ["id", "content_hash", "file_path", "section_title"] {
+            self.create_range_index("CHUNK", property).await?;
+        }
+        self.create_range_index("CANONICAL", "id").await?;
+        for entity_type in EntityType::ALL {
+            self.create_range_index(&self.labels.for_type(entity_type.clone()), "id").await?;
+        }
+        
+        // Create indexes for main entity types
+        for entity_type in INDEXED_ENTITY_TYPES {
+            let label = self.labels.for_type(entity_type);
+            self.create_vector_index(&vector_index_name(&label), &label, "embedding", dimension).await?;
+            self.vector_indexes.mark_indexed(&label);
+            self.create_range_index(&label, "name").await?;
+        }
+        
+        tracing::info!("✅ All vector indexes initialized");
+        Ok(())
+    }
+}
+
+/// Earliest Neo4j release whose vector index syntax and procedures this client uses
+pub const MIN_VECTOR_INDEX_VERSION: (u32, u32) = (5, 21);
+
+/// Whether a Neo4j version string is at least `MIN_VECTOR_INDEX_VERSION`
+/// 
+/// Accepts `5.26.0`, `5.26-aura`, and calendar versions like `2025.01.0`.
+/// `None` when there's no leading `major.minor`.
+pub fn supports_vector_indexes(version: &str) -> Option<bool> {
+    let mut parts = version.trim().split('.').map(|part| {
+        let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse::<u32>().ok()
+    });
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    Some((major, minor) >= MIN_VECTOR_INDEX_VERSION)
+}
+
+/// Entity types that get a vector index and a name lookup index
+const INDEXED_ENTITY_TYPES: [EntityType; 7] = [
+    EntityType::Function,
+    EntityType::Class,
+    EntityType::Document,
+    EntityType::Section,
+    EntityType::Concept,
+    EntityType::File,
+    EntityType::Module,
+];
+
+/// Restricts every node on path `p` to the `$owner_id` tenant (no-op when null)
+const PATH_OWNER_PREDICATE: &str =
+    "ALL(x IN nodes(p) WHERE $owner_id IS NULL OR x.owner_id = $owner_id)";
+
+/// Chunk summary from a row with `chunk_id`, `source_*`, `file_path`, and `updated_at`
+fn recent_chunk_from_row(row: &Row) -> Option<RecentChunk> {
+    let id: String = row.get("chunk_id").unwrap_or_default();
+    let Ok(chunk_id) = Uuid::parse_str(&id) else {
+        tracing::warn!("Skipping chunk with non-UUID id: {}", id);
+        return None;
+    };
+    
+    Some(RecentChunk {
+        chunk_id,
+        source_kind: row.get("source_kind").unwrap_or_default(),
+        source_type: row.get("source_type").unwrap_or_default(),
+        source_id: row.get("source_id").unwrap_or_default(),
+        file_path: row.get("file_path").ok(),
+        updated_at: row.get::<String>("updated_at")
+            .ok()
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|t| t.with_timezone(&Utc)),
+    })
+}
+
+/// Restrict node `var` to the `$owner_id` tenant (no-op when null)
+fn owner_predicate(var: &str) -> String {
+    format!("($owner_id IS NULL OR {}.owner_id = $owner_id)", var)
+}
+
+/// Error for a chunk whose id is already stored for another owner
+pub(crate) fn chunk_owned_elsewhere(chunk: &Chunk) -> GraphError {
+    GraphError::InvalidRequest(format!("Chunk {} belongs to another owner", chunk.id))
+}
+
+/// Keep rows whose `outgoing` flag matches `$direction` (see [`direction_param`])
+const DIRECTION_PREDICATE: &str =
+    "($direction IS NULL OR outgoing = ($direction = 'outgoing'))";
+
+/// Ids as a Cypher list parameter
+fn id_param(ids: &[EntityId]) -> Vec<String> {
+    ids.iter().map(EntityId::to_string).collect()
+}
+
+/// `$direction` for [`DIRECTION_PREDICATE`]: `None` follows both directions
+fn direction_param(direction: &str) -> Option<String> {
+    EdgeDirection::filter(direction).map(|d| d.as_str().to_string())
+}
+
+/// Direction of a row's edge from its `outgoing` column
+/// 
+/// A missing column is a bug in the query, not an outgoing edge.
+fn edge_direction(row: &Row) -> GraphResult<EdgeDirection> {
+    let outgoing = row.get::<bool>("outgoing")
+        .map_err(|e| GraphError::Neo4j(format!("Row has no outgoing column: {}", e)))?;
+    Ok(if outgoing { EdgeDirection::Outgoing } else { EdgeDirection::Incoming })
+}
+
+/// Build a `:TYPE_A|TYPE_B` relationship filter (empty when unfiltered)
+fn relationship_type_filter(relationship_types: Option<&[RelationshipType]>) -> String {
+    match relationship_types {
+        Some(types) if !types.is_empty() => {
+            let type_strs: Vec<&str> = types.iter().map(|t| t.as_str()).collect();
+            format!(":{}", type_strs.join("|"))
+        }
+        _ => String::new(),
+    }
+}
+
+/// Entities with an optional label, filtered by source and owner
+/// 
+/// `label` must already be validated (it comes from `LabelMapping`); the
+/// source and limit are always parameters.
+fn find_entities_cypher(label: Option<&str>) -> String {
+    let type_filter = label.map(|label| format!(":{}", label)).unwrap_or_default();
+    format!(
+        r#"
+        MATCH (n{})
+        WHERE ($source IS NULL OR n.source = $source)
+          AND {}
+        RETURN n.id as id, n.name as name, labels(n)[0] as entity_type
+        LIMIT $limit
+        "#,
+        type_filter, owner_predicate("n")
+    )
+}
+
+/// `CREATE VECTOR INDEX` DDL, which can't take parameters
+/// 
+/// Every interpolated name must be a plain identifier.
+fn vector_index_cypher(index_name: &str, label: &str, property: &str, dimension: usize) -> GraphResult<String> {
+    if let Some(name) = [index_name, label, property].into_iter().find(|name| !is_valid_label(name)) {
+        return Err(GraphError::InvalidRequest(format!("Invalid identifier for vector index: '{}'", name)));
+    }
+    Ok(format!(
+        r#"
+        CREATE VECTOR INDEX {} IF NOT EXISTS
+        FOR (n:{})
+        ON (n.{})
+        OPTIONS {{
+            indexConfig: {{
+                `vector.dimensions`: {},
+                `vector.similarity_function`: 'cosine'
+            }}
+        }}
+        "#,
+        index_name, label, property, dimension
+    ))
+}
+
+/// Build the neighbor query for a traversal pattern
+fn neighbors_cypher(direction_pattern: &str, order_by_confidence: bool) -> String {
+    let order_clause = if order_by_confidence {
+        "ORDER BY confidence DESC"
+    } else {
+        ""
+    };
+    
+    format!(
+        r#"
+        MATCH p = (start {{id: $entity_id}}){}(end)
+        WHERE {}
+        WITH end, r
+        UNWIND r as rel
+        WITH DISTINCT
+            end.id as entity_id,
+            end.name as name,
+            labels(end)[0] as entity_type,
+            end.source as source,
+            end.properties as properties,
+            end.file_path as file_path,
+            type(rel) as rel_type,
+            toFloat(COALESCE(rel.confidence, 1.0)) as confidence
+        WHERE confidence >= $min_confidence
+        RETURN entity_id, name, entity_type, source, properties, file_path, rel_type, confidence
+        {}
+        LIMIT $limit
+        "#,
+        direction_pattern,
+        PATH_OWNER_PREDICATE,
+        order_clause
+    )
+}
+
+/// Assemble a `GraphPath` from the per-path lists returned by Cypher
+/// 
+/// `total_confidence` is the product of the edge confidences along the path.
+fn build_graph_path(
+    node_ids: Vec<String>,
+    rel_types: Vec<String>,
+    confidences: Vec<f64>,
+) -> GraphResult<GraphPath> {
+    let nodes = node_ids
+        .iter()
+        .map(|id| {
+            Uuid::parse_str(id)
+                .map_err(|_| GraphError::Neo4j(format!("Path contains non-UUID node id: {}", id)))
+        })
+        .collect::<GraphResult<Vec<Uuid>>>()?;
+    
+    let total_confidence = confidences.iter().map(|&c| c as f32).product();
+    
+    Ok(GraphPath {
+        nodes,
+        relationships: rel_types,
+        total_confidence,
+    })
+}
+
+/// Build the exact-match chunk lookup over entities with the given labels
+/// 
+/// Each branch is a single-property equality so it can use a range index.
+fn exact_chunks_cypher(entity_labels: &[String]) -> String {
+    format!(
+        r#"
+        CALL {{
+            MATCH (c:CHUNK {{file_path: $text}}) RETURN c
+            UNION
+            MATCH (c:CHUNK {{section_title: $text}}) RETURN c
+            UNION
+            MATCH (e:{} {{name: $text}})
+            MATCH (c:CHUNK {{id: e.chunk_id}})
+            RETURN c
+        }}
+        WITH c AS node
+        WHERE NOT ($exclude_below_min_tokens AND COALESCE(node.below_min_tokens, false))
+          AND ($owner_id IS NULL OR node.owner_id = $owner_id)
+          AND ($source_types IS NULL OR node.source_type IN $source_types)
+          AND ($repo_name IS NULL OR node.repo_name = $repo_name)
+          AND ($path_regex IS NULL OR node.file_path =~ $path_regex)
+          AND ($languages IS NULL OR toLower(node.language) IN $languages)
+        RETURN
+            node.id as chunk_id,
+            node.content as content,
+            node.source_kind as source_kind,
+            node.source_type as source_type,
+            node.file_path as file_path,
+            node.repo_name as repo_name,
+            node.language as language,
+            node.heading_path as heading_path,
+            node.summary as summary,
+            COALESCE(node.commit_date, node.updated_at).epochMillis as last_modified_ms,
+            1.0 as score
+        ORDER BY node.file_path, node.id
+        LIMIT $limit
+        "#,
+        entity_labels.join("|")
+    )
+}
+
+/// Build an export page over nodes with the given labels
+/// 
+/// Each label branch seeks its own id range, so a page never scans the
+/// nodes before `$after`. `labels` must already be validated.
+fn export_page_cypher(labels: &[String]) -> String {
+    let branches: Vec<String> = labels
+        .iter()
+        .map(|label| format!(
+            "MATCH (n:{}) WHERE n.id > $after AND ($owner_id IS NULL OR n.owner_id = $owner_id) \
+             RETURN n ORDER BY n.id LIMIT $limit",
+            label
+        ))
+        .collect();
+    // Datetimes don't decode to JSON, so they're exported as strings
+    let temporal: Vec<String> = TEMPORAL_NODE_PROPERTIES
+        .iter()
+        .map(|key| format!("{key}: toString(n.{key})"))
+        .collect();
+    format!(
+        r#"
+        CALL {{
+            {}
+        }}
+        WITH n ORDER BY n.id LIMIT $limit
+        OPTIONAL MATCH (n)-[r]->(m)
+        WHERE m.id IS NOT NULL AND ($owner_id IS NULL OR m.owner_id = $owner_id)
+        WITH n, n {{.*, {}}} AS stored, collect(CASE WHEN r IS NULL THEN null ELSE {{
+            target: m.id,
+            rel_type: type(r),
+            confidence: r.confidence,
+            extraction_method: r.extraction_method
+        }} END) AS edges
+        RETURN n.id AS id, labels(n) AS labels, edges,
+               CASE WHEN $include_embeddings THEN stored
+                    ELSE stored {{.*, embedding: null, summary_embedding: null}} END AS properties
+        ORDER BY id
+        "#,
+        branches.join("\n            UNION\n            "),
+        temporal.join(", ")
+    )
+}
+
+/// Parse a chunk row returned by the vector or exact-match search
+fn chunk_result_from_row(row: &neo4rs::Row) -> Option<ChunkResult> {
+    let id: String = row.get("chunk_id").unwrap_or_default();
+    let Ok(chunk_id) = Uuid::parse_str(&id) else {
+        tracing::warn!("Skipping search hit with non-UUID chunk id: {}", id);
+        return None;
+    };
+    
+    // Euclidean indexes can report scores slightly outside 0..=1
+    let score = (row.get::<f64>("score").unwrap_or(0.0) as f32).clamp(0.0, 1.0);
+    Some(ChunkResult {
+        chunk_id,
+        content: row.get("content").unwrap_or_default(),
+        source_kind: row.get("source_kind").unwrap_or_default(),
+        source_type: row.get("source_type").unwrap_or_default(),
+        file_path: row.get("file_path").ok(),
+        repo_name: row.get("repo_name").ok(),
+        language: row.get("language").ok(),
+        heading_path: row.get("heading_path").ok(),
+        summary: row.get("summary").ok(),
+        similarity_score: score,
+        raw_score: Some(score),
+        final_score: score,
+        last_modified: row.get::<Option<i64>>("last_modified_ms")
+            .unwrap_or(None)
+            .and_then(DateTime::from_timestamp_millis),
+        pinned: false,
+        cross_source_link_count: None,
+        embedding: row.get::<Option<Vec<f64>>>("embedding")
+            .unwrap_or(None)
+            .map(|values| values.into_iter().map(|x| x as f32).collect()),
+    })
+}
+
+/// Parameters for `find_similar_chunks`
+#[derive(Debug)]
+pub struct ChunkVectorSearch<'a> {
+    /// Vector index to query (`chunk_embedding_idx` for the default model)
+    pub index_name: &'a str,
+    pub limit: usize,
+    pub min_score: f32,
+    pub exclude_below_min_tokens: bool,
+    /// Return stored embeddings with each hit
+    pub include_embeddings: bool,
+    pub owner_id: Option<&'a str>,
+    /// Only chunks from these source types (e.g. "github", "dropbox")
+    pub source_types: Option<&'a [String]>,
+    /// Only chunks from this repository
+    pub repo_name: Option<&'a str>,
+    /// Only chunks whose file path matches
+    pub path_glob: Option<&'a PathGlob>,
+    /// Only chunks in these languages, lowercased (see `language_filter`)
+    pub languages: Option<&'a [String]>,
+}
+
+impl ChunkVectorSearch<'_> {
+    /// Nearest neighbours to fetch from the index before filtering
+    /// 
+    /// The index can't apply filters itself, so filtered searches over-fetch
+    /// to still fill `limit` after the post-filter.
+    pub fn candidate_count(&self) -> usize {
+        let filtered = self.owner_id.is_some()
+            || self.source_types.is_some()
+            || self.repo_name.is_some()
+            || self.path_glob.is_some()
+            || self.languages.is_some();
+        if filtered {
+            self.limit * VECTOR_FILTER_OVERFETCH
+        } else {
+            self.limit
+        }
+    }
+}
+
+/// Candidates fetched per requested result when vector hits are post-filtered
+const VECTOR_FILTER_OVERFETCH: usize = 3;
+
+/// Vector search over chunks, post-filtered by owner, source type, repository, path, and language
+const CHUNK_VECTOR_SEARCH_CYPHER: &str = r#"
+    CALL db.index.vector.queryNodes($index_name, $candidates, $embedding)
+    YIELD node, score
+    WHERE score >= $min_score
+      AND NOT ($exclude_below_min_tokens AND COALESCE(node.below_min_tokens, false))
+      AND ($owner_id IS NULL OR node.owner_id = $owner_id)
+      AND ($source_types IS NULL OR node.source_type IN $source_types)
+      AND ($repo_name IS NULL OR node.repo_name = $repo_name)
+      AND ($path_regex IS NULL OR node.file_path =~ $path_regex)
+      AND ($languages IS NULL OR toLower(node.language) IN $languages)
+    RETURN
+        node.id as chunk_id,
+        node.content as content,
+        node.source_kind as source_kind,
+        node.source_type as source_type,
+        node.file_path as file_path,
+        node.repo_name as repo_name,
+        node.language as language,
+        node.heading_path as heading_path,
+        node.summary as summary,
+        COALESCE(node.commit_date, node.updated_at).epochMillis as last_modified_ms,
+        CASE WHEN $include_embeddings THEN node.embedding ELSE null END as embedding,
+        score
+    ORDER BY score DESC
+    LIMIT $limit
+"#;
+
+/// Which chunks `linkable_chunks` returns
+#[derive(Debug, Default)]
+pub struct LinkableChunkFilter<'a> {
+    /// Restrict to these chunk ids
+    pub chunk_ids: Option<&'a [String]>,
+    pub source_kind: Option<&'a str>,
+    /// Include chunks that already have outgoing cross-source links
+    pub force: bool,
+    pub owner_id: Option<&'a str>,
+}
+
+/// Chunks eligible as cross-link sources, paged by id
+fn linkable_chunks_cypher() -> String {
+    format!(
+        r#"
+        MATCH (c:CHUNK)
+        WHERE c.id > $after
+          AND ($chunk_ids IS NULL OR c.id IN $chunk_ids)
+          AND ($source_kind IS NULL OR c.source_kind = $source_kind)
+          AND c.embedding IS NOT NULL
+          AND NOT COALESCE(c.below_min_tokens, false)
+          AND ($force OR NOT (c)-[:SEMANTICALLY_SIMILAR]->())
+          AND {}
+        RETURN c.id AS id, c.source_kind AS source_kind
+        ORDER BY c.id
+        LIMIT $limit
+        "#,
+        owner_predicate("c")
+    )
+}
+
+/// Nodes eligible for the embedding backfill
+/// 
+/// The label is compared as a parameter so it never needs interpolating.
+fn missing_embeddings_cypher() -> String {
+    format!(
+        r#"
+        MATCH (n)
+        WHERE n.embedding IS NULL
+          AND n.content IS NOT NULL
+          AND n.id IS NOT NULL
+          AND ($label IS NULL OR $label IN labels(n))
+          AND {}
+        RETURN n.id AS id, n.content AS content
+        ORDER BY n.id
+        LIMIT $limit
+        "#,
+        owner_predicate("n")
+    )
+}
+
+/// Delete one batch of a repo's chunks plus the entities extracted from them
+/// 
+/// Incident relationships are counted before `DETACH DELETE` removes them.
+fn delete_repo_batch_cypher() -> String {
+    format!(
+        r#"
+        MATCH (c:CHUNK {{repo_name: $repo_name}})
+        WHERE {}
+        WITH c LIMIT $batch_size
+        OPTIONAL MATCH (e {{chunk_id: c.id}})
+        WHERE NOT e:CHUNK
+        WITH collect(DISTINCT c) AS chunks, collect(DISTINCT e) AS entities
+        WITH chunks + entities AS doomed, size(chunks) AS chunk_count, size(entities) AS entity_count
+        CALL {{
+            WITH doomed
+            UNWIND doomed AS n
+            MATCH (n)-[r]-()
+            RETURN count(DISTINCT r) AS relationships
+        }}
+        FOREACH (n IN doomed | DETACH DELETE n)
+        RETURN chunk_count AS chunks, entity_count AS entities, relationships
+        "#,
+        owner_predicate("c")
+    )
+}
+
+/// Relationship listing filtered by extraction method, type, confidence, and owner
+/// 
+/// With `$exclusive`, only edges whose sole extraction method is `$extraction_method` match.
+fn relationships_cypher() -> String {
+    format!(
+        r#"
+        MATCH (a)-[r]->(b)
+        WITH a, r, b, COALESCE(r.extraction_methods, []) AS methods
+        WHERE ($extraction_method IS NULL OR $extraction_method IN methods)
+          AND (NOT $exclusive OR size(methods) = 1)
+          AND ($relationship_type IS NULL OR type(r) = $relationship_type)
+          AND COALESCE(r.confidence, 0.0) >= $min_confidence
+          AND {}
+          AND {}
+        RETURN a.id AS from_id, b.id AS to_id, type(r) AS rel_type,
+               COALESCE(r.confidence, 0.0) AS confidence,
+               methods AS extraction_methods,
+               r.similarity_score AS similarity_score
+        ORDER BY confidence ASC
+        LIMIT $limit
+        "#,
+        owner_predicate("a"),
+        owner_predicate("b")
+    )
+}
+
+/// Resolve import stubs to declared modules (by name) and file chunks (by path)
+/// 
+/// Targets must belong to the same owner as the stub. `module_label` is the
+/// mapped label of module entities.
+fn resolve_imports_cypher(module_label: &str) -> String {
+    format!(r#"
+    MATCH (stub:{module_label})
+    WHERE stub.is_import = true AND NOT (stub)-[:RESOLVES_TO]->()
+    CALL {{
+        WITH stub
+        MATCH (m:{module_label})
+        WHERE m.name = stub.name
+          AND m.id <> stub.id
+          AND NOT COALESCE(m.is_import, false)
+          AND COALESCE(m.owner_id, '') = COALESCE(stub.owner_id, '')
+        RETURN m AS target, 0.9 AS confidence
+        UNION
+        WITH stub
+        MATCH (c:CHUNK)
+        WHERE c.file_path IS NOT NULL
+          AND COALESCE(c.owner_id, '') = COALESCE(stub.owner_id, '')
+          AND any(s IN COALESCE(stub.import_path_suffixes, [])
+                  WHERE c.file_path = s OR c.file_path ENDS WITH '/' + s)
+        RETURN c AS target, 0.8 AS confidence
+    }}
+    MERGE (stub)-[r:RESOLVES_TO]->(target)
+    ON CREATE SET
+        r.confidence = confidence,
+        r.extraction_method = $extraction_method,
+        r.extraction_methods = [$extraction_method],
+        r.created_at = datetime()
+    RETURN count(r) AS resolved
+"#)
+}
+
+/// Vector search for cross-source link candidates, with mention and author boosts
+/// 
+/// Boost weights come in as `$mention_boost` / `$author_boost` so the Neo4j and
+/// in-memory paths score links with the same configured values.
+const CROSS_LINK_CYPHER: &str = r#"
+    // Get source chunk and its embedding
+    MATCH (source:CHUNK {id: $source_id})
+    WHERE source.embedding IS NOT NULL
+    
+    // Vector similarity search
+    CALL db.index.vector.queryNodes('chunk_embedding_idx', $limit * 2, source.embedding)
+    YIELD node AS target, score
+    
+    // Filter by target source kind and minimum similarity
+    WHERE target.source_kind = $target_kind
+      AND target.id <> $source_id
+      AND score >= $min_similarity
+      AND NOT COALESCE(target.below_min_tokens, false)
+      AND ($owner_id IS NULL OR target.owner_id = $owner_id)
+    
+    // Days between the chunks, dating each by its commit (or its last write);
+    // only meaningful when at least one side has a commit date
+    WITH source, target, score,
+         CASE WHEN source.commit_date IS NOT NULL OR target.commit_date IS NOT NULL
+              THEN abs(duration.inDays(
+                  COALESCE(source.commit_date, source.updated_at),
+                  COALESCE(target.commit_date, target.updated_at)
+              ).days)
+         END AS temporal_distance_days
+    
+    // Detect boost signals
+    WITH source, target, score, temporal_distance_days,
+         COALESCE(source.entity_names IS NOT NULL
+                  AND any(name IN source.entity_names WHERE target.content CONTAINS name), false)
+             AS has_explicit_mention,
+         COALESCE(source.author IS NOT NULL AND source.author = target.author, false)
+             AS has_author_overlap,
+         COALESCE(temporal_distance_days <= $temporal_days, false) AS has_temporal_proximity
+    
+    // Boosts the pair's metadata allows
+    WITH target, score, temporal_distance_days, has_explicit_mention, has_author_overlap, has_temporal_proximity,
+         $mention_boost AS mention_boost,
+         CASE WHEN source.author IS NOT NULL AND target.author IS NOT NULL THEN $author_boost ELSE 0.0 END
+             AS author_boost,
+         CASE WHEN temporal_distance_days IS NOT NULL AND $temporal_days IS NOT NULL THEN $temporal_boost ELSE 0.0 END
+             AS temporal_boost
+    
+    // Earned boost, each applied once (temporal scaled down across the window)
+    WITH target, score, temporal_distance_days, has_explicit_mention, has_author_overlap, has_temporal_proximity,
+         CASE WHEN has_explicit_mention THEN mention_boost ELSE 0.0 END
+             + CASE WHEN has_author_overlap THEN author_boost ELSE 0.0 END
+             + CASE WHEN has_temporal_proximity
+                    THEN temporal_boost * (1.0 - toFloat(temporal_distance_days) / CASE WHEN $temporal_days < 1 THEN 1 ELSE $temporal_days END)
+                    ELSE 0.0 END AS earned,
+         mention_boost + author_boost + temporal_boost AS achievable
+    
+    // Calculate final confidence (normalized when enabled, capped at 1.0)
+    WITH target, score, temporal_distance_days, has_explicit_mention, has_author_overlap, has_temporal_proximity,
+         score + CASE WHEN $normalize AND achievable > 0.0
+                      THEN earned * $total_boost / achievable
+                      ELSE earned END AS raw_confidence
+    
+    RETURN 
+        target.id AS target_id,
+        target.content AS target_content,
+        target.source_type AS target_source_type,
+        target.file_path AS target_file_path,
+        score AS similarity_score,
+        CASE WHEN raw_confidence > 1.0 THEN 1.0 ELSE raw_confidence END AS confidence,
+        has_explicit_mention,
+        has_author_overlap,
+        has_temporal_proximity,
+        temporal_distance_days
+    ORDER BY confidence DESC
+    LIMIT $limit
+"#;
+
+/// Upsert a `SEMANTICALLY_SIMILAR` edge, keeping the max confidence on re-link
+const CROSS_LINK_UPSERT_CYPHER: &str = r#"
+    MATCH (a {id: $from_id}), (b {id: $to_id})
+    MERGE (a)-[r:SEMANTICALLY_SIMILAR]->(b)
+    ON CREATE SET
+        r.confidence = $confidence,
+        r.similarity_score = $similarity_score,
+        r.explicit_mention = $explicit_mention,
+        r.author_overlap = $author_overlap,
+        r.extraction_methods = $extraction_methods,
+        r.created_at = datetime()
+    ON MATCH SET
+        r.confidence = CASE WHEN $confidence > COALESCE(r.confidence, 0.0)
+                            THEN $confidence ELSE r.confidence END,
+        r.similarity_score = CASE WHEN $similarity_score > COALESCE(r.similarity_score, 0.0)
+                                  THEN $similarity_score ELSE r.similarity_score END,
+        r.explicit_mention = COALESCE(r.explicit_mention, false) OR $explicit_mention,
+        r.author_overlap = COALESCE(r.author_overlap, false) OR $author_overlap,
+        r.extraction_methods = COALESCE(r.extraction_methods, [])
+            + [m IN $extraction_methods WHERE NOT m IN COALESCE(r.extraction_methods, [])]
+    SET r.updated_at = datetime(),
+        r.extraction_method = CASE size(r.extraction_methods)
+            WHEN 0 THEN 'vector_similarity'
+            WHEN 1 THEN r.extraction_methods[0]
+            ELSE 'combined'
+        END
+    RETURN elementId(r) as rel_id
+"#;
+
+/// Confidence boosts for cross-source links
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkBoosts {
+    pub explicit_mention: f32,
+    pub author_overlap: f32,
+    pub temporal_proximity: f32,
+    /// Temporal proximity window in days (`None` when the signal is disabled)
+    pub temporal_days: Option<i64>,
+    /// Rescale earned boosts to the signals a pair's metadata allows
+    pub normalize: bool,
+}
+
+/// Which boost signals a chunk pair's metadata lets fire
+/// 
+/// A mention can always be looked for. Author overlap needs an author on
+/// both chunks, and temporal proximity needs a commit date (and a scorer that
+/// checks it).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AvailableSignals {
+    pub author: bool,
+    pub temporal: bool,
+}
+
+impl LinkBoosts {
+    /// Configured boost weights (a disabled signal contributes nothing)
+    pub fn from_config(config: &Config) -> Self {
+        let enabled = |flag: bool, weight: f32| if flag { weight } else { 0.0 };
+        Self {
+            explicit_mention: enabled(config.enable_explicit_mentions, config.explicit_mention_boost),
+            author_overlap: enabled(config.enable_author_overlap, config.author_overlap_boost),
+            temporal_proximity: enabled(config.enable_temporal_proximity, config.temporal_proximity_boost),
+            temporal_days: config.enable_temporal_proximity.then_some(config.temporal_proximity_days),
+            normalize: config.normalize_link_boosts,
+        }
+    }
+    
+    /// Temporal boost factor for chunks `days` apart (1.0 same-day, 0.0 at the window edge)
+    /// 
+    /// `None` outside the window or when the signal is disabled.
+    pub fn temporal_factor(&self, days: i64) -> Option<f32> {
+        let window = self.temporal_days?;
+        (days <= window).then(|| 1.0 - days as f32 / window.max(1) as f32)
+    }
+    
+    /// Sum of every boost weight, the most any link can earn
+    pub fn total(&self) -> f32 {
+        self.explicit_mention + self.author_overlap + self.temporal_proximity
+    }
+    
+    /// Like `confidence`, but comparable across pairs with different metadata
+    /// 
+    /// With `normalize` set, the earned boost is divided by the boost the
+    /// pair could earn given `available`, then scaled to `total`. A pair that
+    /// earns everything its metadata allows gets the full `total`, whether or
+    /// not it has an author or commit date.
+    pub fn normalized_confidence(
+        &self,
+        similarity: f32,
+        explicit_mention: bool,
+        author_overlap: bool,
+        temporal_factor: Option<f32>,
+        available: AvailableSignals,
+    ) -> f32 {
+        let achievable = self.explicit_mention
+            + if available.author { self.author_overlap } else { 0.0 }
+            + if available.temporal { self.temporal_proximity } else { 0.0 };
+        if !self.normalize || achievable <= 0.0 {
+            return self.confidence(similarity, explicit_mention, author_overlap, temporal_factor);
+        }
+        let earned = self.confidence(0.0, explicit_mention, author_overlap && available.author, temporal_factor.filter(|_| available.temporal));
+        (similarity + earned * self.total() / achievable).min(1.0)
+    }
+    
+    /// Link confidence from similarity plus each present signal's boost, capped at 1.0
+    /// 
+    /// `temporal_factor` scales the temporal boost (1.0 for same-day, 0.0 at the window edge).
+    pub fn confidence(
+        &self,
+        similarity: f32,
+        explicit_mention: bool,
+        author_overlap: bool,
+        temporal_factor: Option<f32>,
+    ) -> f32 {
+        let mut confidence = similarity;
+        if explicit_mention {
+            confidence += self.explicit_mention;
+        }
+        if author_overlap {
+            confidence += self.author_overlap;
+        }
+        if let Some(factor) = temporal_factor {
+            confidence += self.temporal_proximity * factor.clamp(0.0, 1.0);
+        }
+        confidence.min(1.0)
+    }
+}
+
+/// A relationship to create in bulk: `(from_id, to_id, type, confidence)`
+pub type BatchEdge = (EntityId, EntityId, RelationshipType, f32);
+
+/// Outcome of `create_relationships_batch`
+#[derive(Debug, Clone, Default)]
+pub struct BatchEdgeResult {
+    pub created: usize,
+    /// Edges not created because an endpoint id matched no node
+    pub missing: Vec<BatchEdge>,
+}
+
+/// An entity reached by traversal, with the relationship that reached it
+#[derive(Debug, Clone)]
+pub struct Neighbor {
+    pub id: EntityId,
+    pub name: String,
+    /// Node label, e.g. `FUNCTION` (or its mapped label)
+    pub entity_type: String,
+    pub source: Option<String>,
+    pub properties: serde_json::Value,
+    /// File path of a chunk node; `None` for entities
+    pub file_path: Option<String>,
+    pub relationship: String,
+    pub confidence: f32,
+}
+
+/// An edge of an entity tree: `child` is reached from `parent_id` at `depth`
+#[derive(Debug, Clone)]
+pub struct TreeEdge {
+    pub parent_id: String,
+    pub child: Neighbor,
+    #[allow(dead_code)]
+    pub depth: usize,
+}
+
+/// A node as written by `GET /api/graph/export`, with its outgoing relationships
+#[derive(Debug, Clone)]
+pub struct ExportNode {
+    pub id: String,
+    pub labels: Vec<String>,
+    pub properties: serde_json::Map<String, serde_json::Value>,
+    pub edges: Vec<ExportEdge>,
+}
+
+/// An outgoing relationship of an `ExportNode`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportEdge {
+    pub target: String,
+    pub rel_type: String,
+    pub confidence: Option<f32>,
+    pub extraction_method: Option<String>,
+}
+
+/// A chunk reached from a search hit over a cross-source edge
+#[derive(Debug, Clone)]
+pub struct LinkedChunk {
+    /// The hit the link was followed from
+    pub hit_id: EntityId,
+    pub relationship: String,
+    pub confidence: f32,
+    /// Which way the link points from the hit
+    pub direction: EdgeDirection,
+    pub chunk: ChunkResult,
+}
+
+/// Cross-source links reached by walking out from a chunk
+#[derive(Debug, Clone, Default)]
+pub struct CrossSourceSubgraph {
+    /// Nodes reached, excluding the start, nearest first
+    pub nodes: Vec<EntityId>,
+    /// The link that first reached each node, oriented away from the start
+    pub edges: Vec<CrossSourceEdge>,
+}
+
+/// A link of a `CrossSourceSubgraph`
+#[derive(Debug, Clone)]
+pub struct CrossSourceEdge {
+    pub from_id: EntityId,
+    pub to_id: EntityId,
+    pub relationship: String,
+    pub confidence: f32,
+    /// `Outgoing` when the stored edge points from `from_id` to `to_id`
+    pub direction: EdgeDirection,
+    /// Hops from the start to `to_id`
+    pub depth: usize,
+    /// File path of the `to_id` chunk, if it has one
+    pub to_file_path: Option<String>,
+}
+
+impl CrossSourceSubgraph {
+    /// Add one hop of `(from_id, to_id, rel_type, confidence, direction, to_file_path)` links
+    /// 
+    /// Links to nodes already reached are dropped, and the strongest link to
+    /// each new node wins until `max_nodes` is reached. Links with an end that
+    /// isn't a UUID are logged and dropped. Returns the ids of the new nodes,
+    /// the frontier of the next hop.
+    pub(crate) fn add_hop(
+        &mut self,
+        mut links: Vec<(String, String, String, f32, EdgeDirection, Option<String>)>,
+        depth: usize,
+        visited: &mut HashSet<String>,
+        max_nodes: usize,
+    ) -> Vec<String> {
+        links.sort_by(|a, b| b.3.total_cmp(&a.3));
+        let mut frontier = Vec::new();
+        for (from_id, to_id, relationship, confidence, direction, to_file_path) in links {
+            if self.nodes.len() >= max_nodes {
+                break;
+            }
+            if visited.contains(&to_id) {
+                continue;
+            }
+            let (Some(from), Some(to)) = (EntityId::from_stored(&from_id), EntityId::from_stored(&to_id)) else {
+                continue;
+            };
+            self.edges.push(CrossSourceEdge {
+                from_id: from,
+                to_id: to,
+                relationship,
+                confidence,
+                direction,
+                depth,
+                to_file_path,
+            });
+            self.nodes.push(to);
+            visited.insert(to_id.clone());
+            frontier.push(to_id);
+        }
+        frontier
+    }
+}
+
+/// Result of a cross-source similarity search
+#[derive(Debug, Clone)]
 pub struct CrossSourceMatch {
-    pub target_id: String,
+    pub target_id: EntityId,
     pub target_content: Option<String>,
     pub target_source_type: Option<String>,
     pub target_file_path: Option<String>,
@@ -669,5 +3285,339 @@ pub struct CrossSourceMatch {
     pub confidence: f32,
     pub has_explicit_mention: bool,
     pub has_author_overlap: bool,
+    /// Within the temporal proximity window
+    pub has_temporal_proximity: bool,
+    /// Days between the chunks' dates, when at least one has a commit date
+    pub temporal_distance_days: Option<i64>,
+}
+
+impl CrossSourceMatch {
+    /// Signals behind this match, in the form stored on the link
+    pub fn extraction_methods(&self) -> Vec<ExtractionMethod> {
+        let mut methods = vec![ExtractionMethod::VectorSimilarity];
+        if self.has_explicit_mention {
+            methods.push(ExtractionMethod::ExplicitMention);
+        }
+        if self.has_temporal_proximity {
+            methods.push(ExtractionMethod::TemporalProximity);
+        }
+        if self.has_author_overlap {
+            methods.push(ExtractionMethod::AuthorOverlap);
+        }
+        methods
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_find_entities_cypher_parameterizes_source_and_limit() {
+        let cypher = find_entities_cypher(Some("FUNCTION"));
+        assert!(cypher.contains("MATCH (n:FUNCTION)"));
+        assert!(cypher.contains("n.source = $source"));
+        assert!(cypher.contains("LIMIT $limit"));
+        assert!(!cypher.contains('\''));
+        assert!(find_entities_cypher(None).contains("MATCH (n)"));
+    }
+    
+    #[test]
+    fn test_exact_chunks_cypher_matches_mapped_entity_labels() {
+        let cypher = exact_chunks_cypher(&["FUNCTION".to_string(), "Class".to_string()]);
+        assert!(cypher.contains("MATCH (e:FUNCTION|Class {name: $text})"));
+        assert!(cypher.contains("MATCH (c:CHUNK {file_path: $text})"));
+        assert!(cypher.contains("($owner_id IS NULL OR node.owner_id = $owner_id)"));
+    }
+    
+    #[test]
+    fn test_export_page_cypher_seeks_ids_per_label() {
+        let cypher = export_page_cypher(&["FUNCTION".to_string(), "CHUNK".to_string()]);
+        assert!(cypher.contains("MATCH (n:FUNCTION) WHERE n.id > $after"));
+        assert!(cypher.contains("MATCH (n:CHUNK) WHERE n.id > $after"));
+        assert_eq!(cypher.matches("UNION").count(), 1);
+        assert!(!cypher.contains("SKIP"));
+        assert!(!cypher.contains("MATCH (n)\n"));
+    }
+    
+    #[test]
+    fn test_vector_index_cypher_rejects_unsafe_identifiers() {
+        let cypher = vector_index_cypher("function_embedding_idx", "FUNCTION", "embedding", 384).unwrap();
+        assert!(cypher.contains("FOR (n:FUNCTION)"));
+        
+        for (index, label, property) in [
+            ("idx IF NOT EXISTS FOR (n:X) ON (n.y) OPTIONS {}; DROP INDEX x", "FUNCTION", "embedding"),
+            ("function_embedding_idx", "FUNCTION) DETACH DELETE n //", "embedding"),
+            ("function_embedding_idx", "FUNCTION", "embedding`"),
+        ] {
+            assert!(matches!(
+                vector_index_cypher(index, label, property, 384),
+                Err(GraphError::InvalidRequest(_))
+            ));
+        }
+    }
+    
+    #[test]
+    fn test_build_graph_path_chain() {
+        // A -[CALLS]-> B -[CALLS]-> C
+        let ids: Vec<String> = (0..3).map(|_| Uuid::new_v4().to_string()).collect();
+        let path = build_graph_path(
+            ids.clone(),
+            vec!["CALLS".to_string(), "CALLS".to_string()],
+            vec![0.5, 0.8],
+        ).unwrap();
+        
+        assert_eq!(path.nodes.len(), 3);
+        assert_eq!(path.nodes[0].to_string(), ids[0]);
+        assert_eq!(path.nodes[2].to_string(), ids[2]);
+        assert_eq!(path.relationships.len(), 2);
+        assert!((path.total_confidence - 0.4).abs() < 0.001);
+    }
+    
+    #[test]
+    fn test_build_graph_path_rejects_invalid_id() {
+        let result = build_graph_path(
+            vec!["not-a-uuid".to_string()],
+            Vec::new(),
+            Vec::new(),
+        );
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_neighbors_cypher_ordering_and_limit() {
+        let unordered = neighbors_cypher("-[r*1..1]-", false);
+        assert!(!unordered.contains("ORDER BY"));
+        assert!(unordered.contains("WHERE confidence >= $min_confidence"));
+        assert!(unordered.contains("LIMIT $limit"));
+        
+        assert!(unordered.contains(PATH_OWNER_PREDICATE));
+        assert!(unordered.contains("labels(end)[0] as entity_type"));
+        
+        let ordered = neighbors_cypher("-[r*1..1]-", true);
+        let order_pos = ordered.find("ORDER BY confidence DESC").unwrap();
+        let limit_pos = ordered.find("LIMIT $limit").unwrap();
+        assert!(order_pos < limit_pos, "ordering must apply before the limit");
+    }
+    
+    #[test]
+    fn test_cross_source_match_extraction_methods() {
+        let m = CrossSourceMatch {
+            target_id: EntityId(Uuid::new_v4()),
+            target_content: None,
+            target_source_type: None,
+            target_file_path: None,
+            similarity_score: 0.8,
+            confidence: 0.8,
+            has_explicit_mention: false,
+            has_author_overlap: false,
+            has_temporal_proximity: false,
+            temporal_distance_days: None,
+        };
+        assert_eq!(m.extraction_methods(), vec![ExtractionMethod::VectorSimilarity]);
+        assert_eq!(ExtractionMethod::primary(&m.extraction_methods()), ExtractionMethod::VectorSimilarity);
+        
+        let m = CrossSourceMatch { has_author_overlap: true, ..m };
+        assert_eq!(ExtractionMethod::primary(&m.extraction_methods()), ExtractionMethod::Combined);
+        
+        let cypher = relationships_cypher();
+        assert!(cypher.contains("$extraction_method IN methods"));
+        assert!(cypher.contains("a.owner_id = $owner_id") && cypher.contains("b.owner_id = $owner_id"));
+    }
+    
+    #[test]
+    fn test_linkable_chunks_cypher_pages_and_skips_linked() {
+        let cypher = linkable_chunks_cypher();
+        assert!(cypher.contains("c.id > $after"));
+        assert!(cypher.contains("ORDER BY c.id"));
+        assert!(cypher.contains("($chunk_ids IS NULL OR c.id IN $chunk_ids)"));
+        assert!(cypher.contains("($force OR NOT (c)-[:SEMANTICALLY_SIMILAR]->())"));
+        assert!(cypher.contains("c.owner_id = $owner_id"));
+    }
+    
+    #[test]
+    fn test_missing_embeddings_cypher_filters_label_and_owner() {
+        let cypher = missing_embeddings_cypher();
+        assert!(cypher.contains("n.embedding IS NULL"));
+        assert!(cypher.contains("n.content IS NOT NULL"));
+        assert!(cypher.contains("($label IS NULL OR $label IN labels(n))"));
+        assert!(cypher.contains(&owner_predicate("n")));
+        assert!(cypher.contains("LIMIT $limit"));
+    }
+    
+    #[test]
+    fn test_resolve_imports_cypher_stays_within_owner() {
+        let cypher = resolve_imports_cypher("MODULE");
+        assert!(cypher.contains("NOT (stub)-[:RESOLVES_TO]->()"));
+        assert!(cypher.contains("AND NOT COALESCE(m.is_import, false)"));
+        assert_eq!(
+            cypher.matches("= COALESCE(stub.owner_id, '')").count(),
+            2
+        );
+        assert!(cypher.contains("c.file_path ENDS WITH '/' + s"));
+        
+        // Stubs and their targets both use the mapped module label
+        let mapped = resolve_imports_cypher("Module");
+        assert!(mapped.contains("MATCH (stub:Module)"));
+        assert!(mapped.contains("MATCH (m:Module)"));
+    }
+    
+    #[test]
+    fn test_delete_repo_cypher_is_batched_and_scoped() {
+        let cypher = delete_repo_batch_cypher();
+        assert!(cypher.contains("MATCH (c:CHUNK {repo_name: $repo_name})"));
+        assert!(cypher.contains("c.owner_id = $owner_id"));
+        assert!(cypher.contains("LIMIT $batch_size"));
+        assert!(cypher.contains("(e {chunk_id: c.id})"));
+        assert!(cypher.contains("DETACH DELETE n"));
+    }
+    
+    #[test]
+    fn test_cross_link_upsert_keeps_max_confidence() {
+        let (on_create, on_match) = CROSS_LINK_UPSERT_CYPHER.split_once("ON MATCH SET").unwrap();
+        assert!(on_create.contains("MERGE (a)-[r:SEMANTICALLY_SIMILAR]->(b)"));
+        assert!(on_create.contains("r.confidence = $confidence"));
+        // An existing edge is never lowered by a weaker re-link
+        assert!(on_match.contains("CASE WHEN $confidence > COALESCE(r.confidence, 0.0)"));
+        assert!(!on_match.contains("r.confidence = $confidence"));
+    }
+    
+    fn vector_search(limit: usize) -> ChunkVectorSearch<'static> {
+        ChunkVectorSearch {
+            index_name: "chunk_embedding_idx",
+            limit,
+            min_score: 0.5,
+            exclude_below_min_tokens: false,
+            include_embeddings: false,
+            owner_id: None,
+            source_types: None,
+            repo_name: None,
+            path_glob: None,
+            languages: None,
+        }
+    }
+    
+    #[test]
+    fn test_unfiltered_vector_search_does_not_overfetch() {
+        assert_eq!(vector_search(10).candidate_count(), 10);
+        assert!(CHUNK_VECTOR_SEARCH_CYPHER.contains("queryNodes($index_name, $candidates, $embedding)"));
+        assert!(CHUNK_VECTOR_SEARCH_CYPHER.contains("LIMIT $limit"));
+    }
+    
+    #[test]
+    fn test_vector_search_source_type_filter() {
+        let types = vec!["github".to_string()];
+        let search = ChunkVectorSearch { source_types: Some(&types), ..vector_search(10) };
+        assert_eq!(search.candidate_count(), 30);
+        assert!(CHUNK_VECTOR_SEARCH_CYPHER
+            .contains("($source_types IS NULL OR node.source_type IN $source_types)"));
+    }
+    
+    #[test]
+    fn test_vector_search_repo_filter() {
+        let search = ChunkVectorSearch { repo_name: Some("relation-graph"), ..vector_search(10) };
+        assert_eq!(search.candidate_count(), 30);
+        assert!(CHUNK_VECTOR_SEARCH_CYPHER
+            .contains("($repo_name IS NULL OR node.repo_name = $repo_name)"));
+    }
+    
+    #[test]
+    fn test_vector_search_path_filter() {
+        let glob = PathGlob::parse("src/auth/**").unwrap();
+        let search = ChunkVectorSearch { path_glob: Some(&glob), ..vector_search(10) };
+        assert_eq!(search.candidate_count(), 30);
+        for cypher in [CHUNK_VECTOR_SEARCH_CYPHER.to_string(), exact_chunks_cypher(&["FUNCTION".to_string()])] {
+            assert!(cypher.contains("($path_regex IS NULL OR node.file_path =~ $path_regex)"));
+        }
+    }
+    
+    #[test]
+    fn test_vector_search_language_filter() {
+        let languages = vec!["python".to_string(), "py".to_string()];
+        let search = ChunkVectorSearch { languages: Some(&languages), ..vector_search(10) };
+        assert_eq!(search.candidate_count(), 30);
+        for cypher in [CHUNK_VECTOR_SEARCH_CYPHER.to_string(), exact_chunks_cypher(&["FUNCTION".to_string()])] {
+            assert!(cypher.contains("($languages IS NULL OR toLower(node.language) IN $languages)"));
+        }
+    }
+    
+    #[test]
+    fn test_vector_search_owner_filter() {
+        let search = ChunkVectorSearch { owner_id: Some("owner-1"), ..vector_search(10) };
+        assert_eq!(search.candidate_count(), 30);
+        assert!(CHUNK_VECTOR_SEARCH_CYPHER
+            .contains("($owner_id IS NULL OR node.owner_id = $owner_id)"));
+    }
+    
+    #[test]
+    fn test_vector_search_combined_filters_overfetch_once() {
+        let types = vec!["github".to_string(), "gitlab".to_string()];
+        let search = ChunkVectorSearch {
+            owner_id: Some("owner-1"),
+            source_types: Some(&types),
+            repo_name: Some("relation-graph"),
+            ..vector_search(10)
+        };
+        assert_eq!(search.candidate_count(), 30);
+    }
+    
+    #[test]
+    fn test_supports_vector_indexes_by_version() {
+        assert_eq!(supports_vector_indexes("5.26.0"), Some(true));
+        assert_eq!(supports_vector_indexes("5.21.0"), Some(true));
+        assert_eq!(supports_vector_indexes("5.26-aura"), Some(true));
+        assert_eq!(supports_vector_indexes("2025.01.0"), Some(true));
+        assert_eq!(supports_vector_indexes("5.20.0"), Some(false));
+        assert_eq!(supports_vector_indexes("4.4.30"), Some(false));
+        assert_eq!(supports_vector_indexes("dev"), None);
+        assert_eq!(supports_vector_indexes("5"), None);
+    }
+    
+    #[test]
+    fn test_cross_link_cypher_takes_boosts_as_params() {
+        assert_eq!(CROSS_LINK_CYPHER.matches("$mention_boost").count(), 1);
+        assert_eq!(CROSS_LINK_CYPHER.matches("$author_boost").count(), 1);
+        assert_eq!(CROSS_LINK_CYPHER.matches("$temporal_boost").count(), 1);
+        assert!(CROSS_LINK_CYPHER.contains("duration.inDays"));
+        assert!(!CROSS_LINK_CYPHER.contains("0.15"));
+        assert!(!CROSS_LINK_CYPHER.contains("0.10"));
+    }
+    
+    #[test]
+    fn test_link_boosts_apply_once_and_respect_flags() {
+        let mut config = Config::test_default();
+        config.explicit_mention_boost = 0.2;
+        config.author_overlap_boost = 0.1;
+        config.temporal_proximity_boost = 0.1;
+        config.enable_author_overlap = true;
+        config.enable_explicit_mentions = true;
+        config.enable_temporal_proximity = true;
+        
+        let boosts = LinkBoosts::from_config(&config);
+        assert!((boosts.confidence(0.5, true, false, None) - 0.7).abs() < 1e-6);
+        assert!((boosts.confidence(0.5, true, true, Some(0.5)) - 0.85).abs() < 1e-6);
+        assert_eq!(boosts.confidence(0.95, true, true, Some(1.0)), 1.0);
+        
+        config.enable_explicit_mentions = false;
+        let boosts = LinkBoosts::from_config(&config);
+        assert_eq!(boosts.explicit_mention, 0.0);
+        assert!((boosts.confidence(0.5, true, false, None) - 0.5).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_neighbor_query_defaults() {
+        let options: NeighborQuery = serde_json::from_str("{}").unwrap();
+        assert!(!options.order_by_confidence);
+        assert_eq!(options.min_confidence, 0.0);
+        assert_eq!(options.limit, 100);
+        
+        let options: NeighborQuery = serde_json::from_value(serde_json::json!({
+            "order_by_confidence": true,
+            "min_confidence": 0.7,
+            "limit": 5,
+        })).unwrap();
+        assert!(options.order_by_confidence);
+        assert_eq!(options.limit, 5);
+    }
+}
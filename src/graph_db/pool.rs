@@ -0,0 +1,252 @@
+//! Neo4j connection pool instrumentation
+//!
+//! neo4rs doesn't expose its pool, so `TrackedGraph` gates every query with a
+//! semaphore the size of the pool and times how long each one waits for a
+//! connection. A result stream holds its permit until it is dropped, like the
+//! pooled connection it reads from. Utilization is reported under `neo4j_pool`
+//! in `GET /metrics`.
+
+use crate::config::Config;
+use futures::stream::{BoxStream, StreamExt, TryStreamExt};
+use neo4rs::{Graph, Query, Row, Txn};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// How often frequent waiting is checked for (and warned about)
+const WARN_WINDOW: Duration = Duration::from_secs(60);
+
+/// Share of queries that must wait longer than `slow_wait` within a window to warn
+const WARN_SLOW_RATIO: f64 = 0.1;
+
+/// Pool size, result fetch size, and what counts as a slow wait
+#[derive(Debug, Clone)]
+pub struct PoolSettings {
+    pub max_connections: usize,
+    pub fetch_size: usize,
+    pub slow_wait: Duration,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self { max_connections: 10, fetch_size: 500, slow_wait: Duration::from_millis(100) }
+    }
+}
+
+impl PoolSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_connections: config.neo4j_max_connections,
+            fetch_size: config.neo4j_fetch_size,
+            slow_wait: Duration::from_millis(config.neo4j_slow_wait_ms),
+        }
+    }
+}
+
+/// A neo4rs `Graph` that records connection wait times
+pub struct TrackedGraph {
+    graph: Graph,
+    permits: Arc<Semaphore>,
+    settings: PoolSettings,
+    stats: PoolStats,
+}
+
+/// Rows of a query, holding its connection until dropped
+pub struct RowStream {
+    rows: BoxStream<'static, Result<Row, neo4rs::Error>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl RowStream {
+    pub async fn next(&mut self) -> Result<Option<Row>, neo4rs::Error> {
+        self.rows.try_next().await
+    }
+}
+
+/// An explicit transaction, holding its connection until dropped
+/// 
+/// A transaction dropped without a commit is rolled back when its
+/// connection is reset on return to the pool.
+pub struct TrackedTxn {
+    txn: Txn,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl TrackedTxn {
+    /// Run a query in the transaction; read its rows with `next`
+    pub async fn execute(&mut self, query: Query) -> Result<neo4rs::RowStream, neo4rs::Error> {
+        self.txn.execute(query).await
+    }
+
+    /// The next row of a result from `execute`
+    pub async fn next(&mut self, rows: &mut neo4rs::RowStream) -> Result<Option<Row>, neo4rs::Error> {
+        rows.next(self.txn.handle()).await
+    }
+
+    pub async fn rollback(self) -> Result<(), neo4rs::Error> {
+        self.txn.rollback().await
+    }
+}
+
+impl TrackedGraph {
+    pub fn new(graph: Graph, settings: PoolSettings) -> Self {
+        Self {
+            graph,
+            permits: Arc::new(Semaphore::new(settings.max_connections)),
+            settings,
+            stats: PoolStats::default(),
+        }
+    }
+
+    /// Run a query and stream its rows (see `Graph::execute`)
+    pub async fn execute(&self, query: Query) -> Result<RowStream, neo4rs::Error> {
+        let permit = self.acquire().await;
+        let rows = self.graph.execute(query).await?;
+        let rows = TryStreamExt::into_stream(rows.into_stream()).boxed();
+        Ok(RowStream { rows, _permit: permit })
+    }
+
+    /// Run a query, discarding its rows (see `Graph::run`)
+    pub async fn run(&self, query: Query) -> Result<(), neo4rs::Error> {
+        let _permit = self.acquire().await;
+        self.graph.run(query).await
+    }
+
+    /// Start an explicit transaction (see `Graph::start_txn`)
+    pub async fn start_txn(&self) -> Result<TrackedTxn, neo4rs::Error> {
+        let permit = self.acquire().await;
+        let txn = self.graph.start_txn().await?;
+        Ok(TrackedTxn { txn, _permit: permit })
+    }
+
+    async fn acquire(&self) -> OwnedSemaphorePermit {
+        let started = Instant::now();
+        let permit = self.permits.clone()
+            .acquire_owned()
+            .await
+            .expect("pool semaphore is never closed");
+        self.stats.record_wait(started.elapsed(), &self.settings);
+        permit
+    }
+
+    /// Current utilization and wait statistics
+    pub fn metrics(&self) -> serde_json::Value {
+        let in_use = self.settings.max_connections - self.permits.available_permits();
+        self.stats.snapshot(in_use, &self.settings)
+    }
+}
+
+/// Wait counters since startup, plus the current warning window
+#[derive(Default)]
+struct PoolStats {
+    acquires: AtomicU64,
+    slow_waits: AtomicU64,
+    wait_micros_total: AtomicU64,
+    wait_micros_max: AtomicU64,
+    window: Mutex<WarnWindow>,
+}
+
+struct WarnWindow {
+    started: Instant,
+    acquires: u64,
+    slow_waits: u64,
+}
+
+impl Default for WarnWindow {
+    fn default() -> Self {
+        Self { started: Instant::now(), acquires: 0, slow_waits: 0 }
+    }
+}
+
+impl PoolStats {
+    fn record_wait(&self, wait: Duration, settings: &PoolSettings) {
+        let micros = wait.as_micros() as u64;
+        let slow = wait >= settings.slow_wait;
+        self.acquires.fetch_add(1, Ordering::Relaxed);
+        self.wait_micros_total.fetch_add(micros, Ordering::Relaxed);
+        self.wait_micros_max.fetch_max(micros, Ordering::Relaxed);
+        if slow {
+            self.slow_waits.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut window = self.window.lock().unwrap();
+        window.acquires += 1;
+        window.slow_waits += u64::from(slow);
+        if window.started.elapsed() < WARN_WINDOW {
+            return;
+        }
+        if window.slow_waits as f64 >= window.acquires as f64 * WARN_SLOW_RATIO && window.slow_waits > 0 {
+            tracing::warn!(
+                "{} of {} Neo4j queries in the last {}s waited over {}ms for a connection; \
+                 consider raising NEO4J_MAX_CONNECTIONS (currently {})",
+                window.slow_waits,
+                window.acquires,
+                window.started.elapsed().as_secs(),
+                settings.slow_wait.as_millis(),
+                settings.max_connections,
+            );
+        }
+        *window = WarnWindow::default();
+    }
+
+    fn snapshot(&self, in_use: usize, settings: &PoolSettings) -> serde_json::Value {
+        let acquires = self.acquires.load(Ordering::Relaxed);
+        let wait_micros_total = self.wait_micros_total.load(Ordering::Relaxed);
+        let avg_wait_ms = if acquires == 0 {
+            0.0
+        } else {
+            wait_micros_total as f64 / acquires as f64 / 1000.0
+        };
+
+        serde_json::json!({
+            "max_connections": settings.max_connections,
+            "in_use": in_use,
+            "idle": settings.max_connections - in_use,
+            "acquires": acquires,
+            "slow_waits": self.slow_waits.load(Ordering::Relaxed),
+            "slow_wait_threshold_ms": settings.slow_wait.as_millis() as u64,
+            "avg_wait_ms": avg_wait_ms,
+            "max_wait_ms": self.wait_micros_max.load(Ordering::Relaxed) as f64 / 1000.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_in_range, NEO4J_FETCH_SIZE_RANGE, NEO4J_MAX_CONNECTIONS_RANGE};
+
+    #[test]
+    fn test_wait_stats_snapshot() {
+        let settings = PoolSettings { max_connections: 4, slow_wait: Duration::from_millis(50), ..PoolSettings::default() };
+        let stats = PoolStats::default();
+        stats.record_wait(Duration::from_millis(0), &settings);
+        stats.record_wait(Duration::from_millis(20), &settings);
+        stats.record_wait(Duration::from_millis(80), &settings);
+
+        let snapshot = stats.snapshot(3, &settings);
+        assert_eq!(snapshot["in_use"], 3);
+        assert_eq!(snapshot["idle"], 1);
+        assert_eq!(snapshot["acquires"], 3);
+        assert_eq!(snapshot["slow_waits"], 1);
+        assert_eq!(snapshot["max_wait_ms"], 80.0);
+        assert!((snapshot["avg_wait_ms"].as_f64().unwrap() - 100.0 / 3.0).abs() < 1e-6);
+
+        // The warning window only counts queries since it last rolled over
+        assert_eq!(stats.window.lock().unwrap().slow_waits, 1);
+    }
+
+    #[test]
+    fn test_settings_follow_configured_values() {
+        let mut config = Config::test_default();
+        config.neo4j_max_connections =
+            parse_in_range("NEO4J_MAX_CONNECTIONS", Some("32".to_string()), NEO4J_MAX_CONNECTIONS_RANGE, 10).unwrap();
+        config.neo4j_fetch_size =
+            parse_in_range("NEO4J_FETCH_SIZE", Some("2000".to_string()), NEO4J_FETCH_SIZE_RANGE, 500).unwrap();
+        let settings = PoolSettings::from_config(&config);
+
+        assert_eq!(settings.max_connections, 32);
+        assert_eq!(settings.fetch_size, 2000);
+    }
+}
@@ -0,0 +1,588 @@
+//! Backend-neutral graph operations
+//!
+//! `GraphStore` covers everything `HybridQueryEngine`, `ChunkProcessor`, and
+//! `CrossSourceLinker` need, so the services work against Neo4j, the
+//! in-memory backend (`GRAPH_BACKEND`), or a test double. Admin features
+//! (Cypher queries, shortest paths, repo deletion, relationship listing)
+//! still go through `Neo4jClient` directly.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use crate::error::GraphResult;
+use crate::graph_db::neo4j_client::{BatchEdge, BatchEdgeResult, ChunkVectorSearch, CrossSourceMatch, CrossSourceSubgraph, ExportEdge, ExportNode, LinkBoosts, LinkableChunkFilter, LinkedChunk, Neighbor, TreeEdge};
+use crate::graph_db::Neo4jClient;
+use crate::models::{
+    CanonicalEntity, Chunk, ChunkResult, CommitChunksResponse, Entity, EntityId, EntityType, ExtractionMethod, GraphPath, NeighborQuery,
+    OrphanNode, RelationshipType, StoredNode, StoredRelationship,
+};
+
+/// Graph storage, traversal, and vector search used by the services
+#[async_trait]
+pub trait GraphStore: Send + Sync {
+    /// Name reported by the health check ("neo4j" or "memory")
+    fn backend(&self) -> &'static str;
+
+    /// Create an entity node, or update the name and properties of an existing one
+    async fn upsert_entity_node(&self, entity: &Entity) -> GraphResult<StoredNode>;
+
+    /// Create or update the `CANONICAL` node for a resolved entity
+    async fn upsert_canonical_node(&self, canonical: &CanonicalEntity) -> GraphResult<()>;
+
+    /// Create a relationship between two existing entities, matched by id (not name)
+    async fn create_relationship(
+        &self,
+        from_id: &EntityId,
+        to_id: &EntityId,
+        rel_type: RelationshipType,
+        confidence: f32,
+        extraction_method: ExtractionMethod,
+        properties: Option<serde_json::Value>,
+    ) -> GraphResult<StoredRelationship>;
+
+    /// Create many relationships at once; edges with a missing endpoint are reported, not created
+    async fn create_relationships_batch(
+        &self,
+        edges: &[BatchEdge],
+        extraction_method: ExtractionMethod,
+    ) -> GraphResult<BatchEdgeResult>;
+
+    /// Entities within `hops` of `entity_id`, with their type and properties
+    async fn get_neighbors(
+        &self,
+        entity_id: &EntityId,
+        relationship_types: Option<&[RelationshipType]>,
+        direction: &str,
+        hops: usize,
+        options: &NeighborQuery,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<Neighbor>>;
+
+    /// Outgoing edges of the given types up to `max_depth` below `root_id`, shallowest first
+    async fn tree_edges(
+        &self,
+        root_id: &EntityId,
+        relationship_types: &[RelationshipType],
+        max_depth: usize,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<TreeEdge>>;
+
+    /// Entities by type and source as `(id, name, entity_type)`
+    async fn find_entities(
+        &self,
+        entity_type: Option<EntityType>,
+        source: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<(String, String, String)>>;
+
+    /// A single entity as `(id, name, entity_type)`
+    async fn get_entity(
+        &self,
+        entity_id: &EntityId,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<(String, String, String)>>;
+
+    /// Merge `patch` into an entity's properties and optionally rename it,
+    /// returning `(id, name, entity_type, properties)` afterwards
+    async fn update_entity_properties(
+        &self,
+        entity_id: &EntityId,
+        name: Option<&str>,
+        patch: &serde_json::Map<String, serde_json::Value>,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<(String, String, String, serde_json::Value)>>;
+
+    /// Paths of up to `hops` relationships starting at `start_id`
+    async fn find_paths(
+        &self,
+        start_id: &EntityId,
+        relationship_types: Option<&[RelationshipType]>,
+        direction: &str,
+        hops: usize,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<GraphPath>>;
+
+    /// Node and relationship counts
+    async fn get_statistics(&self) -> GraphResult<serde_json::Value>;
+
+    // Chunks and embeddings
+
+    /// Create or update a chunk node; returns the previous content hash
+    ///
+    /// Fails when the id belongs to another owner's chunk.
+    async fn upsert_chunk_node(
+        &self,
+        chunk: &Chunk,
+        summary: Option<&str>,
+        below_min_tokens: bool,
+    ) -> GraphResult<Option<String>>;
+
+    /// Id of an owner's stored chunk with this content hash, if any
+    async fn find_chunk_by_hash(&self, content_hash: &str, owner_id: &str) -> GraphResult<Option<String>>;
+
+    async fn set_node_embedding(
+        &self,
+        node_id: &EntityId,
+        embedding: Vec<f32>,
+        model: &str,
+        provider: &str,
+    ) -> GraphResult<()>;
+
+    async fn set_summary_embedding(&self, node_id: &EntityId, embedding: Vec<f32>) -> GraphResult<()>;
+
+    /// Record the entity names extracted from a chunk (for mention boosts)
+    async fn set_chunk_entity_names(&self, chunk_id: &EntityId, names: &[String]) -> GraphResult<()>;
+
+    /// Record how many windows were averaged into a chunk's embedding and whether content was cut off
+    async fn set_embedding_windows(&self, chunk_id: &EntityId, windows: usize, truncated: bool) -> GraphResult<()>;
+
+    /// Set many embeddings at once from `(node_id, embedding, model, provider)`
+    async fn batch_set_embeddings(
+        &self,
+        updates: Vec<(EntityId, Vec<f32>, String, String)>,
+    ) -> GraphResult<usize>;
+
+    /// Nodes with content but no embedding as `(id, content)`
+    ///
+    /// Nodes whose id isn't a UUID are logged and left out.
+    async fn nodes_missing_embeddings(
+        &self,
+        label: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<(EntityId, String)>>;
+
+    /// Chunks nearest to `embedding`
+    async fn find_similar_chunks(
+        &self,
+        embedding: Vec<f32>,
+        search: &ChunkVectorSearch<'_>,
+    ) -> GraphResult<Vec<ChunkResult>>;
+
+    /// Chunks whose file path or section title is exactly `text`, or that
+    /// contain an entity named `text`, using the filters and limit of `search`
+    async fn find_exact_chunks(
+        &self,
+        text: &str,
+        search: &ChunkVectorSearch<'_>,
+    ) -> GraphResult<Vec<ChunkResult>>;
+
+    // Imports
+
+    async fn mark_import_stub(&self, node_id: &EntityId, path_suffixes: Vec<String>) -> GraphResult<()>;
+
+    // Commits
+
+    /// Attach chunks to a commit node with `CONTAINS` edges; returns edges linked
+    async fn link_commit_chunks(&self, commit_id: &EntityId, chunk_ids: &[EntityId]) -> GraphResult<usize>;
+
+    /// Chunks a commit contains, or `None` when no visible commit has this sha
+    async fn commit_chunks(&self, commit_sha: &str, owner_id: Option<&str>) -> GraphResult<Option<CommitChunksResponse>>;
+
+    /// Link import stubs to the modules and files they name; returns links created
+    async fn resolve_imports(&self) -> GraphResult<usize>;
+
+    // Cross-source linking
+
+    /// Chunks of `target_source_kind` similar to `source_chunk_id`, scored for linking
+    ///
+    /// With `owner_id`, only that tenant's chunks are candidates.
+    async fn find_similar_chunks_for_linking(
+        &self,
+        source_chunk_id: &EntityId,
+        target_source_kind: &str,
+        limit: usize,
+        min_similarity: f32,
+        boosts: &LinkBoosts,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<CrossSourceMatch>>;
+
+    /// Chunks eligible as link sources as `(id, source_kind)`, paged by id
+    async fn linkable_chunks(
+        &self,
+        filter: &LinkableChunkFilter<'_>,
+        after: &str,
+        limit: usize,
+    ) -> GraphResult<Vec<(String, String)>>;
+
+    async fn create_cross_source_link(
+        &self,
+        from_id: &EntityId,
+        to_id: &EntityId,
+        confidence: f32,
+        similarity_score: f32,
+        extraction_methods: &[ExtractionMethod],
+    ) -> GraphResult<String>;
+
+    /// Cross-source links reachable from `chunk_id` within `hops`, at most `max_nodes` nodes
+    ///
+    /// See `Neo4jClient::get_cross_source_subgraph`.
+    async fn get_cross_source_subgraph(
+        &self,
+        chunk_id: &EntityId,
+        hops: usize,
+        direction: &str,
+        min_confidence: f32,
+        max_nodes: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<CrossSourceSubgraph>;
+
+    /// Chunks of the other source kind linked to any of `chunk_ids` in `direction`, strongest link first
+    ///
+    /// At most `max_links_per_hit` links are followed from each hit.
+    async fn cross_source_chunks(
+        &self,
+        chunk_ids: &[EntityId],
+        direction: &str,
+        max_links_per_hit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<LinkedChunk>>;
+
+    /// Cross-source link counts by chunk id (links whose other end `owner_id` can see)
+    async fn count_cross_source_links(
+        &self,
+        chunk_ids: &[EntityId],
+        owner_id: Option<&str>,
+    ) -> GraphResult<HashMap<EntityId, usize>>;
+
+    /// Delete a chunk's `SEMANTICALLY_SIMILAR` edges in either direction, returning how many were removed
+    async fn delete_cross_links(&self, chunk_id: &EntityId) -> GraphResult<usize>;
+    
+    /// Up to `limit` nodes after id `after`, in id order, with their outgoing relationships
+    async fn export_page(
+        &self,
+        owner_id: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+        include_embeddings: bool,
+    ) -> GraphResult<Vec<ExportNode>>;
+    
+    /// Create nodes or merge properties into existing ones by id (skipping ids
+    /// held by another owner), returning the ids written
+    async fn import_nodes(&self, nodes: &[ExportNode], owner_id: Option<&str>) -> GraphResult<Vec<String>>;
+    
+    /// Create or update `(source_id, edge)` relationships between visible nodes, returning how many were written
+    async fn import_relationships(&self, edges: &[(String, ExportEdge)], owner_id: Option<&str>) -> GraphResult<usize>;
+    
+    /// Delete `owner_id`'s nodes not in `keep_nodes`, and relationships between its
+    /// nodes not in `keep_edges` (as `from_id|TYPE|to_id`), returning how many nodes were removed
+    async fn prune_owner_graph(
+        &self,
+        owner_id: &str,
+        keep_nodes: &[String],
+        keep_edges: &[String],
+        batch_size: usize,
+    ) -> GraphResult<usize>;
+    
+    /// Up to `limit` nodes without any relationship, in id order
+    /// 
+    /// Without a `label`, nodes labelled with one of `ORPHAN_PROTECTED_LABELS` are skipped.
+    async fn find_orphans(
+        &self,
+        label: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<OrphanNode>>;
+    
+    /// Delete the given nodes if they are still orphans, returning the ids removed
+    async fn delete_orphans(&self, ids: &[String], owner_id: Option<&str>) -> GraphResult<Vec<String>>;
+}
+
+#[async_trait]
+impl GraphStore for Neo4jClient {
+    fn backend(&self) -> &'static str {
+        "neo4j"
+    }
+
+    async fn upsert_entity_node(&self, entity: &Entity) -> GraphResult<StoredNode> {
+        Neo4jClient::upsert_entity_node(self, entity).await
+    }
+
+    async fn create_relationship(
+        &self,
+        from_id: &EntityId,
+        to_id: &EntityId,
+        rel_type: RelationshipType,
+        confidence: f32,
+        extraction_method: ExtractionMethod,
+        properties: Option<serde_json::Value>,
+    ) -> GraphResult<StoredRelationship> {
+        Neo4jClient::create_relationship(self, from_id, to_id, rel_type, confidence, extraction_method, properties)
+            .await
+    }
+
+    async fn upsert_canonical_node(&self, canonical: &CanonicalEntity) -> GraphResult<()> {
+        Neo4jClient::upsert_canonical_node(self, canonical).await
+    }
+
+    async fn create_relationships_batch(
+        &self,
+        edges: &[BatchEdge],
+        extraction_method: ExtractionMethod,
+    ) -> GraphResult<BatchEdgeResult> {
+        Neo4jClient::create_relationships_batch(self, edges, extraction_method).await
+    }
+
+    async fn get_neighbors(
+        &self,
+        entity_id: &EntityId,
+        relationship_types: Option<&[RelationshipType]>,
+        direction: &str,
+        hops: usize,
+        options: &NeighborQuery,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<Neighbor>> {
+        Neo4jClient::get_neighbors(self, entity_id, relationship_types, direction, hops, options, owner_id).await
+    }
+
+    async fn tree_edges(
+        &self,
+        root_id: &EntityId,
+        relationship_types: &[RelationshipType],
+        max_depth: usize,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<TreeEdge>> {
+        Neo4jClient::tree_edges(self, root_id, relationship_types, max_depth, limit, owner_id).await
+    }
+
+    async fn find_entities(
+        &self,
+        entity_type: Option<EntityType>,
+        source: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<(String, String, String)>> {
+        Neo4jClient::find_entities(self, entity_type, source, limit, owner_id).await
+    }
+
+    async fn get_entity(
+        &self,
+        entity_id: &EntityId,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<(String, String, String)>> {
+        Neo4jClient::get_entity(self, entity_id, owner_id).await
+    }
+
+    async fn update_entity_properties(
+        &self,
+        entity_id: &EntityId,
+        name: Option<&str>,
+        patch: &serde_json::Map<String, serde_json::Value>,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Option<(String, String, String, serde_json::Value)>> {
+        Neo4jClient::update_entity_properties(self, entity_id, name, patch, owner_id).await
+    }
+
+    async fn find_paths(
+        &self,
+        start_id: &EntityId,
+        relationship_types: Option<&[RelationshipType]>,
+        direction: &str,
+        hops: usize,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<GraphPath>> {
+        Neo4jClient::find_paths(self, start_id, relationship_types, direction, hops, limit, owner_id).await
+    }
+
+    async fn get_statistics(&self) -> GraphResult<serde_json::Value> {
+        Neo4jClient::get_statistics(self).await
+    }
+
+    async fn upsert_chunk_node(
+        &self,
+        chunk: &Chunk,
+        summary: Option<&str>,
+        below_min_tokens: bool,
+    ) -> GraphResult<Option<String>> {
+        Neo4jClient::upsert_chunk_node(self, chunk, summary, below_min_tokens).await
+    }
+
+    async fn find_chunk_by_hash(&self, content_hash: &str, owner_id: &str) -> GraphResult<Option<String>> {
+        Neo4jClient::find_chunk_by_hash(self, content_hash, owner_id).await
+    }
+
+    async fn set_node_embedding(
+        &self,
+        node_id: &EntityId,
+        embedding: Vec<f32>,
+        model: &str,
+        provider: &str,
+    ) -> GraphResult<()> {
+        Neo4jClient::set_node_embedding(self, node_id, embedding, model, provider).await
+    }
+
+    async fn set_summary_embedding(&self, node_id: &EntityId, embedding: Vec<f32>) -> GraphResult<()> {
+        Neo4jClient::set_summary_embedding(self, node_id, embedding).await
+    }
+
+    async fn set_chunk_entity_names(&self, chunk_id: &EntityId, names: &[String]) -> GraphResult<()> {
+        Neo4jClient::set_chunk_entity_names(self, chunk_id, names).await
+    }
+
+    async fn set_embedding_windows(&self, chunk_id: &EntityId, windows: usize, truncated: bool) -> GraphResult<()> {
+        Neo4jClient::set_embedding_windows(self, chunk_id, windows, truncated).await
+    }
+
+    async fn batch_set_embeddings(
+        &self,
+        updates: Vec<(EntityId, Vec<f32>, String, String)>,
+    ) -> GraphResult<usize> {
+        Neo4jClient::batch_set_embeddings(self, updates).await
+    }
+
+    async fn nodes_missing_embeddings(
+        &self,
+        label: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<(EntityId, String)>> {
+        Neo4jClient::nodes_missing_embeddings(self, label, limit, owner_id).await
+    }
+
+    async fn find_similar_chunks(
+        &self,
+        embedding: Vec<f32>,
+        search: &ChunkVectorSearch<'_>,
+    ) -> GraphResult<Vec<ChunkResult>> {
+        Neo4jClient::find_similar_chunks(self, embedding, search).await
+    }
+
+    async fn find_exact_chunks(
+        &self,
+        text: &str,
+        search: &ChunkVectorSearch<'_>,
+    ) -> GraphResult<Vec<ChunkResult>> {
+        Neo4jClient::find_exact_chunks(self, text, search).await
+    }
+
+    async fn mark_import_stub(&self, node_id: &EntityId, path_suffixes: Vec<String>) -> GraphResult<()> {
+        Neo4jClient::mark_import_stub(self, node_id, path_suffixes).await
+    }
+
+    async fn resolve_imports(&self) -> GraphResult<usize> {
+        Neo4jClient::resolve_imports(self).await
+    }
+
+    async fn link_commit_chunks(&self, commit_id: &EntityId, chunk_ids: &[EntityId]) -> GraphResult<usize> {
+        Neo4jClient::link_commit_chunks(self, commit_id, chunk_ids).await
+    }
+
+    async fn commit_chunks(&self, commit_sha: &str, owner_id: Option<&str>) -> GraphResult<Option<CommitChunksResponse>> {
+        Neo4jClient::commit_chunks(self, commit_sha, owner_id).await
+    }
+
+    async fn find_similar_chunks_for_linking(
+        &self,
+        source_chunk_id: &EntityId,
+        target_source_kind: &str,
+        limit: usize,
+        min_similarity: f32,
+        boosts: &LinkBoosts,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<CrossSourceMatch>> {
+        Neo4jClient::find_similar_chunks_for_linking(
+            self, source_chunk_id, target_source_kind, limit, min_similarity, boosts, owner_id,
+        ).await
+    }
+
+    async fn linkable_chunks(
+        &self,
+        filter: &LinkableChunkFilter<'_>,
+        after: &str,
+        limit: usize,
+    ) -> GraphResult<Vec<(String, String)>> {
+        Neo4jClient::linkable_chunks(self, filter, after, limit).await
+    }
+
+    async fn create_cross_source_link(
+        &self,
+        from_id: &EntityId,
+        to_id: &EntityId,
+        confidence: f32,
+        similarity_score: f32,
+        extraction_methods: &[ExtractionMethod],
+    ) -> GraphResult<String> {
+        Neo4jClient::create_cross_source_link(self, from_id, to_id, confidence, similarity_score, extraction_methods)
+            .await
+    }
+
+    async fn get_cross_source_subgraph(
+        &self,
+        chunk_id: &EntityId,
+        hops: usize,
+        direction: &str,
+        min_confidence: f32,
+        max_nodes: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<CrossSourceSubgraph> {
+        Neo4jClient::get_cross_source_subgraph(self, chunk_id, hops, direction, min_confidence, max_nodes, owner_id).await
+    }
+
+    async fn cross_source_chunks(
+        &self,
+        chunk_ids: &[EntityId],
+        direction: &str,
+        max_links_per_hit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<LinkedChunk>> {
+        Neo4jClient::cross_source_chunks(self, chunk_ids, direction, max_links_per_hit, owner_id).await
+    }
+
+    async fn count_cross_source_links(
+        &self,
+        chunk_ids: &[EntityId],
+        owner_id: Option<&str>,
+    ) -> GraphResult<HashMap<EntityId, usize>> {
+        Neo4jClient::count_cross_source_links(self, chunk_ids, owner_id).await
+    }
+
+    async fn delete_cross_links(&self, chunk_id: &EntityId) -> GraphResult<usize> {
+        Neo4jClient::delete_cross_links(self, chunk_id).await
+    }
+    
+    async fn export_page(
+        &self,
+        owner_id: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+        include_embeddings: bool,
+    ) -> GraphResult<Vec<ExportNode>> {
+        Neo4jClient::export_page(self, owner_id, after, limit, include_embeddings).await
+    }
+    
+    async fn import_nodes(&self, nodes: &[ExportNode], owner_id: Option<&str>) -> GraphResult<Vec<String>> {
+        Neo4jClient::import_nodes(self, nodes, owner_id).await
+    }
+    
+    async fn import_relationships(&self, edges: &[(String, ExportEdge)], owner_id: Option<&str>) -> GraphResult<usize> {
+        Neo4jClient::import_relationships(self, edges, owner_id).await
+    }
+    
+    async fn prune_owner_graph(
+        &self,
+        owner_id: &str,
+        keep_nodes: &[String],
+        keep_edges: &[String],
+        batch_size: usize,
+    ) -> GraphResult<usize> {
+        Neo4jClient::prune_owner_graph(self, owner_id, keep_nodes, keep_edges, batch_size).await
+    }
+
+    async fn find_orphans(
+        &self,
+        label: Option<&str>,
+        limit: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<OrphanNode>> {
+        Neo4jClient::find_orphans(self, label, limit, owner_id).await
+    }
+
+    async fn delete_orphans(&self, ids: &[String], owner_id: Option<&str>) -> GraphResult<Vec<String>> {
+        Neo4jClient::delete_orphans(self, ids, owner_id).await
+    }
+}
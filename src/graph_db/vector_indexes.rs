@@ -0,0 +1,125 @@
+//! Tracks which labels have a vector index
+//!
+//! `initialize_vector_indexes` only covers a fixed label list. With lazy
+//! creation enabled, the first embedding written to a node of any other label
+//! creates `<label>_embedding_idx`. Known labels are cached so repeat writes
+//! don't re-issue `CREATE VECTOR INDEX ... IF NOT EXISTS`.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::graph_db::labels::is_valid_label;
+
+/// Name of the vector index covering `label`
+pub fn vector_index_name(label: &str) -> String {
+    format!("{}_embedding_idx", label.to_lowercase())
+}
+
+/// Labels known to have a vector index
+#[derive(Debug)]
+pub struct VectorIndexRegistry {
+    auto_create: bool,
+    dimension: AtomicUsize,
+    indexed: Mutex<HashSet<String>>,
+}
+
+impl VectorIndexRegistry {
+    pub fn new(auto_create: bool, dimension: usize) -> Self {
+        Self {
+            auto_create,
+            dimension: AtomicUsize::new(dimension),
+            indexed: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension.load(Ordering::Relaxed)
+    }
+
+    /// Create later indexes at `dimension` (after the existing ones were rebuilt)
+    pub fn set_dimension(&self, dimension: usize) {
+        self.dimension.store(dimension, Ordering::Relaxed);
+    }
+
+    /// Record that `label` has an index
+    pub fn mark_indexed(&self, label: &str) {
+        self.indexed.lock().unwrap().insert(label.to_string());
+    }
+
+    /// Claim the labels that still need an index, marking them as indexed
+    ///
+    /// Returns nothing when lazy creation is off. Call `forget` for any label
+    /// whose index creation then fails so a later write retries it.
+    pub fn claim_missing(&self, labels: &[String]) -> Vec<String> {
+        if !self.auto_create {
+            return Vec::new();
+        }
+
+        let mut indexed = self.indexed.lock().unwrap();
+        labels
+            .iter()
+            .filter(|label| {
+                if is_valid_label(label) {
+                    true
+                } else {
+                    tracing::warn!("Not creating a vector index for unsupported label '{}'", label);
+                    false
+                }
+            })
+            .filter(|label| indexed.insert(label.to_string()))
+            .cloned()
+            .collect()
+    }
+
+    /// Drop a label so its index creation is retried
+    pub fn forget(&self, label: &str) {
+        self.indexed.lock().unwrap().remove(label);
+    }
+
+    /// Names of all known vector indexes, sorted
+    pub fn index_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.indexed
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|label| vector_index_name(label))
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_claims_each_new_label_once() {
+        let registry = VectorIndexRegistry::new(true, 384);
+        registry.mark_indexed("CHUNK");
+
+        assert_eq!(registry.claim_missing(&labels(&["CHUNK", "ENDPOINT"])), labels(&["ENDPOINT"]));
+        assert!(registry.claim_missing(&labels(&["ENDPOINT"])).is_empty());
+
+        // Labels that can't be safely interpolated are never claimed
+        assert!(registry.claim_missing(&labels(&["Bad Label", "x`) DROP"])).is_empty());
+
+        registry.forget("ENDPOINT");
+        assert_eq!(registry.claim_missing(&labels(&["ENDPOINT"])), labels(&["ENDPOINT"]));
+        assert_eq!(
+            registry.index_names(),
+            vec!["chunk_embedding_idx".to_string(), "endpoint_embedding_idx".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_disabled_registry_claims_nothing() {
+        let registry = VectorIndexRegistry::new(false, 384);
+        assert!(registry.claim_missing(&labels(&["ENDPOINT"])).is_empty());
+    }
+}
@@ -0,0 +1,401 @@
+//! GraphQL API
+//!
+//! Mirrors the main REST read endpoints (hybrid search, entity lookup,
+//! neighbors, statistics) at `POST /graphql`, backed by the same engines.
+//! Hybrid search only runs graph expansion and cross-source lookups when the
+//! query selects the fields that need them, so `hybridSearch { chunks { content } }`
+//! costs a single vector search.
+
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, InputObject, Json as GqlJson, Object, Schema,
+    SimpleObject,
+};
+use axum::{Extension, Json};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::GraphError;
+use crate::handlers::{collect_statistics, AppState, OwnerScope};
+use crate::models::{
+    ChunkResult, EntityId, EntityResult, HybridSearchRequest, NeighborQuery, RelationshipResult,
+    SearchMetadata as SearchMetadataModel, SearchOptions, SemanticLink as SemanticLinkModel,
+};
+use crate::services::HybridQueryEngine;
+
+pub type GraphSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Build the schema over the shared application state
+pub fn build_schema(state: Arc<AppState>) -> GraphSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(state)
+        .finish()
+}
+
+/// `POST /graphql` (scoped by `X-Owner-Id` like the REST endpoints)
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref()))]
+pub async fn graphql_handler(
+    Extension(schema): Extension<GraphSchema>,
+    owner: OwnerScope,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request.data(owner)).await)
+}
+
+/// A ranked chunk from vector search
+#[derive(SimpleObject)]
+pub struct Chunk {
+    pub chunk_id: Uuid,
+    pub content: String,
+    pub source_kind: String,
+    pub source_type: String,
+    pub file_path: Option<String>,
+    pub repo_name: Option<String>,
+    pub language: Option<String>,
+    pub heading_path: Option<String>,
+    pub summary: Option<String>,
+    pub similarity_score: f32,
+    pub raw_score: Option<f32>,
+    pub final_score: f32,
+    pub pinned: bool,
+    /// Only computed when selected
+    pub cross_source_link_count: Option<usize>,
+}
+
+impl From<ChunkResult> for Chunk {
+    fn from(chunk: ChunkResult) -> Self {
+        Self {
+            chunk_id: chunk.chunk_id,
+            content: chunk.content,
+            source_kind: chunk.source_kind,
+            source_type: chunk.source_type,
+            file_path: chunk.file_path,
+            repo_name: chunk.repo_name,
+            language: chunk.language,
+            heading_path: chunk.heading_path,
+            summary: chunk.summary,
+            similarity_score: chunk.similarity_score,
+            raw_score: chunk.raw_score,
+            final_score: chunk.final_score,
+            pinned: chunk.pinned,
+            cross_source_link_count: chunk.cross_source_link_count,
+        }
+    }
+}
+
+/// An entity reached by graph expansion
+#[derive(SimpleObject)]
+pub struct Entity {
+    pub id: Uuid,
+    pub entity_type: String,
+    pub name: String,
+    pub source: String,
+    pub properties: GqlJson<serde_json::Value>,
+    pub relevance: f32,
+}
+
+impl From<EntityResult> for Entity {
+    fn from(entity: EntityResult) -> Self {
+        Self {
+            id: entity.id,
+            entity_type: entity.entity_type,
+            name: entity.name,
+            source: entity.source,
+            properties: GqlJson(entity.properties),
+            relevance: entity.relevance,
+        }
+    }
+}
+
+/// A relationship between two entities
+#[derive(SimpleObject)]
+pub struct Relationship {
+    pub from_id: Uuid,
+    pub to_id: Uuid,
+    pub from_name: String,
+    pub to_name: String,
+    pub relationship_type: String,
+    pub confidence: f32,
+    pub is_cross_source: bool,
+}
+
+impl From<RelationshipResult> for Relationship {
+    fn from(rel: RelationshipResult) -> Self {
+        Self {
+            from_id: rel.from_id,
+            to_id: rel.to_id,
+            from_name: rel.from_name,
+            to_name: rel.to_name,
+            relationship_type: rel.relationship_type,
+            confidence: rel.confidence,
+            is_cross_source: rel.is_cross_source,
+        }
+    }
+}
+
+/// A cross-source link (docs explaining code, etc.)
+#[derive(SimpleObject)]
+pub struct SemanticLink {
+    pub from_chunk_id: Uuid,
+    pub to_chunk_id: Uuid,
+    pub relationship_type: String,
+    pub confidence: f32,
+    pub similarity_score: Option<f32>,
+    /// "outgoing" when the stored edge points from `from_chunk_id` to `to_chunk_id`
+    pub direction: String,
+}
+
+impl From<SemanticLinkModel> for SemanticLink {
+    fn from(link: SemanticLinkModel) -> Self {
+        Self {
+            from_chunk_id: link.from_chunk_id,
+            to_chunk_id: link.to_chunk_id,
+            relationship_type: link.relationship_type,
+            confidence: link.confidence,
+            similarity_score: link.similarity_score,
+            direction: link.direction.as_str().to_string(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct SearchMetadata {
+    pub query: String,
+    pub vector_results_count: usize,
+    pub graph_hops_performed: usize,
+    pub cross_source_links_count: usize,
+    pub execution_time_ms: u64,
+    pub cached: bool,
+    /// RFC 3339 time the cached response was computed
+    pub cached_at: Option<String>,
+}
+
+impl From<SearchMetadataModel> for SearchMetadata {
+    fn from(metadata: SearchMetadataModel) -> Self {
+        Self {
+            query: metadata.query,
+            vector_results_count: metadata.vector_results_count,
+            graph_hops_performed: metadata.graph_hops_performed,
+            cross_source_links_count: metadata.cross_source_links_count,
+            execution_time_ms: metadata.execution_time_ms,
+            cached: metadata.cached,
+            cached_at: metadata.cached_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct HybridSearchResult {
+    pub chunks: Vec<Chunk>,
+    pub related_entities: Vec<Entity>,
+    pub relationships: Vec<Relationship>,
+    pub cross_source_links: Vec<SemanticLink>,
+    pub metadata: SearchMetadata,
+}
+
+/// An entity as returned by `GET /api/graph/entities/:id`
+#[derive(SimpleObject)]
+pub struct EntitySummary {
+    pub id: String,
+    pub name: String,
+    pub entity_type: String,
+}
+
+/// A neighboring entity and the relationship leading to it
+#[derive(SimpleObject)]
+pub struct Neighbor {
+    pub id: String,
+    pub name: String,
+    pub entity_type: String,
+    pub relationship: String,
+    pub confidence: f32,
+}
+
+/// Optional hybrid search settings (defaults match `POST /api/search`)
+#[derive(InputObject, Default)]
+pub struct SearchInput {
+    pub limit: Option<usize>,
+    pub graph_hops: Option<usize>,
+    pub source_kind: Option<String>,
+    pub source_types: Option<Vec<String>>,
+    pub repo_filter: Option<String>,
+    pub path_glob: Option<String>,
+    pub languages: Option<Vec<String>>,
+    pub min_similarity: Option<f32>,
+    pub embedding_model: Option<String>,
+    pub pin_exact_matches: Option<bool>,
+    pub cross_source_only: Option<bool>,
+    pub rerank_exact: Option<bool>,
+    pub max_cross_source_links: Option<usize>,
+    pub cross_source_hops: Option<usize>,
+    pub cross_source_min_confidence: Option<f32>,
+    pub cross_source_direction: Option<String>,
+}
+
+impl SearchInput {
+    fn into_options(self, owner_id: Option<String>) -> SearchOptions {
+        let defaults = SearchOptions::default();
+        SearchOptions {
+            limit: self.limit.unwrap_or(defaults.limit),
+            graph_hops: self.graph_hops.unwrap_or(defaults.graph_hops),
+            source_kind: self.source_kind.unwrap_or(defaults.source_kind),
+            source_types: self.source_types,
+            repo_filter: self.repo_filter,
+            path_glob: self.path_glob,
+            languages: self.languages,
+            owner_id,
+            min_similarity: self.min_similarity,
+            embedding_model: self.embedding_model,
+            pin_exact_matches: self.pin_exact_matches.unwrap_or(defaults.pin_exact_matches),
+            cross_source_only: self.cross_source_only.unwrap_or(defaults.cross_source_only),
+            rerank_exact: self.rerank_exact.unwrap_or(defaults.rerank_exact),
+            max_cross_source_links: self.max_cross_source_links.unwrap_or(defaults.max_cross_source_links),
+            cross_source_hops: self.cross_source_hops.unwrap_or(defaults.cross_source_hops),
+            cross_source_min_confidence: self.cross_source_min_confidence.unwrap_or(defaults.cross_source_min_confidence),
+            cross_source_direction: self.cross_source_direction.unwrap_or(defaults.cross_source_direction),
+            ..defaults
+        }
+    }
+}
+
+pub struct QueryRoot;
+
+fn app_state<'a>(ctx: &Context<'a>) -> &'a Arc<AppState> {
+    ctx.data_unchecked::<Arc<AppState>>()
+}
+
+fn owner_id<'a>(ctx: &Context<'a>) -> Option<&'a str> {
+    ctx.data_opt::<OwnerScope>().and_then(|owner| owner.0.as_deref())
+}
+
+#[Object]
+impl QueryRoot {
+    /// Vector search plus graph expansion, as `POST /api/search`
+    async fn hybrid_search(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        options: Option<SearchInput>,
+    ) -> async_graphql::Result<HybridSearchResult> {
+        let state = app_state(ctx);
+        let mut options = options.unwrap_or_default().into_options(owner_id(ctx).map(str::to_string));
+
+        // Skip graph work the caller didn't ask for
+        let selection = ctx.look_ahead();
+        if !selection.field("relatedEntities").exists() && !selection.field("relationships").exists() {
+            options.graph_hops = 0;
+        }
+        options.include_cross_source = Some(selection.field("crossSourceLinks").exists());
+        options.include_link_counts = selection.field("chunks").field("crossSourceLinkCount").exists();
+
+        let engine = HybridQueryEngine::new(
+            state.config.clone(),
+            state.graph.clone(),
+            state.embedding_client.clone(),
+        ).with_search_cache(state.search_cache.clone());
+        let response = engine.search(HybridSearchRequest { query, options }).await?;
+
+        Ok(HybridSearchResult {
+            chunks: response.chunks.into_iter().map(Chunk::from).collect(),
+            related_entities: response.related_entities.into_iter().map(Entity::from).collect(),
+            relationships: response.relationships.into_iter().map(Relationship::from).collect(),
+            cross_source_links: response.cross_source_links.into_iter().map(SemanticLink::from).collect(),
+            metadata: response.metadata.into(),
+        })
+    }
+
+    /// Look up an entity by id (null when it doesn't exist)
+    async fn entity(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<Option<EntitySummary>> {
+        let graph = app_state(ctx).graph.as_ref()
+            .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+
+        let entity = graph.get_entity(&EntityId::parse(&id)?, owner_id(ctx)).await?;
+        Ok(entity.map(|(id, name, entity_type)| EntitySummary { id, name, entity_type }))
+    }
+
+    /// Entities within `hops` of an entity (at most `MAX_GRAPH_HOPS`)
+    async fn neighbors(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        #[graphql(default = 1)] hops: usize,
+    ) -> async_graphql::Result<Vec<Neighbor>> {
+        let state = app_state(ctx);
+        if hops > state.config.max_graph_hops {
+            return Err(GraphError::InvalidRequest(format!(
+                "hops must be at most {} (MAX_GRAPH_HOPS), got {}",
+                state.config.max_graph_hops, hops
+            )).into());
+        }
+        let graph = state.graph.as_ref()
+            .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+
+        let hops = hops.max(1);
+        let neighbors = graph
+            .get_neighbors(&EntityId::parse(&id)?, None, "both", hops, &NeighborQuery::default(), owner_id(ctx))
+            .await?;
+
+        Ok(neighbors
+            .into_iter()
+            .map(|neighbor| Neighbor {
+                id: neighbor.id.to_string(),
+                name: neighbor.name,
+                entity_type: neighbor.entity_type,
+                relationship: neighbor.relationship,
+                confidence: neighbor.confidence,
+            })
+            .collect())
+    }
+
+    /// Same payload as `GET /api/graph/statistics`
+    async fn statistics(&self, ctx: &Context<'_>) -> async_graphql::Result<GqlJson<serde_json::Value>> {
+        Ok(GqlJson(collect_statistics(app_state(ctx)).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn schema() -> GraphSchema {
+        build_schema(Arc::new(AppState::for_tests(Config::test_default())))
+    }
+
+    #[tokio::test]
+    async fn test_statistics_query_shape() {
+        let response = schema().execute("{ statistics }").await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["statistics"]["service"], "relation-graph");
+        assert_eq!(data["statistics"]["vector_store"], "neo4j-native");
+    }
+
+    #[tokio::test]
+    async fn test_graph_queries_report_unavailable_graph() {
+        let request = async_graphql::Request::new(r#"{ neighbors(id: "abc", hops: 2) { id name } }"#)
+            .data(OwnerScope(Some("tenant-a".to_string())));
+        let response = schema().execute(request).await;
+
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("Graph store not available"));
+        assert_eq!(response.errors[0].path.len(), 1);
+
+        let response = schema().execute(r#"{ neighbors(id: "abc", hops: 50) { id } }"#).await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("at most 2 (MAX_GRAPH_HOPS), got 50"), "{:?}", response.errors);
+    }
+
+    #[tokio::test]
+    async fn test_schema_exposes_rest_result_types() {
+        let sdl = schema().sdl();
+        for field in [
+            "hybridSearch(query: String!, options: SearchInput): HybridSearchResult!",
+            "entity(id: String!): EntitySummary",
+            "neighbors(id: String!, hops: Int! = 1): [Neighbor!]!",
+            "relatedEntities: [Entity!]!",
+        ] {
+            assert!(sdl.contains(field), "missing `{}` in schema:\n{}", field, sdl);
+        }
+    }
+}
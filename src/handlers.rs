@@ -1,57 +1,314 @@
 //! HTTP handlers module
 
 use axum::{
-    extract::{Path, State},
+    async_trait,
+    body::Body,
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{FromRequest, FromRequestParts, Path, Query, Request, State},
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::field::Empty;
+use tracing::Span;
+use uuid::Uuid;
 
 use crate::config::Config;
-use crate::error::GraphError;
-use crate::graph_db::Neo4jClient;
+use crate::error::{GraphError, GraphResult};
+use crate::extractors::confidence::extraction_confidences;
+use crate::graph_db::neo4j_client::TreeEdge;
+use crate::graph_db::{GraphStore, Neo4jClient};
 use crate::models::*;
-use crate::services::{ChunkProcessor, HybridQueryEngine};
+use crate::services::{ChunkProcessor, ChunkRepository, EmbeddingClient, HybridQueryEngine};
+use crate::services::chunk_processor::check_strict;
+use crate::services::cross_source_linker::CrossSourceLinker;
+use crate::services::entity_resolver::EntityResolver;
+use crate::services::{graph_export, graph_import};
+use crate::services::circuit_breaker::BreakerState;
+use crate::services::ingest_jobs::{IngestJob, IngestJobAccepted, IngestJobRegistry};
+use crate::services::ingest_stream::ingest_ndjson;
+use crate::services::search_cache::SearchCache;
 
 /// Application state shared across handlers
 pub struct AppState {
     pub config: Config,
     pub neo4j: Option<Arc<Neo4jClient>>,
+    /// Entity and traversal operations (Neo4j or the in-memory backend)
+    pub graph: Option<Arc<dyn GraphStore>>,
+    pub embedding_client: Arc<EmbeddingClient>,
     pub db_pool: PgPool,
+    pub ingest_jobs: Arc<IngestJobRegistry>,
+    /// Hybrid search responses (`SEARCH_CACHE_SIZE`; `None` when disabled)
+    pub search_cache: Option<Arc<SearchCache>>,
+    /// Canonical entity merging (`ENTITY_RESOLUTION`; `None` when disabled)
+    pub entity_resolver: Option<Arc<EntityResolver>>,
+    /// Chunk rows and link evidence in Postgres (`None` in tests without a database)
+    pub chunk_repository: Option<Arc<ChunkRepository>>,
+}
+
+impl AppState {
+    /// Drop cached search responses after a write to the graph
+    pub fn invalidate_search_cache(&self) {
+        if let Some(cache) = &self.search_cache {
+            cache.invalidate();
+        }
+    }
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State without Neo4j; the Postgres pool connects lazily and is never used
+    pub fn for_tests(config: Config) -> Self {
+        Self {
+            embedding_client: Arc::new(EmbeddingClient::from_config(&config)),
+            db_pool: sqlx::postgres::PgPoolOptions::new()
+                .connect_lazy(&config.database_url)
+                .expect("valid test database URL"),
+            neo4j: None,
+            graph: None,
+            ingest_jobs: Arc::new(IngestJobRegistry::new()),
+            search_cache: None,
+            entity_resolver: None,
+            chunk_repository: None,
+            config,
+        }
+    }
+    
+    /// Test state backed by an empty in-memory graph (and in-memory entity resolution)
+    pub fn with_memory_graph(config: Config) -> Self {
+        Self {
+            graph: Some(Arc::new(crate::graph_db::InMemoryGraph::new())),
+            entity_resolver: config.entity_resolution.then(|| Arc::new(EntityResolver::in_memory())),
+            ..Self::for_tests(config)
+        }
+    }
+}
+
+/// Chunks removed per transaction by `DELETE /api/graph/repos/:repo_name`
+const REPO_DELETE_BATCH_SIZE: usize = 500;
+
+/// Largest document `POST /api/graph/import` reads
+const MAX_IMPORT_BYTES: usize = 512 * 1024 * 1024;
+
+/// Frames buffered between the search task and a slow WebSocket client
+const STREAM_FRAME_BUFFER: usize = 16;
+
+/// Most matches returned by `GET /api/graph/chunks/:id/cross-source`
+const MAX_CROSS_SOURCE_MATCHES: usize = 100;
+
+/// Most descendants returned by `GET /api/graph/entities/:id/tree`
+const MAX_TREE_NODES: usize = 1000;
+
+/// Relationship types an entity tree follows when `types` is omitted
+const DEFAULT_TREE_TYPES: [RelationshipType; 2] = [RelationshipType::Contains, RelationshipType::ParentOf];
+
+/// Header carrying the caller's tenant, set by the API gateway
+pub const OWNER_ID_HEADER: &str = "x-owner-id";
+
+/// Tenant scope from the `X-Owner-Id` header (unscoped when absent)
+#[derive(Debug, Default)]
+pub struct OwnerScope(pub Option<String>);
+
+impl OwnerScope {
+    /// Combine the header with an `owner_id` from the request body
+    /// 
+    /// The header wins; a body value that disagrees with it is rejected
+    /// rather than silently widening or switching the scope.
+    pub fn resolve(&self, body_owner: Option<String>) -> GraphResult<Option<String>> {
+        match (&self.0, body_owner) {
+            (Some(header), Some(body)) if *header != body => Err(GraphError::InvalidRequest(
+                format!("owner_id '{}' does not match X-Owner-Id header", body),
+            )),
+            (Some(header), _) => Ok(Some(header.clone())),
+            (None, body) => Ok(body),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for OwnerScope {
+    type Rejection = GraphError;
+    
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let owner = parts.headers
+            .get(OWNER_ID_HEADER)
+            .map(|value| {
+                value.to_str()
+                    .map(|s| s.trim().to_string())
+                    .map_err(|_| GraphError::InvalidRequest("X-Owner-Id header is not valid UTF-8".to_string()))
+            })
+            .transpose()?
+            .filter(|s| !s.is_empty());
+        
+        Ok(Self(owner))
+    }
+}
+
+/// JSON request body whose size rejection is reported like other errors
+/// 
+/// Axum answers a body over `MAX_BODY_BYTES` with a plain-text `413`; this
+/// returns `GraphError::PayloadTooLarge` instead. Other rejections (bad JSON,
+/// wrong content type) are passed through unchanged.
+pub struct JsonBody<T>(pub T);
+
+#[async_trait]
+impl<T: serde::de::DeserializeOwned, S: Send + Sync> FromRequest<S> for JsonBody<T> {
+    type Rejection = Response;
+    
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(request, state).await {
+            Ok(Json(value)) => Ok(Self(value)),
+            Err(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => Err(GraphError::PayloadTooLarge(
+                "request body exceeds MAX_BODY_BYTES".to_string(),
+            ).into_response()),
+            Err(rejection) => Err(rejection.into_response()),
+        }
+    }
+}
+
+/// Reject ingest requests with more than `MAX_CHUNKS_PER_REQUEST` chunks
+fn check_chunk_count(request: &IngestChunksRequest, config: &Config) -> GraphResult<()> {
+    if request.chunks.len() > config.max_chunks_per_request {
+        return Err(GraphError::PayloadTooLarge(format!(
+            "{} chunks in one request; MAX_CHUNKS_PER_REQUEST is {} (split the batch or use /api/graph/chunks/stream)",
+            request.chunks.len(),
+            config.max_chunks_per_request
+        )));
+    }
+    Ok(())
+}
+
+/// Outcome of pinging one dependency for `/health`
+struct Probe {
+    up: bool,
+    latency: Duration,
+    error: Option<String>,
+}
+
+impl Probe {
+    /// Time `check`, counting it as down if it fails or outlasts `timeout`
+    async fn run(timeout: Duration, check: impl Future<Output = Result<(), String>>) -> Self {
+        let started = Instant::now();
+        let result = tokio::time::timeout(timeout, check)
+            .await
+            .unwrap_or_else(|_| Err(format!("no response within {}ms", timeout.as_millis())));
+        Self { up: result.is_ok(), latency: started.elapsed(), error: result.err() }
+    }
+    
+    fn down(error: &str) -> Self {
+        Self { up: false, latency: Duration::ZERO, error: Some(error.to_string()) }
+    }
+    
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "status": if self.up { "up" } else { "down" },
+            "latency_ms": self.latency.as_millis() as u64,
+            "error": self.error,
+        })
+    }
 }
 
 /// Health check endpoint
+/// 
+/// Pings Neo4j, Postgres, and the embedding service concurrently. All three
+/// are critical, so the response is `503` when any of them is down. Neo4j is
+/// skipped with the in-memory backend.
 pub async fn health_check(
     State(state): State<Arc<AppState>>,
-) -> Json<serde_json::Value> {
-    let neo4j_status = state.neo4j.is_some();
+) -> (StatusCode, Json<serde_json::Value>) {
+    let timeout = Duration::from_millis(state.config.health_check_timeout_ms);
+    let neo4j = async {
+        match &state.neo4j {
+            Some(neo4j) => Some(Probe::run(timeout, async { neo4j.ping().await.map_err(|e| e.to_string()) }).await),
+            None if state.config.graph_backend == "memory" => None,
+            None => Some(Probe::down("not connected")),
+        }
+    };
+    let postgres = Probe::run(timeout, async {
+        sqlx::query("SELECT 1").execute(&state.db_pool).await.map(|_| ()).map_err(|e| e.to_string())
+    });
+    let embedding = Probe::run(timeout, async {
+        if state.embedding_client.health_check().await {
+            Ok(())
+        } else {
+            Err("health endpoint unreachable or not OK".to_string())
+        }
+    });
+    let (neo4j, postgres, embedding) = tokio::join!(neo4j, postgres, embedding);
     
-    Json(serde_json::json!({
-        "status": "healthy",
+    let embedding_breaker = state.embedding_client.breaker_state();
+    let critical_up = neo4j.as_ref().is_none_or(|probe| probe.up) && postgres.up && embedding.up;
+    let (code, status) = if !critical_up {
+        (StatusCode::SERVICE_UNAVAILABLE, "unhealthy")
+    } else if embedding_breaker == BreakerState::Open {
+        (StatusCode::OK, "degraded")
+    } else {
+        (StatusCode::OK, "healthy")
+    };
+    let neo4j_up = neo4j.as_ref().is_some_and(|probe| probe.up);
+    let neo4j_version = state.neo4j.as_ref().and_then(|neo4j| neo4j.server_version());
+    // Unknown versions were let through at startup
+    let native_vectors = state.neo4j.as_ref().map(|neo4j| neo4j.native_vector_indexes().unwrap_or(true));
+    
+    (code, Json(serde_json::json!({
+        "status": status,
         "service": "relation-graph",
         "version": env!("CARGO_PKG_VERSION"),
         "components": {
-            "neo4j": neo4j_status,
-            "postgres": true,
-            "vector_store": "neo4j-native"  // Vector storage now in Neo4j
+            "neo4j": neo4j.map_or_else(|| serde_json::json!({ "status": "disabled" }), |probe| probe.to_json()),
+            "graph_backend": state.graph.as_ref().map(|graph| graph.backend()),
+            "postgres": postgres.to_json(),
+            "embedding": embedding.to_json(),
+            "vector_store": "neo4j-native",  // Vector storage now in Neo4j
+            "neo4j_version": neo4j_version,
+            "embedding_circuit": embedding_breaker.as_str()
         },
         "features": {
             "hybrid_search": true,
             "cross_source_linking": true,
             "code_entity_extraction": true,
             "document_entity_extraction": true,
-            "native_vector_search": neo4j_status  // Neo4j 5.21+ vector indexes
+            "native_vector_search": neo4j_up && native_vectors == Some(true)  // Neo4j 5.21+ vector indexes
         }
+    })))
+}
+
+/// Service metrics
+pub async fn metrics(
+    State(state): State<Arc<AppState>>,
+) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "embedding": {
+            "circuit_state": state.embedding_client.breaker_state(),
+            "consecutive_failures": state.embedding_client.consecutive_failures()
+        },
+        "neo4j_pool": state.neo4j.as_ref().map(|neo4j| neo4j.pool_metrics())
     }))
 }
 
 /// Create an entity
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref()))]
 pub async fn create_entity(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<CreateEntityRequest>,
+    owner: OwnerScope,
+    JsonBody(mut request): JsonBody<CreateEntityRequest>,
 ) -> Result<Json<CreateEntityResponse>, GraphError> {
-    let entity_type = EntityType::from_str(&request.entity_type)
-        .ok_or_else(|| GraphError::InvalidEntityType(request.entity_type.clone()))?;
+    let body_owner = request.properties
+        .get("owner_id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    if let Some(owner_id) = owner.resolve(body_owner)? {
+        request.properties.insert("owner_id".to_string(), serde_json::json!(owner_id));
+    }
+    
+    let entity_type = EntityType::parse(&request.entity_type)?;
     
     let source = DataSource::from_str(&request.source)
         .unwrap_or(DataSource::LocalFile);
@@ -65,121 +322,928 @@ pub async fn create_entity(
     );
     
     let mut neo4j_node_id = None;
+    let mut canonical = None;
     
-    if let Some(neo4j) = &state.neo4j {
-        neo4j_node_id = Some(neo4j.upsert_entity_node(&entity).await?);
+    if let Some(graph) = &state.graph {
+        neo4j_node_id = graph.upsert_entity_node(&entity).await?.element_id;
+        // The entity is stored either way; resolution failures only leave it unmerged
+        if let Some(resolver) = &state.entity_resolver {
+            match resolver.resolve_and_link(&entity, graph.as_ref()).await {
+                Ok(resolved) => canonical = resolved,
+                Err(e) => tracing::warn!("Entity resolution failed for {}: {}", entity.id, e),
+            }
+        }
+        state.invalidate_search_cache();
     }
     
     Ok(Json(CreateEntityResponse {
         entity_id: entity.id,
         neo4j_node_id,
-        canonical_id: None,
-        resolved: false,
+        canonical_id: canonical.as_ref().map(|c| c.id),
+        // Merged with at least one entity created before it
+        resolved: canonical.is_some_and(|c| c.source_count() > 1),
     }))
 }
 
+/// List entities filtered by type and source
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), entities = Empty))]
+pub async fn list_entities(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<EntityQuery>,
+    owner: OwnerScope,
+) -> Result<Json<serde_json::Value>, GraphError> {
+    let entity_type = filter.entity_type.as_deref().map(EntityType::parse).transpose()?;
+    let graph = state.graph.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+    
+    let entities = graph
+        .find_entities(entity_type, filter.source.as_deref(), filter.limit, owner.0.as_deref())
+        .await?;
+    Span::current().record("entities", entities.len());
+    
+    let entities: Vec<_> = entities
+        .into_iter()
+        .map(|(id, name, entity_type)| serde_json::json!({
+            "id": id,
+            "name": name,
+            "entity_type": entity_type,
+        }))
+        .collect();
+    Ok(Json(serde_json::json!({
+        "count": entities.len(),
+        "entities": entities,
+    })))
+}
+
 /// Get an entity by ID
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), entity_id = %id))]
 pub async fn get_entity(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
+    Path(id): Path<EntityId>,
+    owner: OwnerScope,
 ) -> Result<Json<serde_json::Value>, GraphError> {
-    // Try to get from Neo4j
-    if let Some(neo4j) = &state.neo4j {
-        let entities = neo4j.find_entities(None, None, 1).await?;
-        for (entity_id, name, entity_type) in entities {
-            if entity_id == id {
-                return Ok(Json(serde_json::json!({
-                    "id": entity_id,
-                    "name": name,
-                    "entity_type": entity_type
-                })));
-            }
+    if let Some(graph) = &state.graph {
+        if let Some((entity_id, name, entity_type)) = graph.get_entity(&id, owner.0.as_deref()).await? {
+            return Ok(Json(serde_json::json!({
+                "id": entity_id,
+                "name": name,
+                "entity_type": entity_type
+            })));
         }
     }
     
-    Err(GraphError::EntityNotFound(id))
+    Err(GraphError::EntityNotFound(id.to_string()))
+}
+
+/// Update an entity's name and properties
+/// 
+/// Properties in the body are merged into the stored ones (`null` removes a
+/// key); properties left out are kept.
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), entity_id = %id))]
+pub async fn update_entity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<EntityId>,
+    owner: OwnerScope,
+    JsonBody(request): JsonBody<UpdateEntityRequest>,
+) -> Result<Json<serde_json::Value>, GraphError> {
+    request.validate()?;
+    let graph = state.graph.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+    
+    let Some((entity_id, name, entity_type, properties)) = graph
+        .update_entity_properties(&id, request.name.as_deref(), &request.properties, owner.0.as_deref())
+        .await?
+    else {
+        return Err(GraphError::EntityNotFound(id.to_string()));
+    };
+    state.invalidate_search_cache();
+    
+    Ok(Json(serde_json::json!({
+        "id": entity_id,
+        "name": name,
+        "entity_type": entity_type,
+        "properties": properties
+    })))
 }
 
 /// Get neighbors of an entity
+#[tracing::instrument(
+    skip_all,
+    fields(owner_id = owner.0.as_deref(), entity_id = %id, neighbors = Empty)
+)]
 pub async fn get_neighbors(
     State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
+    Path(id): Path<EntityId>,
+    Query(options): Query<NeighborQuery>,
+    owner: OwnerScope,
 ) -> Result<Json<serde_json::Value>, GraphError> {
-    let neo4j = state.neo4j.as_ref()
-        .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available".to_string()))?;
+    let graph = state.graph.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
     
-    let neighbors = neo4j.get_neighbors(&id, None, "both", 1).await?;
+    validate_confidence("min_confidence", options.min_confidence)?;
+    validate_neighbor_limit(options.limit)?;
+    let neighbors = graph.get_neighbors(&id, None, "both", 1, &options, owner.0.as_deref()).await?;
+    Span::current().record("neighbors", neighbors.len());
     
     Ok(Json(serde_json::json!({
         "entity_id": id,
-        "neighbors": neighbors.iter().map(|(id, name, rel, conf)| {
+        "neighbors": neighbors.iter().map(|neighbor| {
             serde_json::json!({
-                "id": id,
-                "name": name,
-                "relationship": rel,
-                "confidence": conf
+                "id": neighbor.id,
+                "name": neighbor.name,
+                "entity_type": neighbor.entity_type,
+                "relationship": neighbor.relationship,
+                "confidence": neighbor.confidence
             })
         }).collect::<Vec<_>>()
     })))
 }
 
+/// Entities below an entity as a nested tree
+/// 
+/// Follows outgoing relationships of the requested types (`CONTAINS` and
+/// `PARENT_OF` by default) up to `max_depth`, capped at `MAX_TREE_DEPTH`. A
+/// node reachable along several paths appears once, under the shallowest
+/// parent, so cycles end where they loop back.
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), entity_id = %id, nodes = Empty))]
+pub async fn get_entity_tree(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<EntityId>,
+    Query(options): Query<EntityTreeQuery>,
+    owner: OwnerScope,
+) -> Result<Json<EntityTreeResponse>, GraphError> {
+    let graph = state.graph.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+    
+    let relationship_types = match options.types.as_deref() {
+        Some(types) => {
+            let types: Vec<String> = types.split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect();
+            if types.is_empty() {
+                return Err(GraphError::InvalidRequest("types must name at least one relationship type".to_string()));
+            }
+            RelationshipType::parse_all(&types)?
+        }
+        None => DEFAULT_TREE_TYPES.to_vec(),
+    };
+    let max_depth = match options.max_depth {
+        Some(0) => return Err(GraphError::InvalidRequest("max_depth must be at least 1".to_string())),
+        Some(depth) => depth.min(state.config.max_tree_depth),
+        None => state.config.max_tree_depth,
+    }.max(1);
+    
+    let (root_id, name, entity_type) = graph.get_entity(&id, owner.0.as_deref())
+        .await?
+        .ok_or_else(|| GraphError::EntityNotFound(id.to_string()))?;
+    let root = EntityTreeNode {
+        id: root_id,
+        name,
+        entity_type,
+        relationship: None,
+        confidence: None,
+        children: Vec::new(),
+    };
+    
+    // One extra row tells whether the cap cut the tree short
+    let mut edges = graph.tree_edges(&id, &relationship_types, max_depth, MAX_TREE_NODES + 1, owner.0.as_deref()).await?;
+    let truncated = edges.len() > MAX_TREE_NODES;
+    edges.truncate(MAX_TREE_NODES);
+    
+    let (root, node_count) = assemble_tree(root, edges);
+    Span::current().record("nodes", node_count);
+    
+    Ok(Json(EntityTreeResponse {
+        root,
+        max_depth,
+        node_count,
+        truncated,
+    }))
+}
+
+/// Nest tree edges (shallowest first) under `root`; returns the tree and its node count
+/// 
+/// Edges to a node already placed are dropped, which breaks cycles and keeps
+/// each node under its first parent.
+fn assemble_tree(root: EntityTreeNode, edges: Vec<TreeEdge>) -> (EntityTreeNode, usize) {
+    let mut placed = HashSet::from([root.id.clone()]);
+    let mut children: HashMap<String, Vec<EntityTreeNode>> = HashMap::new();
+    for edge in edges {
+        let id = edge.child.id.to_string();
+        if !placed.insert(id.clone()) {
+            continue;
+        }
+        children.entry(edge.parent_id).or_default().push(EntityTreeNode {
+            id,
+            name: edge.child.name,
+            entity_type: edge.child.entity_type,
+            relationship: Some(edge.child.relationship),
+            confidence: Some(edge.child.confidence),
+            children: Vec::new(),
+        });
+    }
+    
+    fn attach(
+        mut node: EntityTreeNode,
+        children: &mut HashMap<String, Vec<EntityTreeNode>>,
+        count: &mut usize,
+    ) -> EntityTreeNode {
+        *count += 1;
+        node.children = children.remove(&node.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| attach(child, children, count))
+            .collect();
+        node
+    }
+    
+    let mut node_count = 0;
+    let root = attach(root, &mut children, &mut node_count);
+    (root, node_count)
+}
+
+/// Create a relationship between two existing entities
+/// 
+/// Both ends are matched on entity `id` (the `entity_id` returned when the
+/// entity was created), never on name. The relationship type must be one of
+/// `RelationshipType`; a missing confidence falls back to
+/// `DEFAULT_MANUAL_RELATIONSHIP_CONFIDENCE`.
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), relationship_type = %request.relationship_type))]
+pub async fn create_relationship(
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+    JsonBody(request): JsonBody<CreateRelationshipRequest>,
+) -> Result<Json<CreateRelationshipResponse>, GraphError> {
+    let rel_type = RelationshipType::parse(&request.relationship_type)?;
+    let confidence = request.confidence_or(state.config.default_manual_relationship_confidence)?;
+    let graph = state.graph.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+    
+    // Both ends must exist and be visible to the caller's tenant
+    let (from_id, to_id) = (EntityId(request.from_entity_id), EntityId(request.to_entity_id));
+    for id in [&from_id, &to_id] {
+        if graph.get_entity(id, owner.0.as_deref()).await?.is_none() {
+            return Err(GraphError::EntityNotFound(id.to_string()));
+        }
+    }
+    
+    let relationship = Relationship::new(request.from_entity_id, request.to_entity_id, rel_type.clone(), confidence)
+        .with_properties(request.properties);
+    let properties = (!relationship.properties.is_null()).then(|| relationship.properties.clone());
+    let stored = graph
+        .create_relationship(&from_id, &to_id, rel_type, confidence, ExtractionMethod::Manual, properties)
+        .await?;
+    state.invalidate_search_cache();
+    
+    Ok(Json(CreateRelationshipResponse {
+        relationship_id: stored.id,
+        neo4j_rel_id: stored.element_id,
+    }))
+}
+
+/// List relationships filtered by extraction method, type, or confidence
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), relationships = Empty))]
+pub async fn list_relationships(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<RelationshipQuery>,
+    owner: OwnerScope,
+) -> Result<Json<serde_json::Value>, GraphError> {
+    let neo4j = state.neo4j.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available".to_string()))?;
+    
+    validate_confidence("min_confidence", filter.min_confidence)?;
+    if filter.exclusive && filter.extraction_method.is_none() {
+        return Err(GraphError::InvalidRequest(
+            "exclusive requires extraction_method".to_string(),
+        ));
+    }
+    
+    let relationships = neo4j.list_relationships(&filter, owner.0.as_deref()).await?;
+    Span::current().record("relationships", relationships.len());
+    
+    Ok(Json(serde_json::json!({
+        "count": relationships.len(),
+        "relationships": relationships,
+    })))
+}
+
+/// Run an ad-hoc read-only Cypher query
+/// 
+/// Arbitrary Cypher can't be confined to one owner's nodes, so owner-scoped
+/// callers are refused.
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), rows = Empty, truncated = Empty))]
+pub async fn run_query(
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+    JsonBody(request): JsonBody<CypherQueryRequest>,
+) -> Result<Json<CypherQueryResponse>, GraphError> {
+    if owner.0.is_some() {
+        return Err(GraphError::InvalidRequest(
+            "Cypher queries are not available to owner-scoped callers".to_string(),
+        ));
+    }
+    let neo4j = state.neo4j.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available".to_string()))?;
+    
+    let (rows, truncated) = neo4j.run_read_query(
+        &request.cypher,
+        request.params,
+        state.config.max_query_rows,
+    ).await?;
+    let span = Span::current();
+    span.record("rows", rows.len());
+    span.record("truncated", truncated);
+    
+    Ok(Json(CypherQueryResponse {
+        row_count: rows.len(),
+        rows,
+        truncated,
+    }))
+}
+
+/// Find the shortest path between two entities
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), paths = Empty))]
+pub async fn find_path(
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+    JsonBody(request): JsonBody<PathSearchRequest>,
+) -> Result<Json<PathSearchResponse>, GraphError> {
+    // Interpolated into the variable-length pattern, so never past the configured cap
+    let max_hops = request.max_hops.unwrap_or(state.config.max_graph_hops);
+    if max_hops > state.config.max_graph_hops {
+        return Err(GraphError::InvalidRequest(format!(
+            "max_hops must be at most {} (MAX_GRAPH_HOPS), got {}",
+            state.config.max_graph_hops, max_hops
+        )));
+    }
+    let neo4j = state.neo4j.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available".to_string()))?;
+    
+    let relationship_types = request.relationship_types
+        .as_deref()
+        .map(RelationshipType::parse_all)
+        .transpose()?;
+    
+    let path = neo4j.shortest_path(
+        &EntityId::parse(&request.from_id)?,
+        &EntityId::parse(&request.to_id)?,
+        max_hops,
+        relationship_types.as_deref(),
+        owner.0.as_deref(),
+    ).await?;
+    Span::current().record("paths", path.iter().count());
+    
+    Ok(Json(PathSearchResponse {
+        from_id: request.from_id,
+        to_id: request.to_id,
+        paths: path.into_iter().collect(),
+    }))
+}
+
 /// Ingest chunks from the chunker service
+#[tracing::instrument(
+    skip_all,
+    fields(
+        owner_id = owner.0.as_deref(),
+        chunks = request.chunks.len(),
+        chunks_ingested = Empty,
+        entities_extracted = Empty,
+        relationships_created = Empty,
+    )
+)]
 pub async fn ingest_chunks(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<IngestChunksRequest>,
+    owner: OwnerScope,
+    JsonBody(request): JsonBody<IngestChunksRequest>,
 ) -> Result<Json<IngestChunksResponse>, GraphError> {
+    check_chunk_count(&request, &state.config)?;
+    // A scoped caller may only write chunks for its own tenant
+    for chunk in &request.chunks {
+        owner.resolve(Some(chunk.owner_id.clone()))?;
+    }
+    
     let processor = ChunkProcessor::new(
         state.config.clone(),
-        state.neo4j.clone(),
-    );
+        state.graph.clone(),
+        state.embedding_client.clone(),
+    )
+    .with_search_cache(state.search_cache.clone())
+    .with_chunk_repository(state.chunk_repository.clone());
     
     let response = processor.ingest_chunks(request).await?;
+    let span = Span::current();
+    span.record("chunks_ingested", response.chunks_ingested);
+    span.record("entities_extracted", response.entities_extracted);
+    span.record("relationships_created", response.relationships_created);
     
     Ok(Json(response))
 }
 
-/// Trigger cross-source linking
-pub async fn trigger_cross_source_linking(
+/// Queue chunks for ingestion in the background
+#[tracing::instrument(
+    skip_all,
+    fields(
+        owner_id = owner.0.as_deref(),
+        chunks = request.chunks.len(),
+        job_id = Empty,
+    )
+)]
+pub async fn ingest_chunks_async(
     State(state): State<Arc<AppState>>,
-    Json(_request): Json<CrossSourceLinkRequest>,
-) -> Result<Json<CrossSourceLinkResponse>, GraphError> {
-    // Use Neo4j native vector search for cross-source linking
+    owner: OwnerScope,
+    JsonBody(request): JsonBody<IngestChunksRequest>,
+) -> Result<(StatusCode, Json<IngestJobAccepted>), GraphError> {
+    check_chunk_count(&request, &state.config)?;
+    for chunk in &request.chunks {
+        owner.resolve(Some(chunk.owner_id.clone()))?;
+    }
+    // Slices are processed independently, so strict mode must check the whole request now
+    if request.strict.unwrap_or(false) {
+        check_strict(&request)?;
+    }
+    
+    let processor = ChunkProcessor::new(
+        state.config.clone(),
+        state.graph.clone(),
+        state.embedding_client.clone(),
+    )
+    .with_search_cache(state.search_cache.clone())
+    .with_chunk_repository(state.chunk_repository.clone());
+    
+    let job = state.ingest_jobs.submit(processor, request, state.config.embedding_batch_size);
+    Span::current().record("job_id", tracing::field::display(job.job_id));
+    
+    Ok((StatusCode::ACCEPTED, Json(IngestJobAccepted {
+        job_id: job.job_id,
+        status: job.status,
+        chunks_total: job.chunks_total,
+    })))
+}
+
+/// Ingest newline-delimited chunks, streaming per-batch progress back as NDJSON
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref()))]
+pub async fn ingest_chunks_stream(
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+    Query(query): Query<IngestStreamQuery>,
+    body: Body,
+) -> Result<Response, GraphError> {
+    // A batch is ingested like one request, so it gets the same chunk cap
+    let batch_size = query.batch_size
+        .unwrap_or(state.config.embedding_batch_size)
+        .min(state.config.max_chunks_per_request);
+    if batch_size == 0 {
+        return Err(GraphError::InvalidRequest("batch_size must be at least 1".to_string()));
+    }
+    
+    let processor = ChunkProcessor::new(
+        state.config.clone(),
+        state.graph.clone(),
+        state.embedding_client.clone(),
+    )
+    .with_search_cache(state.search_cache.clone())
+    .with_chunk_repository(state.chunk_repository.clone());
+    
+    // The body is only read as fast as batches are ingested; a client that
+    // disconnects stops the work at its next progress frame
+    let (frames, receiver) = mpsc::channel(STREAM_FRAME_BUFFER);
+    tokio::spawn(async move {
+        let owner = owner.0;
+        ingest_ndjson(&processor, body.into_data_stream(), &query, batch_size, owner.as_deref(), &frames).await;
+    });
+    
+    let lines = futures::stream::unfold(receiver, |mut receiver| async move {
+        let frame = receiver.recv().await?;
+        let mut line = serde_json::to_vec(&frame).unwrap_or_default();
+        line.push(b'\n');
+        Some((Ok::<_, std::convert::Infallible>(line), receiver))
+    });
+    
+    Ok(([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(lines)).into_response())
+}
+
+/// Get the status of a background ingestion job
+/// 
+/// Another owner's job is reported as not found.
+#[tracing::instrument(skip_all, fields(job_id = %id, owner_id = owner.0.as_deref()))]
+pub async fn get_ingest_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    owner: OwnerScope,
+) -> Result<Json<IngestJob>, GraphError> {
+    state.ingest_jobs
+        .get(&id)
+        .filter(|job| owner.0.is_none() || job.owner_id == owner.0)
+        .map(Json)
+        .ok_or_else(|| GraphError::JobNotFound(id.to_string()))
+}
+
+/// List recently ingested or updated chunks
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), chunks = Empty))]
+pub async fn recent_chunks(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RecentChunksQuery>,
+    owner: OwnerScope,
+) -> Result<Json<RecentChunksResponse>, GraphError> {
     let neo4j = state.neo4j.as_ref()
-        .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available for cross-source linking".to_string()))?;
+        .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available".to_string()))?;
     
-    // Get statistics to show the linking capability
-    let stats = neo4j.get_statistics().await?;
+    let (chunks, counts_by_source_type) = neo4j.recent_chunks(
+        params.limit,
+        params.source_type.as_deref(),
+        params.since,
+        owner.0.as_deref(),
+    ).await?;
+    Span::current().record("chunks", chunks.len());
     
-    Ok(Json(CrossSourceLinkResponse {
-        links_created: 0,
-        chunks_processed: stats["node_count"].as_i64().unwrap_or(0) as usize,
-        errors: vec!["Cross-source linking now uses Neo4j native vector search. Use ingest_chunks with create_cross_links=true".to_string()],
+    Ok(Json(RecentChunksResponse {
+        chunks,
+        counts_by_source_type,
     }))
 }
 
+/// A chunk's full stored row and the evidence behind its links
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), chunk_id = %id, evidence = Empty))]
+pub async fn get_chunk(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    owner: OwnerScope,
+) -> Result<Json<ChunkDetailResponse>, GraphError> {
+    let repository = state.chunk_repository.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Chunk storage not available".to_string()))?;
+    
+    let chunk = repository.get_chunk(id).await?
+        .filter(|chunk| owner.0.as_deref().is_none_or(|owner| owner == chunk.owner_id))
+        .ok_or_else(|| GraphError::EntityNotFound(format!("chunk {}", id)))?;
+    let evidence = repository.evidence_for_chunk(id).await?;
+    Span::current().record("evidence", evidence.len());
+    
+    Ok(Json(ChunkDetailResponse { chunk, evidence }))
+}
+
+/// List the chunks a commit contains (needs `COMMIT_NODES`)
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), commit_sha = %sha, chunks = Empty))]
+pub async fn commit_chunks(
+    State(state): State<Arc<AppState>>,
+    Path(sha): Path<String>,
+    owner: OwnerScope,
+) -> Result<Json<CommitChunksResponse>, GraphError> {
+    let graph = state.graph.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+    
+    let commit = graph.commit_chunks(&sha, owner.0.as_deref())
+        .await?
+        .ok_or_else(|| GraphError::EntityNotFound(format!("commit {}", sha)))?;
+    Span::current().record("chunks", commit.chunks.len());
+    Ok(Json(commit))
+}
+
+/// Delete everything ingested for a repo (before a full re-sync)
+#[tracing::instrument(
+    skip_all,
+    fields(
+        owner_id = owner.0.as_deref(),
+        repo_name = %repo_name,
+        chunks_deleted = Empty,
+    )
+)]
+pub async fn delete_repo(
+    State(state): State<Arc<AppState>>,
+    Path(repo_name): Path<String>,
+    owner: OwnerScope,
+) -> Result<Json<DeleteRepoResponse>, GraphError> {
+    let neo4j = state.neo4j.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available".to_string()))?;
+    
+    let (chunks_deleted, entities_deleted, relationships_deleted) = neo4j
+        .delete_repo(&repo_name, owner.0.as_deref(), REPO_DELETE_BATCH_SIZE)
+        .await?;
+    Span::current().record("chunks_deleted", chunks_deleted);
+    state.invalidate_search_cache();
+    
+    tracing::info!(
+        "Deleted repo {}: {} chunks, {} entities, {} relationships",
+        repo_name, chunks_deleted, entities_deleted, relationships_deleted
+    );
+    
+    Ok(Json(DeleteRepoResponse {
+        repo_name,
+        chunks_deleted,
+        entities_deleted,
+        relationships_deleted,
+    }))
+}
+
+/// Embed nodes that were stored without a vector
+#[tracing::instrument(
+    skip_all,
+    fields(
+        owner_id = owner.0.as_deref(),
+        scanned = Empty,
+        embedded = Empty,
+        failed = Empty,
+    )
+)]
+pub async fn reindex(
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+    JsonBody(request): JsonBody<ReindexRequest>,
+) -> Result<Json<ReindexResponse>, GraphError> {
+    let processor = ChunkProcessor::new(
+        state.config.clone(),
+        state.graph.clone(),
+        state.embedding_client.clone(),
+    ).with_search_cache(state.search_cache.clone());
+    
+    let response = processor.reindex(&request, owner.0.as_deref()).await?;
+    let span = Span::current();
+    span.record("scanned", response.scanned);
+    span.record("embedded", response.embedded);
+    span.record("failed", response.failed);
+    
+    tracing::info!(
+        "Reindex: {} scanned, {} embedded, {} failed",
+        response.scanned, response.embedded, response.failed
+    );
+    
+    Ok(Json(response))
+}
+
+/// Report nodes without relationships, and delete them unless `dry_run`
+/// 
+/// Deleting needs an owner scope, so one call can't remove several tenants'
+/// nodes. Deleted chunks are removed from Postgres too.
+#[tracing::instrument(
+    skip_all,
+    fields(
+        owner_id = owner.0.as_deref(),
+        dry_run = params.dry_run,
+        orphans = Empty,
+        deleted = Empty,
+    )
+)]
+pub async fn prune_orphans(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PruneOrphansQuery>,
+    owner: OwnerScope,
+) -> Result<Json<PruneOrphansResponse>, GraphError> {
+    let graph = state.graph.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+    
+    if !params.dry_run && owner.0.is_none() {
+        return Err(GraphError::InvalidRequest("X-Owner-Id is required to delete orphans".to_string()));
+    }
+    
+    let orphans = graph.find_orphans(params.label.as_deref(), params.limit, owner.0.as_deref()).await?;
+    let deleted = if params.dry_run || orphans.is_empty() {
+        0
+    } else {
+        let ids: Vec<String> = orphans.iter().map(|orphan| orphan.id.clone()).collect();
+        let deleted = graph.delete_orphans(&ids, owner.0.as_deref()).await?;
+        state.invalidate_search_cache();
+        
+        if let Some(repository) = &state.chunk_repository {
+            let chunk_ids: Vec<Uuid> = orphans.iter()
+                .filter(|orphan| orphan.label == "CHUNK" && deleted.contains(&orphan.id))
+                .filter_map(|orphan| Uuid::parse_str(&orphan.id).ok())
+                .collect();
+            if !chunk_ids.is_empty() {
+                repository.delete_chunks(&chunk_ids).await?;
+            }
+        }
+        tracing::info!("Pruned {} of {} orphan nodes", deleted.len(), orphans.len());
+        deleted.len()
+    };
+    let span = Span::current();
+    span.record("orphans", orphans.len());
+    span.record("deleted", deleted);
+    
+    Ok(Json(PruneOrphansResponse {
+        dry_run: params.dry_run,
+        orphans,
+        deleted,
+    }))
+}
+
+/// Drop and recreate the embedding vector indexes at a new dimension
+/// 
+/// For switching embedding models; `CREATE VECTOR INDEX ... IF NOT EXISTS`
+/// keeps an index at its old dimension.
+#[tracing::instrument(skip_all, fields(dimension = params.dimension, indexes = Empty))]
+pub async fn reindex_vectors(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReindexVectorsQuery>,
+) -> Result<Json<ReindexVectorsResponse>, GraphError> {
+    params.validate()?;
+    // Query embeddings are checked against VECTOR_DIMENSION, so indexes of
+    // any other size would reject every search
+    if params.dimension != state.config.vector_dimension {
+        return Err(GraphError::InvalidRequest(format!(
+            "dimension {} differs from VECTOR_DIMENSION ({}); set VECTOR_DIMENSION and restart first",
+            params.dimension, state.config.vector_dimension
+        )));
+    }
+    let neo4j = state.neo4j.as_ref()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available".to_string()))?;
+    
+    let response = neo4j.recreate_vector_indexes(params.dimension).await?;
+    state.invalidate_search_cache();
+    Span::current().record("indexes", response.indexes.len());
+    tracing::warn!("Recreated {} vector indexes at {} dimensions", response.indexes.len(), params.dimension);
+    for failure in &response.failed {
+        tracing::error!(
+            "Vector index '{}' on {} not recreated (dropped: {}): {}",
+            failure.name, failure.label, failure.dropped, failure.error
+        );
+    }
+    
+    Ok(Json(response))
+}
+
+/// Chunks of another source kind similar to this one, without creating links
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), chunk_id = %id, matches = Empty))]
+pub async fn cross_source_matches(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<CrossSourceMatchQuery>,
+    owner: OwnerScope,
+) -> Result<Json<CrossSourceMatchResponse>, GraphError> {
+    if params.limit == 0 || params.limit > MAX_CROSS_SOURCE_MATCHES {
+        return Err(GraphError::InvalidRequest(format!(
+            "limit must be between 1 and {}, got {}",
+            MAX_CROSS_SOURCE_MATCHES, params.limit
+        )));
+    }
+    if let Some(min_similarity) = params.min_similarity {
+        validate_confidence("min_similarity", min_similarity)?;
+    }
+    
+    let linker = CrossSourceLinker::new(state.config.clone(), state.graph.clone());
+    let matches = linker
+        .find_matches(id, &params.target_kind, params.limit, params.min_similarity, owner.0.as_deref())
+        .await?;
+    Span::current().record("matches", matches.len());
+    
+    Ok(Json(CrossSourceMatchResponse {
+        chunk_id: id,
+        target_kind: params.target_kind,
+        matches: matches.into_iter().map(|m| CrossSourceMatchResult {
+            chunk_id: m.target_id.as_uuid(),
+            extraction_methods: m.extraction_methods(),
+            content: m.target_content,
+            source_type: m.target_source_type,
+            file_path: m.target_file_path,
+            similarity_score: m.similarity_score,
+            confidence: m.confidence,
+        }).collect(),
+    }))
+}
+
+/// Trigger cross-source linking
+#[tracing::instrument(
+    skip_all,
+    fields(
+        owner_id = owner.0.as_deref(),
+        chunks_processed = Empty,
+        links_created = Empty,
+    )
+)]
+pub async fn trigger_cross_source_linking(
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+    JsonBody(request): JsonBody<CrossSourceLinkRequest>,
+) -> Result<Json<CrossSourceLinkResponse>, GraphError> {
+    let linker = CrossSourceLinker::new(state.config.clone(), state.graph.clone())
+        .with_chunk_repository(state.chunk_repository.clone());
+    let response = linker.relink(&request, owner.0.as_deref()).await?;
+    if response.links_created > 0 {
+        state.invalidate_search_cache();
+    }
+    let span = Span::current();
+    span.record("chunks_processed", response.chunks_processed);
+    span.record("links_created", response.links_created);
+    
+    Ok(Json(response))
+}
+
 /// Hybrid search (main query API)
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref()))]
 pub async fn hybrid_search(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<HybridSearchRequest>,
+    owner: OwnerScope,
+    JsonBody(mut request): JsonBody<HybridSearchRequest>,
 ) -> Result<Json<HybridSearchResponse>, GraphError> {
+    request.options.owner_id = owner.resolve(request.options.owner_id.take())?;
+    
     let engine = HybridQueryEngine::new(
         state.config.clone(),
-        state.neo4j.clone(),
-    );
+        state.graph.clone(),
+        state.embedding_client.clone(),
+    ).with_search_cache(state.search_cache.clone());
     
     let response = engine.search(request).await?;
     
     Ok(Json(response))
 }
 
+/// Hybrid search over a WebSocket
+/// 
+/// The client sends one text message holding a `HybridSearchRequest`; the
+/// server answers with `SearchFrame`s as results become available and closes
+/// the socket. Disconnecting cancels the search.
+pub async fn search_stream(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+) -> Response {
+    ws.on_upgrade(move |socket| stream_search(socket, state, owner))
+}
+
+async fn stream_search(mut socket: WebSocket, state: Arc<AppState>, owner: OwnerScope) {
+    let request = loop {
+        match socket.recv().await {
+            Some(Ok(Message::Text(text))) => break parse_stream_request(&text, &owner),
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+            Some(Ok(_)) => continue,
+        }
+    };
+    
+    let (frames, receiver) = mpsc::channel(STREAM_FRAME_BUFFER);
+    let work = tokio::spawn(async move {
+        let result = match request {
+            Ok(request) => {
+                let engine = HybridQueryEngine::new(
+                    state.config.clone(),
+                    state.graph.clone(),
+                    state.embedding_client.clone(),
+                );
+                engine.search_stream(request, &frames).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            let _ = frames.send(SearchFrame::Error { message: e.to_string() }).await;
+        }
+    });
+    
+    forward_frames(socket, receiver, work).await;
+}
+
+fn parse_stream_request(text: &str, owner: &OwnerScope) -> GraphResult<HybridSearchRequest> {
+    let mut request: HybridSearchRequest = serde_json::from_str(text)
+        .map_err(|e| GraphError::InvalidRequest(format!("Invalid search request: {}", e)))?;
+    request.options.owner_id = owner.resolve(request.options.owner_id.take())?;
+    Ok(request)
+}
+
+/// Relay frames to the client until the search finishes or the client leaves
+/// 
+/// A disconnect aborts `work` so the search stops querying Neo4j.
+async fn forward_frames(
+    mut socket: WebSocket,
+    mut frames: mpsc::Receiver<SearchFrame>,
+    work: JoinHandle<()>,
+) {
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(frame) => {
+                    let text = serde_json::to_string(&frame).unwrap_or_default();
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                None => {
+                    let _ = socket.send(Message::Close(None)).await;
+                    return;
+                }
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    
+    tracing::debug!("Search stream client disconnected; cancelling search");
+    work.abort();
+}
+
 /// Vector-only search
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref()))]
 pub async fn vector_search(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<VectorSearchRequest>,
+    owner: OwnerScope,
+    JsonBody(mut request): JsonBody<VectorSearchRequest>,
 ) -> Result<Json<VectorSearchResponse>, GraphError> {
+    request.owner_id = owner.resolve(request.owner_id.take())?;
+    
     let engine = HybridQueryEngine::new(
         state.config.clone(),
-        state.neo4j.clone(),
+        state.graph.clone(),
+        state.embedding_client.clone(),
     );
     
     let response = engine.vector_search(request).await?;
@@ -187,14 +1251,39 @@ pub async fn vector_search(
     Ok(Json(response))
 }
 
+/// Vector-only search with a query embedding supplied by the caller
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref()))]
+pub async fn vector_search_by_embedding(
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+    JsonBody(mut request): JsonBody<EmbeddingSearchRequest>,
+) -> Result<Json<VectorSearchResponse>, GraphError> {
+    request.owner_id = owner.resolve(request.owner_id.take())?;
+    
+    let engine = HybridQueryEngine::new(
+        state.config.clone(),
+        state.graph.clone(),
+        state.embedding_client.clone(),
+    );
+    
+    let response = engine.vector_search_by_embedding(request).await?;
+    
+    Ok(Json(response))
+}
+
 /// Graph-only search
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref()))]
 pub async fn graph_search(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<GraphSearchRequest>,
+    owner: OwnerScope,
+    JsonBody(mut request): JsonBody<GraphSearchRequest>,
 ) -> Result<Json<GraphSearchResponse>, GraphError> {
+    request.owner_id = owner.resolve(request.owner_id.take())?;
+    
     let engine = HybridQueryEngine::new(
         state.config.clone(),
-        state.neo4j.clone(),
+        state.graph.clone(),
+        state.embedding_client.clone(),
     );
     
     let response = engine.graph_search(request).await?;
@@ -202,25 +1291,1243 @@ pub async fn graph_search(
     Ok(Json(response))
 }
 
+/// Read-only view of the confidence values assigned at ingest time
+/// 
+/// Extractor confidences are fixed per pattern; the manual default, linking
+/// threshold, and boosts come from the running configuration.
+pub async fn get_schema(
+    State(state): State<Arc<AppState>>,
+) -> Json<serde_json::Value> {
+    let config = &state.config;
+    Json(serde_json::json!({
+        "extraction_confidences": extraction_confidences(),
+        "default_manual_relationship_confidence": config.default_manual_relationship_confidence,
+        "cross_source_linking": {
+            "similarity_threshold": config.similarity_threshold,
+            "explicit_mention_boost": config.explicit_mention_boost,
+            "temporal_proximity_boost": config.temporal_proximity_boost,
+            "author_overlap_boost": config.author_overlap_boost,
+        },
+    }))
+}
+
 /// Get graph statistics
+#[tracing::instrument(skip_all)]
 pub async fn get_statistics(
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<serde_json::Value>, GraphError> {
+    Ok(Json(collect_statistics(&state).await?))
+}
+
+/// Stream the graph, or one owner's part of it, as GraphML or JSON-LD
+#[tracing::instrument(skip_all, fields(owner_id = owner.0.as_deref(), format = ?query.format))]
+pub async fn export_graph(
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, GraphError> {
+    let graph = state.graph.clone()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+    let owner_id = owner.resolve(query.owner_id)?;
+    
+    let pieces = graph_export::export_graph(graph, query.format, owner_id, query.include_embeddings).await?;
+    Ok(([(header::CONTENT_TYPE, query.format.content_type())], Body::from_stream(pieces)).into_response())
+}
+
+/// Restore a graph from a GraphML or JSON-LD export
+#[tracing::instrument(
+    skip_all,
+    fields(
+        owner_id = owner.0.as_deref(),
+        format = ?query.format,
+        mode = ?query.mode,
+        nodes_imported = Empty,
+        relationships_imported = Empty,
+    )
+)]
+pub async fn import_graph(
+    State(state): State<Arc<AppState>>,
+    owner: OwnerScope,
+    Query(query): Query<ImportQuery>,
+    body: Body,
+) -> Result<Json<ImportResponse>, GraphError> {
+    let graph = state.graph.clone()
+        .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+    let owner_id = owner.resolve(query.owner_id)?;
+    
+    let bytes = axum::body::to_bytes(body, MAX_IMPORT_BYTES)
+        .await
+        .map_err(|e| GraphError::InvalidRequest(format!("Failed to read import document: {}", e)))?;
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|_| GraphError::InvalidRequest("Import document is not valid UTF-8".to_string()))?;
+    let document = graph_import::parse_document(query.format, text)?;
+    
+    let response = graph_import::import_graph(graph, document, query.mode, owner_id.as_deref()).await?;
+    state.invalidate_search_cache();
+    let span = Span::current();
+    span.record("nodes_imported", response.nodes_imported);
+    span.record("relationships_imported", response.relationships_imported);
+    
+    Ok(Json(response))
+}
+
+/// Service, graph, vector, and cache statistics (shared with GraphQL)
+pub async fn collect_statistics(state: &AppState) -> GraphResult<serde_json::Value> {
     let mut stats = serde_json::json!({
         "service": "relation-graph",
         "vector_store": "neo4j-native"
     });
     
+    if let Some(graph) = &state.graph {
+        stats["graph"] = graph.get_statistics().await?;
+    }
+    
     if let Some(neo4j) = &state.neo4j {
-        let graph_stats = neo4j.get_statistics().await?;
-        stats["graph"] = graph_stats;
         // Vector stats now included in Neo4j since vectors are stored there
         stats["vector"] = serde_json::json!({
             "store": "neo4j-native",
-            "dimension": 384,
-            "indexes": ["chunk_embedding_idx", "function_embedding_idx", "class_embedding_idx", "document_embedding_idx"]
+            "dimension": state.config.vector_dimension,
+            "indexes": neo4j.vector_index_names()
         });
     }
     
-    Ok(Json(stats))
+    if let Some(cache_stats) = state.embedding_client.cache_stats() {
+        stats["embedding_cache"] = serde_json::json!(cache_stats);
+    }
+    
+    if let Some(cache) = &state.search_cache {
+        stats["search_cache"] = serde_json::json!(cache.stats());
+    }
+    
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    
+    async fn owner_scope(header: Option<&str>) -> OwnerScope {
+        let mut builder = Request::builder();
+        if let Some(value) = header {
+            builder = builder.header("X-Owner-Id", value);
+        }
+        let (mut parts, _) = builder.body(()).unwrap().into_parts();
+        OwnerScope::from_request_parts(&mut parts, &()).await.unwrap()
+    }
+    
+    #[tokio::test]
+    async fn test_owner_scope_from_header() {
+        assert_eq!(owner_scope(Some("tenant-a")).await.0.as_deref(), Some("tenant-a"));
+        assert!(owner_scope(Some("  ")).await.0.is_none());
+        assert!(owner_scope(None).await.0.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_owner_scope_prevents_cross_tenant_requests() {
+        let scope = owner_scope(Some("tenant-a")).await;
+        
+        // The header pins the scope even when the body omits it
+        assert_eq!(scope.resolve(None).unwrap().as_deref(), Some("tenant-a"));
+        assert_eq!(scope.resolve(Some("tenant-a".to_string())).unwrap().as_deref(), Some("tenant-a"));
+        // A body asking for another tenant's data is rejected
+        assert!(matches!(
+            scope.resolve(Some("tenant-b".to_string())),
+            Err(GraphError::InvalidRequest(_))
+        ));
+        
+        // Without the header the body value is used as-is
+        let unscoped = OwnerScope::default();
+        assert_eq!(unscoped.resolve(Some("tenant-b".to_string())).unwrap().as_deref(), Some("tenant-b"));
+    }
+    
+    #[tokio::test]
+    async fn test_queries_and_jobs_are_scoped_to_the_owner() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let request = || serde_json::from_value::<CypherQueryRequest>(serde_json::json!({ "cypher": "MATCH (n) RETURN n" })).unwrap();
+        assert!(matches!(
+            run_query(State(state.clone()), owner_scope(Some("tenant-a")).await, JsonBody(request())).await,
+            Err(GraphError::InvalidRequest(_))
+        ));
+        
+        let chunk = serde_json::from_value(serde_json::json!({
+            "content": "fn main() {}",
+            "embedding": [1.0, 0.0],
+            "source_kind": "code",
+            "source_type": "github",
+            "source_id": "repo/main.rs",
+            "owner_id": "tenant-a",
+        })).unwrap();
+        let request = IngestChunksRequest {
+            chunks: vec![chunk],
+            extract_entities: Some(false),
+            create_cross_links: Some(false),
+            strict: None,
+            detailed: None,
+            store_chunks: None,
+            dedupe: None,
+            return_evidence: None,
+        };
+        let processor = ChunkProcessor::new(state.config.clone(), None, state.embedding_client.clone());
+        let job = state.ingest_jobs.submit(processor, request, 10);
+        
+        let get = |header: Option<&'static str>| {
+            let state = state.clone();
+            async move { get_ingest_job(State(state), Path(job.job_id), owner_scope(header).await).await }
+        };
+        assert!(get(Some("tenant-a")).await.is_ok());
+        assert!(get(None).await.is_ok());
+        assert!(matches!(get(Some("tenant-b")).await, Err(GraphError::JobNotFound(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_health_check_reports_unreachable_embedding_service() {
+        let mut config = Config::test_default();
+        config.embedding_service_url = "http://127.0.0.1:1".to_string();
+        config.health_check_timeout_ms = 500;
+        config.graph_backend = "memory".to_string();
+        let state = Arc::new(AppState::with_memory_graph(config));
+        
+        let (code, Json(body)) = health_check(State(state)).await;
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "unhealthy");
+        assert_eq!(body["components"]["embedding"]["status"], "down");
+        assert!(body["components"]["embedding"]["latency_ms"].is_u64());
+        assert!(body["components"]["embedding"]["error"].is_string());
+        // The in-memory backend has no Neo4j to ping
+        assert_eq!(body["components"]["neo4j"]["status"], "disabled");
+        assert_eq!(body["components"]["graph_backend"], "memory");
+    }
+    
+    #[tokio::test]
+    async fn test_oversized_ingests_are_rejected_with_413() {
+        use axum::extract::DefaultBodyLimit;
+        use axum::routing::post;
+        
+        let mut config = Config::test_default();
+        config.max_chunks_per_request = 2;
+        let state = Arc::new(AppState::with_memory_graph(config));
+        let router = axum::Router::new()
+            .route("/api/graph/chunks", post(ingest_chunks))
+            .route("/api/graph/chunks/async", post(ingest_chunks_async))
+            .with_state(state)
+            .layer(DefaultBodyLimit::max(4096));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        
+        let chunks = |count: usize, content: &str| serde_json::json!({
+            "chunks": (0..count).map(|i| serde_json::json!({
+                "content": content,
+                "source_kind": "code",
+                "source_type": "github",
+                "source_id": format!("repo/src/{}.rs", i),
+                "owner_id": "tenant-a",
+            })).collect::<Vec<_>>(),
+        });
+        let client = reqwest::Client::new();
+        let post_to = |path: &str, body: serde_json::Value| {
+            client.post(format!("http://{}{}", addr, path)).json(&body).send()
+        };
+        
+        // Over MAX_BODY_BYTES: a JSON error, not axum's plain-text rejection
+        let response = post_to("/api/graph/chunks", chunks(1, &"x".repeat(8192))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["status"], 413);
+        assert!(body["error"].as_str().unwrap().contains("MAX_BODY_BYTES"), "{}", body);
+        
+        for path in ["/api/graph/chunks", "/api/graph/chunks/async"] {
+            let response = post_to(path, chunks(3, "fn a() {}")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE, "{}", path);
+            let body: serde_json::Value = response.json().await.unwrap();
+            assert!(body["error"].as_str().unwrap().contains("3 chunks in one request; MAX_CHUNKS_PER_REQUEST is 2"), "{}", body);
+        }
+        
+        let response = post_to("/api/graph/chunks", chunks(2, "fn a() {}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    
+    #[tokio::test]
+    async fn test_commit_chunks_lists_chunks_by_sha() {
+        let mut config = Config::test_default();
+        config.commit_nodes = true;
+        let state = Arc::new(AppState::with_memory_graph(config));
+        let chunk = |content: &str, owner_id: &str, sha: &str| -> ChunkInput {
+            serde_json::from_value(serde_json::json!({
+                "content": content,
+                "source_kind": "code",
+                "source_type": "github",
+                "source_id": "repo/src/auth.rs",
+                "file_path": "src/auth.rs",
+                "owner_id": owner_id,
+                "commit_sha": sha,
+                "commit_date": "2026-03-02T10:00:00Z",
+                "embedding": [1.0, 0.0],
+            })).unwrap()
+        };
+        let request = IngestChunksRequest {
+            chunks: vec![
+                chunk("fn login() {}", "tenant-a", "abc123"),
+                chunk("fn logout() {}", "tenant-a", "abc123"),
+                chunk("fn refresh() {}", "tenant-a", "def456"),
+                chunk("fn other() {}", "tenant-b", "abc123"),
+            ],
+            extract_entities: Some(false),
+            create_cross_links: Some(false),
+            strict: None,
+            detailed: None,
+            store_chunks: None,
+            dedupe: None,
+            return_evidence: None,
+        };
+        let response = ingest_chunks(State(state.clone()), OwnerScope::default(), JsonBody(request)).await.unwrap();
+        assert_eq!(response.relationships_created, 4);
+        
+        let tenant_a = || OwnerScope(Some("tenant-a".to_string()));
+        let commit = commit_chunks(State(state.clone()), Path("abc123".to_string()), tenant_a()).await.unwrap();
+        assert_eq!(commit.chunks.len(), 2);
+        assert_eq!(commit.committed_at.unwrap().to_rfc3339(), "2026-03-02T10:00:00+00:00");
+        
+        let unscoped = commit_chunks(State(state.clone()), Path("abc123".to_string()), OwnerScope::default()).await.unwrap();
+        assert_eq!(unscoped.chunks.len(), 3);
+        assert!(matches!(
+            commit_chunks(State(state), Path("0000000".to_string()), tenant_a()).await,
+            Err(GraphError::EntityNotFound(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_list_entities_filters_by_type_and_source() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let graph = state.graph.clone().unwrap();
+        let entity = |entity_type: EntityType, source: DataSource, name: &str| Entity::new(
+            entity_type,
+            source,
+            format!("repo/src/lib.rs#{}", name),
+            name.to_string(),
+            std::collections::HashMap::new(),
+        );
+        let login = entity(EntityType::Function, DataSource::GitHub, "login");
+        graph.upsert_entity_node(&login).await.unwrap();
+        graph.upsert_entity_node(&entity(EntityType::Class, DataSource::GitHub, "Session")).await.unwrap();
+        graph.upsert_entity_node(&entity(EntityType::Function, DataSource::GitLab, "logout")).await.unwrap();
+        let filter = |entity_type: Option<&str>, source: Option<&str>| Query(EntityQuery {
+            entity_type: entity_type.map(str::to_string),
+            source: source.map(str::to_string),
+            limit: 100,
+        });
+        
+        let listed = list_entities(State(state.clone()), filter(Some("function"), Some("github")), OwnerScope::default())
+            .await
+            .unwrap();
+        assert_eq!(listed.0["count"], 1);
+        assert_eq!(listed.0["entities"][0]["id"], login.id.to_string());
+        assert_eq!(listed.0["entities"][0]["entity_type"], "FUNCTION");
+        
+        let all = list_entities(State(state.clone()), filter(None, None), OwnerScope::default()).await.unwrap();
+        assert_eq!(all.0["count"], 3);
+        assert!(matches!(
+            list_entities(State(state), filter(Some("FUNCTION) RETURN n //"), None), OwnerScope::default()).await,
+            Err(GraphError::InvalidEntityType(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_create_relationship_between_entities_by_id() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let graph = state.graph.clone().unwrap();
+        let entity = |name: &str| Entity::new(
+            EntityType::Function,
+            DataSource::GitHub,
+            format!("repo/src/lib.rs#{}", name),
+            name.to_string(),
+            std::collections::HashMap::new(),
+        );
+        let (caller, callee) = (entity("login"), entity("verify_token"));
+        graph.upsert_entity_node(&caller).await.unwrap();
+        graph.upsert_entity_node(&callee).await.unwrap();
+        let request = |relationship_type: &str, to_entity_id: Uuid| CreateRelationshipRequest {
+            from_entity_id: caller.id,
+            to_entity_id,
+            relationship_type: relationship_type.to_string(),
+            confidence: None,
+            properties: serde_json::json!({ "note": "checked by hand" }),
+        };
+        
+        let created = create_relationship(State(state.clone()), OwnerScope::default(), JsonBody(request("calls", callee.id)))
+            .await
+            .unwrap();
+        let again = create_relationship(State(state.clone()), OwnerScope::default(), JsonBody(request("calls", callee.id)))
+            .await
+            .unwrap();
+        assert_eq!(again.0.relationship_id, created.0.relationship_id);
+        
+        let neighbors = graph
+            .get_neighbors(&EntityId(caller.id), None, "outgoing", 1, &NeighborQuery::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(neighbors.len(), 1);
+        assert_eq!(neighbors[0].name, "verify_token");
+        assert_eq!(neighbors[0].relationship, "CALLS");
+        assert_eq!(neighbors[0].confidence, state.config.default_manual_relationship_confidence);
+        
+        assert!(matches!(
+            create_relationship(State(state.clone()), OwnerScope::default(), JsonBody(request("CALLED_BY", callee.id))).await,
+            Err(GraphError::InvalidRelationshipType(t)) if t == "CALLED_BY"
+        ));
+        assert!(matches!(
+            create_relationship(State(state), OwnerScope::default(), JsonBody(request("CALLS", Uuid::new_v4()))).await,
+            Err(GraphError::EntityNotFound(_))
+        ));
+    }
+    
+    #[test]
+    fn test_relationship_query_params() {
+        let uri: axum::http::Uri = "/api/graph/relationships?extraction_method=vector_similarity&exclusive=true"
+            .parse()
+            .unwrap();
+        let Query(filter) = Query::<RelationshipQuery>::try_from_uri(&uri).unwrap();
+        assert_eq!(filter.extraction_method, Some(ExtractionMethod::VectorSimilarity));
+        assert!(filter.exclusive);
+        assert_eq!(filter.limit, 100);
+        assert_eq!(filter.min_confidence, 0.0);
+        
+        let uri: axum::http::Uri = "/api/graph/relationships?extraction_method=guesswork".parse().unwrap();
+        assert!(Query::<RelationshipQuery>::try_from_uri(&uri).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_entity_handlers_with_memory_graph() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let tenant = || OwnerScope(Some("tenant-a".to_string()));
+        
+        let mut ids = Vec::new();
+        for name in ["login", "verify_token"] {
+            let request: CreateEntityRequest = serde_json::from_value(serde_json::json!({
+                "entity_type": "function",
+                "source": "github",
+                "source_id": format!("auth.rs#{}", name),
+                "name": name,
+            })).unwrap();
+            let Json(created) = create_entity(State(state.clone()), tenant(), JsonBody(request)).await.unwrap();
+            ids.push(EntityId(created.entity_id));
+        }
+        
+        let graph = state.graph.as_ref().unwrap();
+        graph.create_relationship(&ids[0], &ids[1], RelationshipType::Calls, 0.9, ExtractionMethod::Manual, None)
+            .await
+            .unwrap();
+        
+        let Json(entity) = get_entity(State(state.clone()), Path(ids[0]), tenant()).await.unwrap();
+        assert_eq!(entity["name"], "login");
+        assert_eq!(entity["entity_type"], "FUNCTION");
+        
+        // The header scoped both entities to tenant-a
+        let other_tenant = OwnerScope(Some("tenant-b".to_string()));
+        let missing = get_entity(State(state.clone()), Path(ids[0]), other_tenant).await;
+        assert!(matches!(missing, Err(GraphError::EntityNotFound(_))));
+        
+        let Json(neighbors) = get_neighbors(
+            State(state.clone()),
+            Path(ids[0]),
+            Query(NeighborQuery::default()),
+            tenant(),
+        ).await.unwrap();
+        assert_eq!(neighbors["neighbors"][0]["name"], "verify_token");
+        assert_eq!(neighbors["neighbors"][0]["relationship"], "CALLS");
+        assert_eq!(neighbors["neighbors"][0]["entity_type"], "FUNCTION");
+    }
+    
+    #[tokio::test]
+    async fn test_update_entity_merges_properties() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let tenant = || OwnerScope(Some("tenant-a".to_string()));
+        let request: CreateEntityRequest = serde_json::from_value(serde_json::json!({
+            "entity_type": "function",
+            "source": "github",
+            "source_id": "auth.rs#login",
+            "name": "login",
+            "properties": {"language": "rust", "visibility": "pub", "lines": 40}
+        })).unwrap();
+        let Json(created) = create_entity(State(state.clone()), tenant(), JsonBody(request)).await.unwrap();
+        let id = EntityId(created.entity_id);
+        let patch = |body: serde_json::Value| JsonBody(serde_json::from_value::<UpdateEntityRequest>(body).unwrap());
+        
+        let Json(updated) = update_entity(
+            State(state.clone()),
+            Path(id),
+            tenant(),
+            patch(serde_json::json!({"properties": {"lines": 55, "visibility": null}})),
+        ).await.unwrap();
+        assert_eq!(updated["name"], "login");
+        assert_eq!(updated["properties"]["lines"], 55);
+        assert_eq!(updated["properties"]["language"], "rust");
+        assert_eq!(updated["properties"]["owner_id"], "tenant-a");
+        assert!(updated["properties"].get("visibility").is_none());
+        
+        // Renaming alone leaves the properties as they were
+        let Json(renamed) = update_entity(
+            State(state.clone()),
+            Path(id),
+            tenant(),
+            patch(serde_json::json!({"name": "sign_in"})),
+        ).await.unwrap();
+        assert_eq!(renamed["name"], "sign_in");
+        assert_eq!(renamed["properties"], updated["properties"]);
+        
+        let moved = update_entity(
+            State(state.clone()),
+            Path(id),
+            tenant(),
+            patch(serde_json::json!({"properties": {"owner_id": "tenant-b"}})),
+        ).await;
+        assert!(matches!(moved, Err(GraphError::InvalidRequest(_))));
+        let other_tenant = update_entity(
+            State(state.clone()),
+            Path(id),
+            OwnerScope(Some("tenant-b".to_string())),
+            patch(serde_json::json!({"properties": {"lines": 1}})),
+        ).await;
+        assert!(matches!(other_tenant, Err(GraphError::EntityNotFound(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_get_chunk_needs_chunk_storage() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let result = get_chunk(State(state), Path(Uuid::new_v4()), OwnerScope(None)).await;
+        assert!(matches!(result, Err(GraphError::ServiceUnavailable(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_reindex_vectors_requires_confirmation() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let reindex = |query: &str| {
+            let uri: axum::http::Uri = format!("/api/graph/admin/reindex-vectors?{}", query).parse().unwrap();
+            reindex_vectors(State(state.clone()), Query::try_from_uri(&uri).unwrap())
+        };
+        
+        assert!(matches!(reindex("dimension=768").await, Err(GraphError::InvalidRequest(_))));
+        assert!(matches!(reindex("dimension=0&confirm=true").await, Err(GraphError::InvalidRequest(_))));
+        assert!(matches!(reindex("dimension=5000&confirm=true").await, Err(GraphError::InvalidRequest(_))));
+        // Only VECTOR_DIMENSION, which queries are checked against, is accepted
+        let configured = state.config.vector_dimension;
+        let mismatched = format!("dimension={}&confirm=true", configured + 1);
+        assert!(matches!(reindex(&mismatched).await, Err(GraphError::InvalidRequest(_))));
+        // Confirmed, but there are no Neo4j indexes to rebuild
+        let matching = format!("dimension={}&confirm=true", configured);
+        assert!(matches!(reindex(&matching).await, Err(GraphError::ServiceUnavailable(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_path_hops_and_neighbor_limit_are_capped() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let too_deep: PathSearchRequest = serde_json::from_value(serde_json::json!({
+            "from_id": "a",
+            "to_id": "b",
+            "max_hops": state.config.max_graph_hops + 1,
+        })).unwrap();
+        let result = find_path(State(state.clone()), OwnerScope(None), JsonBody(too_deep)).await;
+        assert!(matches!(result, Err(GraphError::InvalidRequest(message)) if message.contains("MAX_GRAPH_HOPS")));
+        
+        for limit in [0, MAX_NEIGHBOR_LIMIT + 1] {
+            let uri: axum::http::Uri = format!("/api/graph/entities/a/neighbors?limit={}", limit).parse().unwrap();
+            let result = get_neighbors(
+                State(state.clone()),
+                Path(EntityId(Uuid::new_v4())),
+                Query::try_from_uri(&uri).unwrap(),
+                OwnerScope(None),
+            ).await;
+            assert!(matches!(result, Err(GraphError::InvalidRequest(_))), "limit={}", limit);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_prune_orphans_reports_then_deletes_isolated_nodes() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let graph = state.graph.clone().unwrap();
+        let mut ids = HashMap::new();
+        for (name, owner) in [("login", "tenant-a"), ("hash", "tenant-a"), ("stale", "tenant-a"), ("other", "tenant-b")] {
+            let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(owner))]);
+            let entity = Entity::new(EntityType::Function, DataSource::LocalFile, name.to_string(), name.to_string(), properties);
+            ids.insert(name, graph.upsert_entity_node(&entity).await.unwrap().id);
+        }
+        graph.create_relationship(&ids["login"], &ids["hash"], RelationshipType::Calls, 0.9, ExtractionMethod::Manual, None)
+            .await
+            .unwrap();
+        let prune = |query: &str| {
+            let uri: axum::http::Uri = format!("/api/graph/maintenance/prune-orphans?{}", query).parse().unwrap();
+            prune_orphans(
+                State(state.clone()),
+                Query::try_from_uri(&uri).unwrap(),
+                OwnerScope(Some("tenant-a".to_string())),
+            )
+        };
+        
+        // Dry run by default: reported, not deleted
+        let Json(report) = prune("").await.unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.orphans.iter().map(|o| o.id.as_str()).collect::<Vec<_>>(), [ids["stale"].to_string()]);
+        assert_eq!(report.deleted, 0);
+        assert!(graph.get_entity(&ids["stale"], None).await.unwrap().is_some());
+        
+        let Json(pruned) = prune("dry_run=false").await.unwrap();
+        assert_eq!(pruned.deleted, 1);
+        assert!(graph.get_entity(&ids["stale"], None).await.unwrap().is_none());
+        // Connected nodes and other tenants' orphans are left alone
+        for name in ["login", "hash", "other"] {
+            assert!(graph.get_entity(&ids[name], None).await.unwrap().is_some(), "{}", name);
+        }
+        assert!(prune("dry_run=false").await.unwrap().0.orphans.is_empty());
+        
+        // Chunks without links are only pruned when asked for by label
+        let chunk = serde_json::from_value::<crate::models::ChunkInput>(serde_json::json!({
+            "content": "fn unlinked() {}",
+            "source_kind": "code",
+            "source_type": "github",
+            "source_id": "repo/unlinked.rs",
+            "owner_id": "tenant-a",
+        })).unwrap().into_chunk();
+        graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+        assert!(prune("").await.unwrap().0.orphans.is_empty());
+        let Json(chunks) = prune("label=CHUNK").await.unwrap();
+        assert_eq!(chunks.orphans.len(), 1);
+        assert_eq!(chunks.orphans[0].id, chunk.id.to_string());
+        
+        // Deleting needs an owner scope
+        let uri: axum::http::Uri = "/api/graph/maintenance/prune-orphans?dry_run=false".parse().unwrap();
+        assert!(matches!(
+            prune_orphans(State(state.clone()), Query::try_from_uri(&uri).unwrap(), OwnerScope(None)).await,
+            Err(GraphError::InvalidRequest(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_vector_search_by_embedding_ranks_the_matching_chunk_first() {
+        let mut config = Config::test_default();
+        config.vector_dimension = 3;
+        let state = Arc::new(AppState::with_memory_graph(config));
+        let graph = state.graph.clone().unwrap();
+        let mut stored = Vec::new();
+        for (path, embedding) in [
+            ("src/auth.rs", vec![0.9, 0.1, 0.0]),
+            ("src/token.rs", vec![0.6, 0.8, 0.0]),
+            ("docs/auth.md", vec![0.7, 0.0, 0.7]),
+        ] {
+            let chunk = serde_json::from_value::<ChunkInput>(serde_json::json!({
+                "content": path,
+                "source_kind": "code",
+                "source_type": "github",
+                "source_id": path,
+                "file_path": path,
+                "owner_id": "tenant-a",
+            })).unwrap().into_chunk();
+            graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+            graph.set_node_embedding(&EntityId(chunk.id), embedding.clone(), "test", "test").await.unwrap();
+            stored.push((chunk.id, embedding));
+        }
+        let search = |embedding: Vec<f32>| {
+            let request = serde_json::from_value::<EmbeddingSearchRequest>(serde_json::json!({
+                "embedding": embedding,
+                "min_similarity": 0.0,
+            })).unwrap();
+            vector_search_by_embedding(State(state.clone()), OwnerScope(Some("tenant-a".to_string())), JsonBody(request))
+        };
+        
+        for (id, embedding) in &stored {
+            let Json(response) = search(embedding.clone()).await.unwrap();
+            assert_eq!(response.total_count, 3);
+            assert_eq!(response.results[0].chunk_id, *id);
+        }
+        
+        match search(vec![1.0, 0.0]).await {
+            Err(GraphError::InvalidRequest(message)) => {
+                assert!(message.contains("2 dimensions"), "{}", message);
+                assert!(message.contains("expects 3"), "{}", message);
+            }
+            other => panic!("expected a dimension error, got {:?}", other.map(|r| r.0)),
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_cross_source_matches_return_closest_code_without_linking() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let graph = state.graph.clone().unwrap();
+        let mut ids = HashMap::new();
+        for (path, kind, owner, embedding) in [
+            ("docs/auth.md", "document", "tenant-a", vec![1.0, 0.0, 0.0]),
+            ("src/auth.rs", "code", "tenant-a", vec![0.95, 0.3, 0.0]),
+            ("src/billing.rs", "code", "tenant-a", vec![0.8, 0.6, 0.0]),
+            ("src/theirs.rs", "code", "tenant-b", vec![1.0, 0.0, 0.0]),
+        ] {
+            let chunk = serde_json::from_value::<ChunkInput>(serde_json::json!({
+                "content": format!("contents of {}", path),
+                "source_kind": kind,
+                "source_type": "github",
+                "source_id": path,
+                "file_path": path,
+                "owner_id": owner,
+            })).unwrap().into_chunk();
+            graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+            graph.set_node_embedding(&EntityId(chunk.id), embedding, "test", "test").await.unwrap();
+            ids.insert(path, chunk.id);
+        }
+        let matches = |id: Uuid, query: &str| {
+            let uri: axum::http::Uri = format!("/api/graph/chunks/{}/cross-source?{}", id, query).parse().unwrap();
+            cross_source_matches(
+                State(state.clone()),
+                Path(id),
+                Query::try_from_uri(&uri).unwrap(),
+                OwnerScope(Some("tenant-a".to_string())),
+            )
+        };
+        
+        let Json(response) = matches(ids["docs/auth.md"], "min_similarity=0.5").await.unwrap();
+        assert_eq!(response.target_kind, "code");
+        let paths: Vec<_> = response.matches.iter().map(|m| m.file_path.as_deref().unwrap()).collect();
+        assert_eq!(paths, ["src/auth.rs", "src/billing.rs"]);
+        assert_eq!(response.matches[0].chunk_id, ids["src/auth.rs"]);
+        assert_eq!(response.matches[0].content.as_deref(), Some("contents of src/auth.rs"));
+        assert!(response.matches[0].confidence >= response.matches[1].confidence);
+        // Read-only: nothing was linked
+        assert_eq!(graph.get_statistics().await.unwrap()["relationship_count"], 0);
+        
+        let Json(limited) = matches(ids["docs/auth.md"], "min_similarity=0.5&limit=1").await.unwrap();
+        assert_eq!(limited.matches.len(), 1);
+        assert!(matches!(
+            matches(ids["docs/auth.md"], "target_kind=slides").await,
+            Err(GraphError::InvalidRequest(_))
+        ));
+        assert!(matches!(
+            matches(ids["src/theirs.rs"], "target_kind=document").await,
+            Err(GraphError::EntityNotFound(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_entity_tree_nests_children_and_stops_at_cycles() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let graph = state.graph.as_ref().unwrap();
+        
+        let mut ids = HashMap::new();
+        for (entity_type, name) in [
+            (EntityType::Module, "auth"),
+            (EntityType::Class, "Session"),
+            (EntityType::Class, "Token"),
+            (EntityType::Function, "refresh"),
+            (EntityType::Function, "verify"),
+        ] {
+            let entity = Entity::new(entity_type, DataSource::GitHub, format!("auth#{}", name), name.to_string(), Default::default());
+            graph.upsert_entity_node(&entity).await.unwrap();
+            ids.insert(name, EntityId(entity.id));
+        }
+        for (from, to, rel_type) in [
+            ("auth", "Session", RelationshipType::Contains),
+            ("auth", "Token", RelationshipType::Contains),
+            ("Token", "refresh", RelationshipType::Contains),
+            // Loops back to the root
+            ("refresh", "auth", RelationshipType::Contains),
+            // Not followed
+            ("Session", "verify", RelationshipType::Calls),
+        ] {
+            graph.create_relationship(&ids[from], &ids[to], rel_type, 1.0, ExtractionMethod::Manual, None)
+                .await
+                .unwrap();
+        }
+        
+        let tree = |types: Option<&str>, max_depth: Option<usize>| get_entity_tree(
+            State(state.clone()),
+            Path(ids["auth"]),
+            Query(EntityTreeQuery { types: types.map(str::to_string), max_depth }),
+            OwnerScope(None),
+        );
+        
+        let Json(response) = tree(Some("contains"), None).await.unwrap();
+        assert_eq!(response.node_count, 4);
+        assert!(!response.truncated);
+        let root = &response.root;
+        assert_eq!(root.name, "auth");
+        assert!(root.relationship.is_none());
+        let mut children: Vec<_> = root.children.iter().map(|c| c.name.as_str()).collect();
+        children.sort();
+        assert_eq!(children, ["Session", "Token"]);
+        let token = root.children.iter().find(|c| c.name == "Token").unwrap();
+        assert_eq!(token.children.len(), 1);
+        assert_eq!(token.children[0].name, "refresh");
+        assert_eq!(token.children[0].relationship.as_deref(), Some("CONTAINS"));
+        assert!(token.children[0].children.is_empty());
+        
+        let Json(shallow) = tree(None, Some(1)).await.unwrap();
+        assert_eq!(shallow.node_count, 3);
+        assert!(shallow.root.children.iter().all(|c| c.children.is_empty()));
+        
+        assert!(matches!(tree(Some("CONTAINS,OWNS"), None).await, Err(GraphError::InvalidRelationshipType(_))));
+        assert!(matches!(tree(None, Some(0)).await, Err(GraphError::InvalidRequest(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_create_entity_merges_same_name_across_sources() {
+        let mut config = Config::test_default();
+        config.entity_resolution = true;
+        let state = Arc::new(AppState::with_memory_graph(config));
+        let tenant = || OwnerScope(Some("tenant-a".to_string()));
+        
+        let mut responses = Vec::new();
+        for source in ["github", "gitlab"] {
+            let request: CreateEntityRequest = serde_json::from_value(serde_json::json!({
+                "entity_type": "class",
+                "source": source,
+                "source_id": format!("{}:auth/service.py#AuthService", source),
+                "name": "AuthService",
+            })).unwrap();
+            let Json(created) = create_entity(State(state.clone()), tenant(), JsonBody(request)).await.unwrap();
+            responses.push(created);
+        }
+        
+        assert!(responses[0].canonical_id.is_some());
+        assert!(!responses[0].resolved);
+        assert_eq!(responses[1].canonical_id, responses[0].canonical_id);
+        assert!(responses[1].resolved);
+        
+        // The canonical node links to both source entities
+        let Json(neighbors) = get_neighbors(
+            State(state.clone()),
+            Path(EntityId(responses[0].canonical_id.unwrap())),
+            Query(NeighborQuery::default()),
+            tenant(),
+        ).await.unwrap();
+        let neighbors = neighbors["neighbors"].as_array().unwrap();
+        assert_eq!(neighbors.len(), 2);
+        assert!(neighbors.iter().all(|n| n["relationship"] == "CANONICAL_OF"));
+    }
+    
+    /// Serve `router` on an ephemeral port and open a WebSocket to `path`
+    async fn connect_ws(
+        router: axum::Router,
+        path: &str,
+    ) -> tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        
+        let (socket, _) = tokio_tungstenite::connect_async(format!("ws://{}{}", addr, path))
+            .await
+            .unwrap();
+        socket
+    }
+    
+    /// `type` of each frame until the server closes the socket
+    async fn frame_types(
+        socket: &mut tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+    ) -> Vec<serde_json::Value> {
+        use futures::StreamExt;
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+        
+        let mut frames = Vec::new();
+        while let Some(Ok(message)) = socket.next().await {
+            match message {
+                WsMessage::Text(text) => frames.push(serde_json::from_str(&text).unwrap()),
+                WsMessage::Close(_) => break,
+                _ => {}
+            }
+        }
+        frames
+    }
+    
+    #[tokio::test]
+    async fn test_search_stream_frames_arrive_in_order() {
+        use axum::routing::get;
+        
+        let router = axum::Router::new().route("/stream", get(|ws: WebSocketUpgrade| async {
+            ws.on_upgrade(|socket| async {
+                let (frames, receiver) = mpsc::channel(STREAM_FRAME_BUFFER);
+                let work = tokio::spawn(async move {
+                    let _ = frames.send(SearchFrame::Chunks { chunks: Vec::new() }).await;
+                    for _ in 0..2 {
+                        let frame = SearchFrame::Expansion {
+                            chunk_id: Uuid::new_v4(),
+                            expansion: ChunkExpansion::default(),
+                        };
+                        let _ = frames.send(frame).await;
+                    }
+                    let _ = frames.send(SearchFrame::Metadata(SearchMetadata {
+                        query: "auth".to_string(),
+                        vector_results_count: 2,
+                        graph_entities_count: 0,
+                        graph_hops_performed: 2,
+                        cross_source_links_count: 0,
+                        execution_time_ms: 1,
+                        cached: false,
+                        cached_at: None,
+                    })).await;
+                });
+                forward_frames(socket, receiver, work).await
+            })
+        }));
+        
+        let mut socket = connect_ws(router, "/stream").await;
+        let types: Vec<_> = frame_types(&mut socket).await
+            .iter()
+            .map(|frame| frame["type"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(types, ["chunks", "expansion", "expansion", "metadata"]);
+    }
+    
+    #[tokio::test]
+    async fn test_search_stream_cancels_search_on_disconnect() {
+        use axum::routing::get;
+        use tokio::sync::oneshot;
+        
+        let (cancelled_tx, cancelled_rx) = oneshot::channel::<()>();
+        let cancelled_tx = Arc::new(std::sync::Mutex::new(Some(cancelled_tx)));
+        let router = axum::Router::new().route("/stream", get(move |ws: WebSocketUpgrade| {
+            let guard = cancelled_tx.lock().unwrap().take().unwrap();
+            async move {
+                ws.on_upgrade(|socket| async move {
+                    let (frames, receiver) = mpsc::channel(STREAM_FRAME_BUFFER);
+                    // Never finishes on its own; dropping `guard` signals the abort
+                    let work = tokio::spawn(async move {
+                        let _guard = guard;
+                        let _ = frames.send(SearchFrame::Chunks { chunks: Vec::new() }).await;
+                        std::future::pending::<()>().await;
+                    });
+                    forward_frames(socket, receiver, work).await
+                })
+            }
+        }));
+        
+        let mut socket = connect_ws(router, "/stream").await;
+        socket.close(None).await.unwrap();
+        
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), cancelled_rx).await;
+        assert!(result.expect("search task was not cancelled").is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_search_stream_reports_errors_as_frames() {
+        use axum::routing::get;
+        use futures::SinkExt;
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let embeddings = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "embedding": [0.1, 0.2, 0.3]
+            })))
+            .mount(&embeddings)
+            .await;
+        
+        let mut config = Config::test_default();
+        config.embedding_service_url = embeddings.uri();
+        let router = axum::Router::new()
+            .route("/api/search/stream", get(search_stream))
+            .with_state(Arc::new(AppState::for_tests(config)));
+        
+        let mut socket = connect_ws(router, "/api/search/stream").await;
+        socket.send(WsMessage::Text(r#"{"query": "how does auth work"}"#.into())).await.unwrap();
+        
+        // Without Neo4j the search fails after embedding; the client gets an error frame
+        let frames = frame_types(&mut socket).await;
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0]["type"], "error");
+        assert!(frames[0]["message"].as_str().unwrap().contains("Graph store not available"));
+    }
+    
+    #[test]
+    fn test_recent_chunks_query_params() {
+        let uri: axum::http::Uri = "/api/graph/chunks/recent?source_type=github&since=2024-05-01T12:00:00Z"
+            .parse()
+            .unwrap();
+        let Query(params) = Query::<RecentChunksQuery>::try_from_uri(&uri).unwrap();
+        assert_eq!(params.limit, 50);
+        assert_eq!(params.source_type.as_deref(), Some("github"));
+        assert_eq!(params.since.unwrap().to_rfc3339(), "2024-05-01T12:00:00+00:00");
+        
+        let uri: axum::http::Uri = "/api/graph/chunks/recent?since=yesterday".parse().unwrap();
+        assert!(Query::<RecentChunksQuery>::try_from_uri(&uri).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_chunk_stream_reports_progress_per_batch() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let line = |i: usize| -> String {
+            if i == 51 {
+                return "{not json\n".to_string();
+            }
+            let chunk = serde_json::json!({
+                "content": format!("fn handler_{}() {{}}", i),
+                "source_kind": "code",
+                "source_type": "github",
+                "source_id": format!("repo/src/handler_{}.rs", i),
+                "owner_id": "tenant-a",
+                "embedding": [1.0, 0.0],
+            });
+            format!("{}\n", chunk)
+        };
+        let (upload, body) = futures::channel::mpsc::unbounded::<Result<String, std::convert::Infallible>>();
+        let query = IngestStreamQuery {
+            batch_size: Some(100),
+            extract_entities: Some(false),
+            create_cross_links: Some(false),
+            ..Default::default()
+        };
+        let response = ingest_chunks_stream(
+            State(state),
+            OwnerScope::default(),
+            Query(query),
+            Body::from_stream(body),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+        let mut output = response.into_body().into_data_stream();
+        let mut buffered = Vec::new();
+        async fn read_frame(output: &mut axum::body::BodyDataStream, buffered: &mut Vec<u8>) -> Option<serde_json::Value> {
+            use futures::StreamExt;
+            loop {
+                if let Some(end) = buffered.iter().position(|&b| b == b'\n') {
+                    let line: Vec<u8> = buffered.drain(..=end).collect();
+                    return Some(serde_json::from_slice(&line).unwrap());
+                }
+                buffered.extend_from_slice(&output.next().await?.unwrap());
+            }
+        }
+        
+        // The first batch is reported while the upload is still open
+        for i in 1..=150 {
+            upload.unbounded_send(Ok(line(i))).unwrap();
+        }
+        let first = read_frame(&mut output, &mut buffered).await.unwrap();
+        assert_eq!(first["type"], "progress");
+        assert_eq!(first["batch"], 1);
+        assert_eq!(first["chunks_ingested"], 100);
+        assert_eq!(first["lines_read"], 101);
+        assert!(first["errors"][0].as_str().unwrap().starts_with("Line 51: invalid chunk"));
+        
+        for i in 151..=1001 {
+            upload.unbounded_send(Ok(line(i))).unwrap();
+        }
+        drop(upload);
+        let mut frames = vec![first];
+        while let Some(frame) = read_frame(&mut output, &mut buffered).await {
+            frames.push(frame);
+        }
+        
+        let (done, progress) = frames.split_last().unwrap();
+        assert_eq!(progress.len(), 10);
+        assert!(progress.iter().all(|frame| frame["type"] == "progress"));
+        assert!(progress.windows(2).all(|pair| pair[0]["lines_read"].as_u64() < pair[1]["lines_read"].as_u64()));
+        assert_eq!(done["type"], "done");
+        assert_eq!(done["batches"], 10);
+        assert_eq!(done["lines_read"], 1001);
+        assert_eq!(done["chunks_ingested"], 1000);
+        assert_eq!(done["errors"], 1);
+    }
+    
+    #[tokio::test]
+    async fn test_chunk_stream_skips_overlong_lines_and_clamps_batch_size() {
+        use crate::services::ingest_stream::MAX_LINE_BYTES;
+        use futures::StreamExt;
+        
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let line = |i: usize| format!("{}\n", serde_json::json!({
+            "content": format!("fn handler_{}() {{}}", i),
+            "source_kind": "code",
+            "source_type": "github",
+            "source_id": format!("repo/src/handler_{}.rs", i),
+            "owner_id": "tenant-a",
+            "embedding": [1.0, 0.0],
+        }));
+        // The long line arrives in pieces, none of which holds its newline
+        let piece = "x".repeat(MAX_LINE_BYTES / 2);
+        let body = vec![line(1), piece.clone(), piece.clone(), piece, "\n".to_string(), line(3)];
+        let query = IngestStreamQuery {
+            batch_size: Some(usize::MAX),
+            extract_entities: Some(false),
+            create_cross_links: Some(false),
+            ..Default::default()
+        };
+        let response = ingest_chunks_stream(
+            State(state),
+            OwnerScope::default(),
+            Query(query),
+            Body::from_stream(futures::stream::iter(body.into_iter().map(Ok::<_, std::convert::Infallible>))),
+        )
+        .await
+        .unwrap();
+        
+        let mut output = Vec::new();
+        let mut data = response.into_body().into_data_stream();
+        while let Some(bytes) = data.next().await {
+            output.extend_from_slice(&bytes.unwrap());
+        }
+        let frames: Vec<serde_json::Value> = output
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        let (done, progress) = frames.split_last().unwrap();
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress[0]["errors"][0], format!("Line 2: longer than {} bytes", MAX_LINE_BYTES));
+        assert_eq!(done["type"], "done");
+        assert_eq!(done["lines_read"], 3);
+        assert_eq!(done["chunks_ingested"], 2);
+        assert_eq!(done["errors"], 1);
+    }
+    
+    /// Check that `xml` is well-formed GraphML, returning its node ids and edge endpoints
+    fn parse_graphml(xml: &str) -> (Vec<String>, Vec<(String, String)>) {
+        let tag = regex::Regex::new(r#"<(/?)([A-Za-z][\w.:]*)((?:\s+[\w.:]+="[^"<]*")*)\s*(/?)>"#).unwrap();
+        let attribute = regex::Regex::new(r#"([\w.:]+)="([^"]*)""#).unwrap();
+        let body = xml.strip_prefix(r#"<?xml version="1.0" encoding="UTF-8"?>"#).expect("XML declaration");
+        
+        let mut open: Vec<&str> = Vec::new();
+        let (mut nodes, mut edges) = (Vec::new(), Vec::new());
+        let mut end = 0;
+        for found in tag.captures_iter(body) {
+            let whole = found.get(0).unwrap();
+            // Text between tags must not contain markup
+            let text = &body[end..whole.start()];
+            assert!(!text.contains('<') && !text.contains('>'), "stray markup in {:?}", text);
+            assert!(text.split('&').skip(1).all(|rest| ["amp;", "lt;", "gt;", "quot;", "apos;"].iter().any(|e| rest.starts_with(e))));
+            end = whole.end();
+            
+            let name = found.get(2).unwrap().as_str();
+            let attrs: HashMap<&str, &str> = attribute
+                .captures_iter(found.get(3).unwrap().as_str())
+                .map(|a| (a.get(1).unwrap().as_str(), a.get(2).unwrap().as_str()))
+                .collect();
+            if !found[1].is_empty() {
+                assert_eq!(open.pop(), Some(name), "mismatched closing tag");
+                continue;
+            }
+            if open.is_empty() {
+                assert_eq!(name, "graphml");
+                assert_eq!(attrs["xmlns"], "http://graphml.graphdrawing.org/xmlns");
+            }
+            match name {
+                "node" => nodes.push(attrs["id"].to_string()),
+                "edge" => edges.push((attrs["source"].to_string(), attrs["target"].to_string())),
+                _ => {}
+            }
+            if found[4].is_empty() {
+                open.push(name);
+            }
+        }
+        assert!(open.is_empty(), "unclosed tags {:?}", open);
+        assert!(body[end..].trim().is_empty());
+        (nodes, edges)
+    }
+    
+    #[tokio::test]
+    async fn test_export_graphml_is_scoped_and_well_formed() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let graph = state.graph.clone().unwrap();
+        let entity = |name: &str, entity_type: EntityType, owner: &str| {
+            let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(owner))]);
+            Entity::new(entity_type, DataSource::LocalFile, name.to_string(), name.to_string(), properties)
+        };
+        let upsert = |entity: Entity| {
+            let graph = graph.clone();
+            async move { graph.upsert_entity_node(&entity).await.unwrap().id }
+        };
+        let login = upsert(entity("login<&>", EntityType::Function, "tenant-a")).await;
+        let hash = upsert(entity("hash_password", EntityType::Function, "tenant-a")).await;
+        let other = upsert(entity("other", EntityType::Function, "tenant-b")).await;
+        graph.create_relationship(&login, &hash, RelationshipType::Calls, 0.9, ExtractionMethod::AstExtraction, None).await.unwrap();
+        graph.create_relationship(&login, &other, RelationshipType::Calls, 0.9, ExtractionMethod::AstExtraction, None).await.unwrap();
+        
+        let query = ExportQuery { owner_id: Some("tenant-a".to_string()), ..Default::default() };
+        let response = export_graph(State(state.clone()), OwnerScope::default(), Query(query)).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/graphml+xml");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let xml = String::from_utf8(body.to_vec()).unwrap();
+        
+        let (mut nodes, edges) = parse_graphml(&xml);
+        nodes.sort();
+        let mut expected = vec![login.to_string(), hash.to_string()];
+        expected.sort();
+        assert_eq!(nodes, expected);
+        // The edge into another tenant's node is left out
+        assert_eq!(edges, vec![(login.to_string(), hash.to_string())]);
+        assert!(xml.contains(r#"<data key="name">login&lt;&amp;&gt;</data>"#));
+        assert!(xml.contains(r#"<data key="type">CALLS</data>"#));
+        assert!(xml.contains(r#"<data key="confidence">0.9"#));
+        assert!(!xml.contains("embedding"));
+        
+        // JSON-LD of the whole graph parses as one document
+        let query = ExportQuery { format: ExportFormat::JsonLd, ..Default::default() };
+        let response = export_graph(State(state), OwnerScope::default(), Query(query)).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let document: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(document["@graph"].as_array().unwrap().len(), 3);
+    }
+    
+    async fn export_text(state: &Arc<AppState>, format: ExportFormat, owner_id: &str) -> String {
+        let query = ExportQuery { format, owner_id: Some(owner_id.to_string()), ..Default::default() };
+        let response = export_graph(State(state.clone()), OwnerScope::default(), Query(query)).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+    
+    async fn import_text(state: &Arc<AppState>, format: ExportFormat, mode: ImportMode, document: String) -> ImportResponse {
+        let query = ImportQuery { format, mode, owner_id: None };
+        let owner = OwnerScope(Some("tenant-a".to_string()));
+        let Json(response) = import_graph(State(state.clone()), owner, Query(query), Body::from(document)).await.unwrap();
+        response
+    }
+    
+    #[tokio::test]
+    async fn test_import_restores_exported_graph() {
+        let state = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let graph = state.graph.clone().unwrap();
+        let mut ids = Vec::new();
+        for (name, owner) in [("login", "tenant-a"), ("hash_password", "tenant-a"), ("session", "tenant-a"), ("other", "tenant-b")] {
+            let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(owner))]);
+            let entity = Entity::new(EntityType::Function, DataSource::LocalFile, name.to_string(), name.to_string(), properties);
+            ids.push(graph.upsert_entity_node(&entity).await.unwrap().id);
+        }
+        graph.create_relationship(&ids[0], &ids[1], RelationshipType::Calls, 0.9, ExtractionMethod::AstExtraction, None).await.unwrap();
+        graph.create_relationship(&ids[0], &ids[2], RelationshipType::Imports, 0.75, ExtractionMethod::PatternMatch, None).await.unwrap();
+        graph.set_node_embedding(&ids[0], vec![1.0, 0.0], "test", "test").await.unwrap();
+        let statistics = graph.get_statistics().await.unwrap();
+        let graphml = export_text(&state, ExportFormat::GraphMl, "tenant-a").await;
+        let jsonld = export_text(&state, ExportFormat::JsonLd, "tenant-a").await;
+        
+        // Replacing removes what the export doesn't have and keeps stored embeddings
+        let properties = HashMap::from([("owner_id".to_string(), serde_json::json!("tenant-a"))]);
+        let stale = Entity::new(EntityType::Function, DataSource::LocalFile, "stale".to_string(), "stale".to_string(), properties);
+        graph.upsert_entity_node(&stale).await.unwrap();
+        graph.create_relationship(&ids[1], &ids[2], RelationshipType::Calls, 0.5, ExtractionMethod::AstExtraction, None).await.unwrap();
+        let response = import_text(&state, ExportFormat::GraphMl, ImportMode::Replace, graphml.clone()).await;
+        assert_eq!(response.nodes_deleted, 1);
+        assert_eq!(response.nodes_imported, 3);
+        assert_eq!(response.relationships_imported, 2);
+        assert_eq!(response.records_skipped, 0);
+        assert_eq!(graph.get_statistics().await.unwrap(), statistics);
+        assert_eq!(export_text(&state, ExportFormat::GraphMl, "tenant-a").await, graphml);
+        
+        // Merging the same document again doesn't duplicate anything
+        import_text(&state, ExportFormat::JsonLd, ImportMode::Merge, jsonld.clone()).await;
+        assert_eq!(graph.get_statistics().await.unwrap(), statistics);
+        
+        // Into an empty graph, from JSON-LD with one malformed record
+        let restored = Arc::new(AppState::with_memory_graph(Config::test_default()));
+        let malformed = jsonld.replacen(r#"{"@id""#, r#"{"no_id": true},{"@id""#, 1);
+        let response = import_text(&restored, ExportFormat::JsonLd, ImportMode::Merge, malformed).await;
+        assert_eq!(response.nodes_imported, 3);
+        assert_eq!(response.records_skipped, 1);
+        assert!(response.errors[0].contains("missing @id"));
+        assert_eq!(export_text(&restored, ExportFormat::JsonLd, "tenant-a").await, jsonld);
+        
+        // Nodes of another tenant in the document are refused
+        let foreign = graphml.replace("tenant-a", "tenant-b");
+        let response = import_text(&restored, ExportFormat::GraphMl, ImportMode::Merge, foreign).await;
+        assert_eq!(response.nodes_imported, 0);
+        assert_eq!(response.records_skipped, 3);
+        assert!(response.errors.iter().all(|error| error.contains("does not match tenant-a")));
+    }
 }
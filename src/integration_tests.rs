@@ -0,0 +1,928 @@
+//! Integration tests against a real Neo4j and Postgres
+//!
+//! Each test starts a Neo4j 5 or Postgres container with `testcontainers`, so
+//! they need a running Docker daemon and only build with `cargo test --features integration`.
+//! They cover what the in-memory backend and the Cypher string tests can't:
+//! labels, properties, and vector indexes as Neo4j actually sees them, and the
+//! migrated Postgres tables.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use testcontainers_modules::neo4j::{Neo4j, Neo4jImage};
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+use testcontainers_modules::testcontainers::ContainerAsync;
+use uuid::Uuid;
+
+use axum::extract::{Path, State};
+use axum::Json;
+
+use crate::config::Config;
+use crate::error::GraphError;
+use crate::graph_db::neo4j_client::{supports_vector_indexes, ChunkVectorSearch, LinkBoosts};
+use crate::graph_db::{GraphStore, LabelMapping, Neo4jClient};
+use crate::handlers::{self, AppState, OwnerScope};
+use crate::models::ids::EntityId;
+use crate::models::{
+    ChunkInput, DataSource, EdgeDirection, Entity, EntityType, ExtractionMethod, IngestChunksRequest, NeighborQuery, RelationshipEvidence,
+    RelationshipType,
+};
+use crate::services::{ChunkProcessor, ChunkRepository, EmbeddingClient};
+
+/// Small vectors keep the corpus readable; indexes are created at this size
+const DIMENSION: usize = 4;
+
+const OWNER: &str = "owner-1";
+
+struct TestGraph {
+    // Dropping the container stops it
+    _container: ContainerAsync<Neo4jImage>,
+    client: Arc<Neo4jClient>,
+}
+
+/// Start Neo4j, create the vector indexes, and wait for them to come online
+async fn start_neo4j(labels: LabelMapping) -> TestGraph {
+    let container = Neo4j::default()
+        .with_version("5.26")
+        .start()
+        .await
+        .expect("Neo4j container failed to start (is Docker running?)");
+    let uri = format!(
+        "bolt://{}:{}",
+        container.get_host().await.unwrap(),
+        container.image().bolt_port_ipv4().unwrap()
+    );
+    let (user, password) = container.image().auth().unwrap();
+
+    let client = Neo4jClient::new(&uri, user, password)
+        .await
+        .unwrap()
+        .with_label_mapping(labels);
+    client.initialize_vector_indexes(DIMENSION).await.unwrap();
+
+    // Vector queries against a populating index silently return nothing
+    let graph = neo4rs::Graph::new(&uri, user, password).await.unwrap();
+    graph.run(neo4rs::query("CALL db.awaitIndexes(60)")).await.unwrap();
+
+    TestGraph { _container: container, client: Arc::new(client) }
+}
+
+fn chunk(content: &str, source_kind: &str, file_path: &str, embedding: [f32; DIMENSION]) -> ChunkInput {
+    serde_json::from_value(serde_json::json!({
+        "id": Uuid::new_v4(),
+        "content": content,
+        "source_kind": source_kind,
+        "source_type": "github",
+        "source_id": format!("repo/{}", file_path),
+        "file_path": file_path,
+        "repo_name": "repo",
+        "owner_id": OWNER,
+        "author": "alice",
+        "embedding": embedding,
+    })).unwrap()
+}
+
+async fn ingest(graph: &TestGraph, chunks: Vec<ChunkInput>) {
+    let mut config = Config::test_default();
+    config.vector_dimension = DIMENSION;
+    let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+    let store: Arc<dyn GraphStore> = graph.client.clone();
+    let processor = ChunkProcessor::new(config, Some(store), embedding_client);
+
+    let response = processor
+        .ingest_chunks(IngestChunksRequest {
+            chunks,
+            extract_entities: Some(true),
+            create_cross_links: Some(true),
+            strict: Some(true),
+            detailed: None,
+            store_chunks: Some(true),
+            dedupe: None,
+            return_evidence: None,
+        })
+        .await
+        .unwrap();
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+}
+
+async fn entity_id(client: &Neo4jClient, entity_type: EntityType, name: &str) -> EntityId {
+    client
+        .find_entities(Some(entity_type.clone()), None, 100, Some(OWNER))
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|(_, entity_name, _)| entity_name == name)
+        .map(|(id, _, _)| EntityId::parse(&id).unwrap())
+        .unwrap_or_else(|| panic!("no {} entity named {}", entity_type.as_str(), name))
+}
+
+#[tokio::test]
+async fn test_ingest_links_docs_to_code() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let code = chunk(
+        "pub struct SessionStore {}\npub fn create_session(user: &User) -> Session { todo!() }",
+        "code",
+        "src/auth/session.rs",
+        [1.0, 0.0, 0.0, 0.0],
+    );
+    let doc = chunk(
+        "Sessions are created by SessionStore when a user logs in.",
+        "document",
+        "docs/auth.md",
+        [0.9, 0.1, 0.0, 0.0],
+    );
+    let unrelated = chunk("Release notes for the billing export.", "document", "docs/billing.md", [0.0, 0.0, 1.0, 0.0]);
+    let (code_id, doc_id, unrelated_id) = (code.id.unwrap(), doc.id.unwrap(), unrelated.id.unwrap());
+    ingest(&graph, vec![code, doc, unrelated]).await;
+
+    let links = graph.client.cross_source_chunks(&[EntityId(doc_id)], "both", 20, Some(OWNER)).await.unwrap();
+    assert!(
+        links.iter().any(|link| link.chunk.chunk_id == code_id && link.relationship == "SEMANTICALLY_SIMILAR"),
+        "{:?}",
+        links
+    );
+    let unrelated_links = graph.client
+        .cross_source_chunks(&[EntityId(unrelated_id)], "both", 20, Some(OWNER))
+        .await
+        .unwrap();
+    assert!(unrelated_links.iter().all(|link| link.chunk.chunk_id != code_id));
+
+    let hits = graph.client
+        .find_similar_chunks(vec![1.0, 0.0, 0.0, 0.0], &ChunkVectorSearch {
+            index_name: "chunk_embedding_idx",
+            limit: 2,
+            min_score: 0.5,
+            exclude_below_min_tokens: false,
+            include_embeddings: false,
+            owner_id: Some(OWNER),
+            source_types: None,
+            repo_name: Some("repo"),
+            path_glob: None,
+            languages: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(hits.first().map(|hit| hit.chunk_id), Some(code_id));
+    assert_eq!(hits.first().and_then(|hit| hit.file_path.as_deref()), Some("src/auth/session.rs"));
+}
+
+#[tokio::test]
+async fn test_linking_query_boosts_chunks_within_temporal_window() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let now = chrono::Utc::now();
+    let dated = |file_path: &str, days_ago: i64| {
+        let mut code = chunk("pub fn refresh_session() {}", "code", file_path, [0.8, 0.6, 0.0, 0.0]);
+        code.commit_date = Some(now - chrono::Duration::days(days_ago));
+        code
+    };
+    let doc = chunk("Sessions are refreshed hourly.", "document", "docs/sessions.md", [1.0, 0.0, 0.0, 0.0]);
+    let (recent, stale) = (dated("src/recent.rs", 2), dated("src/stale.rs", 30));
+    let (doc_id, recent_id, stale_id) = (doc.id.unwrap(), recent.id.unwrap(), stale.id.unwrap());
+    ingest(&graph, vec![doc, recent, stale]).await;
+
+    let boosts = LinkBoosts {
+        explicit_mention: 0.0,
+        author_overlap: 0.0,
+        temporal_proximity: 0.1,
+        temporal_days: Some(7),
+        normalize: false,
+    };
+    let matches = graph.client
+        .find_similar_chunks_for_linking(&EntityId(doc_id), "code", 10, 0.0, &boosts, None)
+        .await
+        .unwrap();
+    let by_target = |id: Uuid| matches.iter().find(|m| m.target_id.as_uuid() == id).unwrap();
+
+    let recent = by_target(recent_id);
+    assert!(recent.has_temporal_proximity);
+    assert_eq!(recent.temporal_distance_days, Some(2));
+    assert!((recent.confidence - (recent.similarity_score + 0.1 * (1.0 - 2.0 / 7.0))).abs() < 1e-4);
+
+    let stale = by_target(stale_id);
+    assert!(!stale.has_temporal_proximity);
+    assert_eq!(stale.temporal_distance_days, Some(30));
+    assert!((stale.confidence - stale.similarity_score).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_linking_query_boosts_mentioned_entities() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let doc = chunk("Call `refresh_session` after login.", "document", "docs/sessions.md", [1.0, 0.0, 0.0, 0.0]);
+    let code = chunk("pub fn refresh_session() {}", "code", "src/session.rs", [0.8, 0.6, 0.0, 0.0]);
+    let other = chunk("pub fn drop_session() {}", "code", "src/drop.rs", [0.8, 0.6, 0.0, 0.0]);
+    let (doc_id, code_id, other_id) = (doc.id.unwrap(), code.id.unwrap(), other.id.unwrap());
+    ingest(&graph, vec![doc, code, other]).await;
+
+    let boosts = LinkBoosts {
+        explicit_mention: 0.15,
+        author_overlap: 0.0,
+        temporal_proximity: 0.0,
+        temporal_days: None,
+        normalize: false,
+    };
+    let matches = graph.client
+        .find_similar_chunks_for_linking(&EntityId(doc_id), "code", 10, 0.0, &boosts, None)
+        .await
+        .unwrap();
+    let by_target = |id: Uuid| matches.iter().find(|m| m.target_id.as_uuid() == id).unwrap();
+
+    let mentioned = by_target(code_id);
+    assert!(mentioned.has_explicit_mention);
+    assert!((mentioned.confidence - (mentioned.similarity_score + 0.15)).abs() < 1e-4);
+    assert!(!by_target(other_id).has_explicit_mention);
+}
+
+#[tokio::test]
+async fn test_server_version_supports_vector_indexes() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let version = graph.client.fetch_server_version().await.unwrap();
+    assert!(version.starts_with("5.26"), "{}", version);
+    assert_eq!(supports_vector_indexes(&version), Some(true));
+}
+
+#[tokio::test]
+async fn test_vector_indexes_are_recreated_at_a_new_dimension() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let dimension_of = |indexes: &[crate::models::VectorIndexInfo], name: &str| {
+        indexes.iter().find(|index| index.name == name).map(|index| index.dimension)
+    };
+
+    let before = graph.client.embedding_vector_indexes().await.unwrap();
+    assert_eq!(dimension_of(&before, "chunk_embedding_idx"), Some(DIMENSION));
+
+    graph.client.drop_vector_index("chunk_embedding_idx").await.unwrap();
+    let dropped = graph.client.embedding_vector_indexes().await.unwrap();
+    assert_eq!(dimension_of(&dropped, "chunk_embedding_idx"), None);
+    graph.client.create_vector_index("chunk_embedding_idx", "CHUNK", "embedding", DIMENSION).await.unwrap();
+
+    // Every embedding index comes back at the new size
+    let recreated = graph.client.recreate_vector_indexes(8).await.unwrap();
+    assert_eq!(recreated.indexes.len(), before.len());
+    assert!(recreated.failed.is_empty(), "{:?}", recreated.failed);
+    let after = graph.client.embedding_vector_indexes().await.unwrap();
+    assert!(after.iter().all(|index| index.dimension == 8), "{:?}", after);
+    assert_eq!(dimension_of(&after, "chunk_embedding_idx"), Some(8));
+
+    assert!(matches!(
+        graph.client.drop_vector_index("chunk_embedding_idx; DROP").await,
+        Err(crate::error::GraphError::InvalidRequest(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_traversal_follows_extracted_relationships() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    ingest(&graph, vec![
+        chunk(
+            "pub struct SessionStore {}\npub fn create_session() {}\npub fn drop_session() {}",
+            "code",
+            "src/auth/session.rs",
+            [1.0, 0.0, 0.0, 0.0],
+        ),
+        chunk("use crate::auth::session;\nfn login() {}", "code", "src/login.rs", [0.0, 1.0, 0.0, 0.0]),
+    ]).await;
+
+    let store = entity_id(&graph.client, EntityType::Class, "SessionStore").await;
+    let neighbors = graph.client
+        .get_neighbors(&store, Some(&[RelationshipType::Contains]), "outgoing", 1, &NeighborQuery::default(), Some(OWNER))
+        .await
+        .unwrap();
+    let mut names: Vec<_> = neighbors.iter().map(|neighbor| neighbor.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, ["create_session", "drop_session"]);
+    assert!(neighbors.iter().all(|neighbor| neighbor.entity_type == "FUNCTION"), "{:?}", neighbors);
+
+    // Other tenants see nothing
+    let hidden = graph.client
+        .get_neighbors(&store, None, "both", 2, &NeighborQuery::default(), Some("owner-2"))
+        .await
+        .unwrap();
+    assert!(hidden.is_empty());
+
+    let paths = graph.client.find_paths(&store, None, "outgoing", 1, 10, Some(OWNER)).await.unwrap();
+    assert_eq!(paths.len(), 2);
+    assert!(paths.iter().all(|path| path.relationships == ["CONTAINS"]));
+
+    // The import stub resolves to the chunk of the file it names
+    let stub = entity_id(&graph.client, EntityType::Module, "crate::auth::session").await;
+    let (rows, _) = graph.client
+        .run_read_query(
+            "MATCH (stub {id: $id})-[:RESOLVES_TO]->(c:CHUNK) RETURN c.file_path AS file_path",
+            HashMap::from([("id".to_string(), serde_json::json!(stub))]),
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows, [serde_json::json!({"file_path": "src/auth/session.rs"})]);
+}
+
+#[tokio::test]
+async fn test_reingest_and_batch_edges_are_not_duplicated() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let (code_id, doc_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let chunks = || {
+        let mut code = chunk("pub struct SessionStore {}\npub fn create_session() {}", "code", "src/auth/session.rs", [1.0, 0.0, 0.0, 0.0]);
+        let mut doc = chunk("Sessions are created by SessionStore.", "document", "docs/auth.md", [0.9, 0.1, 0.0, 0.0]);
+        code.id = Some(code_id);
+        doc.id = Some(doc_id);
+        vec![code, doc]
+    };
+    ingest(&graph, chunks()).await;
+    let statistics = graph.client.get_statistics().await.unwrap();
+    assert!(statistics["relationship_count"].as_i64().unwrap() > 0);
+    ingest(&graph, chunks()).await;
+    assert_eq!(graph.client.get_statistics().await.unwrap()["by_relationship"], statistics["by_relationship"]);
+
+    let store = entity_id(&graph.client, EntityType::Class, "SessionStore").await;
+    let create = entity_id(&graph.client, EntityType::Function, "create_session").await;
+    let edges = vec![(store, create, RelationshipType::Calls, 0.6)];
+    graph.client.create_relationships_batch(&edges, ExtractionMethod::PatternMatch).await.unwrap();
+    graph.client.create_relationships_batch(&edges, ExtractionMethod::PatternMatch).await.unwrap();
+    graph.client.create_relationship(&store, &create, RelationshipType::Calls, 0.8, ExtractionMethod::Manual, None).await.unwrap();
+    let calls = [RelationshipType::Calls];
+    let neighbors = graph.client
+        .get_neighbors(&store, Some(&calls), "outgoing", 1, &NeighborQuery::default(), Some(OWNER))
+        .await
+        .unwrap();
+    assert_eq!(neighbors.len(), 1);
+    assert!((neighbors[0].confidence - 0.8).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_mapped_labels_reach_neo4j() {
+    let mapping = HashMap::from([
+        ("class".to_string(), "Class".to_string()),
+        ("function".to_string(), "Function".to_string()),
+    ]);
+    let graph = start_neo4j(LabelMapping::new(&mapping).unwrap()).await;
+    ingest(&graph, vec![chunk(
+        "pub struct SessionStore {}\npub fn create_session() {}",
+        "code",
+        "src/auth/session.rs",
+        [1.0, 0.0, 0.0, 0.0],
+    )]).await;
+
+    let classes = graph.client.find_entities(Some(EntityType::Class), None, 10, Some(OWNER)).await.unwrap();
+    assert_eq!(classes.len(), 1);
+    assert_eq!(classes[0].2, "Class");
+
+    let function = entity_id(&graph.client, EntityType::Function, "create_session").await;
+    let found = graph.client.get_entity(&function, Some(OWNER)).await.unwrap();
+    assert_eq!(found.map(|(_, _, label)| label).as_deref(), Some("Function"));
+}
+
+#[tokio::test]
+async fn test_source_with_quotes_is_matched_literally() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let mut entity = Entity::new(
+        EntityType::Document,
+        DataSource::LocalFile,
+        "wiki/onboarding".to_string(),
+        "Onboarding".to_string(),
+        HashMap::from([("owner_id".to_string(), serde_json::json!(OWNER))]),
+    );
+    entity.source = "team's wiki".to_string();
+    graph.client.upsert_entity_node(&entity).await.unwrap();
+
+    let found = graph.client.find_entities(None, Some("team's wiki"), 10, Some(OWNER)).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].1, "Onboarding");
+
+    // A quote-breaking value is just a source that matches nothing
+    let injected = graph.client.find_entities(None, Some("x' OR '1'='1"), 10, Some(OWNER)).await.unwrap();
+    assert!(injected.is_empty());
+}
+
+#[tokio::test]
+async fn test_property_update_merges_and_keeps_created_at() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let entity = Entity::new(
+        EntityType::Function,
+        DataSource::LocalFile,
+        "auth.rs#login".to_string(),
+        "login".to_string(),
+        HashMap::from([
+            ("owner_id".to_string(), serde_json::json!(OWNER)),
+            ("language".to_string(), serde_json::json!("rust")),
+            ("lines".to_string(), serde_json::json!(40)),
+        ]),
+    );
+    let id = EntityId(entity.id);
+    graph.client.upsert_entity_node(&entity).await.unwrap();
+    let timestamps = || async {
+        let (rows, _) = graph.client
+            .run_read_query(
+                "MATCH (n {id: $id}) RETURN toString(n.created_at) as created_at, toString(n.updated_at) as updated_at",
+                HashMap::from([("id".to_string(), serde_json::json!(id.to_string()))]),
+                1,
+            )
+            .await
+            .unwrap();
+        rows[0].clone()
+    };
+    let before = timestamps().await;
+    assert!(before["updated_at"].is_null());
+
+    let patch = serde_json::json!({"lines": 55}).as_object().unwrap().clone();
+    let (_, name, _, properties) = graph.client
+        .update_entity_properties(&id, None, &patch, Some(OWNER))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(name, "login");
+    assert_eq!(properties["lines"], 55);
+    assert_eq!(properties["language"], "rust");
+
+    let after = timestamps().await;
+    assert_eq!(after["created_at"], before["created_at"]);
+    assert!(after["updated_at"].is_string());
+
+    // Other tenants can't see the entity, so there is nothing to update
+    assert!(graph.client.update_entity_properties(&id, None, &patch, Some("owner-2")).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_orphans_are_found_and_pruned() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let mut ids = Vec::new();
+    for name in ["login", "hash", "stale"] {
+        let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(OWNER))]);
+        let entity = Entity::new(EntityType::Function, DataSource::LocalFile, name.to_string(), name.to_string(), properties);
+        graph.client.upsert_entity_node(&entity).await.unwrap();
+        ids.push(EntityId(entity.id));
+    }
+    let created = graph.client
+        .create_relationship(&ids[0], &ids[1], RelationshipType::Calls, 0.9, ExtractionMethod::Manual, None)
+        .await
+        .unwrap();
+    let updated = graph.client
+        .create_relationship(&ids[0], &ids[1], RelationshipType::Calls, 0.8, ExtractionMethod::Manual, None)
+        .await
+        .unwrap();
+    assert_eq!(updated.id, created.id);
+    assert!(updated.element_id.is_some());
+
+    let orphans = graph.client.find_orphans(Some("FUNCTION"), 10, Some(OWNER)).await.unwrap();
+    assert_eq!(orphans.len(), 1);
+    assert_eq!(orphans[0].id, ids[2].to_string());
+    assert_eq!(orphans[0].name.as_deref(), Some("stale"));
+    assert!(graph.client.find_orphans(Some("FUNCTION"), 10, Some("owner-2")).await.unwrap().is_empty());
+
+    // Chunks without links are only reported when asked for by label
+    let unlinked = chunk("fn unlinked() {}", "code", "src/unlinked.rs", [1.0, 0.0, 0.0, 0.0]).into_chunk();
+    graph.client.upsert_chunk_node(&unlinked, None, false).await.unwrap();
+    assert!(graph.client.find_orphans(None, 10, Some(OWNER)).await.unwrap().iter().all(|o| o.label != "CHUNK"));
+    assert_eq!(graph.client.find_orphans(Some("CHUNK"), 10, Some(OWNER)).await.unwrap()[0].id, unlinked.id.to_string());
+
+    // Connected nodes survive even when asked for by id
+    let ids_text: Vec<_> = ids.iter().map(EntityId::to_string).collect();
+    assert_eq!(graph.client.delete_orphans(&ids_text, Some(OWNER)).await.unwrap(), vec![ids_text[2].clone()]);
+    assert!(graph.client.find_orphans(None, 10, Some(OWNER)).await.unwrap().is_empty());
+    assert!(graph.client.get_entity(&ids[0], Some(OWNER)).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_exact_chunk_lookup_by_entity_name_and_path() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let code = chunk("pub fn verify_token() {}", "code", "src/auth.rs", [1.0, 0.0, 0.0, 0.0]);
+    let code_id = code.id.unwrap();
+    ingest(&graph, vec![code]).await;
+
+    let search = ChunkVectorSearch {
+        index_name: "chunk_embedding_idx",
+        limit: 5,
+        min_score: 0.0,
+        exclude_below_min_tokens: false,
+        include_embeddings: false,
+        owner_id: Some(OWNER),
+        source_types: None,
+        repo_name: None,
+        path_glob: None,
+        languages: None,
+    };
+    for text in ["verify_token", "src/auth.rs"] {
+        let hits = graph.client.find_exact_chunks(text, &search).await.unwrap();
+        assert_eq!(hits.iter().map(|hit| hit.chunk_id).collect::<Vec<_>>(), [code_id], "{}", text);
+    }
+    assert!(graph.client.find_exact_chunks("verify", &search).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_batch_creates_relationships_per_type() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let mut ids = Vec::new();
+    for i in 0..101 {
+        let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(OWNER))]);
+        let entity = Entity::new(
+            EntityType::Function, DataSource::LocalFile, format!("fn_{}", i), format!("fn_{}", i), properties,
+        );
+        graph.client.upsert_entity_node(&entity).await.unwrap();
+        ids.push(EntityId(entity.id));
+    }
+
+    // 100 edges in a chain, alternating types, plus one to a node that doesn't exist
+    let mut edges: Vec<_> = (0..100)
+        .map(|i| {
+            let rel_type = if i % 2 == 0 { RelationshipType::Calls } else { RelationshipType::Imports };
+            (ids[i], ids[i + 1], rel_type, 0.7)
+        })
+        .collect();
+    let missing = EntityId(Uuid::new_v4());
+    edges.push((ids[0], missing, RelationshipType::Calls, 0.7));
+
+    let result = graph.client.create_relationships_batch(&edges, ExtractionMethod::PatternMatch).await.unwrap();
+    assert_eq!(result.created, 100);
+    assert_eq!(result.missing.len(), 1);
+    assert_eq!(result.missing[0].1, missing);
+
+    let calls = [RelationshipType::Calls];
+    let neighbors = graph.client
+        .get_neighbors(&ids[0], Some(&calls), "outgoing", 1, &NeighborQuery::default(), Some(OWNER))
+        .await
+        .unwrap();
+    assert_eq!(neighbors.len(), 1);
+    assert_eq!(neighbors[0].name, "fn_1");
+    assert!((neighbors[0].confidence - 0.7).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_tree_edges_report_shallowest_depth() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let mut ids = HashMap::new();
+    for (entity_type, name) in [
+        (EntityType::Module, "auth"),
+        (EntityType::Class, "Token"),
+        (EntityType::Function, "refresh"),
+    ] {
+        let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(OWNER))]);
+        let entity = Entity::new(entity_type, DataSource::LocalFile, name.to_string(), name.to_string(), properties);
+        graph.client.upsert_entity_node(&entity).await.unwrap();
+        ids.insert(name, EntityId(entity.id));
+    }
+    for (from, to) in [("auth", "Token"), ("Token", "refresh"), ("refresh", "auth")] {
+        graph.client
+            .create_relationship(&ids[from], &ids[to], RelationshipType::Contains, 1.0, ExtractionMethod::Manual, None)
+            .await
+            .unwrap();
+    }
+
+    let contains = [RelationshipType::Contains];
+    let edges = graph.client.tree_edges(&ids["auth"], &contains, 5, 100, Some(OWNER)).await.unwrap();
+    let reached: Vec<_> = edges.iter()
+        .map(|edge| (edge.parent_id.as_str(), edge.child.name.as_str(), edge.depth))
+        .collect();
+    // The cycle back to the root comes back as an edge to an already-seen node
+    assert_eq!(reached, [
+        (ids["auth"].to_string().as_str(), "Token", 1),
+        (ids["Token"].to_string().as_str(), "refresh", 2),
+        (ids["refresh"].to_string().as_str(), "auth", 3),
+    ]);
+}
+
+#[tokio::test]
+async fn test_chunk_ids_held_by_another_owner_are_rejected() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let original = chunk("pub fn login() {}", "code", "src/auth.rs", [1.0, 0.0, 0.0, 0.0]).into_chunk();
+    graph.client.upsert_chunk_node(&original, None, false).await.unwrap();
+
+    let mut stolen = original.clone();
+    stolen.owner_id = "owner-2".to_string();
+    stolen.content = "pub fn steal() {}".to_string();
+    let result = graph.client.upsert_chunk_node(&stolen, None, false).await;
+    assert!(matches!(result, Err(GraphError::InvalidRequest(_))), "{:?}", result);
+    assert!(graph.client.get_entity(&EntityId(original.id), Some(OWNER)).await.unwrap().is_some());
+    assert!(graph.client.get_entity(&EntityId(original.id), Some("owner-2")).await.unwrap().is_none());
+
+    // The owner can still rewrite it, and the rejected content never landed
+    let previous = graph.client.upsert_chunk_node(&original, None, false).await.unwrap();
+    assert_eq!(previous, Some(original.content_hash.clone()));
+}
+
+#[tokio::test]
+async fn test_cross_source_links_report_edge_direction() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let mut ids = Vec::new();
+    for (name, source_kind) in [("guide", "document"), ("handler", "code")] {
+        let stored = chunk(name, source_kind, &format!("{}.txt", name), [1.0, 0.0, 0.0, 0.0]).into_chunk();
+        graph.client.upsert_chunk_node(&stored, None, false).await.unwrap();
+        ids.push(EntityId(stored.id));
+    }
+    let (doc_id, code_id) = (ids[0], ids[1]);
+    graph.client
+        .create_relationship(&doc_id, &code_id, RelationshipType::Explains, 0.9, ExtractionMethod::Manual, None)
+        .await
+        .unwrap();
+
+    let from_doc = graph.client.cross_source_chunks(&[doc_id], "outgoing", 10, Some(OWNER)).await.unwrap();
+    assert_eq!(from_doc.len(), 1);
+    assert_eq!((from_doc[0].chunk.chunk_id, from_doc[0].direction), (code_id.as_uuid(), EdgeDirection::Outgoing));
+    assert_eq!(from_doc[0].relationship, "EXPLAINS");
+    assert!(graph.client.cross_source_chunks(&[doc_id], "incoming", 10, Some(OWNER)).await.unwrap().is_empty());
+
+    for direction in ["incoming", "both"] {
+        let from_code = graph.client.cross_source_chunks(&[code_id], direction, 10, Some(OWNER)).await.unwrap();
+        assert_eq!(from_code.len(), 1, "{}", direction);
+        assert_eq!((from_code[0].chunk.chunk_id, from_code[0].direction), (doc_id.as_uuid(), EdgeDirection::Incoming));
+
+        let subgraph = graph.client.get_cross_source_subgraph(&code_id, 1, direction, 0.0, 10, Some(OWNER)).await.unwrap();
+        assert_eq!(subgraph.edges.len(), 1, "{}", direction);
+        assert_eq!((subgraph.edges[0].to_id, subgraph.edges[0].direction), (doc_id, EdgeDirection::Incoming));
+    }
+    assert!(graph.client.cross_source_chunks(&[code_id], "outgoing", 10, Some(OWNER)).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cross_source_chunks_keep_strongest_links_per_hit() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let store = |name: &str, source_kind: &str| {
+        let stored = chunk(name, source_kind, &format!("{}.txt", name), [1.0, 0.0, 0.0, 0.0]).into_chunk();
+        let client = graph.client.clone();
+        async move {
+            client.upsert_chunk_node(&stored, None, false).await.unwrap();
+            EntityId(stored.id)
+        }
+    };
+    let doc_id = store("guide", "document").await;
+    for i in 0..30 {
+        let code_id = store(&format!("fn_{}", i), "code").await;
+        let confidence = ((i * 7) % 30) as f32 / 100.0 + 0.5;
+        graph.client.create_cross_source_link(&doc_id, &code_id, confidence, confidence, &[]).await.unwrap();
+    }
+    let other_doc_id = store("notes", "document").await;
+    let other_code_id = store("other", "code").await;
+    graph.client.create_cross_source_link(&other_doc_id, &other_code_id, 0.1, 0.1, &[]).await.unwrap();
+
+    let linked = graph.client.cross_source_chunks(&[doc_id, other_doc_id], "both", 5, Some(OWNER)).await.unwrap();
+    let from_doc: Vec<f32> = linked.iter().filter(|link| link.hit_id == doc_id).map(|link| link.confidence).collect();
+    assert_eq!(from_doc.len(), 5);
+    assert!(from_doc.iter().all(|confidence| *confidence > 0.74), "{:?}", from_doc);
+    assert_eq!(linked.last().map(|link| link.chunk.chunk_id), Some(other_code_id.as_uuid()));
+}
+
+#[tokio::test]
+async fn test_cross_source_subgraph_follows_links_transitively() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let mut ids = HashMap::new();
+    for (name, source_kind) in [("hit", "code"), ("doc", "document"), ("explained", "code")] {
+        let stored = chunk(name, source_kind, &format!("{}.txt", name), [1.0, 0.0, 0.0, 0.0]).into_chunk();
+        graph.client.upsert_chunk_node(&stored, None, false).await.unwrap();
+        ids.insert(name, EntityId(stored.id));
+    }
+    // Only links between chunks are followed
+    let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(OWNER))]);
+    let entity = Entity::new(EntityType::Document, DataSource::LocalFile, "entity".to_string(), "entity".to_string(), properties);
+    graph.client.upsert_entity_node(&entity).await.unwrap();
+    ids.insert("entity", EntityId(entity.id));
+    for (from, to, confidence) in [("hit", "doc", 0.9), ("doc", "explained", 0.8), ("explained", "hit", 0.7), ("hit", "entity", 0.95)] {
+        graph.client.create_cross_source_link(&ids[from], &ids[to], confidence, confidence, &[]).await.unwrap();
+    }
+
+    let reached = |subgraph: crate::graph_db::neo4j_client::CrossSourceSubgraph| {
+        subgraph.edges.iter().map(|edge| (edge.from_id, edge.to_id, edge.depth)).collect::<Vec<_>>()
+    };
+    let edge = |from: &str, to: &str, depth: usize| (ids[from], ids[to], depth);
+
+    let one_hop = graph.client.get_cross_source_subgraph(&ids["hit"], 1, "both", 0.75, 20, Some(OWNER)).await.unwrap();
+    assert_eq!(reached(one_hop), [edge("hit", "doc", 1)]);
+    let two_hops = graph.client.get_cross_source_subgraph(&ids["hit"], 2, "both", 0.75, 20, Some(OWNER)).await.unwrap();
+    assert_eq!(reached(two_hops), [edge("hit", "doc", 1), edge("doc", "explained", 2)]);
+
+    // The weaker closing link reaches `explained` first; the cycle adds nothing more
+    let cyclic = graph.client.get_cross_source_subgraph(&ids["hit"], 3, "both", 0.0, 20, Some(OWNER)).await.unwrap();
+    assert_eq!(reached(cyclic), [edge("hit", "doc", 1), edge("hit", "explained", 1)]);
+}
+
+#[tokio::test]
+async fn test_export_pages_follow_id_order_and_owner() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let mut ids = Vec::new();
+    // Pages merge the id ranges of every label
+    for (entity_type, name, owner) in [
+        (EntityType::Function, "login", OWNER),
+        (EntityType::Class, "Hasher", OWNER),
+        (EntityType::Function, "other", "owner-2"),
+    ] {
+        let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(owner))]);
+        let entity = Entity::new(entity_type, DataSource::LocalFile, name.to_string(), name.to_string(), properties);
+        graph.client.upsert_entity_node(&entity).await.unwrap();
+        ids.push(EntityId(entity.id));
+    }
+    for to in [&ids[1], &ids[2]] {
+        graph.client
+            .create_relationship(&ids[0], to, RelationshipType::Calls, 0.9, ExtractionMethod::AstExtraction, None)
+            .await
+            .unwrap();
+    }
+    graph.client.set_node_embedding(&ids[0], vec![1.0, 0.0, 0.0, 0.0], "test", "test").await.unwrap();
+
+    let mut owned = vec![ids[0].to_string(), ids[1].to_string()];
+    owned.sort();
+    let first = graph.client.export_page(Some(OWNER), None, 1, false).await.unwrap();
+    let second = graph.client.export_page(Some(OWNER), Some(&first[0].id), 1, false).await.unwrap();
+    let rest = graph.client.export_page(Some(OWNER), Some(&second[0].id), 1, false).await.unwrap();
+    assert_eq!(vec![first[0].id.clone(), second[0].id.clone()], owned);
+    assert!(rest.is_empty());
+
+    let login = first.iter().chain(&second).find(|node| node.id == ids[0].to_string()).unwrap();
+    assert!(login.labels.contains(&"FUNCTION".to_string()));
+    assert_eq!(login.properties["name"], "login");
+    assert!(!login.properties.contains_key("embedding"));
+    // The edge to the other owner's node is left out
+    assert_eq!(login.edges.len(), 1);
+    assert_eq!(login.edges[0].target, ids[1].to_string());
+    assert_eq!(login.edges[0].rel_type, "CALLS");
+    assert!((login.edges[0].confidence.unwrap() - 0.9).abs() < 1e-6);
+
+    let all = graph.client.export_page(None, None, 10, true).await.unwrap();
+    assert_eq!(all.len(), 3);
+    assert!(all.iter().any(|node| node.properties.contains_key("embedding")));
+}
+
+#[tokio::test]
+async fn test_import_restores_exported_nodes_and_edges() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    let mut ids = Vec::new();
+    for name in ["login", "hash"] {
+        let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(OWNER))]);
+        let entity = Entity::new(EntityType::Function, DataSource::LocalFile, name.to_string(), name.to_string(), properties);
+        graph.client.upsert_entity_node(&entity).await.unwrap();
+        ids.push(EntityId(entity.id));
+    }
+    graph.client
+        .create_relationship(&ids[0], &ids[1], RelationshipType::Calls, 0.9, ExtractionMethod::AstExtraction, None)
+        .await
+        .unwrap();
+    let statistics = graph.client.get_statistics().await.unwrap();
+    let exported = graph.client.export_page(Some(OWNER), None, 10, true).await.unwrap();
+
+    assert_eq!(graph.client.prune_owner_graph(OWNER, &[], &[], 1).await.unwrap(), 2);
+    let written = graph.client.import_nodes(&exported, Some(OWNER)).await.unwrap();
+    assert_eq!(written.len(), 2);
+    let edges: Vec<_> = exported.iter()
+        .flat_map(|node| node.edges.iter().map(|edge| (node.id.clone(), edge.clone())))
+        .collect();
+    assert_eq!(graph.client.import_relationships(&edges, Some(OWNER)).await.unwrap(), 1);
+    // Importing again updates in place
+    assert_eq!(graph.client.import_relationships(&edges, Some(OWNER)).await.unwrap(), 1);
+
+    assert_eq!(graph.client.get_statistics().await.unwrap(), statistics);
+    let restored = graph.client.export_page(Some(OWNER), None, 10, true).await.unwrap();
+    assert_eq!(restored[0].properties, exported[0].properties);
+    assert_eq!(restored[0].labels, exported[0].labels);
+
+    // Merging an export without embeddings, or under another label, updates the stored node in place
+    graph.client.set_node_embedding(&ids[0], vec![1.0, 0.0, 0.0, 0.0], "test", "test").await.unwrap();
+    let mut relabelled = graph.client.export_page(Some(OWNER), None, 10, false).await.unwrap();
+    for node in &mut relabelled {
+        node.labels = vec!["CLASS".to_string()];
+    }
+    assert_eq!(graph.client.import_nodes(&relabelled, Some(OWNER)).await.unwrap().len(), 2);
+    let merged = graph.client.export_page(Some(OWNER), None, 10, true).await.unwrap();
+    assert_eq!(merged.len(), 2);
+    assert!(merged.iter().all(|node| node.labels == ["FUNCTION"]));
+    assert!(merged.iter().any(|node| node.properties.contains_key("embedding")));
+
+    // Another owner can't take over these ids
+    let taken = graph.client.import_nodes(&exported, Some("owner-2")).await.unwrap();
+    assert!(taken.is_empty());
+}
+
+#[tokio::test]
+async fn test_read_queries_run_in_a_rolled_back_transaction() {
+    let graph = start_neo4j(LabelMapping::default()).await;
+    for name in ["a", "b", "c"] {
+        let entity = Entity::new(EntityType::Function, DataSource::LocalFile, name.to_string(), name.to_string(), HashMap::new());
+        graph.client.upsert_entity_node(&entity).await.unwrap();
+    }
+    let names = "MATCH (n:FUNCTION) RETURN n.name AS name ORDER BY name";
+
+    // A truncated result drops its transaction mid-stream; the pool must still hand out a clean connection
+    for _ in 0..3 {
+        let (rows, truncated) = graph.client.run_read_query(names, HashMap::new(), 2).await.unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(truncated);
+    }
+    let (rows, truncated) = graph.client.run_read_query(names, HashMap::new(), 10).await.unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(!truncated);
+}
+
+/// Start Postgres and apply the migrations
+async fn start_postgres() -> (ContainerAsync<Postgres>, PgPool) {
+    let container = Postgres::default()
+        .start()
+        .await
+        .expect("Postgres container failed to start (is Docker running?)");
+    let url = format!(
+        "postgres://postgres:postgres@{}:{}/postgres",
+        container.get_host().await.unwrap(),
+        container.get_host_port_ipv4(5432).await.unwrap()
+    );
+    let pool = PgPool::connect(&url).await.unwrap();
+    sqlx::migrate!().run(&pool).await.unwrap();
+    (container, pool)
+}
+
+#[tokio::test]
+async fn test_chunk_repository_round_trip() {
+    let (_container, pool) = start_postgres().await;
+    let repository = ChunkRepository::new(pool);
+
+    let mut chunk = chunk("pub fn login() {}", "code", "src/auth.rs", [1.0, 0.0, 0.0, 0.0]).into_chunk();
+    chunk.metadata = serde_json::json!({ "symbols": ["login"] });
+    repository.insert_chunks([&chunk]).await.unwrap();
+
+    let stored = repository.get_chunk(chunk.id).await.unwrap().unwrap();
+    assert_eq!(stored.content, "pub fn login() {}");
+    assert_eq!(stored.file_path.as_deref(), Some("src/auth.rs"));
+    assert_eq!(stored.metadata["symbols"][0], "login");
+
+    // Re-ingesting the same id replaces the row but keeps created_at
+    let mut updated = chunk.clone();
+    updated.content = "pub fn login(user: &User) {}".to_string();
+    updated.created_at = chunk.created_at + chrono::Duration::days(1);
+    repository.insert_chunks([&updated]).await.unwrap();
+    let stored = repository.get_chunk(chunk.id).await.unwrap().unwrap();
+    assert_eq!(stored.content, "pub fn login(user: &User) {}");
+    assert_eq!(stored.created_at.timestamp_micros(), chunk.created_at.timestamp_micros());
+
+    assert!(repository.get_chunk(Uuid::new_v4()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_ingest_persists_chunks_and_link_evidence() {
+    let (_container, pool) = start_postgres().await;
+    let repository = Arc::new(ChunkRepository::new(pool));
+    let config = Config::test_default();
+    let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+    let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+    let processor = ChunkProcessor::new(config, Some(graph), embedding_client)
+        .with_chunk_repository(Some(repository.clone()));
+
+    let (code_id, doc_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let request = || {
+        let mut code = chunk("pub fn login(user: &User) -> Session {}", "code", "src/auth.rs", [1.0, 0.0, 0.0, 0.0]);
+        let mut doc = chunk("Login creates a session for the user.", "document", "docs/auth.md", [0.9, 0.1, 0.0, 0.0]);
+        code.id = Some(code_id);
+        doc.id = Some(doc_id);
+        IngestChunksRequest {
+            chunks: vec![code, doc],
+            extract_entities: Some(false),
+            create_cross_links: Some(true),
+            strict: None,
+            detailed: None,
+            store_chunks: Some(true),
+            dedupe: None,
+            return_evidence: None,
+        }
+    };
+    let response = processor.ingest_chunks(request()).await.unwrap();
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let stored = repository.get_chunk(doc_id).await.unwrap().unwrap();
+    assert_eq!(stored.source_kind, "document");
+    assert_eq!(stored.author.as_deref(), Some("alice"));
+    assert!(repository.get_chunk(code_id).await.unwrap().is_some());
+
+    let evidence = repository.evidence_for_chunk(doc_id).await.unwrap();
+    assert!(!evidence.is_empty());
+    assert!(evidence.iter().all(|e| e.relationship_type == "SEMANTICALLY_SIMILAR"));
+    assert!(evidence.iter().any(|e| e.from_chunk_id == doc_id && e.to_chunk_id == code_id));
+
+    // Re-ingesting replaces the rows instead of adding evidence per run
+    let response = processor.ingest_chunks(request()).await.unwrap();
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(repository.evidence_for_chunk(doc_id).await.unwrap().len(), evidence.len());
+
+    // Deleting a chunk removes its evidence with it
+    assert_eq!(repository.delete_chunks(&[doc_id]).await.unwrap(), 1);
+    assert!(repository.get_chunk(doc_id).await.unwrap().is_none());
+    assert!(repository.evidence_for_chunk(code_id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_chunk_returns_row_and_evidence_for_its_owner() {
+    let (_container, pool) = start_postgres().await;
+    let repository = Arc::new(ChunkRepository::new(pool));
+    let mut state = AppState::with_memory_graph(Config::test_default());
+    state.chunk_repository = Some(repository.clone());
+    let state = Arc::new(state);
+
+    let code = chunk("pub fn login() {}", "code", "src/auth.rs", [1.0, 0.0, 0.0, 0.0]).into_chunk();
+    let doc = chunk("Login starts a session.", "document", "docs/auth.md", [0.9, 0.1, 0.0, 0.0]).into_chunk();
+    repository.insert_chunks([&code, &doc]).await.unwrap();
+    let evidence = RelationshipEvidence::new(doc.id, code.id, "EXPLAINS".to_string(), 0.8, ExtractionMethod::VectorSimilarity);
+    repository.insert_evidence(&[evidence]).await.unwrap();
+
+    let get = |id: Uuid, owner: Option<&str>| handlers::get_chunk(State(state.clone()), Path(id), OwnerScope(owner.map(str::to_string)));
+    let Json(detail) = get(code.id, Some(OWNER)).await.unwrap();
+    assert_eq!(detail.chunk.content, "pub fn login() {}");
+    assert_eq!(detail.chunk.file_path.as_deref(), Some("src/auth.rs"));
+    assert_eq!(detail.evidence.len(), 1);
+    assert_eq!((detail.evidence[0].from_chunk_id, detail.evidence[0].to_chunk_id), (doc.id, code.id));
+
+    assert!(matches!(get(code.id, Some("owner-2")).await, Err(GraphError::EntityNotFound(_))));
+    assert!(matches!(get(Uuid::new_v4(), None).await, Err(GraphError::EntityNotFound(_))));
+}
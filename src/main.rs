@@ -4,11 +4,14 @@
 //! with native vector embeddings for hybrid code and documentation search.
 
 use axum::{
-    routing::{get, post},
-    Router,
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post},
+    Extension, Router,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::info;
@@ -21,10 +24,25 @@ mod graph_db;
 mod extractors;
 mod services;
 mod handlers;
+mod graphql;
+mod rate_limit;
+mod shutdown;
+mod utils;
+
+#[cfg(all(test, feature = "integration"))]
+mod integration_tests;
 
 use config::Config;
-use graph_db::Neo4jClient;
+use graph_db::pool::PoolSettings;
+use graph_db::{GraphStore, InMemoryGraph, LabelMapping, Neo4jClient};
+use graph_db::neo4j_client::MIN_VECTOR_INDEX_VERSION;
+use services::embedding_cache::EmbeddingCache;
+use services::entity_resolver::EntityResolver;
+use services::search_cache::SearchCache;
+use services::{ChunkRepository, EmbeddingClient};
+use services::ingest_jobs::IngestJobRegistry;
 use handlers::AppState;
+use rate_limit::RateLimiter;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -43,28 +61,21 @@ async fn main() -> anyhow::Result<()> {
     info!("🔷 Starting Relation Graph Service v{}", env!("CARGO_PKG_VERSION"));
     info!("Port: {}", config.port);
 
+    // Labels are interpolated into Cypher, so a bad mapping must stop startup
+    let labels = LabelMapping::new(&config.entity_label_mapping).map_err(anyhow::Error::msg)?;
+    services::embedding_provider::check_config(&config).map_err(anyhow::Error::msg)?;
+
     // Initialize Neo4j client (now handles both graph AND vector operations)
-    let neo4j_client = match Neo4jClient::new(
-        &config.neo4j_uri,
-        &config.neo4j_user,
-        &config.neo4j_password,
-    ).await {
-        Ok(client) => {
-            info!("✅ Neo4j connection established");
-            
-            // Initialize vector indexes on startup
-            if let Err(e) = client.initialize_vector_indexes(384).await {
-                tracing::warn!("⚠️ Failed to initialize vector indexes: {}. Will retry on first use.", e);
-            } else {
-                info!("✅ Neo4j vector indexes initialized (384-dim)");
-            }
-            
-            Some(Arc::new(client))
-        }
-        Err(e) => {
-            tracing::warn!("⚠️ Neo4j connection failed: {}. Graph and vector operations will be limited.", e);
-            None
-        }
+    let neo4j_client = if config.graph_backend == "memory" {
+        info!("🧪 Using in-memory graph backend; Cypher queries and paths are unavailable");
+        None
+    } else {
+        connect_neo4j(&config, labels.clone()).await?
+    };
+    let graph: Option<Arc<dyn GraphStore>> = match &neo4j_client {
+        Some(client) => Some(client.clone()),
+        None if config.graph_backend == "memory" => Some(Arc::new(InMemoryGraph::new().with_label_mapping(labels))),
+        None => None,
     };
 
     // Initialize PostgreSQL pool
@@ -75,25 +86,125 @@ async fn main() -> anyhow::Result<()> {
     
     info!("✅ PostgreSQL connection established");
 
+    // Tables for canonical entities, chunk rows, and link evidence
+    sqlx::migrate!().run(&db_pool).await
+        .map_err(|e| anyhow::anyhow!("PostgreSQL migrations failed: {}", e))?;
+    info!("✅ PostgreSQL migrations applied");
+    let chunk_repository = Some(Arc::new(ChunkRepository::new(db_pool.clone())));
+
+    // Embedding client (shared so the circuit breaker and cache see every call)
+    let mut embedding_client = EmbeddingClient::from_config(&config);
+    check_embedding_dimension(&config, &embedding_client).await?;
+    if let (Some(redis_url), true) = (&config.redis_url, config.embedding_cache_size > 0) {
+        match EmbeddingCache::redis(redis_url, Duration::from_secs(config.embedding_cache_ttl_secs)).await {
+            Ok(cache) => {
+                info!("✅ Redis embedding cache enabled");
+                embedding_client = embedding_client.with_cache(cache);
+            }
+            Err(e) => {
+                tracing::warn!("⚠️ Redis unavailable ({}), using in-process embedding cache", e);
+            }
+        }
+    }
+
+    // Search result cache (Redis when configured, like the embedding cache)
+    let search_cache = if config.search_cache_size == 0 {
+        None
+    } else {
+        let ttl = Duration::from_secs(config.search_cache_ttl_secs);
+        let redis_cache = match &config.redis_url {
+            Some(redis_url) => match SearchCache::redis(redis_url, ttl).await {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    tracing::warn!("⚠️ Redis unavailable ({}), using in-process search cache", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        Some(Arc::new(redis_cache.unwrap_or_else(|| SearchCache::in_memory(config.search_cache_size, ttl))))
+    };
+
+    // Per-client rate limit (buckets shared through Redis when configured)
+    let rate_limiter = if config.rate_limit_rps > 0.0 {
+        let (rps, burst) = (config.rate_limit_rps, config.rate_limit_burst);
+        let redis_limiter = match &config.redis_url {
+            Some(redis_url) => match RateLimiter::redis(redis_url, rps, burst).await {
+                Ok(limiter) => Some(limiter),
+                Err(e) => {
+                    tracing::warn!("⚠️ Redis unavailable ({}), rate limiting per replica", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let limiter = redis_limiter.unwrap_or_else(|| RateLimiter::in_memory(rps, burst));
+        info!("✅ Rate limit: {} requests/s per client IP ({})", rps, limiter.backend());
+        Some(Arc::new(limiter))
+    } else {
+        None
+    };
+
+    // Canonical entities live in Postgres, or in memory alongside the in-memory graph
+    let entity_resolver = match (config.entity_resolution, config.graph_backend.as_str()) {
+        (false, _) => None,
+        (true, "memory") => Some(Arc::new(EntityResolver::in_memory())),
+        (true, _) => Some(Arc::new(EntityResolver::postgres(db_pool.clone()))),
+    };
+
     // Build application state
     let state = Arc::new(AppState {
         config: config.clone(),
         neo4j: neo4j_client,
+        graph,
+        embedding_client: Arc::new(embedding_client),
         db_pool,
+        ingest_jobs: Arc::new(IngestJobRegistry::new()),
+        search_cache,
+        entity_resolver,
+        chunk_repository,
     });
 
+    // Kept for cleanup once the server has stopped
+    let shutdown_state = state.clone();
+
     // Build HTTP routes
     let app = Router::new()
         // Health check
         .route("/health", get(handlers::health_check))
+        .route("/metrics", get(handlers::metrics))
         
         // Graph entity endpoints
-        .route("/api/graph/entities", post(handlers::create_entity))
-        .route("/api/graph/entities/:id", get(handlers::get_entity))
+        .route(
+            "/api/graph/entities",
+            get(handlers::list_entities).post(handlers::create_entity),
+        )
+        .route(
+            "/api/graph/entities/:id",
+            get(handlers::get_entity).patch(handlers::update_entity),
+        )
         .route("/api/graph/entities/:id/neighbors", get(handlers::get_neighbors))
+        .route("/api/graph/entities/:id/tree", get(handlers::get_entity_tree))
+        .route(
+            "/api/graph/relationships",
+            get(handlers::list_relationships).post(handlers::create_relationship),
+        )
+        .route("/api/graph/path", post(handlers::find_path))
+        .route("/api/graph/query", post(handlers::run_query))
         
         // Chunk ingestion (receives from chunker service)
         .route("/api/graph/chunks", post(handlers::ingest_chunks))
+        .route("/api/graph/chunks/async", post(handlers::ingest_chunks_async))
+        .route("/api/graph/chunks/stream", post(handlers::ingest_chunks_stream))
+        .route("/api/graph/chunks/recent", get(handlers::recent_chunks))
+        .route("/api/graph/chunks/:id", get(handlers::get_chunk))
+        .route("/api/graph/chunks/:id/cross-source", get(handlers::cross_source_matches))
+        .route("/api/graph/commits/:sha/chunks", get(handlers::commit_chunks))
+        .route("/api/graph/jobs/:id", get(handlers::get_ingest_job))
+        .route("/api/graph/repos/:repo_name", delete(handlers::delete_repo))
+        .route("/api/graph/reindex", post(handlers::reindex))
+        .route("/api/graph/maintenance/prune-orphans", post(handlers::prune_orphans))
+        .route("/api/graph/admin/reindex-vectors", post(handlers::reindex_vectors))
         
         // Cross-source linking
         .route("/api/graph/link", post(handlers::trigger_cross_source_linking))
@@ -101,14 +212,29 @@ async fn main() -> anyhow::Result<()> {
         // Hybrid search (main query API)
         .route("/api/search", post(handlers::hybrid_search))
         .route("/api/search/vector", post(handlers::vector_search))
+        .route("/api/search/vector/by-embedding", post(handlers::vector_search_by_embedding))
         .route("/api/search/graph", post(handlers::graph_search))
+        .route("/api/search/stream", get(handlers::search_stream))
         
-        // Statistics
+        // Statistics and schema
         .route("/api/graph/statistics", get(handlers::get_statistics))
+        .route("/api/graph/export", get(handlers::export_graph))
+        .route("/api/graph/import", post(handlers::import_graph))
+        .route("/api/graph/schema", get(handlers::get_schema))
+        
+        // GraphQL (search, entities, neighbors, statistics)
+        .route("/graphql", post(graphql::graphql_handler))
+        .layer(Extension(graphql::build_schema(state.clone())))
         
         // State
         .with_state(state)
-        // Middleware
+        .layer(DefaultBodyLimit::max(config.max_body_bytes));
+    // Middleware
+    let app = match rate_limiter {
+        Some(limiter) => app.layer(middleware::from_fn_with_state(limiter, rate_limit::limit)),
+        None => app,
+    };
+    let app = app
         .layer(TraceLayer::new_for_http())
         .layer(
             CorsLayer::new()
@@ -122,7 +248,101 @@ async fn main() -> anyhow::Result<()> {
     info!("🚀 Relation Graph Service listening on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    shutdown::serve(listener, app, shutdown::signal()).await?;
+
+    // Let background ingestion finish its current writes before closing the pools
+    let timeout = Duration::from_secs(config.shutdown_timeout_secs);
+    let unfinished = shutdown_state.ingest_jobs.drain(timeout).await;
+    if unfinished > 0 {
+        tracing::warn!("⚠️ {} ingestion jobs still running after {}s; stopping anyway", unfinished, timeout.as_secs());
+    }
+    shutdown_state.db_pool.close().await;
+    // neo4rs closes its pooled connections when the last client is dropped
+    drop(shutdown_state);
+    info!("👋 Relation Graph Service stopped");
+
+    Ok(())
+}
 
+/// Compare the provider's output dimension with `VECTOR_DIMENSION`
+///
+/// Vectors of the wrong length can't be stored in or compared against the
+/// vector indexes, so a mismatch stops startup unless
+/// `QUERY_DIMENSION_MISMATCH=skip`. An unreachable provider is only a warning.
+async fn check_embedding_dimension(config: &Config, client: &EmbeddingClient) -> anyhow::Result<()> {
+    match client.dimension().await {
+        Ok(dimension) if dimension == config.vector_dimension => {
+            info!("✅ Embedding provider '{}' ({} dimensions)", client.provider_name(), dimension);
+        }
+        Ok(dimension) => {
+            let message = format!(
+                "Embedding provider '{}' returns {}-dimensional vectors but VECTOR_DIMENSION is {}",
+                client.provider_name(),
+                dimension,
+                config.vector_dimension,
+            );
+            if config.query_dimension_mismatch != "skip" {
+                anyhow::bail!(message);
+            }
+            tracing::warn!("⚠️ {}", message);
+        }
+        Err(e) => {
+            tracing::warn!("⚠️ Could not check embedding provider '{}' dimension: {}", client.provider_name(), e);
+        }
+    }
     Ok(())
 }
+
+/// Connect to Neo4j and prepare vector indexes (None when unreachable)
+/// 
+/// Neo4j is the only vector store, so a server too old for native vector
+/// indexes stops startup instead of leaving every vector query broken.
+async fn connect_neo4j(config: &Config, labels: LabelMapping) -> anyhow::Result<Option<Arc<Neo4jClient>>> {
+    let pool = PoolSettings::from_config(config);
+    info!("Neo4j pool: {} connections, fetch size {}", pool.max_connections, pool.fetch_size);
+    match Neo4jClient::connect(
+        &config.neo4j_uri,
+        &config.neo4j_user,
+        &config.neo4j_password,
+        pool,
+    ).await {
+        Ok(client) => {
+            info!("✅ Neo4j connection established");
+            let client = client.with_label_mapping(labels);
+            let client = if config.auto_create_vector_indexes {
+                client.with_auto_vector_indexes(config.vector_dimension)
+            } else {
+                client
+            };
+            
+            let version = match client.fetch_server_version().await {
+                Ok(version) => Some(version),
+                Err(e) => {
+                    tracing::warn!("⚠️ Could not detect the Neo4j version: {}. Assuming vector index support.", e);
+                    None
+                }
+            };
+            let client = client.with_server_version(version);
+            if let (Some(version), Some(false)) = (client.server_version(), client.native_vector_indexes()) {
+                let (major, minor) = MIN_VECTOR_INDEX_VERSION;
+                anyhow::bail!(
+                    "Neo4j {} has no native vector indexes (needs {}.{}+), and no other vector store is available",
+                    version, major, minor
+                );
+            }
+            
+            // Initialize vector indexes on startup
+            if let Err(e) = client.initialize_vector_indexes(config.vector_dimension).await {
+                tracing::warn!("⚠️ Failed to initialize vector indexes: {}. Will retry on first use.", e);
+            } else {
+                info!("✅ Neo4j vector indexes initialized ({}-dim)", config.vector_dimension);
+            }
+            
+            Ok(Some(Arc::new(client)))
+        }
+        Err(e) => {
+            tracing::warn!("⚠️ Neo4j connection failed: {}. Graph and vector operations will be limited.", e);
+            Ok(None)
+        }
+    }
+}
@@ -2,17 +2,25 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::FromRow;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::error::{GraphError, GraphResult};
+use crate::extractors::code_entities::ExtractionResult;
+use crate::models::{RelationshipEvidence, SemanticLink};
+
 /// Source kind classification for chunks
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
+#[allow(dead_code)]
 pub enum SourceKind {
     Code,
     Document,
 }
 
+#[allow(dead_code)]
 impl SourceKind {
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -57,8 +65,38 @@ pub struct Chunk {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Hex length of the MD5 content hashes written before the switch to SHA-256
+const LEGACY_CONTENT_HASH_LEN: usize = 32;
+
+impl Chunk {
+    /// SHA-256 hex digest of chunk content, stored as `content_hash`
+    pub fn hash_content(content: &str) -> String {
+        format!("{:x}", Sha256::digest(content.as_bytes()))
+    }
+    
+    /// Whether a stored hash predates SHA-256 content hashing
+    /// 
+    /// Chunks ingested earlier keep their MD5 hash until they are re-ingested,
+    /// so it can't be compared with a fresh hash.
+    pub fn is_legacy_hash(hash: &str) -> bool {
+        hash.len() == LEGACY_CONTENT_HASH_LEN
+    }
+    
+    /// Deterministic chunk id for a dedup key (name-based, MD5 UUID)
+    pub fn id_for_dedup_key(key: &str) -> Uuid {
+        uuid::Builder::from_md5_bytes(md5::compute(key.as_bytes()).0).into_uuid()
+    }
+    
+    /// Token count reported by the chunker, or a whitespace-based estimate
+    pub fn token_count_or_estimate(&self) -> i32 {
+        self.token_count
+            .unwrap_or_else(|| self.content.split_whitespace().count() as i32)
+    }
+}
+
 /// Chunk with its embedding for vector operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct ChunkWithEmbedding {
     pub chunk: Chunk,
     pub embedding: Vec<f32>,
@@ -66,6 +104,7 @@ pub struct ChunkWithEmbedding {
 
 /// Metadata for vector storage (Zilliz)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct ChunkVectorMetadata {
     pub chunk_id: String,
     pub source_kind: String,
@@ -85,6 +124,22 @@ pub struct IngestChunksRequest {
     pub chunks: Vec<ChunkInput>,
     pub extract_entities: Option<bool>,
     pub create_cross_links: Option<bool>,
+    /// Reject the whole request instead of skipping invalid chunks
+    pub strict: Option<bool>,
+    /// Include per-chunk extraction results in the response
+    pub detailed: Option<bool>,
+    /// Store chunk nodes and their embeddings (default true)
+    /// 
+    /// When false only extracted entities and relationships are stored, and
+    /// cross-source linking is skipped since it needs chunk vectors.
+    pub store_chunks: Option<bool>,
+    /// Skip chunks whose content the owner already has stored (default false)
+    /// 
+    /// Matches on `content_hash`, so identical text is skipped even when it
+    /// arrives under a new id. Skips are counted in `chunks_skipped`.
+    pub dedupe: Option<bool>,
+    /// Include the evidence behind each cross-source link created (default false)
+    pub return_evidence: Option<bool>,
 }
 
 /// Input format for a single chunk
@@ -111,13 +166,20 @@ pub struct ChunkInput {
     #[serde(default)]
     pub metadata: serde_json::Value,
     pub embedding: Option<Vec<f32>>,
+    /// Stable identity (e.g. `source_id + content_hash`); when set, the chunk id
+    /// is derived from it and any supplied `id` is ignored
+    pub dedup_key: Option<String>,
 }
 
 impl ChunkInput {
     pub fn into_chunk(self) -> Chunk {
-        let content_hash = format!("{:x}", md5::compute(&self.content));
+        let content_hash = Chunk::hash_content(&self.content);
+        let id = match &self.dedup_key {
+            Some(key) => Chunk::id_for_dedup_key(key),
+            None => self.id.unwrap_or_else(Uuid::new_v4),
+        };
         Chunk {
-            id: self.id.unwrap_or_else(Uuid::new_v4),
+            id,
             content: self.content,
             content_hash,
             source_kind: self.source_kind,
@@ -150,5 +212,286 @@ pub struct IngestChunksResponse {
     pub entities_extracted: usize,
     pub relationships_created: usize,
     pub vectors_stored: usize,
+    /// Chunks with empty content, plus duplicates when `dedupe` was requested
+    pub chunks_skipped: usize,
+    /// Chunks stored but excluded from linking (and optionally search) for being below `min_token_count`
+    pub chunks_below_min_tokens: usize,
+    /// Chunk ids that arrived with content different from what was already stored under them
+    pub id_collisions: Vec<Uuid>,
+    pub errors: Vec<String>,
+    /// Per-chunk extraction results (only when `detailed` was requested)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extractions: Option<Vec<ChunkExtraction>>,
+    /// Cross-source links created and why (only when `return_evidence` was requested)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<Vec<SemanticLink>>,
+}
+
+/// Query parameters for `POST /api/graph/chunks/stream`
+/// 
+/// The body is newline-delimited `ChunkInput` JSON, so the options that
+/// `IngestChunksRequest` carries in its body are passed here instead.
+#[derive(Debug, Default, Deserialize)]
+pub struct IngestStreamQuery {
+    /// Chunks per batch (default `EMBEDDING_BATCH_SIZE`)
+    pub batch_size: Option<usize>,
+    pub extract_entities: Option<bool>,
+    pub create_cross_links: Option<bool>,
+    pub store_chunks: Option<bool>,
+    pub dedupe: Option<bool>,
+}
+
+/// One line of the `POST /api/graph/chunks/stream` response
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IngestStreamFrame {
+    /// Outcome of one batch
+    Progress(IngestStreamProgress),
+    /// Sums over every batch, sent once the whole body was read
+    Done(IngestStreamTotals),
+    /// Reading the body failed; lines after the last progress frame were not ingested
+    Error { message: String },
+}
+
+/// Counters for one batch of a streamed ingest
+#[derive(Debug, Default, Serialize)]
+pub struct IngestStreamProgress {
+    /// 1-based batch number
+    pub batch: usize,
+    /// Lines read from the body so far, including blank and malformed ones
+    pub lines_read: usize,
+    pub chunks_ingested: usize,
+    pub entities_extracted: usize,
+    pub relationships_created: usize,
+    pub chunks_skipped: usize,
+    /// Malformed lines since the previous batch plus the batch's own ingest errors
+    pub errors: Vec<String>,
+}
+
+/// Counters for a whole streamed ingest
+#[derive(Debug, Default, Serialize)]
+pub struct IngestStreamTotals {
+    pub batches: usize,
+    pub lines_read: usize,
+    pub chunks_ingested: usize,
+    pub entities_extracted: usize,
+    pub relationships_created: usize,
+    pub chunks_skipped: usize,
+    /// Number of errors reported across all progress frames
+    pub errors: usize,
+}
+
+impl IngestStreamTotals {
+    pub fn add(&mut self, progress: &IngestStreamProgress) {
+        self.batches += 1;
+        self.lines_read = progress.lines_read;
+        self.chunks_ingested += progress.chunks_ingested;
+        self.entities_extracted += progress.entities_extracted;
+        self.relationships_created += progress.relationships_created;
+        self.chunks_skipped += progress.chunks_skipped;
+        self.errors += progress.errors.len();
+    }
+}
+
+/// Query parameters for listing recently ingested chunks
+#[derive(Debug, Deserialize)]
+pub struct RecentChunksQuery {
+    #[serde(default = "default_recent_limit")]
+    pub limit: usize,
+    pub source_type: Option<String>,
+    /// Only chunks updated at or after this time
+    pub since: Option<DateTime<Utc>>,
+}
+
+fn default_recent_limit() -> usize { 50 }
+
+/// Summary of a recently ingested or updated chunk
+#[derive(Debug, Serialize)]
+pub struct RecentChunk {
+    pub chunk_id: Uuid,
+    pub source_kind: String,
+    pub source_type: String,
+    pub source_id: String,
+    pub file_path: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Recently ingested chunks with per-source counts
+#[derive(Debug, Serialize)]
+pub struct RecentChunksResponse {
+    pub chunks: Vec<RecentChunk>,
+    /// Matching chunks per source type (not capped by `limit`)
+    pub counts_by_source_type: HashMap<String, usize>,
+}
+
+/// Chunks attached to a `COMMIT` node
+#[derive(Debug, Serialize)]
+pub struct CommitChunksResponse {
+    pub commit_sha: String,
+    pub committed_at: Option<DateTime<Utc>>,
+    pub chunks: Vec<RecentChunk>,
+}
+
+/// A chunk's stored row and the evidence for its links
+#[derive(Debug, Serialize)]
+pub struct ChunkDetailResponse {
+    pub chunk: Chunk,
+    /// Newest first
+    pub evidence: Vec<RelationshipEvidence>,
+}
+
+/// Counts removed by a repo deletion
+#[derive(Debug, Serialize)]
+pub struct DeleteRepoResponse {
+    pub repo_name: String,
+    pub chunks_deleted: usize,
+    pub entities_deleted: usize,
+    pub relationships_deleted: usize,
+}
+
+/// Backfill embeddings for nodes stored without one
+#[derive(Debug, Deserialize)]
+pub struct ReindexRequest {
+    /// Only nodes with this label (e.g. "CHUNK")
+    pub label: Option<String>,
+    /// Maximum nodes to embed in this call
+    #[serde(default = "default_reindex_limit")]
+    pub limit: usize,
+}
+
+fn default_reindex_limit() -> usize { 500 }
+
+/// Outcome of a reindex call
+#[derive(Debug, Default, Serialize)]
+pub struct ReindexResponse {
+    /// Nodes found without an embedding
+    pub scanned: usize,
+    pub embedded: usize,
+    pub failed: usize,
     pub errors: Vec<String>,
 }
+
+/// Query for `POST /api/graph/maintenance/prune-orphans`
+#[derive(Debug, Deserialize)]
+pub struct PruneOrphansQuery {
+    /// Only report orphans unless explicitly turned off
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+    /// Only nodes with this label (e.g. "CHUNK"); without it, any label but
+    /// [`ORPHAN_PROTECTED_LABELS`]
+    pub label: Option<String>,
+    /// Maximum orphans to report or delete in this call
+    #[serde(default = "default_prune_limit")]
+    pub limit: usize,
+}
+
+fn default_dry_run() -> bool { true }
+
+/// Labels skipped by orphan pruning unless asked for by name
+/// 
+/// Chunks often have no relationships at all (nothing extracted, nothing
+/// linked), and canonical nodes lose theirs only while being re-resolved.
+pub const ORPHAN_PROTECTED_LABELS: [&str; 2] = ["CHUNK", "CANONICAL"];
+
+fn default_prune_limit() -> usize { 1000 }
+
+/// A node with no relationships
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrphanNode {
+    pub id: String,
+    pub label: String,
+    pub name: Option<String>,
+}
+
+/// Orphans found, and how many were deleted (0 on a dry run)
+#[derive(Debug, Serialize)]
+pub struct PruneOrphansResponse {
+    pub dry_run: bool,
+    pub orphans: Vec<OrphanNode>,
+    pub deleted: usize,
+}
+
+/// Largest vector index dimension Neo4j accepts
+pub const MAX_VECTOR_DIMENSION: usize = 4096;
+
+/// Query for `POST /api/graph/admin/reindex-vectors`
+#[derive(Debug, Deserialize)]
+pub struct ReindexVectorsQuery {
+    /// Dimension to recreate the indexes at
+    pub dimension: usize,
+    /// Must be `true`; dropping indexes makes vector search fail until they're back
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+impl ReindexVectorsQuery {
+    pub fn validate(&self) -> GraphResult<()> {
+        if !self.confirm {
+            return Err(GraphError::InvalidRequest(
+                "Recreating vector indexes drops them first; pass confirm=true".to_string(),
+            ));
+        }
+        if self.dimension == 0 || self.dimension > MAX_VECTOR_DIMENSION {
+            return Err(GraphError::InvalidRequest(format!(
+                "dimension must be between 1 and {}, got {}",
+                MAX_VECTOR_DIMENSION, self.dimension
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A vector index over node embeddings
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VectorIndexInfo {
+    pub name: String,
+    pub label: String,
+    pub dimension: usize,
+}
+
+/// A vector index `reindex-vectors` couldn't rebuild
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VectorIndexFailure {
+    pub name: String,
+    pub label: String,
+    /// The old index was dropped, so the label has no vector index until it's retried
+    pub dropped: bool,
+    pub error: String,
+}
+
+/// Indexes dropped and recreated by `reindex-vectors`
+#[derive(Debug, Serialize)]
+pub struct ReindexVectorsResponse {
+    pub dimension: usize,
+    pub indexes: Vec<VectorIndexInfo>,
+    /// Indexes left at their old dimension (`dropped: false`) or missing
+    pub failed: Vec<VectorIndexFailure>,
+}
+
+/// Entities and relationships extracted from a single chunk
+#[derive(Debug, Serialize)]
+pub struct ChunkExtraction {
+    pub chunk_id: Uuid,
+    #[serde(flatten)]
+    pub result: ExtractionResult,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_content_hash_is_deterministic_sha256() {
+        let hash = Chunk::hash_content("fn main() {}");
+        assert_eq!(hash, Chunk::hash_content("fn main() {}"));
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, Chunk::hash_content("fn main() { }"));
+        assert_eq!(
+            Chunk::hash_content(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        
+        assert!(!Chunk::is_legacy_hash(&hash));
+        assert!(Chunk::is_legacy_hash(&format!("{:x}", md5::compute("fn main() {}"))));
+    }
+}
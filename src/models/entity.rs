@@ -1,5 +1,7 @@
 //! Entity types for the knowledge graph
 
+use crate::error::{GraphError, GraphResult};
+use super::NodeElementId;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -24,6 +26,7 @@ pub enum EntityType {
     Document,
     Section,
     Concept,
+    ExternalLink,
     
     // Communication entities
     Message,
@@ -39,6 +42,28 @@ pub enum EntityType {
 }
 
 impl EntityType {
+    /// Every entity type
+    pub const ALL: [EntityType; 18] = [
+        EntityType::Repository,
+        EntityType::File,
+        EntityType::Function,
+        EntityType::Class,
+        EntityType::Module,
+        EntityType::Commit,
+        EntityType::PullRequest,
+        EntityType::Issue,
+        EntityType::Document,
+        EntityType::Section,
+        EntityType::Concept,
+        EntityType::ExternalLink,
+        EntityType::Message,
+        EntityType::Thread,
+        EntityType::Channel,
+        EntityType::Person,
+        EntityType::Organization,
+        EntityType::CodeEntity,
+    ];
+    
     pub fn as_str(&self) -> &'static str {
         match self {
             EntityType::Repository => "repository",
@@ -52,6 +77,7 @@ impl EntityType {
             EntityType::Document => "document",
             EntityType::Section => "section",
             EntityType::Concept => "concept",
+            EntityType::ExternalLink => "external_link",
             EntityType::Message => "message",
             EntityType::Thread => "thread",
             EntityType::Channel => "channel",
@@ -74,6 +100,7 @@ impl EntityType {
             "document" => Some(EntityType::Document),
             "section" => Some(EntityType::Section),
             "concept" => Some(EntityType::Concept),
+            "external_link" => Some(EntityType::ExternalLink),
             "message" => Some(EntityType::Message),
             "thread" => Some(EntityType::Thread),
             "channel" => Some(EntityType::Channel),
@@ -84,7 +111,13 @@ impl EntityType {
         }
     }
     
+    /// Parse user input, rejecting unknown types
+    pub fn parse(s: &str) -> GraphResult<Self> {
+        Self::from_str(s).ok_or_else(|| GraphError::InvalidEntityType(s.to_string()))
+    }
+    
     /// Returns true if this is a code-related entity
+    #[allow(dead_code)]
     pub fn is_code(&self) -> bool {
         matches!(
             self,
@@ -100,11 +133,23 @@ impl EntityType {
         )
     }
     
+    /// Types that may be merged into one canonical entity share a group
+    /// 
+    /// A service can be a class in one repo and a module in another, so those
+    /// (and generic code entities) resolve together.
+    pub fn resolution_group(&self) -> &'static str {
+        match self {
+            EntityType::Class | EntityType::Module | EntityType::CodeEntity => "code_unit",
+            other => other.as_str(),
+        }
+    }
+    
     /// Returns true if this is a document-related entity
+    #[allow(dead_code)]
     pub fn is_document(&self) -> bool {
         matches!(
             self,
-            EntityType::Document | EntityType::Section | EntityType::Concept
+            EntityType::Document | EntityType::Section | EntityType::Concept | EntityType::ExternalLink
         )
     }
 }
@@ -164,6 +209,7 @@ impl DataSource {
     }
     
     /// Returns the source kind (code or document)
+    #[allow(dead_code)]
     pub fn source_kind(&self) -> &'static str {
         match self {
             DataSource::GitHub | DataSource::GitLab | DataSource::Bitbucket | DataSource::LocalFile => "code",
@@ -209,10 +255,51 @@ impl Entity {
         }
     }
     
+    /// Replace the random id with one derived from owner, type, source and `source_id`
+    /// 
+    /// Re-extracting the same entity then updates its node instead of adding
+    /// another one, while another tenant's copy keeps a node of its own.
+    pub fn with_derived_id(mut self) -> Self {
+        let key = format!(
+            "{}:{}:{}:{}",
+            self.owner_id().unwrap_or_default(),
+            self.entity_type,
+            self.source,
+            self.source_id
+        );
+        self.id = uuid::Builder::from_md5_bytes(md5::compute(key.as_bytes()).0).into_uuid();
+        self
+    }
+    
     pub fn get_entity_type(&self) -> Option<EntityType> {
         EntityType::from_str(&self.entity_type)
     }
     
+    /// Tenant that owns this entity (from the `owner_id` property)
+    pub fn owner_id(&self) -> Option<String> {
+        self.properties
+            .get("owner_id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    }
+    
+    /// Repository this entity belongs to (from the `repo_name` property)
+    pub fn repo_name(&self) -> Option<String> {
+        self.properties
+            .get("repo_name")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    }
+    
+    /// Chunk this entity was extracted from (from the `chunk_id` property)
+    pub fn chunk_id(&self) -> Option<String> {
+        self.properties
+            .get("chunk_id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    }
+    
+    #[allow(dead_code)]
     pub fn get_source(&self) -> Option<DataSource> {
         DataSource::from_str(&self.source)
     }
@@ -224,6 +311,7 @@ pub struct CanonicalEntity {
     pub id: Uuid,
     pub entity_type: String,
     pub canonical_name: String,
+    pub owner_id: Option<String>,
     pub merged_properties: serde_json::Value,
     pub source_entities: serde_json::Value,
     pub confidence_score: f32,
@@ -231,6 +319,13 @@ pub struct CanonicalEntity {
     pub updated_at: DateTime<Utc>,
 }
 
+impl CanonicalEntity {
+    /// Number of source entities merged into this one
+    pub fn source_count(&self) -> usize {
+        self.source_entities.as_array().map_or(0, |sources| sources.len())
+    }
+}
+
 /// Request to create a new entity
 #[derive(Debug, Deserialize)]
 pub struct CreateEntityRequest {
@@ -240,14 +335,58 @@ pub struct CreateEntityRequest {
     pub name: String,
     #[serde(default)]
     pub properties: HashMap<String, serde_json::Value>,
+    #[allow(dead_code)]
     pub text_for_embedding: Option<String>,
 }
 
+/// Partial update of an entity (`PATCH /api/graph/entities/:id`)
+/// 
+/// `properties` is merged into the stored properties: keys in the patch
+/// replace existing values, `null` removes a key, and other keys are kept.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateEntityRequest {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub properties: serde_json::Map<String, serde_json::Value>,
+}
+
+impl UpdateEntityRequest {
+    /// Properties that are also stored on the node itself and can't be patched
+    pub const PROTECTED_PROPERTIES: [&'static str; 2] = ["owner_id", "chunk_id"];
+    
+    pub fn validate(&self) -> GraphResult<()> {
+        if let Some(key) = Self::PROTECTED_PROPERTIES.iter().find(|key| self.properties.contains_key(**key)) {
+            return Err(GraphError::InvalidRequest(format!("Property '{}' can't be updated", key)));
+        }
+        if self.name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+            return Err(GraphError::InvalidRequest("name must not be empty".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Apply a property patch to stored properties (see [`UpdateEntityRequest`])
+pub fn merge_properties(
+    current: &serde_json::Value,
+    patch: &serde_json::Map<String, serde_json::Value>,
+) -> serde_json::Value {
+    let mut merged = current.as_object().cloned().unwrap_or_default();
+    for (key, value) in patch {
+        if value.is_null() {
+            merged.remove(key);
+        } else {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    serde_json::Value::Object(merged)
+}
+
 /// Response after creating entity
 #[derive(Debug, Serialize)]
 pub struct CreateEntityResponse {
     pub entity_id: Uuid,
-    pub neo4j_node_id: Option<String>,
+    /// Neo4j `elementId` of the node (`None` on the in-memory backend)
+    pub neo4j_node_id: Option<NodeElementId>,
     pub canonical_id: Option<Uuid>,
     pub resolved: bool,
 }
@@ -1,5 +1,6 @@
 //! Evidence model for tracking how relationships were inferred
 
+use super::EdgeDirection;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -40,6 +41,15 @@ impl ExtractionMethod {
             ExtractionMethod::Combined => "combined",
         }
     }
+    
+    /// Single method summarizing a set of signals (`Combined` when more than one)
+    pub fn primary(methods: &[ExtractionMethod]) -> ExtractionMethod {
+        match methods {
+            [single] => single.clone(),
+            [] => ExtractionMethod::VectorSimilarity,
+            _ => ExtractionMethod::Combined,
+        }
+    }
 }
 
 /// Evidence for a relationship between chunks/entities
@@ -87,6 +97,7 @@ impl RelationshipEvidence {
         }
     }
     
+    #[allow(dead_code)]
     pub fn with_entity_ids(mut self, from: Uuid, to: Uuid) -> Self {
         self.from_entity_id = Some(from);
         self.to_entity_id = Some(to);
@@ -98,8 +109,8 @@ impl RelationshipEvidence {
         self
     }
     
-    pub fn with_temporal_distance(mut self, days: i32) -> Self {
-        self.temporal_distance_days = Some(days);
+    pub fn with_temporal_distance(mut self, days: Option<i32>) -> Self {
+        self.temporal_distance_days = days;
         self
     }
     
@@ -126,4 +137,8 @@ pub struct SemanticLink {
     pub explicit_mention: Option<String>,
     pub temporal_distance_days: Option<i32>,
     pub author_overlap: bool,
+    /// Which way the stored edge points: `outgoing` from `from_chunk_id` to
+    /// `to_chunk_id`, or `incoming` from `to_chunk_id` to `from_chunk_id`
+    #[serde(default)]
+    pub direction: EdgeDirection,
 }
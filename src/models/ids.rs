@@ -0,0 +1,107 @@
+//! Node identifiers
+//!
+//! Every node carries the application id it was written with (`n.id`, a
+//! UUID string). Neo4j also assigns its own `elementId(n)`, which is only
+//! meaningful to that database and changes if the node is re-imported. Lookups,
+//! edges, and API responses key on the application id; the two are kept as
+//! distinct types so one can't be passed where the other is expected.
+
+use crate::error::{GraphError, GraphResult};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// Application id of a node (`n.id`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EntityId(pub Uuid);
+
+impl EntityId {
+    /// Parse an id given in a request
+    pub fn parse(id: &str) -> GraphResult<Self> {
+        Uuid::parse_str(id)
+            .map(Self)
+            .map_err(|_| GraphError::InvalidRequest(format!("Entity id '{}' is not a UUID", id)))
+    }
+
+    /// Parse an id read back from the graph, logging one that isn't a UUID
+    ///
+    /// Such a node was written outside this service; callers leave it out of
+    /// their results instead of failing the whole request.
+    pub fn from_stored(id: &str) -> Option<Self> {
+        let parsed = Uuid::parse_str(id).ok().map(Self);
+        if parsed.is_none() {
+            tracing::warn!("Skipping node with non-UUID id: {}", id);
+        }
+        parsed
+    }
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl From<Uuid> for EntityId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Neo4j's own id for a node (`elementId(n)`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NodeElementId(pub String);
+
+impl NodeElementId {
+    #[allow(dead_code)]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for NodeElementId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Both ids of a node that was just written
+#[derive(Debug, Clone)]
+pub struct StoredNode {
+    pub id: EntityId,
+    /// `None` on backends other than Neo4j
+    pub element_id: Option<NodeElementId>,
+}
+
+/// Both ids of a relationship that was just written
+///
+/// `id` is stored on the edge (`r.id`) and kept when the edge is merged again.
+#[derive(Debug, Clone)]
+pub struct StoredRelationship {
+    pub id: Uuid,
+    /// `None` on backends other than Neo4j
+    pub element_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_entity_id_is_an_error() {
+        let id = Uuid::new_v4();
+        assert_eq!(EntityId::parse(&id.to_string()).unwrap(), EntityId(id));
+        for bad in ["", "n0", "4:2b6c1f4e-0c1d-4a0e-9a57-5f8a8a0c1e2f:17"] {
+            assert!(matches!(EntityId::parse(bad), Err(GraphError::InvalidRequest(_))), "{}", bad);
+            assert!(EntityId::from_stored(bad).is_none(), "{}", bad);
+        }
+        // Serialized as the bare UUID, like the plain ids it replaces
+        assert_eq!(serde_json::to_value(EntityId(id)).unwrap(), serde_json::json!(id.to_string()));
+    }
+}
@@ -4,10 +4,12 @@ pub mod entity;
 pub mod relationship;
 pub mod chunk;
 pub mod evidence;
+pub mod ids;
 pub mod search;
 
 pub use entity::*;
 pub use relationship::*;
 pub use chunk::*;
 pub use evidence::*;
+pub use ids::*;
 pub use search::*;
@@ -1,5 +1,6 @@
 //! Relationship types for the knowledge graph
 
+use crate::error::{GraphError, GraphResult};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -12,6 +13,7 @@ pub enum RelationshipType {
     // Code structure relationships
     Contains,       // Repo -> File, File -> Class, Class -> Function
     Imports,        // File -> Module
+    ResolvesTo,     // Import stub Module -> declared Module / file Chunk
     Calls,          // Function -> Function
     Implements,     // Class -> Trait/Interface
     Extends,        // Class -> Class
@@ -35,15 +37,41 @@ pub enum RelationshipType {
     CommittedAt,    // Commit -> Timestamp
     UpdatedNear,    // Document -> Commit (temporal proximity)
     
+    // Entity resolution
+    CanonicalOf,    // Canonical -> Entity (same thing in another source)
+    
     // Generic
     RelatedTo,
 }
 
 impl RelationshipType {
+    pub const ALL: [RelationshipType; 19] = [
+        RelationshipType::Contains,
+        RelationshipType::Imports,
+        RelationshipType::ResolvesTo,
+        RelationshipType::Calls,
+        RelationshipType::Implements,
+        RelationshipType::Extends,
+        RelationshipType::ParentOf,
+        RelationshipType::References,
+        RelationshipType::Defines,
+        RelationshipType::Explains,
+        RelationshipType::Documents,
+        RelationshipType::SemanticallySimilar,
+        RelationshipType::MentionsExplicitly,
+        RelationshipType::AuthoredBy,
+        RelationshipType::ContributedTo,
+        RelationshipType::CommittedAt,
+        RelationshipType::UpdatedNear,
+        RelationshipType::CanonicalOf,
+        RelationshipType::RelatedTo,
+    ];
+    
     pub fn as_str(&self) -> &'static str {
         match self {
             RelationshipType::Contains => "CONTAINS",
             RelationshipType::Imports => "IMPORTS",
+            RelationshipType::ResolvesTo => "RESOLVES_TO",
             RelationshipType::Calls => "CALLS",
             RelationshipType::Implements => "IMPLEMENTS",
             RelationshipType::Extends => "EXTENDS",
@@ -58,6 +86,7 @@ impl RelationshipType {
             RelationshipType::ContributedTo => "CONTRIBUTED_TO",
             RelationshipType::CommittedAt => "COMMITTED_AT",
             RelationshipType::UpdatedNear => "UPDATED_NEAR",
+            RelationshipType::CanonicalOf => "CANONICAL_OF",
             RelationshipType::RelatedTo => "RELATED_TO",
         }
     }
@@ -66,6 +95,7 @@ impl RelationshipType {
         match s.to_uppercase().as_str() {
             "CONTAINS" => Some(RelationshipType::Contains),
             "IMPORTS" => Some(RelationshipType::Imports),
+            "RESOLVES_TO" => Some(RelationshipType::ResolvesTo),
             "CALLS" => Some(RelationshipType::Calls),
             "IMPLEMENTS" => Some(RelationshipType::Implements),
             "EXTENDS" => Some(RelationshipType::Extends),
@@ -80,11 +110,25 @@ impl RelationshipType {
             "CONTRIBUTED_TO" => Some(RelationshipType::ContributedTo),
             "COMMITTED_AT" => Some(RelationshipType::CommittedAt),
             "UPDATED_NEAR" => Some(RelationshipType::UpdatedNear),
+            "CANONICAL_OF" => Some(RelationshipType::CanonicalOf),
             "RELATED_TO" => Some(RelationshipType::RelatedTo),
             _ => None,
         }
     }
     
+    /// Parse user input, rejecting unknown types
+    /// 
+    /// Relationship types are formatted into Cypher patterns, so anything from
+    /// a request must go through here (or `parse_all`) first.
+    pub fn parse(s: &str) -> GraphResult<Self> {
+        Self::from_str(s).ok_or_else(|| GraphError::InvalidRelationshipType(s.to_string()))
+    }
+    
+    /// Parse a list of user-supplied types, failing on the first unknown one
+    pub fn parse_all(types: &[String]) -> GraphResult<Vec<Self>> {
+        types.iter().map(|t| Self::parse(t)).collect()
+    }
+    
     /// Returns true if this is a cross-source relationship (the unique value prop)
     pub fn is_cross_source(&self) -> bool {
         matches!(
@@ -134,20 +178,78 @@ impl Relationship {
     }
 }
 
+/// Which way a stored edge points, seen from the node it was reached from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeDirection {
+    #[default]
+    Outgoing,
+    Incoming,
+}
+
+impl EdgeDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Outgoing => "outgoing",
+            Self::Incoming => "incoming",
+        }
+    }
+    
+    /// The single direction a `direction` filter keeps, or `None` for both
+    /// 
+    /// Like traversal, anything but `outgoing` or `incoming` means both.
+    pub fn filter(direction: &str) -> Option<Self> {
+        match direction {
+            "outgoing" => Some(Self::Outgoing),
+            "incoming" => Some(Self::Incoming),
+            _ => None,
+        }
+    }
+}
+
+/// Reject directions other than `outgoing`, `incoming`, and `both`
+pub fn validate_direction(field: &str, value: &str) -> GraphResult<()> {
+    match value {
+        "outgoing" | "incoming" | "both" => Ok(()),
+        other => Err(GraphError::InvalidRequest(format!(
+            "{} must be outgoing, incoming, or both, got {}",
+            field, other
+        ))),
+    }
+}
+
+/// Reject confidences outside `0..=1` (including NaN)
+pub fn validate_confidence(field: &str, value: f32) -> GraphResult<f32> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(GraphError::InvalidRequest(format!(
+            "{} must be between 0 and 1, got {}",
+            field, value
+        )))
+    }
+}
+
 /// Request to create a relationship
+/// 
+/// Manually created relationships carry an asserted confidence; when omitted it
+/// falls back to `DEFAULT_MANUAL_RELATIONSHIP_CONFIDENCE` rather than the
+/// inferred confidences used by extraction and cross-source linking.
 #[derive(Debug, Deserialize)]
 pub struct CreateRelationshipRequest {
     pub from_entity_id: Uuid,
     pub to_entity_id: Uuid,
     pub relationship_type: String,
-    #[serde(default = "default_confidence")]
-    pub confidence: f32,
+    pub confidence: Option<f32>,
     #[serde(default)]
     pub properties: serde_json::Value,
 }
 
-fn default_confidence() -> f32 {
-    1.0
+impl CreateRelationshipRequest {
+    /// Validated confidence, or `default` when the request omits it
+    pub fn confidence_or(&self, default: f32) -> GraphResult<f32> {
+        validate_confidence("confidence", self.confidence.unwrap_or(default))
+    }
 }
 
 /// Response after creating relationship
@@ -156,3 +258,49 @@ pub struct CreateRelationshipResponse {
     pub relationship_id: Uuid,
     pub neo4j_rel_id: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn request(confidence: Option<f32>) -> CreateRelationshipRequest {
+        CreateRelationshipRequest {
+            from_entity_id: Uuid::new_v4(),
+            to_entity_id: Uuid::new_v4(),
+            relationship_type: "CALLS".to_string(),
+            confidence,
+            properties: serde_json::Value::Null,
+        }
+    }
+    
+    #[test]
+    fn test_manual_confidence_default_and_bounds() {
+        assert_eq!(request(None).confidence_or(0.9).unwrap(), 0.9);
+        assert_eq!(request(Some(0.4)).confidence_or(0.9).unwrap(), 0.4);
+        assert_eq!(request(Some(0.0)).confidence_or(0.9).unwrap(), 0.0);
+        
+        for invalid in [-0.1, 1.5, f32::NAN] {
+            assert!(matches!(
+                request(Some(invalid)).confidence_or(0.9),
+                Err(GraphError::InvalidRequest(_))
+            ));
+        }
+    }
+    
+    #[test]
+    fn test_parse_rejects_unknown_types() {
+        assert_eq!(RelationshipType::parse("calls").unwrap(), RelationshipType::Calls);
+        let parsed = RelationshipType::parse_all(&["CONTAINS".to_string(), "similar".to_string()]).unwrap();
+        assert_eq!(parsed, [RelationshipType::Contains, RelationshipType::SemanticallySimilar]);
+        
+        let injected = "CALLS]->(m) DETACH DELETE m //".to_string();
+        assert!(matches!(
+            RelationshipType::parse_all(&["CALLS".to_string(), injected]),
+            Err(GraphError::InvalidRelationshipType(t)) if t.starts_with("CALLS]")
+        ));
+        assert!(matches!(
+            crate::models::EntityType::parse("function'"),
+            Err(GraphError::InvalidEntityType(_))
+        ));
+    }
+}
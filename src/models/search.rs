@@ -1,12 +1,15 @@
 //! Search request and response models
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
-use super::{Entity, Relationship, Chunk, SemanticLink};
+use super::{ExtractionMethod, SemanticLink};
+use crate::error::{GraphError, GraphResult};
 
 /// Options for hybrid search
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SearchOptions {
     /// Maximum number of vector results
     #[serde(default = "default_limit")]
@@ -26,23 +29,108 @@ pub struct SearchOptions {
     /// Filter by repository name
     pub repo_filter: Option<String>,
     
+    /// Only chunks whose file path matches this glob (e.g. `src/auth/**`)
+    /// 
+    /// Supports `*`, `**`, `?`, and `{a,b}`; see `PathGlob`.
+    pub path_glob: Option<String>,
+    
+    /// Only chunks in these languages (case-insensitive; aliases such as
+    /// `ts` and `py` are accepted)
+    pub languages: Option<Vec<String>>,
+    
     /// Filter by owner ID
     pub owner_id: Option<String>,
     
-    /// Include cross-source relationships in results
-    #[serde(default = "default_true")]
-    pub include_cross_source: bool,
+    /// Include cross-source relationships in results (default true)
+    /// 
+    /// Setting it to `true` also lets single-kind searches expand over
+    /// cross-source edges at full weight (see `cross_kind_weight`).
+    pub include_cross_source: Option<bool>,
+    
+    /// Weight (0 to 1) of entities reached over cross-source edges when
+    /// `source_kind` is `code` or `document`
+    /// 
+    /// 0 doesn't follow those edges at all; other values scale the confidence
+    /// of the relationships that reach them. Defaults to
+    /// `CROSS_KIND_EXPANSION_WEIGHT`. Ignored when `include_cross_source` is `true`.
+    pub cross_kind_weight: Option<f32>,
+    
+    /// Minimum vector similarity (0 to 1) for a chunk to be returned
+    /// 
+    /// Defaults to `HYBRID_MIN_SIMILARITY`. Pinned exact matches are exempt.
+    pub min_similarity: Option<f32>,
     
-    /// Minimum similarity threshold for vector results
-    #[serde(default = "default_threshold")]
-    pub min_similarity: f32,
+    /// Rerank vector results with maximal marginal relevance
+    /// 
+    /// 1.0 ranks purely by relevance; lower values favor results unlike those
+    /// already selected. Unset disables reranking.
+    pub mmr_lambda: Option<f32>,
+    
+    /// Rescore vector hits by exact cosine similarity to their stored embeddings
+    /// 
+    /// The vector index scores are approximate; this fetches extra candidates
+    /// with their embeddings and re-ranks them exactly, at some latency cost.
+    #[serde(default)]
+    pub rerank_exact: bool,
+    
+    /// Favor recently changed chunks
+    /// 
+    /// Scores are multiplied by `1 + recency_boost * 0.5^(age / half-life)`,
+    /// so a brand-new chunk gets up to `recency_boost` extra (0 to 1) and the
+    /// bonus halves every `RECENCY_HALF_LIFE_DAYS`. Unset disables the boost.
+    pub recency_boost: Option<f32>,
+    
+    /// Pin chunks that exactly match the query above the vector results
+    /// 
+    /// A chunk matches when its file path or section title equals the query,
+    /// or it contains an entity with that exact name.
+    #[serde(default)]
+    pub pin_exact_matches: bool,
+    
+    /// Most cross-source links listed per result, strongest first (at most `MAX_ENTITIES_PER_TRAVERSAL`)
+    #[serde(default = "default_max_cross_source_links")]
+    pub max_cross_source_links: usize,
+    
+    /// Follow cross-source links this many hops from each result
+    /// 
+    /// 2 also lists what the linked chunks link to, e.g. the code explained
+    /// by a doc similar to the hit. Capped at `max_cross_source_links` links.
+    #[serde(default = "default_cross_source_hops")]
+    pub cross_source_hops: usize,
+    
+    /// Minimum confidence (0 to 1) of the cross-source links followed
+    #[serde(default)]
+    pub cross_source_min_confidence: f32,
+    
+    /// Cross-source links followed: "outgoing" (from the hit), "incoming"
+    /// (pointing at the hit), or "both"
+    #[serde(default = "default_cross_source_direction")]
+    pub cross_source_direction: String,
+    
+    /// Count each result's cross-source links into `cross_source_link_count`
+    #[serde(default)]
+    pub include_link_counts: bool,
+    
+    /// Return only chunks linked to the vector hits across sources
+    /// 
+    /// The hits are replaced by the chunks of the other kind linked to them
+    /// (docs for matched code, code for matched docs), ranked by link
+    /// confidence. `cross_source_links` says which hit each one came from.
+    #[serde(default)]
+    pub cross_source_only: bool,
+    
+    /// Embed the query with this model and search its index
+    /// 
+    /// Must be the default model or one listed in `EMBEDDING_MODEL_INDEXES`.
+    pub embedding_model: Option<String>,
 }
 
 fn default_limit() -> usize { 10 }
 fn default_hops() -> usize { 2 }
 fn default_source_kind_filter() -> String { "all".to_string() }
-fn default_true() -> bool { true }
-fn default_threshold() -> f32 { 0.0 }
+fn default_max_cross_source_links() -> usize { 20 }
+fn default_cross_source_hops() -> usize { 1 }
+fn default_cross_source_direction() -> String { "both".to_string() }
 
 impl Default for SearchOptions {
     fn default() -> Self {
@@ -52,13 +140,34 @@ impl Default for SearchOptions {
             source_kind: "all".to_string(),
             source_types: None,
             repo_filter: None,
+            path_glob: None,
+            languages: None,
             owner_id: None,
-            include_cross_source: true,
-            min_similarity: 0.0,
+            include_cross_source: None,
+            cross_kind_weight: None,
+            min_similarity: None,
+            mmr_lambda: None,
+            rerank_exact: false,
+            recency_boost: None,
+            pin_exact_matches: false,
+            max_cross_source_links: 20,
+            cross_source_hops: 1,
+            cross_source_min_confidence: 0.0,
+            cross_source_direction: "both".to_string(),
+            include_link_counts: false,
+            cross_source_only: false,
+            embedding_model: None,
         }
     }
 }
 
+impl SearchOptions {
+    /// Whether cross-source links are listed in the response
+    pub fn includes_cross_source(&self) -> bool {
+        self.include_cross_source.unwrap_or(true)
+    }
+}
+
 /// Hybrid search request
 #[derive(Debug, Deserialize)]
 pub struct HybridSearchRequest {
@@ -71,7 +180,7 @@ pub struct HybridSearchRequest {
 }
 
 /// A single search result with chunk and score
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChunkResult {
     pub chunk_id: Uuid,
     pub content: String,
@@ -81,21 +190,51 @@ pub struct ChunkResult {
     pub repo_name: Option<String>,
     pub language: Option<String>,
     pub heading_path: Option<String>,
+    /// Short summary of the chunk content (when summarization is enabled)
+    pub summary: Option<String>,
+    /// Same as `final_score`, kept for existing clients
     pub similarity_score: f32,
+    /// Vector similarity as retrieved, before any boost or pinning
+    /// 
+    /// `None` for chunks that only came from the exact-match lookup.
+    pub raw_score: Option<f32>,
+    /// Score after boosts, which the results are ranked by
+    pub final_score: f32,
+    /// Commit date, or when the chunk was last written
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Exact match for the query, placed ahead of the ranked results
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Links to chunks from other sources (with `include_link_counts`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cross_source_link_count: Option<usize>,
+    /// Stored embedding, fetched only for MMR reranking
+    #[serde(skip)]
+    pub embedding: Option<Vec<f32>>,
+}
+
+impl ChunkResult {
+    /// Set the ranking score, leaving `raw_score` as retrieved
+    pub fn set_score(&mut self, score: f32) {
+        self.similarity_score = score;
+        self.final_score = score;
+    }
 }
 
 /// Entity result from graph expansion
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EntityResult {
     pub id: Uuid,
     pub entity_type: String,
     pub name: String,
     pub source: String,
     pub properties: serde_json::Value,
+    /// Confidence of the strongest relationship that reached this entity
+    pub relevance: f32,
 }
 
 /// Relationship result showing connections
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct RelationshipResult {
     pub from_id: Uuid,
     pub to_id: Uuid,
@@ -107,7 +246,7 @@ pub struct RelationshipResult {
 }
 
 /// Full hybrid search response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct HybridSearchResponse {
     /// Ranked chunk results from vector search
     pub chunks: Vec<ChunkResult>,
@@ -125,8 +264,33 @@ pub struct HybridSearchResponse {
     pub metadata: SearchMetadata,
 }
 
-/// Metadata about the search execution
+/// Graph results for a single vector hit
+#[derive(Debug, Default, Serialize)]
+pub struct ChunkExpansion {
+    pub entities: Vec<EntityResult>,
+    pub relationships: Vec<RelationshipResult>,
+    pub cross_source_links: Vec<SemanticLink>,
+}
+
+/// A message sent by `GET /api/search/stream`
+/// 
+/// Serialized with a `type` tag: `chunks` first, then one `expansion` per
+/// chunk, then `metadata`. An `error` frame ends the stream early.
 #[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchFrame {
+    Chunks { chunks: Vec<ChunkResult> },
+    Expansion {
+        chunk_id: Uuid,
+        #[serde(flatten)]
+        expansion: ChunkExpansion,
+    },
+    Metadata(SearchMetadata),
+    Error { message: String },
+}
+
+/// Metadata about the search execution
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SearchMetadata {
     pub query: String,
     pub vector_results_count: usize,
@@ -134,6 +298,12 @@ pub struct SearchMetadata {
     pub graph_hops_performed: usize,
     pub cross_source_links_count: usize,
     pub execution_time_ms: u64,
+    /// Served from the search result cache
+    #[serde(default)]
+    pub cached: bool,
+    /// When a cached response was computed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached_at: Option<DateTime<Utc>>,
 }
 
 /// Vector-only search request
@@ -145,6 +315,28 @@ pub struct VectorSearchRequest {
     pub source_kind: Option<String>,
     pub source_types: Option<Vec<String>>,
     pub owner_id: Option<String>,
+    /// Language filter (see `SearchOptions::languages`)
+    pub languages: Option<Vec<String>>,
+    /// Embedding model override (see `SearchOptions::embedding_model`)
+    pub embedding_model: Option<String>,
+}
+
+/// Vector-only search with a query embedding the caller already has
+/// 
+/// Searched against the default chunk index, so `embedding` must have
+/// `VECTOR_DIMENSION` values.
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingSearchRequest {
+    pub embedding: Vec<f32>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    pub source_kind: Option<String>,
+    pub source_types: Option<Vec<String>>,
+    pub owner_id: Option<String>,
+    /// Language filter (see `SearchOptions::languages`)
+    pub languages: Option<Vec<String>>,
+    /// Overrides `HYBRID_MIN_SIMILARITY`
+    pub min_similarity: Option<f32>,
 }
 
 /// Vector search response
@@ -170,6 +362,11 @@ pub struct GraphSearchRequest {
     /// Maximum results
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Also return the traversed paths (capped by `limit`)
+    #[serde(default)]
+    pub include_paths: bool,
+    /// Only traverse nodes belonging to this owner
+    pub owner_id: Option<String>,
 }
 
 fn default_direction() -> String { "both".to_string() }
@@ -190,6 +387,115 @@ pub struct GraphPath {
     pub total_confidence: f32,
 }
 
+/// Most neighbors or entities one lookup may return
+pub const MAX_NEIGHBOR_LIMIT: usize = 1000;
+
+/// Reject a `limit` outside 1..=`MAX_NEIGHBOR_LIMIT`
+pub fn validate_neighbor_limit(limit: usize) -> GraphResult<()> {
+    if limit == 0 || limit > MAX_NEIGHBOR_LIMIT {
+        return Err(GraphError::InvalidRequest(format!(
+            "limit must be between 1 and {}, got {}",
+            MAX_NEIGHBOR_LIMIT, limit
+        )));
+    }
+    Ok(())
+}
+
+/// Query parameters for neighbor lookups
+#[derive(Debug, Clone, Deserialize)]
+pub struct NeighborQuery {
+    /// Return the strongest relationships first
+    #[serde(default)]
+    pub order_by_confidence: bool,
+    /// Drop relationships below this confidence
+    #[serde(default)]
+    pub min_confidence: f32,
+    /// Maximum number of neighbors (at most `MAX_NEIGHBOR_LIMIT`)
+    #[serde(default = "default_neighbor_limit")]
+    pub limit: usize,
+}
+
+fn default_neighbor_limit() -> usize { 100 }
+
+impl Default for NeighborQuery {
+    fn default() -> Self {
+        Self {
+            order_by_confidence: false,
+            min_confidence: 0.0,
+            limit: 100,
+        }
+    }
+}
+
+/// Shortest-path request between two entities
+#[derive(Debug, Deserialize)]
+pub struct PathSearchRequest {
+    pub from_id: String,
+    pub to_id: String,
+    /// Maximum path length (defaults to, and can't exceed, the configured `max_graph_hops`)
+    pub max_hops: Option<usize>,
+    /// Relationship types the path may traverse (all types if omitted)
+    pub relationship_types: Option<Vec<String>>,
+}
+
+/// Shortest-path response (empty `paths` when the entities are not connected)
+#[derive(Debug, Serialize)]
+pub struct PathSearchResponse {
+    pub from_id: String,
+    pub to_id: String,
+    pub paths: Vec<GraphPath>,
+}
+
+/// Query parameters for entity trees
+#[derive(Debug, Deserialize)]
+pub struct EntityTreeQuery {
+    /// Comma-separated relationship types to follow (`CONTAINS,PARENT_OF` if omitted)
+    pub types: Option<String>,
+    /// Tree depth below the root (defaults to, and is capped at, `max_tree_depth`)
+    pub max_depth: Option<usize>,
+}
+
+/// A node of an entity tree with the relationship from its parent
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityTreeNode {
+    pub id: String,
+    pub name: String,
+    pub entity_type: String,
+    /// `None` for the root
+    pub relationship: Option<String>,
+    pub confidence: Option<f32>,
+    pub children: Vec<EntityTreeNode>,
+}
+
+/// Entity tree response
+#[derive(Debug, Serialize)]
+pub struct EntityTreeResponse {
+    pub root: EntityTreeNode,
+    pub max_depth: usize,
+    /// Nodes in the tree, root included
+    pub node_count: usize,
+    /// The node cap was reached, so some descendants are missing
+    pub truncated: bool,
+}
+
+/// Ad-hoc read-only Cypher query
+#[derive(Debug, Deserialize)]
+pub struct CypherQueryRequest {
+    pub cypher: String,
+    /// Query parameters, referenced as `$name` in the Cypher
+    #[serde(default)]
+    pub params: HashMap<String, serde_json::Value>,
+}
+
+/// Rows returned by an ad-hoc query
+#[derive(Debug, Serialize)]
+pub struct CypherQueryResponse {
+    pub rows: Vec<serde_json::Value>,
+    pub row_count: usize,
+    /// True when the result was cut off at the server-side row cap
+    pub truncated: bool,
+}
+
 /// Request to trigger cross-source linking
 #[derive(Debug, Deserialize)]
 pub struct CrossSourceLinkRequest {
@@ -211,3 +517,143 @@ pub struct CrossSourceLinkResponse {
     pub chunks_processed: usize,
     pub errors: Vec<String>,
 }
+
+/// Query for `GET /api/graph/chunks/:id/cross-source`
+#[derive(Debug, Deserialize)]
+pub struct CrossSourceMatchQuery {
+    /// Source kind to look for ("code" or "document")
+    #[serde(default = "default_target_kind")]
+    pub target_kind: String,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    /// Overrides `SIMILARITY_THRESHOLD`
+    pub min_similarity: Option<f32>,
+}
+
+fn default_target_kind() -> String { "code".to_string() }
+
+/// A chunk that linking would connect to the requested chunk
+#[derive(Debug, Serialize)]
+pub struct CrossSourceMatchResult {
+    pub chunk_id: Uuid,
+    pub content: Option<String>,
+    pub source_type: Option<String>,
+    pub file_path: Option<String>,
+    pub similarity_score: f32,
+    /// Similarity plus the mention, author, and temporal boosts
+    pub confidence: f32,
+    pub extraction_methods: Vec<ExtractionMethod>,
+}
+
+/// Cross-source matches for one chunk, strongest first
+#[derive(Debug, Serialize)]
+pub struct CrossSourceMatchResponse {
+    pub chunk_id: Uuid,
+    pub target_kind: String,
+    pub matches: Vec<CrossSourceMatchResult>,
+}
+
+/// Filters for listing relationships
+#[derive(Debug, Deserialize)]
+pub struct RelationshipQuery {
+    /// Only relationships with this method among their evidence
+    pub extraction_method: Option<ExtractionMethod>,
+    /// Require `extraction_method` to be the only evidence
+    #[serde(default)]
+    pub exclusive: bool,
+    pub relationship_type: Option<String>,
+    #[serde(default)]
+    pub min_confidence: f32,
+    #[serde(default = "default_relationship_limit")]
+    pub limit: usize,
+}
+
+fn default_relationship_limit() -> usize { 100 }
+
+/// Filters for listing entities
+#[derive(Debug, Deserialize)]
+pub struct EntityQuery {
+    pub entity_type: Option<String>,
+    /// Data source the entity came from, e.g. `github`
+    pub source: Option<String>,
+    #[serde(default = "default_relationship_limit")]
+    pub limit: usize,
+}
+
+/// Output format of `GET /api/graph/export`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum ExportFormat {
+    #[default]
+    #[serde(rename = "graphml")]
+    GraphMl,
+    #[serde(rename = "jsonld")]
+    JsonLd,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::GraphMl => "application/graphml+xml",
+            ExportFormat::JsonLd => "application/ld+json",
+        }
+    }
+}
+
+/// Query parameters for exporting the graph
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    pub owner_id: Option<String>,
+    /// Include `embedding` and `summary_embedding` vectors (default false)
+    #[serde(default)]
+    pub include_embeddings: bool,
+}
+
+/// How `POST /api/graph/import` treats what is already stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Upsert nodes and relationships by id, keeping everything else
+    #[default]
+    Merge,
+    /// Also delete the owner's nodes and relationships the document doesn't contain
+    Replace,
+}
+
+/// Query parameters for importing an exported graph
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub mode: ImportMode,
+    pub owner_id: Option<String>,
+}
+
+/// Outcome of an import
+#[derive(Debug, Default, Serialize)]
+pub struct ImportResponse {
+    pub mode: ImportMode,
+    /// Owner's nodes missing from the document, removed after importing (`replace` mode)
+    pub nodes_deleted: usize,
+    /// Nodes created or overwritten
+    pub nodes_imported: usize,
+    /// Relationships created or updated
+    pub relationships_imported: usize,
+    /// Malformed or rejected nodes and relationships
+    pub records_skipped: usize,
+    /// Why each skipped record was skipped
+    pub errors: Vec<String>,
+}
+
+/// Relationship as returned by the listing endpoint
+#[derive(Debug, Serialize)]
+pub struct RelationshipSummary {
+    pub from_id: String,
+    pub to_id: String,
+    pub relationship_type: String,
+    pub confidence: f32,
+    pub extraction_methods: Vec<String>,
+    pub similarity_score: Option<f32>,
+}
@@ -0,0 +1,259 @@
+//! Per-client rate limiting
+//!
+//! Each client IP (the connection's peer address) gets a token bucket holding
+//! `RATE_LIMIT_BURST` requests that refills at `RATE_LIMIT_RPS`. `X-Owner-Id`
+//! isn't authenticated, so it can't pick the bucket: a caller could send a new
+//! value per request. A request that finds its bucket empty gets `429` with a
+//! `Retry-After` header. Buckets live in process memory (the least recently
+//! used are dropped past `MAX_BUCKETS`), or in Redis when `REDIS_URL` is set so
+//! replicas share them. `/health` and `/metrics` are never limited.
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use lru::LruCache;
+use redis::aio::ConnectionManager;
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::{GraphError, GraphResult};
+
+/// Paths that are never limited
+const EXEMPT_PATHS: [&str; 2] = ["/health", "/metrics"];
+
+/// In-memory buckets kept before the least recently used is dropped
+const MAX_BUCKETS: NonZeroUsize = NonZeroUsize::new(10_000).unwrap();
+
+/// Token bucket shared by replicas; returns 0 or the milliseconds until a token is free
+const REDIS_TOKEN_BUCKET: &str = r#"
+local rate = tonumber(ARGV[1])
+local capacity = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(bucket[1]) or capacity
+local updated = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - updated) / 1000 * rate)
+local wait = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+else
+    wait = math.ceil((1 - tokens) / rate * 1000)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', tostring(now))
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / rate * 1000) + 1000)
+return wait
+"#;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Refill for the time since the last request, then take a token
+    ///
+    /// Returns how long until a token is available when the bucket is empty.
+    fn take(&mut self, now: Instant, rate: f64, capacity: f64) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(capacity);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+enum Buckets {
+    Memory(Mutex<LruCache<String, Bucket>>),
+    Redis(ConnectionManager),
+}
+
+/// Token buckets keyed by client
+pub struct RateLimiter {
+    buckets: Buckets,
+    /// Tokens added per second
+    rate: f64,
+    /// Most tokens a bucket holds
+    capacity: f64,
+}
+
+impl RateLimiter {
+    /// Buckets in process memory (per replica)
+    pub fn in_memory(rate: f64, burst: u32) -> Self {
+        Self::with_buckets(Buckets::Memory(Mutex::new(LruCache::new(MAX_BUCKETS))), rate, burst)
+    }
+
+    /// Buckets in Redis, shared by every replica
+    pub async fn redis(url: &str, rate: f64, burst: u32) -> GraphResult<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| GraphError::Config(format!("Invalid REDIS_URL: {}", e)))?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(|e| GraphError::ServiceUnavailable(format!("Redis connection failed: {}", e)))?;
+        Ok(Self::with_buckets(Buckets::Redis(connection), rate, burst))
+    }
+
+    /// `burst` of 0 allows one second's worth of requests at once
+    fn with_buckets(buckets: Buckets, rate: f64, burst: u32) -> Self {
+        let capacity = if burst == 0 { rate.ceil().max(1.0) } else { burst as f64 };
+        Self { buckets, rate, capacity }
+    }
+
+    pub fn backend(&self) -> &'static str {
+        match self.buckets {
+            Buckets::Memory(_) => "memory",
+            Buckets::Redis(_) => "redis",
+        }
+    }
+
+    /// Take a request from `key`'s bucket, or return the wait until one is allowed
+    ///
+    /// Requests are let through when Redis can't be reached.
+    pub async fn check(&self, key: &str) -> Result<(), Duration> {
+        match &self.buckets {
+            Buckets::Memory(buckets) => {
+                let now = Instant::now();
+                buckets.lock().unwrap()
+                    .get_or_insert_mut(key.to_string(), || Bucket { tokens: self.capacity, updated: now })
+                    .take(now, self.rate, self.capacity)
+            }
+            Buckets::Redis(connection) => {
+                let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+                let wait_ms: redis::RedisResult<u64> = redis::Script::new(REDIS_TOKEN_BUCKET)
+                    .key(format!("ratelimit:{}", key))
+                    .arg(self.rate)
+                    .arg(self.capacity)
+                    .arg(now_ms)
+                    .invoke_async(&mut connection.clone())
+                    .await;
+                match wait_ms {
+                    Ok(0) => Ok(()),
+                    Ok(wait_ms) => Err(Duration::from_millis(wait_ms)),
+                    Err(e) => {
+                        tracing::warn!("Rate limit check failed, allowing request: {}", e);
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Bucket key: the peer IP of the connection
+fn client_key(request: &Request) -> String {
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+/// Middleware answering `429` once the caller's bucket is empty
+pub async fn limit(State(limiter): State<Arc<RateLimiter>>, request: Request, next: Next) -> Response {
+    if EXEMPT_PATHS.contains(&request.uri().path()) {
+        return next.run(request).await;
+    }
+    let key = client_key(&request);
+    match limiter.check(&key).await {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            tracing::debug!("Rate limited {} for {:?}", key, wait);
+            // Whole seconds, rounded up so a retry at that time succeeds
+            let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            GraphError::RateLimited(retry_after.max(1)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::OWNER_ID_HEADER;
+    use axum::{middleware, routing::get, Router};
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_bucket_allows_burst_then_refills() {
+        let start = Instant::now();
+        let mut bucket = Bucket { tokens: 2.0, updated: start };
+        assert!(bucket.take(start, 1.0, 2.0).is_ok());
+        assert!(bucket.take(start, 1.0, 2.0).is_ok());
+        assert_eq!(bucket.take(start, 1.0, 2.0), Err(Duration::from_secs(1)));
+
+        // Half a second refills half a token
+        let later = start + Duration::from_millis(500);
+        assert_eq!(bucket.take(later, 1.0, 2.0), Err(Duration::from_millis(500)));
+        assert!(bucket.take(later + Duration::from_millis(500), 1.0, 2.0).is_ok());
+
+        // Idle time never fills past capacity
+        let idle = later + Duration::from_secs(60);
+        assert!(bucket.take(idle, 1.0, 2.0).is_ok());
+        assert!(bucket.take(idle, 1.0, 2.0).is_ok());
+        assert!(bucket.take(idle, 1.0, 2.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_requests_over_the_limit_get_429_until_the_bucket_refills() {
+        let limiter = Arc::new(RateLimiter::in_memory(10.0, 2));
+        let app = Router::new()
+            .route("/api/search", get(|| async { "ok" }))
+            .route("/health", get(|| async { "up" }))
+            .layer(middleware::from_fn_with_state(limiter, limit));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
+        });
+
+        let client = reqwest::Client::new();
+        let get = |path: &str, owner: Option<&str>| {
+            let mut request = client.get(format!("http://{}{}", addr, path));
+            if let Some(owner) = owner {
+                request = request.header(OWNER_ID_HEADER, owner);
+            }
+            request.send()
+        };
+
+        for _ in 0..2 {
+            assert_eq!(get("/api/search", Some("tenant-a")).await.unwrap().status(), StatusCode::OK);
+        }
+        let limited = get("/api/search", Some("tenant-a")).await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()["retry-after"], "1");
+        let body: serde_json::Value = limited.json().await.unwrap();
+        assert_eq!(body["status"], 429);
+
+        // Changing or dropping the unauthenticated owner header doesn't buy a new budget
+        assert_eq!(get("/api/search", Some("tenant-b")).await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(get("/api/search", None).await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+        for _ in 0..5 {
+            assert_eq!(get("/health", Some("tenant-a")).await.unwrap().status(), StatusCode::OK);
+        }
+
+        // One token back after a tenth of a second
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(get("/api/search", Some("tenant-a")).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(get("/api/search", Some("tenant-a")).await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_buckets_are_bounded() {
+        let limiter = RateLimiter::in_memory(1.0, 1);
+        for client in 0..=MAX_BUCKETS.get() {
+            assert!(limiter.check(&format!("ip:{}", client)).await.is_ok());
+        }
+        let Buckets::Memory(buckets) = &limiter.buckets else { unreachable!() };
+        assert_eq!(buckets.lock().unwrap().len(), MAX_BUCKETS.get());
+
+        // The first client's drained bucket was the one dropped; the latest is kept
+        assert!(limiter.check("ip:0").await.is_ok());
+        assert!(limiter.check(&format!("ip:{}", MAX_BUCKETS.get())).await.is_err());
+    }
+}
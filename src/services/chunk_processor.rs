@@ -4,38 +4,82 @@
 
 use crate::config::Config;
 use crate::error::{GraphError, GraphResult};
-use crate::graph_db::Neo4jClient;
+use crate::graph_db::GraphStore;
+use crate::graph_db::neo4j_client::{BatchEdge, CrossSourceMatch, LinkBoosts};
 use crate::extractors::{CodeEntityExtractor, DocumentEntityExtractor};
+use crate::extractors::document_entities::{heading_lines, ConceptFilter};
+use crate::extractors::code_entities::{import_path_suffixes, ExtractedRelationship, ExtractionResult, CHUNK_ENDPOINT};
 use crate::models::{
-    Chunk, ChunkInput,
-    IngestChunksRequest, IngestChunksResponse,
-    Entity, DataSource,
+    Chunk, ChunkExtraction,
+    IngestChunksRequest, IngestChunksResponse, ReindexRequest, ReindexResponse,
+    Entity, EntityId, EntityType, DataSource, ExtractionMethod, SemanticLink,
 };
-use crate::services::EmbeddingClient;
+use crate::services::cross_source_linker::{CrossLinkPlan, CrossSourceLinker};
+use crate::services::search_cache::SearchCache;
+use crate::services::{ChunkRepository, EmbeddingClient, SummarizationClient};
+use futures::future;
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use uuid::Uuid;
+
+/// Provider recorded on nodes embedded by this service
+const EMBEDDING_PROVIDER: &str = "embeddings-service";
 
 /// Chunk processor for ingesting and processing chunks
 pub struct ChunkProcessor {
     config: Config,
-    neo4j: Option<Arc<Neo4jClient>>,
+    graph: Option<Arc<dyn GraphStore>>,
     code_extractor: CodeEntityExtractor,
     doc_extractor: DocumentEntityExtractor,
-    embedding_client: EmbeddingClient,
+    embedding_client: Arc<EmbeddingClient>,
+    summarization_client: Option<SummarizationClient>,
+    search_cache: Option<Arc<SearchCache>>,
+    chunk_repository: Option<Arc<ChunkRepository>>,
+    /// Scores cross-source links between chunks of the same batch
+    linker: CrossSourceLinker,
 }
 
 impl ChunkProcessor {
     pub fn new(
         config: Config,
-        neo4j: Option<Arc<Neo4jClient>>,
+        graph: Option<Arc<dyn GraphStore>>,
+        embedding_client: Arc<EmbeddingClient>,
     ) -> Self {
-        let embedding_client = EmbeddingClient::new(&config.embedding_service_url);
+        let summarization_client = config.summarization_service_url
+            .as_deref()
+            .map(|url| SummarizationClient::new(url, config.summary_max_length));
+        let doc_extractor = DocumentEntityExtractor::new()
+            .with_concept_filter(ConceptFilter::from_config(&config));
         
         Self {
+            linker: CrossSourceLinker::new(config.clone(), None),
             config,
-            neo4j,
+            graph,
             code_extractor: CodeEntityExtractor::new(),
-            doc_extractor: DocumentEntityExtractor::new(),
+            doc_extractor,
             embedding_client,
+            summarization_client,
+            search_cache: None,
+            chunk_repository: None,
+        }
+    }
+    
+    /// Clear `cache` whenever this processor changes the graph
+    pub fn with_search_cache(mut self, cache: Option<Arc<SearchCache>>) -> Self {
+        self.search_cache = cache;
+        self
+    }
+    
+    /// Also store chunk rows and cross-link evidence in Postgres
+    pub fn with_chunk_repository(mut self, repository: Option<Arc<ChunkRepository>>) -> Self {
+        self.chunk_repository = repository;
+        self
+    }
+    
+    fn invalidate_search_cache(&self) {
+        if let Some(cache) = &self.search_cache {
+            cache.invalidate();
         }
     }
     
@@ -45,39 +89,124 @@ impl ChunkProcessor {
         let mut entities_extracted = 0;
         let mut relationships_created = 0;
         let mut vectors_stored = 0;
+        let mut chunks_skipped = 0;
+        let mut chunks_below_min_tokens = 0;
+        let mut id_collisions = Vec::new();
         let mut errors = Vec::new();
         
         let extract_entities = request.extract_entities.unwrap_or(true);
         let create_cross_links = request.create_cross_links.unwrap_or(true);
+        let strict = request.strict.unwrap_or(false);
+        let detailed = request.detailed.unwrap_or(false);
+        let store_chunks = request.store_chunks.unwrap_or(true);
+        let dedupe = request.dedupe.unwrap_or(false);
+        let return_evidence = request.return_evidence.unwrap_or(false);
+        let mut extractions = Vec::new();
+        let mut evidence = Vec::new();
         
-        // Separate code and document chunks
-        let mut code_chunks: Vec<(Chunk, Vec<f32>)> = Vec::new();
-        let mut doc_chunks: Vec<(Chunk, Vec<f32>)> = Vec::new();
+        // In strict mode, reject before anything is embedded or stored
+        if strict {
+            check_strict(&request)?;
+        }
+        
+        let mode = self.config.summary_embedding_mode.as_str();
+        // Content hash per chunk id seen in this batch
+        let mut batch_hashes: HashMap<Uuid, String> = HashMap::new();
+        // (owner, content hash) of every chunk kept so far, for `dedupe`
+        let mut batch_contents: HashSet<(String, String)> = HashSet::new();
+        let mut prepared: Vec<PreparedChunk> = Vec::new();
         
         for chunk_input in request.chunks {
+            // Empty content would only yield a meaningless vector
+            if chunk_input.content.trim().is_empty() {
+                chunks_skipped += 1;
+                continue;
+            }
+            
             // Extract embedding before consuming chunk_input
             let input_embedding = chunk_input.embedding.clone();
             let chunk = chunk_input.into_chunk();
+            if dedupe && self.is_duplicate(&chunk, &mut batch_contents, &mut errors).await {
+                chunks_skipped += 1;
+                continue;
+            }
+            if let Some(previous) = batch_hashes.insert(chunk.id, chunk.content_hash.clone()) {
+                record_collision(&mut id_collisions, &chunk, &previous);
+            }
+            let below_min_tokens = self.is_below_min_tokens(&chunk);
+            if below_min_tokens {
+                chunks_below_min_tokens += 1;
+            }
             
-            // Get or generate embedding
-            let embedding = if let Some(emb) = input_embedding {
-                emb
-            } else {
-                match self.embedding_client.embed(&chunk.content).await {
-                    Ok(emb) => emb,
+            // Summarize large chunks (optional; a failure never drops the chunk).
+            // Summaries live on chunk nodes, so there's nothing to do without them.
+            let summary = if store_chunks {
+                match self.summarize(&chunk).await {
+                    Ok(summary) => summary,
                     Err(e) => {
-                        errors.push(format!("Embedding failed for chunk {}: {}", chunk.id, e));
-                        continue;
+                        errors.push(format!("Summarization failed for chunk {}: {}", chunk.id, e));
+                        None
                     }
                 }
+            } else {
+                None
             };
             
-            // Store chunk in Neo4j with embedding (graph + vector in one place)
-            if let Some(neo4j) = &self.neo4j {
-                // Create chunk node with embedding
-                match self.create_chunk_node_with_embedding(neo4j, &chunk, &embedding).await {
-                    Ok(_) => {
-                        vectors_stored += 1;
+            // Use the precomputed embedding unless the summary should be embedded instead
+            let embedding = match input_embedding {
+                Some(emb) if mode != "summary" || summary.is_none() => Some(emb),
+                _ => None,
+            };
+            
+            prepared.push(PreparedChunk { chunk, summary, below_min_tokens, embedding, fit: EmbeddingFit::WHOLE });
+        }
+        
+        // Generate the missing embeddings in batches (only chunk nodes carry vectors)
+        if store_chunks {
+            self.embed_missing(&mut prepared, &mut errors).await;
+        }
+        
+        // Separate code and document chunks
+        let mut code_chunks: Vec<Chunk> = Vec::new();
+        let mut doc_chunks: Vec<Chunk> = Vec::new();
+        
+        for PreparedChunk { chunk, summary, below_min_tokens, embedding, fit } in prepared {
+            // In "both" mode the summary gets its own vector alongside the content one
+            // (embedding failures were already recorded per chunk)
+            let summary_embedding = match (&summary, mode, &embedding) {
+                (Some(text), "both", Some(_)) => match self.embedding_client.embed(text).await {
+                    Ok(emb) => Some(emb),
+                    Err(e) => {
+                        errors.push(format!("Summary embedding failed for chunk {}: {}", chunk.id, e));
+                        None
+                    }
+                },
+                _ => None,
+            };
+            
+            // Store chunk in Neo4j with embedding (graph + vector in one place).
+            // A chunk whose embedding failed is stored without one so
+            // `POST /api/graph/reindex` can backfill it later.
+            if let (Some(graph), true) = (self.graph.as_deref(), store_chunks) {
+                match self.create_chunk_node_with_embedding(
+                    graph,
+                    &chunk,
+                    embedding.as_deref(),
+                    summary.as_deref(),
+                    summary_embedding,
+                    below_min_tokens,
+                ).await {
+                    Ok(previous_hash) => {
+                        if embedding.is_some() {
+                            vectors_stored += 1;
+                            // A rewritten node may still carry the fit of its old content
+                            if let Err(e) = self.record_fit(graph, &EntityId(chunk.id), fit, previous_hash.is_some()).await {
+                                errors.push(e);
+                            }
+                        }
+                        if let Some(previous) = previous_hash {
+                            record_collision(&mut id_collisions, &chunk, &previous);
+                        }
                         chunks_ingested += 1;
                     }
                     Err(e) => {
@@ -89,23 +218,49 @@ impl ChunkProcessor {
             
             // Categorize chunks for cross-linking
             if chunk.source_kind == "code" {
-                code_chunks.push((chunk, embedding));
+                code_chunks.push(chunk);
             } else {
-                doc_chunks.push((chunk, embedding));
+                doc_chunks.push(chunk);
             }
         }
         
+        // Full rows in Postgres, one statement for the batch; search reads the
+        // graph copy, so a failure here is recorded without dropping the chunks
+        if let (Some(repository), true) = (&self.chunk_repository, store_chunks) {
+            if let Err(e) = repository.insert_chunks(code_chunks.iter().chain(&doc_chunks)).await {
+                errors.push(format!("Chunk persistence failed for {} chunks: {}", code_chunks.len() + doc_chunks.len(), e));
+            }
+        }
+        
+        // One COMMIT node per distinct commit, containing its chunks
+        if let (Some(graph), true) = (self.graph.as_deref(), store_chunks && self.config.commit_nodes) {
+            relationships_created += link_commits(graph, code_chunks.iter().chain(&doc_chunks), &mut errors).await;
+        }
+        
         // Extract entities from chunks
         if extract_entities {
+            // Relationships between extracted entities, flushed once at the end
+            let mut extracted_edges: Vec<BatchEdge> = Vec::new();
+            
             // Process code chunks
-            for (chunk, _embedding) in &code_chunks {
+            for chunk in &code_chunks {
                 let extraction = self.code_extractor.extract_with_relationships(
                     &chunk.content,
                     chunk.language.as_deref(),
                 );
                 
+                if detailed {
+                    extractions.push(ChunkExtraction { chunk_id: chunk.id, result: extraction.clone() });
+                }
+                if store_chunks {
+                    self.store_entity_names(chunk, &extraction, &mut errors).await;
+                }
+                
+                // Stored entity id per extracted name, for relationship endpoints
+                // (references outside any definition start at the chunk node)
+                let mut entity_ids = chunk_endpoint(chunk, store_chunks);
                 for entity in extraction.entities {
-                    if let Some(neo4j) = &self.neo4j {
+                    if let Some(graph) = self.graph.as_deref() {
                         let entity_obj = Entity::new(
                             entity.entity_type,
                             DataSource::from_str(&chunk.source_type).unwrap_or(DataSource::LocalFile),
@@ -114,185 +269,1404 @@ impl ChunkProcessor {
                             std::collections::HashMap::from([
                                 ("chunk_id".to_string(), serde_json::json!(chunk.id.to_string())),
                                 ("file_path".to_string(), serde_json::json!(chunk.file_path)),
+                                ("owner_id".to_string(), serde_json::json!(chunk.owner_id)),
                                 ("confidence".to_string(), serde_json::json!(entity.confidence)),
                             ]),
-                        );
+                        ).with_derived_id();
                         
-                        match neo4j.upsert_entity_node(&entity_obj).await {
-                            Ok(_) => entities_extracted += 1,
+                        match graph.upsert_entity_node(&entity_obj).await {
+                            Ok(stored) => {
+                                entities_extracted += 1;
+                                entity_ids.insert(entity.name, stored.id);
+                            }
                             Err(e) => errors.push(format!("Entity creation failed: {}", e)),
                         }
                     }
                 }
                 
-                // Create relationships
-                for rel in extraction.relationships {
-                    if let Some(neo4j) = &self.neo4j {
-                        match neo4j.create_relationship(
-                            &rel.from_name,
-                            &rel.to_name,
-                            rel.relationship_type,
-                            rel.confidence,
-                            None,
-                        ).await {
-                            Ok(_) => relationships_created += 1,
-                            Err(_) => {} // Silently skip relationship errors (entity may not exist)
+                extracted_edges.extend(resolve_extracted_relationships(&extraction.relationships, &entity_ids));
+                if let Some(graph) = self.graph.as_deref() {
+                    // Record where each import could live so it can be resolved
+                    for import in &extraction.imports {
+                        let Some(id) = entity_ids.get(import) else {
+                            continue;
+                        };
+                        if let Err(e) = graph.mark_import_stub(id, import_path_suffixes(import)).await {
+                            errors.push(format!("Import marking failed for {}: {}", import, e));
                         }
                     }
                 }
             }
             
+            // Link import stubs to the modules and files they name
+            if let (Some(graph), true) = (self.graph.as_deref(), self.config.resolve_imports && !code_chunks.is_empty()) {
+                match graph.resolve_imports().await {
+                    Ok(resolved) => relationships_created += resolved,
+                    Err(e) => errors.push(format!("Import resolution failed: {}", e)),
+                }
+            }
+            
             // Process document chunks
-            for (chunk, _embedding) in &doc_chunks {
+            for chunk in &doc_chunks {
                 let extraction = self.doc_extractor.extract_with_relationships(&chunk.content);
                 
+                if detailed {
+                    extractions.push(ChunkExtraction { chunk_id: chunk.id, result: extraction.clone() });
+                }
+                if store_chunks {
+                    self.store_entity_names(chunk, &extraction, &mut errors).await;
+                }
+                
+                let mut entity_ids = chunk_endpoint(chunk, store_chunks);
                 for entity in extraction.entities {
-                    if let Some(neo4j) = &self.neo4j {
+                    if let Some(graph) = self.graph.as_deref() {
+                        let mut properties = std::collections::HashMap::from([
+                            ("chunk_id".to_string(), serde_json::json!(chunk.id.to_string())),
+                            ("heading_path".to_string(), serde_json::json!(chunk.heading_path)),
+                            ("owner_id".to_string(), serde_json::json!(chunk.owner_id)),
+                            ("confidence".to_string(), serde_json::json!(entity.confidence)),
+                        ]);
+                        // Defined terms keep their definition
+                        if let Some(description) = entity.description {
+                            properties.insert("description".to_string(), serde_json::json!(description));
+                        }
                         let entity_obj = Entity::new(
                             entity.entity_type,
                             DataSource::from_str(&chunk.source_type).unwrap_or(DataSource::LocalFile),
                             format!("{}:{}", chunk.id, entity.name),
                             entity.name.clone(),
-                            std::collections::HashMap::from([
-                                ("chunk_id".to_string(), serde_json::json!(chunk.id.to_string())),
-                                ("heading_path".to_string(), serde_json::json!(chunk.heading_path)),
-                                ("confidence".to_string(), serde_json::json!(entity.confidence)),
-                            ]),
-                        );
+                            properties,
+                        ).with_derived_id();
                         
-                        match neo4j.upsert_entity_node(&entity_obj).await {
-                            Ok(_) => entities_extracted += 1,
+                        match graph.upsert_entity_node(&entity_obj).await {
+                            Ok(stored) => {
+                                entities_extracted += 1;
+                                entity_ids.insert(entity.name, stored.id);
+                            }
                             Err(e) => errors.push(format!("Entity creation failed: {}", e)),
                         }
                     }
                 }
+                
+                // Section hierarchy, references, and EXPLAINS edges to code examples
+                extracted_edges.extend(resolve_extracted_relationships(&extraction.relationships, &entity_ids));
+            }
+            
+            // All extracted relationships are written in one batch
+            if let (Some(graph), false) = (self.graph.as_deref(), extracted_edges.is_empty()) {
+                match graph.create_relationships_batch(&extracted_edges, ExtractionMethod::PatternMatch).await {
+                    Ok(result) => {
+                        relationships_created += result.created;
+                        if !result.missing.is_empty() {
+                            tracing::warn!("{} extracted relationships had a missing endpoint", result.missing.len());
+                        }
+                    }
+                    Err(e) => errors.push(format!("Relationship creation failed: {}", e)),
+                }
             }
         }
         
         // Create cross-source links using Neo4j native vector search
-        if create_cross_links && !code_chunks.is_empty() && !doc_chunks.is_empty() {
-            if let Some(neo4j) = &self.neo4j {
-                let links_created = self.create_cross_source_links(
-                    neo4j, 
+        // (impossible without stored chunk vectors)
+        if create_cross_links && store_chunks {
+            if let Some(graph) = self.graph.as_deref() {
+                evidence = self.create_cross_source_links(
+                    graph, 
                     &code_chunks, 
                     &doc_chunks
                 ).await;
-                relationships_created += links_created;
+                relationships_created += evidence.len();
             }
         }
         
+        if chunks_ingested > 0 || relationships_created > 0 {
+            self.invalidate_search_cache();
+        }
+        
         Ok(IngestChunksResponse {
             chunks_ingested,
             entities_extracted,
             relationships_created,
             vectors_stored,
+            chunks_skipped,
+            chunks_below_min_tokens,
+            id_collisions,
             errors,
+            extractions: detailed.then_some(extractions),
+            evidence: return_evidence.then_some(evidence),
         })
     }
     
-    /// Create a chunk node in Neo4j with its embedding
-    async fn create_chunk_node_with_embedding(
+    /// Whether this chunk's content was already seen in the batch or is stored for its owner
+    /// 
+    /// A failed lookup is recorded and the chunk is ingested anyway.
+    async fn is_duplicate(
         &self,
-        neo4j: &Neo4jClient,
         chunk: &Chunk,
-        embedding: &[f32],
+        batch_contents: &mut HashSet<(String, String)>,
+        errors: &mut Vec<String>,
+    ) -> bool {
+        if !batch_contents.insert((chunk.owner_id.clone(), chunk.content_hash.clone())) {
+            return true;
+        }
+        let Some(graph) = self.graph.as_deref() else {
+            return false;
+        };
+        match graph.find_chunk_by_hash(&chunk.content_hash, &chunk.owner_id).await {
+            Ok(existing) => existing.is_some(),
+            Err(e) => {
+                errors.push(format!("Duplicate check failed for chunk {}: {}", chunk.id, e));
+                false
+            }
+        }
+    }
+    
+    /// Fill in missing embeddings with batched calls to the embedding service
+    /// 
+    /// A failed batch records an error for each of its chunks; the other
+    /// batches are unaffected.
+    async fn embed_missing(&self, prepared: &mut [PreparedChunk], errors: &mut Vec<String>) {
+        let mode = self.config.summary_embedding_mode.as_str();
+        let pending: Vec<usize> = prepared.iter()
+            .enumerate()
+            .filter(|(_, p)| p.embedding.is_none())
+            .map(|(i, _)| i)
+            .collect();
+        
+        for group in pending.chunks(self.config.embedding_batch_size.max(1)) {
+            let texts: Vec<&str> = group.iter()
+                .map(|&i| embedding_text(mode, &prepared[i].chunk.content, prepared[i].summary.as_deref()))
+                .collect();
+            
+            match self.embed_windowed(&texts).await {
+                Ok(embeddings) => {
+                    for (&i, (embedding, fit)) in group.iter().zip(embeddings) {
+                        prepared[i].embedding = Some(embedding);
+                        prepared[i].fit = fit;
+                    }
+                }
+                Err(e) => {
+                    for &i in group {
+                        errors.push(format!("Embedding failed for chunk {}: {}", prepared[i].chunk.id, e));
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Embed nodes stored without a vector (e.g. while the embedding service was down)
+    /// 
+    /// Handles at most `request.limit` nodes per call; call repeatedly until
+    /// `embedded` is 0. A failed batch is retried one node at a time, so a
+    /// single bad node only fails itself; failed nodes stay unembedded, are
+    /// reported by id, and are picked up again next call.
+    pub async fn reindex(&self, request: &ReindexRequest, owner_id: Option<&str>) -> GraphResult<ReindexResponse> {
+        let graph = self.graph.as_deref()
+            .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+        
+        let nodes = graph
+            .nodes_missing_embeddings(request.label.as_deref(), request.limit, owner_id)
+            .await?;
+        let mut response = ReindexResponse { scanned: nodes.len(), ..Default::default() };
+        
+        for group in nodes.chunks(self.config.embedding_batch_size.max(1)) {
+            let e = match self.reindex_group(graph, group, &mut response).await {
+                Ok(()) => continue,
+                Err(e) => e,
+            };
+            // Retrying node by node can't help while the service is down
+            let unavailable = matches!(e, GraphError::ServiceUnavailable(_) | GraphError::EmbeddingUnavailable(_));
+            if group.len() == 1 || unavailable {
+                response.failed += group.len();
+                response.errors.push(format!("Reindexing {} nodes failed: {}", group.len(), e));
+                continue;
+            }
+            for node in group {
+                if let Err(e) = self.reindex_group(graph, std::slice::from_ref(node), &mut response).await {
+                    response.failed += 1;
+                    response.errors.push(format!("Reindexing node {} failed: {}", node.0, e));
+                }
+            }
+        }
+        
+        if response.embedded > 0 {
+            self.invalidate_search_cache();
+        }
+        
+        Ok(response)
+    }
+    
+    /// Embed and store one group of `reindex` nodes, adding what was done to `response`
+    async fn reindex_group(
+        &self,
+        graph: &dyn GraphStore,
+        group: &[(EntityId, String)],
+        response: &mut ReindexResponse,
     ) -> GraphResult<()> {
-        // First create the chunk as an entity node
-        let chunk_entity = Entity::new(
-            crate::models::EntityType::CodeEntity, // Use CodeEntity as generic chunk type
-            DataSource::from_str(&chunk.source_type).unwrap_or(DataSource::LocalFile),
-            chunk.id.to_string(),
-            chunk.file_path.clone().unwrap_or_else(|| "unknown".to_string()),
-            std::collections::HashMap::from([
-                ("content".to_string(), serde_json::json!(chunk.content)),
-                ("source_kind".to_string(), serde_json::json!(chunk.source_kind)),
-                ("source_type".to_string(), serde_json::json!(chunk.source_type)),
-                ("file_path".to_string(), serde_json::json!(chunk.file_path)),
-                ("language".to_string(), serde_json::json!(chunk.language)),
-                ("author".to_string(), serde_json::json!(chunk.author)),
-                ("owner_id".to_string(), serde_json::json!(chunk.owner_id)),
-            ]),
-        );
+        let updates = self.embed_group(group).await?;
+        let updated = graph.batch_set_embeddings(updates).await?;
+        response.embedded += updated;
+        // Nodes deleted since the scan
+        response.failed += group.len() - updated.min(group.len());
+        for (id, content) in group {
+            let (_, fit) = self.embedding_windows(content);
+            if let Err(e) = self.record_fit(graph, id, fit, false).await {
+                response.errors.push(e);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Embed a batch of `(node_id, content)` pairs into `batch_set_embeddings` updates
+    async fn embed_group(&self, group: &[(EntityId, String)]) -> GraphResult<Vec<(EntityId, Vec<f32>, String, String)>> {
+        let texts: Vec<&str> = group.iter().map(|(_, content)| content.as_str()).collect();
+        let embeddings = self.embed_windowed(&texts).await?;
         
-        // Create node
-        neo4j.upsert_entity_node(&chunk_entity).await?;
+        Ok(group.iter()
+            .zip(embeddings)
+            .map(|((id, _), (embedding, _))| (
+                *id,
+                embedding,
+                self.config.embedding_model.clone(),
+                EMBEDDING_PROVIDER.to_string(),
+            ))
+            .collect())
+    }
+    
+    /// `embedding_windows` with the configured limits
+    fn embedding_windows<'a>(&self, text: &'a str) -> (Vec<&'a str>, EmbeddingFit) {
+        embedding_windows(
+            text,
+            self.config.max_embed_chars,
+            self.config.max_embed_windows,
+            &self.config.embed_overflow_mode,
+        )
+    }
+    
+    /// Embed each text in one batch call, fitting oversized texts to `max_embed_chars`
+    /// 
+    /// A text split into windows gets the mean of its window vectors.
+    async fn embed_windowed(&self, texts: &[&str]) -> GraphResult<Vec<(Vec<f32>, EmbeddingFit)>> {
+        let windowed: Vec<(Vec<&str>, EmbeddingFit)> = texts.iter()
+            .map(|text| self.embedding_windows(text))
+            .collect();
+        let pieces: Vec<String> = windowed.iter()
+            .flat_map(|(windows, _)| windows.iter().map(|window| window.to_string()))
+            .collect();
+        let piece_count = pieces.len();
+        let embeddings = self.embedding_client.embed_batch(pieces).await?;
+        if embeddings.len() != piece_count {
+            return Err(GraphError::Embedding(format!(
+                "batch returned {} vectors for {} texts",
+                embeddings.len(), piece_count
+            )));
+        }
+        
+        let mut embeddings = embeddings.into_iter();
+        windowed.into_iter()
+            .map(|(windows, fit)| {
+                let vectors: Vec<Vec<f32>> = embeddings.by_ref().take(windows.len()).collect();
+                Ok((average_embeddings(vectors)?, fit))
+            })
+            .collect()
+    }
+    
+    /// Record a non-trivial fit (or any fit when `overwrite`) on the chunk node
+    async fn record_fit(&self, graph: &dyn GraphStore, chunk_id: &EntityId, fit: EmbeddingFit, overwrite: bool) -> Result<(), String> {
+        if fit == EmbeddingFit::WHOLE && !overwrite {
+            return Ok(());
+        }
+        graph.set_embedding_windows(chunk_id, fit.windows, fit.truncated)
+            .await
+            .map_err(|e| format!("Recording embedding windows failed for chunk {}: {}", chunk_id, e))
+    }
+    
+    /// Whether a chunk falls below the configured `min_token_count`
+    fn is_below_min_tokens(&self, chunk: &Chunk) -> bool {
+        self.config.min_token_count > 0
+            && chunk.token_count_or_estimate() < self.config.min_token_count
+    }
+    
+    /// Summarize a chunk if summarization is enabled and the chunk is large enough
+    async fn summarize(&self, chunk: &Chunk) -> GraphResult<Option<String>> {
+        match &self.summarization_client {
+            Some(client) if chunk.content.len() >= self.config.summarization_min_chars => {
+                client.summarize(&chunk.content).await.map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+    
+    /// Record a chunk's extracted entity names on its node, where the
+    /// linking query looks for explicit mentions
+    async fn store_entity_names(&self, chunk: &Chunk, extraction: &ExtractionResult, errors: &mut Vec<String>) {
+        let Some(graph) = self.graph.as_deref() else {
+            return;
+        };
+        let mut names: Vec<String> = extraction.entities.iter().map(|e| e.name.clone()).collect();
+        names.sort();
+        names.dedup();
+        if names.is_empty() {
+            return;
+        }
+        if let Err(e) = graph.set_chunk_entity_names(&EntityId(chunk.id), &names).await {
+            errors.push(format!("Entity names update failed for chunk {}: {}", chunk.id, e));
+        }
+    }
+    
+    /// Create a chunk node in Neo4j with its embedding (if there is one)
+    async fn create_chunk_node_with_embedding(
+        &self,
+        graph: &dyn GraphStore,
+        chunk: &Chunk,
+        embedding: Option<&[f32]>,
+        summary: Option<&str>,
+        summary_embedding: Option<Vec<f32>>,
+        below_min_tokens: bool,
+    ) -> GraphResult<Option<String>> {
+        // Create the CHUNK node with its properties
+        let previous_hash = graph.upsert_chunk_node(chunk, summary, below_min_tokens).await?;
         
         // Set embedding on the node
-        neo4j.set_node_embedding(
-            &chunk.id.to_string(),
-            embedding.to_vec(),
-            "sentence-transformers-384",  // Default model
-            "embeddings-service",
-        ).await?;
+        if let Some(embedding) = embedding {
+            graph.set_node_embedding(
+                &EntityId(chunk.id),
+                embedding.to_vec(),
+                &self.config.embedding_model,
+                EMBEDDING_PROVIDER,
+            ).await?;
+        }
         
-        Ok(())
+        if let Some(summary_embedding) = summary_embedding {
+            graph.set_summary_embedding(&EntityId(chunk.id), summary_embedding).await?;
+        }
+        
+        Ok(previous_hash)
     }
     
     /// Create cross-source links using Neo4j vector similarity
+    /// 
+    /// Matches from both directions are collected first so each directed link is
+    /// written once and both directions of a pair get the same confidence.
     async fn create_cross_source_links(
         &self,
-        neo4j: &Neo4jClient,
-        code_chunks: &[(Chunk, Vec<f32>)],
-        doc_chunks: &[(Chunk, Vec<f32>)],
-    ) -> usize {
-        let mut links_created = 0;
-        
-        // For each document chunk, find similar code chunks
-        for (doc_chunk, _) in doc_chunks {
-            match neo4j.find_similar_chunks_for_linking(
-                &doc_chunk.id.to_string(),
-                "code",
-                self.config.max_cross_links_per_chunk,
-                self.config.similarity_threshold,
-            ).await {
-                Ok(matches) => {
-                    for m in matches {
-                        if let Ok(_) = neo4j.create_cross_source_link(
-                            &doc_chunk.id.to_string(),
-                            &m.target_id,
-                            m.confidence,
-                            m.similarity_score,
-                            m.has_explicit_mention,
-                            m.has_author_overlap,
-                        ).await {
-                            links_created += 1;
+        graph: &dyn GraphStore,
+        code_chunks: &[Chunk],
+        doc_chunks: &[Chunk],
+    ) -> Vec<SemanticLink> {
+        let boosts = LinkBoosts::from_config(&self.config);
+        let mut plan = CrossLinkPlan::new();
+        let batch: Vec<&Chunk> = code_chunks.iter().chain(doc_chunks).collect();
+        
+        // Each configured (from, to) pair links the batch's `from` chunks to
+        // `to` chunks, skipped when the batch has no `to` chunks (chunks below
+        // min_token_count never act as link sources or targets, nor do chunks
+        // stored without an embedding)
+        let mut sources: Vec<(EntityId, String)> = Vec::new();
+        for (from, to) in &self.config.cross_link_pairs {
+            if !batch.iter().any(|c| c.source_kind == *to) {
+                continue;
+            }
+            sources.extend(batch.iter()
+                .filter(|c| c.source_kind == *from && !self.is_below_min_tokens(c))
+                .map(|c| (EntityId(c.id), to.clone())));
+        }
+        
+        // Similarity lookups run concurrently, never holding more connections
+        // than the Neo4j pool has
+        let concurrency = self.config.linking_concurrency
+            .min(self.config.neo4j_max_connections)
+            .max(1);
+        let mut found: Vec<(usize, EntityId, Vec<CrossSourceMatch>)> = stream::iter(sources.into_iter().enumerate())
+            .map(|(i, (source_id, target_kind))| {
+                let boosts = &boosts;
+                async move {
+                    match graph.find_similar_chunks_for_linking(
+                        &source_id,
+                        &target_kind,
+                        self.config.max_cross_links_per_chunk,
+                        self.config.similarity_threshold,
+                        boosts,
+                        None,
+                    ).await {
+                        Ok(matches) => Some((i, source_id, matches)),
+                        Err(e) => {
+                            tracing::warn!("Cross-source linking failed for chunk {}: {}", source_id, e);
+                            None
                         }
                     }
                 }
-                Err(e) => {
-                    tracing::warn!("Cross-source linking failed for chunk {}: {}", doc_chunk.id, e);
-                }
+            })
+            .buffer_unordered(concurrency)
+            .filter_map(future::ready)
+            .collect()
+            .await;
+        
+        // Lookups finish in any order; planning in source order keeps the
+        // links (and their direction) the same as a sequential run
+        found.sort_by_key(|(i, _, _)| *i);
+        for (_, source_id, matches) in &found {
+            for m in matches {
+                plan.add(*source_id, m);
             }
         }
         
-        // For each code chunk, find similar document chunks
-        for (code_chunk, _) in code_chunks {
-            match neo4j.find_similar_chunks_for_linking(
-                &code_chunk.id.to_string(),
-                "document",
-                self.config.max_cross_links_per_chunk,
-                self.config.similarity_threshold,
-            ).await {
-                Ok(matches) => {
-                    for m in matches {
-                        if let Ok(_) = neo4j.create_cross_source_link(
-                            &code_chunk.id.to_string(),
-                            &m.target_id,
-                            m.confidence,
-                            m.similarity_score,
-                            m.has_explicit_mention,
-                            m.has_author_overlap,
-                        ).await {
-                            links_created += 1;
-                        }
+        // Edges are written one at a time: both directions of a pair touch
+        // the same nodes, and concurrent writes would contend for their locks.
+        // Links within the batch are re-scored with the linker's own signals;
+        // links to chunks stored earlier keep what the graph query found
+        let by_id: HashMap<EntityId, &Chunk> = batch.iter()
+            .map(|chunk| (EntityId(chunk.id), *chunk))
+            .collect();
+        let mut created = Vec::new();
+        let mut evidence = Vec::new();
+        for mut link in plan.into_links() {
+            let explained = match (by_id.get(&link.from_id), by_id.get(&link.to_id)) {
+                (Some(from), Some(to)) => self.linker.explain_link(&mut link, from, to),
+                _ => link.to_semantic_link(),
+            };
+            if graph.create_cross_source_link(
+                &link.from_id,
+                &link.to_id,
+                link.confidence,
+                link.similarity_score,
+                &link.extraction_methods,
+            ).await.is_err() {
+                continue;
+            }
+            evidence.push(link.to_evidence());
+            created.push(explained);
+        }
+        if let Some(repository) = &self.chunk_repository {
+            if let Err(e) = repository.insert_evidence(&evidence).await {
+                tracing::warn!("Evidence storage failed for {} links: {}", evidence.len(), e);
+            }
+        }
+        
+        created
+    }
+}
+
+/// A chunk ready for storage, possibly still waiting for its embedding
+struct PreparedChunk {
+    chunk: Chunk,
+    summary: Option<String>,
+    below_min_tokens: bool,
+    embedding: Option<Vec<f32>>,
+    /// How the embedded text was fit to `max_embed_chars`
+    fit: EmbeddingFit,
+}
+
+/// How a text was fit into the embedding model's context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EmbeddingFit {
+    /// Window vectors averaged into the embedding
+    windows: usize,
+    /// Whether text past the limit was left out
+    truncated: bool,
+}
+
+impl EmbeddingFit {
+    /// The whole text embedded in one piece
+    const WHOLE: Self = Self { windows: 1, truncated: false };
+}
+
+/// Reject a request containing any chunk with empty content
+pub fn check_strict(request: &IngestChunksRequest) -> GraphResult<()> {
+    match request.chunks.iter().position(|c| c.content.trim().is_empty()) {
+        Some(index) => Err(GraphError::InvalidRequest(format!(
+            "Chunk at index {} has empty content",
+            index
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Upsert a `COMMIT` node per (owner, `commit_sha`) and link it to its chunks
+/// 
+/// Returns the number of `CONTAINS` edges linked.
+async fn link_commits<'a>(
+    graph: &dyn GraphStore,
+    chunks: impl Iterator<Item = &'a Chunk>,
+    errors: &mut Vec<String>,
+) -> usize {
+    let mut commits: BTreeMap<(&str, &str), Vec<&Chunk>> = BTreeMap::new();
+    for chunk in chunks {
+        if let Some(sha) = chunk.commit_sha.as_deref() {
+            commits.entry((chunk.owner_id.as_str(), sha)).or_default().push(chunk);
+        }
+    }
+    
+    let mut linked = 0;
+    for ((owner_id, sha), chunks) in commits {
+        let committed_at = chunks.iter().find_map(|chunk| chunk.commit_date);
+        let commit = Entity::new(
+            EntityType::Commit,
+            DataSource::from_str(&chunks[0].source_type).unwrap_or(DataSource::LocalFile),
+            format!("{}:{}", owner_id, sha),
+            sha.to_string(),
+            HashMap::from([
+                ("owner_id".to_string(), serde_json::json!(owner_id)),
+                ("committed_at".to_string(), serde_json::json!(committed_at)),
+            ]),
+        ).with_derived_id();
+        let chunk_ids: Vec<EntityId> = chunks.iter().map(|chunk| EntityId(chunk.id)).collect();
+        
+        let result = match graph.upsert_entity_node(&commit).await {
+            Ok(_) => graph.link_commit_chunks(&EntityId(commit.id), &chunk_ids).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(count) => linked += count,
+            Err(e) => errors.push(format!("Commit linking failed for {}: {}", sha, e)),
+        }
+    }
+    linked
+}
+
+/// Entity ids to start from, holding the chunk's own node when it was stored
+fn chunk_endpoint(chunk: &Chunk, store_chunks: bool) -> HashMap<String, EntityId> {
+    let mut entity_ids = HashMap::new();
+    if store_chunks {
+        entity_ids.insert(CHUNK_ENDPOINT.to_string(), EntityId(chunk.id));
+    }
+    entity_ids
+}
+
+/// Edges for extracted relationships between entities stored for the same chunk
+/// 
+/// Relationships whose endpoints weren't stored are skipped.
+fn resolve_extracted_relationships<'a>(
+    relationships: &'a [ExtractedRelationship],
+    entity_ids: &'a HashMap<String, EntityId>,
+) -> impl Iterator<Item = BatchEdge> + 'a {
+    relationships.iter().filter_map(|rel| {
+        let from_id = entity_ids.get(&rel.from_name)?;
+        let to_id = entity_ids.get(&rel.to_name)?;
+        Some((*from_id, *to_id, rel.relationship_type.clone(), rel.confidence))
+    })
+}
+
+/// Note an id that now carries different content than before
+/// 
+/// A legacy MD5 hash can't be compared, so it never counts as a collision.
+fn record_collision(id_collisions: &mut Vec<Uuid>, chunk: &Chunk, previous_hash: &str) {
+    if previous_hash != chunk.content_hash
+        && !Chunk::is_legacy_hash(previous_hash)
+        && !id_collisions.contains(&chunk.id)
+    {
+        tracing::warn!("Chunk id {} reused with different content", chunk.id);
+        id_collisions.push(chunk.id);
+    }
+}
+
+/// Pick the text to embed for a chunk based on the summary embedding mode
+/// 
+/// Only "summary" swaps in the summary; "content" and "both" embed the content
+/// (in "both" mode the summary is embedded separately).
+fn embedding_text<'a>(mode: &str, content: &'a str, summary: Option<&'a str>) -> &'a str {
+    match (mode, summary) {
+        ("summary", Some(summary)) => summary,
+        _ => content,
+    }
+}
+
+/// Split `text` into the pieces to embed
+///
+/// Text over `max_chars` characters (0 = no limit) becomes consecutive
+/// windows of at most `max_chars`, at most `max_windows` of them, or just its
+/// first `max_chars` in "truncate" mode. Each window ends at the last
+/// function, class, or markdown heading that starts within reach, and is
+/// cut at `max_chars` only when none does.
+fn embedding_windows<'a>(text: &'a str, max_chars: usize, max_windows: usize, mode: &str) -> (Vec<&'a str>, EmbeddingFit) {
+    if max_chars == 0 || text.chars().count() <= max_chars {
+        return (vec![text], EmbeddingFit::WHOLE);
+    }
+    // Byte offset `max_chars` characters past `start`, if the text goes on that far
+    let reach = |start: usize| text[start..].char_indices().nth(max_chars).map(|(offset, _)| start + offset);
+    if mode == "truncate" {
+        let end = reach(0).unwrap_or(text.len());
+        return (vec![&text[..end]], EmbeddingFit { windows: 1, truncated: true });
+    }
+    let max_windows = max_windows.max(1);
+    let boundaries = split_points(text);
+    let mut windows = Vec::new();
+    let mut start = 0;
+    while start < text.len() && windows.len() <= max_windows {
+        let Some(limit) = reach(start) else {
+            windows.push(&text[start..]);
+            break;
+        };
+        let end = boundaries.iter()
+            .rev()
+            .copied()
+            .find(|&boundary| boundary > start && boundary <= limit)
+            .unwrap_or(limit);
+        windows.push(&text[start..end]);
+        start = end;
+    }
+    let truncated = windows.len() > max_windows;
+    windows.truncate(max_windows);
+    let fit = EmbeddingFit { windows: windows.len(), truncated };
+    (windows, fit)
+}
+
+/// Byte offsets of the lines where a function or class starts (as found by
+/// `CodeEntityExtractor`) or a markdown heading does, in order
+fn split_points(text: &str) -> Vec<usize> {
+    let mut lines: HashSet<usize> = CodeEntityExtractor::new()
+        .extract(text, None)
+        .into_iter()
+        .filter(|entity| matches!(entity.entity_type, EntityType::Function | EntityType::Class))
+        .filter_map(|entity| entity.start_line)
+        .collect();
+    lines.extend(heading_lines(text));
+    let mut offset = 0;
+    let mut points = Vec::new();
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        if lines.contains(&(i + 1)) {
+            points.push(offset);
+        }
+        offset += line.len();
+    }
+    points
+}
+
+/// Element-wise mean of the window vectors of one text
+fn average_embeddings(mut vectors: Vec<Vec<f32>>) -> GraphResult<Vec<f32>> {
+    if vectors.len() == 1 {
+        return Ok(vectors.remove(0));
+    }
+    let dimension = vectors.first().map_or(0, |v| v.len());
+    if vectors.iter().any(|v| v.len() != dimension) {
+        return Err(GraphError::Embedding("window embeddings differ in dimension".to_string()));
+    }
+    let count = vectors.len() as f32;
+    Ok((0..dimension)
+        .map(|d| vectors.iter().map(|v| v[d]).sum::<f32>() / count)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChunkInput, EdgeDirection, RelationshipType};
+    
+    fn chunk_input(content: &str) -> ChunkInput {
+        serde_json::from_value(serde_json::json!({
+            "content": content,
+            "source_kind": "code",
+            "source_type": "github",
+            "source_id": "repo/file.rs",
+            "owner_id": "owner-1",
+        })).unwrap()
+    }
+    
+    fn test_processor(config: Config) -> ChunkProcessor {
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        ChunkProcessor::new(config, None, embedding_client)
+    }
+    
+    fn request(chunks: Vec<ChunkInput>, strict: Option<bool>) -> IngestChunksRequest {
+        IngestChunksRequest {
+            chunks,
+            extract_entities: Some(false),
+            create_cross_links: Some(false),
+            strict,
+            detailed: None,
+            store_chunks: None,
+            dedupe: None,
+            return_evidence: None,
+        }
+    }
+    
+    #[test]
+    fn test_embedding_text_follows_summary_mode() {
+        assert_eq!(embedding_text("content", "full", Some("short")), "full");
+        assert_eq!(embedding_text("summary", "full", Some("short")), "short");
+        assert_eq!(embedding_text("summary", "full", None), "full");
+        assert_eq!(embedding_text("both", "full", Some("short")), "full");
+    }
+    
+    #[tokio::test]
+    async fn test_empty_content_chunk_is_skipped() {
+        // Unreachable embedding service: any embed attempt would surface as an error
+        let mut config = Config::test_default();
+        config.embedding_service_url = "http://127.0.0.1:1".to_string();
+        let processor = test_processor(config);
+        
+        let response = processor
+            .ingest_chunks(request(vec![chunk_input("   \n\t ")], None))
+            .await
+            .unwrap();
+        
+        assert_eq!(response.chunks_skipped, 1);
+        assert_eq!(response.chunks_ingested, 0);
+        assert_eq!(response.vectors_stored, 0);
+        assert!(response.errors.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_empty_content_chunk_rejected_in_strict_mode() {
+        let processor = test_processor(Config::test_default());
+        
+        let result = processor
+            .ingest_chunks(request(vec![chunk_input("fn main() {}"), chunk_input("")], Some(true)))
+            .await;
+        
+        assert!(matches!(result, Err(GraphError::InvalidRequest(_))));
+    }
+    
+    #[test]
+    fn test_min_token_count_threshold() {
+        let mut config = Config::test_default();
+        config.min_token_count = 5;
+        let processor = test_processor(config);
+        
+        let short = chunk_input("use std::fmt;").into_chunk();
+        let long = chunk_input("pub fn add(a: i32, b: i32) -> i32 { a + b }").into_chunk();
+        assert!(processor.is_below_min_tokens(&short));
+        assert!(!processor.is_below_min_tokens(&long));
+        
+        // An explicit token count from the chunker wins over the estimate
+        let mut counted = chunk_input("use std::fmt;");
+        counted.token_count = Some(12);
+        assert!(!processor.is_below_min_tokens(&counted.into_chunk()));
+        
+        // Disabled by default
+        let processor = test_processor(Config::test_default());
+        assert!(!processor.is_below_min_tokens(&short));
+    }
+    
+    #[tokio::test]
+    async fn test_detailed_ingest_reports_extractions() {
+        let processor = test_processor(Config::test_default());
+        let mut input = chunk_input("pub struct UserService {}\npub fn login() {}");
+        input.embedding = Some(vec![0.1, 0.2]);
+        let chunk_id = Uuid::new_v4();
+        input.id = Some(chunk_id);
+        
+        let mut req = request(vec![input], None);
+        req.extract_entities = Some(true);
+        req.detailed = Some(true);
+        let response = processor.ingest_chunks(req).await.unwrap();
+        
+        let extractions = response.extractions.unwrap();
+        assert_eq!(extractions.len(), 1);
+        assert_eq!(extractions[0].chunk_id, chunk_id);
+        assert!(extractions[0].result.entities.iter().any(|e| e.name == "UserService"));
+        assert!(extractions[0].result.relationships.iter().any(|r| r.to_name == "login"));
+        
+        // Off by default, and omitted from the JSON entirely
+        let mut input = chunk_input("fn main() {}");
+        input.embedding = Some(vec![0.1, 0.2]);
+        let mut req = request(vec![input], None);
+        req.extract_entities = Some(true);
+        let response = processor.ingest_chunks(req).await.unwrap();
+        assert!(response.extractions.is_none());
+        assert!(serde_json::to_value(&response).unwrap().get("extractions").is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_ingest_rejects_chunk_ids_of_another_owner() {
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+        let processor = ChunkProcessor::new(config, Some(graph.clone()), embedding_client);
+        let id = Uuid::new_v4();
+        let input = |content: &str, owner: &str| {
+            let mut input = chunk_input(content);
+            input.id = Some(id);
+            input.owner_id = owner.to_string();
+            input.embedding = Some(vec![1.0, 0.0]);
+            input
+        };
+        
+        let response = processor.ingest_chunks(request(vec![input("fn login() {}", "owner-1")], None)).await.unwrap();
+        assert_eq!(response.chunks_ingested, 1);
+        
+        let response = processor.ingest_chunks(request(vec![input("fn steal() {}", "owner-2")], None)).await.unwrap();
+        assert_eq!(response.chunks_ingested, 0);
+        assert!(response.errors.iter().any(|e| e.contains(&format!("Chunk {} belongs to another owner", id))), "{:?}", response.errors);
+        assert!(graph.get_entity(&EntityId(id), Some("owner-1")).await.unwrap().is_some());
+        assert!(graph.get_entity(&EntityId(id), Some("owner-2")).await.unwrap().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_ingest_links_chunks_in_memory_graph() {
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+        let processor = ChunkProcessor::new(config, Some(graph.clone()), embedding_client);
+        
+        let mut code = chunk_input("pub fn login(user: &User) -> Session {}");
+        code.embedding = Some(vec![1.0, 0.0]);
+        let mut doc = chunk_input("Login creates a session for the user.");
+        doc.source_kind = "document".to_string();
+        doc.embedding = Some(vec![0.9, 0.1]);
+        let doc_id = Uuid::new_v4();
+        doc.id = Some(doc_id);
+        
+        let mut req = request(vec![code, doc], None);
+        req.create_cross_links = Some(true);
+        let response = processor.ingest_chunks(req).await.unwrap();
+        assert_eq!(response.chunks_ingested, 2);
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert!(response.evidence.is_none());
+        
+        let links = graph.cross_source_chunks(&[EntityId(doc_id)], "both", 100, None).await.unwrap();
+        // doc→code and its code→doc counterpart
+        assert_eq!(links.len(), 2);
+        assert!(links.iter().all(|link| link.relationship == "SEMANTICALLY_SIMILAR"));
+    }
+    
+    #[tokio::test]
+    async fn test_same_kind_linking_follows_configured_pairs() {
+        let ingest = |config: Config, source_kind: &str| {
+            let source_kind = source_kind.to_string();
+            async move {
+                let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+                let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+                let processor = ChunkProcessor::new(config, Some(graph), embedding_client);
+                let chunks = [("Sessions expire after an hour.", vec![1.0, 0.0]), ("Sessions last one hour.", vec![0.9, 0.1])]
+                    .into_iter()
+                    .map(|(content, embedding)| {
+                        let mut chunk = chunk_input(content);
+                        chunk.source_kind = source_kind.clone();
+                        chunk.embedding = Some(embedding);
+                        chunk
+                    })
+                    .collect();
+                let mut req = request(chunks, None);
+                req.create_cross_links = Some(true);
+                processor.ingest_chunks(req).await.unwrap().relationships_created
+            }
+        };
+        
+        // Only code↔document by default
+        assert_eq!(ingest(Config::test_default(), "code").await, 0);
+        assert_eq!(ingest(Config::test_default(), "document").await, 0);
+        
+        let mut config = Config::test_default();
+        config.cross_link_pairs = vec![("document".to_string(), "document".to_string())];
+        assert_eq!(ingest(config, "document").await, 2);
+    }
+    
+    #[tokio::test]
+    async fn test_ingest_returns_link_evidence_with_boosters() {
+        let mut config = Config::test_default();
+        config.enable_explicit_mentions = true;
+        config.enable_author_overlap = true;
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+        let processor = ChunkProcessor::new(config, Some(graph.clone()), embedding_client);
+        
+        let mut code = chunk_input("pub fn login(user: &User) -> Session {}");
+        code.embedding = Some(vec![1.0, 0.0]);
+        code.author = Some("ana".to_string());
+        let code_id = Uuid::new_v4();
+        code.id = Some(code_id);
+        let mut doc = chunk_input("Call `login` to create a session for the user.");
+        doc.source_kind = "document".to_string();
+        doc.embedding = Some(vec![0.9, 0.1]);
+        doc.author = Some("ana".to_string());
+        let doc_id = Uuid::new_v4();
+        doc.id = Some(doc_id);
+        
+        let mut req = request(vec![code, doc], None);
+        req.create_cross_links = Some(true);
+        req.return_evidence = Some(true);
+        let response = processor.ingest_chunks(req).await.unwrap();
+        
+        // The mention is found by the linker, not the graph query, and still counts
+        let evidence = response.evidence.unwrap();
+        assert_eq!(evidence.len(), 2);
+        for link in &evidence {
+            assert_eq!(link.explicit_mention.as_deref(), Some("Mentions: `login`"));
+            assert!(link.author_overlap);
+            assert_eq!(link.extraction_methods, ["vector_similarity", "author_overlap", "explicit_mention"]);
+            assert!(link.confidence > link.similarity_score.unwrap());
+        }
+        assert_eq!(evidence[0].confidence, evidence[1].confidence);
+        
+        // The stored edge carries the boosted confidence
+        let links = graph.cross_source_chunks(&[EntityId(doc_id)], "both", 100, None).await.unwrap();
+        assert!(links.iter().all(|link| link.confidence == evidence[0].confidence));
+    }
+    
+    #[tokio::test]
+    async fn test_concurrent_linking_matches_sequential() {
+        let ids: Vec<Uuid> = (0..24).map(|_| Uuid::new_v4()).collect();
+        let link = |concurrency: usize| {
+            let ids = ids.clone();
+            async move {
+                let mut config = Config::test_default();
+                config.linking_concurrency = concurrency;
+                let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+                let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+                let processor = ChunkProcessor::new(config, Some(graph.clone()), embedding_client);
+                
+                let chunks = ids.iter().enumerate().map(|(i, id)| {
+                    let mut input = chunk_input(&format!("chunk {}", i));
+                    if i % 2 == 1 {
+                        input.source_kind = "document".to_string();
                     }
-                }
-                Err(e) => {
-                    tracing::warn!("Cross-source linking failed for chunk {}: {}", code_chunk.id, e);
-                }
+                    let angle = i as f32 * 0.07;
+                    input.embedding = Some(vec![angle.cos(), angle.sin()]);
+                    input.id = Some(*id);
+                    input
+                }).collect();
+                let mut req = request(chunks, None);
+                req.create_cross_links = Some(true);
+                req.return_evidence = Some(true);
+                let response = processor.ingest_chunks(req).await.unwrap();
+                
+                let hit_ids: Vec<EntityId> = ids.iter().copied().map(EntityId).collect();
+                let mut edges: Vec<_> = graph.cross_source_chunks(&hit_ids, "both", 100, None)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|link| (link.hit_id, link.chunk.chunk_id, link.relationship, link.confidence, link.direction))
+                    .collect();
+                edges.sort_by_key(|(hit_id, chunk_id, _, _, direction)| (*hit_id, *chunk_id, *direction == EdgeDirection::Incoming));
+                let evidence: Vec<(Uuid, Uuid, f32)> = response.evidence.unwrap()
+                    .iter()
+                    .map(|link| (link.from_chunk_id, link.to_chunk_id, link.confidence))
+                    .collect();
+                (response.relationships_created, evidence, edges)
+            }
+        };
+        
+        let sequential = link(1).await;
+        assert!(sequential.0 > 20);
+        assert_eq!(link(8).await, sequential);
+    }
+    
+    #[tokio::test]
+    async fn test_extracted_entity_names_activate_mention_boost() {
+        let mut config = Config::test_default();
+        config.enable_explicit_mentions = true;
+        let boosts = LinkBoosts::from_config(&config);
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+        let processor = ChunkProcessor::new(config, Some(graph.clone()), embedding_client);
+        
+        let mut code = chunk_input("pub fn login(user: &User) -> Session {}");
+        code.embedding = Some(vec![1.0, 0.0]);
+        let mut doc = chunk_input("Call `login` to create a session for the user.");
+        doc.source_kind = "document".to_string();
+        doc.embedding = Some(vec![0.9, 0.1]);
+        let doc_id = Uuid::new_v4();
+        doc.id = Some(doc_id);
+        
+        let mut req = request(vec![code, doc], None);
+        req.extract_entities = Some(true);
+        let response = processor.ingest_chunks(req).await.unwrap();
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        
+        // The doc's extracted `login` reference appears in the code chunk
+        let matches = graph
+            .find_similar_chunks_for_linking(&EntityId(doc_id), "code", 5, 0.0, &boosts, None)
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].has_explicit_mention);
+        assert!(matches[0].confidence > matches[0].similarity_score);
+    }
+    
+    #[tokio::test]
+    async fn test_extracted_contains_edge_lands_in_graph() {
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+        let processor = ChunkProcessor::new(config, Some(graph.clone()), embedding_client);
+        let chunk_id = Uuid::new_v4();
+        let ingest = || {
+            let mut input = chunk_input("class UserService:\n    def login(self):\n        pass\n");
+            input.language = Some("python".to_string());
+            input.id = Some(chunk_id);
+            input.embedding = Some(vec![1.0, 0.0]);
+            let mut req = request(vec![input], None);
+            req.extract_entities = Some(true);
+            processor.ingest_chunks(req)
+        };
+        
+        let response = ingest().await.unwrap();
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        
+        let classes = graph.find_entities(Some(EntityType::Class), None, 10, None).await.unwrap();
+        assert_eq!(classes.len(), 1);
+        let contains = [RelationshipType::Contains];
+        let neighbors = graph
+            .get_neighbors(&EntityId::parse(&classes[0].0).unwrap(), Some(&contains), "outgoing", 1, &Default::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(neighbors.len(), 1);
+        assert_eq!(neighbors[0].name, "login");
+        
+        // Re-ingesting the chunk updates the same entity nodes and edges
+        ingest().await.unwrap();
+        let classes = graph.find_entities(Some(EntityType::Class), None, 10, None).await.unwrap();
+        assert_eq!(classes.len(), 1);
+        let neighbors = graph
+            .get_neighbors(&EntityId::parse(&classes[0].0).unwrap(), Some(&contains), "outgoing", 1, &Default::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(neighbors.len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_same_entity_from_two_owners_keeps_both_nodes() {
+        let graph = crate::graph_db::InMemoryGraph::new();
+        let entity = |owner: &str| Entity::new(
+            EntityType::Function,
+            DataSource::GitHub,
+            "shared-chunk:login".to_string(),
+            "login".to_string(),
+            HashMap::from([("owner_id".to_string(), serde_json::json!(owner))]),
+        ).with_derived_id();
+        let (first, second) = (entity("owner-1"), entity("owner-2"));
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.id, entity("owner-1").id);
+        
+        graph.upsert_entity_node(&first).await.unwrap();
+        graph.upsert_entity_node(&second).await.unwrap();
+        for owner in ["owner-1", "owner-2"] {
+            let found = graph.find_entities(Some(EntityType::Function), None, 10, Some(owner)).await.unwrap();
+            assert_eq!(found.len(), 1, "{}", owner);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_chunk_level_ticket_reference_starts_at_chunk() {
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+        let processor = ChunkProcessor::new(config, Some(graph.clone()), embedding_client);
+        let chunk_id = Uuid::new_v4();
+        let mut input = chunk_input("// Tracked in AUTH-42\nconst TIMEOUT: u64 = 30;\n");
+        input.id = Some(chunk_id);
+        input.embedding = Some(vec![1.0, 0.0]);
+        let mut req = request(vec![input], None);
+        req.extract_entities = Some(true);
+        
+        let response = processor.ingest_chunks(req).await.unwrap();
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        
+        let references = [RelationshipType::References];
+        let neighbors = graph
+            .get_neighbors(&EntityId(chunk_id), Some(&references), "outgoing", 1, &Default::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(neighbors.len(), 1);
+        assert_eq!(neighbors[0].name, "AUTH-42");
+    }
+    
+    #[tokio::test]
+    async fn test_dedupe_skips_content_already_stored() {
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+        let processor = ChunkProcessor::new(config, Some(graph.clone()), embedding_client);
+        let input = || {
+            let mut input = chunk_input("pub fn login() {}");
+            input.embedding = Some(vec![1.0, 0.0]);
+            input
+        };
+        
+        let mut first = request(vec![input()], None);
+        first.dedupe = Some(true);
+        let response = processor.ingest_chunks(first).await.unwrap();
+        assert_eq!((response.chunks_ingested, response.chunks_skipped), (1, 0));
+        
+        // Same content under a fresh id
+        let mut second = request(vec![input()], None);
+        second.dedupe = Some(true);
+        let response = processor.ingest_chunks(second).await.unwrap();
+        assert_eq!((response.chunks_ingested, response.chunks_skipped), (0, 1));
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        
+        let stats = graph.get_statistics().await.unwrap();
+        assert_eq!(stats["node_count"], 1);
+        
+        // Without the flag the copy is stored
+        let response = processor.ingest_chunks(request(vec![input()], None)).await.unwrap();
+        assert_eq!(response.chunks_ingested, 1);
+    }
+    
+    #[test]
+    fn test_dedup_key_overrides_supplied_id() {
+        let mut first = chunk_input("fn main() {}");
+        first.id = Some(Uuid::new_v4());
+        first.dedup_key = Some("repo/file.rs:abc123".to_string());
+        let mut retry = chunk_input("fn main() {}");
+        retry.id = Some(Uuid::new_v4());
+        retry.dedup_key = Some("repo/file.rs:abc123".to_string());
+        
+        let first = first.into_chunk();
+        assert_eq!(first.id, retry.into_chunk().id);
+        assert_eq!(first.id, Chunk::id_for_dedup_key("repo/file.rs:abc123"));
+        assert_ne!(first.id, Chunk::id_for_dedup_key("repo/file.rs:def456"));
+    }
+    
+    #[tokio::test]
+    async fn test_same_id_with_different_content_is_reported() {
+        let processor = test_processor(Config::test_default());
+        let id = Uuid::new_v4();
+        let chunks = ["fn a() {}", "fn a() {}", "fn b() {}"]
+            .iter()
+            .map(|content| {
+                let mut input = chunk_input(content);
+                input.id = Some(id);
+                input.embedding = Some(vec![0.1, 0.2]);
+                input
+            })
+            .collect();
+        
+        let response = processor.ingest_chunks(request(chunks, None)).await.unwrap();
+        assert_eq!(response.id_collisions, vec![id]);
+    }
+    
+    #[tokio::test]
+    async fn test_missing_embeddings_are_batched() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/batch/embed"))
+            .respond_with(|req: &Request| {
+                let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+                let count = body["texts"].as_array().unwrap().len();
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "embeddings": vec![vec![0.1, 0.2]; count]
+                }))
+            })
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let mut config = Config::test_default();
+        config.embedding_service_url = server.uri();
+        config.embedding_batch_size = 64;
+        let processor = test_processor(config);
+        
+        let chunks = (0..50).map(|i| chunk_input(&format!("fn handler_{}() {{}}", i))).collect();
+        let mut req = request(chunks, None);
+        req.extract_entities = Some(true);
+        req.detailed = Some(true);
+        let response = processor.ingest_chunks(req).await.unwrap();
+        
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        // Every chunk came out of the single batch call with a vector
+        assert_eq!(response.extractions.unwrap().len(), 50);
+        
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["texts"].as_array().unwrap().len(), 50);
+    }
+    
+    #[test]
+    fn test_embedding_windows_split_at_definitions_and_headings() {
+        let code = "fn login() {\n    check();\n}\n\nfn logout() {\n    clear();\n}\n";
+        let (windows, fit) = embedding_windows(code, 40, 8, "window");
+        assert_eq!(windows, ["fn login() {\n    check();\n}\n\n", "fn logout() {\n    clear();\n}\n"]);
+        assert_eq!(fit, EmbeddingFit { windows: 2, truncated: false });
+        
+        let doc = "# Auth\nTokens expire.\n## Sessions\nSessions last a day.\n";
+        let (windows, _) = embedding_windows(doc, 40, 8, "window");
+        assert_eq!(windows, ["# Auth\nTokens expire.\n", "## Sessions\nSessions last a day.\n"]);
+        
+        // No boundary within reach: plain character windows
+        let (windows, _) = embedding_windows("abcdefghij", 4, 8, "window");
+        assert_eq!(windows, ["abcd", "efgh", "ij"]);
+        
+        // Truncation keeps the whole first `max_chars`
+        let (windows, fit) = embedding_windows(code, 20, 8, "truncate");
+        assert_eq!(windows, ["fn login() {\n    che"]);
+        assert_eq!(fit, EmbeddingFit { windows: 1, truncated: true });
+    }
+    
+    #[tokio::test]
+    async fn test_oversized_chunk_is_embedded_as_averaged_windows() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+        
+        // Each text embeds as [its length, 1.0]
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/batch/embed"))
+            .respond_with(|req: &Request| {
+                let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+                let embeddings: Vec<[f32; 2]> = body["texts"].as_array().unwrap()
+                    .iter()
+                    .map(|text| [text.as_str().unwrap().chars().count() as f32, 1.0])
+                    .collect();
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "embeddings": embeddings }))
+            })
+            .mount(&server)
+            .await;
+        
+        let ingest = |mode: &str, max_windows: usize| {
+            let mut config = Config::test_default();
+            config.embedding_service_url = server.uri();
+            config.max_embed_chars = 10;
+            config.max_embed_windows = max_windows;
+            config.embed_overflow_mode = mode.to_string();
+            let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+            let graph = Arc::new(crate::graph_db::InMemoryGraph::new());
+            let processor = ChunkProcessor::new(config, Some(graph.clone() as Arc<dyn GraphStore>), embedding_client);
+            async move {
+                // 25 characters (multi-byte included): windows of 10, 10, and 5
+                let chunk = chunk_input("fn résumé() { save(); }  ");
+                let response = processor.ingest_chunks(request(vec![chunk], None)).await.unwrap();
+                assert!(response.errors.is_empty(), "{:?}", response.errors);
+                assert_eq!(response.vectors_stored, 1);
+                graph.export_page(None, None, 10, true).await.unwrap().remove(0).properties
+            }
+        };
+        
+        let windowed = ingest("window", 16).await;
+        assert_eq!(windowed["embedding"], serde_json::json!([25.0_f32 / 3.0, 1.0]));
+        assert_eq!(windowed["embedding_windows"], 3);
+        assert_eq!(windowed["embedding_truncated"], false);
+        
+        let truncated = ingest("truncate", 16).await;
+        assert_eq!(truncated["embedding"], serde_json::json!([10.0, 1.0]));
+        assert_eq!(truncated["embedding_windows"], 1);
+        assert_eq!(truncated["embedding_truncated"], true);
+        
+        // Windows past the cap are left out
+        let capped = ingest("window", 2).await;
+        assert_eq!(capped["embedding"], serde_json::json!([10.0, 1.0]));
+        assert_eq!(capped["embedding_windows"], 2);
+        assert_eq!(capped["embedding_truncated"], true);
+    }
+    
+    #[tokio::test]
+    async fn test_reindex_embeds_nodes_missing_vectors() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        // With the embedding service down, the chunk gets no vector
+        let mut config = Config::test_default();
+        config.embedding_service_url = "http://127.0.0.1:1".to_string();
+        let processor = test_processor(config);
+        let response = processor
+            .ingest_chunks(request(vec![chunk_input("fn main() {}")], None))
+            .await
+            .unwrap();
+        assert_eq!(response.vectors_stored, 0);
+        assert_eq!(response.errors.len(), 1);
+        
+        // Once it's back, the backfill embeds the stored content
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/batch/embed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "embeddings": [[0.1, 0.2], [0.3, 0.4]]
+            })))
+            .mount(&server)
+            .await;
+        let mut config = Config::test_default();
+        config.embedding_service_url = server.uri();
+        let processor = test_processor(config);
+        
+        let nodes = vec![
+            (EntityId(Uuid::new_v4()), "fn main() {}".to_string()),
+            (EntityId(Uuid::new_v4()), "fn other() {}".to_string()),
+        ];
+        let updates = processor.embed_group(&nodes).await.unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[1].0, nodes[1].0);
+        assert_eq!(updates[1].1, vec![0.3, 0.4]);
+        assert_eq!(updates[1].3, EMBEDDING_PROVIDER);
+        
+        // A vector count mismatch fails the whole batch
+        assert!(processor.embed_group(&nodes[..1]).await.is_err());
+        
+        // Reindexing itself needs the graph
+        let reindex = ReindexRequest { label: Some("CHUNK".to_string()), limit: 10 };
+        assert!(matches!(
+            processor.reindex(&reindex, None).await,
+            Err(GraphError::ServiceUnavailable(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_reindex_isolates_a_failing_node() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+        
+        // Chunks stored while the embedding service was down
+        let mut config = Config::test_default();
+        config.embedding_service_url = "http://127.0.0.1:1".to_string();
+        let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+        let processor = ChunkProcessor::new(config.clone(), Some(graph.clone()), Arc::new(EmbeddingClient::from_config(&config)));
+        let mut bad_id = None;
+        let mut chunks = Vec::new();
+        for content in ["fn main() {}", "fn rejected() {}", "fn other() {}"] {
+            let mut chunk = chunk_input(content);
+            let id = Uuid::new_v4();
+            chunk.id = Some(id);
+            if content.contains("rejected") {
+                bad_id = Some(id);
             }
+            chunks.push(chunk);
         }
+        let mut req = request(chunks, None);
+        req.store_chunks = Some(true);
+        processor.ingest_chunks(req).await.unwrap();
+        let bad_id = bad_id.unwrap();
+        assert_eq!(graph.nodes_missing_embeddings(Some("CHUNK"), 10, None).await.unwrap().len(), 3);
+        
+        // The service rejects any batch holding one of the texts
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/batch/embed"))
+            .respond_with(|req: &Request| {
+                let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+                let texts = body["texts"].as_array().unwrap();
+                if texts.iter().any(|text| text.as_str().unwrap().contains("rejected")) {
+                    return ResponseTemplate::new(422).set_body_string("rejected");
+                }
+                let embeddings: Vec<Vec<f32>> = texts.iter().map(|_| vec![0.1, 0.2]).collect();
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "embeddings": embeddings }))
+            })
+            .mount(&server)
+            .await;
+        let mut config = Config::test_default();
+        config.embedding_service_url = server.uri();
+        let processor = ChunkProcessor::new(config.clone(), Some(graph.clone()), Arc::new(EmbeddingClient::from_config(&config)));
+        
+        let reindex = ReindexRequest { label: Some("CHUNK".to_string()), limit: 10 };
+        let response = processor.reindex(&reindex, None).await.unwrap();
+        assert_eq!((response.scanned, response.embedded, response.failed), (3, 2, 1));
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].contains(&bad_id.to_string()), "{:?}", response.errors);
+        
+        let missing = graph.nodes_missing_embeddings(Some("CHUNK"), 10, None).await.unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].0, EntityId(bad_id));
+    }
+    
+    #[tokio::test]
+    async fn test_extraction_only_ingest_skips_embedding() {
+        // Unreachable embedding service: any embed attempt would surface as an error
+        let mut config = Config::test_default();
+        config.embedding_service_url = "http://127.0.0.1:1".to_string();
+        let processor = test_processor(config);
+        
+        let mut req = request(vec![chunk_input("pub struct UserService {}\npub fn login() {}")], None);
+        req.extract_entities = Some(true);
+        req.detailed = Some(true);
+        req.store_chunks = Some(false);
+        let response = processor.ingest_chunks(req).await.unwrap();
         
-        links_created
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(response.vectors_stored, 0);
+        assert_eq!(response.chunks_ingested, 0);
+        let extractions = response.extractions.unwrap();
+        assert!(extractions[0].result.entities.iter().any(|e| e.name == "UserService"));
     }
 }
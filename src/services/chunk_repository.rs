@@ -0,0 +1,199 @@
+//! Postgres storage for chunks and relationship evidence
+//!
+//! Neo4j holds the graph and vectors; Postgres keeps the full chunk rows
+//! (content and metadata) and the evidence behind each cross-source link, so
+//! a vector hit's id can be looked up for everything the chunker sent
+//! (`GET /api/graph/chunks/:id`). Tables are created by the migrations in
+//! `migrations/`.
+
+use crate::error::GraphResult;
+use crate::models::{Chunk, RelationshipEvidence};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Insert chunks, or replace everything but `created_at` on re-ingest
+///
+/// One statement per batch: each parameter is a column array, zipped back into
+/// rows by `UNNEST`.
+const UPSERT_CHUNKS: &str = r#"
+    INSERT INTO chunks (
+        id, content, content_hash, source_kind, source_type, source_id, file_path, repo_name,
+        branch, language, heading_path, section_title, owner_id, author, commit_sha, commit_date,
+        start_line, end_line, token_count, metadata, created_at, updated_at
+    )
+    SELECT * FROM UNNEST(
+        $1::uuid[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[], $7::text[], $8::text[],
+        $9::text[], $10::text[], $11::text[], $12::text[], $13::text[], $14::text[], $15::text[], $16::timestamptz[],
+        $17::int4[], $18::int4[], $19::int4[], $20::jsonb[], $21::timestamptz[], $22::timestamptz[]
+    )
+    ON CONFLICT (id) DO UPDATE SET
+        content = EXCLUDED.content,
+        content_hash = EXCLUDED.content_hash,
+        source_kind = EXCLUDED.source_kind,
+        source_type = EXCLUDED.source_type,
+        source_id = EXCLUDED.source_id,
+        file_path = EXCLUDED.file_path,
+        repo_name = EXCLUDED.repo_name,
+        branch = EXCLUDED.branch,
+        language = EXCLUDED.language,
+        heading_path = EXCLUDED.heading_path,
+        section_title = EXCLUDED.section_title,
+        owner_id = EXCLUDED.owner_id,
+        author = EXCLUDED.author,
+        commit_sha = EXCLUDED.commit_sha,
+        commit_date = EXCLUDED.commit_date,
+        start_line = EXCLUDED.start_line,
+        end_line = EXCLUDED.end_line,
+        token_count = EXCLUDED.token_count,
+        metadata = EXCLUDED.metadata,
+        updated_at = EXCLUDED.updated_at
+"#;
+
+/// Insert evidence, replacing the earlier row for the same link on re-ingest
+const UPSERT_EVIDENCE: &str = r#"
+    INSERT INTO relationship_evidence (
+        id, from_chunk_id, to_chunk_id, from_entity_id, to_entity_id, relationship_type,
+        confidence, extraction_method, evidence_text, similarity_score, temporal_distance_days,
+        author_match, properties, created_at
+    )
+    SELECT * FROM UNNEST(
+        $1::uuid[], $2::uuid[], $3::uuid[], $4::uuid[], $5::uuid[], $6::text[],
+        $7::real[], $8::text[], $9::text[], $10::real[], $11::int4[],
+        $12::bool[], $13::jsonb[], $14::timestamptz[]
+    )
+    ON CONFLICT (from_chunk_id, to_chunk_id, relationship_type) DO UPDATE SET
+        from_entity_id = EXCLUDED.from_entity_id,
+        to_entity_id = EXCLUDED.to_entity_id,
+        confidence = EXCLUDED.confidence,
+        extraction_method = EXCLUDED.extraction_method,
+        evidence_text = EXCLUDED.evidence_text,
+        similarity_score = EXCLUDED.similarity_score,
+        temporal_distance_days = EXCLUDED.temporal_distance_days,
+        author_match = EXCLUDED.author_match,
+        properties = EXCLUDED.properties,
+        created_at = EXCLUDED.created_at
+"#;
+
+/// Chunk and evidence rows in Postgres
+pub struct ChunkRepository {
+    pool: PgPool,
+}
+
+impl ChunkRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Store chunks' full rows (replacing earlier versions of the same ids)
+    ///
+    /// A chunk repeated in `chunks` is stored once, as its last occurrence:
+    /// one upsert can't touch the same row twice.
+    pub async fn insert_chunks<'a>(&self, chunks: impl IntoIterator<Item = &'a Chunk>) -> GraphResult<()> {
+        let chunks: Vec<&Chunk> = chunks.into_iter().collect();
+        let mut seen = HashSet::new();
+        let mut unique: Vec<&Chunk> = chunks.into_iter().rev().filter(|chunk| seen.insert(chunk.id)).collect();
+        if unique.is_empty() {
+            return Ok(());
+        }
+        unique.reverse();
+        let column = |f: fn(&Chunk) -> Option<String>| unique.iter().map(|c| f(c)).collect::<Vec<_>>();
+        sqlx::query(UPSERT_CHUNKS)
+            .bind(unique.iter().map(|c| c.id).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.content.clone()).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.content_hash.clone()).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.source_kind.clone()).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.source_type.clone()).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.source_id.clone()).collect::<Vec<_>>())
+            .bind(column(|c| c.file_path.clone()))
+            .bind(column(|c| c.repo_name.clone()))
+            .bind(column(|c| c.branch.clone()))
+            .bind(column(|c| c.language.clone()))
+            .bind(column(|c| c.heading_path.clone()))
+            .bind(column(|c| c.section_title.clone()))
+            .bind(unique.iter().map(|c| c.owner_id.clone()).collect::<Vec<_>>())
+            .bind(column(|c| c.author.clone()))
+            .bind(column(|c| c.commit_sha.clone()))
+            .bind(unique.iter().map(|c| c.commit_date).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.start_line).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.end_line).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.token_count).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.metadata.clone()).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.created_at).collect::<Vec<_>>())
+            .bind(unique.iter().map(|c| c.updated_at).collect::<Vec<_>>())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The stored row of a chunk, if there is one
+    pub async fn get_chunk(&self, id: Uuid) -> GraphResult<Option<Chunk>> {
+        let chunk = sqlx::query_as::<_, Chunk>("SELECT * FROM chunks WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(chunk)
+    }
+
+    /// Store evidence rows, one per link (`from_chunk_id`, `to_chunk_id`, `relationship_type`)
+    ///
+    /// Evidence for a link that already has a row replaces it, so re-linking
+    /// the same chunks leaves one up-to-date row. Within `evidence`, the last
+    /// row for a link wins.
+    pub async fn insert_evidence(&self, evidence: &[RelationshipEvidence]) -> GraphResult<()> {
+        let mut seen = HashSet::new();
+        let mut unique: Vec<&RelationshipEvidence> = evidence.iter()
+            .rev()
+            .filter(|e| seen.insert((e.from_chunk_id, e.to_chunk_id, e.relationship_type.as_str())))
+            .collect();
+        if unique.is_empty() {
+            return Ok(());
+        }
+        unique.reverse();
+        sqlx::query(UPSERT_EVIDENCE)
+            .bind(unique.iter().map(|e| e.id).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.from_chunk_id).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.to_chunk_id).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.from_entity_id).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.to_entity_id).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.relationship_type.clone()).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.confidence).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.extraction_method.clone()).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.evidence_text.clone()).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.similarity_score).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.temporal_distance_days).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.author_match).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.properties.clone()).collect::<Vec<_>>())
+            .bind(unique.iter().map(|e| e.created_at).collect::<Vec<_>>())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete chunk rows and the evidence touching them, returning how many chunks were removed
+    pub async fn delete_chunks(&self, ids: &[Uuid]) -> GraphResult<u64> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM relationship_evidence WHERE from_chunk_id = ANY($1) OR to_chunk_id = ANY($1)")
+            .bind(ids)
+            .execute(&mut *tx)
+            .await?;
+        let deleted = sqlx::query("DELETE FROM chunks WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Evidence for links starting or ending at `chunk_id`, newest first
+    pub async fn evidence_for_chunk(&self, chunk_id: Uuid) -> GraphResult<Vec<RelationshipEvidence>> {
+        let evidence = sqlx::query_as::<_, RelationshipEvidence>(
+            "SELECT * FROM relationship_evidence WHERE from_chunk_id = $1 OR to_chunk_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(chunk_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(evidence)
+    }
+}
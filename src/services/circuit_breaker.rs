@@ -0,0 +1,188 @@
+//! Circuit breaker for upstream service calls
+//!
+//! Opens after a run of consecutive failures so callers fail fast instead of
+//! queueing behind a struggling service. After the cooldown a single probe call
+//! is let through (half-open); its outcome closes or re-opens the breaker.
+//! A probe dropped without an outcome (e.g. a cancelled request) frees the
+//! slot for the next caller.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Externally visible breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl BreakerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Open => "open",
+            BreakerState::HalfOpen => "half_open",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct BreakerInner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probe_in_flight: bool,
+}
+
+/// Consecutive-failure circuit breaker
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            inner: Mutex::new(BreakerInner::default()),
+        }
+    }
+
+    /// A permit for one call, or `None` while open; claims the probe slot when half-open
+    pub fn try_acquire(&self) -> Option<BreakerPermit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+        let probe = match inner.opened_at {
+            None => false,
+            Some(opened_at) if opened_at.elapsed() >= self.cooldown && !inner.probe_in_flight => {
+                inner.probe_in_flight = true;
+                true
+            }
+            Some(_) => return None,
+        };
+        Some(BreakerPermit { breaker: self, probe })
+    }
+
+    /// Record a successful call (closes the breaker)
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner = BreakerInner::default();
+    }
+
+    /// Record a failed call (opens the breaker at the threshold or on a failed probe)
+    fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        if inner.probe_in_flight || inner.consecutive_failures >= self.failure_threshold {
+            inner.opened_at = Some(Instant::now());
+            inner.probe_in_flight = false;
+        }
+    }
+
+    /// Current state
+    pub fn state(&self) -> BreakerState {
+        let inner = self.inner.lock().unwrap();
+        match inner.opened_at {
+            None => BreakerState::Closed,
+            Some(_) if inner.probe_in_flight => BreakerState::HalfOpen,
+            Some(opened_at) if opened_at.elapsed() >= self.cooldown => BreakerState::HalfOpen,
+            Some(_) => BreakerState::Open,
+        }
+    }
+
+    /// Current run of consecutive failures
+    pub fn consecutive_failures(&self) -> u32 {
+        self.inner.lock().unwrap().consecutive_failures
+    }
+}
+
+/// One call let through by the breaker
+///
+/// Consumed by `record_success` or `record_failure`. Dropping it unrecorded
+/// leaves the counts alone but frees the probe slot, so a cancelled probe
+/// can't hold the breaker open forever.
+#[must_use]
+#[derive(Debug)]
+pub struct BreakerPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+}
+
+impl BreakerPermit<'_> {
+    pub fn record_success(mut self) {
+        self.probe = false;
+        self.breaker.record_success();
+    }
+
+    pub fn record_failure(mut self) {
+        self.probe = false;
+        self.breaker.record_failure();
+    }
+}
+
+impl Drop for BreakerPermit<'_> {
+    fn drop(&mut self) {
+        if self.probe {
+            self.breaker.inner.lock().unwrap().probe_in_flight = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold_and_probes_after_cooldown() {
+        let breaker = CircuitBreaker::new(3, Duration::from_millis(20));
+
+        for _ in 0..2 {
+            breaker.try_acquire().unwrap().record_failure();
+        }
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        breaker.try_acquire().unwrap().record_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(breaker.try_acquire().is_none());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        let probe = breaker.try_acquire().unwrap();
+        // Only one probe at a time
+        assert!(breaker.try_acquire().is_none());
+
+        probe.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.try_acquire().is_some());
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(10));
+        breaker.try_acquire().unwrap().record_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        std::thread::sleep(Duration::from_millis(15));
+        breaker.try_acquire().unwrap().record_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(breaker.try_acquire().is_none());
+    }
+
+    #[test]
+    fn test_dropped_probe_frees_the_slot() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(10));
+        breaker.try_acquire().unwrap().record_failure();
+        std::thread::sleep(Duration::from_millis(15));
+
+        // A probe cancelled before recording an outcome
+        drop(breaker.try_acquire().unwrap());
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert_eq!(breaker.consecutive_failures(), 1);
+        breaker.try_acquire().unwrap().record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+}
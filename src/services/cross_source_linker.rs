@@ -4,13 +4,18 @@
 //! vector similarity (via Neo4j native indexes), explicit mentions, 
 //! temporal proximity, and author overlap.
 
-use crate::config::Config;
-use crate::error::GraphResult;
-use crate::graph_db::Neo4jClient;
+use crate::config::{Config, CROSS_LINK_KINDS};
+use crate::error::{GraphError, GraphResult};
+use crate::graph_db::GraphStore;
+use crate::graph_db::neo4j_client::{AvailableSignals, CrossSourceMatch, LinkBoosts, LinkableChunkFilter};
 use crate::models::{
-    Chunk, RelationshipType, RelationshipEvidence, ExtractionMethod, SemanticLink,
+    Chunk, CrossSourceLinkRequest, CrossSourceLinkResponse,
+    EntityId, RelationshipType, RelationshipEvidence, ExtractionMethod, SemanticLink, EdgeDirection,
 };
+use crate::services::ChunkRepository;
+use crate::utils::cosine_similarity;
 use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -19,11 +24,13 @@ use uuid::Uuid;
 /// Now uses Neo4j native vector indexes instead of separate Zilliz database.
 pub struct CrossSourceLinker {
     config: Config,
-    neo4j: Option<Arc<Neo4jClient>>,
+    graph: Option<Arc<dyn GraphStore>>,
+    chunk_repository: Option<Arc<ChunkRepository>>,
 }
 
 /// Result of a linking operation
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct LinkResult {
     pub links_created: usize,
     pub evidence_records: Vec<RelationshipEvidence>,
@@ -33,9 +40,15 @@ pub struct LinkResult {
 impl CrossSourceLinker {
     pub fn new(
         config: Config,
-        neo4j: Option<Arc<Neo4jClient>>,
+        graph: Option<Arc<dyn GraphStore>>,
     ) -> Self {
-        Self { config, neo4j }
+        Self { config, graph, chunk_repository: None }
+    }
+    
+    /// Record the evidence for each link written by `relink` in Postgres
+    pub fn with_chunk_repository(mut self, repository: Option<Arc<ChunkRepository>>) -> Self {
+        self.chunk_repository = repository;
+        self
     }
     
     /// Create cross-source links between code and document chunks using Neo4j
@@ -47,6 +60,7 @@ impl CrossSourceLinker {
     /// 4. Boost with author overlap
     /// 
     /// All operations happen in Neo4j, eliminating the need for separate Zilliz queries.
+    #[allow(dead_code)]
     pub async fn link_chunks(
         &self,
         code_chunks: &[Chunk],
@@ -65,7 +79,9 @@ impl CrossSourceLinker {
             doc_chunks.iter().map(|c| (c.id, c)).collect();
         
         // For each document chunk, find similar code chunks via Neo4j vector index
-        if let Some(neo4j) = &self.neo4j {
+        if let Some(graph) = self.graph.as_deref() {
+            // Guards against linking the same pair twice when inputs repeat
+            let mut linked: HashSet<(Uuid, Uuid)> = HashSet::new();
             for (doc_id, _doc_embedding) in doc_embeddings {
                 let doc_chunk = match doc_map.get(doc_id) {
                     Some(c) => *c,
@@ -73,96 +89,43 @@ impl CrossSourceLinker {
                 };
                 
                 // Use Neo4j native vector search with confidence boosters
-                match neo4j.find_similar_chunks_for_linking(
-                    &doc_id.to_string(),
+                match graph.find_similar_chunks_for_linking(
+                    &EntityId(*doc_id),
                     "code",
                     self.config.max_cross_links_per_chunk,
                     self.config.similarity_threshold,
+                    &LinkBoosts::from_config(&self.config),
+                    None,
                 ).await {
                     Ok(matches) => {
                         for m in matches {
-                            let code_id = Uuid::parse_str(&m.target_id).unwrap_or_else(|_| Uuid::new_v4());
+                            let code_id = m.target_id.as_uuid();
                             let code_chunk = match code_map.get(&code_id) {
                                 Some(c) => *c,
                                 None => continue,
                             };
-                            
-                            // Calculate additional confidence boosters if needed
-                            let mut confidence = m.confidence;
-                            let mut extraction_methods = vec![ExtractionMethod::VectorSimilarity];
-                            let mut evidence_text = None;
-                            let mut author_match = m.has_author_overlap;
-                            let mut temporal_distance = None;
-                            
-                            // Additional explicit mention detection (beyond what Neo4j does)
-                            if self.config.enable_explicit_mentions && !m.has_explicit_mention {
-                                if let Some(mention) = self.detect_explicit_mention(&doc_chunk.content, code_chunk) {
-                                    confidence += self.config.explicit_mention_boost;
-                                    confidence = confidence.min(1.0);
-                                    extraction_methods.push(ExtractionMethod::ExplicitMention);
-                                    evidence_text = Some(mention);
-                                }
-                            } else if m.has_explicit_mention {
-                                extraction_methods.push(ExtractionMethod::ExplicitMention);
-                            }
-                            
-                            // Temporal proximity boost
-                            if self.config.enable_temporal_proximity {
-                                if let Some(code_date) = code_chunk.commit_date {
-                                    let doc_date = doc_chunk.updated_at;
-                                    let days = self.temporal_proximity_score(doc_date, code_date);
-                                    if days <= self.config.temporal_proximity_days {
-                                        let boost = self.config.temporal_proximity_boost 
-                                            * (1.0 - (days as f32 / self.config.temporal_proximity_days as f32));
-                                        confidence += boost;
-                                        confidence = confidence.min(1.0);
-                                        extraction_methods.push(ExtractionMethod::TemporalProximity);
-                                        temporal_distance = Some(days as i32);
-                                    }
-                                }
+                            if !linked.insert((*doc_id, code_id)) {
+                                continue;
                             }
                             
-                            // Author overlap (may already be detected by Neo4j)
-                            if m.has_author_overlap {
-                                extraction_methods.push(ExtractionMethod::AuthorOverlap);
-                            }
-                            
-                            // Determine relationship type
-                            let rel_type = self.determine_relationship_type(doc_chunk, code_chunk);
-                            
-                            // Create evidence record
-                            let mut evidence = RelationshipEvidence::new(
-                                *doc_id,
-                                code_id,
-                                rel_type.as_str().to_string(),
-                                confidence,
-                                if extraction_methods.len() > 1 {
-                                    ExtractionMethod::Combined
-                                } else {
-                                    ExtractionMethod::VectorSimilarity
-                                },
+                            let link = self.score_link(
+                                doc_chunk,
+                                code_chunk,
+                                m.similarity_score,
+                                m.has_explicit_mention,
+                                m.has_author_overlap,
                             );
-                            evidence = evidence
-                                .with_similarity_score(m.similarity_score)
-                                .with_author_match(author_match);
-                            
-                            if let Some(days) = temporal_distance {
-                                evidence = evidence.with_temporal_distance(days);
-                            }
-                            if let Some(text) = evidence_text {
-                                evidence = evidence.with_evidence_text(text);
-                            }
-                            
-                            evidence_records.push(evidence);
+                            let confidence = link.confidence;
+                            let extraction_methods = link.extraction_methods.clone();
+                            evidence_records.push(link.into_evidence(*doc_id, code_id, m.similarity_score));
                             
                             // Create relationship in Neo4j
-                            match neo4j.create_cross_source_link(
-                                &doc_id.to_string(),
+                            match graph.create_cross_source_link(
+                                &EntityId(*doc_id),
                                 &m.target_id,
                                 confidence,
                                 m.similarity_score,
-                                m.has_explicit_mention,
-                                m.has_author_overlap,
+                                &extraction_methods,
                             ).await {
                                 Ok(_) => links_created += 1,
                                 Err(e) => errors.push(format!("Neo4j relationship error: {}", e)),
@@ -174,9 +137,6 @@ impl CrossSourceLinker {
             }
         } else {
             // Fallback: in-memory linking without Neo4j
-            let code_embedding_map: std::collections::HashMap<Uuid, &Vec<f32>> = 
-                code_embeddings.iter().map(|(id, emb)| (*id, emb)).collect();
-            
             for (doc_id, doc_embedding) in doc_embeddings {
                 let doc_chunk = match doc_map.get(doc_id) {
                     Some(c) => *c,
@@ -200,17 +160,8 @@ impl CrossSourceLinker {
                         None => continue,
                     };
                     
-                    let rel_type = self.determine_relationship_type(doc_chunk, code_chunk);
-                    
-                    let evidence = RelationshipEvidence::new(
-                        *doc_id,
-                        code_id,
-                        rel_type.as_str().to_string(),
-                        similarity,
-                        ExtractionMethod::VectorSimilarity,
-                    ).with_similarity_score(similarity);
-                    
-                    evidence_records.push(evidence);
+                    let link = self.score_link(doc_chunk, code_chunk, similarity, false, false);
+                    evidence_records.push(link.into_evidence(*doc_id, code_id, similarity));
                     links_created += 1;
                 }
             }
@@ -223,6 +174,101 @@ impl CrossSourceLinker {
         })
     }
     
+    /// Score a candidate link, applying each confidence boost at most once
+    /// 
+    /// `mention_found` / `author_found` carry signals already detected by the
+    /// Neo4j query; Rust-side detection only fills in what it missed.
+    fn score_link(
+        &self,
+        doc_chunk: &Chunk,
+        code_chunk: &Chunk,
+        similarity: f32,
+        mention_found: bool,
+        author_found: bool,
+    ) -> ScoredLink {
+        let evidence_text = if !mention_found && self.config.enable_explicit_mentions {
+            self.detect_explicit_mention(&doc_chunk.content, code_chunk)
+        } else {
+            None
+        };
+        let explicit_mention = mention_found || evidence_text.is_some();
+        
+        let author_overlap = author_found || (self.config.enable_author_overlap
+            && doc_chunk.author.is_some()
+            && doc_chunk.author == code_chunk.author);
+        
+        let boosts = LinkBoosts::from_config(&self.config);
+        let temporal_distance = code_chunk.commit_date
+            .map(|code_date| self.temporal_proximity_score(doc_chunk.updated_at, code_date))
+            .filter(|days| boosts.temporal_factor(*days).is_some());
+        let temporal_factor = temporal_distance.and_then(|days| boosts.temporal_factor(days));
+        
+        let mut extraction_methods = vec![ExtractionMethod::VectorSimilarity];
+        if explicit_mention {
+            extraction_methods.push(ExtractionMethod::ExplicitMention);
+        }
+        if temporal_distance.is_some() {
+            extraction_methods.push(ExtractionMethod::TemporalProximity);
+        }
+        if author_overlap {
+            extraction_methods.push(ExtractionMethod::AuthorOverlap);
+        }
+        
+        ScoredLink {
+            relationship_type: self.determine_relationship_type(doc_chunk, code_chunk),
+            confidence: boosts.normalized_confidence(
+                similarity,
+                explicit_mention,
+                author_overlap,
+                temporal_factor,
+                AvailableSignals {
+                    author: author_found || (doc_chunk.author.is_some() && code_chunk.author.is_some()),
+                    temporal: code_chunk.commit_date.is_some(),
+                },
+            ),
+            extraction_methods,
+            evidence_text,
+            temporal_distance,
+            author_overlap,
+        }
+    }
+    
+    /// Re-score a planned link between two known chunks and describe why it was made
+    /// 
+    /// Adds the signals the graph query can't see (mentions it missed, temporal
+    /// proximity) to `link`, never lowering its confidence. The result is the
+    /// same whichever direction `link` points, so both directions of a pair
+    /// stay in agreement.
+    pub fn explain_link(&self, link: &mut PlannedLink, from_chunk: &Chunk, to_chunk: &Chunk) -> SemanticLink {
+        let (doc_chunk, code_chunk) = if from_chunk.source_kind == "code" {
+            (to_chunk, from_chunk)
+        } else {
+            (from_chunk, to_chunk)
+        };
+        let scored = self.score_link(
+            doc_chunk,
+            code_chunk,
+            link.similarity_score,
+            link.extraction_methods.contains(&ExtractionMethod::ExplicitMention),
+            link.extraction_methods.contains(&ExtractionMethod::AuthorOverlap),
+        );
+        
+        link.confidence = link.confidence.max(scored.confidence);
+        for method in scored.extraction_methods {
+            if !link.extraction_methods.contains(&method) {
+                link.extraction_methods.push(method);
+            }
+        }
+        SemanticLink {
+            from_chunk_id: from_chunk.id,
+            to_chunk_id: to_chunk.id,
+            explicit_mention: scored.evidence_text,
+            temporal_distance_days: scored.temporal_distance.or(link.temporal_distance_days).map(|days| days as i32),
+            author_overlap: scored.author_overlap,
+            ..link.to_semantic_link()
+        }
+    }
+    
     /// Find similar vectors using cosine similarity (fallback for when Neo4j unavailable)
     fn find_similar_vectors(
         &self,
@@ -246,7 +292,7 @@ impl CrossSourceLinker {
         
         // Check file name
         if let Some(file_path) = &code_chunk.file_path {
-            if let Some(file_name) = file_path.split('/').last() {
+            if let Some(file_name) = file_path.split('/').next_back() {
                 let file_base = file_name.split('.').next().unwrap_or(file_name);
                 if doc_lower.contains(&file_base.to_lowercase()) {
                     return Some(format!("Mentions file: {}", file_name));
@@ -316,23 +362,228 @@ impl CrossSourceLinker {
         RelationshipType::SemanticallySimilar
     }
     
-    /// Get semantic links for a chunk
-    pub async fn get_links_for_chunk(&self, chunk_id: Uuid) -> GraphResult<Vec<SemanticLink>> {
-        if let Some(neo4j) = &self.neo4j {
-            let relationships = neo4j.get_cross_source_relationships(&chunk_id.to_string()).await?;
+    /// The `(from_kind, to_kind)` passes to run for the requested kinds
+    /// 
+    /// Both kinds given select that one pair; otherwise the configured pairs
+    /// are narrowed to the kind that was given.
+    fn link_pairs(
+        &self,
+        from_kind: Option<&str>,
+        to_kind: Option<&str>,
+    ) -> GraphResult<Vec<(&'static str, &'static str)>> {
+        let from_kind = from_kind.map(validate_source_kind).transpose()?;
+        let to_kind = to_kind.map(validate_source_kind).transpose()?;
+        if let (Some(from), Some(to)) = (from_kind, to_kind) {
+            return Ok(vec![(from, to)]);
+        }
+        
+        let pairs: Vec<_> = self.config.cross_link_pairs
+            .iter()
+            .filter_map(|(from, to)| Some((validate_source_kind(from).ok()?, validate_source_kind(to).ok()?)))
+            .filter(|(from, to)| from_kind.is_none_or(|kind| kind == *from) && to_kind.is_none_or(|kind| kind == *to))
+            .collect();
+        if pairs.is_empty() {
+            return Err(GraphError::InvalidRequest(format!(
+                "No cross-link pairs configured from {} to {}",
+                from_kind.unwrap_or("any kind"),
+                to_kind.unwrap_or("any kind"),
+            )));
+        }
+        Ok(pairs)
+    }
+    
+    /// Re-run cross-source linking for chunks already in the graph
+    /// 
+    /// Links the given `chunk_ids`, or every chunk that is the source of a
+    /// linking pass. Passes come from `from_source_kind`/`to_source_kind` and
+    /// the configured pairs (see `link_pairs`); passes whose target kind has
+    /// no linkable chunks are skipped. Chunks that already have links are
+    /// skipped unless `force` is set, in which case their links in both
+    /// directions are deleted and rebuilt. A chunk's links are only deleted
+    /// once every search for it has succeeded, so a failed search keeps them.
+    pub async fn relink(
+        &self,
+        request: &CrossSourceLinkRequest,
+        owner_id: Option<&str>,
+    ) -> GraphResult<CrossSourceLinkResponse> {
+        let graph = self.graph.as_deref()
+            .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available for cross-source linking".to_string()))?;
+        
+        let mut pairs = self.link_pairs(request.from_source_kind.as_deref(), request.to_source_kind.as_deref())?;
+        let mut has_targets: HashMap<&str, bool> = HashMap::new();
+        for (_, to_kind) in &pairs {
+            if has_targets.contains_key(to_kind) {
+                continue;
+            }
+            let targets = LinkableChunkFilter { chunk_ids: None, source_kind: Some(to_kind), force: true, owner_id };
+            has_targets.insert(to_kind, !graph.linkable_chunks(&targets, "", 1).await?.is_empty());
+        }
+        pairs.retain(|(_, to_kind)| has_targets[to_kind]);
+        let Some((first_from, _)) = pairs.first().copied() else {
+            return Ok(CrossSourceLinkResponse { links_created: 0, chunks_processed: 0, errors: Vec::new() });
+        };
+        
+        let targeted = request.chunk_ids.as_ref().filter(|ids| !ids.is_empty());
+        let chunk_ids: Option<Vec<String>> = targeted
+            .map(|ids| ids.iter().map(|id| id.to_string()).collect());
+        let filter = LinkableChunkFilter {
+            chunk_ids: chunk_ids.as_deref(),
+            // Narrow the scan when every pass links from the same kind
+            source_kind: pairs.iter().all(|(from, _)| *from == first_from).then_some(first_from),
+            force: request.force,
+            owner_id,
+        };
+        
+        let boosts = LinkBoosts::from_config(&self.config);
+        let mut plan = CrossLinkPlan::new();
+        let mut chunks_processed = 0;
+        let mut errors = Vec::new();
+        let mut after = String::new();
+        
+        loop {
+            let page = graph.linkable_chunks(&filter, &after, RELINK_PAGE_SIZE).await?;
+            let Some((last_id, _)) = page.last() else {
+                break;
+            };
+            after = last_id.clone();
+            
+            for (chunk_id, source_kind) in &page {
+                let Ok(chunk_id) = EntityId::parse(chunk_id) else {
+                    tracing::warn!("Skipping chunk with non-UUID id: {}", chunk_id);
+                    continue;
+                };
+                let target_kinds: Vec<&str> = pairs.iter()
+                    .filter(|(from, _)| from == source_kind)
+                    .map(|(_, to)| *to)
+                    .collect();
+                if target_kinds.is_empty() {
+                    continue;
+                }
+                
+                let mut found = Vec::new();
+                let (mut searched, mut failed) = (false, false);
+                for target_kind in target_kinds {
+                    match graph.find_similar_chunks_for_linking(
+                        &chunk_id,
+                        target_kind,
+                        self.config.max_cross_links_per_chunk,
+                        self.config.similarity_threshold,
+                        &boosts,
+                        None,
+                    ).await {
+                        Ok(matches) => {
+                            searched = true;
+                            found.extend(matches.into_iter().map(|m| (target_kind, m)));
+                        }
+                        Err(e) => {
+                            errors.push(format!("Vector search error for chunk {}: {}", chunk_id, e));
+                            failed = true;
+                        }
+                    }
+                }
+                if request.force {
+                    if failed {
+                        continue;
+                    }
+                    if let Err(e) = graph.delete_cross_links(&chunk_id).await {
+                        errors.push(format!("Failed to clear links for chunk {}: {}", chunk_id, e));
+                        continue;
+                    }
+                }
+                for (target_kind, m) in &found {
+                    plan.add(chunk_id, m);
+                    // The links pointing at this chunk were deleted too; rebuild those the reverse pass would make
+                    if request.force && pairs.contains(&(*target_kind, source_kind.as_str())) {
+                        plan.add(m.target_id, &CrossSourceMatch { target_id: chunk_id, ..m.clone() });
+                    }
+                }
+                if searched {
+                    chunks_processed += 1;
+                }
+            }
+        }
+        
+        let mut links_created = 0;
+        let mut evidence = Vec::new();
+        for link in plan.into_links() {
+            match graph.create_cross_source_link(
+                &link.from_id,
+                &link.to_id,
+                link.confidence,
+                link.similarity_score,
+                &link.extraction_methods,
+            ).await {
+                Ok(_) => links_created += 1,
+                Err(e) => {
+                    errors.push(format!("Neo4j relationship error: {}", e));
+                    continue;
+                }
+            }
+            evidence.push(link.to_evidence());
+        }
+        if let Some(repository) = &self.chunk_repository {
+            if let Err(e) = repository.insert_evidence(&evidence).await {
+                errors.push(format!("Evidence storage failed for {} links: {}", evidence.len(), e));
+            }
+        }
+        
+        Ok(CrossSourceLinkResponse {
+            links_created,
+            chunks_processed,
+            errors,
+        })
+    }
+    
+    /// Chunks of `target_kind` that linking would connect `chunk_id` to
+    /// 
+    /// Scored exactly like ingestion-time linking, but read-only: no edges are
+    /// written. With `owner_id`, the chunk must belong to that tenant and other
+    /// tenants' matches are left out.
+    pub async fn find_matches(
+        &self,
+        chunk_id: Uuid,
+        target_kind: &str,
+        limit: usize,
+        min_similarity: Option<f32>,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<CrossSourceMatch>> {
+        let target_kind = validate_source_kind(target_kind)?;
+        let graph = self.graph.as_deref()
+            .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+        let id = EntityId(chunk_id);
+        if graph.get_entity(&id, owner_id).await?.is_none() {
+            return Err(GraphError::EntityNotFound(id.to_string()));
+        }
+        
+        graph.find_similar_chunks_for_linking(
+            &id,
+            target_kind,
+            limit,
+            min_similarity.unwrap_or(self.config.similarity_threshold),
+            &LinkBoosts::from_config(&self.config),
+            owner_id,
+        ).await
+    }
+    
+    /// Get the `limit` strongest semantic links of a chunk
+    #[allow(dead_code)]
+    pub async fn get_links_for_chunk(&self, chunk_id: Uuid, limit: usize) -> GraphResult<Vec<SemanticLink>> {
+        if let Some(graph) = self.graph.as_deref() {
+            let linked = graph.cross_source_chunks(&[EntityId(chunk_id)], "both", limit, None).await?;
             
-            Ok(relationships
+            Ok(linked
                 .into_iter()
-                .map(|(target_id, _target_name, rel_type, confidence)| SemanticLink {
+                .map(|rel| SemanticLink {
                     from_chunk_id: chunk_id,
-                    to_chunk_id: Uuid::parse_str(&target_id).unwrap_or_else(|_| Uuid::new_v4()),
-                    relationship_type: rel_type,
-                    confidence,
+                    to_chunk_id: rel.chunk.chunk_id,
+                    relationship_type: rel.relationship,
+                    confidence: rel.confidence,
                     extraction_methods: vec!["neo4j_vector_similarity".to_string()],
                     similarity_score: None,
                     explicit_mention: None,
                     temporal_distance_days: None,
                     author_overlap: false,
+                    direction: rel.direction,
                 })
                 .collect())
         } else {
@@ -341,34 +592,471 @@ impl CrossSourceLinker {
     }
 }
 
-/// Calculate cosine similarity between two vectors
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-    if a.len() != b.len() || a.is_empty() {
-        return 0.0;
+/// Chunks fetched per page when re-linking
+const RELINK_PAGE_SIZE: usize = 200;
+
+/// Accept only the source kinds cross-source linking understands
+fn validate_source_kind(kind: &str) -> GraphResult<&'static str> {
+    CROSS_LINK_KINDS.iter().copied().find(|known| *known == kind).ok_or_else(|| {
+        GraphError::InvalidRequest(format!(
+            "Unsupported source kind for linking: {} (expected one of {})",
+            kind,
+            CROSS_LINK_KINDS.join(", ")
+        ))
+    })
+}
+
+/// Key identifying one directed link: `(from_id, to_id, rel_type)`
+type LinkKey = (EntityId, EntityId, &'static str);
+
+/// A cross-source link ready to be written
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedLink {
+    pub from_id: EntityId,
+    pub to_id: EntityId,
+    pub confidence: f32,
+    pub similarity_score: f32,
+    pub extraction_methods: Vec<ExtractionMethod>,
+    /// Days between the chunks' dates, as reported by the similarity search
+    pub temporal_distance_days: Option<i64>,
+}
+
+impl PlannedLink {
+    /// Evidence row recording why the link was made
+    pub fn to_evidence(&self) -> RelationshipEvidence {
+        let mut evidence = RelationshipEvidence::new(
+            self.from_id.as_uuid(),
+            self.to_id.as_uuid(),
+            RelationshipType::SemanticallySimilar.as_str().to_string(),
+            self.confidence,
+            ExtractionMethod::primary(&self.extraction_methods),
+        )
+        .with_similarity_score(self.similarity_score)
+        .with_temporal_distance(self.temporal_distance_days.map(|days| days as i32))
+        .with_author_match(self.extraction_methods.contains(&ExtractionMethod::AuthorOverlap));
+        evidence.properties = serde_json::json!({
+            "extraction_methods": self.extraction_methods.iter().map(|m| m.as_str()).collect::<Vec<_>>(),
+        });
+        evidence
     }
     
-    let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    /// The link as reported to callers, with only the signals it records
+    pub fn to_semantic_link(&self) -> SemanticLink {
+        SemanticLink {
+            from_chunk_id: self.from_id.as_uuid(),
+            to_chunk_id: self.to_id.as_uuid(),
+            relationship_type: RelationshipType::SemanticallySimilar.as_str().to_string(),
+            confidence: self.confidence,
+            extraction_methods: self.extraction_methods.iter().map(|m| m.as_str().to_string()).collect(),
+            similarity_score: Some(self.similarity_score),
+            explicit_mention: None,
+            temporal_distance_days: self.temporal_distance_days.map(|days| days as i32),
+            author_overlap: self.extraction_methods.contains(&ExtractionMethod::AuthorOverlap),
+            direction: EdgeDirection::Outgoing,
+        }
+    }
+}
+
+/// Deduplicates cross-source link candidates before they are written
+/// 
+/// Each directed `(from, to, rel_type)` link is written once per run, and both
+/// directions of a pair share one confidence (the highest seen for the pair)
+/// and the union of their evidence, so forward and reverse linking can't
+/// disagree about the same logical pair.
+#[derive(Debug, Default)]
+pub struct CrossLinkPlan {
+    /// Directed links in first-seen order
+    directions: Vec<LinkKey>,
+    seen: HashSet<LinkKey>,
+    /// Merged evidence per unordered pair
+    pairs: HashMap<LinkKey, PlannedLink>,
+}
+
+impl CrossLinkPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
     
-    if norm_a == 0.0 || norm_b == 0.0 {
-        return 0.0;
+    /// Add a match found while linking from `source_id`
+    pub fn add(&mut self, source_id: EntityId, m: &CrossSourceMatch) {
+        let rel_type = RelationshipType::SemanticallySimilar.as_str();
+        let target_id = m.target_id;
+        let direction = (source_id, target_id, rel_type);
+        if self.seen.insert(direction) {
+            self.directions.push(direction);
+        }
+        
+        let pair_key = if source_id <= target_id {
+            (source_id, target_id, rel_type)
+        } else {
+            (target_id, source_id, rel_type)
+        };
+        let pair = self.pairs.entry(pair_key).or_insert_with(|| PlannedLink {
+            from_id: source_id,
+            to_id: target_id,
+            confidence: 0.0,
+            similarity_score: 0.0,
+            extraction_methods: Vec::new(),
+            temporal_distance_days: None,
+        });
+        pair.confidence = pair.confidence.max(m.confidence);
+        pair.temporal_distance_days = pair.temporal_distance_days.or(m.temporal_distance_days);
+        pair.similarity_score = pair.similarity_score.max(m.similarity_score);
+        for method in m.extraction_methods() {
+            if !pair.extraction_methods.contains(&method) {
+                pair.extraction_methods.push(method);
+            }
+        }
     }
     
-    dot_product / (norm_a * norm_b)
+    /// One link per distinct direction, carrying its pair's merged evidence
+    pub fn into_links(self) -> Vec<PlannedLink> {
+        self.directions
+            .into_iter()
+            .map(|(from_id, to_id, rel_type)| {
+                let pair_key = if from_id <= to_id {
+                    (from_id, to_id, rel_type)
+                } else {
+                    (to_id, from_id, rel_type)
+                };
+                let pair = &self.pairs[&pair_key];
+                PlannedLink {
+                    from_id,
+                    to_id,
+                    confidence: pair.confidence,
+                    similarity_score: pair.similarity_score,
+                    extraction_methods: pair.extraction_methods.clone(),
+                    temporal_distance_days: pair.temporal_distance_days,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A scored cross-source link candidate
+struct ScoredLink {
+    relationship_type: RelationshipType,
+    confidence: f32,
+    extraction_methods: Vec<ExtractionMethod>,
+    evidence_text: Option<String>,
+    temporal_distance: Option<i64>,
+    author_overlap: bool,
+}
+
+impl ScoredLink {
+    fn into_evidence(self, doc_id: Uuid, code_id: Uuid, similarity: f32) -> RelationshipEvidence {
+        let method = ExtractionMethod::primary(&self.extraction_methods);
+        
+        let mut evidence = RelationshipEvidence::new(
+            doc_id,
+            code_id,
+            self.relationship_type.as_str().to_string(),
+            self.confidence,
+            method,
+        )
+        .with_similarity_score(similarity)
+        .with_author_match(self.author_overlap);
+        
+        if let Some(days) = self.temporal_distance {
+            evidence = evidence.with_temporal_distance(Some(days as i32));
+        }
+        if let Some(text) = self.evidence_text {
+            evidence = evidence.with_evidence_text(text);
+        }
+        evidence
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{ChunkInput, EdgeDirection};
+    
+    fn chunk(content: &str, source_kind: &str, file_path: &str) -> Chunk {
+        let input: ChunkInput = serde_json::from_value(serde_json::json!({
+            "content": content,
+            "source_kind": source_kind,
+            "source_type": "github",
+            "source_id": file_path,
+            "file_path": file_path,
+            "owner_id": "owner-1",
+        })).unwrap();
+        input.into_chunk()
+    }
+    
+    #[test]
+    fn test_explicit_mention_boost_applied_once() {
+        let mut config = Config::test_default();
+        config.enable_explicit_mentions = true;
+        config.enable_temporal_proximity = false;
+        config.enable_author_overlap = false;
+        config.explicit_mention_boost = 0.15;
+        let linker = CrossSourceLinker::new(config, None);
+        
+        let doc = chunk("Call `authenticate` before any request.", "document", "docs/auth.md");
+        let code = chunk("pub fn authenticate(token: &str) -> bool { true }", "code", "src/session.rs");
+        
+        // Detected in Rust only
+        let link = linker.score_link(&doc, &code, 0.6, false, false);
+        assert!((link.confidence - 0.75).abs() < 1e-6);
+        assert!(link.evidence_text.is_some());
+        
+        // Already detected by Neo4j: Rust detection must not add the boost again
+        let link = linker.score_link(&doc, &code, 0.6, true, false);
+        assert!((link.confidence - 0.75).abs() < 1e-6);
+        assert_eq!(
+            link.extraction_methods.iter().filter(|m| **m == ExtractionMethod::ExplicitMention).count(),
+            1
+        );
+        
+        // No mention anywhere: similarity only
+        let unrelated = chunk("Deployment notes for the staging cluster.", "document", "docs/deploy.md");
+        let link = linker.score_link(&unrelated, &code, 0.6, false, false);
+        assert!((link.confidence - 0.6).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_boosts_normalized_by_available_metadata() {
+        let mut config = Config::test_default();
+        config.enable_explicit_mentions = true;
+        config.enable_author_overlap = true;
+        config.enable_temporal_proximity = true;
+        config.explicit_mention_boost = 0.15;
+        config.author_overlap_boost = 0.1;
+        config.temporal_proximity_boost = 0.1;
+        config.normalize_link_boosts = true;
+        let linker = CrossSourceLinker::new(config.clone(), None);
+        
+        let doc = chunk("Call `authenticate` before any request.", "document", "docs/auth.md");
+        let code = chunk("pub fn authenticate(token: &str) -> bool { true }", "code", "src/session.rs");
+        let (mut rich_doc, mut rich_code) = (doc.clone(), code.clone());
+        rich_doc.author = Some("ana".to_string());
+        rich_code.author = Some("ana".to_string());
+        rich_code.commit_date = Some(rich_doc.updated_at);
+        
+        // Each pair earns everything its metadata allows, so they tie
+        let rich = linker.score_link(&rich_doc, &rich_code, 0.5, false, false);
+        let poor = linker.score_link(&doc, &code, 0.5, false, false);
+        assert!((rich.confidence - 0.85).abs() < 1e-6);
+        assert!((poor.confidence - 0.85).abs() < 1e-6);
+        
+        // A rich pair that only shares a mention earns a share of the total
+        rich_code.author = Some("bo".to_string());
+        rich_code.commit_date = Some(rich_doc.updated_at - chrono::Duration::days(30));
+        let partial = linker.score_link(&rich_doc, &rich_code, 0.5, false, false);
+        assert!((partial.confidence - 0.65).abs() < 1e-6);
+        
+        // Without normalization the poor pair only gets the mention boost
+        config.normalize_link_boosts = false;
+        let linker = CrossSourceLinker::new(config, None);
+        let poor = linker.score_link(&doc, &code, 0.5, false, false);
+        assert!((poor.confidence - 0.65).abs() < 1e-6);
+    }
+    
+    fn link_match(target_id: EntityId, confidence: f32, mention: bool) -> CrossSourceMatch {
+        CrossSourceMatch {
+            target_id,
+            target_content: None,
+            target_source_type: None,
+            target_file_path: None,
+            similarity_score: confidence,
+            confidence,
+            has_explicit_mention: mention,
+            has_author_overlap: false,
+            has_temporal_proximity: false,
+            temporal_distance_days: None,
+        }
+    }
+    
+    #[test]
+    fn test_overlapping_links_yield_one_edge_per_direction() {
+        let (doc, code, other) = (EntityId(Uuid::new_v4()), EntityId(Uuid::new_v4()), EntityId(Uuid::new_v4()));
+        let matches = [
+            (doc, link_match(code, 0.8, true)),
+            // Overlapping chunk in the same batch finds the same pair again, weaker
+            (doc, link_match(code, 0.7, false)),
+            (code, link_match(doc, 0.9, false)),
+            (doc, link_match(other, 0.76, false)),
+        ];
+        
+        let mut plan = CrossLinkPlan::new();
+        for (source, m) in &matches {
+            plan.add(*source, m);
+        }
+        let links = plan.into_links();
+        
+        let directions: Vec<(EntityId, EntityId)> = links.iter()
+            .map(|l| (l.from_id, l.to_id))
+            .collect();
+        assert_eq!(directions, vec![(doc, code), (code, doc), (doc, other)]);
+        
+        // Both directions of the pair agree on the strongest confidence and merged evidence
+        for link in &links[..2] {
+            assert_eq!(link.confidence, 0.9);
+            assert_eq!(
+                link.extraction_methods,
+                vec![ExtractionMethod::VectorSimilarity, ExtractionMethod::ExplicitMention]
+            );
+        }
+        assert_eq!(links[2].confidence, 0.76);
+        
+        // Order of discovery doesn't change the outcome
+        let mut reversed = CrossLinkPlan::new();
+        for (source, m) in matches.iter().rev() {
+            reversed.add(*source, m);
+        }
+        let mut reversed = reversed.into_links();
+        reversed.sort_by(|a, b| (&a.from_id, &a.to_id).cmp(&(&b.from_id, &b.to_id)));
+        let mut expected = links.clone();
+        expected.sort_by(|a, b| (&a.from_id, &a.to_id).cmp(&(&b.from_id, &b.to_id)));
+        assert_eq!(
+            reversed.iter().map(|l| l.confidence).collect::<Vec<_>>(),
+            expected.iter().map(|l| l.confidence).collect::<Vec<_>>()
+        );
+    }
     
     #[test]
-    fn test_cosine_similarity() {
-        let a = vec![1.0, 0.0, 0.0];
-        let b = vec![1.0, 0.0, 0.0];
-        assert!((cosine_similarity(&a, &b) - 1.0).abs() < 0.001);
+    fn test_planned_link_evidence_records_methods() {
+        let (doc, code) = (Uuid::new_v4(), Uuid::new_v4());
+        let link = PlannedLink {
+            from_id: EntityId(doc),
+            to_id: EntityId(code),
+            confidence: 0.9,
+            similarity_score: 0.8,
+            extraction_methods: vec![ExtractionMethod::VectorSimilarity, ExtractionMethod::AuthorOverlap],
+            temporal_distance_days: Some(3),
+        };
+        
+        let evidence = link.to_evidence();
+        assert_eq!(evidence.from_chunk_id, doc);
+        assert_eq!(evidence.to_chunk_id, code);
+        assert_eq!(evidence.relationship_type, "SEMANTICALLY_SIMILAR");
+        assert_eq!(evidence.extraction_method, "combined");
+        assert_eq!(evidence.similarity_score, Some(0.8));
+        assert!(evidence.author_match);
+        assert_eq!(evidence.temporal_distance_days, Some(3));
+        assert_eq!(evidence.properties["extraction_methods"], serde_json::json!(["vector_similarity", "author_overlap"]));
+    }
+    
+    #[tokio::test]
+    async fn test_relink_validates_source_kinds() {
+        let linker = CrossSourceLinker::new(Config::test_default(), None);
+        let request: CrossSourceLinkRequest = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(matches!(
+            linker.relink(&request, None).await,
+            Err(GraphError::ServiceUnavailable(_))
+        ));
+        
+        assert_eq!(validate_source_kind("code").unwrap(), "code");
+        assert!(matches!(validate_source_kind("wiki"), Err(GraphError::InvalidRequest(_))));
+    }
+    
+    #[test]
+    fn test_link_pairs_follow_request_then_config() {
+        let linker = CrossSourceLinker::new(Config::test_default(), None);
+        assert_eq!(linker.link_pairs(None, None).unwrap(), [("document", "code"), ("code", "document")]);
+        assert_eq!(linker.link_pairs(Some("code"), None).unwrap(), [("code", "document")]);
+        assert_eq!(linker.link_pairs(None, Some("code")).unwrap(), [("document", "code")]);
+        // An explicit pair is honored even when it isn't configured
+        assert_eq!(linker.link_pairs(Some("document"), Some("document")).unwrap(), [("document", "document")]);
+        assert!(matches!(linker.link_pairs(Some("wiki"), None), Err(GraphError::InvalidRequest(_))));
+        
+        let mut config = Config::test_default();
+        config.cross_link_pairs = vec![("document".to_string(), "code".to_string())];
+        let linker = CrossSourceLinker::new(config, None);
+        assert!(matches!(linker.link_pairs(Some("code"), None), Err(GraphError::InvalidRequest(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_relink_skips_pairs_without_targets() {
+        let graph = crate::graph_db::InMemoryGraph::new();
+        for path in ["docs/a.md", "docs/b.md"] {
+            let chunk = chunk(path, "document", path);
+            graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+            graph.set_node_embedding(&EntityId(chunk.id), vec![1.0, 0.0], "test", "test").await.unwrap();
+        }
+        let graph: Arc<dyn GraphStore> = Arc::new(graph);
+        let request = |body| serde_json::from_value::<CrossSourceLinkRequest>(body).unwrap();
+        
+        // No code chunks: the default passes have nothing to link
+        let linker = CrossSourceLinker::new(Config::test_default(), Some(graph.clone()));
+        let response = linker.relink(&request(serde_json::json!({})), None).await.unwrap();
+        assert_eq!((response.links_created, response.chunks_processed), (0, 0));
+        
+        let mut config = Config::test_default();
+        config.cross_link_pairs.push(("document".to_string(), "document".to_string()));
+        let linker = CrossSourceLinker::new(config, Some(graph));
+        let response = linker.relink(&request(serde_json::json!({})), None).await.unwrap();
+        assert_eq!((response.links_created, response.chunks_processed), (2, 2));
+    }
+    
+    /// A doc chunk, a matching and a dissimilar code chunk, and stale links between the doc and the dissimilar one
+    async fn stale_link_graph(graph: crate::graph_db::InMemoryGraph) -> (Arc<dyn GraphStore>, [EntityId; 3]) {
+        let mut ids = Vec::new();
+        for (path, kind, embedding) in [
+            ("docs/auth.md", "document", vec![1.0, 0.0]),
+            ("src/auth.rs", "code", vec![0.9, 0.1]),
+            ("src/other.rs", "code", vec![-1.0, 0.0]),
+        ] {
+            let chunk = chunk(path, kind, path);
+            graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+            graph.set_node_embedding(&EntityId(chunk.id), embedding, "test", "test").await.unwrap();
+            ids.push(EntityId(chunk.id));
+        }
+        let [doc, code, other] = ids[..] else { unreachable!() };
+        for (from, to) in [(doc, other), (other, doc)] {
+            graph.create_cross_source_link(&from, &to, 0.9, 0.9, &[ExtractionMethod::VectorSimilarity]).await.unwrap();
+        }
+        (Arc::new(graph), [doc, code, other])
+    }
+    
+    async fn linked_to(graph: &dyn GraphStore, id: &EntityId) -> Vec<(EntityId, EdgeDirection)> {
+        let mut links: Vec<_> = graph.cross_source_chunks(&[*id], "both", 100, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|link| (EntityId(link.chunk.chunk_id), link.direction))
+            .collect();
+        links.sort_by_key(|(id, direction)| (*id, *direction == EdgeDirection::Incoming));
+        links
+    }
+    
+    #[tokio::test]
+    async fn test_forced_relink_replaces_links_in_both_directions() {
+        let (graph, [doc, code, other]) = stale_link_graph(crate::graph_db::InMemoryGraph::new()).await;
+        let linker = CrossSourceLinker::new(Config::test_default(), Some(graph.clone()));
+        let request: CrossSourceLinkRequest = serde_json::from_value(serde_json::json!({
+            "chunk_ids": [doc.as_uuid()],
+            "force": true,
+        })).unwrap();
+        
+        let response = linker.relink(&request, None).await.unwrap();
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!((response.links_created, response.chunks_processed), (2, 1));
+        // The stale link from the other chunk is gone too, and the match is linked both ways
+        assert!(linked_to(graph.as_ref(), &other).await.is_empty());
+        assert_eq!(
+            linked_to(graph.as_ref(), &doc).await,
+            [(code, EdgeDirection::Outgoing), (code, EdgeDirection::Incoming)]
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_forced_relink_keeps_links_when_the_search_fails() {
+        let failing = crate::graph_db::InMemoryGraph::new().with_failing_link_searches();
+        let (graph, [doc, _, other]) = stale_link_graph(failing).await;
+        let linker = CrossSourceLinker::new(Config::test_default(), Some(graph.clone()));
+        let request: CrossSourceLinkRequest = serde_json::from_value(serde_json::json!({
+            "chunk_ids": [doc.as_uuid()],
+            "force": true,
+        })).unwrap();
         
-        let c = vec![0.0, 1.0, 0.0];
-        assert!((cosine_similarity(&a, &c)).abs() < 0.001);
+        let response = linker.relink(&request, None).await.unwrap();
+        assert_eq!((response.links_created, response.chunks_processed), (0, 0));
+        assert_eq!(response.errors.len(), 1);
+        assert_eq!(
+            linked_to(graph.as_ref(), &doc).await,
+            [(other, EdgeDirection::Outgoing), (other, EdgeDirection::Incoming)]
+        );
     }
 }
@@ -0,0 +1,156 @@
+//! Cache for embedding vectors
+//!
+//! Keyed by model and whitespace-normalized text. Uses Redis when `REDIS_URL`
+//! is configured so the cache is shared across replicas, otherwise an
+//! in-process LRU. Entries expire after a TTL in both backends.
+
+use crate::error::{GraphError, GraphResult};
+use lru::LruCache;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::Serialize;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+enum CacheBackend {
+    Memory(Mutex<LruCache<String, (Instant, Vec<f32>)>>),
+    Redis(ConnectionManager),
+}
+
+/// Hit/miss counters for the statistics endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingCacheStats {
+    pub backend: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Embedding cache with TTL
+pub struct EmbeddingCache {
+    backend: CacheBackend,
+    ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl EmbeddingCache {
+    /// In-process LRU holding at most `max_entries` vectors
+    pub fn in_memory(max_entries: usize, ttl: Duration) -> Self {
+        let capacity = NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+        Self::with_backend(CacheBackend::Memory(Mutex::new(LruCache::new(capacity))), ttl)
+    }
+
+    /// Redis-backed cache (entry count is bounded by Redis eviction policy)
+    pub async fn redis(url: &str, ttl: Duration) -> GraphResult<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| GraphError::Config(format!("Invalid REDIS_URL: {}", e)))?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(|e| GraphError::ServiceUnavailable(format!("Redis connection failed: {}", e)))?;
+        Ok(Self::with_backend(CacheBackend::Redis(connection), ttl))
+    }
+
+    fn with_backend(backend: CacheBackend, ttl: Duration) -> Self {
+        Self {
+            backend,
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache key for a text under a given model
+    pub fn key(model: &str, text: &str) -> String {
+        let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        format!("embedding:{}:{:x}", model, md5::compute(normalized.as_bytes()))
+    }
+
+    /// Look up a cached vector, counting the hit or miss
+    pub async fn get(&self, key: &str) -> Option<Vec<f32>> {
+        let cached = match &self.backend {
+            CacheBackend::Memory(cache) => {
+                let mut cache = cache.lock().unwrap();
+                match cache.get(key) {
+                    Some((stored_at, embedding)) if stored_at.elapsed() < self.ttl => {
+                        Some(embedding.clone())
+                    }
+                    Some(_) => {
+                        cache.pop(key);
+                        None
+                    }
+                    None => None,
+                }
+            }
+            CacheBackend::Redis(connection) => {
+                let mut connection = connection.clone();
+                match connection.get::<_, Option<String>>(key).await {
+                    Ok(value) => value.and_then(|json| serde_json::from_str(&json).ok()),
+                    Err(e) => {
+                        tracing::warn!("Embedding cache read failed: {}", e);
+                        None
+                    }
+                }
+            }
+        };
+
+        let counter = if cached.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    /// Store a vector (cache write failures are logged, never surfaced)
+    pub async fn put(&self, key: String, embedding: &[f32]) {
+        match &self.backend {
+            CacheBackend::Memory(cache) => {
+                cache.lock().unwrap().put(key, (Instant::now(), embedding.to_vec()));
+            }
+            CacheBackend::Redis(connection) => {
+                let mut connection = connection.clone();
+                let json = serde_json::to_string(embedding).unwrap_or_default();
+                let ttl_secs = self.ttl.as_secs().max(1);
+                if let Err(e) = connection.set_ex::<_, _, ()>(key, json, ttl_secs).await {
+                    tracing::warn!("Embedding cache write failed: {}", e);
+                }
+            }
+        }
+    }
+
+    pub fn stats(&self) -> EmbeddingCacheStats {
+        EmbeddingCacheStats {
+            backend: match self.backend {
+                CacheBackend::Memory(_) => "memory",
+                CacheBackend::Redis(_) => "redis",
+            },
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_cache_ttl_and_capacity() {
+        let cache = EmbeddingCache::in_memory(1, Duration::from_millis(20));
+        let key = EmbeddingCache::key("model", "hello   world");
+        assert_eq!(key, EmbeddingCache::key("model", " hello world\n"));
+        assert_ne!(key, EmbeddingCache::key("other-model", "hello world"));
+
+        cache.put(key.clone(), &[1.0, 2.0]).await;
+        assert_eq!(cache.get(&key).await, Some(vec![1.0, 2.0]));
+
+        // Capacity of one evicts the older entry
+        cache.put("other".to_string(), &[3.0]).await;
+        assert_eq!(cache.get(&key).await, None);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(cache.get("other").await, None);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+}
@@ -1,104 +1,423 @@
 //! Embedding client for calling the embeddings service
 
+use crate::config::Config;
 use crate::error::{GraphError, GraphResult};
+use crate::services::circuit_breaker::{BreakerPermit, BreakerState, CircuitBreaker};
+use crate::services::embedding_cache::{EmbeddingCache, EmbeddingCacheStats};
+use crate::services::embedding_provider::{self, EmbeddingProvider};
+#[cfg(test)]
+use crate::services::embedding_provider::MicroserviceProvider;
+use futures::{stream, StreamExt, TryStreamExt};
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
-/// Client for the embeddings microservice
+/// Client for the configured embedding provider
+/// 
+/// Calls go through a circuit breaker so an overloaded embedding service
+/// results in fast `ServiceUnavailable` errors rather than piled-up requests.
+/// Share one instance across requests so the breaker sees every call.
 pub struct EmbeddingClient {
-    client: Client,
-    base_url: String,
-}
-
-#[derive(Debug, Serialize)]
-struct EmbedRequest {
-    text: String,
-}
-
-#[derive(Debug, Serialize)]
-struct BatchEmbedRequest {
-    texts: Vec<String>,
-}
-
-#[derive(Debug, Deserialize)]
-struct EmbedResponse {
-    embedding: Vec<f32>,
-}
-
-#[derive(Debug, Deserialize)]
-struct BatchEmbedResponse {
-    embeddings: Vec<Vec<f32>>,
+    provider: Box<dyn EmbeddingProvider>,
+    model: String,
+    breaker: CircuitBreaker,
+    cache: Option<EmbeddingCache>,
+    /// Max texts per provider request
+    batch_size: usize,
+    /// Sub-batch requests in flight per `embed_batch` call
+    batch_concurrency: usize,
 }
 
 impl EmbeddingClient {
-    pub fn new(base_url: &str) -> Self {
+    /// Client for the embeddings microservice at `base_url`
+    #[cfg(test)]
+    pub fn new(base_url: &str, model: &str, timeout: Duration, breaker: CircuitBreaker) -> Self {
+        let provider = MicroserviceProvider::new(http_client(timeout, DEFAULT_CONNECT_TIMEOUT), base_url);
+        Self::with_provider(Box::new(provider), model, breaker)
+    }
+    
+    pub fn with_provider(provider: Box<dyn EmbeddingProvider>, model: &str, breaker: CircuitBreaker) -> Self {
         Self {
-            client: Client::new(),
-            base_url: base_url.trim_end_matches('/').to_string(),
+            provider,
+            model: model.to_string(),
+            breaker,
+            cache: None,
+            batch_size: 32,
+            batch_concurrency: 4,
         }
     }
     
+    /// Create a client for `EMBEDDING_PROVIDER` using the configured timeout, breaker, and in-process cache settings
+    /// 
+    /// Swap in a Redis-backed cache with `with_cache` when `REDIS_URL` is set.
+    pub fn from_config(config: &Config) -> Self {
+        let provider = embedding_provider::from_config(
+            config,
+            http_client(
+                Duration::from_millis(config.embedding_timeout_ms),
+                Duration::from_millis(config.embedding_connect_timeout_ms),
+            ),
+        );
+        let client = Self::with_provider(
+            provider,
+            &config.embedding_model,
+            CircuitBreaker::new(
+                config.embedding_breaker_threshold,
+                Duration::from_secs(config.embedding_breaker_cooldown_secs),
+            ),
+        )
+        .with_batching(config.embedding_batch_size, config.embedding_batch_concurrency);
+        
+        if config.embedding_cache_size > 0 {
+            client.with_cache(EmbeddingCache::in_memory(
+                config.embedding_cache_size,
+                Duration::from_secs(config.embedding_cache_ttl_secs),
+            ))
+        } else {
+            client
+        }
+    }
+    
+    /// Split `embed_batch` calls into requests of at most `batch_size` texts, `concurrency` at a time
+    pub fn with_batching(mut self, batch_size: usize, concurrency: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self.batch_concurrency = concurrency.max(1);
+        self
+    }
+    
+    /// Cache `embed` results
+    pub fn with_cache(mut self, cache: EmbeddingCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+    
+    /// Cache hit/miss counters (None when caching is disabled)
+    pub fn cache_stats(&self) -> Option<EmbeddingCacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+    
+    /// Current state of the circuit breaker
+    pub fn breaker_state(&self) -> BreakerState {
+        self.breaker.state()
+    }
+    
+    /// Consecutive failed calls seen by the circuit breaker
+    pub fn consecutive_failures(&self) -> u32 {
+        self.breaker.consecutive_failures()
+    }
+    
     /// Embed a single text
     pub async fn embed(&self, text: &str) -> GraphResult<Vec<f32>> {
-        let url = format!("{}/embed", self.base_url);
-        
-        let response = self.client
-            .post(&url)
-            .json(&EmbedRequest { text: text.to_string() })
-            .send()
-            .await
-            .map_err(|e| GraphError::Embedding(format!("Request failed: {}", e)))?;
-        
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(GraphError::Embedding(format!("Embed failed: {} - {}", status, body)));
-        }
+        self.embed_with_model(text, None).await
+    }
+    
+    /// Embed a single text with a specific model (`None` uses the configured one)
+    pub async fn embed_with_model(&self, text: &str, model: Option<&str>) -> GraphResult<Vec<f32>> {
+        let cache_key = match &self.cache {
+            Some(cache) => {
+                let key = EmbeddingCache::key(model.unwrap_or(&self.model), text);
+                if let Some(embedding) = cache.get(&key).await {
+                    return Ok(embedding);
+                }
+                Some(key)
+            }
+            None => None,
+        };
         
-        let result: EmbedResponse = response
-            .json()
-            .await
-            .map_err(|e| GraphError::Embedding(format!("Parse failed: {}", e)))?;
+        let permit = self.acquire()?;
+        let result = self.provider.embed(text, model).await;
+        record(permit, &result);
         
-        Ok(result.embedding)
+        if let (Some(cache), Some(key), Ok(embedding)) = (&self.cache, cache_key, &result) {
+            cache.put(key, embedding).await;
+        }
+        result
     }
     
-    /// Embed multiple texts in a batch
+    /// Embed multiple texts, returning one vector per text in input order
+    /// 
+    /// Texts are sent in sub-batches of at most `batch_size`, up to
+    /// `batch_concurrency` at once. Each sub-batch is retried on its own by the
+    /// provider; the call fails if any sub-batch still fails after its retries.
     pub async fn embed_batch(&self, texts: Vec<String>) -> GraphResult<Vec<Vec<f32>>> {
         if texts.is_empty() {
             return Ok(Vec::new());
         }
         
-        let url = format!("{}/batch/embed", self.base_url);
-        
-        let response = self.client
-            .post(&url)
-            .json(&BatchEmbedRequest { texts })
-            .send()
-            .await
-            .map_err(|e| GraphError::Embedding(format!("Batch request failed: {}", e)))?;
-        
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(GraphError::Embedding(format!("Batch embed failed: {} - {}", status, body)));
+        let total = texts.len();
+        let sub_batches: Vec<Vec<String>> = texts.chunks(self.batch_size).map(<[String]>::to_vec).collect();
+        if sub_batches.len() == 1 {
+            return self.embed_sub_batch(texts).await;
         }
         
-        let result: BatchEmbedResponse = response
-            .json()
-            .await
-            .map_err(|e| GraphError::Embedding(format!("Parse failed: {}", e)))?;
+        let count = sub_batches.len();
+        // `buffered` yields in submission order, so results line up with the input
+        let embeddings: Vec<Vec<Vec<f32>>> = stream::iter(sub_batches.into_iter().enumerate())
+            .map(|(i, sub_batch)| async move {
+                let start = i * self.batch_size;
+                let end = start + sub_batch.len();
+                self.embed_sub_batch(sub_batch).await.map_err(|e| match e {
+                    // An open breaker stays a `ServiceUnavailable`
+                    GraphError::Embedding(message) => GraphError::Embedding(format!(
+                        "Sub-batch {} of {} (texts {}..{} of {}) failed: {}",
+                        i + 1, count, start, end, total, message
+                    )),
+                    GraphError::EmbeddingUnavailable(message) => GraphError::EmbeddingUnavailable(format!(
+                        "Sub-batch {} of {} (texts {}..{} of {}) failed: {}",
+                        i + 1, count, start, end, total, message
+                    )),
+                    other => other,
+                })
+            })
+            .buffered(self.batch_concurrency)
+            .try_collect()
+            .await?;
         
-        Ok(result.embeddings)
+        Ok(embeddings.into_iter().flatten().collect())
+    }
+    
+    /// One provider request, checked to return a vector per text
+    async fn embed_sub_batch(&self, texts: Vec<String>) -> GraphResult<Vec<Vec<f32>>> {
+        let expected = texts.len();
+        let permit = self.acquire()?;
+        let result = self.provider.embed_batch(texts).await.and_then(|embeddings| {
+            if embeddings.len() == expected {
+                Ok(embeddings)
+            } else {
+                Err(GraphError::Embedding(format!(
+                    "batch returned {} vectors for {} texts",
+                    embeddings.len(), expected
+                )))
+            }
+        });
+        record(permit, &result);
+        result
+    }
+    
+    /// Fail fast while the breaker is open
+    fn acquire(&self) -> GraphResult<BreakerPermit<'_>> {
+        self.breaker.try_acquire().ok_or_else(|| GraphError::ServiceUnavailable(
+            "Embedding service circuit breaker is open; retry after cooldown".to_string(),
+        ))
+    }
+    
+    /// Name of the configured provider
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+    
+    /// Output dimension of the configured model
+    /// 
+    /// Bypasses the breaker and cache; meant for the startup check against `VECTOR_DIMENSION`.
+    pub async fn dimension(&self) -> GraphResult<usize> {
+        self.provider.dimension().await
     }
     
     /// Health check
     pub async fn health_check(&self) -> bool {
-        let url = format!("{}/health", self.base_url);
+        self.provider.health_check().await
+    }
+}
+
+/// Feed a call's outcome to the breaker
+/// 
+/// Only an unreachable service or a server error counts as a failure; any
+/// other answer shows the service is up.
+fn record<T>(permit: BreakerPermit<'_>, result: &GraphResult<T>) {
+    match result {
+        Err(GraphError::EmbeddingUnavailable(_)) => permit.record_failure(),
+        _ => permit.record_success(),
+    }
+}
+
+/// Connect timeout for clients built with `EmbeddingClient::new`
+#[cfg(test)]
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// HTTP client with a per-attempt timeout, so a stalled provider can't hang ingestion
+fn http_client(timeout: Duration, connect_timeout: Duration) -> Client {
+    Client::builder()
+        .timeout(timeout)
+        .connect_timeout(connect_timeout.min(timeout))
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_breaker_fails_fast_after_consecutive_failures() {
+        // Nothing listens on port 1, so every call fails at the transport level
+        let client = EmbeddingClient::new(
+            "http://127.0.0.1:1",
+            "test-model",
+            Duration::from_millis(500),
+            CircuitBreaker::new(2, Duration::from_secs(60)),
+        );
         
-        match self.client.get(&url).send().await {
-            Ok(response) => response.status().is_success(),
-            Err(_) => false,
+        for _ in 0..2 {
+            assert!(matches!(client.embed("query").await, Err(GraphError::EmbeddingUnavailable(_))));
         }
+        assert_eq!(client.breaker_state(), BreakerState::Open);
+        
+        let result = client.embed("query").await;
+        assert!(matches!(result, Err(GraphError::ServiceUnavailable(_))));
+        let result = client.embed_batch(vec!["query".to_string()]).await;
+        assert!(matches!(result, Err(GraphError::ServiceUnavailable(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_repeated_embed_is_served_from_cache() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "embedding": [0.1, 0.2, 0.3]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let client = EmbeddingClient::new(
+            &server.uri(),
+            "test-model",
+            Duration::from_secs(5),
+            CircuitBreaker::new(5, Duration::from_secs(30)),
+        )
+        .with_cache(EmbeddingCache::in_memory(16, Duration::from_secs(60)));
+        
+        let first = client.embed("how does auth work").await.unwrap();
+        let second = client.embed("  how does   auth work ").await.unwrap();
+        assert_eq!(first, second);
+        
+        let stats = client.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        // MockServer verifies the `expect(1)` call count on drop
+    }
+    
+    fn retrying_client(base_url: &str, timeout: Duration) -> EmbeddingClient {
+        use crate::services::embedding_provider::RetryPolicy;
+        
+        let provider = MicroserviceProvider::new(http_client(timeout, timeout), base_url)
+            .with_retry(RetryPolicy { max_retries: 2, base_delay: Duration::from_millis(10) });
+        EmbeddingClient::with_provider(Box::new(provider), "test-model", CircuitBreaker::new(5, Duration::from_secs(30)))
+    }
+    
+    #[tokio::test]
+    async fn test_slow_service_times_out_instead_of_hanging() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embed"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "embedding": [0.1] }))
+                    .set_delay(Duration::from_secs(30)),
+            )
+            .expect(3)
+            .mount(&server)
+            .await;
+        
+        let client = retrying_client(&server.uri(), Duration::from_millis(200));
+        let started = std::time::Instant::now();
+        let result = client.embed("query").await;
+        assert!(matches!(result, Err(GraphError::EmbeddingUnavailable(_))), "{:?}", result);
+        // Three timed-out attempts plus backoff, nowhere near the 30s delay
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(client.consecutive_failures(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_large_batch_is_split_and_reassembled_in_order() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        let failed_once = Arc::new(AtomicBool::new(false));
+        let flag = failed_once.clone();
+        Mock::given(method("POST"))
+            .and(path("/batch/embed"))
+            .respond_with(move |req: &Request| {
+                let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+                let ids: Vec<f32> = body["texts"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|text| text.as_str().unwrap().trim_start_matches("text-").parse().unwrap())
+                    .collect();
+                // The sub-batch starting at 128 fails once
+                if ids[0] == 128.0 && !flag.swap(true, Ordering::SeqCst) {
+                    return ResponseTemplate::new(503);
+                }
+                // Earlier sub-batches answer last, so completion order is reversed
+                let delay = Duration::from_millis(200 - ids[0] as u64 / 2);
+                let embeddings: Vec<Vec<f32>> = ids.iter().map(|&id| vec![id]).collect();
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "embeddings": embeddings }))
+                    .set_delay(delay)
+            })
+            .mount(&server)
+            .await;
+        
+        let client = retrying_client(&server.uri(), Duration::from_secs(5)).with_batching(64, 4);
+        let texts: Vec<String> = (0..250).map(|i| format!("text-{}", i)).collect();
+        let embeddings = client.embed_batch(texts).await.unwrap();
+        
+        let ids: Vec<f32> = embeddings.iter().map(|embedding| embedding[0]).collect();
+        assert_eq!(ids, (0..250).map(|i| i as f32).collect::<Vec<_>>());
+        
+        // Four sub-batches plus one retry of the failed one
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 5);
+        let mut sizes: Vec<usize> = requests
+            .iter()
+            .map(|req| serde_json::from_slice::<serde_json::Value>(&req.body).unwrap()["texts"].as_array().unwrap().len())
+            .collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![58, 64, 64, 64, 64]);
+    }
+    
+    #[tokio::test]
+    async fn test_server_errors_are_retried_and_client_errors_are_not() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embed"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/embed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "embedding": [0.5] })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/batch/embed"))
+            .respond_with(ResponseTemplate::new(422).set_body_string("text too long"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let client = retrying_client(&server.uri(), Duration::from_secs(5));
+        assert_eq!(client.embed("query").await.unwrap(), vec![0.5]);
+        
+        let result = client.embed_batch(vec!["query".to_string()]).await;
+        assert!(matches!(result, Err(GraphError::Embedding(message)) if message.contains("422")));
+        // The service answered, so neither error counts against the breaker
+        assert_eq!(client.consecutive_failures(), 0);
     }
 }
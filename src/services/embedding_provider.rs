@@ -0,0 +1,574 @@
+//! Embedding backends behind `EmbeddingClient`
+//!
+//! `EMBEDDING_PROVIDER` picks where vectors come from:
+//!
+//! - `microservice` (default): the embeddings service at
+//!   `EMBEDDING_SERVICE_URL` (`POST /embed`, `POST /batch/embed`)
+//! - `openai`: OpenAI's embeddings API, or any server implementing it
+//!   (`OPENAI_BASE_URL`, `OPENAI_API_KEY`)
+//! - `ollama`: a model served locally by Ollama (`OLLAMA_URL`)
+//! - `local`: a sentence-transformer run in process from `LOCAL_MODEL_PATH`
+//!   (builds with `--features local-embeddings`; see `local_embedding`)
+//!
+//! Providers translate requests and retry transient HTTP failures; the
+//! circuit breaker and cache stay in `EmbeddingClient`, so they apply whichever
+//! backend is configured.
+
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::{GraphError, GraphResult};
+
+/// Values accepted for `EMBEDDING_PROVIDER`
+pub const EMBEDDING_PROVIDERS: [&str; 4] = ["microservice", "openai", "ollama", "local"];
+
+/// Files the local provider loads from `LOCAL_MODEL_PATH`
+pub const LOCAL_MODEL_FILES: [&str; 3] = ["config.json", "tokenizer.json", "model.safetensors"];
+
+/// Text embedded to learn a provider's output dimension
+const DIMENSION_PROBE: &str = "dimension probe";
+
+/// A source of embedding vectors
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Name as configured in `EMBEDDING_PROVIDER`
+    fn name(&self) -> &'static str;
+
+    /// Embed one text, with `model` overriding the configured model
+    async fn embed(&self, text: &str, model: Option<&str>) -> GraphResult<Vec<f32>>;
+
+    /// Embed several texts with the configured model, in order
+    async fn embed_batch(&self, texts: Vec<String>) -> GraphResult<Vec<Vec<f32>>>;
+
+    /// Length of the vectors the configured model returns
+    ///
+    /// Embeds a probe text unless the provider knows the dimension up front.
+    async fn dimension(&self) -> GraphResult<usize> {
+        Ok(self.embed(DIMENSION_PROBE, None).await?.len())
+    }
+
+    async fn health_check(&self) -> bool;
+}
+
+/// Check the provider settings before anything is embedded
+pub fn check_config(config: &Config) -> Result<(), String> {
+    match config.embedding_provider.as_str() {
+        "microservice" | "ollama" => Ok(()),
+        "openai" if config.openai_api_key.is_none() => {
+            Err("EMBEDDING_PROVIDER=openai needs OPENAI_API_KEY".to_string())
+        }
+        "openai" => Ok(()),
+        "local" if !cfg!(feature = "local-embeddings") => {
+            Err("EMBEDDING_PROVIDER=local needs a build with --features local-embeddings".to_string())
+        }
+        "local" => {
+            let dir = config.local_model_path.as_deref()
+                .ok_or_else(|| "EMBEDDING_PROVIDER=local needs LOCAL_MODEL_PATH".to_string())?;
+            match LOCAL_MODEL_FILES.iter().find(|file| !std::path::Path::new(dir).join(file).is_file()) {
+                Some(missing) => Err(format!("LOCAL_MODEL_PATH {} has no {}", dir, missing)),
+                None => Ok(()),
+            }
+        }
+        other => Err(format!(
+            "Unknown EMBEDDING_PROVIDER '{}' (expected one of {})",
+            other,
+            EMBEDDING_PROVIDERS.join(", "),
+        )),
+    }
+}
+
+/// The provider named by `EMBEDDING_PROVIDER` (the microservice when unrecognized)
+pub fn from_config(config: &Config, client: Client) -> Box<dyn EmbeddingProvider> {
+    let retry = RetryPolicy::from_config(config);
+    match config.embedding_provider.as_str() {
+        "openai" => Box::new(OpenAiProvider::new(
+            client,
+            &config.openai_base_url,
+            config.openai_api_key.clone().unwrap_or_default(),
+            &config.embedding_model,
+            config.openai_embedding_dimensions,
+        ).with_retry(retry)),
+        "ollama" => Box::new(OllamaProvider::new(client, &config.ollama_url, &config.embedding_model).with_retry(retry)),
+        #[cfg(feature = "local-embeddings")]
+        "local" => Box::new(crate::services::local_embedding::LocalProvider::new(
+            config.local_model_path.as_deref().unwrap_or_default(),
+            &config.embedding_model,
+        )),
+        _ => Box::new(MicroserviceProvider::new(client, &config.embedding_service_url).with_retry(retry)),
+    }
+}
+
+/// How often and how patiently transient failures are retried
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retrying)
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each one after
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 2, base_delay: Duration::from_millis(200) }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_retries: config.embedding_max_retries,
+            base_delay: Duration::from_millis(config.embedding_retry_base_ms),
+        }
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        self.base_delay.saturating_mul(1 << retry.min(16))
+    }
+}
+
+/// Whether a response status is worth retrying: rate limiting and server errors
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Send a request and decode a JSON response, mapping failures to `GraphError::Embedding`
+///
+/// Timeouts, connection errors, `429`, and `5xx` are retried with backoff;
+/// other error statuses are returned at once. Transport errors and `5xx`
+/// come back as `GraphError::EmbeddingUnavailable`.
+async fn send_json<T: DeserializeOwned>(request: RequestBuilder, retry: &RetryPolicy, what: &str) -> GraphResult<T> {
+    let mut attempt = 0;
+    let response = loop {
+        // Bodies here are always JSON, so cloning only fails on a streaming body
+        let next = request
+            .try_clone()
+            .ok_or_else(|| GraphError::Internal(format!("{} request can't be retried", what)))?;
+        let failure = match next.send().await {
+            Ok(response) if response.status().is_success() => break response,
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                let message = format!("{} failed: {} - {}", what, status, body);
+                if !is_transient(status) {
+                    return Err(GraphError::Embedding(message));
+                }
+                if status.is_server_error() {
+                    GraphError::EmbeddingUnavailable(message)
+                } else {
+                    GraphError::Embedding(message)
+                }
+            }
+            Err(e) => GraphError::EmbeddingUnavailable(format!("{} request failed: {}", what, e)),
+        };
+        if attempt >= retry.max_retries {
+            return Err(failure);
+        }
+
+        let delay = retry.delay(attempt);
+        tracing::warn!("{} (retry {} of {} in {:?})", failure, attempt + 1, retry.max_retries, delay);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    };
+
+    response
+        .json()
+        .await
+        .map_err(|e| GraphError::Embedding(format!("Parse failed: {}", e)))
+}
+
+/// The in-house embeddings microservice
+pub struct MicroserviceProvider {
+    client: Client,
+    base_url: String,
+    retry: RetryPolicy,
+}
+
+#[derive(Debug, Serialize)]
+struct EmbedRequest<'a> {
+    text: &'a str,
+    /// Only sent when overriding the service's default model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchEmbedRequest {
+    texts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbedResponse {
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+impl MicroserviceProvider {
+    pub fn new(client: Client, base_url: &str) -> Self {
+        Self { client, base_url: base_url.trim_end_matches('/').to_string(), retry: RetryPolicy::default() }
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for MicroserviceProvider {
+    fn name(&self) -> &'static str {
+        "microservice"
+    }
+
+    async fn embed(&self, text: &str, model: Option<&str>) -> GraphResult<Vec<f32>> {
+        let request = self.client
+            .post(format!("{}/embed", self.base_url))
+            .json(&EmbedRequest { text, model });
+        let response: EmbedResponse = send_json(request, &self.retry, "Embed").await?;
+        Ok(response.embedding)
+    }
+
+    async fn embed_batch(&self, texts: Vec<String>) -> GraphResult<Vec<Vec<f32>>> {
+        let request = self.client
+            .post(format!("{}/batch/embed", self.base_url))
+            .json(&BatchEmbedRequest { texts });
+        let response: BatchEmbedResponse = send_json(request, &self.retry, "Batch embed").await?;
+        Ok(response.embeddings)
+    }
+
+    async fn health_check(&self) -> bool {
+        match self.client.get(format!("{}/health", self.base_url)).send().await {
+            Ok(response) => response.status().is_success(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// OpenAI's `POST /embeddings`
+pub struct OpenAiProvider {
+    client: Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+    /// Requested output size (`dimensions`), for models that can shorten their vectors
+    dimensions: Option<usize>,
+    retry: RetryPolicy,
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAiRequest<'a> {
+    model: &'a str,
+    input: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl OpenAiProvider {
+    pub fn new(client: Client, base_url: &str, api_key: String, model: &str, dimensions: Option<usize>) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model: model.to_string(),
+            dimensions,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Native output size of OpenAI's embedding models
+    fn native_dimension(model: &str) -> Option<usize> {
+        match model {
+            "text-embedding-3-small" | "text-embedding-ada-002" => Some(1536),
+            "text-embedding-3-large" => Some(3072),
+            _ => None,
+        }
+    }
+
+    async fn request(&self, input: Vec<&str>, model: Option<&str>) -> GraphResult<Vec<Vec<f32>>> {
+        let expected = input.len();
+        let request = self.client
+            .post(format!("{}/embeddings", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&OpenAiRequest {
+                model: model.unwrap_or(&self.model),
+                input,
+                // Shortening only applies to the configured model
+                dimensions: self.dimensions.filter(|_| model.is_none()),
+            });
+        let mut response: OpenAiResponse = send_json(request, &self.retry, "OpenAI embed").await?;
+
+        // Entries carry their input position; don't rely on response order
+        response.data.sort_by_key(|entry| entry.index);
+        if response.data.len() != expected {
+            return Err(GraphError::Embedding(format!(
+                "OpenAI returned {} embeddings for {} inputs",
+                response.data.len(),
+                expected,
+            )));
+        }
+        Ok(response.data.into_iter().map(|entry| entry.embedding).collect())
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    async fn embed(&self, text: &str, model: Option<&str>) -> GraphResult<Vec<f32>> {
+        let mut embeddings = self.request(vec![text], model).await?;
+        Ok(embeddings.remove(0))
+    }
+
+    async fn embed_batch(&self, texts: Vec<String>) -> GraphResult<Vec<Vec<f32>>> {
+        self.request(texts.iter().map(String::as_str).collect(), None).await
+    }
+
+    async fn dimension(&self) -> GraphResult<usize> {
+        match self.dimensions.or_else(|| Self::native_dimension(&self.model)) {
+            Some(dimension) => Ok(dimension),
+            None => Ok(self.embed(DIMENSION_PROBE, None).await?.len()),
+        }
+    }
+
+    async fn health_check(&self) -> bool {
+        let request = self.client
+            .get(format!("{}/models/{}", self.base_url, self.model))
+            .bearer_auth(&self.api_key);
+        match request.send().await {
+            Ok(response) => response.status().is_success(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// A local model served by Ollama's `POST /api/embed`
+pub struct OllamaProvider {
+    client: Client,
+    base_url: String,
+    model: String,
+    retry: RetryPolicy,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaRequest<'a> {
+    model: &'a str,
+    input: Vec<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+impl OllamaProvider {
+    pub fn new(client: Client, base_url: &str, model: &str) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    async fn request(&self, input: Vec<&str>, model: Option<&str>) -> GraphResult<Vec<Vec<f32>>> {
+        let expected = input.len();
+        let request = self.client
+            .post(format!("{}/api/embed", self.base_url))
+            .json(&OllamaRequest { model: model.unwrap_or(&self.model), input });
+        let response: OllamaResponse = send_json(request, &self.retry, "Ollama embed").await?;
+        if response.embeddings.len() != expected {
+            return Err(GraphError::Embedding(format!(
+                "Ollama returned {} embeddings for {} inputs",
+                response.embeddings.len(),
+                expected,
+            )));
+        }
+        Ok(response.embeddings)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaProvider {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    async fn embed(&self, text: &str, model: Option<&str>) -> GraphResult<Vec<f32>> {
+        let mut embeddings = self.request(vec![text], model).await?;
+        Ok(embeddings.remove(0))
+    }
+
+    async fn embed_batch(&self, texts: Vec<String>) -> GraphResult<Vec<Vec<f32>>> {
+        self.request(texts.iter().map(String::as_str).collect(), None).await
+    }
+
+    async fn health_check(&self) -> bool {
+        match self.client.get(format!("{}/api/version", self.base_url)).send().await {
+            Ok(response) => response.status().is_success(),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_microservice_request_mapping() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embed"))
+            .and(body_json(serde_json::json!({ "text": "auth", "model": "e5-large" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "embedding": [0.5, 0.25] })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/batch/embed"))
+            .and(body_json(serde_json::json!({ "texts": ["a", "b"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "embeddings": [[1.0], [2.0]] })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = MicroserviceProvider::new(Client::new(), &server.uri());
+        assert_eq!(provider.embed("auth", Some("e5-large")).await.unwrap(), vec![0.5, 0.25]);
+        let batch = provider.embed_batch(vec!["a".to_string(), "b".to_string()]).await.unwrap();
+        assert_eq!(batch, vec![vec![1.0], vec![2.0]]);
+    }
+
+    #[tokio::test]
+    async fn test_openai_request_mapping() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .and(header("authorization", "Bearer sk-test"))
+            .and(body_json(serde_json::json!({
+                "model": "text-embedding-3-small",
+                "input": ["first", "second"],
+                "dimensions": 256,
+            })))
+            // Out of order on purpose; `index` decides the position
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [
+                    { "object": "embedding", "index": 1, "embedding": [0.0, 1.0] },
+                    { "object": "embedding", "index": 0, "embedding": [1.0, 0.0] },
+                ],
+                "model": "text-embedding-3-small",
+                "usage": { "prompt_tokens": 2, "total_tokens": 2 },
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .and(body_json(serde_json::json!({ "model": "text-embedding-3-large", "input": ["query"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{ "index": 0, "embedding": [0.5, 0.5, 0.5] }],
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = OpenAiProvider::new(
+            Client::new(),
+            &format!("{}/v1/", server.uri()),
+            "sk-test".to_string(),
+            "text-embedding-3-small",
+            Some(256),
+        );
+        let batch = provider.embed_batch(vec!["first".to_string(), "second".to_string()]).await.unwrap();
+        assert_eq!(batch, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        // A per-call model override isn't shortened
+        let single = provider.embed("query", Some("text-embedding-3-large")).await.unwrap();
+        assert_eq!(single.len(), 3);
+        assert_eq!(provider.dimension().await.unwrap(), 256);
+
+        let unshortened = OpenAiProvider::new(Client::new(), &server.uri(), String::new(), "text-embedding-3-large", None);
+        assert_eq!(unshortened.dimension().await.unwrap(), 3072);
+    }
+
+    #[tokio::test]
+    async fn test_ollama_request_mapping() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/embed"))
+            .and(body_json(serde_json::json!({ "model": "nomic-embed-text", "input": ["dimension probe"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model": "nomic-embed-text",
+                "embeddings": [[0.1, 0.2, 0.3, 0.4]],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/embed"))
+            .and(body_json(serde_json::json!({ "model": "nomic-embed-text", "input": ["a", "b"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "embeddings": [[1.0, 0.0, 0.0, 0.0]],
+            })))
+            .mount(&server)
+            .await;
+
+        let provider = OllamaProvider::new(Client::new(), &server.uri(), "nomic-embed-text");
+        assert_eq!(provider.dimension().await.unwrap(), 4);
+        // A short response is an error rather than silently misaligned vectors
+        let result = provider.embed_batch(vec!["a".to_string(), "b".to_string()]).await;
+        assert!(matches!(result, Err(GraphError::Embedding(message)) if message.contains("1 embeddings for 2 inputs")));
+    }
+
+    #[test]
+    fn test_check_config() {
+        let mut config = Config::test_default();
+        config.embedding_provider = "openai".to_string();
+        config.openai_api_key = None;
+        assert!(check_config(&config).unwrap_err().contains("OPENAI_API_KEY"));
+        config.openai_api_key = Some("sk-test".to_string());
+        assert!(check_config(&config).is_ok());
+        config.embedding_provider = "onnx".to_string();
+        assert!(check_config(&config).unwrap_err().contains("Unknown EMBEDDING_PROVIDER"));
+
+        config.embedding_provider = "local".to_string();
+        config.local_model_path = None;
+        if cfg!(feature = "local-embeddings") {
+            assert!(check_config(&config).unwrap_err().contains("LOCAL_MODEL_PATH"));
+            config.local_model_path = Some(std::env::temp_dir().to_string_lossy().into_owned());
+            assert!(check_config(&config).unwrap_err().contains("config.json"));
+        } else {
+            assert!(check_config(&config).unwrap_err().contains("--features local-embeddings"));
+        }
+    }
+}
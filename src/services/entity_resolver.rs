@@ -0,0 +1,283 @@
+//! Entity resolution across sources
+//!
+//! Entities whose names normalize to the same key (case and punctuation
+//! ignored, so `AuthService` matches `auth_service`) and whose types are
+//! compatible are merged under one `CanonicalEntity` per owner and repository
+//! (the `repo_name` property; entities without one match each other). Canonicals
+//! are stored in Postgres, or in memory with the in-memory graph backend. The
+//! graph gets a `CANONICAL` node with a `CANONICAL_OF` edge to each source
+//! entity. Resolving an entity again leaves its canonical and edge as they are.
+
+use crate::error::{GraphError, GraphResult};
+use crate::graph_db::GraphStore;
+use crate::models::{CanonicalEntity, Entity, EntityId, ExtractionMethod, RelationshipType};
+use chrono::Utc;
+use sqlx::PgPool;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Confidence of a merge between different (but compatible) entity types
+const COMPATIBLE_TYPE_CONFIDENCE: f32 = 0.8;
+
+/// Insert a canonical with one source entity, or add the entity to the existing one
+const UPSERT: &str = r#"
+    INSERT INTO canonical_entities (
+        id, entity_type, type_group, canonical_name, normalized_name, owner_id, repo_name,
+        merged_properties, source_entities, confidence_score, created_at, updated_at
+    )
+    VALUES ($1, $2, $3, $4, $5, $6, $10, $7, jsonb_build_array($8::jsonb), 1.0, now(), now())
+    ON CONFLICT (type_group, normalized_name, (COALESCE(owner_id, '')), repo_name) DO UPDATE SET
+        merged_properties = EXCLUDED.merged_properties || canonical_entities.merged_properties,
+        source_entities = CASE
+            WHEN canonical_entities.source_entities @> jsonb_build_array(jsonb_build_object('entity_id', $8::jsonb -> 'entity_id'))
+            THEN canonical_entities.source_entities
+            ELSE canonical_entities.source_entities || EXCLUDED.source_entities
+        END,
+        confidence_score = CASE
+            WHEN canonical_entities.entity_type = EXCLUDED.entity_type THEN canonical_entities.confidence_score
+            ELSE LEAST(canonical_entities.confidence_score, $9)
+        END,
+        updated_at = now()
+    RETURNING id, entity_type, canonical_name, owner_id, merged_properties, source_entities,
+        confidence_score, created_at, updated_at
+"#;
+
+/// In-memory canonical with its lookup key
+struct StoredCanonical {
+    type_group: &'static str,
+    normalized_name: String,
+    repo_name: String,
+    canonical: CanonicalEntity,
+}
+
+enum ResolverBackend {
+    Memory(Mutex<Vec<StoredCanonical>>),
+    Postgres(PgPool),
+}
+
+/// Merges entities from different sources into canonical entities
+pub struct EntityResolver {
+    backend: ResolverBackend,
+}
+
+impl EntityResolver {
+    /// Resolver keeping canonicals in process memory (lost on restart)
+    pub fn in_memory() -> Self {
+        Self { backend: ResolverBackend::Memory(Mutex::new(Vec::new())) }
+    }
+
+    /// Resolver backed by the `canonical_entities` table (see `migrations/`)
+    pub fn postgres(pool: PgPool) -> Self {
+        Self { backend: ResolverBackend::Postgres(pool) }
+    }
+
+    /// Attach `entity` to its canonical and link the two in the graph
+    ///
+    /// Returns `None` for names with nothing to match on (no letters or digits).
+    pub async fn resolve_and_link(
+        &self,
+        entity: &Entity,
+        graph: &dyn GraphStore,
+    ) -> GraphResult<Option<CanonicalEntity>> {
+        let Some(canonical) = self.resolve(entity).await? else {
+            return Ok(None);
+        };
+        graph.upsert_canonical_node(&canonical).await?;
+        graph.create_relationship(
+            &EntityId(canonical.id),
+            &EntityId(entity.id),
+            RelationshipType::CanonicalOf,
+            canonical.confidence_score,
+            ExtractionMethod::PatternMatch,
+            None,
+        ).await?;
+        Ok(Some(canonical))
+    }
+
+    /// Find or create the canonical for `entity` and record it as a source
+    pub async fn resolve(&self, entity: &Entity) -> GraphResult<Option<CanonicalEntity>> {
+        let normalized_name = normalize_name(&entity.name);
+        if normalized_name.is_empty() {
+            return Ok(None);
+        }
+        let entity_type = entity.get_entity_type()
+            .ok_or_else(|| GraphError::InvalidEntityType(entity.entity_type.clone()))?;
+        let type_group = entity_type.resolution_group();
+        let owner_id = entity.owner_id();
+        let repo_name = entity.repo_name().unwrap_or_default();
+        let member = serde_json::json!({
+            "entity_id": entity.id,
+            "entity_type": entity.entity_type,
+            "source": entity.source,
+            "source_id": entity.source_id,
+            "name": entity.name,
+        });
+
+        match &self.backend {
+            ResolverBackend::Memory(canonicals) => {
+                let mut canonicals = canonicals.lock().unwrap();
+                let existing = canonicals.iter_mut().find(|stored| {
+                    stored.type_group == type_group
+                        && stored.normalized_name == normalized_name
+                        && stored.canonical.owner_id == owner_id
+                        && stored.repo_name == repo_name
+                });
+                let canonical = match existing {
+                    Some(stored) if stored.canonical.source_entities.as_array()
+                        .is_some_and(|sources| sources.iter().any(|s| s["entity_id"] == member["entity_id"])) =>
+                    {
+                        stored.canonical.clone()
+                    }
+                    Some(stored) => {
+                        let canonical = &mut stored.canonical;
+                        if canonical.entity_type != entity.entity_type {
+                            canonical.confidence_score = canonical.confidence_score.min(COMPATIBLE_TYPE_CONFIDENCE);
+                        }
+                        if let (Some(merged), Some(properties)) =
+                            (canonical.merged_properties.as_object_mut(), entity.properties.as_object())
+                        {
+                            for (key, value) in properties {
+                                merged.entry(key.clone()).or_insert_with(|| value.clone());
+                            }
+                        }
+                        if let Some(sources) = canonical.source_entities.as_array_mut() {
+                            sources.push(member);
+                        }
+                        canonical.updated_at = Utc::now();
+                        canonical.clone()
+                    }
+                    None => {
+                        let canonical = CanonicalEntity {
+                            id: Uuid::new_v4(),
+                            entity_type: entity.entity_type.clone(),
+                            canonical_name: entity.name.clone(),
+                            owner_id,
+                            merged_properties: entity.properties.clone(),
+                            source_entities: serde_json::json!([member]),
+                            confidence_score: 1.0,
+                            created_at: Utc::now(),
+                            updated_at: Utc::now(),
+                        };
+                        canonicals.push(StoredCanonical {
+                            type_group,
+                            normalized_name,
+                            repo_name,
+                            canonical: canonical.clone(),
+                        });
+                        canonical
+                    }
+                };
+                Ok(Some(canonical))
+            }
+            ResolverBackend::Postgres(pool) => {
+                let canonical = sqlx::query_as::<_, CanonicalEntity>(UPSERT)
+                    .bind(Uuid::new_v4())
+                    .bind(&entity.entity_type)
+                    .bind(type_group)
+                    .bind(&entity.name)
+                    .bind(&normalized_name)
+                    .bind(&owner_id)
+                    .bind(&entity.properties)
+                    .bind(member)
+                    .bind(COMPATIBLE_TYPE_CONFIDENCE)
+                    .bind(&repo_name)
+                    .fetch_one(pool)
+                    .await?;
+                Ok(Some(canonical))
+            }
+        }
+    }
+}
+
+/// Lowercased letters and digits of a name, the key entities are matched on
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DataSource, EntityType};
+    use std::collections::HashMap;
+
+    fn entity(entity_type: EntityType, source: DataSource, name: &str, owner: &str) -> Entity {
+        let properties = HashMap::from([("owner_id".to_string(), serde_json::json!(owner))]);
+        Entity::new(entity_type, source, format!("{}#{}", owner, name), name.to_string(), properties)
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("AuthService"), "authservice");
+        assert_eq!(normalize_name("auth_service"), "authservice");
+        assert_eq!(normalize_name(" Auth-Service "), "authservice");
+        assert_eq!(normalize_name("--"), "");
+    }
+
+    #[tokio::test]
+    async fn test_compatible_types_merge_within_owner() {
+        let resolver = EntityResolver::in_memory();
+        let class = resolver
+            .resolve(&entity(EntityType::Class, DataSource::GitHub, "AuthService", "tenant-a"))
+            .await
+            .unwrap()
+            .unwrap();
+        let module = resolver
+            .resolve(&entity(EntityType::Module, DataSource::GitLab, "auth_service", "tenant-a"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(module.id, class.id);
+        assert_eq!(module.canonical_name, "AuthService");
+        assert_eq!(module.source_count(), 2);
+        assert_eq!(module.confidence_score, COMPATIBLE_TYPE_CONFIDENCE);
+
+        // Neither an incompatible type nor another owner's entity joins it
+        let function = resolver
+            .resolve(&entity(EntityType::Function, DataSource::GitHub, "authService", "tenant-a"))
+            .await
+            .unwrap()
+            .unwrap();
+        let other_owner = resolver
+            .resolve(&entity(EntityType::Class, DataSource::GitHub, "AuthService", "tenant-b"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(function.id, class.id);
+        assert_ne!(other_owner.id, class.id);
+    }
+    
+    #[tokio::test]
+    async fn test_same_name_in_other_repo_is_not_merged() {
+        let resolver = EntityResolver::in_memory();
+        let in_repo = |repo: &str| {
+            let mut entity = entity(EntityType::Class, DataSource::GitHub, "AuthService", "tenant-a");
+            entity.properties["repo_name"] = serde_json::json!(repo);
+            entity
+        };
+        let api = resolver.resolve(&in_repo("org/api")).await.unwrap().unwrap();
+        let web = resolver.resolve(&in_repo("org/web")).await.unwrap().unwrap();
+        let api_again = resolver.resolve(&in_repo("org/api")).await.unwrap().unwrap();
+        assert_ne!(web.id, api.id);
+        assert_eq!(api_again.id, api.id);
+        assert_eq!(api_again.source_count(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_resolving_again_is_idempotent() {
+        let resolver = EntityResolver::in_memory();
+        let graph = crate::graph_db::InMemoryGraph::new();
+        let entity = entity(EntityType::Class, DataSource::GitHub, "AuthService", "tenant-a");
+        graph.upsert_entity_node(&entity).await.unwrap();
+        
+        let first = resolver.resolve_and_link(&entity, &graph).await.unwrap().unwrap();
+        let second = resolver.resolve_and_link(&entity, &graph).await.unwrap().unwrap();
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.source_count(), 1);
+        
+        let statistics = graph.get_statistics().await.unwrap();
+        assert_eq!(statistics["by_relationship"]["CANONICAL_OF"], 1);
+    }
+}
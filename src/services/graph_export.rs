@@ -0,0 +1,259 @@
+//! Graph export as GraphML or JSON-LD
+//!
+//! `GET /api/graph/export` pages through the graph with
+//! `GraphStore::export_page` and writes each page as soon as it is fetched, so
+//! the whole graph is never held in memory. GraphML keys have to be declared
+//! before the graph, and node properties vary by label, so all of a node's
+//! properties go in one `properties` key as a JSON object. Labels, `name`, and
+//! the relationship fields get keys of their own so tools can style by them.
+
+use futures::{stream, Stream};
+use std::fmt::Write;
+use std::sync::Arc;
+
+use crate::error::GraphResult;
+use crate::graph_db::neo4j_client::ExportNode;
+use crate::graph_db::GraphStore;
+use crate::models::ExportFormat;
+
+/// Nodes fetched per export query
+pub const EXPORT_PAGE_SIZE: usize = 500;
+
+const GRAPHML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">
+  <key id="labels" for="node" attr.name="labels" attr.type="string"/>
+  <key id="name" for="node" attr.name="name" attr.type="string"/>
+  <key id="properties" for="node" attr.name="properties" attr.type="string"/>
+  <key id="type" for="edge" attr.name="type" attr.type="string"/>
+  <key id="confidence" for="edge" attr.name="confidence" attr.type="double"/>
+  <key id="extraction_method" for="edge" attr.name="extraction_method" attr.type="string"/>
+  <graph id="G" edgedefault="directed">
+"#;
+
+const GRAPHML_FOOTER: &str = "  </graph>\n</graphml>\n";
+
+/// Node ids are resolved against `@base`, property names against `@vocab`
+const JSONLD_HEADER: &str = r#"{"@context":{"@vocab":"urn:relation-graph:","@base":"urn:relation-graph:node:"},"@graph":["#;
+
+const JSONLD_FOOTER: &str = "\n]}\n";
+
+/// Export the graph (or one owner's part of it) as a stream of text pieces
+///
+/// The first page is fetched before returning, so an unreachable graph is
+/// reported as an error instead of an empty export. Later failures end the
+/// stream with an error.
+pub async fn export_graph(
+    graph: Arc<dyn GraphStore>,
+    format: ExportFormat,
+    owner_id: Option<String>,
+    include_embeddings: bool,
+) -> GraphResult<impl Stream<Item = GraphResult<String>>> {
+    let first = graph
+        .export_page(owner_id.as_deref(), None, EXPORT_PAGE_SIZE, include_embeddings)
+        .await?;
+    let export = GraphExport {
+        graph,
+        owner_id,
+        include_embeddings,
+        writer: ExportWriter::new(format),
+        next_page: Some(Ok(first)),
+    };
+
+    Ok(stream::unfold(export, |mut export| async move {
+        let piece = export.next_piece().await?;
+        Some((piece, export))
+    }))
+}
+
+struct GraphExport {
+    graph: Arc<dyn GraphStore>,
+    owner_id: Option<String>,
+    include_embeddings: bool,
+    writer: ExportWriter,
+    /// Fetched but not yet written; `None` once the footer or an error is out
+    next_page: Option<GraphResult<Vec<ExportNode>>>,
+}
+
+impl GraphExport {
+    async fn next_piece(&mut self) -> Option<GraphResult<String>> {
+        let page = match self.next_page.take()? {
+            Ok(page) => page,
+            Err(e) => return Some(Err(e)),
+        };
+        let mut out = String::new();
+        if self.writer.pages_written == 0 {
+            out.push_str(self.writer.header());
+        }
+        for node in &page {
+            self.writer.write_node(&mut out, node);
+        }
+        self.writer.pages_written += 1;
+
+        match page.last() {
+            Some(last) if page.len() == EXPORT_PAGE_SIZE => {
+                // Fetched now so the page written so far still goes out if this fails
+                let next = self.graph
+                    .export_page(self.owner_id.as_deref(), Some(&last.id), EXPORT_PAGE_SIZE, self.include_embeddings)
+                    .await
+                    .inspect_err(|e| tracing::error!("Graph export failed after {} nodes: {}", self.writer.nodes_written, e));
+                self.next_page = Some(next);
+            }
+            _ => out.push_str(self.writer.footer()),
+        }
+        Some(Ok(out))
+    }
+}
+
+/// Serializes nodes and their outgoing edges in one format
+pub struct ExportWriter {
+    format: ExportFormat,
+    nodes_written: usize,
+    edges_written: usize,
+    pages_written: usize,
+}
+
+impl ExportWriter {
+    pub fn new(format: ExportFormat) -> Self {
+        Self { format, nodes_written: 0, edges_written: 0, pages_written: 0 }
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self.format {
+            ExportFormat::GraphMl => GRAPHML_HEADER,
+            ExportFormat::JsonLd => JSONLD_HEADER,
+        }
+    }
+
+    pub fn footer(&self) -> &'static str {
+        match self.format {
+            ExportFormat::GraphMl => GRAPHML_FOOTER,
+            ExportFormat::JsonLd => JSONLD_FOOTER,
+        }
+    }
+
+    pub fn write_node(&mut self, out: &mut String, node: &ExportNode) {
+        match self.format {
+            ExportFormat::GraphMl => self.write_graphml_node(out, node),
+            ExportFormat::JsonLd => self.write_jsonld_node(out, node),
+        }
+        self.nodes_written += 1;
+    }
+
+    fn write_graphml_node(&mut self, out: &mut String, node: &ExportNode) {
+        let labels: String = node.labels.iter().map(|label| format!(":{}", label)).collect();
+        let _ = writeln!(out, r#"    <node id="{}">"#, xml_escape(&node.id));
+        let _ = writeln!(out, r#"      <data key="labels">{}</data>"#, xml_escape(&labels));
+        if let Some(name) = node.properties.get("name").and_then(|name| name.as_str()) {
+            let _ = writeln!(out, r#"      <data key="name">{}</data>"#, xml_escape(name));
+        }
+        let properties = serde_json::Value::Object(node.properties.clone()).to_string();
+        let _ = writeln!(out, r#"      <data key="properties">{}</data>"#, xml_escape(&properties));
+        out.push_str("    </node>\n");
+
+        for edge in &node.edges {
+            let _ = writeln!(
+                out,
+                r#"    <edge id="e{}" source="{}" target="{}">"#,
+                self.edges_written,
+                xml_escape(&node.id),
+                xml_escape(&edge.target),
+            );
+            let _ = writeln!(out, r#"      <data key="type">{}</data>"#, xml_escape(&edge.rel_type));
+            if let Some(confidence) = edge.confidence {
+                let _ = writeln!(out, r#"      <data key="confidence">{}</data>"#, confidence);
+            }
+            if let Some(method) = &edge.extraction_method {
+                let _ = writeln!(out, r#"      <data key="extraction_method">{}</data>"#, xml_escape(method));
+            }
+            out.push_str("    </edge>\n");
+            self.edges_written += 1;
+        }
+    }
+
+    fn write_jsonld_node(&mut self, out: &mut String, node: &ExportNode) {
+        let mut object = serde_json::Map::new();
+        object.insert("@id".to_string(), node.id.clone().into());
+        object.insert("@type".to_string(), node.labels.clone().into());
+        for (key, value) in &node.properties {
+            // Keys starting with `@` would be read as JSON-LD keywords
+            if !key.starts_with('@') {
+                object.insert(key.clone(), value.clone());
+            }
+        }
+        if !node.edges.is_empty() {
+            let relationships: Vec<serde_json::Value> = node.edges
+                .iter()
+                .map(|edge| serde_json::json!({
+                    "@type": edge.rel_type,
+                    "target": { "@id": edge.target },
+                    "confidence": edge.confidence,
+                    "extraction_method": edge.extraction_method,
+                }))
+                .collect();
+            self.edges_written += relationships.len();
+            object.insert("relationships".to_string(), relationships.into());
+        }
+
+        out.push_str(if self.nodes_written == 0 { "\n" } else { ",\n" });
+        out.push_str(&serde_json::Value::Object(object).to_string());
+    }
+}
+
+/// Escape text for XML content or a double-quoted attribute
+///
+/// Control characters XML 1.0 can't represent are dropped.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() && (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_db::InMemoryGraph;
+    use crate::models::{DataSource, Entity, EntityType};
+    use futures::StreamExt;
+    use std::collections::HashMap;
+    
+    #[tokio::test]
+    async fn test_export_spans_pages() {
+        let graph = Arc::new(InMemoryGraph::new());
+        for i in 0..EXPORT_PAGE_SIZE * 2 + 1 {
+            let properties = HashMap::from([("owner_id".to_string(), serde_json::json!("tenant-a"))]);
+            let name = format!("fn_{}", i);
+            let entity = Entity::new(EntityType::Function, DataSource::LocalFile, name.clone(), name, properties);
+            graph.upsert_entity_node(&entity).await.unwrap();
+        }
+        
+        let pieces: Vec<String> = export_graph(graph, ExportFormat::JsonLd, None, false)
+            .await
+            .unwrap()
+            .map(|piece| piece.unwrap())
+            .collect()
+            .await;
+        assert_eq!(pieces.len(), 3);
+        let document: serde_json::Value = serde_json::from_str(&pieces.concat()).unwrap();
+        let nodes = document["@graph"].as_array().unwrap();
+        assert_eq!(nodes.len(), EXPORT_PAGE_SIZE * 2 + 1);
+        let ids: std::collections::HashSet<_> = nodes.iter().map(|node| node["@id"].as_str().unwrap()).collect();
+        assert_eq!(ids.len(), nodes.len());
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(xml_escape(r#"a < b && "c" > 'd'"#), "a &lt; b &amp;&amp; &quot;c&quot; &gt; &apos;d&apos;");
+        assert_eq!(xml_escape("bell\u{7}\ttab"), "bell\ttab");
+    }
+}
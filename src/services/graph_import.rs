@@ -0,0 +1,406 @@
+//! Restoring a graph written by `GET /api/graph/export`
+//!
+//! The document is parsed and validated in full, then nodes and relationships
+//! are written in batches with their exported ids. A document that isn't GraphML or
+//! JSON-LD at all is rejected; individual nodes and edges that are malformed
+//! (no id or one that isn't a UUID, an unusable label or type, properties that
+//! aren't an object) are skipped and reported. The GraphML reader understands
+//! what the exporter writes and the common subset other tools produce: `key`
+//! declarations matched by `attr.name`, `node` and `edge` elements, and their
+//! `data`.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::error::{GraphError, GraphResult};
+use crate::graph_db::labels::is_valid_label;
+use crate::graph_db::neo4j_client::{ExportEdge, ExportNode};
+use crate::graph_db::GraphStore;
+use crate::models::{EntityId, ExportFormat, ImportMode, ImportResponse};
+
+/// Nodes or relationships written per batch
+pub const IMPORT_BATCH_SIZE: usize = 500;
+
+lazy_static! {
+    static ref XML_TAG: Regex = Regex::new(
+        r#"<(/?)([A-Za-z_][\w.:-]*)((?:\s+[\w.:-]+\s*=\s*(?:"[^"]*"|'[^']*'))*)\s*(/?)>"#
+    ).unwrap();
+    static ref XML_ATTRIBUTE: Regex = Regex::new(r#"([\w.:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+    /// Declarations, comments, and doctypes, which carry no graph data
+    static ref XML_PROLOG: Regex = Regex::new(r"(?s)<\?.*?\?>|<!--.*?-->|<!DOCTYPE[^>]*>").unwrap();
+    static ref XML_ENTITY: Regex = Regex::new(r"&(#x[0-9A-Fa-f]+|#[0-9]+|[a-z]+);").unwrap();
+}
+
+/// Nodes and `(source_id, edge)` relationships read from a document
+#[derive(Debug, Default)]
+pub struct ImportDocument {
+    pub nodes: Vec<ExportNode>,
+    pub edges: Vec<(String, ExportEdge)>,
+    /// Records that couldn't be read
+    pub errors: Vec<String>,
+}
+
+/// Parse an exported document
+///
+/// Returns `InvalidRequest` when the document as a whole can't be read.
+pub fn parse_document(format: ExportFormat, text: &str) -> GraphResult<ImportDocument> {
+    match format {
+        ExportFormat::GraphMl => parse_graphml(text),
+        ExportFormat::JsonLd => parse_jsonld(text),
+    }
+}
+
+/// Write a parsed document to the graph
+///
+/// Every record is validated before anything is written. In `replace` mode,
+/// which needs an owner, the document is written first and only then are
+/// the owner's nodes and relationships it doesn't contain deleted, so a
+/// failure partway leaves the old graph in place. With `owner_id` set, nodes
+/// must belong to that owner (nodes without an `owner_id` are given it) and
+/// ids held by another owner are not overwritten.
+pub async fn import_graph(
+    graph: Arc<dyn GraphStore>,
+    document: ImportDocument,
+    mode: ImportMode,
+    owner_id: Option<&str>,
+) -> GraphResult<ImportResponse> {
+    let mut response = ImportResponse {
+        mode,
+        errors: document.errors,
+        ..Default::default()
+    };
+
+    let mut nodes = Vec::with_capacity(document.nodes.len());
+    for mut node in document.nodes {
+        match check_node(&mut node, owner_id) {
+            Ok(()) => nodes.push(node),
+            Err(reason) => response.errors.push(format!("Node {}: {}", node.id, reason)),
+        }
+    }
+    let mut edges = Vec::with_capacity(document.edges.len());
+    for (source, edge) in document.edges {
+        if is_valid_label(&edge.rel_type) {
+            edges.push((source, edge));
+        } else {
+            response.errors.push(format!("Edge {} -> {}: invalid type '{}'", source, edge.target, edge.rel_type));
+        }
+    }
+
+    let replace_owner = match mode {
+        ImportMode::Replace => Some(owner_id.ok_or_else(|| {
+            GraphError::InvalidRequest("replace mode needs an owner_id (or X-Owner-Id header)".to_string())
+        })?),
+        ImportMode::Merge => None,
+    };
+
+    let mut kept_nodes = Vec::with_capacity(nodes.len());
+    for batch in nodes.chunks(IMPORT_BATCH_SIZE) {
+        let written: HashSet<String> = graph.import_nodes(batch, owner_id).await?.into_iter().collect();
+        response.nodes_imported += written.len();
+        for node in batch.iter().filter(|node| !written.contains(&node.id)) {
+            response.errors.push(format!("Node {}: id belongs to another owner", node.id));
+        }
+        kept_nodes.extend(written);
+    }
+
+    response.records_skipped = response.errors.len();
+
+    // Relationships aren't reported one by one; the store only returns a count
+    let mut edges_skipped = 0;
+    for batch in edges.chunks(IMPORT_BATCH_SIZE) {
+        let written = graph.import_relationships(batch, owner_id).await?;
+        response.relationships_imported += written;
+        edges_skipped += batch.len().saturating_sub(written);
+    }
+    if edges_skipped > 0 {
+        response.records_skipped += edges_skipped;
+        response.errors.push(format!(
+            "{} relationships skipped: an endpoint is missing or belongs to another owner",
+            edges_skipped,
+        ));
+    }
+
+    if let Some(owner_id) = replace_owner {
+        let kept_edges: Vec<String> = edges.iter()
+            .map(|(source, edge)| format!("{}|{}|{}", source, edge.rel_type, edge.target))
+            .collect();
+        response.nodes_deleted = graph.prune_owner_graph(owner_id, &kept_nodes, &kept_edges, IMPORT_BATCH_SIZE).await?;
+    }
+
+    Ok(response)
+}
+
+/// Validate a node's id, labels, and owner before it is written
+fn check_node(node: &mut ExportNode, owner_id: Option<&str>) -> Result<(), String> {
+    // Everything that reads nodes back expects entity ids
+    if EntityId::parse(&node.id).is_err() {
+        return Err("id is not a UUID".to_string());
+    }
+    if node.labels.is_empty() {
+        return Err("no labels".to_string());
+    }
+    if let Some(label) = node.labels.iter().find(|label| !is_valid_label(label)) {
+        return Err(format!("invalid label '{}'", label));
+    }
+    // Maps are written as JSON strings, but lists must hold plain values
+    for (key, value) in &node.properties {
+        if let serde_json::Value::Array(items) = value {
+            if items.iter().any(|item| item.is_array() || item.is_object()) {
+                return Err(format!("property '{}' is a list of lists or maps", key));
+            }
+        }
+    }
+    if let Some(owner_id) = owner_id {
+        match node.properties.get("owner_id") {
+            None | Some(serde_json::Value::Null) => {
+                node.properties.insert("owner_id".to_string(), owner_id.into());
+            }
+            Some(owner) if owner.as_str() == Some(owner_id) => {}
+            Some(owner) => return Err(format!("owner_id {} does not match {}", owner, owner_id)),
+        }
+    }
+    Ok(())
+}
+
+fn parse_jsonld(text: &str) -> GraphResult<ImportDocument> {
+    let document: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| GraphError::InvalidRequest(format!("Invalid JSON-LD: {}", e)))?;
+    let records = document
+        .get("@graph")
+        .and_then(|graph| graph.as_array())
+        .ok_or_else(|| GraphError::InvalidRequest("JSON-LD document has no @graph array".to_string()))?;
+
+    let mut parsed = ImportDocument::default();
+    for (index, record) in records.iter().enumerate() {
+        let Some(object) = record.as_object() else {
+            parsed.errors.push(format!("@graph[{}]: not an object", index));
+            continue;
+        };
+        let Some(id) = object.get("@id").and_then(|id| id.as_str()) else {
+            parsed.errors.push(format!("@graph[{}]: missing @id", index));
+            continue;
+        };
+        let labels = match object.get("@type") {
+            Some(serde_json::Value::String(label)) => vec![label.clone()],
+            Some(serde_json::Value::Array(labels)) => labels.iter().filter_map(|l| l.as_str().map(str::to_string)).collect(),
+            _ => Vec::new(),
+        };
+        let properties = object
+            .iter()
+            .filter(|(key, _)| !key.starts_with('@') && *key != "relationships")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        for relationship in object.get("relationships").and_then(|r| r.as_array()).into_iter().flatten() {
+            let target = relationship.pointer("/target/@id").and_then(|t| t.as_str());
+            let rel_type = relationship.get("@type").and_then(|t| t.as_str());
+            match (target, rel_type) {
+                (Some(target), Some(rel_type)) => parsed.edges.push((id.to_string(), ExportEdge {
+                    target: target.to_string(),
+                    rel_type: rel_type.to_string(),
+                    confidence: relationship.get("confidence").and_then(|c| c.as_f64()).map(|c| c as f32),
+                    extraction_method: relationship.get("extraction_method").and_then(|m| m.as_str()).map(str::to_string),
+                })),
+                _ => parsed.errors.push(format!("Node {}: relationship without @type or target", id)),
+            }
+        }
+        parsed.nodes.push(ExportNode { id: id.to_string(), labels, properties, edges: Vec::new() });
+    }
+    Ok(parsed)
+}
+
+/// A `node` or `edge` element being read, with its `data` by attribute name
+struct OpenElement {
+    is_node: bool,
+    attributes: HashMap<String, String>,
+    data: HashMap<String, String>,
+}
+
+fn parse_graphml(text: &str) -> GraphResult<ImportDocument> {
+    let invalid = |reason: &str| GraphError::InvalidRequest(format!("Invalid GraphML: {}", reason));
+    let body = XML_PROLOG.replace_all(text, "");
+
+    let mut parsed = ImportDocument::default();
+    // key id -> attr.name
+    let mut keys: HashMap<String, String> = HashMap::new();
+    let mut open_tags: Vec<String> = Vec::new();
+    let mut element: Option<OpenElement> = None;
+    let mut data_key: Option<String> = None;
+    let mut end = 0;
+
+    for tag in XML_TAG.captures_iter(&body) {
+        let whole = tag.get(0).unwrap();
+        let text = &body[end..whole.start()];
+        if text.contains('<') {
+            return Err(invalid("malformed markup"));
+        }
+        end = whole.end();
+        if let (Some(key), Some(element)) = (&data_key, element.as_mut()) {
+            element.data.entry(key.clone()).or_default().push_str(&xml_unescape(text));
+        }
+
+        let name = tag[2].to_string();
+        if !tag[1].is_empty() {
+            if open_tags.pop().as_deref() != Some(name.as_str()) {
+                return Err(invalid(&format!("unexpected </{}>", name)));
+            }
+            match name.as_str() {
+                "data" => data_key = None,
+                "node" | "edge" => {
+                    if let Some(finished) = element.take() {
+                        finish_element(finished, &mut parsed);
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let attributes: HashMap<String, String> = XML_ATTRIBUTE
+            .captures_iter(&tag[3])
+            .map(|a| (a[1].to_string(), xml_unescape(a.get(2).or(a.get(3)).map_or("", |v| v.as_str()))))
+            .collect();
+        if open_tags.is_empty() && name != "graphml" {
+            return Err(invalid("root element is not <graphml>"));
+        }
+        let self_closing = !tag[4].is_empty();
+        match name.as_str() {
+            "key" => {
+                if let Some(id) = attributes.get("id") {
+                    let attr_name = attributes.get("attr.name").unwrap_or(id);
+                    keys.insert(id.clone(), attr_name.clone());
+                }
+            }
+            "node" | "edge" => {
+                let opened = OpenElement { is_node: name == "node", attributes, data: HashMap::new() };
+                if self_closing {
+                    finish_element(opened, &mut parsed);
+                } else {
+                    element = Some(opened);
+                }
+            }
+            "data" if !self_closing => {
+                data_key = attributes.get("key").map(|key| keys.get(key).unwrap_or(key).clone());
+            }
+            _ => {}
+        }
+        if !self_closing {
+            open_tags.push(name);
+        }
+    }
+
+    if !open_tags.is_empty() {
+        return Err(invalid(&format!("<{}> is never closed", open_tags.last().unwrap())));
+    }
+    if end == 0 {
+        return Err(invalid("no <graphml> element"));
+    }
+    Ok(parsed)
+}
+
+fn finish_element(element: OpenElement, parsed: &mut ImportDocument) {
+    let OpenElement { is_node, attributes, mut data } = element;
+    if is_node {
+        let Some(id) = attributes.get("id") else {
+            parsed.errors.push("Node without an id".to_string());
+            return;
+        };
+        let mut properties = match data.remove("properties").map(|json| serde_json::from_str(&json)) {
+            Some(Ok(serde_json::Value::Object(properties))) => properties,
+            None => serde_json::Map::new(),
+            Some(_) => {
+                parsed.errors.push(format!("Node {}: properties are not a JSON object", id));
+                return;
+            }
+        };
+        let labels = data
+            .remove("labels")
+            .map(|labels| labels.split(':').map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default();
+        // Other tools' keys become plain properties
+        for (key, value) in data {
+            properties.entry(key).or_insert(value.into());
+        }
+        parsed.nodes.push(ExportNode { id: id.clone(), labels, properties, edges: Vec::new() });
+    } else {
+        let (Some(source), Some(target)) = (attributes.get("source"), attributes.get("target")) else {
+            parsed.errors.push("Edge without a source or target".to_string());
+            return;
+        };
+        let Some(rel_type) = data.remove("type").or_else(|| attributes.get("label").cloned()) else {
+            parsed.errors.push(format!("Edge {} -> {}: no type", source, target));
+            return;
+        };
+        let confidence = match data.remove("confidence").map(|c| c.trim().parse::<f32>()) {
+            Some(Ok(confidence)) => Some(confidence),
+            None => None,
+            Some(Err(_)) => {
+                parsed.errors.push(format!("Edge {} -> {}: confidence is not a number", source, target));
+                return;
+            }
+        };
+        parsed.edges.push((source.clone(), ExportEdge {
+            target: target.clone(),
+            rel_type: rel_type.trim().to_string(),
+            confidence,
+            extraction_method: data.remove("extraction_method"),
+        }));
+    }
+}
+
+/// Resolve the predefined and numeric character entities
+fn xml_unescape(text: &str) -> String {
+    XML_ENTITY
+        .replace_all(text, |entity: &regex::Captures| {
+            let name = &entity[1];
+            let decoded = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => name
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| name.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            decoded.map_or_else(|| entity[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphml_records_are_skipped_not_fatal() {
+        let xml = r#"<?xml version="1.0"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="labels" attr.type="string"/>
+  <key id="d1" for="edge" attr.name="type" attr.type="string"/>
+  <graph edgedefault="directed">
+    <node id="a"><data key="d0">:FUNCTION</data><data key="name">a &amp; b</data></node>
+    <node><data key="d0">:FUNCTION</data></node>
+    <node id="b"><data key="d0">:FUNCTION</data></node>
+    <edge source="a" target="b"><data key="d1">CALLS</data></edge>
+    <edge source="a" target="b"/>
+  </graph>
+</graphml>"#;
+        let document = parse_document(ExportFormat::GraphMl, xml).unwrap();
+        assert_eq!(document.nodes.len(), 2);
+        assert_eq!(document.nodes[0].labels, ["FUNCTION"]);
+        assert_eq!(document.nodes[0].properties["name"], "a & b");
+        assert_eq!(document.edges.len(), 1);
+        assert_eq!(document.edges[0].1.rel_type, "CALLS");
+        assert_eq!(document.errors.len(), 2);
+
+        assert!(parse_document(ExportFormat::GraphMl, "<graphml><graph></graphml>").is_err());
+        assert!(parse_document(ExportFormat::GraphMl, "<svg></svg>").is_err());
+        assert!(parse_document(ExportFormat::JsonLd, r#"{"nodes": []}"#).is_err());
+    }
+}
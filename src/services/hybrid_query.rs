@@ -4,17 +4,26 @@
 
 use crate::config::Config;
 use crate::error::{GraphError, GraphResult};
-use crate::graph_db::Neo4jClient;
+use crate::graph_db::GraphStore;
+use crate::graph_db::neo4j_client::{ChunkVectorSearch, Neighbor};
 use crate::models::{
     HybridSearchRequest, HybridSearchResponse, SearchOptions, SearchMetadata,
     ChunkResult, EntityResult, RelationshipResult, SemanticLink,
-    VectorSearchRequest, VectorSearchResponse,
+    ChunkExpansion, EntityId, SearchFrame,
+    EmbeddingSearchRequest, VectorSearchRequest, VectorSearchResponse,
     GraphSearchRequest, GraphSearchResponse,
-    RelationshipType,
+    NeighborQuery, RelationshipType, validate_direction,
 };
 use crate::services::EmbeddingClient;
+use crate::services::search_cache::SearchCache;
+use crate::utils::{cosine_similarity, language_filter, vector_score, PathGlob};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
+use tokio::sync::mpsc;
+use tracing::field::Empty;
+use tracing::Span;
 use uuid::Uuid;
 
 /// Hybrid query engine combining vector and graph search
@@ -22,82 +31,117 @@ use uuid::Uuid;
 /// Now uses Neo4j native vector indexes instead of separate Zilliz database.
 pub struct HybridQueryEngine {
     config: Config,
-    neo4j: Option<Arc<Neo4jClient>>,
-    embedding_client: EmbeddingClient,
+    graph: Option<Arc<dyn GraphStore>>,
+    embedding_client: Arc<EmbeddingClient>,
+    search_cache: Option<Arc<SearchCache>>,
 }
 
 impl HybridQueryEngine {
     pub fn new(
         config: Config,
-        neo4j: Option<Arc<Neo4jClient>>,
+        graph: Option<Arc<dyn GraphStore>>,
+        embedding_client: Arc<EmbeddingClient>,
     ) -> Self {
-        let embedding_client = EmbeddingClient::new(&config.embedding_service_url);
         Self {
             config,
-            neo4j,
+            graph,
             embedding_client,
+            search_cache: None,
         }
     }
     
+    /// Serve repeated identical `search` requests from `cache`
+    pub fn with_search_cache(mut self, cache: Option<Arc<SearchCache>>) -> Self {
+        self.search_cache = cache;
+        self
+    }
+    
     /// Execute a hybrid search combining vector and graph results
+    /// 
+    /// The `hybrid_search` span records per-phase timings (`embed_ms`,
+    /// `vector_ms`, `graph_ms`) and result counts as they become known.
+    /// With a search cache, an identical earlier request is returned as-is
+    /// with `metadata.cached` set.
+    #[tracing::instrument(
+        name = "hybrid_search",
+        skip_all,
+        fields(
+            owner_id = request.options.owner_id.as_deref(),
+            limit = request.options.limit,
+            embed_ms = Empty,
+            vector_ms = Empty,
+            graph_ms = Empty,
+            total_ms = Empty,
+            vector_results = Empty,
+            related_entities = Empty,
+            cross_source_links = Empty,
+            cached = Empty,
+        )
+    )]
     pub async fn search(&self, request: HybridSearchRequest) -> GraphResult<HybridSearchResponse> {
         let start_time = Instant::now();
-        let options = request.options;
+        let span = Span::current();
+        
+        let cache_key = self.search_cache.as_ref().map(|_| SearchCache::key(&request));
+        if let (Some(cache), Some(key)) = (&self.search_cache, &cache_key) {
+            if let Some(mut response) = cache.get(key).await {
+                response.metadata.execution_time_ms = elapsed_ms(start_time);
+                span.record("cached", true);
+                span.record("total_ms", response.metadata.execution_time_ms);
+                return Ok(response);
+            }
+        }
+        let mut options = request.options;
+        self.check_hops("graph_hops", options.graph_hops)?;
+        self.clamp_cross_source(&mut options);
+        validate_direction("cross_source_direction", &options.cross_source_direction)?;
+        parse_path_glob(&options)?;
+        parse_languages(&options)?;
         
-        // Step 1: Embed the query
+        // Step 1: Embed the query (with the requested model, if any)
+        let phase = Instant::now();
+        let (model, index_name) = self.resolve_embedding_model(options.embedding_model.as_deref())?;
         let query_embedding = self.embedding_client
-            .embed(&request.query)
-            .await
-            .map_err(|e| GraphError::Embedding(e.to_string()))?;
+            .embed_with_model(&request.query, model)
+            .await?;
+        span.record("embed_ms", elapsed_ms(phase));
         
         // Step 2: Vector search using Neo4j native vector index
+        let phase = Instant::now();
         let vector_results = self.vector_search_internal(
             query_embedding.clone(),
             &options,
+            index_name,
         ).await?;
+        let mut vector_results = self.pin_exact_matches(&request.query, &options, vector_results).await?;
+        if !options.cross_source_only {
+            self.attach_link_counts(&options, &mut vector_results).await?;
+        }
+        span.record("vector_ms", elapsed_ms(phase));
+        span.record("vector_results", vector_results.len());
         
-        // Step 3: Graph expansion for each vector hit
+        // Step 3: Graph expansion for each vector hit, or only their
+        // cross-source chunks in place of the hits
+        let phase = Instant::now();
+        let mut chunks = vector_results.clone();
         let mut related_entities = Vec::new();
         let mut relationships = Vec::new();
         let mut cross_source_links = Vec::new();
         
-        if let Some(neo4j) = &self.neo4j {
+        if options.cross_source_only {
+            (chunks, cross_source_links) = self.cross_source_only_results(&vector_results, &options).await?;
+            self.attach_link_counts(&options, &mut chunks).await?;
+        } else if let Some(graph) = self.graph.as_deref() {
             for chunk in &vector_results {
-                // Expand via graph traversal
-                let (entities, rels) = self.graph_expand(
-                    &chunk.chunk_id.to_string(),
-                    options.graph_hops,
-                    neo4j,
-                ).await?;
-                
-                related_entities.extend(entities);
-                relationships.extend(rels);
-                
-                // Get cross-source links if enabled
-                if options.include_cross_source {
-                    let cross_links = neo4j
-                        .get_cross_source_relationships(&chunk.chunk_id.to_string())
-                        .await?;
-                    
-                    for (target_id, target_name, rel_type, confidence) in cross_links {
-                        cross_source_links.push(SemanticLink {
-                            from_chunk_id: chunk.chunk_id,
-                            to_chunk_id: Uuid::parse_str(&target_id).unwrap_or_else(|_| Uuid::new_v4()),
-                            relationship_type: rel_type,
-                            confidence,
-                            extraction_methods: vec!["neo4j_vector_similarity".to_string()],
-                            similarity_score: Some(chunk.similarity_score),
-                            explicit_mention: None,
-                            temporal_distance_days: None,
-                            author_overlap: false,
-                        });
-                    }
-                }
+                let expansion = self.expand_chunk(chunk, &options, graph).await?;
+                related_entities.extend(expansion.entities);
+                relationships.extend(expansion.relationships);
+                cross_source_links.extend(expansion.cross_source_links);
             }
         }
         
         // Deduplicate entities and relationships
-        related_entities.sort_by(|a, b| a.id.cmp(&b.id));
+        related_entities.sort_by_key(|e| e.id);
         related_entities.dedup_by(|a, b| a.id == b.id);
         
         relationships.sort_by(|a, b| {
@@ -108,11 +152,15 @@ impl HybridQueryEngine {
             a.from_id == b.from_id && a.to_id == b.to_id && a.relationship_type == b.relationship_type
         });
         
-        let execution_time = start_time.elapsed().as_millis() as u64;
+        let execution_time = elapsed_ms(start_time);
         let cross_source_links_count = cross_source_links.len();
+        span.record("graph_ms", elapsed_ms(phase));
+        span.record("total_ms", execution_time);
+        span.record("related_entities", related_entities.len());
+        span.record("cross_source_links", cross_source_links_count);
         
-        Ok(HybridSearchResponse {
-            chunks: vector_results.clone(),
+        let response = HybridSearchResponse {
+            chunks,
             related_entities,
             relationships,
             cross_source_links,
@@ -123,26 +171,125 @@ impl HybridQueryEngine {
                 graph_hops_performed: options.graph_hops,
                 cross_source_links_count,
                 execution_time_ms: execution_time,
+                cached: false,
+                cached_at: None,
             },
-        })
+        };
+        if let (Some(cache), Some(key)) = (&self.search_cache, cache_key) {
+            cache.put(key, &response, Utc::now()).await;
+        }
+        
+        Ok(response)
+    }
+    
+    /// Hybrid search that sends results to `frames` as they're found
+    /// 
+    /// Emits one `Chunks` frame with the vector hits, an `Expansion` frame per
+    /// hit, then a `Metadata` frame. Expansion frames are not deduplicated
+    /// across hits. Stops early, without error, once the receiver is dropped.
+    #[tracing::instrument(
+        name = "hybrid_search_stream",
+        skip_all,
+        fields(owner_id = request.options.owner_id.as_deref(), limit = request.options.limit)
+    )]
+    pub async fn search_stream(
+        &self,
+        request: HybridSearchRequest,
+        frames: &mpsc::Sender<SearchFrame>,
+    ) -> GraphResult<()> {
+        let start_time = Instant::now();
+        let mut options = request.options;
+        self.check_hops("graph_hops", options.graph_hops)?;
+        self.clamp_cross_source(&mut options);
+        validate_direction("cross_source_direction", &options.cross_source_direction)?;
+        parse_path_glob(&options)?;
+        parse_languages(&options)?;
+        
+        let (model, index_name) = self.resolve_embedding_model(options.embedding_model.as_deref())?;
+        let query_embedding = self.embedding_client
+            .embed_with_model(&request.query, model)
+            .await?;
+        let vector_results = self.vector_search_internal(query_embedding, &options, index_name).await?;
+        let mut vector_results = self.pin_exact_matches(&request.query, &options, vector_results).await?;
+        let vector_results_count = vector_results.len();
+        
+        // Cross-source-only results replace the hits and aren't expanded
+        let mut cross_source_links_count = 0;
+        if options.cross_source_only {
+            let (mut chunks, links) = self.cross_source_only_results(&vector_results, &options).await?;
+            self.attach_link_counts(&options, &mut chunks).await?;
+            cross_source_links_count = links.len();
+            if frames.send(SearchFrame::Chunks { chunks }).await.is_err() {
+                return Ok(());
+            }
+        } else {
+            self.attach_link_counts(&options, &mut vector_results).await?;
+            if frames.send(SearchFrame::Chunks { chunks: vector_results.clone() }).await.is_err() {
+                return Ok(());
+            }
+        }
+        
+        let expand_graph = self.graph.as_deref().filter(|_| !options.cross_source_only);
+        if let Some(graph) = expand_graph {
+            for chunk in &vector_results {
+                let expansion = self.expand_chunk(chunk, &options, graph).await?;
+                cross_source_links_count += expansion.cross_source_links.len();
+                
+                let frame = SearchFrame::Expansion { chunk_id: chunk.chunk_id, expansion };
+                if frames.send(frame).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        
+        let _ = frames.send(SearchFrame::Metadata(SearchMetadata {
+            query: request.query,
+            vector_results_count,
+            graph_entities_count: 0,
+            graph_hops_performed: options.graph_hops,
+            cross_source_links_count,
+            execution_time_ms: elapsed_ms(start_time),
+            cached: false,
+            cached_at: None,
+        })).await;
+        
+        Ok(())
     }
     
     /// Vector-only search using Neo4j native vector index
+    #[tracing::instrument(
+        name = "vector_search",
+        skip_all,
+        fields(
+            owner_id = request.owner_id.as_deref(),
+            limit = request.limit,
+            embed_ms = Empty,
+            vector_ms = Empty,
+            vector_results = Empty,
+        )
+    )]
     pub async fn vector_search(&self, request: VectorSearchRequest) -> GraphResult<VectorSearchResponse> {
+        let span = Span::current();
+        let phase = Instant::now();
+        let (model, index_name) = self.resolve_embedding_model(request.embedding_model.as_deref())?;
         let query_embedding = self.embedding_client
-            .embed(&request.query)
-            .await
-            .map_err(|e| GraphError::Embedding(e.to_string()))?;
+            .embed_with_model(&request.query, model)
+            .await?;
+        span.record("embed_ms", elapsed_ms(phase));
         
         let options = SearchOptions {
             limit: request.limit,
             source_kind: request.source_kind.unwrap_or_else(|| "all".to_string()),
             source_types: request.source_types,
             owner_id: request.owner_id,
+            languages: request.languages,
             ..Default::default()
         };
         
-        let results = self.vector_search_internal(query_embedding, &options).await?;
+        let phase = Instant::now();
+        let results = self.vector_search_internal(query_embedding, &options, index_name).await?;
+        span.record("vector_ms", elapsed_ms(phase));
+        span.record("vector_results", results.len());
         
         Ok(VectorSearchResponse {
             results: results.clone(),
@@ -150,79 +297,423 @@ impl HybridQueryEngine {
         })
     }
     
+    /// Vector search with a caller-supplied embedding, skipping the embedding service
+    /// 
+    /// A vector of the wrong size is always rejected, whatever
+    /// `QUERY_DIMENSION_MISMATCH` says, since it can't be the caller's model drifting.
+    #[tracing::instrument(
+        name = "vector_search_by_embedding",
+        skip_all,
+        fields(
+            owner_id = request.owner_id.as_deref(),
+            limit = request.limit,
+            vector_ms = Empty,
+            vector_results = Empty,
+        )
+    )]
+    pub async fn vector_search_by_embedding(&self, request: EmbeddingSearchRequest) -> GraphResult<VectorSearchResponse> {
+        if request.embedding.len() != self.config.vector_dimension {
+            return Err(GraphError::InvalidRequest(format!(
+                "embedding has {} dimensions but {} expects {} (VECTOR_DIMENSION)",
+                request.embedding.len(),
+                DEFAULT_CHUNK_INDEX,
+                self.config.vector_dimension
+            )));
+        }
+        if request.embedding.iter().any(|value| !value.is_finite()) {
+            return Err(GraphError::InvalidRequest("embedding must only contain finite numbers".to_string()));
+        }
+        
+        let options = SearchOptions {
+            limit: request.limit,
+            source_kind: request.source_kind.unwrap_or_else(|| "all".to_string()),
+            source_types: request.source_types,
+            owner_id: request.owner_id,
+            languages: request.languages,
+            min_similarity: request.min_similarity,
+            ..Default::default()
+        };
+        
+        let span = Span::current();
+        let phase = Instant::now();
+        let results = self.vector_search_internal(request.embedding, &options, DEFAULT_CHUNK_INDEX).await?;
+        span.record("vector_ms", elapsed_ms(phase));
+        span.record("vector_results", results.len());
+        
+        Ok(VectorSearchResponse {
+            total_count: results.len(),
+            results,
+        })
+    }
+    
+    /// Model and chunk vector index to use for a search
+    /// 
+    /// `None` (or the default model's name) keeps the default model and
+    /// `chunk_embedding_idx`; other models must be listed in `EMBEDDING_MODEL_INDEXES`.
+    fn resolve_embedding_model<'a>(
+        &'a self,
+        requested: Option<&'a str>,
+    ) -> GraphResult<(Option<&'a str>, &'a str)> {
+        match requested {
+            None => Ok((None, DEFAULT_CHUNK_INDEX)),
+            Some(model) if model == self.config.embedding_model => Ok((None, DEFAULT_CHUNK_INDEX)),
+            Some(model) => match self.config.embedding_model_indexes.get(model) {
+                Some(index_name) => Ok((Some(model), index_name.as_str())),
+                None => {
+                    let mut configured: Vec<&str> = self.config.embedding_model_indexes
+                        .keys()
+                        .map(|m| m.as_str())
+                        .chain(std::iter::once(self.config.embedding_model.as_str()))
+                        .collect();
+                    configured.sort();
+                    Err(GraphError::InvalidRequest(format!(
+                        "Embedding model '{}' is not configured (available: {})",
+                        model,
+                        configured.join(", ")
+                    )))
+                }
+            },
+        }
+    }
+    
     /// Internal vector search using Neo4j native vector index
     async fn vector_search_internal(
         &self,
         query_embedding: Vec<f32>,
         options: &SearchOptions,
+        index_name: &str,
     ) -> GraphResult<Vec<ChunkResult>> {
-        let neo4j = self.neo4j.as_ref()
-            .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available for vector search".to_string()))?;
-        
-        // Use the chunk embedding index
-        let similar_nodes = neo4j.find_similar_nodes(
-            query_embedding,
-            "chunk_embedding_idx",
-            options.limit,
-            options.min_similarity,
-        ).await?;
+        let graph = self.graph.as_deref()
+            .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available for vector search".to_string()))?;
         
-        // Convert to ChunkResult (basic info from vector search)
-        Ok(similar_nodes
-            .into_iter()
-            .map(|(id, score)| ChunkResult {
-                chunk_id: Uuid::parse_str(&id).unwrap_or_else(|_| Uuid::new_v4()),
-                content: String::new(), // Would need additional query to get content
-                source_kind: options.source_kind.clone(),
-                source_type: String::new(),
-                file_path: None,
-                repo_name: None,
-                language: None,
-                heading_path: None,
-                similarity_score: score,
-            })
-            .collect())
+        let min_similarity = options.min_similarity.unwrap_or(self.config.hybrid_min_similarity);
+        for (name, value) in [
+            ("min_similarity", Some(min_similarity)),
+            ("mmr_lambda", options.mmr_lambda),
+            ("recency_boost", options.recency_boost),
+            ("cross_kind_weight", options.cross_kind_weight),
+        ] {
+            if let Some(value) = value.filter(|v| !(0.0..=1.0).contains(v)) {
+                return Err(GraphError::InvalidRequest(format!(
+                    "{} must be between 0 and 1, got {}",
+                    name, value
+                )));
+            }
+        }
+        
+        // Catch model drift before Neo4j rejects the query with an opaque error.
+        // Indexes for other models aren't checked; they can have any dimension.
+        if index_name == DEFAULT_CHUNK_INDEX && query_embedding.len() != self.config.vector_dimension {
+            let message = format!(
+                "Query embedding has {} dimensions but {} expects {} (check EMBEDDING_MODEL and VECTOR_DIMENSION)",
+                query_embedding.len(),
+                index_name,
+                self.config.vector_dimension
+            );
+            if self.config.query_dimension_mismatch == "skip" {
+                tracing::warn!("{}; skipping vector search", message);
+                return Ok(Vec::new());
+            }
+            return Err(GraphError::Embedding(message));
+        }
+        
+        let exclude_below_min_tokens = self.config.min_token_count > 0
+            && self.config.min_token_count_scope == "search";
+        let path_glob = parse_path_glob(options)?;
+        let languages = parse_languages(options)?;
+        let mut search = ChunkVectorSearch {
+            index_name,
+            limit: options.limit,
+            min_score: min_similarity,
+            exclude_below_min_tokens,
+            include_embeddings: false,
+            owner_id: options.owner_id.as_deref(),
+            source_types: options.source_types.as_deref(),
+            repo_name: options.repo_filter.as_deref(),
+            path_glob: path_glob.as_ref(),
+            languages: languages.as_deref(),
+        };
+        
+        let rerank = options.mmr_lambda.is_some() || options.recency_boost.is_some() || options.rerank_exact;
+        if rerank {
+            // Over-fetch so reranking has alternatives to promote
+            search.limit = options.limit * RERANK_CANDIDATE_FACTOR;
+            search.include_embeddings = options.mmr_lambda.is_some() || options.rerank_exact;
+        }
+        let exact_query = options.rerank_exact.then(|| query_embedding.clone());
+        let mut candidates = graph.find_similar_chunks(query_embedding, &search).await?;
+        if let Some(query) = exact_query {
+            rescore_exact(&mut candidates, &query);
+        }
+        // Backends filter on the threshold too; this guards against one that doesn't
+        candidates.retain(|chunk| chunk.raw_score.is_none_or(|score| score >= min_similarity));
+        if !rerank {
+            return Ok(candidates);
+        }
+        
+        if let Some(boost) = options.recency_boost {
+            apply_recency_boost(&mut candidates, boost, self.config.recency_half_life_days, Utc::now());
+        }
+        
+        Ok(match options.mmr_lambda {
+            Some(lambda) => mmr_rerank(candidates, lambda, options.limit),
+            None => {
+                candidates.truncate(options.limit);
+                candidates
+            }
+        })
+    }
+    
+    /// Put chunks exactly matching `query` ahead of the ranked results
+    /// 
+    /// Pinned chunks are flagged and scored 1.0, replace their own ranked
+    /// entry if they had one, and the combined list is cut back to `limit`.
+    /// A no-op unless `pin_exact_matches` is set.
+    async fn pin_exact_matches(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+        ranked: Vec<ChunkResult>,
+    ) -> GraphResult<Vec<ChunkResult>> {
+        let query = query.trim();
+        if !options.pin_exact_matches || query.is_empty() {
+            return Ok(ranked);
+        }
+        let graph = self.graph.as_deref()
+            .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available for exact match".to_string()))?;
+        
+        let path_glob = parse_path_glob(options)?;
+        let languages = parse_languages(options)?;
+        let search = ChunkVectorSearch {
+            index_name: DEFAULT_CHUNK_INDEX,
+            limit: options.limit,
+            min_score: 0.0,
+            exclude_below_min_tokens: self.config.min_token_count > 0
+                && self.config.min_token_count_scope == "search",
+            include_embeddings: false,
+            owner_id: options.owner_id.as_deref(),
+            source_types: options.source_types.as_deref(),
+            repo_name: options.repo_filter.as_deref(),
+            path_glob: path_glob.as_ref(),
+            languages: languages.as_deref(),
+        };
+        let mut results = graph.find_exact_chunks(query, &search).await?;
+        for chunk in &mut results {
+            // Keep the vector score of a chunk that was also retrieved by similarity
+            chunk.raw_score = ranked.iter()
+                .find(|hit| hit.chunk_id == chunk.chunk_id)
+                .and_then(|hit| hit.raw_score);
+            chunk.set_score(1.0);
+            chunk.pinned = true;
+        }
+        
+        let pinned: HashSet<Uuid> = results.iter().map(|chunk| chunk.chunk_id).collect();
+        results.extend(ranked.into_iter().filter(|chunk| !pinned.contains(&chunk.chunk_id)));
+        results.truncate(options.limit);
+        Ok(results)
+    }
+    
+    /// Set `cross_source_link_count` on each result when `include_link_counts` is on
+    /// 
+    /// One batched degree query for all results, so the badge doesn't need
+    /// `include_cross_source` to list every link.
+    async fn attach_link_counts(&self, options: &SearchOptions, results: &mut [ChunkResult]) -> GraphResult<()> {
+        let Some(graph) = self.graph.as_deref().filter(|_| options.include_link_counts) else {
+            return Ok(());
+        };
+        if results.is_empty() {
+            return Ok(());
+        }
+        
+        let chunk_ids: Vec<EntityId> = results.iter().map(|chunk| EntityId(chunk.chunk_id)).collect();
+        let counts = graph.count_cross_source_links(&chunk_ids, options.owner_id.as_deref()).await?;
+        for chunk in results {
+            chunk.cross_source_link_count = Some(counts.get(&EntityId(chunk.chunk_id)).copied().unwrap_or(0));
+        }
+        Ok(())
+    }
+    
+    /// Chunks linked to `hits` across sources, each once under its strongest link
+    /// 
+    /// Ranked by link confidence and cut to `limit`. The links name the hit
+    /// each chunk was reached from.
+    async fn cross_source_only_results(
+        &self,
+        hits: &[ChunkResult],
+        options: &SearchOptions,
+    ) -> GraphResult<(Vec<ChunkResult>, Vec<SemanticLink>)> {
+        let graph = self.graph.as_deref()
+            .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available for cross-source search".to_string()))?;
+        if hits.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        
+        let path_glob = parse_path_glob(options)?;
+        let languages = parse_languages(options)?;
+        let hit_ids: Vec<EntityId> = hits.iter().map(|hit| EntityId(hit.chunk_id)).collect();
+        let hit_scores: HashMap<Uuid, f32> = hits.iter()
+            .map(|hit| (hit.chunk_id, hit.similarity_score))
+            .collect();
+        
+        let mut seen = HashSet::new();
+        let mut chunks = Vec::new();
+        let mut links = Vec::new();
+        let linked_chunks = graph
+            .cross_source_chunks(&hit_ids, &options.cross_source_direction, options.max_cross_source_links, options.owner_id.as_deref())
+            .await?;
+        // Strongest link first, so the first time a chunk appears is its best link
+        for linked in linked_chunks {
+            if chunks.len() == options.limit {
+                break;
+            }
+            let path_matches = path_allowed(path_glob.as_ref(), linked.chunk.file_path.as_deref());
+            let language_matches = languages.as_ref().is_none_or(|languages| {
+                linked.chunk.language.as_deref().is_some_and(|language| languages.contains(&language.to_lowercase()))
+            });
+            if !path_matches || !language_matches || !seen.insert(linked.chunk.chunk_id) {
+                continue;
+            }
+            links.push(SemanticLink {
+                from_chunk_id: linked.hit_id.as_uuid(),
+                to_chunk_id: linked.chunk.chunk_id,
+                relationship_type: linked.relationship,
+                confidence: linked.confidence,
+                extraction_methods: vec!["neo4j_vector_similarity".to_string()],
+                similarity_score: hit_scores.get(&linked.hit_id.as_uuid()).copied(),
+                explicit_mention: None,
+                temporal_distance_days: None,
+                author_overlap: false,
+                direction: linked.direction,
+            });
+            chunks.push(linked.chunk);
+        }
+        Ok((chunks, links))
+    }
+    
+    /// Graph neighbors and cross-source links of a single vector hit
+    /// 
+    /// Traversal is skipped when `graph_hops` is 0 and link lookup when
+    /// `include_cross_source` is off.
+    async fn expand_chunk(
+        &self,
+        chunk: &ChunkResult,
+        options: &SearchOptions,
+        graph: &dyn GraphStore,
+    ) -> GraphResult<ChunkExpansion> {
+        let mut expansion = ChunkExpansion::default();
+        let chunk_id = EntityId(chunk.chunk_id);
+        let path_glob = parse_path_glob(options)?;
+        
+        if options.graph_hops > 0 {
+            let (entities, relationships) = self.graph_expand(
+                &chunk_id,
+                options.graph_hops,
+                graph,
+                options.owner_id.as_deref(),
+                self.cross_kind_weight(options),
+                path_glob.as_ref(),
+            ).await?;
+            expansion.entities = entities;
+            expansion.relationships = relationships;
+        }
+        
+        if options.includes_cross_source() {
+            let subgraph = graph
+                .get_cross_source_subgraph(
+                    &chunk_id,
+                    options.cross_source_hops.max(1),
+                    &options.cross_source_direction,
+                    options.cross_source_min_confidence,
+                    options.max_cross_source_links,
+                    options.owner_id.as_deref(),
+                )
+                .await?;
+            
+            for edge in subgraph.edges {
+                if !path_allowed(path_glob.as_ref(), edge.to_file_path.as_deref()) {
+                    continue;
+                }
+                expansion.cross_source_links.push(SemanticLink {
+                    from_chunk_id: edge.from_id.as_uuid(),
+                    to_chunk_id: edge.to_id.as_uuid(),
+                    relationship_type: edge.relationship,
+                    confidence: edge.confidence,
+                    extraction_methods: vec!["neo4j_vector_similarity".to_string()],
+                    // The hit's similarity only describes its own links
+                    similarity_score: (edge.depth == 1).then_some(chunk.similarity_score),
+                    explicit_mention: None,
+                    temporal_distance_days: None,
+                    author_overlap: false,
+                    direction: edge.direction,
+                });
+            }
+        }
+        
+        Ok(expansion)
+    }
+    
+    /// Weight for entities reached over cross-source edges, or `None` for full weight
+    /// 
+    /// Only single-kind searches are down-weighted, and only while the caller
+    /// hasn't explicitly asked for cross-source results.
+    fn cross_kind_weight(&self, options: &SearchOptions) -> Option<f32> {
+        if options.source_kind == "all" || options.include_cross_source == Some(true) {
+            return None;
+        }
+        Some(options.cross_kind_weight.unwrap_or(self.config.cross_kind_expansion_weight))
+            .filter(|weight| *weight < 1.0)
     }
     
     /// Graph expansion from a starting entity
+    /// 
+    /// With a `cross_kind_weight` of 0 cross-source edges aren't traversed;
+    /// otherwise relationships of those types have their confidence scaled by it.
+    /// Chunks reached outside `path_glob` are left out.
     async fn graph_expand(
         &self,
-        entity_id: &str,
+        entity_id: &EntityId,
         hops: usize,
-        neo4j: &Neo4jClient,
+        graph: &dyn GraphStore,
+        owner_id: Option<&str>,
+        cross_kind_weight: Option<f32>,
+        path_glob: Option<&PathGlob>,
     ) -> GraphResult<(Vec<EntityResult>, Vec<RelationshipResult>)> {
-        let neighbors = neo4j.get_neighbors(
+        let same_kind_types: Vec<RelationshipType> = RelationshipType::ALL
+            .into_iter()
+            .filter(|rel_type| !rel_type.is_cross_source())
+            .collect();
+        let relationship_types = (cross_kind_weight == Some(0.0)).then_some(same_kind_types.as_slice());
+        let mut neighbors = self.traverse(
+            graph,
             entity_id,
-            None, // All relationship types
+            relationship_types, // All types unless cross-source edges are excluded
             "both",
             hops,
+            owner_id,
         ).await?;
+        neighbors.retain(|neighbor| {
+            neighbor.entity_type != "CHUNK" || path_allowed(path_glob, neighbor.file_path.as_deref())
+        });
         
-        let entities: Vec<EntityResult> = neighbors
-            .iter()
-            .map(|(id, name, _rel_type, _conf)| EntityResult {
-                id: Uuid::parse_str(id).unwrap_or_else(|_| Uuid::new_v4()),
-                entity_type: "unknown".to_string(),
-                name: name.clone(),
-                source: "graph".to_string(),
-                properties: serde_json::json!({}),
-            })
-            .collect();
+        let from_id = entity_id.as_uuid();
+        let entities: Vec<EntityResult> = neighbors.iter().map(entity_result).collect();
         
         let relationships: Vec<RelationshipResult> = neighbors
             .iter()
-            .map(|(id, name, rel_type, conf)| {
-                let is_cross_source = RelationshipType::from_str(rel_type)
+            .map(|neighbor| {
+                let is_cross_source = RelationshipType::from_str(&neighbor.relationship)
                     .map(|rt| rt.is_cross_source())
                     .unwrap_or(false);
                 
+                let weight = if is_cross_source { cross_kind_weight.unwrap_or(1.0) } else { 1.0 };
+                
                 RelationshipResult {
-                    from_id: Uuid::parse_str(entity_id).unwrap_or_else(|_| Uuid::new_v4()),
-                    to_id: Uuid::parse_str(id).unwrap_or_else(|_| Uuid::new_v4()),
+                    from_id,
+                    to_id: neighbor.id.as_uuid(),
                     from_name: "source".to_string(),
-                    to_name: name.clone(),
-                    relationship_type: rel_type.clone(),
-                    confidence: *conf,
+                    to_name: neighbor.name.clone(),
+                    relationship_type: neighbor.relationship.clone(),
+                    confidence: neighbor.confidence * weight,
                     is_cross_source,
                 }
             })
@@ -231,53 +722,137 @@ impl HybridQueryEngine {
         Ok((entities, relationships))
     }
     
+    /// Reject traversals deeper than `MAX_GRAPH_HOPS`
+    /// 
+    /// Neo4j can't take a variable-length bound as a query parameter, so the
+    /// hop count is written into the Cypher and has to be checked here.
+    fn check_hops(&self, name: &str, hops: usize) -> GraphResult<()> {
+        if hops > self.config.max_graph_hops {
+            return Err(GraphError::InvalidRequest(format!(
+                "{} must be at most {} (MAX_GRAPH_HOPS), got {}",
+                name, self.config.max_graph_hops, hops
+            )));
+        }
+        Ok(())
+    }
+    
+    /// Hold cross-source expansion to the configured traversal caps
+    /// 
+    /// `max_cross_source_links` can't exceed `MAX_ENTITIES_PER_TRAVERSAL`, nor
+    /// `cross_source_hops` `MAX_GRAPH_HOPS`.
+    fn clamp_cross_source(&self, options: &mut SearchOptions) {
+        options.max_cross_source_links = options.max_cross_source_links.min(self.config.max_entities_per_traversal);
+        options.cross_source_hops = options.cross_source_hops.min(self.config.max_graph_hops);
+    }
+    
+    /// Neighbors within `hops`, strongest first, up to `MAX_ENTITIES_PER_TRAVERSAL`
+    async fn traverse(
+        &self,
+        graph: &dyn GraphStore,
+        entity_id: &EntityId,
+        relationship_types: Option<&[RelationshipType]>,
+        direction: &str,
+        hops: usize,
+        owner_id: Option<&str>,
+    ) -> GraphResult<Vec<Neighbor>> {
+        let query = NeighborQuery {
+            order_by_confidence: true,
+            limit: self.config.max_entities_per_traversal,
+            ..Default::default()
+        };
+        let neighbors = graph.get_neighbors(entity_id, relationship_types, direction, hops, &query, owner_id).await?;
+        if neighbors.len() >= query.limit {
+            tracing::warn!(
+                "Traversal from {} hit MAX_ENTITIES_PER_TRAVERSAL ({}); weaker neighbors were dropped",
+                entity_id, query.limit
+            );
+        }
+        Ok(neighbors)
+    }
+    
     /// Graph-only search
+    #[tracing::instrument(
+        name = "graph_search",
+        skip_all,
+        fields(
+            owner_id = request.owner_id.as_deref(),
+            start_entities = request.start_entities.len(),
+            hops = request.hops,
+            graph_ms = Empty,
+            entities = Empty,
+            paths = Empty,
+        )
+    )]
     pub async fn graph_search(&self, request: GraphSearchRequest) -> GraphResult<GraphSearchResponse> {
-        let neo4j = self.neo4j.as_ref()
-            .ok_or_else(|| GraphError::ServiceUnavailable("Neo4j not available".to_string()))?;
+        let phase = Instant::now();
+        let graph = self.graph.as_deref()
+            .ok_or_else(|| GraphError::ServiceUnavailable("Graph store not available".to_string()))?;
+        self.check_hops("hops", request.hops)?;
+        
+        let relationship_types = request.relationship_types
+            .as_deref()
+            .map(RelationshipType::parse_all)
+            .transpose()?;
         
         let mut all_entities = Vec::new();
         let mut all_relationships = Vec::new();
-        let all_paths = Vec::new();
+        let mut all_paths = Vec::new();
         
         for start_entity in &request.start_entities {
-            let neighbors = neo4j.get_neighbors(
-                start_entity,
-                None,
+            let start_id = EntityId::parse(start_entity)?;
+            let neighbors = self.traverse(
+                graph,
+                &start_id,
+                relationship_types.as_deref(),
                 &request.direction,
                 request.hops,
+                request.owner_id.as_deref(),
             ).await?;
             
-            for (id, name, rel_type, conf) in neighbors {
-                all_entities.push(EntityResult {
-                    id: Uuid::parse_str(&id).unwrap_or_else(|_| Uuid::new_v4()),
-                    entity_type: "unknown".to_string(),
-                    name: name.clone(),
-                    source: "graph".to_string(),
-                    properties: serde_json::json!({}),
-                });
+            if request.include_paths && all_paths.len() < request.limit {
+                let paths = graph.find_paths(
+                    &start_id,
+                    relationship_types.as_deref(),
+                    &request.direction,
+                    request.hops,
+                    request.limit - all_paths.len(),
+                    request.owner_id.as_deref(),
+                ).await?;
+                all_paths.extend(paths);
+            }
+            
+            let from_id = start_id.as_uuid();
+            for neighbor in neighbors {
+                all_entities.push(entity_result(&neighbor));
                 
-                let is_cross_source = RelationshipType::from_str(&rel_type)
+                let is_cross_source = RelationshipType::from_str(&neighbor.relationship)
                     .map(|rt| rt.is_cross_source())
                     .unwrap_or(false);
                 
                 all_relationships.push(RelationshipResult {
-                    from_id: Uuid::parse_str(start_entity).unwrap_or_else(|_| Uuid::new_v4()),
-                    to_id: Uuid::parse_str(&id).unwrap_or_else(|_| Uuid::new_v4()),
+                    from_id,
+                    to_id: neighbor.id.as_uuid(),
                     from_name: start_entity.clone(),
-                    to_name: name,
-                    relationship_type: rel_type,
-                    confidence: conf,
+                    to_name: neighbor.name,
+                    relationship_type: neighbor.relationship,
+                    confidence: neighbor.confidence,
                     is_cross_source,
                 });
             }
         }
         
-        // Deduplicate
-        all_entities.sort_by(|a, b| a.id.cmp(&b.id));
+        // Deduplicate, keeping each entity's strongest relationship, then
+        // rank by it so `limit` drops the weakest neighbors
+        all_entities.sort_by(|a, b| a.id.cmp(&b.id).then(b.relevance.total_cmp(&a.relevance)));
         all_entities.dedup_by(|a, b| a.id == b.id);
+        all_entities.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
         all_entities.truncate(request.limit);
         
+        let span = Span::current();
+        span.record("graph_ms", elapsed_ms(phase));
+        span.record("entities", all_entities.len());
+        span.record("paths", all_paths.len());
+        
         Ok(GraphSearchResponse {
             entities: all_entities,
             relationships: all_relationships,
@@ -285,3 +860,1062 @@ impl HybridQueryEngine {
         })
     }
 }
+
+/// Milliseconds since `start`, for span fields and response metadata
+fn elapsed_ms(start: Instant) -> u64 {
+    start.elapsed().as_millis() as u64
+}
+
+/// The compiled `path_glob` of a search, if it has one
+fn parse_path_glob(options: &SearchOptions) -> GraphResult<Option<PathGlob>> {
+    options.path_glob.as_deref().map(PathGlob::parse).transpose()
+}
+
+/// Whether a chunk at `file_path` passes `path_glob`; without a path it only passes when there is no glob
+fn path_allowed(path_glob: Option<&PathGlob>, file_path: Option<&str>) -> bool {
+    path_glob.is_none_or(|glob| file_path.is_some_and(|path| glob.matches(path)))
+}
+
+/// The stored language names a search's `languages` filter accepts, if it has one
+fn parse_languages(options: &SearchOptions) -> GraphResult<Option<Vec<String>>> {
+    options.languages.as_deref().map(language_filter).transpose()
+}
+
+/// Vector index for chunks embedded with the default model
+const DEFAULT_CHUNK_INDEX: &str = "chunk_embedding_idx";
+
+/// Candidates fetched per requested result when MMR, exact, or recency reranking is on
+const RERANK_CANDIDATE_FACTOR: usize = 4;
+
+/// Replace index scores with exact cosine scores on the same 0..=1 scale, then re-sort
+/// 
+/// Candidates returned without an embedding keep their index score.
+fn rescore_exact(candidates: &mut [ChunkResult], query: &[f32]) {
+    let mut missing = 0;
+    for chunk in candidates.iter_mut() {
+        match chunk.embedding.as_deref() {
+            Some(embedding) if embedding.len() == query.len() => {
+                let score = vector_score(query, embedding);
+                chunk.raw_score = Some(score);
+                chunk.set_score(score);
+            }
+            _ => missing += 1,
+        }
+    }
+    if missing > 0 {
+        tracing::debug!("{} of {} candidates had no usable embedding; kept their index scores", missing, candidates.len());
+    }
+    candidates.sort_by(|a, b| b.final_score.total_cmp(&a.final_score));
+}
+
+/// Multiply scores by a bonus that halves every `half_life_days`, then re-sort
+/// 
+/// Chunks with no known date keep their score.
+fn apply_recency_boost(chunks: &mut [ChunkResult], boost: f32, half_life_days: f32, now: DateTime<Utc>) {
+    for chunk in chunks.iter_mut() {
+        let Some(last_modified) = chunk.last_modified else {
+            continue;
+        };
+        let age_days = ((now - last_modified).num_seconds().max(0) as f32) / 86_400.0;
+        chunk.set_score(chunk.final_score * (1.0 + boost * 0.5_f32.powf(age_days / half_life_days)));
+    }
+    chunks.sort_by(|a, b| b.final_score.total_cmp(&a.final_score));
+}
+
+/// Select `k` results by maximal marginal relevance
+/// 
+/// Each pick maximizes `lambda * relevance - (1 - lambda) * max_similarity`,
+/// where `max_similarity` is the cosine similarity to the closest result
+/// already picked. Candidates without an embedding are never penalized.
+fn mmr_rerank(mut candidates: Vec<ChunkResult>, lambda: f32, k: usize) -> Vec<ChunkResult> {
+    let mut selected: Vec<ChunkResult> = Vec::with_capacity(k.min(candidates.len()));
+    
+    while selected.len() < k && !candidates.is_empty() {
+        let mmr_score = |candidate: &ChunkResult| {
+            let redundancy = candidate.embedding.as_deref()
+                .map(|embedding| {
+                    selected.iter()
+                        .filter_map(|s| s.embedding.as_deref())
+                        .map(|other| cosine_similarity(embedding, other))
+                        .fold(0.0_f32, f32::max)
+                })
+                .unwrap_or(0.0);
+            lambda * candidate.final_score - (1.0 - lambda) * redundancy
+        };
+        
+        let best = candidates.iter()
+            .enumerate()
+            .map(|(i, c)| (i, mmr_score(c)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        selected.push(candidates.remove(best));
+    }
+    
+    selected
+}
+
+/// Entity result for a traversal neighbor
+fn entity_result(neighbor: &Neighbor) -> EntityResult {
+    EntityResult {
+        id: neighbor.id.as_uuid(),
+        entity_type: neighbor.entity_type.clone(),
+        name: neighbor.name.clone(),
+        source: neighbor.source.clone().unwrap_or_else(|| "graph".to_string()),
+        properties: neighbor.properties.clone(),
+        relevance: neighbor.confidence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn chunk_result(score: f32, embedding: Vec<f32>) -> ChunkResult {
+        ChunkResult {
+            chunk_id: Uuid::new_v4(),
+            content: String::new(),
+            source_kind: "code".to_string(),
+            source_type: "github".to_string(),
+            file_path: None,
+            repo_name: None,
+            language: None,
+            heading_path: None,
+            summary: None,
+            similarity_score: score,
+            raw_score: Some(score),
+            final_score: score,
+            last_modified: None,
+            pinned: false,
+            cross_source_link_count: None,
+            embedding: Some(embedding),
+        }
+    }
+    
+    #[test]
+    fn test_embedding_model_override_must_be_configured() {
+        let mut config = Config::test_default();
+        config.embedding_model = "default-model".to_string();
+        config.embedding_model_indexes = std::collections::HashMap::from([
+            ("e5-large".to_string(), "chunk_e5_idx".to_string()),
+        ]);
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, None, embedding_client);
+        
+        assert_eq!(engine.resolve_embedding_model(None).unwrap(), (None, DEFAULT_CHUNK_INDEX));
+        assert_eq!(
+            engine.resolve_embedding_model(Some("default-model")).unwrap(),
+            (None, DEFAULT_CHUNK_INDEX)
+        );
+        assert_eq!(
+            engine.resolve_embedding_model(Some("e5-large")).unwrap(),
+            (Some("e5-large"), "chunk_e5_idx")
+        );
+        assert!(matches!(
+            engine.resolve_embedding_model(Some("unknown")),
+            Err(GraphError::InvalidRequest(_))
+        ));
+    }
+    
+    #[test]
+    fn test_mmr_promotes_distinct_result() {
+        let candidates = || vec![
+            chunk_result(0.95, vec![1.0, 0.0, 0.0]),
+            chunk_result(0.94, vec![0.99, 0.01, 0.0]),
+            chunk_result(0.93, vec![0.98, 0.02, 0.0]),
+            chunk_result(0.80, vec![0.0, 1.0, 0.0]),
+        ];
+        let distinct = |results: &[ChunkResult]| {
+            results.iter().position(|r| r.similarity_score == 0.80)
+        };
+        
+        // Diversity-leaning lambda pulls the distinct chunk up to second place
+        let diverse = mmr_rerank(candidates(), 0.3, 2);
+        assert_eq!(diverse[0].similarity_score, 0.95);
+        assert_eq!(distinct(&diverse), Some(1));
+        
+        // Lambda 1.0 is plain relevance order
+        let relevance = mmr_rerank(candidates(), 1.0, 2);
+        assert_eq!(distinct(&relevance), None);
+        assert_eq!(relevance[1].similarity_score, 0.94);
+    }
+    
+    #[test]
+    fn test_exact_rerank_fixes_approximate_order() {
+        let query = vec![1.0, 0.0];
+        // The index ranked the worse match first
+        let mut candidates = vec![
+            chunk_result(0.95, vec![0.0, 1.0]),
+            chunk_result(0.90, vec![1.0, 0.1]),
+            chunk_result(0.85, vec![1.0, 1.0]),
+        ];
+        // A candidate without its embedding keeps the index score
+        let mut no_embedding = chunk_result(0.8, Vec::new());
+        no_embedding.embedding = None;
+        candidates.push(no_embedding);
+        let ids: Vec<Uuid> = candidates.iter().map(|c| c.chunk_id).collect();
+        
+        rescore_exact(&mut candidates, &query);
+        let order: Vec<Uuid> = candidates.iter().map(|c| c.chunk_id).collect();
+        // Orthogonal vectors score 0.5, below the unscored candidate's 0.8
+        assert_eq!(order, vec![ids[1], ids[2], ids[3], ids[0]]);
+        assert!((candidates[0].raw_score.unwrap() - vector_score(&query, &[1.0, 0.1])).abs() < 1e-6);
+        assert_eq!(candidates[0].raw_score, Some(candidates[0].final_score));
+        assert_eq!(candidates[2].final_score, 0.8);
+        assert!((candidates[3].final_score - 0.5).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_recency_boost_breaks_ties_without_overriding_relevance() {
+        let now = Utc::now();
+        let dated = |score: f32, age_days: i64| ChunkResult {
+            last_modified: Some(now - chrono::Duration::days(age_days)),
+            ..chunk_result(score, vec![])
+        };
+        let mut chunks = vec![
+            dated(0.90, 365),
+            dated(0.88, 0),
+            dated(0.50, 0),
+            chunk_result(0.89, vec![]),
+        ];
+        
+        apply_recency_boost(&mut chunks, 0.2, 30.0, now);
+        
+        // The fresh near-tie moves to the top, the weak fresh match stays last
+        assert!((chunks[0].similarity_score - 0.88 * 1.2).abs() < 1e-5);
+        assert!((chunks[1].similarity_score - 0.90).abs() < 1e-3);
+        assert_eq!(chunks[2].similarity_score, 0.89);
+        assert!((chunks[3].similarity_score - 0.60).abs() < 1e-5);
+        
+        // One half-life halves the bonus
+        let mut month_old = vec![dated(0.5, 30)];
+        apply_recency_boost(&mut month_old, 0.2, 30.0, now);
+        assert!((month_old[0].similarity_score - 0.55).abs() < 1e-5);
+        assert_eq!(month_old[0].final_score, month_old[0].similarity_score);
+        assert_eq!(month_old[0].raw_score, Some(0.5));
+    }
+    
+    #[test]
+    fn test_graph_search_paths_are_opt_in() {
+        let request: GraphSearchRequest = serde_json::from_value(serde_json::json!({
+            "start_entities": ["a"]
+        })).unwrap();
+        assert!(!request.include_paths);
+        
+        let request: GraphSearchRequest = serde_json::from_value(serde_json::json!({
+            "start_entities": ["a"],
+            "include_paths": true
+        })).unwrap();
+        assert!(request.include_paths);
+    }
+    
+    #[tokio::test]
+    async fn test_graph_search_reports_neighbor_type_and_properties() {
+        use crate::graph_db::InMemoryGraph;
+        use crate::models::{DataSource, Entity, EntityType, ExtractionMethod};
+        
+        let graph = InMemoryGraph::new();
+        let entity = |name: &str, entity_type| Entity::new(
+            entity_type,
+            DataSource::GitHub,
+            format!("auth.rs#{}", name),
+            name.to_string(),
+            std::collections::HashMap::from([("line".to_string(), serde_json::json!(12))]),
+        );
+        let (module, function) = (entity("auth", EntityType::Module), entity("login", EntityType::Function));
+        graph.upsert_entity_node(&module).await.unwrap();
+        graph.upsert_entity_node(&function).await.unwrap();
+        graph.create_relationship(
+            &EntityId(module.id), &EntityId(function.id), RelationshipType::Contains, 0.8, ExtractionMethod::Manual, None,
+        ).await.unwrap();
+        
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let request: GraphSearchRequest = serde_json::from_value(serde_json::json!({
+            "start_entities": [module.id.to_string()]
+        })).unwrap();
+        let response = engine.graph_search(request).await.unwrap();
+        
+        assert_eq!(response.entities.len(), 1);
+        assert_eq!(response.entities[0].entity_type, "FUNCTION");
+        assert_eq!(response.entities[0].source, "github");
+        assert_eq!(response.entities[0].properties["line"], 12);
+        assert_eq!(response.entities[0].relevance, 0.8);
+    }
+    
+    #[tokio::test]
+    async fn test_graph_search_skips_non_uuid_node_ids() {
+        use crate::graph_db::neo4j_client::{ExportEdge, ExportNode};
+        use crate::graph_db::InMemoryGraph;
+        use crate::models::{DataSource, Entity, EntityType};
+        
+        // A node written by another tool, with an id that isn't a UUID
+        let graph = InMemoryGraph::new();
+        let module = Entity::new(EntityType::Module, DataSource::GitHub, "auth.rs".to_string(), "auth".to_string(), Default::default());
+        graph.upsert_entity_node(&module).await.unwrap();
+        let mut properties = serde_json::Map::new();
+        properties.insert("name".to_string(), serde_json::json!("legacy"));
+        let legacy = ExportNode { id: "n0".to_string(), labels: vec!["Function".to_string()], properties, edges: vec![] };
+        graph.import_nodes(&[legacy], None).await.unwrap();
+        let edge = ExportEdge { target: "n0".to_string(), rel_type: "CALLS".to_string(), confidence: Some(0.9), extraction_method: None };
+        graph.import_relationships(&[(module.id.to_string(), edge)], None).await.unwrap();
+        
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let request = |start: String| serde_json::from_value::<GraphSearchRequest>(serde_json::json!({
+            "start_entities": [start]
+        })).unwrap();
+        
+        // The stored node is left out of the traversal rather than failing it
+        let response = engine.graph_search(request(module.id.to_string())).await.unwrap();
+        assert!(response.entities.iter().all(|entity| entity.name != "legacy"));
+        // A non-UUID start id is the caller's mistake
+        let result = engine.graph_search(request("n0".to_string())).await;
+        assert!(matches!(result, Err(GraphError::InvalidRequest(message)) if message.contains("'n0'")));
+    }
+    
+    #[tokio::test]
+    async fn test_graph_search_enforces_hop_and_entity_caps() {
+        use crate::graph_db::InMemoryGraph;
+        use crate::models::{DataSource, Entity, EntityType, ExtractionMethod};
+        
+        let graph = InMemoryGraph::new();
+        let entity = |name: &str| Entity::new(
+            EntityType::Function,
+            DataSource::GitHub,
+            format!("auth.rs#{}", name),
+            name.to_string(),
+            std::collections::HashMap::new(),
+        );
+        let module = entity("auth");
+        graph.upsert_entity_node(&module).await.unwrap();
+        for (name, confidence) in [("weak", 0.3), ("strong", 0.95), ("middling", 0.6), ("strongish", 0.9)] {
+            let function = entity(name);
+            graph.upsert_entity_node(&function).await.unwrap();
+            graph.create_relationship(
+                &EntityId(module.id), &EntityId(function.id), RelationshipType::Calls, confidence, ExtractionMethod::Manual, None,
+            ).await.unwrap();
+        }
+        
+        let mut config = Config::test_default();
+        config.max_graph_hops = 2;
+        config.max_entities_per_traversal = 2;
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let request = |hops: usize| serde_json::from_value::<GraphSearchRequest>(serde_json::json!({
+            "start_entities": [module.id.to_string()],
+            "hops": hops,
+        })).unwrap();
+        
+        // The traversal keeps only the strongest neighbors, whatever `limit` allows
+        let response = engine.graph_search(request(2)).await.unwrap();
+        let names: Vec<&str> = response.entities.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["strong", "strongish"]);
+        
+        let too_deep = engine.graph_search(request(3)).await;
+        assert!(
+            matches!(&too_deep, Err(GraphError::InvalidRequest(message)) if message == "hops must be at most 2 (MAX_GRAPH_HOPS), got 3"),
+            "{:?}",
+            too_deep.err()
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_graph_search_keeps_strongest_neighbors() {
+        use crate::graph_db::InMemoryGraph;
+        use crate::models::{DataSource, Entity, EntityType, ExtractionMethod};
+        
+        let graph = InMemoryGraph::new();
+        let entity = |name: &str| Entity::new(
+            EntityType::Function,
+            DataSource::GitHub,
+            format!("auth.rs#{}", name),
+            name.to_string(),
+            std::collections::HashMap::new(),
+        );
+        let module = entity("auth");
+        graph.upsert_entity_node(&module).await.unwrap();
+        for (name, confidence) in [("weak", 0.3), ("strong", 0.95), ("middling", 0.6), ("strongish", 0.9)] {
+            let function = entity(name);
+            graph.upsert_entity_node(&function).await.unwrap();
+            graph.create_relationship(
+                &EntityId(module.id), &EntityId(function.id), RelationshipType::Calls, confidence, ExtractionMethod::Manual, None,
+            ).await.unwrap();
+        }
+        
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let request: GraphSearchRequest = serde_json::from_value(serde_json::json!({
+            "start_entities": [module.id.to_string()],
+            "limit": 2
+        })).unwrap();
+        let response = engine.graph_search(request).await.unwrap();
+        
+        let ranked: Vec<(&str, f32)> = response.entities.iter().map(|e| (e.name.as_str(), e.relevance)).collect();
+        assert_eq!(ranked, [("strong", 0.95), ("strongish", 0.9)]);
+    }
+    
+    #[tokio::test]
+    async fn test_min_similarity_defaults_to_config_and_filters_results() {
+        let graph = crate::graph_db::InMemoryGraph::new();
+        for (path, embedding) in [("src/close.rs", vec![1.0, 0.0]), ("src/far.rs", vec![0.0, 1.0])] {
+            let chunk: crate::models::Chunk = serde_json::from_value::<crate::models::ChunkInput>(serde_json::json!({
+                "content": path,
+                "source_kind": "code",
+                "source_type": "github",
+                "source_id": path,
+                "file_path": path,
+                "owner_id": "owner-1",
+            })).unwrap().into_chunk();
+            graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+            graph.set_node_embedding(&EntityId(chunk.id), embedding, "test", "test").await.unwrap();
+        }
+        
+        let mut config = Config::test_default();
+        config.hybrid_min_similarity = 0.9;
+        config.vector_dimension = 2;
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let paths = |results: Vec<ChunkResult>| {
+            results.into_iter().map(|r| r.file_path.unwrap()).collect::<Vec<_>>()
+        };
+        
+        // The orthogonal chunk scores 0.5 and falls below the configured default
+        let options = SearchOptions::default();
+        let results = engine.vector_search_internal(vec![1.0, 0.0], &options, DEFAULT_CHUNK_INDEX).await.unwrap();
+        assert_eq!(paths(results), ["src/close.rs"]);
+        
+        // Reranked searches apply it too, and a request can lower it
+        let boosted = SearchOptions { recency_boost: Some(0.5), ..Default::default() };
+        let results = engine.vector_search_internal(vec![1.0, 0.0], &boosted, DEFAULT_CHUNK_INDEX).await.unwrap();
+        assert_eq!(paths(results), ["src/close.rs"]);
+        let permissive = SearchOptions { min_similarity: Some(0.0), ..Default::default() };
+        let results = engine.vector_search_internal(vec![1.0, 0.0], &permissive, DEFAULT_CHUNK_INDEX).await.unwrap();
+        assert_eq!(paths(results), ["src/close.rs", "src/far.rs"]);
+        
+        let invalid = SearchOptions { min_similarity: Some(1.5), ..Default::default() };
+        assert!(matches!(
+            engine.vector_search_internal(vec![1.0, 0.0], &invalid, DEFAULT_CHUNK_INDEX).await,
+            Err(GraphError::InvalidRequest(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_path_glob_limits_vector_and_linked_chunks() {
+        use crate::models::{Chunk, ChunkInput, ExtractionMethod};
+        
+        let graph = crate::graph_db::InMemoryGraph::new();
+        let mut chunks = HashMap::new();
+        for (path, source_kind) in [
+            ("src/auth/login.rs", "code"),
+            ("src/auth/oauth/google.rs", "code"),
+            ("src/billing.rs", "code"),
+            ("docs/auth.md", "document"),
+            ("docs/auth.txt", "document"),
+        ] {
+            let chunk: Chunk = serde_json::from_value::<ChunkInput>(serde_json::json!({
+                "content": path,
+                "source_kind": source_kind,
+                "source_type": "github",
+                "source_id": path,
+                "file_path": path,
+                "owner_id": "owner-1",
+            })).unwrap().into_chunk();
+            graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+            graph.set_node_embedding(&EntityId(chunk.id), vec![1.0, 0.0], "test", "test").await.unwrap();
+            chunks.insert(path, chunk.id);
+        }
+        for doc in ["docs/auth.md", "docs/auth.txt"] {
+            graph.create_cross_source_link(
+                &EntityId(chunks[doc]), &EntityId(chunks["src/auth/login.rs"]), 0.8, 0.8, &[ExtractionMethod::VectorSimilarity],
+            ).await.unwrap();
+        }
+        
+        let mut config = Config::test_default();
+        config.vector_dimension = 2;
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let options = |glob: &str| SearchOptions { path_glob: Some(glob.to_string()), ..Default::default() };
+        let paths = |results: Vec<ChunkResult>| {
+            let mut paths: Vec<String> = results.into_iter().map(|r| r.file_path.unwrap()).collect();
+            paths.sort();
+            paths
+        };
+        
+        let module = engine.vector_search_internal(vec![1.0, 0.0], &options("src/auth/**"), DEFAULT_CHUNK_INDEX).await.unwrap();
+        assert_eq!(paths(module), ["src/auth/login.rs", "src/auth/oauth/google.rs"]);
+        let top_level = engine.vector_search_internal(vec![1.0, 0.0], &options("src/*.rs"), DEFAULT_CHUNK_INDEX).await.unwrap();
+        assert_eq!(paths(top_level), ["src/billing.rs"]);
+        
+        // Chunks reached over links are held to the glob too
+        let hits = vec![ChunkResult { chunk_id: chunks["src/auth/login.rs"], ..chunk_result(0.9, vec![]) }];
+        let (linked, _) = engine.cross_source_only_results(&hits, &options("docs/*.{md,rst}")).await.unwrap();
+        assert_eq!(paths(linked), ["docs/auth.md"]);
+        
+        // And when a hit is expanded, both over links and over graph edges
+        let expand = SearchOptions { graph_hops: 1, include_cross_source: Some(true), ..options("docs/*.md") };
+        let expansion = engine.expand_chunk(&hits[0], &expand, engine.graph.as_deref().unwrap()).await.unwrap();
+        let linked: Vec<Uuid> = expansion.cross_source_links.iter().map(|link| link.to_chunk_id).collect();
+        assert_eq!(linked, [chunks["docs/auth.md"]]);
+        let reached: Vec<Uuid> = expansion.entities.iter().map(|entity| entity.id).collect();
+        assert_eq!(reached, [chunks["docs/auth.md"]]);
+        assert_eq!(expansion.relationships.len(), 1);
+        
+        assert!(matches!(
+            engine.vector_search_internal(vec![1.0, 0.0], &options("src/[ab].rs"), DEFAULT_CHUNK_INDEX).await,
+            Err(GraphError::InvalidRequest(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_languages_limit_vector_and_linked_chunks() {
+        use crate::models::{Chunk, ChunkInput, ExtractionMethod};
+        
+        let graph = crate::graph_db::InMemoryGraph::new();
+        let mut chunks = HashMap::new();
+        for (path, language, source_kind) in [
+            ("src/lib.rs", "rust", "code"),
+            ("src/main.rs", "Rust", "code"),
+            ("scripts/load.py", "python", "code"),
+            ("docs/README.md", "md", "document"),
+            ("docs/notes.txt", "text", "document"),
+        ] {
+            let chunk: Chunk = serde_json::from_value::<ChunkInput>(serde_json::json!({
+                "content": path,
+                "source_kind": source_kind,
+                "source_type": "github",
+                "source_id": path,
+                "file_path": path,
+                "language": language,
+                "owner_id": "owner-1",
+            })).unwrap().into_chunk();
+            graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+            graph.set_node_embedding(&EntityId(chunk.id), vec![1.0, 0.0], "test", "test").await.unwrap();
+            chunks.insert(path, chunk.id);
+        }
+        for linked in ["docs/README.md", "docs/notes.txt"] {
+            graph.create_cross_source_link(
+                &EntityId(chunks[linked]), &EntityId(chunks["src/lib.rs"]), 0.8, 0.8, &[ExtractionMethod::VectorSimilarity],
+            ).await.unwrap();
+        }
+        
+        let mut config = Config::test_default();
+        config.vector_dimension = 2;
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let options = |languages: &[&str]| SearchOptions {
+            languages: Some(languages.iter().map(|l| l.to_string()).collect()),
+            ..Default::default()
+        };
+        let paths = |results: Vec<ChunkResult>| {
+            let mut paths: Vec<String> = results.into_iter().map(|r| r.file_path.unwrap()).collect();
+            paths.sort();
+            paths
+        };
+        
+        let rust = engine.vector_search_internal(vec![1.0, 0.0], &options(&["RUST"]), DEFAULT_CHUNK_INDEX).await.unwrap();
+        assert_eq!(paths(rust), ["src/lib.rs", "src/main.rs"]);
+        let python = engine.vector_search_internal(vec![1.0, 0.0], &options(&["py"]), DEFAULT_CHUNK_INDEX).await.unwrap();
+        assert_eq!(paths(python), ["scripts/load.py"]);
+        
+        // Chunks reached over links are held to the filter too, and aliases match either way
+        let hits = vec![ChunkResult { chunk_id: chunks["src/lib.rs"], ..chunk_result(0.9, vec![]) }];
+        let (linked, _) = engine.cross_source_only_results(&hits, &options(&["Markdown"])).await.unwrap();
+        assert_eq!(paths(linked), ["docs/README.md"]);
+        
+        assert!(matches!(
+            engine.vector_search_internal(vec![1.0, 0.0], &options(&[]), DEFAULT_CHUNK_INDEX).await,
+            Err(GraphError::InvalidRequest(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_exact_matches_are_pinned_above_ranked_results() {
+        use crate::models::{Chunk, DataSource, Entity, EntityType};
+        
+        let graph = crate::graph_db::InMemoryGraph::new();
+        let stored: Chunk = serde_json::from_value::<crate::models::ChunkInput>(serde_json::json!({
+            "content": "pub fn verify_token(token: &str) -> bool { true }",
+            "source_kind": "code",
+            "source_type": "github",
+            "source_id": "repo/src/auth.rs",
+            "file_path": "src/auth.rs",
+            "owner_id": "owner-1",
+        })).unwrap().into_chunk();
+        graph.upsert_chunk_node(&stored, None, false).await.unwrap();
+        graph.upsert_entity_node(&Entity::new(
+            EntityType::Function,
+            DataSource::GitHub,
+            format!("{}:verify_token", stored.id),
+            "verify_token".to_string(),
+            std::collections::HashMap::from([
+                ("chunk_id".to_string(), serde_json::json!(stored.id.to_string())),
+                ("owner_id".to_string(), serde_json::json!("owner-1")),
+            ]),
+        )).await.unwrap();
+        
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let mut options = SearchOptions { limit: 2, pin_exact_matches: true, ..Default::default() };
+        let ranked = || vec![chunk_result(0.9, vec![]), chunk_result(0.8, vec![])];
+        
+        for query in ["verify_token", "src/auth.rs"] {
+            let results = engine.pin_exact_matches(query, &options, ranked()).await.unwrap();
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].chunk_id, stored.id);
+            assert!(results[0].pinned);
+            assert_eq!(results[0].raw_score, None);
+            assert!(!results[1].pinned);
+            assert_eq!(results[1].similarity_score, 0.9);
+        }
+        
+        // Other tenants and near misses pin nothing
+        options.owner_id = Some("owner-2".to_string());
+        let results = engine.pin_exact_matches("verify_token", &options, ranked()).await.unwrap();
+        assert!(results.iter().all(|chunk| !chunk.pinned));
+        options.owner_id = None;
+        let results = engine.pin_exact_matches("verify", &options, ranked()).await.unwrap();
+        assert!(results.iter().all(|chunk| !chunk.pinned));
+    }
+    
+    #[tokio::test]
+    async fn test_link_counts_are_opt_in_and_owner_scoped() {
+        use crate::models::{Chunk, ChunkInput, ExtractionMethod};
+        
+        let graph = crate::graph_db::InMemoryGraph::new();
+        let stored = |path: &str, source_kind: &str| -> Chunk {
+            serde_json::from_value::<ChunkInput>(serde_json::json!({
+                "content": path,
+                "source_kind": source_kind,
+                "source_type": "github",
+                "source_id": path,
+                "file_path": path,
+                "owner_id": "owner-1",
+            })).unwrap().into_chunk()
+        };
+        let (code, doc, undocumented) = (
+            stored("src/auth.rs", "code"),
+            stored("docs/auth.md", "document"),
+            stored("src/util.rs", "code"),
+        );
+        for chunk in [&code, &doc, &undocumented] {
+            graph.upsert_chunk_node(chunk, None, false).await.unwrap();
+        }
+        graph.create_cross_source_link(
+            &EntityId(doc.id), &EntityId(code.id), 0.9, 0.9, &[ExtractionMethod::VectorSimilarity],
+        ).await.unwrap();
+        
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let results = || vec![
+            ChunkResult { chunk_id: code.id, ..chunk_result(0.9, vec![]) },
+            ChunkResult { chunk_id: undocumented.id, ..chunk_result(0.8, vec![]) },
+        ];
+        let counts = |results: &[ChunkResult]| {
+            results.iter().map(|chunk| chunk.cross_source_link_count).collect::<Vec<_>>()
+        };
+        
+        let mut unrequested = results();
+        engine.attach_link_counts(&SearchOptions::default(), &mut unrequested).await.unwrap();
+        assert_eq!(counts(&unrequested), [None, None]);
+        
+        let mut options = SearchOptions { include_link_counts: true, ..Default::default() };
+        let mut counted = results();
+        engine.attach_link_counts(&options, &mut counted).await.unwrap();
+        assert_eq!(counts(&counted), [Some(1), Some(0)]);
+        
+        // Links to another tenant's chunks aren't counted
+        options.owner_id = Some("owner-2".to_string());
+        let mut scoped = results();
+        engine.attach_link_counts(&options, &mut scoped).await.unwrap();
+        assert_eq!(counts(&scoped), [Some(0), Some(0)]);
+    }
+    
+    #[tokio::test]
+    async fn test_cross_source_only_returns_linked_chunks_by_confidence() {
+        use crate::models::{Chunk, ChunkInput, ExtractionMethod};
+        
+        let graph = crate::graph_db::InMemoryGraph::new();
+        let stored = |path: &str, source_kind: &str| -> Chunk {
+            serde_json::from_value::<ChunkInput>(serde_json::json!({
+                "content": path,
+                "source_kind": source_kind,
+                "source_type": "github",
+                "source_id": path,
+                "file_path": path,
+                "owner_id": "owner-1",
+            })).unwrap().into_chunk()
+        };
+        let (code, other_code, design, guide, sibling) = (
+            stored("src/auth.rs", "code"),
+            stored("src/session.rs", "code"),
+            stored("docs/design.md", "document"),
+            stored("docs/guide.md", "document"),
+            stored("src/token.rs", "code"),
+        );
+        for chunk in [&code, &other_code, &design, &guide, &sibling] {
+            graph.upsert_chunk_node(chunk, None, false).await.unwrap();
+        }
+        for (from, to, confidence) in [
+            (&guide, &code, 0.7),
+            (&design, &code, 0.9),
+            (&design, &other_code, 0.6),
+            (&sibling, &code, 0.95),
+        ] {
+            graph.create_cross_source_link(
+                &EntityId(from.id), &EntityId(to.id), confidence, confidence, &[ExtractionMethod::VectorSimilarity],
+            ).await.unwrap();
+        }
+        
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let hits = vec![
+            ChunkResult { chunk_id: code.id, ..chunk_result(0.9, vec![]) },
+            ChunkResult { chunk_id: other_code.id, ..chunk_result(0.8, vec![]) },
+        ];
+        let options = SearchOptions { cross_source_only: true, ..Default::default() };
+        let (chunks, links) = engine.cross_source_only_results(&hits, &options).await.unwrap();
+        
+        // The same-kind link is skipped and the design doc appears once, under its stronger link
+        let ids: Vec<Uuid> = chunks.iter().map(|chunk| chunk.chunk_id).collect();
+        assert_eq!(ids, [design.id, guide.id]);
+        assert_eq!(chunks[0].similarity_score, 0.9);
+        assert_eq!(links.len(), 2);
+        assert!(links.iter().all(|link| link.from_chunk_id == code.id));
+        assert_eq!(links[0].to_chunk_id, design.id);
+        assert_eq!(links[0].similarity_score, Some(0.9));
+        // The docs link into the code hits
+        assert!(links.iter().all(|link| link.direction == crate::models::EdgeDirection::Incoming));
+        
+        let outgoing = SearchOptions {
+            cross_source_only: true,
+            cross_source_direction: "outgoing".to_string(),
+            ..Default::default()
+        };
+        let (chunks, _) = engine.cross_source_only_results(&hits, &outgoing).await.unwrap();
+        assert!(chunks.is_empty());
+        
+        let limited = SearchOptions { limit: 1, ..options };
+        let (chunks, _) = engine.cross_source_only_results(&hits, &limited).await.unwrap();
+        assert_eq!(chunks.len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_cross_source_hops_reach_linked_chunks_of_linked_chunks() {
+        use crate::models::{Chunk, ChunkInput, ExtractionMethod};
+        
+        let graph = crate::graph_db::InMemoryGraph::new();
+        let stored = |path: &str, source_kind: &str| -> Chunk {
+            serde_json::from_value::<ChunkInput>(serde_json::json!({
+                "content": path,
+                "source_kind": source_kind,
+                "source_type": "github",
+                "source_id": path,
+                "file_path": path,
+                "owner_id": "owner-1",
+            })).unwrap().into_chunk()
+        };
+        // The hit's similar doc explains other code, which links back to the hit
+        let (hit, doc, explained) = (stored("src/auth.rs", "code"), stored("docs/auth.md", "document"), stored("src/token.rs", "code"));
+        for chunk in [&hit, &doc, &explained] {
+            graph.upsert_chunk_node(chunk, None, false).await.unwrap();
+        }
+        for (from, to, confidence) in [(&hit, &doc, 0.9), (&doc, &explained, 0.8), (&explained, &hit, 0.7)] {
+            graph.create_cross_source_link(
+                &EntityId(from.id), &EntityId(to.id), confidence, confidence, &[ExtractionMethod::VectorSimilarity],
+            ).await.unwrap();
+        }
+        // A link to an entity node isn't a chunk link, however strong
+        let entity = crate::models::Entity::new(
+            crate::models::EntityType::Document,
+            crate::models::DataSource::GitHub,
+            "guide".to_string(),
+            "guide".to_string(),
+            Default::default(),
+        );
+        graph.upsert_entity_node(&entity).await.unwrap();
+        graph.create_cross_source_link(&EntityId(hit.id), &EntityId(entity.id), 0.95, 0.95, &[]).await.unwrap();
+        
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, None, embedding_client);
+        let result = ChunkResult { chunk_id: hit.id, ..chunk_result(0.9, vec![]) };
+        let links = |cross_source_hops: usize, cross_source_min_confidence: f32, max_cross_source_links: usize| {
+            let options = SearchOptions {
+                graph_hops: 0,
+                cross_source_hops,
+                cross_source_min_confidence,
+                max_cross_source_links,
+                ..Default::default()
+            };
+            let (engine, graph, result) = (&engine, &graph, &result);
+            async move {
+                let expansion = engine.expand_chunk(result, &options, graph).await.unwrap();
+                expansion.cross_source_links
+                    .iter()
+                    .map(|link| (link.from_chunk_id, link.to_chunk_id, link.similarity_score))
+                    .collect::<Vec<_>>()
+            }
+        };
+        
+        // One hop reaches both neighbors of the hit, strongest first
+        assert_eq!(links(1, 0.0, 20).await, [(hit.id, doc.id, Some(0.9)), (hit.id, explained.id, Some(0.9))]);
+        
+        // Without the closing link, the far node only shows up at two hops
+        assert_eq!(links(1, 0.75, 20).await, [(hit.id, doc.id, Some(0.9))]);
+        assert_eq!(links(2, 0.75, 20).await, [(hit.id, doc.id, Some(0.9)), (doc.id, explained.id, None)]);
+        
+        // The cycle back to the hit adds nothing, and the node cap holds
+        assert_eq!(links(3, 0.0, 20).await.len(), 2);
+        assert_eq!(links(3, 0.0, 1).await, [(hit.id, doc.id, Some(0.9))]);
+    }
+    
+    #[test]
+    fn test_cross_source_options_are_clamped_to_config() {
+        let mut config = Config::test_default();
+        config.max_entities_per_traversal = 5;
+        config.max_graph_hops = 2;
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, None, embedding_client);
+        
+        let mut options = SearchOptions { max_cross_source_links: 10_000, cross_source_hops: 50, ..Default::default() };
+        engine.clamp_cross_source(&mut options);
+        assert_eq!((options.max_cross_source_links, options.cross_source_hops), (5, 2));
+        
+        let mut options = SearchOptions { max_cross_source_links: 3, cross_source_hops: 1, ..Default::default() };
+        engine.clamp_cross_source(&mut options);
+        assert_eq!((options.max_cross_source_links, options.cross_source_hops), (3, 1));
+    }
+    
+    #[tokio::test]
+    async fn test_single_kind_search_down_weights_cross_source_expansion() {
+        use crate::graph_db::InMemoryGraph;
+        use crate::models::{DataSource, Entity, EntityType, ExtractionMethod};
+        
+        let graph = InMemoryGraph::new();
+        let entity = |name: &str, entity_type| Entity::new(
+            entity_type, DataSource::GitHub, name.to_string(), name.to_string(), std::collections::HashMap::new(),
+        );
+        let (module, function, section) = (
+            entity("auth", EntityType::Module),
+            entity("login", EntityType::Function),
+            entity("Logging in", EntityType::Section),
+        );
+        for node in [&module, &function, &section] {
+            graph.upsert_entity_node(node).await.unwrap();
+        }
+        for (from, to, rel_type) in [
+            (&module, &function, RelationshipType::Contains),
+            (&section, &module, RelationshipType::Explains),
+        ] {
+            graph.create_relationship(
+                &EntityId(from.id), &EntityId(to.id), rel_type, 0.8, ExtractionMethod::Manual, None,
+            ).await.unwrap();
+        }
+        
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client);
+        let expand = |options: SearchOptions| {
+            let weight = engine.cross_kind_weight(&options);
+            let graph = engine.graph.clone().unwrap();
+            let module_id = EntityId(module.id);
+            let engine = &engine;
+            async move {
+                let (entities, relationships) = engine
+                    .graph_expand(&module_id, 1, graph.as_ref(), None, weight, None)
+                    .await
+                    .unwrap();
+                let mut names: Vec<_> = entities.into_iter().map(|e| e.name).collect();
+                names.sort();
+                let explains = relationships.iter().find(|r| r.relationship_type == "EXPLAINS").map(|r| r.confidence);
+                (names, explains)
+            }
+        };
+        let code_only = || SearchOptions { source_kind: "code".to_string(), ..Default::default() };
+        
+        // By default a code search doesn't follow the EXPLAINS edge to the doc section
+        assert_eq!(expand(code_only()).await, (vec!["login".to_string()], None));
+        
+        // A partial weight keeps it with reduced confidence
+        let (names, explains) = expand(SearchOptions { cross_kind_weight: Some(0.5), ..code_only() }).await;
+        assert_eq!(names, ["Logging in", "login"]);
+        assert!((explains.unwrap() - 0.4).abs() < 1e-6);
+        
+        // Asking for cross-source results, or searching all kinds, keeps full weight
+        let explicit = SearchOptions { include_cross_source: Some(true), ..code_only() };
+        assert_eq!(expand(explicit).await.1, Some(0.8));
+        assert_eq!(expand(SearchOptions::default()).await.1, Some(0.8));
+    }
+    
+    #[tokio::test]
+    async fn test_query_dimension_mismatch_is_reported() {
+        let engine = |policy: &str| {
+            let mut config = Config::test_default();
+            config.vector_dimension = 384;
+            config.query_dimension_mismatch = policy.to_string();
+            let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+            let graph: Arc<dyn GraphStore> = Arc::new(crate::graph_db::InMemoryGraph::new());
+            HybridQueryEngine::new(config, Some(graph), embedding_client)
+        };
+        let options = SearchOptions::default();
+        
+        match engine("error").vector_search_internal(vec![0.1; 768], &options, DEFAULT_CHUNK_INDEX).await {
+            Err(GraphError::Embedding(message)) => {
+                assert!(message.contains("768 dimensions"), "{}", message);
+                assert!(message.contains("expects 384"), "{}", message);
+            }
+            other => panic!("expected a dimension error, got {:?}", other),
+        }
+        
+        let skipped = engine("skip").vector_search_internal(vec![0.1; 768], &options, DEFAULT_CHUNK_INDEX).await;
+        assert!(skipped.unwrap().is_empty());
+        
+        // Indexes for other models can have their own dimension
+        let other_model = engine("error").vector_search_internal(vec![0.1; 768], &options, "chunk_e5_idx").await;
+        assert!(other_model.is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_repeated_search_is_served_from_cache() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let graph = crate::graph_db::InMemoryGraph::new();
+        let chunk: crate::models::Chunk = serde_json::from_value::<crate::models::ChunkInput>(serde_json::json!({
+            "content": "pub struct SessionStore {}",
+            "source_kind": "code",
+            "source_type": "github",
+            "source_id": "repo/src/session.rs",
+            "file_path": "src/session.rs",
+            "owner_id": "owner-1",
+        })).unwrap().into_chunk();
+        graph.upsert_chunk_node(&chunk, None, false).await.unwrap();
+        graph.set_node_embedding(&EntityId(chunk.id), vec![1.0, 0.0], "test", "test").await.unwrap();
+        
+        // Only the first search reaches the embedding service
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "embedding": [1.0, 0.0]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        
+        let mut config = Config::test_default();
+        config.embedding_service_url = server.uri();
+        config.embedding_cache_size = 0;
+        config.vector_dimension = 2;
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let cache = Arc::new(SearchCache::in_memory(8, std::time::Duration::from_secs(60)));
+        let engine = HybridQueryEngine::new(config, Some(Arc::new(graph)), embedding_client)
+            .with_search_cache(Some(cache.clone()));
+        let request = |query: &str| HybridSearchRequest {
+            query: query.to_string(),
+            options: SearchOptions { owner_id: Some("owner-1".to_string()), ..Default::default() },
+        };
+        
+        let first = engine.search(request("session store")).await.unwrap();
+        assert!(!first.metadata.cached);
+        assert!(first.metadata.cached_at.is_none());
+        
+        let second = engine.search(request("session   store")).await.unwrap();
+        assert!(second.metadata.cached);
+        assert!(second.metadata.cached_at.is_some());
+        assert_eq!(second.chunks.len(), 1);
+        assert_eq!(second.chunks[0].chunk_id, chunk.id);
+        assert_eq!(cache.stats().hits, 1);
+    }
+    
+    /// Collects span fields by span name
+    #[derive(Clone, Default)]
+    struct SpanFields(Arc<std::sync::Mutex<std::collections::HashMap<String, std::collections::HashMap<String, String>>>>);
+    
+    struct FieldVisitor<'a>(&'a mut std::collections::HashMap<String, String>);
+    
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+        
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+    
+    impl<S> tracing_subscriber::Layer<S> for SpanFields
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _id: &tracing::span::Id,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut spans = self.0.lock().unwrap();
+            attrs.record(&mut FieldVisitor(spans.entry(attrs.metadata().name().to_string()).or_default()));
+        }
+        
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let Some(span) = ctx.span(id) else { return };
+            let mut spans = self.0.lock().unwrap();
+            values.record(&mut FieldVisitor(spans.entry(span.name().to_string()).or_default()));
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_search_span_records_owner_and_phase_timings() {
+        use tracing_subscriber::layer::SubscriberExt;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+        
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "embedding": [0.1, 0.2, 0.3]
+            })))
+            .mount(&server)
+            .await;
+        
+        let mut config = Config::test_default();
+        config.embedding_service_url = server.uri();
+        config.embedding_cache_size = 0;
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        let engine = HybridQueryEngine::new(config, None, embedding_client);
+        
+        let fields = SpanFields::default();
+        let _guard = tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(fields.clone()),
+        );
+        
+        let request: HybridSearchRequest = serde_json::from_value(serde_json::json!({
+            "query": "how does auth work",
+            "limit": 5,
+            "owner_id": "owner-1",
+        })).unwrap();
+        // Without Neo4j the vector phase fails, after the embed phase was timed
+        assert!(engine.search(request).await.is_err());
+        
+        let spans = fields.0.lock().unwrap();
+        let span = &spans["hybrid_search"];
+        assert_eq!(span["owner_id"], "owner-1");
+        assert_eq!(span["limit"], "5");
+        assert!(span.contains_key("embed_ms"));
+        assert!(!span.contains_key("vector_ms"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Background chunk ingestion jobs
+//!
+//! Large ingest requests are queued and processed in a Tokio task so the HTTP
+//! connection returns immediately with a job id. The job is processed in
+//! slices and its counters are updated after each one, so polling shows
+//! progress. Jobs live in memory only; finished jobs are pruned after
+//! `FINISHED_JOB_RETENTION`.
+
+use crate::models::IngestChunksRequest;
+use crate::services::ChunkProcessor;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long completed and failed jobs stay queryable
+const FINISHED_JOB_RETENTION: chrono::Duration = chrono::Duration::hours(1);
+
+/// How often `drain` checks for unfinished jobs
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Lifecycle of an ingestion job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed)
+    }
+}
+
+/// Progress and outcome of an ingestion job
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestJob {
+    pub job_id: Uuid,
+    /// Tenant whose chunks the job ingests (`None` when they span owners)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<String>,
+    pub status: JobStatus,
+    pub chunks_total: usize,
+    pub chunks_processed: usize,
+    pub chunks_ingested: usize,
+    pub entities_extracted: usize,
+    pub relationships_created: usize,
+    pub errors: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Response after queueing an ingestion job
+#[derive(Debug, Serialize)]
+pub struct IngestJobAccepted {
+    pub job_id: Uuid,
+    pub status: JobStatus,
+    pub chunks_total: usize,
+}
+
+/// In-memory registry of ingestion jobs
+#[derive(Default)]
+pub struct IngestJobRegistry {
+    jobs: DashMap<Uuid, IngestJob>,
+}
+
+impl IngestJobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a request and process it in the background, `slice_size` chunks at a time
+    pub fn submit(
+        self: &Arc<Self>,
+        processor: ChunkProcessor,
+        request: IngestChunksRequest,
+        slice_size: usize,
+    ) -> IngestJob {
+        self.prune_finished();
+
+        let now = Utc::now();
+        let owner_id = request.chunks.first()
+            .map(|chunk| chunk.owner_id.clone())
+            .filter(|owner| request.chunks.iter().all(|chunk| chunk.owner_id == *owner));
+        let job = IngestJob {
+            job_id: Uuid::new_v4(),
+            owner_id,
+            status: JobStatus::Queued,
+            chunks_total: request.chunks.len(),
+            chunks_processed: 0,
+            chunks_ingested: 0,
+            entities_extracted: 0,
+            relationships_created: 0,
+            errors: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        self.jobs.insert(job.job_id, job.clone());
+
+        let registry = Arc::clone(self);
+        let job_id = job.job_id;
+        tokio::spawn(async move {
+            registry.run(job_id, processor, request, slice_size.max(1)).await;
+        });
+
+        job
+    }
+
+    /// Snapshot of a job's current state
+    pub fn get(&self, job_id: &Uuid) -> Option<IngestJob> {
+        self.jobs.get(job_id).map(|job| job.clone())
+    }
+
+    /// Wait up to `timeout` for queued and running jobs; returns how many are still unfinished
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let unfinished = self.jobs.iter().filter(|job| !job.status.is_finished()).count();
+            if unfinished == 0 || Instant::now() >= deadline {
+                return unfinished;
+            }
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn run(
+        &self,
+        job_id: Uuid,
+        processor: ChunkProcessor,
+        mut request: IngestChunksRequest,
+        slice_size: usize,
+    ) {
+        self.update(&job_id, |job| job.status = JobStatus::Running);
+
+        while !request.chunks.is_empty() {
+            let take = slice_size.min(request.chunks.len());
+            let slice = IngestChunksRequest {
+                chunks: request.chunks.drain(..take).collect(),
+                extract_entities: request.extract_entities,
+                create_cross_links: request.create_cross_links,
+                strict: request.strict,
+                detailed: None,
+                store_chunks: request.store_chunks,
+                dedupe: request.dedupe,
+                return_evidence: None,
+            };
+
+            match processor.ingest_chunks(slice).await {
+                Ok(response) => self.update(&job_id, |job| {
+                    job.chunks_processed += take;
+                    job.chunks_ingested += response.chunks_ingested;
+                    job.entities_extracted += response.entities_extracted;
+                    job.relationships_created += response.relationships_created;
+                    job.errors.extend(response.errors);
+                }),
+                Err(e) => {
+                    tracing::error!("Ingestion job {} failed: {}", job_id, e);
+                    self.update(&job_id, |job| {
+                        job.status = JobStatus::Failed;
+                        job.errors.push(e.to_string());
+                    });
+                    return;
+                }
+            }
+        }
+
+        self.update(&job_id, |job| job.status = JobStatus::Completed);
+    }
+
+    fn update(&self, job_id: &Uuid, apply: impl FnOnce(&mut IngestJob)) {
+        if let Some(mut job) = self.jobs.get_mut(job_id) {
+            apply(&mut job);
+            job.updated_at = Utc::now();
+        }
+    }
+
+    fn prune_finished(&self) {
+        let cutoff = Utc::now() - FINISHED_JOB_RETENTION;
+        self.jobs.retain(|_, job| !(job.status.is_finished() && job.updated_at < cutoff));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::services::EmbeddingClient;
+
+    fn request(chunk_count: usize) -> IngestChunksRequest {
+        let chunks = (0..chunk_count)
+            .map(|i| serde_json::from_value(serde_json::json!({
+                "content": format!("fn handler_{}() {{}}", i),
+                "embedding": [0.1, 0.2, 0.3],
+                "source_kind": "code",
+                "source_type": "github",
+                "source_id": format!("repo/file_{}.rs", i),
+                "owner_id": "owner-1",
+            })).unwrap())
+            .collect();
+
+        IngestChunksRequest {
+            chunks,
+            extract_entities: Some(false),
+            create_cross_links: Some(false),
+            strict: None,
+            detailed: None,
+            store_chunks: None,
+            dedupe: None,
+            return_evidence: None,
+        }
+    }
+
+    fn processor() -> ChunkProcessor {
+        let config = Config::test_default();
+        let embedding_client = Arc::new(EmbeddingClient::from_config(&config));
+        ChunkProcessor::new(config, None, embedding_client)
+    }
+
+    async fn wait_until_finished(registry: &IngestJobRegistry, job_id: Uuid) -> IngestJob {
+        for _ in 0..200 {
+            let job = registry.get(&job_id).unwrap();
+            if job.status.is_finished() {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job {} did not finish", job_id);
+    }
+
+    #[tokio::test]
+    async fn test_submitted_job_completes() {
+        let registry = Arc::new(IngestJobRegistry::new());
+
+        let job = registry.submit(processor(), request(5), 2);
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(job.chunks_total, 5);
+        assert_eq!(job.owner_id.as_deref(), Some("owner-1"));
+
+        let job = wait_until_finished(&registry, job.job_id).await;
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.chunks_processed, 5);
+        assert!(job.errors.is_empty());
+        assert!(registry.get(&Uuid::new_v4()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_for_unfinished_jobs() {
+        let registry = Arc::new(IngestJobRegistry::new());
+        assert_eq!(registry.drain(Duration::ZERO).await, 0);
+
+        let job = registry.submit(processor(), request(4), 1);
+        assert_eq!(registry.drain(Duration::from_secs(5)).await, 0);
+        assert_eq!(registry.get(&job.job_id).unwrap().status, JobStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_submissions_are_tracked_separately() {
+        let registry = Arc::new(IngestJobRegistry::new());
+
+        let submissions = (1..=8).map(|n| {
+            let registry = Arc::clone(&registry);
+            tokio::spawn(async move { (n, registry.submit(processor(), request(n), 3).job_id) })
+        });
+        let submitted = futures::future::join_all(submissions).await;
+
+        for result in submitted {
+            let (chunk_count, job_id) = result.unwrap();
+            let job = wait_until_finished(&registry, job_id).await;
+            assert_eq!(job.status, JobStatus::Completed);
+            assert_eq!(job.chunks_total, chunk_count);
+            assert_eq!(job.chunks_processed, chunk_count);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Streaming NDJSON chunk ingestion
+//!
+//! `POST /api/graph/chunks/stream` bodies are read as they arrive and split
+//! into lines, so a bulk upload never has to fit in memory. Chunks are
+//! ingested in batches and a progress frame is sent after each one. Lines that
+//! aren't valid chunks, or are longer than `MAX_LINE_BYTES`, are reported in
+//! the next frame and skipped.
+
+use crate::models::{
+    ChunkInput, IngestChunksRequest, IngestStreamFrame, IngestStreamProgress, IngestStreamQuery,
+    IngestStreamTotals,
+};
+use crate::services::ChunkProcessor;
+use futures::{Stream, StreamExt};
+use std::fmt::Display;
+use std::pin::pin;
+use tokio::sync::mpsc;
+
+/// Longest line accepted; the rest of a longer one is discarded unread
+pub const MAX_LINE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Ingest newline-delimited chunks from `body`, sending a frame per batch
+///
+/// Lines whose `owner_id` differs from `owner` are rejected like malformed
+/// ones. Stops early when the receiver of `frames` goes away.
+pub async fn ingest_ndjson<S, B, E>(
+    processor: &ChunkProcessor,
+    body: S,
+    query: &IngestStreamQuery,
+    batch_size: usize,
+    owner: Option<&str>,
+    frames: &mpsc::Sender<IngestStreamFrame>,
+) where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: Display,
+{
+    let mut ingest = StreamIngest {
+        processor,
+        query,
+        batch_size,
+        owner,
+        batch: Vec::with_capacity(batch_size),
+        errors: Vec::new(),
+        lines_read: 0,
+        totals: IngestStreamTotals::default(),
+    };
+    let mut body = pin!(body);
+    // Bytes after the last newline, waiting for the rest of their line
+    let mut pending: Vec<u8> = Vec::new();
+    // Inside a line already over the limit, dropping bytes until its newline
+    let mut oversized = false;
+
+    while let Some(data) = body.next().await {
+        let data = match data {
+            Ok(data) => data,
+            Err(e) => {
+                let message = format!("Failed to read request body: {}", e);
+                let _ = frames.send(IngestStreamFrame::Error { message }).await;
+                return;
+            }
+        };
+        pending.extend_from_slice(data.as_ref());
+
+        let mut start = 0;
+        while let Some(end) = pending[start..].iter().position(|&b| b == b'\n') {
+            if oversized || end > MAX_LINE_BYTES {
+                ingest.reject_long_line();
+                oversized = false;
+            } else {
+                ingest.read_line(&pending[start..start + end]);
+            }
+            start += end + 1;
+            if ingest.batch.len() >= batch_size && !ingest.flush(frames).await {
+                return;
+            }
+        }
+        pending.drain(..start);
+        if pending.len() > MAX_LINE_BYTES {
+            oversized = true;
+            pending.clear();
+        }
+    }
+
+    // The last line may not end in a newline
+    if oversized {
+        ingest.reject_long_line();
+    } else if !pending.is_empty() {
+        ingest.read_line(&pending);
+    }
+    if (!ingest.batch.is_empty() || !ingest.errors.is_empty()) && !ingest.flush(frames).await {
+        return;
+    }
+
+    let mut totals = ingest.totals;
+    totals.lines_read = ingest.lines_read;
+    let _ = frames.send(IngestStreamFrame::Done(totals)).await;
+}
+
+struct StreamIngest<'a> {
+    processor: &'a ChunkProcessor,
+    query: &'a IngestStreamQuery,
+    batch_size: usize,
+    owner: Option<&'a str>,
+    batch: Vec<ChunkInput>,
+    /// Line errors since the last flush
+    errors: Vec<String>,
+    lines_read: usize,
+    totals: IngestStreamTotals,
+}
+
+impl StreamIngest<'_> {
+    fn read_line(&mut self, line: &[u8]) {
+        self.lines_read += 1;
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            return;
+        }
+
+        match serde_json::from_slice::<ChunkInput>(line) {
+            Ok(chunk) => match self.owner {
+                Some(owner) if owner != chunk.owner_id => self.errors.push(format!(
+                    "Line {}: owner_id '{}' does not match X-Owner-Id header",
+                    self.lines_read, chunk.owner_id,
+                )),
+                _ => self.batch.push(chunk),
+            },
+            Err(e) => self.errors.push(format!("Line {}: invalid chunk: {}", self.lines_read, e)),
+        }
+    }
+
+    fn reject_long_line(&mut self) {
+        self.lines_read += 1;
+        self.errors.push(format!("Line {}: longer than {} bytes", self.lines_read, MAX_LINE_BYTES));
+    }
+
+    /// Ingest the buffered batch and report it; false once nobody is listening
+    async fn flush(&mut self, frames: &mpsc::Sender<IngestStreamFrame>) -> bool {
+        let chunks = std::mem::replace(&mut self.batch, Vec::with_capacity(self.batch_size));
+        let mut progress = IngestStreamProgress {
+            batch: self.totals.batches + 1,
+            lines_read: self.lines_read,
+            errors: std::mem::take(&mut self.errors),
+            ..Default::default()
+        };
+
+        if !chunks.is_empty() {
+            let request = IngestChunksRequest {
+                chunks,
+                extract_entities: self.query.extract_entities,
+                create_cross_links: self.query.create_cross_links,
+                strict: None,
+                detailed: None,
+                store_chunks: self.query.store_chunks,
+                dedupe: self.query.dedupe,
+                return_evidence: None,
+            };
+            match self.processor.ingest_chunks(request).await {
+                Ok(response) => {
+                    progress.chunks_ingested = response.chunks_ingested;
+                    progress.entities_extracted = response.entities_extracted;
+                    progress.relationships_created = response.relationships_created;
+                    progress.chunks_skipped = response.chunks_skipped;
+                    progress.errors.extend(response.errors);
+                }
+                Err(e) => {
+                    tracing::error!("Streamed ingest batch {} failed: {}", progress.batch, e);
+                    progress.errors.push(format!("Batch {} failed: {}", progress.batch, e));
+                }
+            }
+        }
+
+        self.totals.add(&progress);
+        frames.send(IngestStreamFrame::Progress(progress)).await.is_ok()
+    }
+}
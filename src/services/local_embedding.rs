@@ -0,0 +1,242 @@
+//! In-process embedding model (`EMBEDDING_PROVIDER=local`)
+//!
+//! Runs a BERT-style sentence-transformer (e.g. `all-MiniLM-L6-v2`) with
+//! candle on the CPU, so no embedding service is needed. `LOCAL_MODEL_PATH` is
+//! a directory holding the model's `config.json`, `tokenizer.json`, and
+//! `model.safetensors`, as published on the Hugging Face hub; nothing is
+//! downloaded. Vectors are the attention-masked mean of the last hidden
+//! states, L2-normalized, as sentence-transformers computes them.
+//!
+//! Only built with `--features local-embeddings`.
+
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config as BertConfig};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+use tokio::sync::OnceCell;
+
+use crate::error::{GraphError, GraphResult};
+use crate::services::embedding_provider::EmbeddingProvider;
+
+/// A loaded model and its tokenizer
+struct LocalModel {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    hidden_size: usize,
+}
+
+impl LocalModel {
+    fn load(dir: &Path) -> GraphResult<Self> {
+        let load_error = |what: &str, e: String| {
+            GraphError::Embedding(format!("Failed to load {} from {}: {}", what, dir.display(), e))
+        };
+
+        let config = std::fs::read_to_string(dir.join("config.json")).map_err(|e| load_error("config.json", e.to_string()))?;
+        let config: BertConfig = serde_json::from_str(&config).map_err(|e| load_error("config.json", e.to_string()))?;
+
+        let mut tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| load_error("tokenizer.json", e.to_string()))?;
+        // Longer inputs would overrun the position embeddings
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: config.max_position_embeddings,
+                ..Default::default()
+            }))
+            .map_err(|e| load_error("tokenizer.json", e.to_string()))?;
+        if tokenizer.get_padding().is_none() {
+            tokenizer.with_padding(Some(PaddingParams {
+                strategy: PaddingStrategy::BatchLongest,
+                pad_id: config.pad_token_id as u32,
+                ..Default::default()
+            }));
+        }
+
+        let weights = std::fs::read(dir.join("model.safetensors"))
+            .map_err(|e| load_error("model.safetensors", e.to_string()))?;
+        let vb = VarBuilder::from_buffered_safetensors(weights, DType::F32, &Device::Cpu)
+            .map_err(|e| load_error("model.safetensors", e.to_string()))?;
+        let model = BertModel::load(vb, &config).map_err(|e| load_error("model.safetensors", e.to_string()))?;
+
+        Ok(Self { model, tokenizer, hidden_size: config.hidden_size })
+    }
+
+    /// Mean-pooled, normalized vectors for `texts`, in order
+    fn embed(&self, texts: Vec<String>) -> candle_core::Result<Vec<Vec<f32>>> {
+        let encodings = self.tokenizer.encode_batch(texts, true).map_err(candle_core::Error::msg)?;
+        let device = &self.model.device;
+        let rows = |field: fn(&tokenizers::Encoding) -> &[u32]| {
+            let rows = encodings.iter()
+                .map(|encoding| Tensor::new(field(encoding), device))
+                .collect::<candle_core::Result<Vec<_>>>()?;
+            Tensor::stack(&rows, 0)
+        };
+        let input_ids = rows(|encoding| encoding.get_ids())?;
+        let attention_mask = rows(|encoding| encoding.get_attention_mask())?;
+        let token_type_ids = input_ids.zeros_like()?;
+
+        let hidden = self.model.forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
+        // Padding positions are left out of the mean
+        let mask = attention_mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+        let pooled = hidden.broadcast_mul(&mask)?.sum(1)?.broadcast_div(&mask.sum(1)?)?;
+        let norms = pooled.sqr()?.sum_keepdim(1)?.sqrt()?;
+        pooled.broadcast_div(&norms)?.to_vec2()
+    }
+}
+
+/// A sentence-transformer run in process
+pub struct LocalProvider {
+    dir: PathBuf,
+    /// `EMBEDDING_MODEL`; requests for any other model are rejected
+    model_name: String,
+    /// Loaded on first use, so startup isn't held up reading the weights
+    model: OnceCell<Arc<LocalModel>>,
+}
+
+impl LocalProvider {
+    pub fn new(dir: &str, model_name: &str) -> Self {
+        Self { dir: PathBuf::from(dir), model_name: model_name.to_string(), model: OnceCell::new() }
+    }
+
+    async fn model(&self) -> GraphResult<Arc<LocalModel>> {
+        let model = self.model.get_or_try_init(|| async {
+            let dir = self.dir.clone();
+            let model = tokio::task::spawn_blocking(move || LocalModel::load(&dir))
+                .await
+                .map_err(|e| GraphError::Internal(format!("Local model load panicked: {}", e)))??;
+            tracing::info!("✅ Loaded local embedding model from {}", self.dir.display());
+            Ok::<_, GraphError>(Arc::new(model))
+        }).await?;
+        Ok(model.clone())
+    }
+
+    async fn run(&self, texts: Vec<String>) -> GraphResult<Vec<Vec<f32>>> {
+        let model = self.model().await?;
+        // Inference is CPU-bound; keep it off the async workers
+        tokio::task::spawn_blocking(move || model.embed(texts))
+            .await
+            .map_err(|e| GraphError::Internal(format!("Local embedding panicked: {}", e)))?
+            .map_err(|e| GraphError::Embedding(format!("Local embedding failed: {}", e)))
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn embed(&self, text: &str, model: Option<&str>) -> GraphResult<Vec<f32>> {
+        if let Some(model) = model.filter(|model| *model != self.model_name) {
+            return Err(GraphError::Embedding(format!(
+                "The local provider only runs '{}', not '{}'",
+                self.model_name, model
+            )));
+        }
+        let mut embeddings = self.run(vec![text.to_string()]).await?;
+        Ok(embeddings.remove(0))
+    }
+
+    async fn embed_batch(&self, texts: Vec<String>) -> GraphResult<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.run(texts).await
+    }
+
+    async fn dimension(&self) -> GraphResult<usize> {
+        Ok(self.model().await?.hidden_size)
+    }
+
+    async fn health_check(&self) -> bool {
+        self.model().await.is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_nn::VarMap;
+
+    /// Write a tiny randomly initialized BERT and a word-level tokenizer to a fresh directory
+    fn tiny_model_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("local-embedding-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let config = serde_json::json!({
+            "vocab_size": 8,
+            "hidden_size": 8,
+            "num_hidden_layers": 1,
+            "num_attention_heads": 2,
+            "intermediate_size": 16,
+            "hidden_act": "gelu",
+            "hidden_dropout_prob": 0.0,
+            "max_position_embeddings": 16,
+            "type_vocab_size": 2,
+            "initializer_range": 0.02,
+            "layer_norm_eps": 1e-12,
+            "pad_token_id": 0,
+            "classifier_dropout": null,
+            "model_type": "bert",
+        });
+        std::fs::write(dir.join("config.json"), config.to_string()).unwrap();
+
+        let tokenizer = serde_json::json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": null,
+            "pre_tokenizer": { "type": "Whitespace" },
+            "post_processor": null,
+            "decoder": null,
+            "model": {
+                "type": "WordLevel",
+                "vocab": { "[PAD]": 0, "[UNK]": 1, "login": 2, "creates": 3, "a": 4, "session": 5, "user": 6, "token": 7 },
+                "unk_token": "[UNK]",
+            },
+        });
+        std::fs::write(dir.join("tokenizer.json"), tokenizer.to_string()).unwrap();
+
+        let varmap = VarMap::new();
+        let vb = VarBuilder::from_varmap(&varmap, DType::F32, &Device::Cpu);
+        BertModel::load(vb, &serde_json::from_value(config).unwrap()).unwrap();
+        varmap.save(dir.join("model.safetensors")).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_local_model_embeds_normalized_vectors_in_order() {
+        let dir = tiny_model_dir();
+        let provider = LocalProvider::new(dir.to_str().unwrap(), "tiny-bert");
+        assert_eq!(provider.dimension().await.unwrap(), 8);
+        assert!(provider.health_check().await);
+
+        let single = provider.embed("login creates a session", None).await.unwrap();
+        assert_eq!(single.len(), 8);
+        let norm: f32 = single.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-4, "{}", norm);
+
+        // Padding a shorter text in a batch doesn't change its vector
+        let batch = provider
+            .embed_batch(vec!["user token".to_string(), "login creates a session".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(batch.len(), 2);
+        assert!(batch[1].iter().zip(&single).all(|(a, b)| (a - b).abs() < 1e-4));
+        let alone = provider.embed("user token", Some("tiny-bert")).await.unwrap();
+        assert!(batch[0].iter().zip(&alone).all(|(a, b)| (a - b).abs() < 1e-4));
+
+        assert!(matches!(provider.embed("login", Some("other-model")).await, Err(GraphError::Embedding(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_model_files_fail_to_load() {
+        let provider = LocalProvider::new("/nonexistent/model", "tiny-bert");
+        assert!(matches!(provider.embed("login", None).await, Err(GraphError::Embedding(_))));
+        assert!(!provider.health_check().await);
+    }
+}
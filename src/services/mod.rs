@@ -3,9 +3,23 @@
 pub mod cross_source_linker;
 pub mod hybrid_query;
 pub mod chunk_processor;
+pub mod chunk_repository;
+pub mod circuit_breaker;
+pub mod embedding_cache;
 pub mod embedding_client;
+pub mod embedding_provider;
+pub mod entity_resolver;
+pub mod graph_export;
+pub mod graph_import;
+pub mod ingest_jobs;
+pub mod ingest_stream;
+#[cfg(feature = "local-embeddings")]
+pub mod local_embedding;
+pub mod search_cache;
+pub mod summarization_client;
 
-pub use cross_source_linker::CrossSourceLinker;
 pub use hybrid_query::HybridQueryEngine;
 pub use chunk_processor::ChunkProcessor;
+pub use chunk_repository::ChunkRepository;
 pub use embedding_client::EmbeddingClient;
+pub use summarization_client::SummarizationClient;
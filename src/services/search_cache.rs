@@ -0,0 +1,214 @@
+//! Cache for hybrid search responses
+//!
+//! Keyed by a hash of the whitespace-normalized query and every search
+//! option, including the owner scope. Uses Redis when `REDIS_URL` is
+//! configured, otherwise an in-process LRU. Ingestion clears the in-process
+//! cache; Redis entries are shared across replicas and stay until their
+//! (short) TTL runs out.
+
+use crate::error::{GraphError, GraphResult};
+use crate::models::{HybridSearchRequest, HybridSearchResponse};
+use chrono::{DateTime, Utc};
+use lru::LruCache;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+enum CacheBackend {
+    Memory(Mutex<LruCache<String, (Instant, String)>>),
+    Redis(ConnectionManager),
+}
+
+/// Hit/miss counters for the statistics endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchCacheStats {
+    pub backend: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Search response cache with TTL
+pub struct SearchCache {
+    backend: CacheBackend,
+    ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl SearchCache {
+    /// In-process LRU holding at most `max_entries` responses
+    pub fn in_memory(max_entries: usize, ttl: Duration) -> Self {
+        let capacity = NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+        Self::with_backend(CacheBackend::Memory(Mutex::new(LruCache::new(capacity))), ttl)
+    }
+
+    /// Redis-backed cache (entry count is bounded by Redis eviction policy)
+    pub async fn redis(url: &str, ttl: Duration) -> GraphResult<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| GraphError::Config(format!("Invalid REDIS_URL: {}", e)))?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(|e| GraphError::ServiceUnavailable(format!("Redis connection failed: {}", e)))?;
+        Ok(Self::with_backend(CacheBackend::Redis(connection), ttl))
+    }
+
+    fn with_backend(backend: CacheBackend, ttl: Duration) -> Self {
+        Self {
+            backend,
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache key for a request; identical queries with identical options share one
+    pub fn key(request: &HybridSearchRequest) -> String {
+        let normalized = serde_json::json!({
+            "query": request.query.split_whitespace().collect::<Vec<_>>().join(" "),
+            "options": request.options,
+        });
+        format!("search:{:x}", Sha256::digest(normalized.to_string().as_bytes()))
+    }
+
+    /// Look up a cached response, marked `cached` with the time it was stored
+    pub async fn get(&self, key: &str) -> Option<HybridSearchResponse> {
+        let cached = match &self.backend {
+            CacheBackend::Memory(cache) => {
+                let mut cache = cache.lock().unwrap();
+                match cache.get(key) {
+                    Some((stored_at, json)) if stored_at.elapsed() < self.ttl => Some(json.clone()),
+                    Some(_) => {
+                        cache.pop(key);
+                        None
+                    }
+                    None => None,
+                }
+            }
+            CacheBackend::Redis(connection) => {
+                let mut connection = connection.clone();
+                match connection.get::<_, Option<String>>(key).await {
+                    Ok(value) => value,
+                    Err(e) => {
+                        tracing::warn!("Search cache read failed: {}", e);
+                        None
+                    }
+                }
+            }
+        };
+        let cached = cached.and_then(|json| serde_json::from_str::<HybridSearchResponse>(&json).ok());
+
+        let counter = if cached.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached.map(|mut response| {
+            response.metadata.cached = true;
+            response
+        })
+    }
+
+    /// Store a response stamped with `cached_at` (write failures are logged, never surfaced)
+    pub async fn put(&self, key: String, response: &HybridSearchResponse, cached_at: DateTime<Utc>) {
+        let Ok(mut value) = serde_json::to_value(response) else {
+            return;
+        };
+        value["metadata"]["cached_at"] = serde_json::json!(cached_at);
+        let json = value.to_string();
+
+        match &self.backend {
+            CacheBackend::Memory(cache) => {
+                cache.lock().unwrap().put(key, (Instant::now(), json));
+            }
+            CacheBackend::Redis(connection) => {
+                let mut connection = connection.clone();
+                let ttl_secs = self.ttl.as_secs().max(1);
+                if let Err(e) = connection.set_ex::<_, _, ()>(key, json, ttl_secs).await {
+                    tracing::warn!("Search cache write failed: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Drop cached responses after the graph changes
+    ///
+    /// Only the in-process cache can be cleared; Redis entries expire by TTL.
+    pub fn invalidate(&self) {
+        if let CacheBackend::Memory(cache) = &self.backend {
+            cache.lock().unwrap().clear();
+        }
+    }
+
+    pub fn stats(&self) -> SearchCacheStats {
+        SearchCacheStats {
+            backend: match self.backend {
+                CacheBackend::Memory(_) => "memory",
+                CacheBackend::Redis(_) => "redis",
+            },
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SearchMetadata, SearchOptions};
+
+    fn request(query: &str, owner_id: Option<&str>) -> HybridSearchRequest {
+        HybridSearchRequest {
+            query: query.to_string(),
+            options: SearchOptions { owner_id: owner_id.map(str::to_string), ..SearchOptions::default() },
+        }
+    }
+
+    fn response(query: &str) -> HybridSearchResponse {
+        HybridSearchResponse {
+            chunks: Vec::new(),
+            related_entities: Vec::new(),
+            relationships: Vec::new(),
+            cross_source_links: Vec::new(),
+            metadata: SearchMetadata {
+                query: query.to_string(),
+                vector_results_count: 0,
+                graph_entities_count: 0,
+                graph_hops_performed: 2,
+                cross_source_links_count: 0,
+                execution_time_ms: 40,
+                cached: false,
+                cached_at: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_key_covers_query_and_options() {
+        let key = SearchCache::key(&request("session  store", Some("tenant-a")));
+        assert_eq!(key, SearchCache::key(&request(" session store\n", Some("tenant-a"))));
+        assert_ne!(key, SearchCache::key(&request("session store", Some("tenant-b"))));
+        assert_ne!(key, SearchCache::key(&request("session", Some("tenant-a"))));
+    }
+
+    #[tokio::test]
+    async fn test_memory_cache_marks_hits_and_clears_on_invalidate() {
+        let cache = SearchCache::in_memory(8, Duration::from_secs(60));
+        let key = SearchCache::key(&request("sessions", None));
+        assert!(cache.get(&key).await.is_none());
+
+        let stored_at = Utc::now();
+        cache.put(key.clone(), &response("sessions"), stored_at).await;
+        let hit = cache.get(&key).await.unwrap();
+        assert!(hit.metadata.cached);
+        assert_eq!(hit.metadata.cached_at, Some(stored_at));
+        assert_eq!(hit.metadata.query, "sessions");
+
+        cache.invalidate();
+        assert!(cache.get(&key).await.is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+}
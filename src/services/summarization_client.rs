@@ -0,0 +1,72 @@
+//! Summarization client for calling the summarization service
+
+use crate::error::{GraphError, GraphResult};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+/// Client for the summarization microservice
+pub struct SummarizationClient {
+    client: Client,
+    base_url: String,
+    max_length: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct SummarizeRequest {
+    text: String,
+    max_length: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SummarizeResponse {
+    summary: String,
+}
+
+impl SummarizationClient {
+    pub fn new(base_url: &str, max_length: usize) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            max_length,
+        }
+    }
+    
+    /// Summarize a single text
+    pub async fn summarize(&self, text: &str) -> GraphResult<String> {
+        let url = format!("{}/summarize", self.base_url);
+        
+        let response = self.client
+            .post(&url)
+            .json(&SummarizeRequest {
+                text: text.to_string(),
+                max_length: self.max_length,
+            })
+            .send()
+            .await
+            .map_err(|e| GraphError::Summarization(format!("Request failed: {}", e)))?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(GraphError::Summarization(format!("Summarize failed: {} - {}", status, body)));
+        }
+        
+        let result: SummarizeResponse = response
+            .json()
+            .await
+            .map_err(|e| GraphError::Summarization(format!("Parse failed: {}", e)))?;
+        
+        Ok(result.summary)
+    }
+    
+    /// Health check
+    #[allow(dead_code)]
+    pub async fn health_check(&self) -> bool {
+        let url = format!("{}/health", self.base_url);
+        
+        match self.client.get(&url).send().await {
+            Ok(response) => response.status().is_success(),
+            Err(_) => false,
+        }
+    }
+}
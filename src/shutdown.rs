@@ -0,0 +1,122 @@
+//! Graceful shutdown
+//!
+//! On `SIGTERM` or Ctrl-C the server stops accepting connections and lets
+//! requests already in flight finish, so an ingest isn't cut off halfway
+//! through its graph writes. `main` then waits for background ingestion jobs
+//! (up to `SHUTDOWN_TIMEOUT_SECS`) before closing the database pools.
+
+use axum::{
+    extract::{Request, State},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Resolves on Ctrl-C or `SIGTERM`
+pub async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received Ctrl-C"),
+        _ = terminate => tracing::info!("Received SIGTERM"),
+    }
+}
+
+/// Number of requests currently being handled
+#[derive(Clone, Default)]
+struct RequestTracker(Arc<AtomicUsize>);
+
+/// Decrements the in-flight count when a request finishes or is dropped
+struct InFlight(Arc<AtomicUsize>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+async fn track(State(tracker): State<RequestTracker>, request: Request, next: Next) -> Response {
+    tracker.0.fetch_add(1, Ordering::SeqCst);
+    let _in_flight = InFlight(tracker.0.clone());
+    next.run(request).await
+}
+
+/// Serve `app` until `shutdown` resolves, then drain in-flight requests
+///
+/// Returns how many requests were still running when shutdown began; the
+/// call returns once all of them have completed.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<usize> {
+    let tracker = RequestTracker::default();
+    let app = app.layer(middleware::from_fn_with_state(tracker.clone(), track));
+
+    let in_flight = tracker.0.clone();
+    let draining = Arc::new(AtomicUsize::new(0));
+    let drained = draining.clone();
+    // Connection info gives the rate limiter the client IP
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            shutdown.await;
+            let count = in_flight.load(Ordering::SeqCst);
+            drained.store(count, Ordering::SeqCst);
+            tracing::info!("🛑 Shutting down; draining {} in-flight requests", count);
+        })
+        .await?;
+
+    let drained = draining.load(Ordering::SeqCst);
+    tracing::info!("Drained {} requests", drained);
+    Ok(drained)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_shutdown_drains_requests_and_stops_accepting() {
+        let app = Router::new().route("/slow", get(|| async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            "done"
+        }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (trigger, triggered) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve(listener, app, async {
+            let _ = triggered.await;
+        }));
+
+        let request = tokio::spawn(reqwest::get(format!("http://{}/slow", addr)));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        trigger.send(()).unwrap();
+
+        // The in-flight request still completes
+        let response = request.await.unwrap().unwrap();
+        assert_eq!(response.text().await.unwrap(), "done");
+        assert_eq!(server.await.unwrap().unwrap(), 1);
+
+        // The listener is gone once the server has stopped
+        assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+    }
+}
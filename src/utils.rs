@@ -0,0 +1,226 @@
+//! Shared helpers
+
+use regex::Regex;
+
+use crate::error::{GraphError, GraphResult};
+
+/// Longest path glob accepted
+const MAX_GLOB_LEN: usize = 256;
+
+/// Most `*`, `**`, and `?` wildcards in one path glob, which bounds how much
+/// a backtracking regex engine can do per path
+const MAX_GLOB_WILDCARDS: usize = 8;
+
+/// Canonical language names and the other names chunks get tagged with
+const LANGUAGE_ALIASES: &[(&str, &[&str])] = &[
+    ("typescript", &["ts", "tsx"]),
+    ("javascript", &["js", "jsx", "mjs", "cjs"]),
+    ("python", &["py"]),
+    ("rust", &["rs"]),
+    ("go", &["golang"]),
+    ("ruby", &["rb"]),
+    ("kotlin", &["kt"]),
+    ("csharp", &["c#", "cs"]),
+    ("cpp", &["c++", "cxx", "cc"]),
+    ("shell", &["sh", "bash", "zsh"]),
+    ("markdown", &["md"]),
+    ("yaml", &["yml"]),
+];
+
+/// Calculate cosine similarity between two vectors
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    
+    let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    
+    dot_product / (norm_a * norm_b)
+}
+
+/// Cosine similarity rescaled to 0..=1, as Neo4j's cosine vector index reports it
+pub fn vector_score(a: &[f32], b: &[f32]) -> f32 {
+    ((1.0 + cosine_similarity(a, b)) / 2.0).clamp(0.0, 1.0)
+}
+
+/// A file path glob compiled to an anchored regex
+/// 
+/// `*` and `?` stay within one path segment, `**` spans any number of them
+/// (`src/**/*.rs` also matches `src/main.rs`), and `{a,b}` matches either
+/// literal. Everything else is literal. The pattern only uses syntax that
+/// Neo4j's `=~` and the `regex` crate read the same way.
+#[derive(Debug, Clone)]
+pub struct PathGlob {
+    regex: Regex,
+}
+
+impl PathGlob {
+    pub fn parse(glob: &str) -> GraphResult<Self> {
+        let invalid = |reason: &str| GraphError::InvalidRequest(format!("Invalid path_glob '{}': {}", glob, reason));
+        if glob.is_empty() {
+            return Err(invalid("empty"));
+        }
+        if glob.chars().count() > MAX_GLOB_LEN {
+            return Err(invalid(&format!("longer than {} characters", MAX_GLOB_LEN)));
+        }
+        
+        let mut pattern = String::from("^");
+        let mut wildcards = 0;
+        let mut chars = glob.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => {
+                    wildcards += 1;
+                    if chars.next_if_eq(&'*').is_none() {
+                        pattern.push_str("[^/]*");
+                        continue;
+                    }
+                    while chars.next_if_eq(&'*').is_some() {}
+                    if chars.next_if_eq(&'/').is_some() {
+                        pattern.push_str("(?:.*/)?");
+                    } else {
+                        pattern.push_str(".*");
+                    }
+                }
+                '?' => {
+                    wildcards += 1;
+                    pattern.push_str("[^/]");
+                }
+                '{' => {
+                    let mut group = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{' | '*' | '?' | '[' | ']') => return Err(invalid("only literals are allowed inside {}")),
+                            Some(c) => group.push(c),
+                            None => return Err(invalid("unclosed {")),
+                        }
+                    }
+                    let alternatives: Vec<String> = group.split(',').map(regex::escape).collect();
+                    pattern.push_str(&format!("(?:{})", alternatives.join("|")));
+                }
+                '}' => return Err(invalid("unmatched }")),
+                '[' | ']' => return Err(invalid("character classes are not supported")),
+                c => pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        pattern.push('$');
+        
+        if wildcards > MAX_GLOB_WILDCARDS {
+            return Err(invalid(&format!("more than {} wildcards", MAX_GLOB_WILDCARDS)));
+        }
+        let regex = Regex::new(&pattern).map_err(|e| invalid(&e.to_string()))?;
+        Ok(Self { regex })
+    }
+    
+    /// The regex, for Neo4j's `=~`
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+    
+    pub fn matches(&self, path: &str) -> bool {
+        self.regex.is_match(path)
+    }
+}
+
+/// Lowercased canonical name of a language (`TS` and `tsx` both give `typescript`)
+pub fn normalize_language(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    LANGUAGE_ALIASES.iter()
+        .find(|(canonical, aliases)| *canonical == name || aliases.contains(&name.as_str()))
+        .map(|(canonical, _)| canonical.to_string())
+        .unwrap_or(name)
+}
+
+/// Stored `language` values a `languages` search filter accepts
+/// 
+/// Each requested language in canonical form plus its aliases, all lowercased,
+/// so chunks tagged `ts` and `TypeScript` are both found by `["typescript"]`.
+pub fn language_filter(languages: &[String]) -> GraphResult<Vec<String>> {
+    if languages.is_empty() {
+        return Err(GraphError::InvalidRequest("languages must not be empty".to_string()));
+    }
+    let mut accepted = Vec::new();
+    for language in languages {
+        if language.trim().is_empty() {
+            return Err(GraphError::InvalidRequest("languages must not contain blank names".to_string()));
+        }
+        let canonical = normalize_language(language);
+        let aliases = LANGUAGE_ALIASES.iter()
+            .find(|(name, _)| *name == canonical)
+            .map(|(_, aliases)| *aliases)
+            .unwrap_or_default();
+        for name in std::iter::once(canonical.as_str()).chain(aliases.iter().copied()) {
+            if !accepted.iter().any(|seen: &String| seen == name) {
+                accepted.push(name.to_string());
+            }
+        }
+    }
+    Ok(accepted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_cosine_similarity() {
+        let a = vec![1.0, 0.0, 0.0];
+        let b = vec![1.0, 0.0, 0.0];
+        assert!((cosine_similarity(&a, &b) - 1.0).abs() < 0.001);
+        
+        let c = vec![0.0, 1.0, 0.0];
+        assert!((cosine_similarity(&a, &c)).abs() < 0.001);
+    }
+    
+    #[test]
+    fn test_path_glob_shapes() {
+        let module = PathGlob::parse("src/auth/**").unwrap();
+        assert!(module.matches("src/auth/login.rs"));
+        assert!(module.matches("src/auth/oauth/google.rs"));
+        assert!(!module.matches("src/authz/login.rs"));
+        assert!(!module.matches("lib/src/auth/login.rs"));
+        
+        let rust = PathGlob::parse("**/*.rs").unwrap();
+        assert!(rust.matches("main.rs"));
+        assert!(rust.matches("src/graph_db/memory.rs"));
+        assert!(!rust.matches("src/main.rsx"));
+        
+        let docs = PathGlob::parse("docs/?-*.{md,txt}").unwrap();
+        assert!(docs.matches("docs/a-setup.md"));
+        assert!(docs.matches("docs/b-notes.txt"));
+        assert!(!docs.matches("docs/a-setup.rst"));
+        assert!(!docs.matches("docs/guides/a-setup.md"));
+        
+        // Regex syntax in a glob is literal
+        let literal = PathGlob::parse("src/(a|b).rs").unwrap();
+        assert!(literal.matches("src/(a|b).rs"));
+        assert!(!literal.matches("src/a.rs"));
+    }
+    
+    #[test]
+    fn test_language_filter_normalizes_aliases() {
+        assert_eq!(normalize_language(" TS "), "typescript");
+        assert_eq!(normalize_language("Rust"), "rust");
+        assert_eq!(normalize_language("elixir"), "elixir");
+        
+        let filter = language_filter(&["PY".to_string(), "python".to_string(), "ts".to_string()]).unwrap();
+        assert_eq!(filter, vec!["python", "py", "typescript", "ts", "tsx"]);
+        assert!(language_filter(&[]).is_err());
+        assert!(language_filter(&[" ".to_string()]).is_err());
+    }
+    
+    #[test]
+    fn test_path_glob_rejects_unsupported_and_oversized_patterns() {
+        for glob in ["", "src/[ab].rs", "src/{a,{b}}", "src/{a*,b}", "src/{a,b", "src/a}", "*/*/*/*/*/*/*/*/*"] {
+            assert!(matches!(PathGlob::parse(glob), Err(GraphError::InvalidRequest(_))), "{}", glob);
+        }
+        assert!(PathGlob::parse(&"a".repeat(MAX_GLOB_LEN + 1)).is_err());
+    }
+}